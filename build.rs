@@ -0,0 +1,23 @@
+//! Records the git commit of source builds as `RALPH_GIT_SHA`.
+//!
+//! Builds from a published crate have no `.git` and leave it unset.
+
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs/heads");
+    println!("cargo:rerun-if-env-changed=RALPH_GIT_SHA");
+    if std::env::var_os("RALPH_GIT_SHA").is_some() || !std::path::Path::new(".git").exists() {
+        return;
+    }
+    let sha = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .and_then(|out| String::from_utf8(out.stdout).ok());
+    if let Some(sha) = sha.map(|s| s.trim().to_string()).filter(|s| !s.is_empty()) {
+        println!("cargo:rustc-env=RALPH_GIT_SHA={}", sha);
+    }
+}
@@ -0,0 +1,452 @@
+//! Aborting a single in-flight task without stopping the run.
+//!
+//! `ralph tasks abort <ID> [--block]` drops an [`AbortRequest`] into
+//! [`ABORT_REQUEST_FILE`]. The loop checks for it between stream events (the
+//! safe point: no tool is executing) and, when the request names the task in
+//! progress:
+//!
+//! 1. Restores every file the task wrote, from the [`WriteBackup`] the file
+//!    tool keeps since the task started
+//! 2. Records the attempt as failed with reason "aborted by user" and puts the
+//!    task back to pending, or blocks it when `--block` was given or the
+//!    attempt limit is reached
+//! 3. Raises the [`AbortSignal`], so the agent's further file, test and git
+//!    calls for the task are refused until it asks for the next task
+//!
+//! With a [`TaskRollback`] attached, a task that ends up blocked is also reset
+//! to the commit it started from.
+
+use crate::models::{TaskList, TaskStatus};
+use crate::tools::TaskRollback;
+use adk_rust::{Result as AdkResult, Tool, ToolContext};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Control file (relative to the project) holding a pending abort request.
+pub const ABORT_REQUEST_FILE: &str = ".ralph/abort.json";
+
+/// Failure reason recorded on an aborted attempt.
+pub const ABORT_REASON: &str = "aborted by user";
+
+/// Tools that keep working after an abort so the agent can move on.
+const UNGUARDED_TOOLS: &[&str] = &["tasks", "progress", "exit_loop"];
+
+/// A request to abort the task currently in progress.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AbortRequest {
+    /// Task to abort
+    pub task_id: String,
+    /// Block the task instead of returning it to the queue
+    #[serde(default)]
+    pub block: bool,
+}
+
+impl AbortRequest {
+    /// Create a new abort request.
+    pub fn new(task_id: impl Into<String>, block: bool) -> Self {
+        Self {
+            task_id: task_id.into(),
+            block,
+        }
+    }
+
+    /// Path of the control file in a project.
+    pub fn path(project_path: &Path) -> PathBuf {
+        project_path.join(ABORT_REQUEST_FILE)
+    }
+
+    /// Write the request for a running loop to pick up.
+    pub fn submit(&self, project_path: &Path) -> Result<(), String> {
+        let path = Self::path(project_path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create '{}': {}", parent.display(), e))?;
+        }
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize abort request: {}", e))?;
+        fs::write(&path, content)
+            .map_err(|e| format!("Failed to write abort request '{}': {}", path.display(), e))
+    }
+
+    /// Read and remove a pending request, if any.
+    pub fn take(project_path: &Path) -> Option<Self> {
+        let path = Self::path(project_path);
+        let content = fs::read_to_string(&path).ok()?;
+        let _ = fs::remove_file(&path);
+        match serde_json::from_str(&content) {
+            Ok(request) => Some(request),
+            Err(e) => {
+                tracing::warn!(error = %e, "Ignoring malformed abort request");
+                None
+            }
+        }
+    }
+}
+
+/// Original contents of the files written since the current task started.
+///
+/// Only the first write to a path is recorded, so a restore returns each file
+/// to its state before the task touched it.
+#[derive(Debug, Default)]
+pub struct WriteBackup {
+    /// Path and original bytes (`None` if the file did not exist)
+    entries: Mutex<Vec<(PathBuf, Option<Vec<u8>>)>>,
+}
+
+impl WriteBackup {
+    /// Create an empty backup.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Snapshot a file before it is written or deleted.
+    pub fn record(&self, path: &Path) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.iter().any(|(p, _)| p == path) {
+            return;
+        }
+        entries.push((path.to_path_buf(), fs::read(path).ok()));
+    }
+
+    /// Forget all snapshots (the task finished or a new one started).
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    /// Check if nothing has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.entries.lock().unwrap().is_empty()
+    }
+
+    /// Put every recorded file back and return the paths restored.
+    ///
+    /// Files that did not exist before the task are removed.
+    pub fn restore(&self) -> Vec<PathBuf> {
+        let mut entries = self.entries.lock().unwrap();
+        let mut restored = Vec::new();
+        for (path, original) in entries.drain(..).rev() {
+            let result = match original {
+                Some(bytes) => fs::write(&path, bytes),
+                None if path.exists() => fs::remove_file(&path),
+                None => Ok(()),
+            };
+            match result {
+                Ok(()) => restored.push(path),
+                Err(e) => tracing::warn!(path = %path.display(), error = %e, "Could not restore file"),
+            }
+        }
+        restored.reverse();
+        restored
+    }
+}
+
+/// Marks a task as aborted while the agent may still be working on it.
+#[derive(Debug, Default)]
+pub struct AbortSignal {
+    aborted: Mutex<Option<String>>,
+}
+
+impl AbortSignal {
+    /// Create a clear signal.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Raise the signal for a task.
+    pub fn raise(&self, task_id: impl Into<String>) {
+        *self.aborted.lock().unwrap() = Some(task_id.into());
+    }
+
+    /// Clear the signal.
+    pub fn clear(&self) {
+        *self.aborted.lock().unwrap() = None;
+    }
+
+    /// Task that was aborted, if the signal is raised.
+    pub fn aborted(&self) -> Option<String> {
+        self.aborted.lock().unwrap().clone()
+    }
+}
+
+/// What happened to an aborted task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AbortOutcome {
+    /// Returned to pending for another attempt
+    Requeued,
+    /// Blocked (on request or because the attempt limit was reached)
+    Blocked,
+}
+
+/// Result of applying an abort request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AbortReport {
+    /// Task that was aborted
+    pub task_id: String,
+    /// Where the task went
+    pub outcome: AbortOutcome,
+    /// Files put back to their state before the task
+    pub restored_files: Vec<PathBuf>,
+}
+
+/// Record a failed, aborted attempt on an in-progress task.
+pub fn abort_task(
+    list: &mut TaskList,
+    task_id: &str,
+    block: bool,
+    max_attempts: u32,
+) -> Result<AbortOutcome, String> {
+    let task = list
+        .get_task_mut(task_id)
+        .ok_or_else(|| format!("Task not found: {}", task_id))?;
+    if task.status != TaskStatus::InProgress {
+        return Err(format!("Task {} is not in progress ({})", task_id, task.status));
+    }
+
+    task.add_note(&format!("Attempt {} failed: {}", task.attempts, ABORT_REASON));
+    let outcome = if block || task.attempts >= max_attempts {
+        task.block(ABORT_REASON);
+        list.mark_blocked_tasks();
+        AbortOutcome::Blocked
+    } else {
+        task.set_status(TaskStatus::Pending);
+        AbortOutcome::Requeued
+    };
+    list.updated_at = Some(crate::timefmt::now());
+    Ok(outcome)
+}
+
+/// Applies abort requests for a running loop.
+#[derive(Debug)]
+pub struct TaskAborter {
+    project_path: PathBuf,
+    tasks_path: PathBuf,
+    max_attempts: u32,
+    backup: Arc<WriteBackup>,
+    signal: Arc<AbortSignal>,
+    rollback: Option<Arc<TaskRollback>>,
+}
+
+impl TaskAborter {
+    /// Create an aborter sharing the loop's backup and signal.
+    pub fn new(
+        project_path: impl Into<PathBuf>,
+        tasks_path: impl Into<PathBuf>,
+        max_attempts: u32,
+        backup: Arc<WriteBackup>,
+        signal: Arc<AbortSignal>,
+    ) -> Self {
+        Self {
+            project_path: project_path.into(),
+            tasks_path: tasks_path.into(),
+            max_attempts,
+            backup,
+            signal,
+            rollback: None,
+        }
+    }
+
+    /// Roll blocked tasks back to the commit they started from.
+    pub fn with_rollback(mut self, rollback: Arc<TaskRollback>) -> Self {
+        self.rollback = Some(rollback);
+        self
+    }
+
+    /// Apply a pending abort request, if there is one.
+    ///
+    /// Call only at a safe point, between tool executions. The task list is
+    /// left untouched when the request does not name the task in progress.
+    pub fn check(&self) -> Result<Option<AbortReport>, String> {
+        let Some(request) = AbortRequest::take(&self.project_path) else {
+            return Ok(None);
+        };
+
+        let mut list = TaskList::load(&self.tasks_path)?;
+        let outcome = abort_task(&mut list, &request.task_id, request.block, self.max_attempts)?;
+
+        let restored_files = self.backup.restore();
+        if outcome == AbortOutcome::Blocked {
+            let task = self.rollback.as_ref().zip(list.get_task_mut(&request.task_id));
+            if let Some((rollback, task)) = task {
+                rollback.apply(task);
+            }
+        }
+        list.save(&self.tasks_path)?;
+        self.signal.raise(&request.task_id);
+
+        Ok(Some(AbortReport {
+            task_id: request.task_id,
+            outcome,
+            restored_files,
+        }))
+    }
+}
+
+/// Wraps a tool so it refuses to run for a task that was aborted.
+///
+/// The `tasks` tool keeps working; a `get_next` or `complete` through it clears
+/// the signal and the write backup, since a new task boundary has been reached.
+pub struct AbortGuardTool {
+    inner: Arc<dyn Tool>,
+    signal: Arc<AbortSignal>,
+    backup: Arc<WriteBackup>,
+}
+
+impl AbortGuardTool {
+    /// Guard a tool with the loop's abort signal.
+    pub fn new(inner: Arc<dyn Tool>, signal: Arc<AbortSignal>, backup: Arc<WriteBackup>) -> Self {
+        Self { inner, signal, backup }
+    }
+}
+
+impl std::fmt::Debug for AbortGuardTool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AbortGuardTool")
+            .field("inner", &self.inner.name())
+            .finish()
+    }
+}
+
+#[async_trait]
+impl Tool for AbortGuardTool {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn description(&self) -> &str {
+        self.inner.description()
+    }
+
+    fn parameters_schema(&self) -> Option<Value> {
+        self.inner.parameters_schema()
+    }
+
+    async fn execute(&self, ctx: Arc<dyn ToolContext>, args: Value) -> AdkResult<Value> {
+        if !UNGUARDED_TOOLS.contains(&self.inner.name()) {
+            if let Some(task_id) = self.signal.aborted() {
+                return Err(adk_rust::AdkError::Tool(format!(
+                    "Task {} was {} and its changes were rolled back. \
+                     Stop working on it and call tasks get_next.",
+                    task_id, ABORT_REASON
+                )));
+            }
+        }
+
+        let boundary = self.inner.name() == "tasks"
+            && matches!(args["operation"].as_str(), Some("get_next") | Some("complete"));
+        let result = self.inner.execute(ctx, args).await;
+        if boundary && result.is_ok() {
+            self.signal.clear();
+            self.backup.clear();
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Task;
+
+    fn in_progress_list(tasks_path: &Path, attempts: u32) {
+        let mut list = TaskList::new("calc", "rust");
+        let mut task = Task::new("T-009", "Parser", "Parse input", 1);
+        for _ in 0..attempts {
+            task.start();
+        }
+        list.add_task(task);
+        list.add_task(Task::new("T-010", "Eval", "Evaluate", 2));
+        list.save(tasks_path).unwrap();
+    }
+
+    fn aborter(dir: &Path, max_attempts: u32) -> (TaskAborter, Arc<WriteBackup>, Arc<AbortSignal>) {
+        let backup = Arc::new(WriteBackup::new());
+        let signal = Arc::new(AbortSignal::new());
+        let aborter = TaskAborter::new(dir, dir.join("tasks.json"), max_attempts, backup.clone(), signal.clone());
+        (aborter, backup, signal)
+    }
+
+    #[test]
+    fn test_abort_mid_task_restores_files_and_requeues() {
+        let dir = tempfile::tempdir().unwrap();
+        let tasks_path = dir.path().join("tasks.json");
+        in_progress_list(&tasks_path, 1);
+        let (aborter, backup, signal) = aborter(dir.path(), 3);
+
+        // The task is mid-way: it rewrote one file and created another
+        let existing = dir.path().join("lib.rs");
+        let created = dir.path().join("parser.rs");
+        fs::write(&existing, "pub fn old() {}").unwrap();
+        backup.record(&existing);
+        fs::write(&existing, "pub fn half_done(").unwrap();
+        backup.record(&created);
+        fs::write(&created, "mod parser;").unwrap();
+        backup.record(&existing);
+        fs::write(&existing, "pub fn still_half_done(").unwrap();
+
+        assert!(aborter.check().unwrap().is_none());
+        AbortRequest::new("T-009", false).submit(dir.path()).unwrap();
+
+        let report = aborter.check().unwrap().unwrap();
+        assert_eq!(report.task_id, "T-009");
+        assert_eq!(report.outcome, AbortOutcome::Requeued);
+        assert_eq!(report.restored_files, vec![existing.clone(), created.clone()]);
+        assert_eq!(fs::read_to_string(&existing).unwrap(), "pub fn old() {}");
+        assert!(!created.exists());
+        assert!(backup.is_empty());
+        assert_eq!(signal.aborted().as_deref(), Some("T-009"));
+
+        let list = TaskList::load(&tasks_path).unwrap();
+        let task = list.get_task("T-009").unwrap();
+        assert_eq!(task.status, TaskStatus::Pending);
+        assert!(task.notes.contains("Attempt 1 failed: aborted by user"));
+        assert_eq!(list.get_task("T-010").unwrap().status, TaskStatus::Pending);
+
+        // The request is consumed
+        assert!(!AbortRequest::path(dir.path()).exists());
+        assert!(aborter.check().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_abort_blocks_on_request_or_attempt_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let tasks_path = dir.path().join("tasks.json");
+
+        in_progress_list(&tasks_path, 1);
+        let (aborter, _, _) = aborter(dir.path(), 3);
+        AbortRequest::new("T-009", true).submit(dir.path()).unwrap();
+        assert_eq!(aborter.check().unwrap().unwrap().outcome, AbortOutcome::Blocked);
+        let list = TaskList::load(&tasks_path).unwrap();
+        assert!(list.get_task("T-009").unwrap().is_blocked());
+
+        in_progress_list(&tasks_path, 3);
+        let (aborter, _, _) = aborter(dir.path(), 3);
+        AbortRequest::new("T-009", false).submit(dir.path()).unwrap();
+        assert_eq!(aborter.check().unwrap().unwrap().outcome, AbortOutcome::Blocked);
+    }
+
+    #[test]
+    fn test_abort_of_idle_task_leaves_state_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let tasks_path = dir.path().join("tasks.json");
+        in_progress_list(&tasks_path, 1);
+        let (aborter, backup, signal) = aborter(dir.path(), 3);
+
+        let file = dir.path().join("lib.rs");
+        backup.record(&file);
+        fs::write(&file, "pub fn kept() {}").unwrap();
+
+        AbortRequest::new("T-010", false).submit(dir.path()).unwrap();
+        let err = aborter.check().unwrap_err();
+        assert!(err.contains("not in progress"));
+
+        assert!(file.exists());
+        assert!(!backup.is_empty());
+        assert!(signal.aborted().is_none());
+        let list = TaskList::load(&tasks_path).unwrap();
+        assert_eq!(list.get_task("T-009").unwrap().status, TaskStatus::InProgress);
+    }
+}
@@ -4,14 +4,24 @@
 //! - `design.md` - System architecture with component diagrams
 //! - `tasks.json` - Structured task list with priorities and dependencies
 //!
+//! The model's raw JSON reply is kept as [`ARCHITECT_RESPONSE_FILE`].
+//!
 //! This agent uses LlmAgent with:
 //! - `read_file` tool to read PRD
 //! - `write_file` tool to save design and tasks
 //! - `output_key` to store outputs in session state
 //! - Session state access to read PRD from previous agent
 
-use crate::models::ModelConfig;
+use crate::agents::prompt::{architect_replan_message, architect_user_message, AssembledPrompt};
+use crate::model_timeout::TimeoutModel;
+use crate::models::{create_model, CommitType, ModelConfig, StructureLimits};
+use crate::output::RalphOutput;
+use crate::provenance::{self, AgentProvenance, Provenance};
+use crate::retry::RetryPolicy;
+use crate::schema::{DESIGN_SCHEMA_ID, TASKS_SCHEMA_ID};
+use crate::textfmt::truncate_display;
 use crate::{RalphError, Result};
+use crate::warnings::{Warning, WarningCategory};
 use adk_rust::agent::LlmAgentBuilder;
 use adk_rust::{Agent, Llm};
 use serde_json::json;
@@ -58,8 +68,9 @@ Generate a JSON response with two sections: `design` and `tasks`.
     "language": "rust",
     "technology_stack": {
       "testing": "cargo test",
-      "build": "cargo",
-      "dependencies": ["serde", "clap"]
+      "build_tool": "cargo",
+      "key_dependencies": ["serde", "clap"],
+      "framework": "axum (optional, like database and deployment)"
     },
     "architecture_diagram": "```mermaid\nflowchart ...\n```",
     "components": [
@@ -75,6 +86,18 @@ Generate a JSON response with two sections: `design` and `tasks`.
       "directories": ["src"],
       "files": ["Cargo.toml", "src/main.rs"]
     },
+    "test_infrastructure": {
+      "helper_module": "tests/common/mod.rs",
+      "fixtures": ["temp_workspace() -> TempDir"],
+      "factories": ["sample_config() -> Config"]
+    },
+    "glossary": [
+      {
+        "term": "order",
+        "definition": "a customer's request to buy items",
+        "avoid": ["purchase", "transaction"]
+      }
+    ],
     "design_decisions": [
       {
         "decision": "what was decided",
@@ -121,10 +144,14 @@ Generate a JSON response with two sections: `design` and `tasks`.
 - Each task maps to real work that produces a testable result
 - Priority: 1 = must do first, 2 = important, 3 = standard, 4 = polish, 5 = optional
 - Complexity: "low" (< 30 min), "medium" (30 min - 2 hrs), "high" (2+ hrs)
-- First task sets up project structure and builds successfully (no dependencies)
+- First task sets up project structure and builds successfully (no dependencies); set "setup": true on it. The build is checked as soon as it completes
 - Order: project setup → core logic → features → integration → polish
 - Link every task to a user story from the PRD
 - Use relative paths in files_to_create and files_to_modify
+- Integration tasks that need environment variables at test time (e.g. DATABASE_URL for a test database, API_BASE_URL for a local mock) may set them in an optional "env" object of names to values. Never put real credentials there
+- List the project's domain nouns in "glossary": one canonical term per concept, what it means, and the synonyms code must not use for it. Every task names things with these terms, so the API stays consistent
+- When tests will span several files, plan the helpers they share in the optional "test_infrastructure" section (helper module path, fixtures, factories) and create them in an early task right after setup. Later tasks import these helpers instead of writing their own. Leave the section out for projects with one test file or none
+- Only when the project description asks for a specific model on a task, set that task's optional "model" to {"provider": ..., "model_name": ...}; otherwise leave it out
 
 ## Scaling Examples
 
@@ -159,6 +186,77 @@ Generate a JSON response with two sections: `design` and `tasks`.
 pub struct ArchitectAgent {
     agent: Arc<dyn Agent + Send + Sync>,
     project_path: PathBuf,
+    /// PRD read by `generate`, resolved against the project path
+    prd_path: PathBuf,
+    /// Design document written by `generate`, resolved against the project path
+    design_path: PathBuf,
+    /// Structured design written next to design.md, resolved against the project path
+    design_json_path: PathBuf,
+    /// Task list written by `generate`, resolved against the project path
+    tasks_path: PathBuf,
+    /// Model details recorded in design.md and tasks.json
+    provenance: AgentProvenance,
+    /// Limits on the proposed file tree
+    structure_limits: StructureLimits,
+    /// Fail instead of warning when a component has no task creating it
+    coverage_strict: bool,
+    /// Warn instead of failing when the plan has no tasks
+    allow_empty_plan: bool,
+    /// PRD to plan from instead of the project's prd.md
+    prd_content: Option<String>,
+    /// Leave design.md, tasks.json and the recorded prompt untouched
+    dry_run: bool,
+    /// Print the generated plan to stdout in this format
+    stdout_format: Option<PlanFormat>,
+    /// How many times to re-ask after a reply that is not valid JSON
+    max_parse_retries: u32,
+    /// Attempts and backoff for model calls that fail transiently
+    retry_policy: RetryPolicy,
+    /// Where retries are reported, besides the log
+    output: Option<RalphOutput>,
+    /// Give each implementation task its own test-writing task
+    split_test_tasks: bool,
+}
+
+/// How a plan printed to stdout is laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlanFormat {
+    /// design.md, a [`PLAN_SEPARATOR`] line, then tasks.json
+    #[default]
+    Text,
+    /// One JSON object with `design_markdown` and `tasks` fields
+    Json,
+}
+
+/// Architect's latest raw JSON reply (relative to the project).
+pub const ARCHITECT_RESPONSE_FILE: &str = ".ralph/architect-response.json";
+
+/// Line between design.md and tasks.json in [`PlanFormat::Text`] output.
+pub const PLAN_SEPARATOR: &str = "<!-- ralph:tasks.json -->";
+
+/// Render a plan for stdout.
+pub fn render_plan(
+    design: &crate::models::DesignDocument,
+    tasks: &crate::models::TaskList,
+    format: PlanFormat,
+) -> Result<String> {
+    match format {
+        PlanFormat::Text => {
+            let tasks_json = serde_json::to_string_pretty(tasks)
+                .map_err(|e| RalphError::Task(format!("Failed to serialize tasks: {}", e)))?;
+            let markdown = design.to_markdown();
+            Ok(format!("{}\n{}\n{}\n", markdown.trim_end(), PLAN_SEPARATOR, tasks_json))
+        }
+        PlanFormat::Json => {
+            let envelope = json!({
+                "design_markdown": design.to_markdown(),
+                "tasks": tasks,
+            });
+            serde_json::to_string_pretty(&envelope)
+                .map(|s| s + "\n")
+                .map_err(|e| RalphError::Task(format!("Failed to serialize plan: {}", e)))
+        }
+    }
 }
 
 impl std::fmt::Debug for ArchitectAgent {
@@ -190,6 +288,26 @@ impl ArchitectAgent {
     pub fn project_path(&self) -> &PathBuf {
         &self.project_path
     }
+
+    /// Where the PRD is read from.
+    pub fn prd_path(&self) -> &PathBuf {
+        &self.prd_path
+    }
+
+    /// Where the design document is written.
+    pub fn design_path(&self) -> &PathBuf {
+        &self.design_path
+    }
+
+    /// Where the structured design is written.
+    pub fn design_json_path(&self) -> &PathBuf {
+        &self.design_json_path
+    }
+
+    /// Where the task list is written.
+    pub fn tasks_path(&self) -> &PathBuf {
+        &self.tasks_path
+    }
 }
 
 /// Builder for creating an ArchitectAgent with fluent API.
@@ -198,8 +316,20 @@ pub struct ArchitectAgentBuilder {
     model_config: ModelConfig,
     prd_path: PathBuf,
     design_path: PathBuf,
+    design_json_path: PathBuf,
     tasks_path: PathBuf,
     project_path: PathBuf,
+    structure_limits: StructureLimits,
+    coverage_strict: bool,
+    allow_empty_plan: bool,
+    prd_content: Option<String>,
+    dry_run: bool,
+    output_to_stdout: bool,
+    plan_format: PlanFormat,
+    max_parse_retries: u32,
+    retry_policy: RetryPolicy,
+    output: Option<RalphOutput>,
+    split_test_tasks: bool,
 }
 
 impl std::fmt::Debug for ArchitectAgentBuilder {
@@ -209,8 +339,19 @@ impl std::fmt::Debug for ArchitectAgentBuilder {
             .field("model_config", &self.model_config)
             .field("prd_path", &self.prd_path)
             .field("design_path", &self.design_path)
+            .field("design_json_path", &self.design_json_path)
             .field("tasks_path", &self.tasks_path)
             .field("project_path", &self.project_path)
+            .field("structure_limits", &self.structure_limits)
+            .field("coverage_strict", &self.coverage_strict)
+            .field("allow_empty_plan", &self.allow_empty_plan)
+            .field("prd_content", &self.prd_content.as_ref().map(String::len))
+            .field("dry_run", &self.dry_run)
+            .field("output_to_stdout", &self.output_to_stdout)
+            .field("plan_format", &self.plan_format)
+            .field("max_parse_retries", &self.max_parse_retries)
+            .field("retry_policy", &self.retry_policy)
+            .field("split_test_tasks", &self.split_test_tasks)
             .finish()
     }
 }
@@ -222,8 +363,20 @@ impl Default for ArchitectAgentBuilder {
             model_config: ModelConfig::new("gemini", "gemini-3-pro-preview"),
             prd_path: PathBuf::from("prd.md"),
             design_path: PathBuf::from("design.md"),
+            design_json_path: PathBuf::from("design.json"),
             tasks_path: PathBuf::from("tasks.json"),
             project_path: PathBuf::from("."),
+            structure_limits: StructureLimits::default(),
+            coverage_strict: false,
+            allow_empty_plan: false,
+            prd_content: None,
+            dry_run: false,
+            output_to_stdout: false,
+            plan_format: PlanFormat::default(),
+            max_parse_retries: DEFAULT_MAX_PARSE_RETRIES,
+            retry_policy: RetryPolicy::default(),
+            output: None,
+            split_test_tasks: false,
         }
     }
 }
@@ -253,6 +406,14 @@ impl ArchitectAgentBuilder {
         self
     }
 
+    /// Where to write the serialized `DesignDocument` (default `design.json`),
+    /// so later agents can read components and decisions without parsing
+    /// design.md. Reload it with `DesignDocument::load`.
+    pub fn design_json_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.design_json_path = path.into();
+        self
+    }
+
     pub fn tasks_path(mut self, path: impl Into<PathBuf>) -> Self {
         self.tasks_path = path.into();
         self
@@ -263,11 +424,88 @@ impl ArchitectAgentBuilder {
         self
     }
 
+    pub fn structure_limits(mut self, limits: StructureLimits) -> Self {
+        self.structure_limits = limits;
+        self
+    }
+
+    /// Fail generation when a design component has no task creating its file.
+    pub fn coverage_strict(mut self, strict: bool) -> Self {
+        self.coverage_strict = strict;
+        self
+    }
+
+    /// Accept a plan with no tasks, with a warning, instead of failing generation.
+    pub fn allow_empty_plan(mut self, allow: bool) -> Self {
+        self.allow_empty_plan = allow;
+        self
+    }
+
+    /// Plan from this PRD text instead of reading the project's prd.md.
+    pub fn prd_content(mut self, prd: impl Into<String>) -> Self {
+        self.prd_content = Some(prd.into());
+        self
+    }
+
+    /// Generate without writing design.md, tasks.json or the recorded prompt.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Print the generated design and tasks to stdout (see [`PlanFormat`]).
+    ///
+    /// With [`dry_run`](Self::dry_run) and [`prd_content`](Self::prd_content)
+    /// the architect becomes a pure PRD-in, plan-out filter.
+    pub fn output_to_stdout(mut self, enabled: bool) -> Self {
+        self.output_to_stdout = enabled;
+        self
+    }
+
+    /// Layout of the plan printed with [`output_to_stdout`](Self::output_to_stdout).
+    pub fn plan_format(mut self, format: PlanFormat) -> Self {
+        self.plan_format = format;
+        self
+    }
+
+    /// How many times to re-ask the architect, quoting the parse error, when
+    /// its reply is not valid JSON (default 3; 0 fails on the first bad reply).
+    pub fn max_parse_retries(mut self, retries: u32) -> Self {
+        self.max_parse_retries = retries;
+        self
+    }
+
+    /// Attempts and backoff for model calls that fail with a rate limit,
+    /// overload, 5xx, timeout or empty reply (default: 3 attempts, 2s, 4s...
+    /// up to 30s, with jitter). Other failures are never retried.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Report each retry as a warning on `output`.
+    pub fn output(mut self, output: RalphOutput) -> Self {
+        self.output = Some(output);
+        self
+    }
+
+    /// Track tests separately: every implementation task gets a `<id>-test`
+    /// task that depends on it and carries its acceptance criteria (default off).
+    pub fn split_test_tasks(mut self, split: bool) -> Self {
+        self.split_test_tasks = split;
+        self
+    }
+
     pub async fn build(self) -> Result<ArchitectAgent> {
+        let provenance = match self.model {
+            Some(ref m) => AgentProvenance::from_model_name(m.name(), &self.model_config, ARCHITECT_INSTRUCTION),
+            None => AgentProvenance::from_config(&self.model_config, ARCHITECT_INSTRUCTION),
+        };
         let model = match self.model {
             Some(m) => m,
-            None => create_model_from_config(&self.model_config).await?,
+            None => create_model(&self.model_config).await?,
         };
+        let model = TimeoutModel::wrap(model, self.model_config.model_call_timeout());
 
         // Define the JSON schema for structured design + tasks output
         let architect_schema = json!({
@@ -296,7 +534,10 @@ impl ArchitectAgentBuilder {
                                 "key_dependencies": {
                                     "type": "array",
                                     "items": { "type": "string" }
-                                }
+                                },
+                                "framework": { "type": "string" },
+                                "database": { "type": "string" },
+                                "deployment": { "type": "string" }
                             },
                             "required": ["testing", "build_tool"]
                         },
@@ -341,6 +582,41 @@ impl ArchitectAgentBuilder {
                             },
                             "required": ["files"]
                         },
+                        "test_infrastructure": {
+                            "type": "object",
+                            "description": "Test helpers shared by every test file, created by an early task",
+                            "properties": {
+                                "helper_module": {
+                                    "type": "string",
+                                    "description": "Module holding the shared helpers (e.g., 'tests/common/mod.rs')"
+                                },
+                                "fixtures": {
+                                    "type": "array",
+                                    "items": { "type": "string" }
+                                },
+                                "factories": {
+                                    "type": "array",
+                                    "items": { "type": "string" }
+                                }
+                            }
+                        },
+                        "glossary": {
+                            "type": "array",
+                            "description": "Domain terms every task names things by",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "term": { "type": "string" },
+                                    "definition": { "type": "string" },
+                                    "avoid": {
+                                        "type": "array",
+                                        "items": { "type": "string" },
+                                        "description": "Synonyms not to use for this concept"
+                                    }
+                                },
+                                "required": ["term"]
+                            }
+                        },
                         "design_decisions": {
                             "type": "array",
                             "items": {
@@ -400,6 +676,24 @@ impl ArchitectAgentBuilder {
                             "acceptance_criteria": {
                                 "type": "array",
                                 "items": { "type": "string" }
+                            },
+                            "env": {
+                                "type": "object",
+                                "additionalProperties": { "type": "string" },
+                                "description": "Environment variables for this task's build/test commands"
+                            },
+                            "model": {
+                                "type": "object",
+                                "properties": {
+                                    "provider": { "type": "string" },
+                                    "model_name": { "type": "string" }
+                                },
+                                "required": ["provider", "model_name"],
+                                "description": "Model to implement this task with, instead of the default"
+                            },
+                            "setup": {
+                                "type": "boolean",
+                                "description": "True for the task that scaffolds the project"
                             }
                         },
                         "required": ["id", "title", "description", "priority", "estimated_complexity"]
@@ -422,72 +716,29 @@ impl ArchitectAgentBuilder {
                 message: e.to_string(),
             })?;
 
+        // Relative paths are inside the project; absolute ones replace it
         Ok(ArchitectAgent {
             agent: Arc::new(agent),
+            prd_path: self.project_path.join(&self.prd_path),
+            design_path: self.project_path.join(&self.design_path),
+            design_json_path: self.project_path.join(&self.design_json_path),
+            tasks_path: self.project_path.join(&self.tasks_path),
             project_path: self.project_path,
+            provenance,
+            structure_limits: self.structure_limits,
+            coverage_strict: self.coverage_strict,
+            allow_empty_plan: self.allow_empty_plan,
+            prd_content: self.prd_content,
+            dry_run: self.dry_run,
+            stdout_format: self.output_to_stdout.then_some(self.plan_format),
+            max_parse_retries: self.max_parse_retries,
+            retry_policy: self.retry_policy,
+            output: self.output,
+            split_test_tasks: self.split_test_tasks,
         })
     }
 }
 
-
-/// Create an LLM model from configuration.
-async fn create_model_from_config(config: &ModelConfig) -> Result<Arc<dyn Llm>> {
-    use std::env;
-
-    let model: Arc<dyn Llm> = match config.provider.to_lowercase().as_str() {
-        "anthropic" => {
-            use adk_rust::model::anthropic::{AnthropicClient, AnthropicConfig};
-
-            let api_key = env::var("ANTHROPIC_API_KEY").map_err(|_| {
-                RalphError::Configuration("ANTHROPIC_API_KEY environment variable not set".into())
-            })?;
-            let anthropic_config = AnthropicConfig::new(api_key, &config.model_name);
-            let client = AnthropicClient::new(anthropic_config).map_err(|e| RalphError::Model {
-                provider: "anthropic".into(),
-                message: e.to_string(),
-            })?;
-            Arc::new(client)
-        }
-        "openai" => {
-            use adk_rust::model::openai::{OpenAIClient, OpenAIConfig};
-
-            let api_key = env::var("OPENAI_API_KEY").map_err(|_| {
-                RalphError::Configuration("OPENAI_API_KEY environment variable not set".into())
-            })?;
-            let openai_config = OpenAIConfig::new(api_key, &config.model_name);
-            let client = OpenAIClient::new(openai_config).map_err(|e| RalphError::Model {
-                provider: "openai".into(),
-                message: e.to_string(),
-            })?;
-            Arc::new(client)
-        }
-        "gemini" => {
-            use adk_rust::model::GeminiModel;
-
-            let api_key = env::var("GEMINI_API_KEY")
-                .or_else(|_| env::var("GOOGLE_API_KEY"))
-                .map_err(|_| {
-                    RalphError::Configuration(
-                        "GEMINI_API_KEY or GOOGLE_API_KEY environment variable not set".into(),
-                    )
-                })?;
-            let client = GeminiModel::new(api_key, &config.model_name).map_err(|e| RalphError::Model {
-                provider: "gemini".into(),
-                message: e.to_string(),
-            })?;
-            Arc::new(client)
-        }
-        provider => {
-            return Err(RalphError::Configuration(format!(
-                "Unsupported model provider: {}. Supported: anthropic, openai, gemini",
-                provider
-            )));
-        }
-    };
-
-    Ok(model)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -499,6 +750,7 @@ mod tests {
         assert_eq!(builder.model_config.provider, "gemini");
         assert_eq!(builder.prd_path, PathBuf::from("prd.md"));
         assert_eq!(builder.design_path, PathBuf::from("design.md"));
+        assert_eq!(builder.design_json_path, PathBuf::from("design.json"));
         assert_eq!(builder.tasks_path, PathBuf::from("tasks.json"));
     }
 
@@ -509,6 +761,646 @@ mod tests {
         assert!(instruction.contains("tasks"));
         assert!(instruction.contains("components"));
     }
+
+    #[test]
+    fn test_task_env_parsed_from_architect_json() {
+        let json = serde_json::json!({
+            "design": { "language": "rust" },
+            "tasks": [{
+                "id": "T-004",
+                "title": "Integration tests",
+                "description": "Exercise the API against a mock",
+                "priority": 3,
+                "estimated_complexity": "medium",
+                "env": { "API_BASE_URL": "http://localhost:9000", "bad-name": "x", "PORT": 8080 }
+            }]
+        });
+        let tasks = json_to_task_list(&json, "api").unwrap();
+        let env = &tasks.tasks[0].env;
+        assert_eq!(env.len(), 1);
+        assert_eq!(env["API_BASE_URL"], "http://localhost:9000");
+    }
+
+    #[test]
+    fn test_empty_task_list_is_rejected() {
+        let json = serde_json::json!({ "design": { "language": "rust" }, "tasks": [] });
+        let empty = json_to_task_list(&json, "calc").unwrap();
+        let err = check_task_count(&empty, false).unwrap_err();
+        assert!(err.to_string().contains("architect produced no tasks"));
+        assert!(check_task_count(&empty, true).is_ok());
+
+        let json = serde_json::json!({
+            "design": { "language": "rust" },
+            "tasks": [{ "id": "T-001", "title": "Hello", "description": "Print it", "priority": 1 }]
+        });
+        assert!(check_task_count(&json_to_task_list(&json, "hello").unwrap(), false).is_ok());
+    }
+
+    #[test]
+    fn test_task_model_parsed_from_architect_json() {
+        let json = serde_json::json!({
+            "design": { "language": "rust" },
+            "tasks": [
+                {
+                    "id": "T-001",
+                    "title": "Scaffold",
+                    "description": "Set up the crate",
+                    "priority": 1,
+                    "estimated_complexity": "low"
+                },
+                {
+                    "id": "T-002",
+                    "title": "Type checker",
+                    "description": "The hard part",
+                    "priority": 2,
+                    "estimated_complexity": "high",
+                    "model": { "provider": "anthropic", "model_name": "claude-opus-4-5" }
+                },
+                {
+                    "id": "T-003",
+                    "title": "Docs",
+                    "description": "Write the README",
+                    "priority": 4,
+                    "estimated_complexity": "low",
+                    "model": { "provider": "nonexistent", "model_name": "x" }
+                }
+            ]
+        });
+        let tasks = json_to_task_list(&json, "lang").unwrap();
+        assert_eq!(tasks.tasks[0].model, None);
+        let model = tasks.tasks[1].model.as_ref().unwrap();
+        assert_eq!(model.provider, "anthropic");
+        assert_eq!(model.model_name, "claude-opus-4-5");
+        assert_eq!(tasks.tasks[2].model, None);
+    }
+
+    #[test]
+    fn test_flatten_design_structure_moves_paths_and_logs_changes() {
+        let json = json!({
+            "design": {
+                "project": "calc",
+                "overview": "Calculator",
+                "language": "rust",
+                "components": [{ "name": "Eval", "purpose": "Evaluate", "file": "src/core/eval/mod.rs" }],
+                "file_structure": { "files": ["Cargo.toml", "src/main.rs", "src/core/eval/mod.rs"] }
+            },
+            "tasks": [{
+                "id": "TASK-001",
+                "title": "Evaluator",
+                "description": "Evaluate",
+                "priority": 1,
+                "estimated_complexity": "low",
+                "files_to_create": ["src/core/eval/mod.rs"],
+                "files_to_modify": ["src/main.rs"]
+            }]
+        });
+        let (mut design, mut tasks) = parse_architect_response(&json.to_string()).unwrap();
+        assert!(!StructureLimits::default().violations(design.file_structure.as_ref().unwrap()).is_empty());
+
+        flatten_design_structure(&mut design, &mut tasks);
+        assert_eq!(design.components[0].file_path.as_deref(), Some("src/core/mod.rs"));
+        assert_eq!(tasks.tasks[0].files_created, vec!["src/core/mod.rs"]);
+        assert_eq!(tasks.tasks[0].files_modified, vec!["src/main.rs"]);
+        assert_eq!(design.changelog, vec!["Flattened single-child directories `src/core/eval/` into `src/core/`"]);
+        assert!(design.to_markdown().contains("## Changelog"));
+    }
+
+    #[test]
+    fn test_test_infrastructure_becomes_an_early_task() {
+        let mut json = json!({
+            "design": {
+                "project": "calc",
+                "overview": "Calculator",
+                "language": "rust",
+                "components": [],
+                "test_infrastructure": {
+                    "helper_module": "tests/common/mod.rs",
+                    "fixtures": ["temp_workspace()"],
+                    "factories": ["expr(&str)"]
+                }
+            },
+            "tasks": [
+                { "id": "T-001", "title": "Setup", "description": "Scaffold", "priority": 1,
+                  "estimated_complexity": "low", "setup": true },
+                { "id": "T-002", "title": "Parser", "description": "Parse", "priority": 2,
+                  "estimated_complexity": "medium", "dependencies": ["T-001"] }
+            ]
+        });
+        let (design, tasks) = parse_architect_response(&json.to_string()).unwrap();
+        let infra = design.test_infrastructure.as_ref().unwrap();
+        assert_eq!(infra.helper_module.as_deref(), Some("tests/common/mod.rs"));
+        assert_eq!(infra.fixtures, vec!["temp_workspace()"]);
+        assert_eq!(infra.factories, vec!["expr(&str)"]);
+
+        let ids: Vec<&str> = tasks.tasks.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["T-001", TEST_INFRASTRUCTURE_TASK_ID, "T-002"]);
+        let helpers = &tasks.tasks[1];
+        assert_eq!(helpers.dependencies, vec!["T-001"]);
+        assert_eq!(helpers.files_created, vec!["tests/common/mod.rs"]);
+        assert!(helpers.description.contains("Fixtures: temp_workspace()."));
+        assert!(tasks.validate().is_ok());
+
+        // A planned task already creating the module is kept as the only one
+        json["tasks"][1]["files_to_create"] = json!(["./tests/common/mod.rs"]);
+        let (_, tasks) = parse_architect_response(&json.to_string()).unwrap();
+        assert_eq!(tasks.tasks.len(), 2);
+
+        // No section, no task
+        json["design"]["test_infrastructure"] = json!({ "fixtures": [] });
+        let (design, tasks) = parse_architect_response(&json.to_string()).unwrap();
+        assert_eq!(design.test_infrastructure, None);
+        assert_eq!(tasks.tasks.len(), 2);
+    }
+
+    #[test]
+    fn test_uncovered_component_list_names_components_and_files() {
+        let json = json!({
+            "design": {
+                "project": "calc",
+                "overview": "Calculator",
+                "language": "rust",
+                "components": [
+                    { "name": "Parser", "purpose": "Parse", "file": "src/parser.rs" },
+                    { "name": "History", "purpose": "Remember results", "file": "src/history.rs" }
+                ]
+            },
+            "tasks": [{
+                "id": "T-001",
+                "title": "Parser",
+                "description": "Write the parser",
+                "priority": 1,
+                "files_to_create": ["src/parser.rs"]
+            }]
+        });
+        let (design, tasks) = parse_architect_response(&json.to_string()).unwrap();
+        assert_eq!(uncovered_component_list(&design, &tasks), "History (src/history.rs)");
+    }
+
+    #[test]
+    fn test_glossary_parsed_from_architect_json() {
+        let json = json!({
+            "design": {
+                "project": "shop",
+                "glossary": [
+                    { "term": "Order", "definition": "A request to buy", "avoid": ["purchase"] },
+                    { "definition": "no term" },
+                    { "term": "orders" }
+                ]
+            },
+            "tasks": [{ "id": "T-001", "title": "Setup", "description": "Set up", "priority": 1 }]
+        });
+        let (design, _) = parse_architect_response(&json.to_string()).unwrap();
+        assert_eq!(
+            design.glossary,
+            vec![crate::glossary::GlossaryTerm::new("order")
+                .with_definition("A request to buy")
+                .with_avoid(&["purchase"])]
+        );
+    }
+
+    #[test]
+    fn test_extra_technology_stack_keys_are_kept() {
+        let json = json!({
+            "design": {
+                "project": "shop",
+                "language": "rust",
+                "technology_stack": {
+                    "testing": "cargo test",
+                    "build": "cargo",
+                    "dependencies": ["axum"],
+                    "framework": "axum",
+                    "database": " PostgreSQL ",
+                    "deployment": ["docker", "fly.io"],
+                    "ports": { "http": 8080 },
+                    "notes": ""
+                }
+            },
+            "tasks": [{ "id": "T-001", "title": "Setup", "description": "Set up", "priority": 1 }]
+        });
+        let (design, _) = parse_architect_response(&json.to_string()).unwrap();
+        let tech = design.technology_stack.as_ref().unwrap();
+        assert_eq!((tech.build_tool.as_str(), tech.dependencies.clone()), ("cargo", vec!["axum".to_string()]));
+        let mut additional: Vec<_> = tech.additional.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        additional.sort();
+        assert_eq!(
+            additional,
+            [("database", "PostgreSQL"), ("deployment", "docker, fly.io"), ("framework", "axum"), ("ports", r#"{"http":8080}"#)]
+        );
+
+        let markdown = design.to_markdown();
+        assert!(markdown.contains("### Other\n\n- **database**: PostgreSQL\n"), "{}", markdown);
+        let parsed = crate::models::DesignDocument::parse_markdown(&markdown).unwrap();
+        assert_eq!(parsed.technology_stack.unwrap().additional, tech.additional);
+    }
+
+    #[test]
+    fn test_render_plan_text_and_json() {
+        let json = json!({
+            "design": { "project": "calc", "overview": "Calculator", "language": "rust" },
+            "tasks": [{ "id": "T-001", "title": "Parser", "description": "Write the parser", "priority": 1 }]
+        });
+        let (design, tasks) = parse_architect_response(&json.to_string()).unwrap();
+
+        let text = render_plan(&design, &tasks, PlanFormat::Text).unwrap();
+        let (markdown, tasks_json) = text.split_once(&format!("\n{}\n", PLAN_SEPARATOR)).unwrap();
+        assert_eq!(markdown, design.to_markdown().trim_end());
+        let parsed: crate::models::TaskList = serde_json::from_str(tasks_json).unwrap();
+        assert_eq!(parsed.get_all_tasks()[0].id, "T-001");
+
+        let envelope: serde_json::Value =
+            serde_json::from_str(&render_plan(&design, &tasks, PlanFormat::Json).unwrap()).unwrap();
+        assert_eq!(envelope["design_markdown"], design.to_markdown());
+        assert_eq!(envelope["tasks"]["project"], tasks.project.as_str());
+    }
+
+    #[test]
+    fn test_builder_dry_run_and_stdout_defaults() {
+        let builder = ArchitectAgentBuilder::default();
+        assert!(!builder.dry_run && !builder.output_to_stdout);
+        let builder = builder.prd_content("# PRD").dry_run(true).output_to_stdout(true).plan_format(PlanFormat::Json);
+        assert_eq!(builder.prd_content.as_deref(), Some("# PRD"));
+        assert_eq!(builder.plan_format, PlanFormat::Json);
+    }
+
+    #[test]
+    fn test_invalid_json_is_retried_with_the_parse_error() {
+        assert_eq!(ArchitectAgentBuilder::default().max_parse_retries, DEFAULT_MAX_PARSE_RETRIES);
+        assert_eq!(ArchitectAgentBuilder::default().max_parse_retries(0).max_parse_retries, 0);
+
+        let secs = |attempt| parse_retry_delay(attempt).as_secs();
+        assert_eq!([secs(1), secs(2), secs(3)], [1, 2, 4]);
+        assert_eq!(secs(40), 64);
+
+        let error = serde_json::from_str::<serde_json::Value>("```json\n{}\n```").unwrap_err();
+        let message = invalid_json_message(&error);
+        assert!(message.contains(&format!("({})", error)), "{}", message);
+        assert!(message.contains("no markdown code fences"));
+    }
+
+    #[test]
+    fn test_extract_json_strips_fences_and_prose() {
+        let object = r#"{"design": {"diagram": "```mermaid\nflowchart LR\n```"}}"#;
+        assert_eq!(extract_json(object), object);
+        assert_eq!(extract_json(&format!("  {}\n", object)), object);
+
+        // Fenced, with and without a language tag
+        assert_eq!(extract_json(&format!("```json\n{}\n```", object)), object);
+        assert_eq!(extract_json(&format!("```\n{}\n```\n", object)), object);
+
+        // Double-fenced
+        assert_eq!(extract_json(&format!("```json\n```json\n{}\n```\n```", object)), object);
+
+        // Prose before and after
+        assert_eq!(extract_json(&format!("Here is the design:\n\n{}", object)), object);
+        assert_eq!(
+            extract_json(&format!("Here is the design:\n```json\n{}\n```\nLet me know if you need changes.", object)),
+            object
+        );
+        assert_eq!(extract_json(&format!("{}\nHope this helps!", object)), object);
+        assert_eq!(extract_json("Plan: [1, 2]"), "[1, 2]");
+
+        // Nothing that looks like JSON is passed through for the parse error
+        assert_eq!(extract_json(" I cannot do that. "), "I cannot do that.");
+    }
+
+    #[test]
+    fn test_parse_json_reply_recovers_the_first_object() {
+        let object = r#"{"design": {"name": "a } b", "quote": "\"{"}}"#;
+        assert_eq!(balanced_object(object), Some(object));
+        assert_eq!(balanced_object("{ unclosed"), None);
+        assert_eq!(balanced_object("no object"), None);
+
+        // Trailing commentary with its own braces defeats `extract_json` alone
+        let reply = format!("Here is the design:\n{}\nUse {{braces}} as needed.", object);
+        assert_eq!(parse_json_reply(&reply).unwrap()["design"]["name"], "a } b");
+        let fenced = format!("```json\n{}\n```\nSee {{x}}.", object);
+        assert_eq!(parse_json_reply(&fenced).unwrap()["design"]["quote"], "\"{");
+    }
+
+    #[test]
+    fn test_broken_json_error_names_the_offset() {
+        let reply = "Here is the design:\n```json\n{\"design\": {\"name\": oops}}\n```";
+        let error = parse_architect_response(reply).unwrap_err().to_string();
+        let offset = reply.find("oops").unwrap();
+        assert!(error.contains(&format!("at byte {}", offset)), "{}", error);
+        assert!(error.contains("\"name\": <<HERE>>oops"), "{}", error);
+        assert!(error.contains("Response: Here is the design:"), "{}", error);
+
+        // A long reply is quoted well past the old 500 characters
+        let long = format!("{{\"design\": \"{}", "é".repeat(3000));
+        let error = parse_architect_response(&long).unwrap_err().to_string();
+        assert!(error.matches('é').count() > 1500, "{}", error.len());
+    }
+
+    #[test]
+    fn test_structure_retry_message_quotes_violations() {
+        let message = structure_retry_message(&["directories containing only another directory: src/a/b".to_string()]);
+        assert!(message.contains("- directories containing only another directory: src/a/b"));
+        assert!(message.contains("files_to_create"));
+    }
+
+    fn text_chunk(text: &str) -> std::result::Result<ReplyChunk, String> {
+        Ok(ReplyChunk {
+            text: text.to_string(),
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_collect_reply_rejects_empty_stream() {
+        let err = collect_reply(futures::stream::iter(Vec::new()), &|_| {}).await.unwrap_err();
+        assert!(matches!(err, RalphError::EmptyResponse { .. }));
+        assert!(err.is_retryable());
+        assert!(err.to_string().contains("no finish reason"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_collect_reply_rejects_whitespace_with_finish_reason() {
+        let chunks = vec![
+            text_chunk("  \n"),
+            Ok(ReplyChunk {
+                text: "\t".to_string(),
+                finish_reason: Some("MaxTokens".to_string()),
+                error: Some("output truncated".to_string()),
+            }),
+        ];
+        let err = collect_reply(futures::stream::iter(chunks), &|_| {}).await.unwrap_err();
+        assert!(err.is_retryable());
+        assert_eq!(
+            err.to_string(),
+            "Empty response from architect: no text, finish reason MaxTokens; \
+             model error: output truncated"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_collect_reply_surfaces_stream_error() {
+        let chunks = vec![text_chunk("{\"design\""), Err("connection reset".to_string())];
+        let err = collect_reply(futures::stream::iter(chunks), &|_| {}).await.unwrap_err();
+        assert!(matches!(&err, RalphError::Agent { message, .. } if message == "connection reset"));
+        assert!(!err.is_retryable());
+
+        let text = collect_reply(futures::stream::iter(vec![text_chunk("{"), text_chunk("}")]), &|_| {})
+            .await
+            .unwrap();
+        assert_eq!(text, "{}");
+    }
+
+    #[tokio::test]
+    async fn test_collect_reply_streams_text_chunks_in_order() {
+        let seen = std::sync::Mutex::new(Vec::new());
+        let chunks = vec![
+            text_chunk("{\"design\""),
+            Ok(ReplyChunk {
+                finish_reason: Some("Stop".to_string()),
+                ..Default::default()
+            }),
+            text_chunk(": {}}"),
+        ];
+        let text = collect_reply(futures::stream::iter(chunks), &|chunk| {
+            seen.lock().unwrap().push(chunk.to_string())
+        })
+        .await
+        .unwrap();
+        assert_eq!(text, "{\"design\": {}}");
+        // Chunks without text are not passed on
+        assert_eq!(*seen.lock().unwrap(), vec!["{\"design\"", ": {}}"]);
+    }
+
+    /// Fails its first `failures` calls with `error`, then answers with a one-task plan.
+    struct FlakyModel {
+        failures: usize,
+        error: &'static str,
+        calls: std::sync::Mutex<usize>,
+    }
+
+    #[async_trait::async_trait]
+    impl Llm for FlakyModel {
+        fn name(&self) -> &str {
+            "flaky"
+        }
+
+        async fn generate_content(
+            &self,
+            _request: adk_rust::LlmRequest,
+            _stream: bool,
+        ) -> adk_rust::Result<adk_rust::LlmResponseStream> {
+            let call = {
+                let mut calls = self.calls.lock().unwrap();
+                *calls += 1;
+                *calls
+            };
+            if call <= self.failures {
+                return Err(adk_rust::AdkError::Model(self.error.to_string()));
+            }
+            let plan = json!({
+                "design": { "project": "hello", "overview": "Says hello", "language": "rust", "components": [] },
+                "tasks": [{ "id": "T-001", "title": "Hello", "description": "Print it", "priority": 1,
+                            "estimated_complexity": "low" }]
+            });
+            let response = adk_rust::LlmResponse::new(adk_rust::Content::new("model").with_text(plan.to_string()));
+            Ok(Box::pin(futures::stream::once(async move { Ok(response) })))
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct BufferWriter(Arc<std::sync::Mutex<String>>);
+
+    impl crate::output::OutputWriter for BufferWriter {
+        fn write_stdout(&mut self, text: &str) {
+            self.0.lock().unwrap().push_str(text);
+        }
+
+        fn write_stderr(&mut self, text: &str) {
+            self.0.lock().unwrap().push_str(text);
+        }
+    }
+
+    /// Run generate() against `model` with no waits between attempts.
+    async fn generate_with(model: Arc<FlakyModel>, max_attempts: u32) -> (Result<usize>, String) {
+        let dir = tempfile::tempdir().unwrap();
+        let buffer = BufferWriter::default();
+        let output = RalphOutput::with_writer(crate::models::DebugLevel::Normal, buffer.clone());
+        let policy = RetryPolicy::default()
+            .with_max_attempts(max_attempts)
+            .with_delays(std::time::Duration::ZERO, std::time::Duration::ZERO);
+        let architect = ArchitectAgent::builder()
+            .model(model)
+            .project_path(dir.path())
+            .prd_content("# Hello\n\nPrint hello.")
+            .dry_run(true)
+            .retry_policy(policy)
+            .output(output.clone())
+            .build()
+            .await
+            .unwrap();
+        let result = architect.generate().await.map(|(_, tasks)| tasks.get_all_tasks().len());
+        output.flush();
+        let printed = buffer.0.lock().unwrap().clone();
+        (result, printed)
+    }
+
+    #[tokio::test]
+    async fn test_transient_failures_are_retried_with_warnings() {
+        let model = Arc::new(FlakyModel { failures: 2, error: "HTTP 503: Service Unavailable", calls: Default::default() });
+        let (result, printed) = generate_with(model.clone(), 3).await;
+        assert_eq!(result.unwrap(), 1);
+        assert_eq!(*model.calls.lock().unwrap(), 3);
+        assert!(printed.contains("retrying in 0ms (attempt 2 of 3)"), "{}", printed);
+        assert!(printed.contains("(attempt 3 of 3)"), "{}", printed);
+
+        // Out of attempts: the last failure is returned
+        let model = Arc::new(FlakyModel { failures: 5, error: "HTTP 429 Too Many Requests", calls: Default::default() });
+        let (result, _) = generate_with(model.clone(), 2).await;
+        assert!(result.unwrap_err().is_retryable());
+        assert_eq!(*model.calls.lock().unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_non_retryable_failures_fail_at_once() {
+        let model = Arc::new(FlakyModel { failures: 1, error: "HTTP 401: Unauthorized", calls: Default::default() });
+        let (result, printed) = generate_with(model.clone(), 3).await;
+        assert!(!result.unwrap_err().is_retryable());
+        assert_eq!(*model.calls.lock().unwrap(), 1);
+        assert!(!printed.contains("retrying"), "{}", printed);
+    }
+
+    #[test]
+    fn test_split_test_tasks_pairs_each_implementation_task() {
+        assert!(!ArchitectAgentBuilder::default().split_test_tasks);
+        assert!(ArchitectAgentBuilder::default().split_test_tasks(true).split_test_tasks);
+
+        let reply = json!({
+            "design": { "project": "calc", "overview": "o", "language": "rust", "components": [] },
+            "tasks": [
+                { "id": "T-001", "title": "Setup", "description": "", "priority": 1, "setup": true },
+                { "id": "T-002", "title": "Parser", "description": "", "priority": 2,
+                  "estimated_complexity": "high", "user_story_id": "US-001",
+                  "acceptance_criteria": ["parses 1+2"], "dependencies": ["T-001"] },
+                { "id": "T-003", "title": "Eval", "description": "", "priority": 3, "dependencies": ["T-002"] },
+                { "id": "T-003-test", "title": "Eval tests", "description": "", "priority": 3,
+                  "dependencies": ["T-003"] },
+                { "id": "T-004", "title": "More tests", "description": "", "priority": 4,
+                  "files_to_create": ["tests/more.rs"] }
+            ]
+        });
+        let (_, mut tasks) = parse_architect_response(&reply.to_string()).unwrap();
+        split_test_tasks(&mut tasks);
+
+        let ids: Vec<&str> = tasks.tasks.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, ["T-001", "T-002", "T-002-test", "T-003", "T-003-test", "T-004"]);
+        let test = tasks.get_task("T-002-test").unwrap();
+        assert_eq!(test.dependencies, ["T-002"]);
+        assert_eq!(test.acceptance_criteria, ["parses 1+2"]);
+        assert_eq!(test.user_story_id.as_deref(), Some("US-001"));
+        assert_eq!(test.priority, 2);
+        assert_eq!(test.conventional_type(), CommitType::Test);
+        assert!(check_dependencies(&tasks).is_ok());
+
+        // Running it again adds nothing
+        split_test_tasks(&mut tasks);
+        assert_eq!(tasks.tasks.len(), 6);
+    }
+
+    #[test]
+    fn test_plans_with_impossible_dependencies_are_rejected() {
+        let plan = |tasks: serde_json::Value| {
+            let reply = json!({
+                "design": { "project": "p", "overview": "o", "language": "rust", "components": [] },
+                "tasks": tasks
+            });
+            parse_architect_response(&reply.to_string()).unwrap().1
+        };
+
+        let ok = plan(json!([
+            { "id": "T-001", "title": "a", "description": "", "priority": 1 },
+            { "id": "T-002", "title": "b", "description": "", "priority": 1, "dependencies": ["T-001"] }
+        ]));
+        assert!(check_dependencies(&ok).is_ok());
+
+        let cyclic = plan(json!([
+            { "id": "T-001", "title": "a", "description": "", "priority": 1, "dependencies": ["T-002"] },
+            { "id": "T-002", "title": "b", "description": "", "priority": 1, "dependencies": ["T-001", "T-009"] }
+        ]));
+        let error = check_dependencies(&cyclic).unwrap_err();
+        assert!(matches!(error, RalphError::Task(_)));
+        let message = error.to_string();
+        assert!(message.contains("T-002 depends on unknown task T-009"), "{}", message);
+        assert!(message.contains("dependency cycle among T-001, T-002"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn test_generate_uses_the_configured_paths() {
+        let model = || Arc::new(FlakyModel { failures: 0, error: "", calls: Default::default() });
+
+        // Defaults: prd.md in, design.md and tasks.json out, all in the project
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("prd.md"), "# Hello\n\nPrint hello.").unwrap();
+        let architect = ArchitectAgent::builder().model(model()).project_path(dir.path()).build().await.unwrap();
+        architect.generate().await.unwrap();
+        assert!(dir.path().join("design.md").is_file());
+        assert!(dir.path().join("tasks.json").is_file());
+        let design = crate::models::DesignDocument::load(dir.path().join("design.json")).unwrap();
+        let markdown = std::fs::read_to_string(dir.path().join("design.md")).unwrap();
+        assert_eq!(design.to_markdown(), markdown);
+        let response = std::fs::read_to_string(dir.path().join(ARCHITECT_RESPONSE_FILE)).unwrap();
+        assert!(serde_json::from_str::<serde_json::Value>(&response).is_ok());
+
+        // Relative paths resolve against the project; missing directories are created
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("docs")).unwrap();
+        std::fs::write(dir.path().join("docs/requirements.md"), "# Hello\n\nPrint hello.").unwrap();
+        let architect = ArchitectAgent::builder()
+            .model(model())
+            .project_path(dir.path())
+            .prd_path("docs/requirements.md")
+            .design_path(".ralph/design.md")
+            .design_json_path(".ralph/design.json")
+            .tasks_path(".ralph/tasks.json")
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(architect.tasks_path(), &dir.path().join(".ralph/tasks.json"));
+        architect.generate().await.unwrap();
+        assert!(dir.path().join(".ralph/design.md").is_file());
+        assert!(crate::models::DesignDocument::load(dir.path().join(".ralph/design.json")).is_ok());
+        assert!(crate::models::TaskList::load(dir.path().join(".ralph/tasks.json")).is_ok());
+        assert!(!dir.path().join("design.md").exists());
+        assert!(!dir.path().join("tasks.json").exists());
+
+        // A PRD that is not where it was configured is reported by path
+        let architect = ArchitectAgent::builder()
+            .model(model())
+            .project_path(dir.path())
+            .prd_path("prd.md")
+            .build()
+            .await
+            .unwrap();
+        let error = architect.generate().await.unwrap_err().to_string();
+        assert!(error.contains("prd.md"), "{}", error);
+
+        // An absolute PRD path is read as is, outside the project
+        let specs = tempfile::tempdir().unwrap();
+        std::fs::write(specs.path().join("spec.md"), "# Hello\n\nPrint hello.").unwrap();
+        let architect = ArchitectAgent::builder()
+            .model(model())
+            .project_path(dir.path())
+            .prd_path(specs.path().join("spec.md"))
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(architect.prd_path(), &specs.path().join("spec.md"));
+        architect.generate().await.unwrap();
+    }
+
+    #[test]
+    fn test_parse_error_snippet_is_char_safe() {
+        let err = parse_architect_response("é").unwrap_err();
+        assert!(err.to_string().ends_with("Response: é"), "{}", err);
+        let long = "ü".repeat(600);
+        let err = parse_architect_response(&long).unwrap_err();
+        assert!(err.to_string().ends_with(&"ü".repeat(500)));
+    }
 }
 
 
@@ -519,18 +1411,136 @@ impl ArchitectAgent {
     /// 1. Reads the PRD file
     /// 2. Creates a session for the agent
     /// 3. Runs the agent with PRD content (returns structured JSON)
-    /// 4. Parses the JSON and writes design.md + tasks.json
+    /// 4. Parses the JSON and writes design.md + tasks.json (unless dry run)
+    ///    and prints them when stdout output is on
     /// 5. Returns the parsed documents
     pub async fn generate(&self) -> Result<(crate::models::DesignDocument, crate::models::TaskList)> {
+        self.generate_with_callback(|_| {}).await
+    }
+
+    /// [`generate`](Self::generate), calling `on_text` with each chunk of
+    /// reply text as it streams in, before the reply is parsed.
+    ///
+    /// Design generation can take minutes; wire this to
+    /// [`RalphOutput::llm_text`] to show the reply live at Verbose. Replies
+    /// re-asked after a parse error or structure violation stream too.
+    pub async fn generate_with_callback(
+        &self,
+        on_text: impl Fn(&str) + Sync,
+    ) -> Result<(crate::models::DesignDocument, crate::models::TaskList)> {
+        // Read the PRD file first
+        let prd_content = self.read_prd()?;
+
+        if !self.dry_run {
+            if let Err(e) = AssembledPrompt::architect(&prd_content).record(&self.project_path) {
+                tracing::debug!(error = %e, "Could not record architect prompt");
+            }
+        }
+
+        let mut message = architect_user_message(&prd_content);
+        if let Some(context) = crate::template::warm_start_context(&self.project_path) {
+            message.push_str(&context);
+        }
+        if self.split_test_tasks {
+            message.push_str(SPLIT_TEST_TASKS_NOTE);
+        }
+        let (mut design, mut tasks) = self.plan(message, &on_text).await?;
+        if self.split_test_tasks {
+            split_test_tasks(&mut tasks);
+        }
+        self.check_task_count(&tasks)?;
+        self.check_component_coverage(&design, &tasks)?;
+
+        // Record this run's models, keeping the PRD agent's entry from prd.md
+        let meta = Provenance::new()
+            .with_agent("architect", self.provenance.clone())
+            .merged_with(provenance::parse_markdown_header(&prd_content).as_ref());
+        design.meta = Some(meta.clone());
+        tasks.meta = Some(meta);
+
+        self.save(&design, &tasks)?;
+        self.save_glossary(&design, &prd_content);
+        Ok((design, tasks))
+    }
+
+    /// Revise the design after tasks failed because the design was wrong.
+    ///
+    /// `failures` pairs each failed task ID with a summary of its errors. The
+    /// architect gets the current design.md, the failed tasks and the PRD, and
+    /// returns a corrected design and task list. Completed tasks are carried
+    /// over from `tasks` (see [`TaskList::merge_from`]), and the replan is noted
+    /// in the design changelog. Both files are rewritten.
+    ///
+    /// [`TaskList::merge_from`]: crate::models::TaskList::merge_from
+    pub async fn replan(
+        &self,
+        tasks: &crate::models::TaskList,
+        failures: &[(String, String)],
+    ) -> Result<(crate::models::DesignDocument, crate::models::TaskList)> {
+        if failures.is_empty() {
+            return Err(RalphError::Design(
+                "Nothing to replan: no failed tasks were given".to_string(),
+            ));
+        }
+
+        let prd_content = self.read_prd()?;
+        let design_markdown = std::fs::read_to_string(&self.design_path).map_err(|e| {
+            RalphError::Design(format!("Failed to read {}: {}", self.design_path.display(), e))
+        })?;
+
+        let message = architect_replan_message(&prd_content, &design_markdown, tasks, failures);
+        let (mut design, mut new_tasks) = self.plan(message, &|_| {}).await?;
+
+        let preserved = new_tasks.merge_from(tasks);
+        new_tasks.validate().map_err(|e| {
+            RalphError::Task(format!("Replanned task list is invalid: {}", e))
+        })?;
+        self.check_task_count(&new_tasks)?;
+        self.check_component_coverage(&design, &new_tasks)?;
+
+        // Keep the earlier changelog and record why the design changed
+        let previous = crate::models::DesignDocument::parse_markdown(&design_markdown).ok();
+        let mut changelog = previous.map(|d| d.changelog).unwrap_or_default();
+        changelog.push(format!(
+            "Replanned after failures in {} (kept {} completed task(s))",
+            failures.iter().map(|(id, _)| id.as_str()).collect::<Vec<_>>().join(", "),
+            preserved.len()
+        ));
+        changelog.append(&mut design.changelog);
+        design.changelog = changelog;
+
+        let meta = Provenance::new()
+            .with_agent("architect", self.provenance.clone())
+            .merged_with(provenance::parse_markdown_header(&design_markdown).as_ref());
+        design.meta = Some(meta.clone());
+        new_tasks.meta = Some(meta);
+
+        self.save(&design, &new_tasks)?;
+        self.save_glossary(&design, &prd_content);
+        Ok((design, new_tasks))
+    }
+
+    /// Read the PRD from its configured path, or the PRD given to the builder.
+    fn read_prd(&self) -> Result<String> {
+        if let Some(prd) = &self.prd_content {
+            return Ok(prd.clone());
+        }
+        std::fs::read_to_string(&self.prd_path).map_err(|e| {
+            RalphError::Prd(format!("Failed to read PRD file {}: {}", self.prd_path.display(), e))
+        })
+    }
+
+    /// Run the agent in a fresh session with `message` and parse its plan,
+    /// keeping the file tree within the structure limits. Reply text is
+    /// passed to `on_text` as it arrives.
+    async fn plan(
+        &self,
+        message: String,
+        on_text: &(dyn Fn(&str) + Sync),
+    ) -> Result<(crate::models::DesignDocument, crate::models::TaskList)> {
         use adk_rust::{Content, Part};
         use adk_rust::runner::{Runner, RunnerConfig};
         use adk_rust::session::{CreateRequest, InMemorySessionService, SessionService};
-        use futures::StreamExt;
-
-        // Read the PRD file first
-        let prd_path = self.project_path.join("prd.md");
-        let prd_content = std::fs::read_to_string(&prd_path)
-            .map_err(|e| RalphError::Prd(format!("Failed to read PRD file: {}", e)))?;
 
         // Create session service
         let session_service: Arc<dyn SessionService> = Arc::new(InMemorySessionService::new());
@@ -565,77 +1575,680 @@ impl ArchitectAgent {
             message: e.to_string(),
         })?;
 
-        // Create user content with the PRD included
         let user_content = Content {
             role: "user".to_string(),
-            parts: vec![Part::Text {
-                text: format!(
-                    "Generate the system design and task breakdown for the following PRD:\n\n---\n{}\n---",
-                    prd_content
-                ),
-            }],
+            parts: vec![Part::Text { text: message }],
         };
 
         // Run the agent and collect the structured JSON response
-        let mut stream = runner
-            .run("user".to_string(), session_id, user_content)
-            .await
-            .map_err(|e| RalphError::Agent {
-                agent: "architect".to_string(),
-                message: e.to_string(),
-            })?;
+        let (mut design, mut tasks) = self.ask_for_plan(&runner, &session_id, user_content, on_text).await?;
+
+        // Keep the file tree proportional: re-ask once in strict mode, else flatten
+        let mut violations = self.structure_violations(&design);
+        if !violations.is_empty() && self.structure_limits.strict {
+            tracing::warn!(violations = ?violations, "Architect file structure exceeds limits; asking for a flatter one");
+            let retry_content = Content {
+                role: "user".to_string(),
+                parts: vec![Part::Text {
+                    text: structure_retry_message(&violations),
+                }],
+            };
+            (design, tasks) = self.ask_for_plan(&runner, &session_id, retry_content, on_text).await?;
+            violations = self.structure_violations(&design);
+        }
+        if !violations.is_empty() {
+            tracing::warn!(violations = ?violations, "Architect file structure exceeds limits; flattening single-child directories");
+            flatten_design_structure(&mut design, &mut tasks);
+            let remaining = self.structure_violations(&design);
+            if !remaining.is_empty() {
+                tracing::warn!(violations = ?remaining, "File structure still exceeds limits after flattening");
+            }
+        }
 
-        // Collect all text from the response
-        let mut response_text = String::new();
-        while let Some(result) = stream.next().await {
-            match result {
-                Ok(event) => {
-                    if let Some(content) = &event.llm_response.content {
-                        for part in &content.parts {
-                            if let Part::Text { text } = part {
-                                response_text.push_str(text);
-                            }
-                        }
+        // A plan that can never be finished is not worth starting
+        check_dependencies(&tasks)?;
+
+        Ok((design, tasks))
+    }
+
+    /// Write design.md, design.json and tasks.json (unless dry run), and
+    /// print the plan when stdout output is on.
+    fn save(
+        &self,
+        design: &crate::models::DesignDocument,
+        tasks: &crate::models::TaskList,
+    ) -> Result<()> {
+        if let Some(format) = self.stdout_format {
+            use std::io::Write as _;
+            let plan = render_plan(design, tasks, format)?;
+            let mut stdout = std::io::stdout().lock();
+            stdout
+                .write_all(plan.as_bytes())
+                .and_then(|_| stdout.flush())
+                .map_err(|e| RalphError::Design(format!("Failed to write plan to stdout: {}", e)))?;
+        }
+        if self.dry_run {
+            return Ok(());
+        }
+
+        // Atomic, and creates missing directories
+        crate::persist::write_atomic(&self.design_path, design.to_markdown().as_bytes()).map_err(|e| {
+            RalphError::Design(format!("Failed to write {}: {}", self.design_path.display(), e))
+        })?;
+        design.save(&self.design_json_path).map_err(RalphError::Design)?;
+
+        tasks.save(&self.tasks_path).map_err(RalphError::Task)
+    }
+}
+
+impl ArchitectAgent {
+    /// Send `content` to the architect and parse its plan, re-asking with the
+    /// parse error while the reply is not valid JSON.
+    ///
+    /// Waits 1s, 2s, 4s... between attempts; after `max_parse_retries` re-asks
+    /// the last parse error is returned.
+    async fn ask_for_plan(
+        &self,
+        runner: &adk_rust::runner::Runner,
+        session_id: &str,
+        content: adk_rust::Content,
+        on_text: &(dyn Fn(&str) + Sync),
+    ) -> Result<(crate::models::DesignDocument, crate::models::TaskList)> {
+        let mut content = content;
+        let mut attempt = 0;
+        loop {
+            let response_text = self.ask_architect(runner, session_id, content, on_text).await?;
+            self.record_response(&response_text);
+            match parse_json_reply(&response_text) {
+                Err(e) if attempt < self.max_parse_retries => {
+                    attempt += 1;
+                    tracing::warn!(error = %e, attempt, "Architect reply is not valid JSON; asking again");
+                    tokio::time::sleep(parse_retry_delay(attempt)).await;
+                    content = adk_rust::Content {
+                        role: "user".to_string(),
+                        parts: vec![adk_rust::Part::Text { text: invalid_json_message(&e) }],
+                    };
+                }
+                _ => return parse_architect_response(&response_text),
+            }
+        }
+    }
+
+    /// Keep the architect's latest reply as [`ARCHITECT_RESPONSE_FILE`] (unless
+    /// dry run), so a run archive shows exactly what the model returned.
+    fn record_response(&self, response_text: &str) {
+        if self.dry_run {
+            return;
+        }
+        let path = self.project_path.join(ARCHITECT_RESPONSE_FILE);
+        if let Err(e) = crate::persist::write_atomic(&path, extract_json(response_text).as_bytes()) {
+            tracing::debug!(error = %e, "Could not record architect response");
+        }
+    }
+
+    /// Send one message to the architect, asking again while the failure is
+    /// retryable and the [`RetryPolicy`] allows.
+    ///
+    /// Each retry is logged and, with an output, shown as a warning. An empty
+    /// reply that survives every attempt is reported with the attempt count.
+    async fn ask_architect(
+        &self,
+        runner: &adk_rust::runner::Runner,
+        session_id: &str,
+        content: adk_rust::Content,
+        on_text: &(dyn Fn(&str) + Sync),
+    ) -> Result<String> {
+        let mut attempt = 1;
+        loop {
+            match collect_response(runner, session_id, content.clone(), on_text).await {
+                Err(e) if e.is_retryable() && self.retry_policy.allows_retry(attempt) => {
+                    let delay = self.retry_policy.random_delay(attempt);
+                    tracing::warn!(error = %e, attempt, delay_ms = delay.as_millis() as u64, "Architect reply failed; asking again");
+                    if let Some(output) = &self.output {
+                        output.warn(&retry_warning(&e, delay, attempt + 1, self.retry_policy.max_attempts));
                     }
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
                 }
-                Err(e) => {
-                    return Err(RalphError::Agent {
-                        agent: "architect".to_string(),
-                        message: e.to_string(),
+                Err(RalphError::EmptyResponse { agent, detail }) => {
+                    return Err(RalphError::EmptyResponse {
+                        agent,
+                        detail: format!("{} (after {} attempts)", detail, attempt),
                     });
                 }
+                result => return result,
             }
         }
+    }
 
-        // Parse the JSON response
-        let architect_json: serde_json::Value = serde_json::from_str(&response_text)
-            .map_err(|e| RalphError::Design(format!(
-                "Failed to parse architect JSON: {} - Response: {}", 
-                e, 
-                &response_text[..response_text.len().min(500)]
-            )))?;
+    /// Write `.ralph/glossary.md` from the design's glossary, or from the
+    /// design and PRD when the architect gave none. Failures only warn.
+    fn save_glossary(&self, design: &crate::models::DesignDocument, prd_content: &str) {
+        if self.dry_run {
+            return;
+        }
+        let prd = crate::models::PrdDocument::parse_markdown(prd_content).ok();
+        let glossary = crate::glossary::extract(design, prd.as_ref());
+        if glossary.is_empty() {
+            return;
+        }
+        if let Err(e) = glossary.save(&self.project_path) {
+            tracing::warn!(error = %e, "Could not save the project glossary");
+        }
+    }
 
-        // Convert JSON to DesignDocument and TaskList
-        let design = json_to_design_document(&architect_json["design"])?;
-        let tasks = json_to_task_list(&architect_json, &design.project)?;
+    /// How the design's file tree breaks the configured limits.
+    fn structure_violations(&self, design: &crate::models::DesignDocument) -> Vec<String> {
+        design
+            .file_structure
+            .as_ref()
+            .map(|root| self.structure_limits.violations(root))
+            .unwrap_or_default()
+    }
 
-        // Write design.md
-        let design_path = self.project_path.join("design.md");
-        let design_markdown = design.to_markdown();
-        std::fs::write(&design_path, &design_markdown)
-            .map_err(|e| RalphError::Design(format!("Failed to write design.md: {}", e)))?;
+    /// Reject (or, when allowed, warn about) a plan with no tasks.
+    fn check_task_count(&self, tasks: &crate::models::TaskList) -> Result<()> {
+        check_task_count(tasks, self.allow_empty_plan)
+    }
 
-        // Write tasks.json
-        let tasks_path = self.project_path.join("tasks.json");
-        tasks.save(&tasks_path).map_err(RalphError::Task)?;
+    /// Warn about (or, when strict, reject) components no task creates.
+    fn check_component_coverage(
+        &self,
+        design: &crate::models::DesignDocument,
+        tasks: &crate::models::TaskList,
+    ) -> Result<()> {
+        let uncovered = uncovered_component_list(design, tasks);
+        if uncovered.is_empty() {
+            return Ok(());
+        }
+        if self.coverage_strict {
+            return Err(RalphError::Design(format!(
+                "No task creates the files of these design components: {}",
+                uncovered
+            )));
+        }
+        tracing::warn!(components = %uncovered, "Design components have no task creating their files");
+        crate::warnings::record(Warning::new(
+            WarningCategory::Coverage,
+            format!("No task creates the files of these design components: {}", uncovered),
+        ));
+        Ok(())
+    }
+}
 
-        Ok((design, tasks))
+/// Fail on a plan with no tasks unless `allow_empty`, in which case warn.
+///
+/// An empty plan is almost always a model hiccup or a degenerate PRD;
+/// a single task is a legitimate plan for a trivial project.
+fn check_task_count(tasks: &crate::models::TaskList, allow_empty: bool) -> Result<()> {
+    if !tasks.get_all_tasks().is_empty() {
+        return Ok(());
+    }
+    if !allow_empty {
+        return Err(RalphError::Task("architect produced no tasks".to_string()));
+    }
+    tracing::warn!("Architect produced no tasks; continuing because empty plans are allowed");
+    Ok(())
+}
+
+/// Warning shown before attempt `next` of `max` after `error`.
+fn retry_warning(error: &RalphError, delay: std::time::Duration, next: u32, max: u32) -> String {
+    format!(
+        "Architect call failed: {}; retrying in {} (attempt {} of {})",
+        error.user_message(),
+        crate::timefmt::duration(delay),
+        next,
+        max
+    )
+}
+
+/// Send one message to the architect and collect the text of its reply,
+/// passing each text chunk to `on_text` as it arrives.
+async fn collect_response(
+    runner: &adk_rust::runner::Runner,
+    session_id: &str,
+    content: adk_rust::Content,
+    on_text: &(dyn Fn(&str) + Sync),
+) -> Result<String> {
+    use adk_rust::Part;
+    use futures::StreamExt;
+
+    let stream = runner
+        .run("user".to_string(), session_id.to_string(), content)
+        .await
+        .map_err(|e| RalphError::Agent {
+            agent: "architect".to_string(),
+            message: e.to_string(),
+        })?;
+
+    let chunks = stream.map(|result| {
+        result.map_err(|e| e.to_string()).map(|event| {
+            let response = &event.llm_response;
+            ReplyChunk {
+                text: response
+                    .content
+                    .iter()
+                    .flat_map(|content| &content.parts)
+                    .filter_map(|part| match part {
+                        Part::Text { text } => Some(text.as_str()),
+                        _ => None,
+                    })
+                    .collect(),
+                finish_reason: response.finish_reason.as_ref().map(|r| format!("{:?}", r)),
+                error: response.error_message.clone(),
+            }
+        })
+    });
+    collect_reply(chunks, on_text).await
+}
+
+/// One event of the architect's reply, reduced to what [`collect_reply`] needs.
+#[derive(Debug, Default)]
+struct ReplyChunk {
+    text: String,
+    finish_reason: Option<String>,
+    error: Option<String>,
+}
+
+/// Join the text of a reply stream, passing each non-empty chunk to
+/// `on_text` as it arrives.
+///
+/// A stream error fails with [`RalphError::Agent`], so provider failures are
+/// decoded as usual. A reply with no text but whitespace fails with the
+/// retryable [`RalphError::EmptyResponse`], naming the last finish reason and
+/// any error the model reported.
+async fn collect_reply<S>(mut stream: S, on_text: &(dyn Fn(&str) + Sync)) -> Result<String>
+where
+    S: futures::Stream<Item = std::result::Result<ReplyChunk, String>> + Unpin,
+{
+    use futures::StreamExt;
+
+    let mut response_text = String::new();
+    let mut finish_reason = None;
+    let mut errors = Vec::new();
+    while let Some(result) = stream.next().await {
+        let chunk = result.map_err(|message| RalphError::agent_stream("architect", message))?;
+        if !chunk.text.is_empty() {
+            on_text(&chunk.text);
+        }
+        response_text.push_str(&chunk.text);
+        finish_reason = chunk.finish_reason.or(finish_reason);
+        errors.extend(chunk.error);
+    }
+
+    if !response_text.trim().is_empty() {
+        return Ok(response_text);
+    }
+    let mut detail = match finish_reason {
+        Some(reason) => format!("no text, finish reason {}", reason),
+        None => "no text and no finish reason".to_string(),
+    };
+    if !errors.is_empty() {
+        detail.push_str(&format!("; model error: {}", errors.join("; ")));
+    }
+    Err(RalphError::EmptyResponse {
+        agent: "architect".to_string(),
+        detail,
+    })
+}
+
+/// Keys of the architect's `technology_stack` with a dedicated field.
+const KNOWN_STACK_KEYS: &[&str] = &["testing", "build_tool", "build", "key_dependencies", "dependencies"];
+
+/// Every other `technology_stack` entry (`framework`, `database`, ...) as text:
+/// strings as given, lists joined with commas, anything else as JSON.
+fn additional_stack_entries(stack: &serde_json::Value) -> std::collections::HashMap<String, String> {
+    let Some(entries) = stack.as_object() else {
+        return Default::default();
+    };
+    entries
+        .iter()
+        .filter(|(key, _)| !KNOWN_STACK_KEYS.contains(&key.as_str()))
+        .filter_map(|(key, value)| {
+            let text = match value {
+                serde_json::Value::String(s) => s.trim().to_string(),
+                serde_json::Value::Array(items) => items
+                    .iter()
+                    .map(|item| item.as_str().map(String::from).unwrap_or_else(|| item.to_string()))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                serde_json::Value::Null => String::new(),
+                other => other.to_string(),
+            };
+            (!text.is_empty()).then(|| (key.clone(), text))
+        })
+        .collect()
+}
+
+/// Default for [`ArchitectAgentBuilder::max_parse_retries`].
+const DEFAULT_MAX_PARSE_RETRIES: u32 = 3;
+
+/// Pause before re-asking for the `attempt`th time after invalid JSON: 1s, 2s, 4s...
+fn parse_retry_delay(attempt: u32) -> std::time::Duration {
+    std::time::Duration::from_secs(1 << attempt.saturating_sub(1).min(6))
+}
+
+/// Follow-up telling the architect its previous reply was not valid JSON.
+fn invalid_json_message(error: &serde_json::Error) -> String {
+    format!(
+        "Your previous reply was not valid JSON ({}). Reply again with the complete plan as \
+         a single JSON object matching the response schema: no markdown code fences and no \
+         text before or after it.",
+        error
+    )
+}
+
+/// The JSON in a model reply, without markdown code fences or prose around it.
+///
+/// Handles ```` ```json ```` and bare ```` ``` ```` fences (also nested ones),
+/// text before the fence or the object ("Here is the design:"), and text
+/// after it. Fences inside the JSON's strings (a mermaid diagram) are left
+/// alone. Returns the trimmed input when no JSON start is found.
+fn extract_json(raw: &str) -> &str {
+    let mut text = raw.trim();
+    loop {
+        let fence = text.find("```");
+        let object = text.find(['{', '[']);
+        match (fence, object) {
+            // A fence before any JSON: keep what is between it and the last fence
+            (Some(start), object) if object.is_none_or(|o| start < o) => {
+                let after_open = &text[start + 3..];
+                // The rest of the opening line is the language tag
+                let body = after_open.find('\n').map_or(after_open, |end| &after_open[end + 1..]);
+                let body = body.rfind("```").map_or(body, |end| &body[..end]);
+                text = body.trim();
+            }
+            (_, Some(start)) => {
+                let close = if text[start..].starts_with('{') { '}' } else { ']' };
+                let end = text.rfind(close).filter(|end| *end > start).map_or(text.len(), |end| end + 1);
+                return &text[start..end];
+            }
+            (_, None) => return text,
+        }
+    }
+}
+
+/// The first complete `{...}` object in `text`, counting braces outside strings.
+///
+/// The fallback for replies where [`extract_json`] keeps too much, such as
+/// trailing commentary that itself contains a closing brace.
+fn balanced_object(text: &str) -> Option<&str> {
+    let start = text.find('{')?;
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in text[start..].char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '{' if !in_string => depth += 1,
+            '}' if !in_string => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&text[start..start + i + 1]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Parse a model reply as JSON, tolerating fences and prose around it.
+///
+/// Tries [`extract_json`] first, then the first balanced object. The error is
+/// the one for the [`extract_json`] candidate.
+fn parse_json_reply(text: &str) -> std::result::Result<serde_json::Value, serde_json::Error> {
+    let candidate = extract_json(text);
+    serde_json::from_str(candidate).or_else(|e| {
+        balanced_object(text)
+            .filter(|object| *object != candidate)
+            .and_then(|object| serde_json::from_str(object).ok())
+            .ok_or(e)
+    })
+}
+
+/// Characters shown either side of a JSON parse error.
+const PARSE_ERROR_CONTEXT: usize = 300;
+
+/// Characters of the reply quoted in a JSON parse error.
+const PARSE_ERROR_EXCERPT: usize = 2000;
+
+/// Describe a parse error in `text`: the byte offset it occurred at, the
+/// text around it, and the start of the reply.
+fn json_parse_error(text: &str, error: &serde_json::Error) -> String {
+    let candidate = extract_json(text);
+    // `extract_json` returns a subslice, so its position is a pointer difference
+    let base = candidate.as_ptr() as usize - text.as_ptr() as usize;
+    let line_start: usize = candidate
+        .split_inclusive('\n')
+        .take(error.line().saturating_sub(1))
+        .map(str::len)
+        .sum();
+    let mut offset = (base + line_start + error.column().saturating_sub(1)).min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+
+    let before_start = text[..offset]
+        .char_indices()
+        .rev()
+        .nth(PARSE_ERROR_CONTEXT - 1)
+        .map_or(0, |(i, _)| i);
+    let before = &text[before_start..offset];
+    let after: String = text[offset..].chars().take(PARSE_ERROR_CONTEXT).collect();
+    format!(
+        "Failed to parse architect JSON at byte {}: {} - Near: {}<<HERE>>{} - Response: {}",
+        offset,
+        error,
+        before,
+        after,
+        truncate_display(text, PARSE_ERROR_EXCERPT)
+    )
+}
+
+/// Parse the architect's JSON reply into a design and task list.
+fn parse_architect_response(
+    response_text: &str,
+) -> Result<(crate::models::DesignDocument, crate::models::TaskList)> {
+    let architect_json = parse_json_reply(response_text)
+        .map_err(|e| RalphError::Design(json_parse_error(response_text, &e)))?;
+
+    let design = json_to_design_document(&architect_json["design"])?;
+    let mut tasks = json_to_task_list(&architect_json, &design.project)?;
+    ensure_test_infrastructure_task(&design, &mut tasks);
+    Ok((design, tasks))
+}
+
+/// Reject a task list whose dependencies name unknown tasks or form a cycle.
+fn check_dependencies(tasks: &crate::models::TaskList) -> Result<()> {
+    tasks.validate_dependencies().map_err(|problems| {
+        RalphError::Task(format!(
+            "Architect planned an impossible task order: {}",
+            problems.join("; ")
+        ))
+    })
+}
+
+/// ID of the task Ralph adds when no planned task creates the shared test helpers.
+const TEST_INFRASTRUCTURE_TASK_ID: &str = "T-TEST-INFRA";
+
+/// Make sure an early task creates the design's shared test helper module.
+///
+/// When the architect planned a helper module but no task creates it, a task
+/// is inserted right after the setup task, depending on it.
+fn ensure_test_infrastructure_task(
+    design: &crate::models::DesignDocument,
+    tasks: &mut crate::models::TaskList,
+) {
+    use crate::models::{Task, TaskComplexity};
+    use crate::scope::normalize_path;
+
+    let Some(infra) = &design.test_infrastructure else {
+        return;
+    };
+    let Some(module) = &infra.helper_module else {
+        return;
+    };
+    let module = normalize_path(module);
+    let planned = tasks.tasks.iter().any(|t| {
+        t.files_created.iter().chain(&t.files_modified).any(|f| normalize_path(f) == module)
+    });
+    if planned || tasks.get_task(TEST_INFRASTRUCTURE_TASK_ID).is_some() {
+        return;
+    }
+
+    let mut description = format!(
+        "Create the shared test helpers in {} for every test file to import.",
+        module
+    );
+    for (title, items) in [("Fixtures", &infra.fixtures), ("Factories", &infra.factories)] {
+        if !items.is_empty() {
+            description.push_str(&format!(" {}: {}.", title, items.join(", ")));
+        }
+    }
+    let mut task = Task::new(TEST_INFRASTRUCTURE_TASK_ID, "Create shared test helpers", description, 1);
+    task.estimated_complexity = TaskComplexity::Low;
+    task.files_created = vec![module];
+
+    let setup = tasks
+        .tasks
+        .iter()
+        .position(|t| t.setup)
+        .or((!tasks.tasks.is_empty()).then_some(0));
+    match setup {
+        Some(index) => {
+            task.dependencies = vec![tasks.tasks[index].id.clone()];
+            tasks.tasks.insert(index + 1, task);
+        }
+        None => tasks.tasks.insert(0, task),
+    }
+}
+
+/// Added to the architect's message when test tasks are split out.
+const SPLIT_TEST_TASKS_NOTE: &str = "\n\n## Test Tasks\n\nTests are tracked as tasks of their own. \
+Implementation tasks must not write tests. After each implementation task `<id>`, add a task \
+with ID `<id>-test` that writes the tests for it, depends on it, and repeats its acceptance \
+criteria.\n";
+
+/// Suffix of the test-writing task that follows an implementation task.
+const TEST_TASK_SUFFIX: &str = "-test";
+
+/// Give every implementation task a `<id>-test` task right after it.
+///
+/// The test task depends on its implementation task and copies its story,
+/// priority and acceptance criteria. Setup, shared-helper and test-only
+/// tasks get none, nor do tasks the architect already paired. The IDs are
+/// derived from the implementation task's, so existing dependencies keep
+/// pointing where they did.
+fn split_test_tasks(tasks: &mut crate::models::TaskList) {
+    use crate::models::{Task, TaskComplexity};
+
+    let ids: std::collections::HashSet<String> = tasks.get_all_tasks().iter().map(|t| t.id.clone()).collect();
+    let mut split = Vec::with_capacity(tasks.tasks.len() * 2);
+    for task in std::mem::take(&mut tasks.tasks) {
+        let test_id = format!("{}{}", task.id, TEST_TASK_SUFFIX);
+        let needs_tests = !task.setup
+            && task.id != TEST_INFRASTRUCTURE_TASK_ID
+            && !task.id.ends_with(TEST_TASK_SUFFIX)
+            && task.conventional_type() != CommitType::Test
+            && !ids.contains(&test_id);
+        let test_task = needs_tests.then(|| {
+            let mut test = Task::new(
+                test_id,
+                format!("Test {}", task.title),
+                format!(
+                    "Write tests for {} ({}) that check each of its acceptance criteria. \
+                     Do not change the implementation unless a test exposes a bug in it.",
+                    task.id, task.title
+                ),
+                task.priority,
+            );
+            test.dependencies = vec![task.id.clone()];
+            test.user_story_id = task.user_story_id.clone();
+            test.acceptance_criteria = task.acceptance_criteria.clone();
+            test.estimated_complexity = match task.estimated_complexity {
+                TaskComplexity::High => TaskComplexity::Medium,
+                _ => TaskComplexity::Low,
+            };
+            test.env = task.env.clone();
+            test.commit_type = Some(CommitType::Test);
+            test
+        });
+        split.push(task);
+        split.extend(test_task);
+    }
+    tasks.tasks = split;
+}
+
+/// `Name (path), ...` for components whose file no task creates.
+fn uncovered_component_list(
+    design: &crate::models::DesignDocument,
+    tasks: &crate::models::TaskList,
+) -> String {
+    design
+        .uncovered_components(tasks)
+        .iter()
+        .map(|c| format!("{} ({})", c.name, c.file_path.as_deref().unwrap_or_default()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Follow-up asking the architect for a flatter file tree.
+fn structure_retry_message(violations: &[String]) -> String {
+    let mut message = String::from(
+        "Your file_structure is deeper and narrower than this project needs:\n",
+    );
+    for violation in violations {
+        message.push_str(&format!("- {}\n", violation));
+    }
+    message.push_str(
+        "\nReturn the complete JSON again with a flatter file_structure. Keep files that \
+         belong together in one directory, and update component files and task \
+         files_to_create/files_to_modify to the new paths.",
+    );
+    message
+}
+
+/// Collapse single-child directory chains and move every path that pointed into them.
+///
+/// Each collapsed chain is recorded in the design changelog.
+fn flatten_design_structure(
+    design: &mut crate::models::DesignDocument,
+    tasks: &mut crate::models::TaskList,
+) {
+    use crate::models::design::rename_path;
+
+    let Some(root) = design.file_structure.as_mut() else {
+        return;
+    };
+    let renames = root.flatten_single_child_chains();
+    if renames.is_empty() {
+        return;
+    }
+
+    for component in &mut design.components {
+        if let Some(path) = component.file_path.as_mut() {
+            *path = rename_path(path, &renames);
+        }
+    }
+    for task in &mut tasks.tasks {
+        for path in task.files_created.iter_mut().chain(task.files_modified.iter_mut()) {
+            *path = rename_path(path, &renames);
+        }
+    }
+    for (from, to) in &renames {
+        design
+            .changelog
+            .push(format!("Flattened single-child directories `{}/` into `{}/`", from, to));
     }
 }
 
 /// Convert JSON to DesignDocument
 fn json_to_design_document(json: &serde_json::Value) -> Result<crate::models::DesignDocument> {
-    use crate::models::{Component, DesignDocument, TechnologyStack};
+    use crate::glossary::{Glossary, GlossaryTerm};
+    use crate::models::{Component, DesignDocument, TechnologyStack, TestInfrastructure};
 
     let project = json["project"]
         .as_str()
@@ -652,21 +2265,24 @@ fn json_to_design_document(json: &serde_json::Value) -> Result<crate::models::De
         .unwrap_or("rust")
         .to_string();
 
+    let stack = &json["technology_stack"];
     let technology_stack = TechnologyStack {
         language: language.clone(),
-        testing_framework: json["technology_stack"]["testing"]
+        testing_framework: stack["testing"]
             .as_str()
             .unwrap_or("cargo test")
             .to_string(),
-        build_tool: json["technology_stack"]["build_tool"]
+        build_tool: stack["build_tool"]
             .as_str()
+            .or_else(|| stack["build"].as_str())
             .unwrap_or("cargo")
             .to_string(),
-        dependencies: json["technology_stack"]["key_dependencies"]
+        dependencies: stack["key_dependencies"]
             .as_array()
+            .or_else(|| stack["dependencies"].as_array())
             .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
             .unwrap_or_default(),
-        additional: std::collections::HashMap::new(),
+        additional: additional_stack_entries(stack),
     };
 
     let component_diagram = json["architecture_diagram"]
@@ -710,16 +2326,55 @@ fn json_to_design_document(json: &serde_json::Value) -> Result<crate::models::De
     // Parse file_structure - handle both new object format and legacy string format
     let file_structure = parse_file_structure(&json["file_structure"], &project);
 
+    let strings = |value: &serde_json::Value| -> Vec<String> {
+        value
+            .as_array()
+            .map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+            .unwrap_or_default()
+    };
+    let test_infrastructure = Some(TestInfrastructure {
+        helper_module: json["test_infrastructure"]["helper_module"]
+            .as_str()
+            .map(str::trim)
+            .filter(|m| !m.is_empty())
+            .map(String::from),
+        fixtures: strings(&json["test_infrastructure"]["fixtures"]),
+        factories: strings(&json["test_infrastructure"]["factories"]),
+    })
+    .filter(|infra| !infra.is_empty());
+
+    let glossary = json["glossary"]
+        .as_array()
+        .map(|terms| {
+            terms
+                .iter()
+                .filter_map(|t| {
+                    Some(GlossaryTerm {
+                        term: t["term"].as_str()?.to_string(),
+                        definition: t["definition"].as_str().unwrap_or("").to_string(),
+                        avoid: strings(&t["avoid"]),
+                    })
+                })
+                .collect()
+        })
+        .map(|terms| Glossary::new(terms).terms)
+        .unwrap_or_default();
+
     Ok(DesignDocument {
+        schema: DESIGN_SCHEMA_ID.to_string(),
+        meta: None,
         project,
         overview,
         component_diagram,
         components,
         file_structure,
         technology_stack: Some(technology_stack),
+        test_infrastructure,
+        glossary,
         design_decisions,
+        changelog: Vec::new(),
         version: "1.0".to_string(),
-        created_at: Some(chrono::Utc::now().to_rfc3339()),
+        created_at: Some(crate::timefmt::now()),
         updated_at: None,
     })
 }
@@ -775,6 +2430,10 @@ fn parse_file_structure(json: &serde_json::Value, project_name: &str) -> Option<
         }
 
         tracing::warn!("Legacy string file_structure format detected - consider updating to structured format");
+        crate::warnings::record(Warning::new(
+            WarningCategory::Legacy,
+            "Design uses the legacy string file_structure format; consider the structured format",
+        ));
 
         // Simple parsing: treat each non-empty line as a file path
         let mut root = FileStructure::directory(project_name, "Project root");
@@ -813,6 +2472,7 @@ fn clean_path(path: &str, project_name: &str) -> String {
             path,
             stripped
         );
+        record_stripped_prefix(path, stripped);
         clean = stripped;
     }
 
@@ -825,12 +2485,24 @@ fn clean_path(path: &str, project_name: &str) -> String {
             path,
             stripped
         );
+        record_stripped_prefix(path, stripped);
         clean = stripped;
     }
 
     clean.to_string()
 }
 
+/// Record a stripped project prefix for the end-of-run warnings section.
+fn record_stripped_prefix(original: &str, stripped: &str) {
+    crate::warnings::record(
+        Warning::new(
+            WarningCategory::FilePath,
+            format!("Stripped redundant project name prefix (now {})", stripped),
+        )
+        .with_file(original.trim()),
+    );
+}
+
 /// Add a path to the FileStructure tree, creating intermediate directories as needed.
 fn add_path_to_structure(root: &mut crate::models::FileStructure, path: &str, is_directory: bool) {
     use crate::models::FileStructure;
@@ -869,6 +2541,39 @@ fn add_path_to_structure(root: &mut crate::models::FileStructure, path: &str, is
     }
 }
 
+/// Task environment variables from the architect, skipping invalid names and non-string values.
+fn parse_task_env(json: &serde_json::Value) -> std::collections::BTreeMap<String, String> {
+    json.as_object()
+        .map(|vars| {
+            vars.iter()
+                .filter(|(name, _)| crate::exec_env::validate_name(name).is_ok())
+                .filter_map(|(name, value)| value.as_str().map(|v| (name.clone(), v.to_string())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Task model override from the architect, skipping ones that fail validation.
+fn parse_task_model(json: &serde_json::Value) -> Option<crate::models::ModelConfig> {
+    if json.is_null() {
+        return None;
+    }
+    let model: crate::models::ModelConfig = match serde_json::from_value(json.clone()) {
+        Ok(model) => model,
+        Err(e) => {
+            tracing::warn!(error = %e, "Ignoring malformed task model override");
+            return None;
+        }
+    };
+    match model.validate() {
+        Ok(()) => Some(model),
+        Err(e) => {
+            tracing::warn!(error = %e, "Ignoring invalid task model override");
+            None
+        }
+    }
+}
+
 /// Convert JSON to TaskList
 fn json_to_task_list(json: &serde_json::Value, project: &str) -> Result<crate::models::TaskList> {
     use crate::models::{Task, TaskComplexity, TaskList, TaskStatus};
@@ -895,6 +2600,11 @@ fn json_to_task_list(json: &serde_json::Value, project: &str) -> Result<crate::m
                             .map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect())
                             .unwrap_or_default(),
                         user_story_id: t["user_story_id"].as_str().map(String::from),
+                        acceptance_criteria: t["acceptance_criteria"]
+                            .as_array()
+                            .map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+                            .unwrap_or_default(),
+                        unmet_criteria: None,
                         estimated_complexity: complexity,
                         files_created: t["files_to_create"]
                             .as_array()
@@ -905,11 +2615,21 @@ fn json_to_task_list(json: &serde_json::Value, project: &str) -> Result<crate::m
                             .map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect())
                             .unwrap_or_default(),
                         commit_hash: None,
+                        diff_stats: None,
                         attempts: 0,
-                        notes: t["acceptance_criteria"]
-                            .as_array()
-                            .map(|a| a.iter().filter_map(|v| v.as_str()).collect::<Vec<_>>().join("\n"))
-                            .unwrap_or_default(),
+                        notes: String::new(),
+                        needs_review: None,
+                        env: parse_task_env(&t["env"]),
+                        model: parse_task_model(&t["model"]),
+                        fingerprint: None,
+                        commit_type: t["commit_type"].as_str().and_then(CommitType::parse),
+                        setup: t["setup"].as_bool().unwrap_or(false),
+                        resources: None,
+                        started_at: None,
+                        completed_at: None,
+                        branch: None,
+                        start_commit: None,
+                        rolled_back: None,
                     }
                 })
                 .collect()
@@ -922,12 +2642,14 @@ fn json_to_task_list(json: &serde_json::Value, project: &str) -> Result<crate::m
         .to_string();
 
     Ok(TaskList {
+        schema: TASKS_SCHEMA_ID.to_string(),
+        meta: None,
         project: project.to_string(),
         language,
         phases: Vec::new(),
         tasks,
         version: "1.0".to_string(),
-        created_at: Some(chrono::Utc::now().to_rfc3339()),
+        created_at: Some(crate::timefmt::now()),
         updated_at: None,
     })
 }
@@ -0,0 +1,495 @@
+//! Judge Agent for reviewing a task's acceptance criteria.
+//!
+//! Tests only catch what they assert. The Judge Agent reads a task's
+//! `acceptance_criteria` together with the files the task produced and decides,
+//! criterion by criterion, whether the code satisfies it — covering semantic
+//! requirements such as "error messages must be user-friendly".
+//!
+//! The judge is opt-in (`RALPH_JUDGE_ENABLED`) because it costs one model call
+//! per completed task. When enabled, the `tasks` tool refuses to complete a task
+//! until every criterion is met.
+//!
+//! This agent uses LlmAgent with:
+//! - `output_schema` to force a structured verdict per criterion
+
+use crate::agents::prompt::{judge_user_message, AssembledPrompt};
+use crate::model_timeout::TimeoutModel;
+use crate::models::{create_model, ModelConfig, Task};
+use crate::textfmt::truncate_display;
+use crate::{RalphError, Result};
+use adk_rust::agent::LlmAgentBuilder;
+use adk_rust::{Agent, Llm};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Instruction prompt for the Judge Agent.
+const JUDGE_INSTRUCTION: &str = r#"You are a strict but fair code reviewer. You decide whether an implementation satisfies each acceptance criterion of a task.
+
+## How to Judge
+
+- Judge every criterion you are given, in the order given, and nothing else
+- Base your verdict only on the code shown. If the code needed to satisfy a criterion is not shown, the criterion is NOT met
+- A criterion is met only if the code clearly implements it. Plausible intent is not enough
+- Do not fail a criterion for style preferences or for issues unrelated to the criterion
+- Keep each reason to one or two sentences and point at the relevant function or file
+
+## Output Format
+
+Return JSON with one entry per criterion:
+
+```json
+{
+  "criteria": [
+    {
+      "criterion": "the criterion text, copied exactly",
+      "met": true,
+      "reason": "why it is or is not met"
+    }
+  ]
+}
+```
+"#;
+
+/// Maximum characters of a single file included in the judge's prompt.
+pub const MAX_JUDGED_FILE_CHARS: usize = 20_000;
+
+/// Verdict for a single acceptance criterion.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CriterionVerdict {
+    /// The acceptance criterion as written on the task
+    pub criterion: String,
+    /// Whether the implementation satisfies it
+    pub met: bool,
+    /// The judge's reasoning
+    pub reason: String,
+}
+
+/// Verdicts for all acceptance criteria of a task.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JudgeVerdict {
+    /// Task that was judged
+    pub task_id: String,
+    /// One verdict per acceptance criterion, in task order
+    pub criteria: Vec<CriterionVerdict>,
+}
+
+impl JudgeVerdict {
+    /// Check if every criterion is met.
+    pub fn all_met(&self) -> bool {
+        self.criteria.iter().all(|c| c.met)
+    }
+
+    /// Criteria that are not met.
+    pub fn unmet(&self) -> Vec<&CriterionVerdict> {
+        self.criteria.iter().filter(|c| !c.met).collect()
+    }
+
+    /// One line per unmet criterion, for notes and tool responses.
+    pub fn summary(&self) -> String {
+        let unmet = self.unmet();
+        if unmet.is_empty() {
+            return format!("All {} acceptance criteria met", self.criteria.len());
+        }
+        let mut summary = format!(
+            "{} of {} acceptance criteria not met:",
+            unmet.len(),
+            self.criteria.len()
+        );
+        for verdict in unmet {
+            summary.push_str(&format!("\n- {} — {}", verdict.criterion, verdict.reason));
+        }
+        summary
+    }
+}
+
+/// Judge Agent that checks a task's acceptance criteria using LlmAgent.
+pub struct JudgeAgent {
+    agent: Arc<dyn Agent + Send + Sync>,
+    project_path: PathBuf,
+}
+
+impl std::fmt::Debug for JudgeAgent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JudgeAgent")
+            .field("name", &self.agent.name())
+            .field("project_path", &self.project_path)
+            .finish()
+    }
+}
+
+impl JudgeAgent {
+    /// Create a new builder for JudgeAgent.
+    pub fn builder() -> JudgeAgentBuilder {
+        JudgeAgentBuilder::default()
+    }
+
+    /// Get the instruction prompt.
+    pub fn instruction() -> &'static str {
+        JUDGE_INSTRUCTION
+    }
+
+    /// Get the project path.
+    pub fn project_path(&self) -> &PathBuf {
+        &self.project_path
+    }
+
+    /// Judge a task against the files it created and modified.
+    pub async fn judge_task(&self, task: &Task) -> Result<JudgeVerdict> {
+        self.judge(task, &task_files(task)).await
+    }
+
+    /// Judge a task's acceptance criteria against the given files.
+    ///
+    /// Paths are relative to the project root; files that cannot be read are
+    /// skipped. A task without acceptance criteria passes without a model call.
+    pub async fn judge(&self, task: &Task, files: &[String]) -> Result<JudgeVerdict> {
+        use adk_rust::{Content, Part};
+        use adk_rust::runner::{Runner, RunnerConfig};
+        use adk_rust::session::{CreateRequest, InMemorySessionService, SessionService};
+        use futures::StreamExt;
+
+        if task.acceptance_criteria.is_empty() {
+            return Ok(JudgeVerdict {
+                task_id: task.id.clone(),
+                criteria: Vec::new(),
+            });
+        }
+
+        let contents = read_task_files(&self.project_path, files);
+        let prompt = AssembledPrompt::judge(task, &contents);
+        if let Err(e) = prompt.record(&self.project_path) {
+            tracing::debug!(error = %e, "Could not record judge prompt");
+        }
+
+        let session_service: Arc<dyn SessionService> = Arc::new(InMemorySessionService::new());
+
+        let session_id = format!("judge-{}", uuid::Uuid::new_v4());
+        session_service
+            .create(CreateRequest {
+                app_name: "ralph-judge".to_string(),
+                user_id: "user".to_string(),
+                session_id: Some(session_id.clone()),
+                state: std::collections::HashMap::new(),
+            })
+            .await
+            .map_err(|e| RalphError::Agent {
+                agent: "judge".to_string(),
+                message: format!("Failed to create session: {}", e),
+            })?;
+
+        let runner = Runner::new(RunnerConfig {
+            app_name: "ralph-judge".to_string(),
+            agent: self.agent.clone(),
+            session_service,
+            artifact_service: None,
+            memory_service: None,
+            plugin_manager: None,
+            compaction_config: None,
+            run_config: None,
+        }).map_err(|e| RalphError::Agent {
+            agent: "judge".to_string(),
+            message: e.to_string(),
+        })?;
+
+        let user_content = Content {
+            role: "user".to_string(),
+            parts: vec![Part::Text {
+                text: judge_user_message(task, &contents),
+            }],
+        };
+
+        let mut stream = runner
+            .run("user".to_string(), session_id, user_content)
+            .await
+            .map_err(|e| RalphError::Agent {
+                agent: "judge".to_string(),
+                message: e.to_string(),
+            })?;
+
+        let mut response_text = String::new();
+        while let Some(result) = stream.next().await {
+            match result {
+                Ok(event) => {
+                    if let Some(content) = &event.llm_response.content {
+                        for part in &content.parts {
+                            if let Part::Text { text } = part {
+                                response_text.push_str(text);
+                            }
+                        }
+                    }
+                }
+                Err(e) => return Err(RalphError::agent_stream("judge", e.to_string())),
+            }
+        }
+
+        let judge_json: serde_json::Value = serde_json::from_str(&response_text).map_err(|e| {
+            RalphError::Agent {
+                agent: "judge".to_string(),
+                message: format!(
+                    "Failed to parse judge JSON: {} - Response: {}",
+                    e,
+                    truncate_display(&response_text, 500)
+                ),
+            }
+        })?;
+
+        Ok(parse_verdict(task, &judge_json))
+    }
+}
+
+/// Builder for creating a JudgeAgent with fluent API.
+pub struct JudgeAgentBuilder {
+    model: Option<Arc<dyn Llm>>,
+    model_config: ModelConfig,
+    project_path: PathBuf,
+}
+
+impl std::fmt::Debug for JudgeAgentBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JudgeAgentBuilder")
+            .field("model", &self.model.as_ref().map(|m| m.name()))
+            .field("model_config", &self.model_config)
+            .field("project_path", &self.project_path)
+            .finish()
+    }
+}
+
+impl Default for JudgeAgentBuilder {
+    fn default() -> Self {
+        Self {
+            model: None,
+            model_config: ModelConfig::new("gemini", "gemini-2.5-flash"),
+            project_path: PathBuf::from("."),
+        }
+    }
+}
+
+impl JudgeAgentBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn model(mut self, model: Arc<dyn Llm>) -> Self {
+        self.model = Some(model);
+        self
+    }
+
+    pub fn model_config(mut self, config: ModelConfig) -> Self {
+        self.model_config = config;
+        self
+    }
+
+    pub fn project_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.project_path = path.into();
+        self
+    }
+
+    pub async fn build(self) -> Result<JudgeAgent> {
+        let model = match self.model {
+            Some(m) => m,
+            None => create_model(&self.model_config).await?,
+        };
+        let model = TimeoutModel::wrap(model, self.model_config.model_call_timeout());
+
+        let judge_schema = json!({
+            "type": "object",
+            "properties": {
+                "criteria": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "criterion": {
+                                "type": "string",
+                                "description": "The acceptance criterion, copied exactly"
+                            },
+                            "met": {
+                                "type": "boolean",
+                                "description": "Whether the code satisfies the criterion"
+                            },
+                            "reason": {
+                                "type": "string",
+                                "description": "Short justification"
+                            }
+                        },
+                        "required": ["criterion", "met", "reason"]
+                    }
+                }
+            },
+            "required": ["criteria"]
+        });
+
+        let agent = LlmAgentBuilder::new("judge-agent")
+            .description("Checks a task's acceptance criteria against the produced code")
+            .model(model)
+            .instruction(JUDGE_INSTRUCTION)
+            .output_schema(judge_schema)
+            .output_key("judge_output")
+            .build()
+            .map_err(|e| RalphError::Agent {
+                agent: "judge".to_string(),
+                message: e.to_string(),
+            })?;
+
+        Ok(JudgeAgent {
+            agent: Arc::new(agent),
+            project_path: self.project_path,
+        })
+    }
+}
+
+/// Files a task created or modified, without duplicates.
+pub fn task_files(task: &Task) -> Vec<String> {
+    let mut files: Vec<String> = Vec::new();
+    for file in task.files_created.iter().chain(&task.files_modified) {
+        if !files.contains(file) {
+            files.push(file.clone());
+        }
+    }
+    files
+}
+
+/// Read files relative to the project root, truncating large ones.
+///
+/// Unreadable files are skipped; the judge treats missing code as unmet.
+pub fn read_task_files(project_path: &Path, files: &[String]) -> Vec<(String, String)> {
+    files
+        .iter()
+        .filter_map(|file| {
+            let content = std::fs::read_to_string(project_path.join(file)).ok()?;
+            let content = if content.chars().count() > MAX_JUDGED_FILE_CHARS {
+                let truncated: String = content.chars().take(MAX_JUDGED_FILE_CHARS).collect();
+                format!("{}\n... (truncated)", truncated)
+            } else {
+                content
+            };
+            Some((file.clone(), content))
+        })
+        .collect()
+}
+
+/// Convert the judge's JSON into a verdict aligned with the task's criteria.
+///
+/// Criteria the judge skipped are reported as not met, so a partial answer can
+/// never let a task through.
+pub fn parse_verdict(task: &Task, json: &serde_json::Value) -> JudgeVerdict {
+    let returned: Vec<CriterionVerdict> = json["criteria"]
+        .as_array()
+        .map(|arr| {
+            arr.iter()
+                .map(|c| CriterionVerdict {
+                    criterion: c["criterion"].as_str().unwrap_or("").trim().to_string(),
+                    met: c["met"].as_bool().unwrap_or(false),
+                    reason: c["reason"].as_str().unwrap_or("").to_string(),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let positional = returned.len() == task.acceptance_criteria.len();
+    let criteria = task
+        .acceptance_criteria
+        .iter()
+        .enumerate()
+        .map(|(i, criterion)| {
+            // Match by text first; fall back to position when the judge paraphrased
+            returned
+                .iter()
+                .find(|v| v.criterion == criterion.trim())
+                .or_else(|| returned.get(i).filter(|_| positional))
+                .map(|v| CriterionVerdict {
+                    criterion: criterion.clone(),
+                    met: v.met,
+                    reason: v.reason.clone(),
+                })
+                .unwrap_or_else(|| CriterionVerdict {
+                    criterion: criterion.clone(),
+                    met: false,
+                    reason: "Judge did not evaluate this criterion".to_string(),
+                })
+        })
+        .collect();
+
+    JudgeVerdict {
+        task_id: task.id.clone(),
+        criteria,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task_with_criteria() -> Task {
+        Task::new("TASK-003", "Input errors", "Report invalid input", 2)
+            .with_acceptance_criterion("WHEN input is empty, THE cli SHALL print a usage hint")
+            .with_acceptance_criterion("Error messages SHALL be user-friendly")
+    }
+
+    #[test]
+    fn test_parse_verdict_matches_criteria() {
+        let task = task_with_criteria();
+        let json = json!({
+            "criteria": [
+                {"criterion": "Error messages SHALL be user-friendly", "met": false, "reason": "Prints a raw ParseIntError"},
+                {"criterion": "WHEN input is empty, THE cli SHALL print a usage hint", "met": true, "reason": "main() checks args.is_empty()"}
+            ]
+        });
+
+        let verdict = parse_verdict(&task, &json);
+        assert_eq!(verdict.task_id, "TASK-003");
+        assert_eq!(verdict.criteria.len(), 2);
+        assert!(verdict.criteria[0].met);
+        assert!(!verdict.criteria[1].met);
+        assert!(!verdict.all_met());
+        assert_eq!(verdict.unmet().len(), 1);
+        assert!(verdict.summary().contains("ParseIntError"));
+    }
+
+    #[test]
+    fn test_parse_verdict_missing_criterion_is_unmet() {
+        let task = task_with_criteria();
+        let json = json!({
+            "criteria": [
+                {"criterion": "WHEN input is empty, THE cli SHALL print a usage hint", "met": true, "reason": "ok"}
+            ]
+        });
+
+        let verdict = parse_verdict(&task, &json);
+        assert!(verdict.criteria[0].met);
+        assert!(!verdict.criteria[1].met);
+        assert_eq!(verdict.criteria[1].reason, "Judge did not evaluate this criterion");
+
+        let garbage = parse_verdict(&task, &json!({"unexpected": true}));
+        assert!(garbage.unmet().len() == 2);
+    }
+
+    #[test]
+    fn test_task_files_and_reading() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/main.rs"), "fn main() {}").unwrap();
+
+        let mut task = task_with_criteria();
+        task.add_file_created("src/main.rs");
+        task.add_file_modified("src/main.rs");
+        task.add_file_modified("src/missing.rs");
+
+        let files = task_files(&task);
+        assert_eq!(files, vec!["src/main.rs", "src/missing.rs"]);
+
+        let contents = read_task_files(dir.path(), &files);
+        assert_eq!(contents, vec![("src/main.rs".to_string(), "fn main() {}".to_string())]);
+    }
+
+    #[test]
+    fn test_judge_agent_builder_defaults() {
+        let builder = JudgeAgentBuilder::default();
+        assert!(builder.model.is_none());
+        assert_eq!(builder.model_config.provider, "gemini");
+        assert_eq!(builder.project_path, PathBuf::from("."));
+        assert!(JudgeAgent::instruction().contains("criterion"));
+    }
+}
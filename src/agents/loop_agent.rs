@@ -27,15 +27,44 @@
 //! - 5.1: THE Ralph_Loop_Agent SHALL work on ONLY ONE task per iteration
 //! - 7.4: WHEN starting each iteration, THE Ralph_Loop_Agent SHALL read `progress.json`
 
-use crate::models::{DesignDocument, ModelConfig, RalphConfig};
+use crate::abort::{AbortGuardTool, AbortOutcome, AbortSignal, TaskAborter, WriteBackup};
+use crate::agents::judge_agent::JudgeAgent;
+use crate::agents::prompt::{AssembledPrompt, LOOP_KICKOFF_MESSAGE};
+use crate::cleanup::{record_cleanup_task, CleanupReport};
+use crate::context_trim::ContextTrimModel;
+use crate::dataset::{self, DatasetRecorder, DatasetTarget, Redactor};
+use crate::dependencies::{record_upgrade_tasks, DependencyAdvisor, Ecosystem, HttpRegistry};
+use crate::exec_env::ExecEnv;
+use crate::gates::CompletionGates;
+use crate::glossary::GlossaryCheck;
+use crate::incremental::Fingerprinter;
+use crate::last_run::RunSnapshot;
+use crate::model_timeout::TimeoutModel;
+use crate::models::{create_model, DesignDocument, ModelConfig, RalphConfig, TaskList, TaskStatus};
 use crate::output::{process_event_part, RalphOutput};
-use crate::tools::{FileTool, GitTool, ProgressTool, TaskTool, TestTool};
+use crate::pause::{LoopState, PauseControl};
+use crate::persist::WriteBehind;
+use crate::provenance::{AgentProvenance, Provenance};
+use crate::provider_error::ProviderError;
+use crate::retry::RetryPolicy;
+use crate::run_state::{RunState, TaskProgress};
+use crate::scaffold::ScaffoldCheck;
+use crate::scope::FileScope;
+use crate::smoke::SmokeGate;
+use crate::tool_log::ToolLog;
+use crate::tools::{FileTool, FlakyRetryPolicy, GitTool, ProgressTool, TaskBranches, TaskRollback, TaskTool, TestTool};
+use crate::verification::ContinuousVerifier;
+use crate::warnings::{Warning, WarningCategory};
 use crate::{RalphError, Result};
 use adk_rust::agent::{LlmAgentBuilder, LoopAgent};
-use adk_rust::{Agent, Llm, Tool};
 use adk_rust::tool::ExitLoopTool;
+use adk_rust::{Agent, Llm, LlmRequest, LlmResponseStream, Part, Result as AdkResult, Tool};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
 /// Instruction prompt for the Ralph Loop Agent.
 ///
@@ -53,7 +82,7 @@ const RALPH_LOOP_INSTRUCTION: &str = r#"You are Ralph, an autonomous development
 - `tasks`: Manage task list (operations: list, get_next, update_status, complete)
 - `test`: Run tests (operations: run, detect, check)
 - `file`: File operations (operations: read, write, list, delete)
-- `git`: Git operations (operations: status, add, commit, diff)
+- `git`: Git operations (operations: status, add, commit, diff, diff_stat)
 - `exit_loop`: Signal completion or end of iteration
 
 ## Workflow for Each Iteration
@@ -65,7 +94,7 @@ const RALPH_LOOP_INSTRUCTION: &str = r#"You are Ralph, an autonomous development
 ### 2. Implement the Task
 - Read relevant files using `file` with operation "read"
 - Write implementation code using `file` with operation "write"
-- Create tests for the implementation
+- Create tests for the implementation; when the Project Context lists shared test helpers, import them instead of redefining fixtures or factories in each test file
 
 ### 3. Verify Implementation
 - Call `test` with operation "run" to run the test suite
@@ -73,7 +102,7 @@ const RALPH_LOOP_INSTRUCTION: &str = r#"You are Ralph, an autonomous development
 - If tests still fail after 3 attempts, mark task as blocked
 
 ### 4. Commit and Record
-- If tests pass, call `git` with operation "add" then "commit"
+- If tests pass, call `git` with operation "add" then "commit" (pass the task's `task_id` so the message gets its conventional-commit type, e.g. `feat:`, and its diff stats are recorded)
 - Call `progress` with operation "append" to record:
   - approach: How you implemented it
   - learnings: What you learned
@@ -81,6 +110,7 @@ const RALPH_LOOP_INSTRUCTION: &str = r#"You are Ralph, an autonomous development
   - files_created/files_modified: What changed
   - test_results: Pass/fail counts
 - Call `tasks` with operation "complete" to mark the task done
+  - `complete` is refused until the full test suite has passed since the task started; if it is refused, the task is still in progress, so follow the message and call it again
 
 ### 5. Check Completion
 - Call `tasks` with operation "list" to check overall status
@@ -135,6 +165,24 @@ pub struct RalphLoopAgent {
     config: RalphConfig,
     /// Project base directory
     project_path: PathBuf,
+    /// Raised when the user aborts the task in progress
+    abort_signal: Arc<AbortSignal>,
+    /// Files written by the task in progress, for rollback on abort
+    write_backup: Arc<WriteBackup>,
+    /// Buffered progress.json writes, flushed at task boundaries
+    write_behind: Arc<WriteBehind>,
+    /// Variables for the project's test commands, including the current task's
+    exec_env: Arc<ExecEnv>,
+    /// Quick syntax check after each file write (counts its catches)
+    smoke_gate: Arc<SmokeGate>,
+    /// Model details recorded in tasks.json
+    provenance: AgentProvenance,
+    /// Resets the project when a task is blocked (opt-in)
+    rollback: Option<Arc<TaskRollback>>,
+    /// Holds the loop before its next task while paused
+    pause_control: PauseControl,
+    /// How often the loop resumes after a transient model failure
+    retry_policy: RetryPolicy,
 }
 
 impl std::fmt::Debug for RalphLoopAgent {
@@ -190,6 +238,26 @@ impl RalphLoopAgent {
         DesignDocument::load_markdown(&design_path).map_err(RalphError::Design)
     }
 
+    /// Get the handle that pauses and resumes this loop.
+    pub fn pause_control(&self) -> &PauseControl {
+        &self.pause_control
+    }
+
+    /// Stop before the next task; the task in progress finishes first.
+    pub fn pause(&self) -> bool {
+        self.pause_control.pause()
+    }
+
+    /// Continue a paused loop.
+    pub fn resume(&self) -> bool {
+        self.pause_control.resume()
+    }
+
+    /// Whether the loop is running, paused or finished.
+    pub fn state(&self) -> LoopState {
+        self.pause_control.state()
+    }
+
     /// Convert to Arc<dyn Agent> for use with ADK runner.
     pub fn into_agent(self) -> Arc<dyn Agent> {
         self.agent
@@ -204,6 +272,9 @@ pub struct RalphLoopAgentBuilder {
     project_path: PathBuf,
     additional_tools: Vec<Arc<dyn Tool>>,
     custom_instruction: Option<String>,
+    judge: Option<Arc<JudgeAgent>>,
+    pause_control: PauseControl,
+    retry_policy: RetryPolicy,
 }
 
 impl std::fmt::Debug for RalphLoopAgentBuilder {
@@ -213,6 +284,7 @@ impl std::fmt::Debug for RalphLoopAgentBuilder {
             .field("model_config", &self.model_config)
             .field("project_path", &self.project_path)
             .field("additional_tools_count", &self.additional_tools.len())
+            .field("judge", &self.judge.is_some())
             .finish()
     }
 }
@@ -227,6 +299,9 @@ impl Default for RalphLoopAgentBuilder {
             project_path: PathBuf::from("."),
             additional_tools: Vec::new(),
             custom_instruction: None,
+            judge: None,
+            pause_control: PauseControl::new(),
+            retry_policy: RetryPolicy::default(),
         }
     }
 }
@@ -282,15 +357,44 @@ impl RalphLoopAgentBuilder {
         self
     }
 
+    /// Set the judge that must approve acceptance criteria before a task completes.
+    pub fn judge(mut self, judge: Arc<JudgeAgent>) -> Self {
+        self.judge = Some(judge);
+        self
+    }
+
+    /// Share a pause control with the caller (a new one by default).
+    pub fn pause_control(mut self, control: PauseControl) -> Self {
+        self.pause_control = control;
+        self
+    }
+
+    /// Set how the loop retries after a transient model failure.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
     /// Build the RalphLoopAgent.
     ///
     /// If no model is provided, this will create one based on the model_config.
+    /// When `judge_enabled` is set and no judge was given, one is built from
+    /// `agents.judge_model`.
     pub async fn build(mut self) -> Result<RalphLoopAgent> {
         let model = match self.model.take() {
             Some(m) => m,
-            None => create_model_from_config(&self.model_config).await?,
+            None => create_model(&self.model_config).await?,
         };
 
+        if self.config.judge_enabled && self.judge.is_none() {
+            let judge = JudgeAgent::builder()
+                .model_config(self.config.agents.judge_model.clone())
+                .project_path(&self.project_path)
+                .build()
+                .await?;
+            self.judge = Some(Arc::new(judge));
+        }
+
         self.build_with_model(model)
     }
 
@@ -300,55 +404,133 @@ impl RalphLoopAgentBuilder {
         let progress_path = self.project_path.join(&self.config.progress_path);
         let tasks_path = self.project_path.join(&self.config.tasks_path);
 
-        let progress_tool = Arc::new(ProgressTool::new(progress_path, &self.config.prd_path));
-        let task_tool = Arc::new(TaskTool::new(tasks_path));
-        let test_tool = Arc::new(TestTool::new(&self.project_path));
-        let file_tool = Arc::new(FileTool::new(&self.project_path));
-        let git_tool = Arc::new(GitTool::new(&self.project_path));
+        let write_behind = Arc::new(WriteBehind::default());
+        let progress_tool = Arc::new(
+            ProgressTool::new(&progress_path, &self.config.prd_path)
+                .with_write_behind(write_behind.clone()),
+        );
+        let file_scope = Arc::new(FileScope::new(self.config.max_files_per_task));
+        let exec_env = Arc::new(
+            ExecEnv::new(self.config.execution_env.clone())
+                .with_limits(self.config.resource_limits())
+                .with_isolation(self.config.artifact_isolation()),
+        );
+        let gates = Arc::new(CompletionGates::new(self.config.verify_completion));
+        let commit_author = self.config.commit_author().map_err(RalphError::config)?;
+        let mut task_tool = TaskTool::new(&tasks_path)
+            .with_scheduling(self.config.scheduling)
+            .with_scope(file_scope.clone())
+            .with_exec_env(exec_env.clone())
+            .with_gates(gates.clone())
+            .with_fingerprints(Fingerprinter::new(
+                &self.project_path,
+                self.project_path.join(&self.config.design_path),
+            ));
+        if self.config.task_branches {
+            let git = GitTool::new(&self.project_path)
+                .with_tasks_path(&tasks_path)
+                .with_author(commit_author.clone());
+            let branches = TaskBranches::new(git, self.config.task_merge)
+                .carrying([tasks_path.clone(), progress_path.clone()]);
+            task_tool = task_tool.with_branches(Arc::new(branches));
+        }
+        let rollback = match (self.config.rollback_failed, self.config.task_branches) {
+            (true, true) => {
+                tracing::warn!("Rollback of blocked tasks is off: with task branches their work already stays on its branch");
+                None
+            }
+            (true, false) => {
+                let git = GitTool::new(&self.project_path).with_author(commit_author.clone());
+                let rollback = TaskRollback::new(git).carrying([tasks_path.clone(), progress_path.clone()]);
+                Some(Arc::new(rollback))
+            }
+            (false, _) => None,
+        };
+        if let Some(rollback) = &rollback {
+            task_tool = task_tool.with_rollback(rollback.clone());
+        }
+        let task_tool = match self.judge {
+            Some(judge) => Arc::new(task_tool.with_judge(judge)),
+            None => Arc::new(task_tool),
+        };
+        let test_tool = Arc::new(
+            TestTool::new(&self.project_path)
+                .with_flaky_policy(FlakyRetryPolicy::new(
+                    self.config.flaky_test_retries,
+                    self.config.flaky_confirm_runs,
+                ))
+                .with_exec_env(exec_env.clone())
+                .with_jobs(self.config.tool_jobs())
+                .with_gates(gates)
+                .with_scope(file_scope.clone(), self.config.extension_map()),
+        );
+        let abort_signal = Arc::new(AbortSignal::new());
+        let write_backup = Arc::new(WriteBackup::new());
+        let guard = |tool: Arc<dyn Tool>| -> Arc<dyn Tool> {
+            Arc::new(AbortGuardTool::new(tool, abort_signal.clone(), write_backup.clone()))
+        };
+
+        let smoke_gate = Arc::new(
+            SmokeGate::new(&self.project_path, self.config.smoke_gate.clone())
+                .with_exec_env(exec_env.clone()),
+        );
+        let mut file_tool = FileTool::new(&self.project_path)
+            .with_backup(write_backup.clone())
+            .with_scope(file_scope);
+        if smoke_gate.is_enabled() {
+            file_tool = file_tool.with_smoke_gate(smoke_gate.clone());
+        }
+        let edit_guard = self.config.edit_guard();
+        if edit_guard.is_enabled() {
+            file_tool = file_tool.with_edit_guard(edit_guard);
+        }
+        let file_tool = Arc::new(file_tool);
+        let git_tool = Arc::new(
+            GitTool::new(&self.project_path)
+                .with_tasks_path(&tasks_path)
+                .with_author(commit_author),
+        );
         let exit_loop_tool = Arc::new(ExitLoopTool::new());
 
         // Build instruction with design context if available
         let instruction = self.custom_instruction.unwrap_or_else(|| {
-            let mut inst = RALPH_LOOP_INSTRUCTION.to_string();
-            
-            // Try to add design context
             let design_path = self.project_path.join(&self.config.design_path);
-            if let Ok(design) = DesignDocument::load_markdown(&design_path) {
-                inst.push_str("\n\n## Project Context\n\n");
-                inst.push_str(&format!("Project: {}\n", design.project));
-                inst.push_str(&format!("Project root: {} (all file paths are relative to this directory)\n", self.project_path.display()));
-                if let Some(ref tech) = design.technology_stack {
-                    inst.push_str(&format!("Language: {}\n", tech.language));
-                }
-                if !design.overview.is_empty() {
-                    inst.push_str(&format!("\nOverview: {}\n", design.overview));
-                }
+            let design = DesignDocument::load_markdown(&design_path).ok();
+            let prompt = AssembledPrompt::developer(
+                design.as_ref(),
+                &self.project_path,
+                &self.config.completion_promise,
+            );
+            if let Err(e) = prompt.record(&self.project_path) {
+                tracing::debug!(error = %e, "Could not record developer prompt");
             }
-            
-            // Add completion promise
-            inst.push_str(&format!(
-                "\n\n## Completion Promise\n\nWhen all tasks are done, output: \"{}\"\n",
-                self.config.completion_promise
-            ));
-            
-            inst
+            prompt.instruction_text()
         });
 
+        let provenance = AgentProvenance::from_config(&self.model_config, &instruction);
+
         // Build the inner LlmAgent with all tools
         let mut llm_builder = LlmAgentBuilder::new("ralph-worker")
             .description("Implements tasks autonomously using available tools")
             .instruction(instruction)
-            .model(model)
+            .model(ContextTrimModel::wrap(
+                TimeoutModel::wrap(
+                    Arc::new(TaskModelRouter::new(model, &tasks_path)),
+                    self.model_config.model_call_timeout(),
+                ),
+                self.config.trim_on_overflow,
+                Some(RalphOutput::for_config(&self.config)),
+            ))
             .tool(progress_tool)
-            .tool(task_tool)
-            .tool(test_tool)
-            .tool(file_tool)
-            .tool(git_tool)
+            .tool(guard(task_tool))
+            .tool(guard(test_tool))
+            .tool(guard(file_tool))
+            .tool(guard(git_tool))
             .tool(exit_loop_tool);
 
         // Add any additional tools
         for tool in self.additional_tools {
-            llm_builder = llm_builder.tool(tool);
+            llm_builder = llm_builder.tool(guard(tool));
         }
 
         let llm_agent = llm_builder.build().map_err(|e| RalphError::Agent {
@@ -366,6 +548,15 @@ impl RalphLoopAgentBuilder {
             model_config: self.model_config,
             config: self.config,
             project_path: self.project_path,
+            abort_signal,
+            write_backup,
+            write_behind,
+            exec_env,
+            smoke_gate,
+            provenance,
+            rollback,
+            pause_control: self.pause_control,
+            retry_policy: self.retry_policy,
         })
     }
 }
@@ -453,15 +644,21 @@ impl RalphLoopAgent {
     /// 2. Runs until completion or max iterations
     /// 3. Returns a CompletionStatus based on task state
     ///
-    /// Output verbosity is controlled by the `debug_level` config setting.
+    /// Each event part goes to [`Self::on_part`]; output verbosity is
+    /// controlled by the `debug_level` config setting.
     pub async fn run(&self) -> Result<CompletionStatus> {
-        use adk_rust::{Content, Part};
+        use adk_rust::Content;
         use adk_rust::runner::{Runner, RunnerConfig};
         use adk_rust::session::{CreateRequest, InMemorySessionService, SessionService};
         use futures::StreamExt;
 
         // Create output handler based on debug level
-        let output = RalphOutput::new(self.config.debug_level);
+        let output =
+            RalphOutput::for_config(&self.config).with_multi_progress(self.config.multi_progress);
+        let _flush = output.flush_on_drop();
+        let started = std::time::Instant::now();
+        self.pause_control.reopen();
+        let _finished = FinishOnDrop(&self.pause_control);
 
         // Show startup info based on debug level
         if output.level().is_normal() {
@@ -509,7 +706,7 @@ impl RalphLoopAgent {
         let initial_content = Content {
             role: "user".to_string(),
             parts: vec![Part::Text {
-                text: "Start implementing tasks. Read progress first, then get the next task and implement it.".to_string(),
+                text: LOOP_KICKOFF_MESSAGE.to_string(),
             }],
         };
 
@@ -517,230 +714,996 @@ impl RalphLoopAgent {
 
         // Run the agent
         let mut event_stream = runner
-            .run("ralph-agent".to_string(), session_id, initial_content)
+            .run("ralph-agent".to_string(), session_id.clone(), initial_content.clone())
             .await
             .map_err(|e| RalphError::Agent {
                 agent: "ralph-loop".to_string(),
                 message: format!("Runner failed: {}", e),
             })?;
 
-        // Track iterations and tool calls
-        let mut iteration_count = 0u32;
-        let mut tool_call_count = 0u32;
-        let mut _current_task: Option<String> = None;
-        
-        // Load task stats for progress bar
         let tasks_path = self.project_path.join(&self.config.tasks_path);
-        let initial_tasks = crate::models::TaskList::load(&tasks_path).ok();
-        let total_tasks = initial_tasks.as_ref().map(|t| t.get_stats().total).unwrap_or(0);
-        let mut completed_tasks = initial_tasks.as_ref().map(|t| t.get_stats().completed).unwrap_or(0);
-        
+        self.record_provenance(&tasks_path);
+        let mut run = self.start_run(tasks_path);
+        let _flush_timer = self.write_behind.spawn_timer();
+
         // Show initial progress bar
-        if total_tasks > 0 {
-            output.progress_bar(completed_tasks, total_tasks);
-        }
+        let progress = run.state.progress();
+        output.progress_bar(progress.completed, progress.total);
 
-        // Process events with level-appropriate output
         while let Some(event_result) = event_stream.next().await {
-            match event_result {
-                Ok(event) => {
-                    // Process content parts
-                    if let Some(ref content) = event.llm_response.content {
-                        for part in &content.parts {
-                            // Track tool calls and detect task changes
-                            if let Part::FunctionCall { name, args, .. } = part {
-                                tool_call_count += 1;
-                                
-                                // Detect task operations to show progress
-                                if name == "tasks" {
-                                    if let Some(op) = args.get("operation").and_then(|v| v.as_str()) {
-                                        match op {
-                                            "get_next" => {
-                                                output.status("Getting next task...");
-                                            }
-                                            "update_status" => {
-                                                if let Some(task_id) = args.get("task_id").and_then(|v| v.as_str()) {
-                                                    if let Some(status) = args.get("status").and_then(|v| v.as_str()) {
-                                                        if status == "in_progress" {
-                                                            _current_task = Some(task_id.to_string());
-                                                            output.clear_line();
-                                                            output.task_start(task_id, "Starting implementation");
-                                                            output.progress_bar_with_task(completed_tasks, total_tasks, task_id);
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                            "complete" => {
-                                                if let Some(task_id) = args.get("task_id").and_then(|v| v.as_str()) {
-                                                    completed_tasks += 1;
-                                                    output.clear_line();
-                                                    output.task_complete(task_id, true);
-                                                    output.progress_bar(completed_tasks, total_tasks);
-                                                    _current_task = None;
-                                                }
-                                            }
-                                            _ => {}
-                                        }
-                                    }
-                                }
-                                
-                                // Show file operations at normal level
-                                if name == "file" {
-                                    if let Some(op) = args.get("operation").and_then(|v| v.as_str()) {
-                                        if let Some(path) = args.get("path").and_then(|v| v.as_str()) {
-                                            match op {
-                                                "write" => output.status(&format!("Writing {}", path)),
-                                                "read" => output.status(&format!("Reading {}", path)),
-                                                _ => {}
-                                            }
-                                        }
-                                    }
-                                }
-                                
-                                // Show test runs
-                                if name == "test" {
-                                    if let Some(op) = args.get("operation").and_then(|v| v.as_str()) {
-                                        if op == "run" {
-                                            output.status("Running tests...");
-                                        }
-                                    }
-                                }
-                                
-                                // Show git operations
-                                if name == "git" {
-                                    if let Some(op) = args.get("operation").and_then(|v| v.as_str()) {
-                                        if op == "commit" {
-                                            output.status("Committing changes...");
-                                        }
-                                    }
-                                }
-                            }
-                            
-                            // Output based on debug level (verbose shows all tool details)
-                            process_event_part(&output, part);
-                        }
-                    }
+            // Between events no tool is running, so a task abort can be applied safely
+            self.apply_abort(&mut run, &output);
+
+            let event = match event_result {
+                Ok(event) => event,
+                Err(e) => {
+                    let delay = self.on_stream_error(&mut run, e.to_string(), &output)?;
+                    tokio::time::sleep(delay).await;
+                    event_stream = runner
+                        .run("ralph-agent".to_string(), session_id.clone(), initial_content.clone())
+                        .await
+                        .map_err(|e| RalphError::Agent {
+                            agent: "ralph-loop".to_string(),
+                            message: format!("Runner failed: {}", e),
+                        })?;
+                    continue;
+                }
+            };
+            run.failed_attempts = 0;
+
+            for part in event.llm_response.content.iter().flat_map(|content| &content.parts) {
+                self.on_part(&mut run, part, &output).await?;
+            }
 
-                    // Count iterations (each escalate = one iteration complete)
-                    if event.actions.escalate {
-                        iteration_count += 1;
-                        output.iteration(iteration_count, self.config.max_iterations);
+            // Count iterations (each escalate = one iteration complete)
+            if event.actions.escalate {
+                run.iterations += 1;
+                output.iteration(run.iterations, self.config.max_iterations);
+            }
+        }
+
+        self.flush_journals();
+        self.finish_run(run, started, &output).await
+    }
+
+    /// Per-run state: the task store's view, the checks run at task
+    /// boundaries, and the counters reported in the summary.
+    fn start_run(&self, tasks_path: PathBuf) -> LoopRun {
+        let mut aborter = TaskAborter::new(
+            &self.project_path,
+            &tasks_path,
+            self.config.max_task_retries as u32,
+            self.write_backup.clone(),
+            self.abort_signal.clone(),
+        );
+        if let Some(rollback) = &self.rollback {
+            aborter = aborter.with_rollback(rollback.clone());
+        }
+        let verifier = ContinuousVerifier::new(self.config.verification_cadence, &self.project_path, &tasks_path)
+            .with_exec_env(self.exec_env.clone())
+            .with_jobs(self.config.tool_jobs());
+        let scaffold = ScaffoldCheck::new(&self.project_path, &tasks_path)
+            .with_exec_env(self.exec_env.clone())
+            .with_jobs(self.config.tool_jobs());
+        let state = TaskList::load(&tasks_path).map(|list| RunState::new(&list)).unwrap_or_default();
+        LoopRun {
+            aborter,
+            verifier,
+            scaffold,
+            glossary_check: GlossaryCheck::new(&self.project_path, &tasks_path),
+            dataset: self.config.dataset_export.as_ref().map(|_| DatasetRecorder::new()),
+            tool_log: ToolLog::create(&self.project_path),
+            state,
+            tasks_path,
+            current_task: None,
+            advised_manifests: HashSet::new(),
+            iterations: 0,
+            tool_calls: 0,
+            tasks_started: 0,
+            failed_attempts: 0,
+            flaky_test_passes: 0,
+            judge_rejections: 0,
+            gate_rejections: 0,
+        }
+    }
+
+    /// Apply a pending task abort.
+    fn apply_abort(&self, run: &mut LoopRun, output: &RalphOutput) {
+        match run.aborter.check() {
+            Ok(Some(report)) => {
+                output.clear_line();
+                output.warn(&format!(
+                    "{} aborted by user ({} file(s) rolled back); {}",
+                    report.task_id,
+                    report.restored_files.len(),
+                    match report.outcome {
+                        AbortOutcome::Requeued => "returned to the queue",
+                        AbortOutcome::Blocked => "marked blocked",
                     }
+                ));
+                run.current_task = None;
+                run.update_progress("task abort", output);
+            }
+            Ok(None) => {}
+            Err(e) => output.warn(&format!("Abort request ignored: {}", e)),
+        }
+    }
+
+    /// The wait before resuming after the model stream failed, or the error
+    /// that ends the run.
+    ///
+    /// Progress lives in the task store, so a resumed loop picks up where the
+    /// failed call stopped; only retryable failures are resumed, as often as
+    /// the [`RetryPolicy`] allows in a row.
+    fn on_stream_error(&self, run: &mut LoopRun, message: String, output: &RalphOutput) -> Result<Duration> {
+        self.flush_journals();
+        let description = ProviderError::describe(self.model_config.provider.as_str(), &message);
+        let error = RalphError::agent_stream("ralph-loop", message);
+        run.failed_attempts += 1;
+        let Some(delay) = stream_retry_delay(&self.retry_policy, &error, run.failed_attempts) else {
+            output.error(&description);
+            tracing::error!(error = %error, "Agent error");
+            return Err(error);
+        };
+        tracing::warn!(error = %error, attempt = run.failed_attempts, delay_ms = delay.as_millis() as u64, "Model call failed; resuming the loop");
+        output.clear_line();
+        output.warn(&format!(
+            "Model call failed: {}; resuming in {} (attempt {} of {})",
+            description,
+            crate::timefmt::duration(delay),
+            run.failed_attempts + 1,
+            self.retry_policy.max_attempts
+        ));
+        Ok(delay)
+    }
+
+    /// Record one part of a model event and act on its tool call or response.
+    async fn on_part(&self, run: &mut LoopRun, part: &Part, output: &RalphOutput) -> Result<()> {
+        if let (Some(recorder), Some(task_id)) = (run.dataset.as_mut(), run.current_task.as_deref()) {
+            recorder.record(task_id, part);
+        }
+        run.tool_log.record(run.current_task.as_deref(), part);
+
+        match part {
+            Part::FunctionCall { name, args, .. } => self.on_tool_call(run, name, args, output).await,
+            Part::FunctionResponse { function_response, .. } => {
+                self.on_tool_response(run, &function_response.name, &function_response.response, output)
+                    .await?
+            }
+            _ => {}
+        }
+
+        // Output based on debug level (verbose shows all tool details)
+        process_event_part(output, part);
+        output.refresh_progress();
+        Ok(())
+    }
+
+    /// Show what a tool call is about to do, starting the task it moves to
+    /// in progress.
+    async fn on_tool_call(&self, run: &mut LoopRun, name: &str, args: &Value, output: &RalphOutput) {
+        run.tool_calls += 1;
+        if let Some(task_id) = started_task(name, args) {
+            self.start_task(run, task_id, output).await;
+        }
+
+        // Signalling the end of the loop does not make the work complete
+        if name == "exit_loop" {
+            if let Ok(list) = TaskList::load(&run.tasks_path) {
+                let stats = list.get_stats();
+                if !list.is_complete() {
+                    output.clear_line();
+                    output.warn(&format!(
+                        "Agent signalled completion but {} task(s) are not complete",
+                        stats.pending + stats.in_progress
+                    ));
                 }
-                Err(e) => {
+            }
+        }
+
+        if let Some(status) = tool_call_status(name, args) {
+            output.status(&status);
+        }
+    }
+
+    /// Make `task_id` the task in progress, holding first while paused and
+    /// pacing task starts (not tool calls) against burst limits.
+    async fn start_task(&self, run: &mut LoopRun, task_id: &str, output: &RalphOutput) {
+        if self.pause_control.state() == LoopState::Paused {
+            self.flush_journals();
+            output.clear_line();
+            output.status(&format!("Paused before starting {}; progress is saved", task_id));
+            self.pause_control.wait_while_paused().await;
+            output.status("Resumed");
+        }
+        let pause = inter_task_pause(
+            self.config.inter_task_delay,
+            self.config.inter_task_jitter,
+            uuid::Uuid::new_v4().as_u64_pair().0,
+        );
+        if let (true, Some(pause)) = (run.tasks_started > 0, pause) {
+            output.clear_line();
+            output.status(&format!("Pacing: starting {} in {}", task_id, crate::timefmt::duration(pause)));
+            tokio::time::sleep(pause).await;
+        }
+        run.tasks_started += 1;
+        self.flush_journals();
+        run.current_task = Some(task_id.to_string());
+        output.clear_line();
+        output.task_start(task_id, "Starting implementation");
+        let progress = run.state.progress();
+        output.progress_bar_with_task(progress.completed, progress.total, task_id);
+    }
+
+    /// Act on a tool's response: count flaky passes, report refused and
+    /// accepted completions, and check newly written manifests.
+    ///
+    /// Fails only when a completed setup task leaves a project that does not build.
+    async fn on_tool_response(
+        &self,
+        run: &mut LoopRun,
+        name: &str,
+        response: &Value,
+        output: &RalphOutput,
+    ) -> Result<()> {
+        // Count test runs that only went green after a retry
+        if name == "test" && response["passed_after_retry"].as_bool() == Some(true) {
+            run.flaky_test_passes += 1;
+        }
+
+        // Check a newly written manifest's dependencies once per run
+        if self.config.dependency_advice && name == "file" && response["operation"] == "write" {
+            if let Some(manifest) = response["full_path"].as_str().map(PathBuf::from) {
+                if Ecosystem::from_manifest(&manifest).is_some() && run.advised_manifests.insert(manifest.clone()) {
+                    self.advise_dependencies(manifest, &run.tasks_path, output).await;
+                    refresh_run_state(&mut run.state, &run.tasks_path, "dependency advice", output);
+                }
+            }
+        }
+
+        if name != "tasks" {
+            return Ok(());
+        }
+
+        // The judge and the test gate can refuse a `complete` that was already shown as done
+        if let Some(warning) = judge_rejection(response) {
+            run.judge_rejections += 1;
+            output.clear_line();
+            output.warn(&warning);
+        }
+        if let Some(warning) = gate_rejection(response) {
+            run.gate_rejections += 1;
+            output.clear_line();
+            output.warn(&warning);
+        }
+
+        // Count completions from the task list, not from the model's claims
+        let completed = completed_task(response);
+        if let Some(task_id) = completed {
+            output.clear_line();
+            output.task_complete(task_id, true);
+            self.flush_journals();
+            run.update_progress("tasks.json edits", output);
+            run.current_task = None;
+        }
+
+        report_scope(response, output);
+
+        // Redraw the overall bar and current task from the task store
+        if self.config.multi_progress {
+            if let Ok(list) = TaskList::load(&run.tasks_path) {
+                output.task_progress(&list);
+            }
+        }
+
+        match completed {
+            Some(task_id) => self.on_task_completed(run, task_id, output).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Checks after a task is accepted: the scaffold build, glossary drift
+    /// and, at the configured cadence, the full test suite.
+    async fn on_task_completed(&self, run: &mut LoopRun, task_id: &str, output: &RalphOutput) -> Result<()> {
+        // A setup task must leave a project that builds
+        if self.config.scaffold_check {
+            match run.scaffold.on_task_completed(task_id).await {
+                Ok(true) => output.status("Project scaffold builds"),
+                Ok(false) => {}
+                Err(e @ RalphError::ScaffoldBuild { .. }) => {
+                    self.flush_journals();
                     output.error(&e.to_string());
-                    tracing::error!(error = %e, "Agent error");
-                    return Err(RalphError::Agent {
-                        agent: "ralph-loop".to_string(),
-                        message: e.to_string(),
-                    });
+                    tracing::error!(task_id, "Project scaffold does not build");
+                    return Err(e);
                 }
+                Err(e) => output.warn(&format!("Scaffold build check skipped: {}", e)),
             }
         }
 
-        // Determine completion status by reading task state
-        let tasks_path = self.project_path.join(&self.config.tasks_path);
-        let task_list = crate::models::TaskList::load(&tasks_path).map_err(RalphError::Task)?;
+        // Names drifting from the glossary go to the task notes
+        if self.config.glossary_check {
+            match run.glossary_check.on_task_completed(task_id) {
+                Ok(drifts) => {
+                    for drift in drifts {
+                        output.warn_about(drift.to_warning(task_id));
+                    }
+                }
+                Err(e) => output.warn(&format!("Glossary check skipped: {}", e)),
+            }
+        }
+
+        match run.verifier.on_task_completed(task_id).await {
+            Ok(Some(report)) if report.passed => {
+                output.status(&format!("Full test suite passed: {}", report.results));
+            }
+            Ok(Some(report)) => {
+                output.warn(&format!(
+                    "Full test suite failed ({}); added {} (suspect: {})",
+                    report.results,
+                    report.regression_task.as_deref().unwrap_or("-"),
+                    report.suspect_task.as_deref().unwrap_or("unknown")
+                ));
+                run.update_progress("full-suite verification", output);
+            }
+            Ok(None) => {}
+            Err(e) => output.warn(&format!("Full-suite verification skipped: {}", e)),
+        }
+        Ok(())
+    }
+
+    /// Print the run's summary, save its snapshot and exports, and read the
+    /// outcome from the task store.
+    async fn finish_run(
+        &self,
+        run: LoopRun,
+        started: std::time::Instant,
+        output: &RalphOutput,
+    ) -> Result<CompletionStatus> {
+        let task_list = TaskList::load(&run.tasks_path).map_err(RalphError::Task)?;
         let stats = task_list.get_stats();
 
         // Output summary
-        let success = task_list.is_complete();
-        output.summary(iteration_count, stats.completed, stats.total, success);
+        let progress = TaskProgress::of(&task_list);
+        output.summary(
+            run.iterations,
+            progress.completed,
+            progress.total,
+            started.elapsed(),
+            task_list.is_complete(),
+        );
+        let plan = run.state.summary(&task_list);
+        if plan.added > 0 || plan.dropped > 0 {
+            output.status(&plan.to_string());
+        }
+        let (task_time, timed_tasks) = task_list.task_time();
+        output.timing_summary(task_time.to_std().unwrap_or_default(), timed_tasks, started.elapsed());
+        if let Some(diff_stats) = task_list.total_diff_stats() {
+            output.diff_summary(&diff_stats);
+        }
+        let (average_attempts, max_attempts, worst_task) = task_list.attempt_stats();
+        if max_attempts > 0 {
+            output.attempt_summary(average_attempts, max_attempts, &worst_task);
+        }
+        if run.flaky_test_passes > 0 {
+            output.warn_about(Warning::new(
+                WarningCategory::FlakyTests,
+                format!("{} test run(s) passed only after retry - tests may be flaky", run.flaky_test_passes),
+            ));
+        }
+        if run.judge_rejections > 0 {
+            output.status(&format!(
+                "Judge sent {} task completion(s) back for unmet acceptance criteria",
+                run.judge_rejections
+            ));
+        }
+        if run.gate_rejections > 0 {
+            output.status(&format!(
+                "Refused {} completion claim(s) made before the task's tests passed",
+                run.gate_rejections
+            ));
+        }
+        let smoke_catches = self.smoke_gate.catches();
+        if smoke_catches > 0 {
+            output.status(&format!(
+                "Smoke gate caught syntax errors right after {} of {} checked write(s)",
+                smoke_catches,
+                self.smoke_gate.checks()
+            ));
+        }
+        let run_warnings = crate::warnings::global().take();
+        output.warning_summary(&run_warnings, self.config.expand_warnings);
+        self.report_since_last_run(&task_list, run_warnings, output);
+        self.report_cleanup(&task_list, &run.tasks_path, output);
+        if let (Some(target), Some(recorder)) = (&self.config.dataset_export, &run.dataset) {
+            self.export_dataset(target, recorder, &task_list, output).await;
+        }
 
         // Debug: show detailed stats
         if output.level().is_debug() {
-            output.debug("stats", &format!("tool_calls: {}", tool_call_count));
+            output.debug("stats", &format!("tool_calls: {}", run.tool_calls));
             output.debug("stats", &format!("blocked: {}", stats.blocked));
             output.debug("stats", &format!("pending: {}", stats.pending));
             output.debug("stats", &format!("in_progress: {}", stats.in_progress));
         }
 
-        if task_list.is_complete() {
-            Ok(CompletionStatus::Complete {
-                iterations: iteration_count,
-                tasks_completed: stats.completed,
-                message: self.config.completion_promise.clone(),
-            })
-        } else if stats.blocked > 0 && stats.pending == 0 && stats.in_progress == 0 {
-            Ok(CompletionStatus::AllTasksBlocked {
-                iterations: iteration_count,
-                tasks_completed: stats.completed,
-                tasks_blocked: stats.blocked,
-                reason: "All remaining tasks are blocked".to_string(),
-            })
-        } else {
-            Ok(CompletionStatus::MaxIterationsReached {
-                iterations: iteration_count,
-                tasks_completed: stats.completed,
-                tasks_remaining: stats.pending + stats.in_progress,
-            })
+        Ok(completion_status(&task_list, run.iterations, &self.config.completion_promise))
+    }
+
+    /// Print what changed since the previous run and save this run's snapshot
+    /// (with its warnings).
+    fn report_since_last_run(
+        &self,
+        task_list: &TaskList,
+        warnings: Vec<Warning>,
+        output: &RalphOutput,
+    ) {
+        let provenance = Provenance::new()
+            .with_agent("ralph", self.provenance.clone())
+            .with_config(&self.config);
+        let snapshot = RunSnapshot::of(task_list).with_warnings(warnings).with_provenance(provenance);
+        if let Some(previous) = RunSnapshot::load(&self.project_path) {
+            output.status(&snapshot.since(&previous).to_string());
+        }
+        if let Err(e) = snapshot.save(&self.project_path) {
+            tracing::warn!(error = %e, "Could not save the last-run snapshot");
         }
     }
-}
 
-/// Create an LLM model from configuration.
-async fn create_model_from_config(config: &ModelConfig) -> Result<Arc<dyn Llm>> {
-    use std::env;
+    /// Print what the run left behind and, when enabled, queue a cleanup task for it.
+    fn report_cleanup(&self, task_list: &TaskList, tasks_path: &Path, output: &RalphOutput) {
+        let report = CleanupReport::for_tasks(&self.project_path, task_list);
+        output.cleanup_report(&report);
+        if !self.config.cleanup_task || report.is_empty() {
+            return;
+        }
+        let recorded = TaskList::load(tasks_path).and_then(|mut list| {
+            let id = record_cleanup_task(&mut list, &report);
+            list.save(tasks_path).map(|_| id)
+        });
+        match recorded {
+            Ok(Some(id)) => output.status(&format!("Queued {} for the next run", id)),
+            Ok(None) => {}
+            Err(e) => output.warn(&format!("Could not record the cleanup task: {}", e)),
+        }
+    }
 
-    let model: Arc<dyn Llm> = match config.provider.to_lowercase().as_str() {
-        "anthropic" => {
-            use adk_rust::model::anthropic::{AnthropicClient, AnthropicConfig};
+    /// Write buffered journal entries at a task boundary.
+    fn flush_journals(&self) {
+        if let Err(e) = self.write_behind.flush() {
+            tracing::warn!(error = %e, "Could not flush buffered writes");
+        }
+    }
 
-            let api_key = env::var("ANTHROPIC_API_KEY").map_err(|_| {
-                RalphError::Configuration("ANTHROPIC_API_KEY environment variable not set".into())
-            })?;
-            let anthropic_config = AnthropicConfig::new(api_key, &config.model_name);
-            let client = AnthropicClient::new(anthropic_config).map_err(|e| RalphError::Model {
-                provider: "anthropic".into(),
-                message: e.to_string(),
-            })?;
-            Arc::new(client)
+    /// Write the run's Done tasks as a dataset shard.
+    ///
+    /// Failures are reported as warnings; they never fail the run.
+    async fn export_dataset(
+        &self,
+        target: &str,
+        recorder: &DatasetRecorder,
+        task_list: &TaskList,
+        output: &RalphOutput,
+    ) {
+        let redactor = Redactor::new(&self.exec_env);
+        let records = recorder.records(task_list, &self.project_path, &redactor);
+        if records.is_empty() {
+            output.status("No completed tasks to export as a dataset");
+            return;
+        }
+        let written = match dataset::shard_jsonl(&records) {
+            Ok(shard) => {
+                dataset::export_shard(&DatasetTarget::parse(target), &records[0].run_id, &shard).await
+            }
+            Err(e) => Err(e),
+        };
+        match written {
+            Ok(location) => output.status(&format!(
+                "Exported {} task(s) as a dataset shard to {}",
+                records.len(),
+                location
+            )),
+            Err(e) => output.warn(&format!("Dataset export failed: {}", e)),
         }
-        "openai" => {
-            use adk_rust::model::openai::{OpenAIClient, OpenAIConfig};
+    }
 
-            let api_key = env::var("OPENAI_API_KEY").map_err(|_| {
-                RalphError::Configuration("OPENAI_API_KEY environment variable not set".into())
-            })?;
-            let openai_config = OpenAIConfig::new(api_key, &config.model_name);
-            let client = OpenAIClient::new(openai_config).map_err(|e| RalphError::Model {
-                provider: "openai".into(),
-                message: e.to_string(),
-            })?;
-            Arc::new(client)
-        }
-        "gemini" => {
-            use adk_rust::model::GeminiModel;
-
-            let api_key = env::var("GEMINI_API_KEY")
-                .or_else(|_| env::var("GOOGLE_API_KEY"))
-                .map_err(|_| {
-                    RalphError::Configuration(
-                        "GEMINI_API_KEY or GOOGLE_API_KEY environment variable not set".into(),
-                    )
-                })?;
-            let client = GeminiModel::new(api_key, &config.model_name).map_err(|e| {
-                RalphError::Model {
-                    provider: "gemini".into(),
-                    message: e.to_string(),
-                }
-            })?;
-            Arc::new(client)
+    /// Add this agent's model to the task list's provenance, keeping earlier entries.
+    fn record_provenance(&self, tasks_path: &Path) {
+        let Ok(mut list) = TaskList::load(tasks_path) else {
+            return;
+        };
+        let meta = Provenance::new()
+            .with_agent("ralph", self.provenance.clone())
+            .merged_with(list.meta.as_ref());
+        list.meta = Some(meta);
+        if let Err(e) = list.save(tasks_path) {
+            tracing::debug!(error = %e, "Could not record loop provenance");
         }
-        provider => {
-            return Err(RalphError::Configuration(format!(
-                "Unsupported model provider: {}. Supported: anthropic, openai, gemini",
-                provider
-            )));
+    }
+
+    /// Bump compatible dependencies in a new manifest and queue major upgrades.
+    ///
+    /// Failures are reported as warnings; they never stop the loop.
+    async fn advise_dependencies(&self, manifest: PathBuf, tasks_path: &Path, output: &RalphOutput) {
+        let registry = HttpRegistry::new(&self.project_path, self.config.offline);
+        let advised = tokio::task::spawn_blocking(move || DependencyAdvisor::new(registry).advise(&manifest))
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|r| r);
+        let report = match advised {
+            Ok(report) => report,
+            Err(e) => {
+                output.warn(&format!("Dependency check skipped: {}", e));
+                return;
+            }
+        };
+
+        for advice in report.bumped() {
+            output.status(&format!(
+                "Bumped {} {} → {}",
+                advice.name,
+                advice.requirement,
+                advice.bumped_to.as_deref().unwrap_or_default()
+            ));
+        }
+        for (name, reason) in &report.skipped {
+            output.debug("deps", &format!("{} not checked: {}", name, reason));
+        }
+        if report.major_gaps().next().is_none() {
+            return;
         }
+
+        let created = TaskList::load(tasks_path).and_then(|mut list| {
+            let created = record_upgrade_tasks(&mut list, &report);
+            list.save(tasks_path).map(|_| created)
+        });
+        match created {
+            Ok(ids) if !ids.is_empty() => output.warn(&format!(
+                "{} dependenc{} behind a major version; queued {}",
+                ids.len(),
+                if ids.len() == 1 { "y" } else { "ies" },
+                ids.join(", ")
+            )),
+            Ok(_) => {}
+            Err(e) => output.warn(&format!("Could not record upgrade tasks: {}", e)),
+        }
+    }
+}
+
+/// Recount progress from tasks.json, noting tasks added or removed by `cause`.
+fn refresh_run_state(
+    run_state: &mut RunState,
+    tasks_path: &Path,
+    cause: &str,
+    output: &RalphOutput,
+) {
+    let Ok(list) = TaskList::load(tasks_path) else {
+        return;
     };
+    if let Some(note) = run_state.refresh(&list).annotation(cause) {
+        output.clear_line();
+        output.status(&note);
+    }
+}
+
+/// Marks the loop finished however `run` returns.
+struct FinishOnDrop<'a>(&'a PauseControl);
+
+impl Drop for FinishOnDrop<'_> {
+    fn drop(&mut self) {
+        self.0.finish();
+    }
+}
+
+/// State of one [`RalphLoopAgent::run`], passed to its event handlers.
+struct LoopRun {
+    /// Applies task aborts between events
+    aborter: TaskAborter,
+    /// Full-suite verification at the configured cadence
+    verifier: ContinuousVerifier,
+    /// Build check after setup tasks
+    scaffold: ScaffoldCheck,
+    /// Naming drift check after each task
+    glossary_check: GlossaryCheck,
+    /// Conversation per task, when exporting a dataset
+    dataset: Option<DatasetRecorder>,
+    /// Tool calls and results of this run
+    tool_log: ToolLog,
+    /// The task store as last seen, for progress and the plan summary
+    state: RunState,
+    tasks_path: PathBuf,
+    /// Task in progress, as last announced by the agent
+    current_task: Option<String>,
+    /// Manifests whose dependencies were already checked
+    advised_manifests: HashSet<PathBuf>,
+    iterations: u32,
+    tool_calls: u32,
+    tasks_started: u32,
+    /// Model stream failures since the last event that arrived
+    failed_attempts: u32,
+    flaky_test_passes: u32,
+    judge_rejections: u32,
+    gate_rejections: u32,
+}
+
+impl LoopRun {
+    /// Re-read the task store after `cause` changed it and redraw the bar.
+    fn update_progress(&mut self, cause: &str, output: &RalphOutput) {
+        refresh_run_state(&mut self.state, &self.tasks_path, cause, output);
+        let progress = self.state.progress();
+        output.progress_bar(progress.completed, progress.total);
+    }
+}
+
+/// The task a `tasks` call moves to in progress.
+fn started_task<'a>(name: &str, args: &'a Value) -> Option<&'a str> {
+    if name != "tasks" || args["operation"] != "update_status" || args["status"] != "in_progress" {
+        return None;
+    }
+    args["task_id"].as_str()
+}
+
+/// Status line shown for a tool call at normal output level, if any.
+fn tool_call_status(name: &str, args: &Value) -> Option<String> {
+    let arg = |key: &str| args[key].as_str();
+    match (name, arg("operation")?) {
+        ("tasks", "get_next") => Some("Getting next task...".to_string()),
+        // Only a successful response counts; the claim itself may be refused
+        ("tasks", "complete") => arg("task_id").map(|task_id| format!("Checking {} is complete...", task_id)),
+        ("file", "write") => arg("path").map(|path| format!("Writing {}", path)),
+        ("file", "read") => arg("path").map(|path| format!("Reading {}", path)),
+        ("test", "run") => Some("Running tests...".to_string()),
+        ("git", "commit") => Some("Committing changes...".to_string()),
+        _ => None,
+    }
+}
+
+/// Warning for a `complete` the judge refused over unmet acceptance criteria.
+fn judge_rejection(response: &Value) -> Option<String> {
+    if response["success"].as_bool() != Some(false) || response["judge"].is_null() {
+        return None;
+    }
+    Some(format!(
+        "{} not accepted: {} acceptance criteria unmet",
+        response["task_id"].as_str().unwrap_or("task"),
+        response["judge"]["criteria"]
+            .as_array()
+            .map(|c| c.iter().filter(|v| v["met"] == false).count())
+            .unwrap_or(0)
+    ))
+}
+
+/// Warning for a completion claim made without passing tests (kept in progress).
+fn gate_rejection(response: &Value) -> Option<String> {
+    if response["success"].as_bool() != Some(false) || response["gate"].is_null() {
+        return None;
+    }
+    Some(format!(
+        "{} claimed complete but {}; kept in progress",
+        response["task_id"].as_str().unwrap_or("task"),
+        response["gate"]["failures"]
+            .as_array()
+            .map(|f| f.iter().filter_map(|v| v.as_str()).collect::<Vec<_>>().join("; "))
+            .unwrap_or_default()
+    ))
+}
+
+/// The task a successful `complete` response marked done.
+fn completed_task(response: &Value) -> Option<&str> {
+    if response["success"].as_bool() != Some(true) || response["all_complete"].is_null() {
+        return None;
+    }
+    response["task_id"].as_str()
+}
+
+/// Surface a task that strayed outside its declared files.
+fn report_scope(response: &Value, output: &RalphOutput) {
+    let scope = &response["scope"];
+    if !scope.is_object() {
+        return;
+    }
+    let task_id = response["task_id"].as_str().unwrap_or("task");
+    if let Some(reason) = scope["needs_review"].as_str() {
+        output.warn(&format!("{} flagged for review: {}", task_id, reason));
+    }
+    if let Some(deviation) = scope["deviation"].as_str() {
+        output.warn(&format!("{} {}", task_id, deviation));
+    }
+    // One line per task; the files themselves only when verbose
+    if let Some(skipped) = scope["skipped"].as_str() {
+        output.warn_about(
+            Warning::new(WarningCategory::SkippedFiles, format!("{} {}", task_id, skipped)).with_task(task_id),
+        );
+        if output.level().is_verbose() {
+            for file in scope["skipped_files"].as_array().into_iter().flatten() {
+                output.list_item(file.as_str().unwrap_or_default());
+            }
+        }
+    }
+}
+
+/// How the run ended, read from the task store.
+fn completion_status(task_list: &TaskList, iterations: u32, promise: &str) -> CompletionStatus {
+    let stats = task_list.get_stats();
+    if task_list.is_complete() {
+        CompletionStatus::Complete {
+            iterations,
+            tasks_completed: stats.completed,
+            message: promise.to_string(),
+        }
+    } else if stats.blocked > 0 && stats.pending == 0 && stats.in_progress == 0 {
+        CompletionStatus::AllTasksBlocked {
+            iterations,
+            tasks_completed: stats.completed,
+            tasks_blocked: stats.blocked,
+            reason: "All remaining tasks are blocked".to_string(),
+        }
+    } else {
+        CompletionStatus::MaxIterationsReached {
+            iterations,
+            tasks_completed: stats.completed,
+            tasks_remaining: stats.pending + stats.in_progress,
+        }
+    }
+}
+
+/// Wait before resuming the loop after its model stream failed `attempt`
+/// times in a row, or `None` when the failure is not retryable or the
+/// policy has no attempts left.
+fn stream_retry_delay(policy: &RetryPolicy, error: &RalphError, attempt: u32) -> Option<Duration> {
+    (error.is_retryable() && policy.allows_retry(attempt)).then(|| policy.random_delay(attempt))
+}
+
+/// Pause before a task start: `delay` plus `sample` scaled into `0..=jitter`.
+///
+/// `None` when there is nothing to wait for.
+fn inter_task_pause(
+    delay: Option<Duration>,
+    jitter: Option<Duration>,
+    sample: u64,
+) -> Option<Duration> {
+    let jitter_ms = jitter.map_or(0, |j| j.as_millis() as u64);
+    let extra = if jitter_ms == 0 { 0 } else { sample % (jitter_ms + 1) };
+    let pause = delay.unwrap_or_default() + Duration::from_millis(extra);
+    (!pause.is_zero()).then_some(pause)
+}
 
-    Ok(model)
+/// Sends each request to the in-progress task's own model, if it names one.
+///
+/// The loop keeps one agent for the whole run, so a task's `model` override
+/// is looked up in tasks.json per request. Override models are created on
+/// first use and reused; tasks without one, and overrides that cannot be
+/// created, use the run's default model.
+struct TaskModelRouter {
+    default: Arc<dyn Llm>,
+    tasks_path: PathBuf,
+    /// Models created for overrides, by `provider/model_name`
+    overrides: tokio::sync::Mutex<HashMap<String, Arc<dyn Llm>>>,
+}
+
+impl TaskModelRouter {
+    fn new(default: Arc<dyn Llm>, tasks_path: &Path) -> Self {
+        Self {
+            default,
+            tasks_path: tasks_path.to_path_buf(),
+            overrides: tokio::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Model for the task currently in progress.
+    async fn current_model(&self) -> Arc<dyn Llm> {
+        let task_model = TaskList::load(&self.tasks_path).ok().and_then(|list| {
+            list.get_all_tasks()
+                .into_iter()
+                .find(|t| t.status == TaskStatus::InProgress)
+                .and_then(|t| t.model.clone().map(|model| (t.id.clone(), model)))
+        });
+        let Some((task_id, config)) = task_model else {
+            return self.default.clone();
+        };
+
+        let key = format!("{}/{}", config.provider, config.model_name);
+        let mut overrides = self.overrides.lock().await;
+        if let Some(model) = overrides.get(&key) {
+            return model.clone();
+        }
+        let model = match create_model(&config).await {
+            Ok(model) => {
+                tracing::info!(task = %task_id, model = %key, "Using the task's model override");
+                model
+            }
+            Err(e) => {
+                tracing::warn!(
+                    task = %task_id,
+                    model = %key,
+                    error = %e,
+                    "Task model override unavailable, using the default model"
+                );
+                self.default.clone()
+            }
+        };
+        overrides.insert(key, model.clone());
+        model
+    }
+}
+
+#[async_trait]
+impl Llm for TaskModelRouter {
+    fn name(&self) -> &str {
+        self.default.name()
+    }
+
+    async fn generate_content(
+        &self,
+        request: LlmRequest,
+        stream: bool,
+    ) -> AdkResult<LlmResponseStream> {
+        self.current_model().await.generate_content(request, stream).await
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_inter_task_pause() {
+        let ms = Duration::from_millis;
+        assert_eq!(inter_task_pause(None, None, 7), None);
+        assert_eq!(inter_task_pause(Some(ms(0)), None, 7), None);
+        assert_eq!(inter_task_pause(Some(ms(2000)), None, 7), Some(ms(2000)));
+        assert_eq!(inter_task_pause(Some(ms(2000)), Some(ms(500)), 1_234), Some(ms(2232)));
+        assert_eq!(inter_task_pause(None, Some(ms(500)), 500), Some(ms(500)));
+        for sample in [0, 1, 499, 500, 501, u64::MAX] {
+            let pause = inter_task_pause(Some(ms(1000)), Some(ms(500)), sample).unwrap();
+            assert!(pause >= ms(1000) && pause <= ms(1500), "{:?}", pause);
+        }
+    }
+
+    #[test]
+    fn test_stream_retry_follows_the_provider_classification() {
+        let policy = RetryPolicy::default()
+            .with_max_attempts(3)
+            .with_delays(Duration::from_secs(1), Duration::from_secs(1))
+            .with_jitter(false);
+        let overloaded = RalphError::agent_stream(
+            "ralph-loop",
+            r#"HTTP 529: {"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#,
+        );
+        assert_eq!(stream_retry_delay(&policy, &overloaded, 1), Some(Duration::from_secs(1)));
+        assert_eq!(stream_retry_delay(&policy, &overloaded, 2), Some(Duration::from_secs(1)));
+        assert_eq!(stream_retry_delay(&policy, &overloaded, 3), None);
+
+        // A 429 for exhausted quota will not clear up by waiting
+        let quota = RalphError::agent_stream(
+            "ralph-loop",
+            r#"HTTP 429: {"error":{"message":"You exceeded your current quota","code":"insufficient_quota"}}"#,
+        );
+        assert_eq!(stream_retry_delay(&policy, &quota, 1), None);
+        assert_eq!(stream_retry_delay(&RetryPolicy::none(), &overloaded, 1), None);
+    }
+
+    #[derive(Clone, Default)]
+    struct BufferWriter(Arc<std::sync::Mutex<String>>);
+
+    impl crate::output::OutputWriter for BufferWriter {
+        fn write_stdout(&mut self, text: &str) {
+            self.0.lock().unwrap().push_str(text);
+        }
+
+        fn write_stderr(&mut self, text: &str) {
+            self.0.lock().unwrap().push_str(text);
+        }
+    }
+
+    #[test]
+    fn test_tool_call_status_lines() {
+        let status = |name: &str, args: Value| tool_call_status(name, &args);
+        assert_eq!(status("tasks", json!({"operation": "get_next"})).as_deref(), Some("Getting next task..."));
+        assert_eq!(
+            status("tasks", json!({"operation": "complete", "task_id": "T-002"})).as_deref(),
+            Some("Checking T-002 is complete...")
+        );
+        assert_eq!(
+            status("file", json!({"operation": "write", "path": "src/lib.rs"})).as_deref(),
+            Some("Writing src/lib.rs")
+        );
+        assert_eq!(status("git", json!({"operation": "commit"})).as_deref(), Some("Committing changes..."));
+        assert_eq!(status("file", json!({"operation": "delete", "path": "x"})), None);
+        assert_eq!(status("test", json!({})), None);
+
+        let start = json!({"operation": "update_status", "task_id": "T-003", "status": "in_progress"});
+        assert_eq!(started_task("tasks", &start), Some("T-003"));
+        assert_eq!(started_task("git", &start), None);
+        let done = json!({"operation": "update_status", "task_id": "T-003", "status": "done"});
+        assert_eq!(started_task("tasks", &done), None);
+    }
+
+    #[test]
+    fn test_task_responses_are_classified() {
+        let judged = json!({
+            "success": false,
+            "task_id": "T-001",
+            "judge": {"criteria": [{"met": true}, {"met": false}, {"met": false}]}
+        });
+        assert_eq!(judge_rejection(&judged).as_deref(), Some("T-001 not accepted: 2 acceptance criteria unmet"));
+        assert_eq!(gate_rejection(&judged), None);
+
+        let gated = json!({
+            "success": false,
+            "task_id": "T-001",
+            "gate": {"failures": ["tests not run", "build failed"]}
+        });
+        assert_eq!(
+            gate_rejection(&gated).as_deref(),
+            Some("T-001 claimed complete but tests not run; build failed; kept in progress")
+        );
+        assert_eq!(completed_task(&gated), None);
+
+        // The verdict of an accepted task is not a rejection
+        let accepted = json!({"success": true, "task_id": "T-001", "judge": {"criteria": []}, "all_complete": false});
+        assert_eq!(completed_task(&accepted), Some("T-001"));
+        assert_eq!(judge_rejection(&accepted), None);
+        // `get_next` reports all_complete without a task
+        assert_eq!(completed_task(&json!({"success": true, "all_complete": true})), None);
+    }
+
+    #[test]
+    fn test_completion_status_from_task_store() {
+        use crate::models::Task;
+
+        let mut list = TaskList::new("demo", "rust");
+        list.add_task(Task::new("T-001", "Scaffold", "", 1));
+        list.add_task(Task::new("T-002", "Solver", "", 2));
+        assert!(matches!(
+            completion_status(&list, 4, "done"),
+            CompletionStatus::MaxIterationsReached { iterations: 4, tasks_completed: 0, tasks_remaining: 2 }
+        ));
+
+        list.get_task_mut("T-001").unwrap().complete(None);
+        list.get_task_mut("T-002").unwrap().start();
+        assert!(matches!(
+            completion_status(&list, 5, "done"),
+            CompletionStatus::MaxIterationsReached { tasks_completed: 1, tasks_remaining: 1, .. }
+        ));
+
+        // A blocked task is finished with; the run is over
+        list.get_task_mut("T-002").unwrap().block("out of attempts");
+        assert_eq!(
+            completion_status(&list, 6, "done"),
+            CompletionStatus::Complete { iterations: 6, tasks_completed: 1, message: "done".to_string() }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tool_events_update_the_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let model: Arc<dyn Llm> = Arc::new(crate::replay::ReplayModel::from_json("default", "[]").unwrap());
+        let agent = RalphLoopAgent::builder()
+            .project_path(dir.path())
+            .instruction("Implement the tasks")
+            .build_with_model(model)
+            .unwrap();
+        let buffer = BufferWriter::default();
+        let output = RalphOutput::with_writer(crate::models::DebugLevel::Normal, buffer.clone());
+        let mut run = agent.start_run(dir.path().join("tasks.json"));
+
+        let start = json!({"operation": "update_status", "task_id": "T-001", "status": "in_progress"});
+        agent.on_tool_call(&mut run, "tasks", &start, &output).await;
+        assert_eq!(run.current_task.as_deref(), Some("T-001"));
+        assert_eq!((run.tool_calls, run.tasks_started), (1, 1));
+
+        let flaky = json!({"passed": true, "passed_after_retry": true});
+        agent.on_tool_response(&mut run, "test", &flaky, &output).await.unwrap();
+        let refused = json!({"success": false, "task_id": "T-001", "gate": {"failures": ["tests not run"]}});
+        agent.on_tool_response(&mut run, "tasks", &refused, &output).await.unwrap();
+        assert_eq!((run.flaky_test_passes, run.gate_rejections, run.judge_rejections), (1, 1, 0));
+        assert_eq!(run.current_task.as_deref(), Some("T-001"));
+
+        output.flush();
+        let printed = buffer.0.lock().unwrap().clone();
+        assert!(printed.contains("T-001 claimed complete but tests not run; kept in progress"), "{}", printed);
+    }
 
     #[test]
     fn test_builder_defaults() {
@@ -750,6 +1713,41 @@ mod tests {
         assert!(builder.additional_tools.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_in_progress_task_model_override_is_used() {
+        use crate::models::Task;
+
+        let dir = tempfile::tempdir().unwrap();
+        let tasks_path = dir.path().join("tasks.json");
+        let default: Arc<dyn Llm> =
+            Arc::new(crate::replay::ReplayModel::from_json("default", "[]").unwrap());
+        let router = TaskModelRouter::new(default, &tasks_path);
+
+        // No tasks.json yet
+        assert_eq!(router.current_model().await.name(), "default");
+
+        let mut list = TaskList::new("demo", "rust");
+        let mut easy = Task::new("T-001", "Scaffold", "", 1);
+        easy.start();
+        list.add_task(easy);
+        let hard = Task::new("T-002", "Solver", "", 2)
+            .with_model(ModelConfig::new("replay", "demo-architect"));
+        list.add_task(hard);
+        list.save(&tasks_path).unwrap();
+        assert_eq!(router.current_model().await.name(), "default");
+
+        list.get_task_mut("T-001").unwrap().complete(None);
+        list.get_task_mut("T-002").unwrap().start();
+        list.save(&tasks_path).unwrap();
+        assert_eq!(router.current_model().await.name(), "demo-architect");
+
+        // An override that cannot be created falls back to the default
+        let task = list.get_task_mut("T-002").unwrap();
+        task.model = Some(ModelConfig::new("replay", "missing-recording.json"));
+        list.save(&tasks_path).unwrap();
+        assert_eq!(router.current_model().await.name(), "default");
+    }
+
     #[test]
     fn test_instruction_content() {
         let instruction = RalphLoopAgent::instruction();
@@ -4,11 +4,16 @@
 //! - [`PrdAgent`] - Generates structured requirements from user prompts
 //! - [`ArchitectAgent`] - Creates system design and task breakdown from PRD
 //! - [`RalphLoopAgent`] - Iteratively implements tasks until completion
+//! - [`JudgeAgent`] - Optionally checks a task's acceptance criteria before it completes
 
 pub mod architect_agent;
+pub mod judge_agent;
 pub mod loop_agent;
 pub mod prd_agent;
+pub mod prompt;
 
-pub use architect_agent::ArchitectAgent;
+pub use architect_agent::{render_plan, ArchitectAgent, ArchitectAgentBuilder, PlanFormat, ARCHITECT_RESPONSE_FILE, PLAN_SEPARATOR};
+pub use judge_agent::{CriterionVerdict, JudgeAgent, JudgeAgentBuilder, JudgeVerdict};
 pub use loop_agent::{CompletionStatus, RalphLoopAgent, RalphLoopAgentBuilder};
 pub use prd_agent::{PrdAgent, PrdAgentBuilder, PrdResult};
+pub use prompt::{AssembledPrompt, PromptSection};
@@ -9,7 +9,11 @@
 //! - `output_schema` to force structured JSON response
 //! - `output_key` to store PRD in session state for downstream agents
 
-use crate::models::ModelConfig;
+use crate::agents::prompt::{prd_user_message, AssembledPrompt};
+use crate::model_timeout::TimeoutModel;
+use crate::models::{create_model, ModelConfig};
+use crate::provenance::{self, AgentProvenance, Provenance};
+use crate::textfmt::truncate_display;
 use crate::{RalphError, Result};
 use adk_rust::agent::LlmAgentBuilder;
 use adk_rust::{Agent, Llm};
@@ -66,6 +70,10 @@ pub struct PrdResult {
 pub struct PrdAgent {
     agent: Arc<dyn Agent + Send + Sync>,
     project_path: PathBuf,
+    /// Where the PRD is written, relative to the project folder unless absolute
+    output_path: PathBuf,
+    /// Model details recorded in the PRD header
+    provenance: AgentProvenance,
 }
 
 impl std::fmt::Debug for PrdAgent {
@@ -73,6 +81,7 @@ impl std::fmt::Debug for PrdAgent {
         f.debug_struct("PrdAgent")
             .field("name", &self.agent.name())
             .field("project_path", &self.project_path)
+            .field("output_path", &self.output_path)
             .finish()
     }
 }
@@ -97,6 +106,11 @@ impl PrdAgent {
     pub fn project_path(&self) -> &PathBuf {
         &self.project_path
     }
+
+    /// Get the PRD path, relative to the generated project folder.
+    pub fn output_path(&self) -> &PathBuf {
+        &self.output_path
+    }
 }
 
 /// Builder for creating a PrdAgent with fluent API.
@@ -144,6 +158,8 @@ impl PrdAgentBuilder {
         self
     }
 
+    /// Write the PRD here instead of `prd.md`; relative paths are inside the
+    /// project folder the agent creates.
     pub fn output_path(mut self, path: impl Into<PathBuf>) -> Self {
         self.output_path = path.into();
         self
@@ -155,10 +171,15 @@ impl PrdAgentBuilder {
     }
 
     pub async fn build(self) -> Result<PrdAgent> {
+        let provenance = match self.model {
+            Some(ref m) => AgentProvenance::from_model_name(m.name(), &self.model_config, PRD_INSTRUCTION),
+            None => AgentProvenance::from_config(&self.model_config, PRD_INSTRUCTION),
+        };
         let model = match self.model {
             Some(m) => m,
-            None => create_model_from_config(&self.model_config).await?,
+            None => create_model(&self.model_config).await?,
         };
+        let model = TimeoutModel::wrap(model, self.model_config.model_call_timeout());
 
         // Define the JSON schema for structured PRD output
         let prd_schema = json!({
@@ -243,71 +264,12 @@ impl PrdAgentBuilder {
         Ok(PrdAgent {
             agent: Arc::new(agent),
             project_path: self.project_path,
+            output_path: self.output_path,
+            provenance,
         })
     }
 }
 
-
-/// Create an LLM model from configuration.
-async fn create_model_from_config(config: &ModelConfig) -> Result<Arc<dyn Llm>> {
-    use std::env;
-
-    let model: Arc<dyn Llm> = match config.provider.to_lowercase().as_str() {
-        "anthropic" => {
-            use adk_rust::model::anthropic::{AnthropicClient, AnthropicConfig};
-
-            let api_key = env::var("ANTHROPIC_API_KEY").map_err(|_| {
-                RalphError::Configuration("ANTHROPIC_API_KEY environment variable not set".into())
-            })?;
-            let anthropic_config = AnthropicConfig::new(api_key, &config.model_name);
-            let client = AnthropicClient::new(anthropic_config).map_err(|e| RalphError::Model {
-                provider: "anthropic".into(),
-                message: e.to_string(),
-            })?;
-            Arc::new(client)
-        }
-        "openai" => {
-            use adk_rust::model::openai::{OpenAIClient, OpenAIConfig};
-
-            let api_key = env::var("OPENAI_API_KEY").map_err(|_| {
-                RalphError::Configuration("OPENAI_API_KEY environment variable not set".into())
-            })?;
-            let openai_config = OpenAIConfig::new(api_key, &config.model_name);
-            let client = OpenAIClient::new(openai_config).map_err(|e| RalphError::Model {
-                provider: "openai".into(),
-                message: e.to_string(),
-            })?;
-            Arc::new(client)
-        }
-        "gemini" => {
-            use adk_rust::model::GeminiModel;
-
-            let api_key = env::var("GEMINI_API_KEY")
-                .or_else(|_| env::var("GOOGLE_API_KEY"))
-                .map_err(|_| {
-                    RalphError::Configuration(
-                        "GEMINI_API_KEY or GOOGLE_API_KEY environment variable not set".into(),
-                    )
-                })?;
-            let client = GeminiModel::new(api_key, &config.model_name).map_err(|e| {
-                RalphError::Model {
-                    provider: "gemini".into(),
-                    message: e.to_string(),
-                }
-            })?;
-            Arc::new(client)
-        }
-        provider => {
-            return Err(RalphError::Configuration(format!(
-                "Unsupported model provider: {}. Supported: anthropic, openai, gemini",
-                provider
-            )));
-        }
-    };
-
-    Ok(model)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -334,6 +296,48 @@ mod tests {
         assert_eq!(builder.project_path, PathBuf::from("/tmp/project"));
     }
 
+    /// Replies with a one-story PRD.
+    struct PrdModel;
+
+    #[async_trait::async_trait]
+    impl Llm for PrdModel {
+        fn name(&self) -> &str {
+            "prd-model"
+        }
+
+        async fn generate_content(
+            &self,
+            _request: adk_rust::LlmRequest,
+            _stream: bool,
+        ) -> adk_rust::Result<adk_rust::LlmResponseStream> {
+            let prd = json!({
+                "project_name": "Hello", "project_folder": "hello", "overview": "Says hello",
+                "user_stories": [{ "id": "US-001", "title": "Greet", "priority": 1,
+                                   "story": "As a user, I want a greeting", "acceptance_criteria": ["Prints hello"] }]
+            });
+            let response = adk_rust::LlmResponse::new(adk_rust::Content::new("model").with_text(prd.to_string()));
+            Ok(Box::pin(futures::stream::once(async move { Ok(response) })))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generate_writes_the_prd_to_the_output_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let agent = PrdAgent::builder()
+            .model(Arc::new(PrdModel))
+            .project_path(dir.path())
+            .output_path("requirements/spec.md")
+            .build()
+            .await
+            .unwrap();
+        let result = agent.generate("Say hello").await.unwrap();
+
+        assert_eq!(result.project_dir, dir.path().join("hello"));
+        let prd = crate::models::PrdDocument::load_markdown(result.project_dir.join("requirements/spec.md")).unwrap();
+        assert_eq!(prd.user_stories[0].id, "US-001");
+        assert!(!result.project_dir.join("prd.md").exists());
+    }
+
     #[test]
     fn test_prd_instruction_content() {
         let instruction = PrdAgent::instruction();
@@ -395,7 +399,7 @@ impl PrdAgent {
         let user_content = Content {
             role: "user".to_string(),
             parts: vec![Part::Text {
-                text: format!("Generate a PRD for the following project:\n\n{}", prompt),
+                text: prd_user_message(prompt),
             }],
         };
 
@@ -421,18 +425,13 @@ impl PrdAgent {
                         }
                     }
                 }
-                Err(e) => {
-                    return Err(RalphError::Agent {
-                        agent: "prd".to_string(),
-                        message: e.to_string(),
-                    });
-                }
+                Err(e) => return Err(RalphError::agent_stream("prd", e.to_string())),
             }
         }
 
         // Parse the JSON response
         let prd_json: serde_json::Value = serde_json::from_str(&response_text)
-            .map_err(|e| RalphError::Prd(format!("Failed to parse PRD JSON: {} - Response: {}", e, &response_text[..response_text.len().min(500)])))?;
+            .map_err(|e| RalphError::Prd(format!("Failed to parse PRD JSON: {} - Response: {}", e, truncate_display(&response_text, 500))))?;
 
         // Convert JSON to PrdDocument
         let prd = json_to_prd_document(&prd_json)?;
@@ -460,10 +459,15 @@ impl PrdAgent {
         std::fs::create_dir_all(&project_dir)
             .map_err(|e| RalphError::Prd(format!("Failed to create project folder '{}': {}", project_dir.display(), e)))?;
 
+        if let Err(e) = AssembledPrompt::prd(prompt).record(&project_dir) {
+            tracing::debug!(error = %e, "Could not record PRD prompt");
+        }
+
         // Write the PRD as markdown inside the project folder
-        let prd_path = project_dir.join("prd.md");
-        let markdown = prd_to_markdown(&prd);
-        std::fs::write(&prd_path, &markdown)
+        let prd_path = project_dir.join(&self.output_path);
+        let meta = Provenance::new().with_agent("prd", self.provenance.clone());
+        let markdown = provenance::with_markdown_header(&prd_to_markdown(&prd), &meta);
+        crate::persist::write_atomic(&prd_path, markdown.as_bytes())
             .map_err(|e| RalphError::Prd(format!("Failed to write PRD file: {}", e)))?;
 
         Ok(PrdResult { prd, project_dir })
@@ -520,7 +524,7 @@ fn json_to_prd_document(json: &serde_json::Value) -> Result<crate::models::PrdDo
         language: None,
         user_stories,
         version: "1.0".to_string(),
-        created_at: Some(chrono::Utc::now().to_rfc3339()),
+        created_at: Some(crate::timefmt::now()),
         updated_at: None,
     })
 }
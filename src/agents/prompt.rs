@@ -0,0 +1,435 @@
+//! Prompt assembly for Ralph's agents.
+//!
+//! Each agent's prompt is built from ordered [`PromptSection`]s by pure functions,
+//! so the exact text an agent receives can be inspected without calling a model
+//! (`ralph prompt show <agent>`). The agents use the same functions at build and
+//! run time.
+//!
+//! The last assembled prompt for each agent is recorded under
+//! [`PROMPT_JOURNAL_DIR`] so `ralph prompt show --diff` can compare against it.
+
+use crate::agents::{ArchitectAgent, JudgeAgent, PrdAgent, RalphLoopAgent};
+use crate::diff::DiffLine;
+use crate::glossary::Glossary;
+use crate::models::{DesignDocument, Task, TaskList, TestInfrastructure};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory (relative to the project) holding the last prompt sent to each agent.
+pub const PROMPT_JOURNAL_DIR: &str = ".ralph/prompts";
+
+/// Agents whose prompts can be inspected.
+pub const PROMPT_AGENTS: &[&str] = &["prd", "architect", "developer", "judge"];
+
+/// One named part of an assembled prompt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptSection {
+    /// Section name shown in size annotations
+    pub title: String,
+    /// Exact text contributed to the prompt
+    pub content: String,
+}
+
+impl PromptSection {
+    /// Create a new section.
+    pub fn new(title: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            content: content.into(),
+        }
+    }
+}
+
+/// A fully assembled prompt: the system instruction plus the first user message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssembledPrompt {
+    /// Agent name (prd, architect, developer)
+    pub agent: String,
+    /// Sections of the system instruction, in order
+    pub instruction: Vec<PromptSection>,
+    /// Sections of the first user message, in order
+    pub user_message: Vec<PromptSection>,
+}
+
+impl AssembledPrompt {
+    /// Prompt for the PRD agent, given the user's project description.
+    pub fn prd(project_prompt: &str) -> Self {
+        Self {
+            agent: "prd".to_string(),
+            instruction: vec![PromptSection::new("Instruction", PrdAgent::instruction())],
+            user_message: vec![PromptSection::new("Request", prd_user_message(project_prompt))],
+        }
+    }
+
+    /// Prompt for the architect agent, given the PRD markdown.
+    pub fn architect(prd_content: &str) -> Self {
+        Self {
+            agent: "architect".to_string(),
+            instruction: vec![PromptSection::new("Instruction", ArchitectAgent::instruction())],
+            user_message: vec![PromptSection::new("PRD", architect_user_message(prd_content))],
+        }
+    }
+
+    /// Prompt for the developer (Ralph loop) agent.
+    ///
+    /// Task details are not part of it: the agent fetches them with `tasks get_next`.
+    pub fn developer(design: Option<&DesignDocument>, project_path: &Path, completion_promise: &str) -> Self {
+        let mut instruction = vec![PromptSection::new("Instruction", RalphLoopAgent::instruction())];
+        if let Some(design) = design {
+            instruction.push(PromptSection::new("Project Context", project_context(design, project_path)));
+        }
+        if let Some(glossary) = Glossary::load(project_path) {
+            instruction.push(PromptSection::new("Glossary", glossary.prompt_section()));
+        }
+        instruction.push(PromptSection::new(
+            "Completion Promise",
+            format!(
+                "\n\n## Completion Promise\n\nWhen all tasks are done, output: \"{}\"\n",
+                completion_promise
+            ),
+        ));
+
+        Self {
+            agent: "developer".to_string(),
+            instruction,
+            user_message: vec![PromptSection::new("Kickoff", LOOP_KICKOFF_MESSAGE)],
+        }
+    }
+
+    /// Prompt for the judge agent, given a task and the contents of its files.
+    pub fn judge(task: &Task, files: &[(String, String)]) -> Self {
+        Self {
+            agent: "judge".to_string(),
+            instruction: vec![PromptSection::new("Instruction", JudgeAgent::instruction())],
+            user_message: vec![PromptSection::new("Review", judge_user_message(task, files))],
+        }
+    }
+
+    /// The system instruction text exactly as given to the agent.
+    pub fn instruction_text(&self) -> String {
+        self.instruction.iter().map(|s| s.content.as_str()).collect()
+    }
+
+    /// The first user message text exactly as sent to the agent.
+    pub fn user_message_text(&self) -> String {
+        self.user_message.iter().map(|s| s.content.as_str()).collect()
+    }
+
+    /// Full prompt text (instruction, then user message).
+    pub fn render(&self) -> String {
+        format!(
+            "{}\n\n--- user ---\n\n{}",
+            self.instruction_text(),
+            self.user_message_text()
+        )
+    }
+
+    /// Render with a header per section annotating its size.
+    pub fn annotated(&self) -> String {
+        let mut out = String::new();
+        for (part, sections) in [("instruction", &self.instruction), ("user", &self.user_message)] {
+            for section in sections.iter() {
+                out.push_str(&format!(
+                    "===== [{}] {} — {} chars, {} lines, ~{} tokens =====\n",
+                    part,
+                    section.title,
+                    section.content.chars().count(),
+                    section.content.lines().count(),
+                    approx_tokens(&section.content)
+                ));
+                out.push_str(section.content.trim_matches('\n'));
+                out.push_str("\n\n");
+            }
+        }
+        let total = self.render();
+        out.push_str(&format!(
+            "===== total — {} chars, ~{} tokens =====\n",
+            total.chars().count(),
+            approx_tokens(&total)
+        ));
+        out
+    }
+
+    /// Path of the recorded prompt for this agent in a project.
+    pub fn journal_path(project_path: &Path, agent: &str) -> PathBuf {
+        project_path.join(PROMPT_JOURNAL_DIR).join(format!("{}.md", agent))
+    }
+
+    /// Record this prompt as the agent's last real invocation.
+    pub fn record(&self, project_path: &Path) -> Result<(), String> {
+        let path = Self::journal_path(project_path, &self.agent);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create '{}': {}", parent.display(), e))?;
+        }
+        fs::write(&path, self.render())
+            .map_err(|e| format!("Failed to record prompt '{}': {}", path.display(), e))
+    }
+
+    /// Load the prompt recorded for an agent's last real invocation, if any.
+    pub fn load_recorded(project_path: &Path, agent: &str) -> Option<String> {
+        fs::read_to_string(Self::journal_path(project_path, agent)).ok()
+    }
+}
+
+/// First message sent to the developer loop.
+pub const LOOP_KICKOFF_MESSAGE: &str =
+    "Start implementing tasks. Read progress first, then get the next task and implement it.";
+
+/// User message sent to the PRD agent.
+pub fn prd_user_message(project_prompt: &str) -> String {
+    format!("Generate a PRD for the following project:\n\n{}", project_prompt)
+}
+
+/// User message sent to the architect agent.
+pub fn architect_user_message(prd_content: &str) -> String {
+    format!(
+        "Generate the system design and task breakdown for the following PRD:\n\n---\n{}\n---",
+        crate::provenance::strip_markdown_header(prd_content)
+    )
+}
+
+/// User message asking the architect to correct a design that made tasks fail.
+///
+/// Lists each failed task with its error summary, the completed tasks whose IDs
+/// must be kept, then the current design and the PRD.
+pub fn architect_replan_message(
+    prd_content: &str,
+    design_markdown: &str,
+    tasks: &TaskList,
+    failures: &[(String, String)],
+) -> String {
+    let mut message = String::from(
+        "The design below was implemented, but the following tasks failed for reasons \
+         that point at the design itself. Produce a corrected system design and task \
+         breakdown.\n\n## Failed Tasks\n\n",
+    );
+    for (id, summary) in failures {
+        match tasks.get_task(id) {
+            Some(task) => {
+                message.push_str(&format!("- {} ({}): {}\n", id, task.title, summary))
+            }
+            None => message.push_str(&format!("- {}: {}\n", id, summary)),
+        }
+    }
+
+    let completed: Vec<&Task> =
+        tasks.get_all_tasks().into_iter().filter(|t| t.is_completed()).collect();
+    if !completed.is_empty() {
+        message.push_str(
+            "\n## Completed Tasks\n\nThis work is done; keep these task IDs and do not redo them.\n\n",
+        );
+        for task in completed {
+            message.push_str(&format!("- {}: {}\n", task.id, task.title));
+        }
+    }
+
+    message.push_str(&format!(
+        "\n## Current Design\n\n---\n{}\n---\n\n## PRD\n\n---\n{}\n---\n\n\
+         Return the complete JSON in the same format. Change the design only where the \
+         failures require it, and replace or rework the failed tasks so they can succeed.",
+        crate::provenance::strip_markdown_header(design_markdown).trim(),
+        crate::provenance::strip_markdown_header(prd_content).trim()
+    ));
+    message
+}
+
+/// User message sent to the judge agent: the criteria, then each file's code.
+pub fn judge_user_message(task: &Task, files: &[(String, String)]) -> String {
+    let mut message = format!(
+        "Judge task {} ({}) against its acceptance criteria.\n\n## Acceptance Criteria\n\n",
+        task.id, task.title
+    );
+    for (i, criterion) in task.acceptance_criteria.iter().enumerate() {
+        message.push_str(&format!("{}. {}\n", i + 1, criterion));
+    }
+    message.push_str("\n## Code\n");
+    if files.is_empty() {
+        message.push_str("\n(no files were recorded for this task)\n");
+    }
+    for (path, content) in files {
+        message.push_str(&format!("\n### {}\n\n```\n{}\n```\n", path, content.trim_end()));
+    }
+    message
+}
+
+/// Design excerpt appended to the developer instruction.
+pub fn project_context(design: &DesignDocument, project_path: &Path) -> String {
+    let mut ctx = String::from("\n\n## Project Context\n\n");
+    ctx.push_str(&format!("Project: {}\n", design.project));
+    ctx.push_str(&format!(
+        "Project root: {} (all file paths are relative to this directory)\n",
+        project_path.display()
+    ));
+    if let Some(ref tech) = design.technology_stack {
+        ctx.push_str(&format!("Language: {}\n", tech.language));
+    }
+    if !design.overview.is_empty() {
+        ctx.push_str(&format!("\nOverview: {}\n", design.overview));
+    }
+    if let Some(helpers) = design
+        .test_infrastructure
+        .as_ref()
+        .and_then(|infra| shared_test_helpers(infra, project_path))
+    {
+        ctx.push_str(&helpers);
+    }
+    ctx
+}
+
+/// Shared test helpers for the developer to import rather than re-implement.
+fn shared_test_helpers(infra: &TestInfrastructure, project_path: &Path) -> Option<String> {
+    if infra.is_empty() {
+        return None;
+    }
+    let mut helpers = String::from(
+        "\n### Shared Test Helpers\n\nImport these in tests; do not redefine them in test files.\n",
+    );
+    if let Some(module) = &infra.helper_module {
+        let state = if project_path.join(module).exists() { "" } else { " (not written yet)" };
+        helpers.push_str(&format!("Module: {}{}\n", module, state));
+    }
+    for (title, items) in [("Fixtures", &infra.fixtures), ("Factories", &infra.factories)] {
+        if !items.is_empty() {
+            helpers.push_str(&format!("{}: {}\n", title, items.join(", ")));
+        }
+    }
+    Some(helpers)
+}
+
+/// Line-level diff between a recorded prompt and the current one.
+///
+/// Lines only in `old` are prefixed with `-`, lines only in `new` with `+`,
+/// shared lines with a space.
+pub fn diff_lines(old: &str, new: &str) -> Vec<String> {
+    crate::diff::line_diff(old, new)
+        .into_iter()
+        .map(|line| match line {
+            DiffLine::Context(text) => format!("  {}", text),
+            DiffLine::Removed(text) => format!("- {}", text),
+            DiffLine::Added(text) => format!("+ {}", text),
+        })
+        .collect()
+}
+
+/// Rough token estimate (about four characters per token).
+fn approx_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TechnologyStack;
+
+    fn titles(sections: &[PromptSection]) -> Vec<&str> {
+        sections.iter().map(|s| s.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_developer_prompt_sections_in_order() {
+        let mut design = DesignDocument::new("calc", "A calculator");
+        design.technology_stack = Some(TechnologyStack::new("rust"));
+
+        let prompt = AssembledPrompt::developer(Some(&design), Path::new("/tmp/calc"), "Done!");
+        assert_eq!(titles(&prompt.instruction), vec!["Instruction", "Project Context", "Completion Promise"]);
+        assert_eq!(titles(&prompt.user_message), vec!["Kickoff"]);
+
+        let text = prompt.instruction_text();
+        let context_at = text.find("## Project Context").unwrap();
+        let promise_at = text.find("## Completion Promise").unwrap();
+        assert!(context_at < promise_at);
+        assert!(text.contains("Language: rust"));
+        assert_eq!(prompt.user_message_text(), LOOP_KICKOFF_MESSAGE);
+    }
+
+    #[test]
+    fn test_project_context_lists_shared_test_helpers() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut design = DesignDocument::new("calc", "A calculator");
+        assert!(!project_context(&design, dir.path()).contains("Shared Test Helpers"));
+
+        design.test_infrastructure = Some(TestInfrastructure {
+            helper_module: Some("tests/common/mod.rs".to_string()),
+            fixtures: vec!["temp_workspace()".to_string()],
+            factories: vec!["expr(&str)".to_string(), "config()".to_string()],
+        });
+        let context = project_context(&design, dir.path());
+        assert!(context.contains("### Shared Test Helpers"));
+        assert!(context.contains("Module: tests/common/mod.rs (not written yet)\n"));
+        assert!(context.contains("Fixtures: temp_workspace()\n"));
+        assert!(context.contains("Factories: expr(&str), config()\n"));
+
+        std::fs::create_dir_all(dir.path().join("tests/common")).unwrap();
+        std::fs::write(dir.path().join("tests/common/mod.rs"), "").unwrap();
+        let context = project_context(&design, dir.path());
+        assert!(context.contains("Module: tests/common/mod.rs\n"));
+    }
+
+    #[test]
+    fn test_developer_prompt_without_design() {
+        let prompt = AssembledPrompt::developer(None, Path::new("."), "Done!");
+        assert_eq!(titles(&prompt.instruction), vec!["Instruction", "Completion Promise"]);
+        assert_eq!(prompt.user_message_text(), LOOP_KICKOFF_MESSAGE);
+        assert!(prompt.instruction_text().starts_with(RalphLoopAgent::instruction()));
+    }
+
+    #[test]
+    fn test_developer_prompt_includes_project_glossary() {
+        let dir = tempfile::tempdir().unwrap();
+        let design = DesignDocument::new("shop", "A shop");
+        crate::glossary::Glossary::new(vec![crate::glossary::GlossaryTerm::new("order").with_avoid(&["purchase"])])
+            .save(dir.path())
+            .unwrap();
+
+        let prompt = AssembledPrompt::developer(Some(&design), dir.path(), "Done!");
+        assert_eq!(titles(&prompt.instruction), vec!["Instruction", "Project Context", "Glossary", "Completion Promise"]);
+        assert!(prompt.instruction_text().contains("- **order** (not: purchase)"));
+    }
+
+    #[test]
+    fn test_annotated_and_journal_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        let prompt = AssembledPrompt::architect("# PRD\nUser stories");
+        let annotated = prompt.annotated();
+        assert!(annotated.contains("[instruction] Instruction"));
+        assert!(annotated.contains("[user] PRD"));
+        assert!(annotated.find("[instruction]").unwrap() < annotated.find("[user]").unwrap());
+
+        assert!(AssembledPrompt::load_recorded(dir.path(), "architect").is_none());
+        prompt.record(dir.path()).unwrap();
+        let recorded = AssembledPrompt::load_recorded(dir.path(), "architect").unwrap();
+
+        let changed = AssembledPrompt::architect("# PRD\nMore stories");
+        let diff = diff_lines(&recorded, &changed.render());
+        assert!(diff.contains(&"- User stories".to_string()));
+        assert!(diff.contains(&"+ More stories".to_string()));
+        assert!(diff.iter().filter(|l| l.starts_with('-') || l.starts_with('+')).count() == 2);
+    }
+
+    #[test]
+    fn test_architect_replan_message_lists_failures_and_completed_work() {
+        let mut tasks = TaskList::new("calc", "rust");
+        let mut setup = Task::new("T-001", "Setup", "Set up", 1);
+        setup.complete(None);
+        tasks.add_task(setup);
+        tasks.add_task(Task::new("T-002", "Parser", "Parse", 2));
+
+        let failures = vec![
+            ("T-002".to_string(), "parser cannot see the lexer module".to_string()),
+            ("T-009".to_string(), "unknown task".to_string()),
+        ];
+        let message = architect_replan_message(
+            "# PRD\nStories",
+            "# System Design: calc\nOld",
+            &tasks,
+            &failures,
+        );
+        assert!(message.contains("- T-002 (Parser): parser cannot see the lexer module"));
+        assert!(message.contains("- T-009: unknown task"));
+        assert!(message.contains("## Completed Tasks"));
+        assert!(message.contains("- T-001: Setup"));
+        assert!(message.find("## Current Design").unwrap() < message.find("## PRD").unwrap());
+        assert!(message.contains("# System Design: calc\nOld"));
+    }
+}
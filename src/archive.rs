@@ -0,0 +1,391 @@
+//! Export a run as a single shareable archive.
+//!
+//! [`export_run_archive`] bundles everything a run leaves in the project
+//! directory into one `.tar.gz` with a `manifest.json` at its root:
+//!
+//! - the PRD, design document and task list
+//! - the architect's raw JSON reply ([`ARCHITECT_RESPONSE_FILE`])
+//! - the progress log (the per-iteration record of what the loop did)
+//! - the tool calls and results of the last run ([`TOOL_LOG_FILE`])
+//! - the event log, when one is written to a file (`event_log`)
+//! - the prompts recorded for each agent's last run ([`PROMPT_JOURNAL_DIR`])
+//! - the run report ([`LAST_RUN_FILE`]) and the interactive session history,
+//!   when present
+//!
+//! The manifest lists every entry with its size and SHA-256, and records the
+//! PRD hash separately so an archive can be matched to the requirements that
+//! produced it. Artifacts that were expected but not found are listed as
+//! `missing` rather than failing the export.
+//!
+//! Collecting artifacts and building the manifest is always available;
+//! writing the compressed archive requires the `archive` feature.
+
+use crate::agents::prompt::PROMPT_JOURNAL_DIR;
+use crate::agents::ARCHITECT_RESPONSE_FILE;
+use crate::interactive::session::DEFAULT_SESSION_PATH;
+use crate::last_run::LAST_RUN_FILE;
+use crate::models::{RalphConfig, TaskList};
+use crate::output::OutputSink;
+use crate::tool_log::TOOL_LOG_FILE;
+use crate::{RalphError, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// Name of the manifest at the archive root.
+pub const ARCHIVE_MANIFEST: &str = "manifest.json";
+
+/// Manifest format version, bumped on incompatible changes.
+pub const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+/// Kind of artifact stored in the archive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactKind {
+    Prd,
+    Design,
+    /// The architect's raw JSON reply
+    ArchitectResponse,
+    Tasks,
+    Progress,
+    ToolLog,
+    EventLog,
+    Prompt,
+    /// Task counts, statuses and warnings at the end of the last run
+    Report,
+    Session,
+}
+
+/// One file in the archive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveEntry {
+    /// Path inside the archive (relative to the project)
+    pub path: String,
+    pub kind: ArtifactKind,
+    /// Size in bytes
+    pub bytes: u64,
+    /// Hex-encoded SHA-256 of the contents
+    pub sha256: String,
+}
+
+/// Requirements the run was built from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrdRecord {
+    pub path: String,
+    pub sha256: String,
+}
+
+/// Self-describing index of an exported run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub format_version: u32,
+    /// Ralph version that wrote the archive
+    pub ralph_version: String,
+    /// When the archive was created (RFC 3339)
+    pub created_at: String,
+    /// Project name from the task list
+    #[serde(default)]
+    pub project: Option<String>,
+    /// PRD path and hash
+    #[serde(default)]
+    pub prd: Option<PrdRecord>,
+    pub entries: Vec<ArchiveEntry>,
+    /// Expected artifacts that were not found
+    #[serde(default)]
+    pub missing: Vec<String>,
+}
+
+/// Relative paths of a run's artifacts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunArtifacts {
+    pub prd_path: String,
+    pub design_path: String,
+    pub tasks_path: String,
+    pub progress_path: String,
+    /// Event log file, when events are written to one
+    pub event_log: Option<String>,
+}
+
+impl RunArtifacts {
+    /// Artifact paths configured for a run.
+    pub fn from_config(config: &RalphConfig) -> Self {
+        Self {
+            prd_path: config.prd_path.clone(),
+            design_path: config.design_path.clone(),
+            tasks_path: config.tasks_path.clone(),
+            progress_path: config.progress_path.clone(),
+            event_log: None,
+        }
+        .with_event_log(config.event_log.as_deref())
+    }
+
+    /// Include the event log for an `event_log` setting; events written to
+    /// stdout (`-`) leave nothing to archive.
+    pub fn with_event_log(mut self, event_log: Option<&str>) -> Self {
+        self.event_log = match OutputSink::from_event_log(event_log) {
+            OutputSink::Jsonl(path) => Some(path.to_string_lossy().into_owned()),
+            OutputSink::Console | OutputSink::JsonlStdout => None,
+        };
+        self
+    }
+}
+
+impl Default for RunArtifacts {
+    fn default() -> Self {
+        Self::from_config(&RalphConfig::default())
+    }
+}
+
+/// Hex-encoded SHA-256 of some bytes.
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Find a run's artifacts and describe them in a manifest.
+///
+/// Returns the manifest and the source file of each entry, in entry order.
+pub fn collect_run_artifacts(
+    project_path: &Path,
+    artifacts: &RunArtifacts,
+) -> Result<(ArchiveManifest, Vec<PathBuf>)> {
+    let mut manifest = ArchiveManifest {
+        format_version: ARCHIVE_FORMAT_VERSION,
+        ralph_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: crate::timefmt::now(),
+        project: TaskList::load(project_path.join(&artifacts.tasks_path))
+            .ok()
+            .map(|t| t.project),
+        prd: None,
+        entries: Vec::new(),
+        missing: Vec::new(),
+    };
+    let mut sources = Vec::new();
+
+    let mut add = |rel: String, kind: ArtifactKind, manifest: &mut ArchiveManifest| -> Result<bool> {
+        let source = project_path.join(&rel);
+        if !source.is_file() {
+            return Ok(false);
+        }
+        let content = std::fs::read(&source)
+            .map_err(|e| RalphError::file(source.display().to_string(), e.to_string()))?;
+        // Files outside the project (an absolute event log) are stored by name
+        let name = if Path::new(&rel).is_absolute() {
+            Path::new(&rel).file_name().map_or(rel.clone(), |n| n.to_string_lossy().into_owned())
+        } else {
+            rel
+        };
+        manifest.entries.push(ArchiveEntry {
+            path: name.replace('\\', "/"),
+            kind,
+            bytes: content.len() as u64,
+            sha256: sha256_hex(&content),
+        });
+        sources.push(source);
+        Ok(true)
+    };
+
+    let expected = [
+        (Some(artifacts.prd_path.as_str()), ArtifactKind::Prd),
+        (Some(artifacts.design_path.as_str()), ArtifactKind::Design),
+        (Some(ARCHITECT_RESPONSE_FILE), ArtifactKind::ArchitectResponse),
+        (Some(artifacts.tasks_path.as_str()), ArtifactKind::Tasks),
+        (Some(artifacts.progress_path.as_str()), ArtifactKind::Progress),
+        (Some(TOOL_LOG_FILE), ArtifactKind::ToolLog),
+        (artifacts.event_log.as_deref(), ArtifactKind::EventLog),
+    ];
+    for (rel, kind) in expected {
+        let Some(rel) = rel else { continue };
+        if !add(rel.to_string(), kind, &mut manifest)? {
+            manifest.missing.push(rel.to_string());
+        }
+    }
+
+    let journal = project_path.join(PROMPT_JOURNAL_DIR);
+    if let Ok(dir) = std::fs::read_dir(&journal) {
+        let mut names: Vec<String> = dir
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().is_file())
+            .filter_map(|e| e.file_name().to_str().map(String::from))
+            .collect();
+        names.sort();
+        for name in names {
+            add(format!("{}/{}", PROMPT_JOURNAL_DIR, name), ArtifactKind::Prompt, &mut manifest)?;
+        }
+    }
+
+    add(LAST_RUN_FILE.to_string(), ArtifactKind::Report, &mut manifest)?;
+    add(DEFAULT_SESSION_PATH.to_string(), ArtifactKind::Session, &mut manifest)?;
+
+    manifest.prd = manifest
+        .entries
+        .iter()
+        .find(|e| e.kind == ArtifactKind::Prd)
+        .map(|e| PrdRecord {
+            path: e.path.clone(),
+            sha256: e.sha256.clone(),
+        });
+
+    Ok((manifest, sources))
+}
+
+/// Bundle a run's artifacts (default paths, and the event log named by
+/// `RALPH_EVENT_LOG`) into a `.tar.gz` at `out`.
+#[cfg(feature = "archive")]
+pub fn export_run_archive(project_path: &Path, out: &Path) -> Result<ArchiveManifest> {
+    let event_log = std::env::var("RALPH_EVENT_LOG").ok();
+    let artifacts = RunArtifacts::default().with_event_log(event_log.as_deref());
+    export_artifacts(project_path, &artifacts, out)
+}
+
+/// Bundle a run's artifacts into a `.tar.gz` at `out`.
+#[cfg(feature = "archive")]
+pub fn export_artifacts(
+    project_path: &Path,
+    artifacts: &RunArtifacts,
+    out: &Path,
+) -> Result<ArchiveManifest> {
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+
+    let (manifest, sources) = collect_run_artifacts(project_path, artifacts)?;
+    let out_error = |e: std::io::Error| RalphError::file(out.display().to_string(), e.to_string());
+
+    if let Some(parent) = out.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(out_error)?;
+    }
+    let file = std::fs::File::create(out).map_err(out_error)?;
+    let mut tar = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+
+    let manifest_json = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| RalphError::Serialization(e.to_string()))?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(manifest_json.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(chrono::Utc::now().timestamp().max(0) as u64);
+    header.set_cksum();
+    tar.append_data(&mut header, ARCHIVE_MANIFEST, manifest_json.as_slice())
+        .map_err(out_error)?;
+
+    for (entry, source) in manifest.entries.iter().zip(&sources) {
+        tar.append_path_with_name(source, &entry.path).map_err(out_error)?;
+    }
+
+    tar.into_inner()
+        .and_then(|gz| gz.finish())
+        .map_err(out_error)?;
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write(root: &Path, rel: &str, content: &str) {
+        let path = root.join(rel);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_collect_records_hashes_and_missing() {
+        let dir = tempdir().unwrap();
+        write(dir.path(), "prd.md", "# Calculator");
+        write(dir.path(), "design.md", "# Design");
+        write(dir.path(), ".ralph/prompts/developer.md", "prompt");
+        write(dir.path(), ".ralph/prompts/architect.md", "prompt");
+
+        let (manifest, sources) = collect_run_artifacts(dir.path(), &RunArtifacts::default()).unwrap();
+
+        let paths: Vec<&str> = manifest.entries.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["prd.md", "design.md", ".ralph/prompts/architect.md", ".ralph/prompts/developer.md"]
+        );
+        assert_eq!(sources.len(), 4);
+        assert_eq!(
+            manifest.missing,
+            vec![ARCHITECT_RESPONSE_FILE, "tasks.json", "progress.json", TOOL_LOG_FILE]
+        );
+        assert_eq!(manifest.prd.as_ref().unwrap().sha256, sha256_hex(b"# Calculator"));
+        assert_eq!(manifest.entries[0].bytes, 12);
+        assert_eq!(manifest.format_version, ARCHIVE_FORMAT_VERSION);
+    }
+
+    #[test]
+    fn test_sha256_hex() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[cfg(feature = "archive")]
+    #[test]
+    fn test_export_round_trip() {
+        use flate2::read::GzDecoder;
+        use std::io::Read;
+
+        let dir = tempdir().unwrap();
+        write(dir.path(), "prd.md", "# Calculator");
+        write(dir.path(), ARCHITECT_RESPONSE_FILE, r##"{"design_markdown":"# Design"}"##);
+        write(dir.path(), "tasks.json", &serde_json::to_string(&TaskList::new("calc", "rust")).unwrap());
+        write(dir.path(), TOOL_LOG_FILE, r#"{"type":"tool_call","tool":"run_tests"}"#);
+        write(dir.path(), ".ralph/events.jsonl", r#"{"type":"phase"}"#);
+        write(dir.path(), LAST_RUN_FILE, "{}");
+        let out = dir.path().join("out/run.tar.gz");
+
+        let artifacts = RunArtifacts::default().with_event_log(Some(".ralph/events.jsonl"));
+        let manifest = export_artifacts(dir.path(), &artifacts, &out).unwrap();
+        assert_eq!(manifest.project.as_deref(), Some("calc"));
+        let kinds: Vec<ArtifactKind> = manifest.entries.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                ArtifactKind::Prd,
+                ArtifactKind::ArchitectResponse,
+                ArtifactKind::Tasks,
+                ArtifactKind::ToolLog,
+                ArtifactKind::EventLog,
+                ArtifactKind::Report
+            ]
+        );
+
+        let mut archive = tar::Archive::new(GzDecoder::new(std::fs::File::open(&out).unwrap()));
+        let mut names = Vec::new();
+        let mut stored_manifest = None;
+        for entry in archive.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let name = entry.path().unwrap().display().to_string();
+            if name == ARCHIVE_MANIFEST {
+                let mut json = String::new();
+                entry.read_to_string(&mut json).unwrap();
+                stored_manifest = Some(serde_json::from_str::<ArchiveManifest>(&json).unwrap());
+            }
+            names.push(name);
+        }
+        assert_eq!(
+            names,
+            vec![
+                ARCHIVE_MANIFEST,
+                "prd.md",
+                ARCHITECT_RESPONSE_FILE,
+                "tasks.json",
+                TOOL_LOG_FILE,
+                ".ralph/events.jsonl",
+                LAST_RUN_FILE
+            ]
+        );
+        assert_eq!(stored_manifest.unwrap(), manifest);
+    }
+    #[test]
+    fn test_event_log_on_stdout_is_not_archived() {
+        assert_eq!(RunArtifacts::default().with_event_log(Some("-")).event_log, None);
+        assert_eq!(RunArtifacts::default().with_event_log(None).event_log, None);
+        let config = RalphConfig { event_log: Some("logs/events.jsonl".to_string()), ..Default::default() };
+        assert_eq!(RunArtifacts::from_config(&config).event_log.as_deref(), Some("logs/events.jsonl"));
+    }
+}
@@ -0,0 +1,72 @@
+//! What this build of ralph can do, for embedders that check at runtime.
+//!
+//! Optional parts of ralph are behind cargo features, so a platform shipping
+//! its own build can ask [`capabilities`] whether, say, run archives were
+//! compiled in before offering them, instead of finding out from an error.
+//! `ralph --capabilities` prints the same report as JSON.
+
+use crate::models::SUPPORTED_PROVIDERS;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Optional cargo features and whether this build has them.
+const FEATURES: &[(&str, bool)] = &[
+    ("archive", cfg!(feature = "archive")),
+    ("dataset-upload", cfg!(feature = "dataset-upload")),
+];
+
+/// The version, model providers and optional features of this build.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Capabilities {
+    /// Crate version, e.g. "0.3.2"
+    pub version: String,
+    /// Values accepted for `RALPH_*_PROVIDER`
+    pub providers: Vec<String>,
+    /// Every optional feature, enabled or not
+    pub features: BTreeMap<String, bool>,
+}
+
+impl Capabilities {
+    /// Whether `provider` (case-insensitive) can be configured.
+    pub fn supports_provider(&self, provider: &str) -> bool {
+        self.providers.iter().any(|p| p.eq_ignore_ascii_case(provider))
+    }
+
+    /// Whether the cargo feature `feature` was compiled in; false for
+    /// features this version does not know.
+    pub fn has_feature(&self, feature: &str) -> bool {
+        self.features.get(feature).copied().unwrap_or(false)
+    }
+}
+
+/// Describe this build.
+pub fn capabilities() -> Capabilities {
+    Capabilities {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        providers: SUPPORTED_PROVIDERS.iter().map(|p| p.to_string()).collect(),
+        features: FEATURES
+            .iter()
+            .map(|(name, enabled)| (name.to_string(), *enabled))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capabilities_describe_this_build() {
+        let caps = capabilities();
+        assert_eq!(caps.version, env!("CARGO_PKG_VERSION"));
+        assert!(caps.supports_provider("Anthropic"));
+        assert!(!caps.supports_provider("bedrock"));
+        assert_eq!(caps.has_feature("archive"), cfg!(feature = "archive"));
+        assert!(!caps.has_feature("server"));
+
+        // Disabled features are listed too, so callers can tell "off" from "unknown"
+        let json = serde_json::to_value(&caps).unwrap();
+        assert!(json["features"]["dataset-upload"].is_boolean());
+        assert_eq!(serde_json::from_value::<Capabilities>(json).unwrap(), caps);
+    }
+}
@@ -0,0 +1,663 @@
+//! End-of-run cleanup advice: leftover scaffolding and dead code.
+//!
+//! Runs often leave vestigial files behind: a `utils.rs` from an abandoned
+//! attempt, TODOs the agent meant to come back to, a module created empty.
+//! [`CleanupReport::analyze`] finds them mechanically, without a model, among
+//! the files the run's tasks recorded creating or modifying:
+//!
+//! - **Unreferenced files**: source files no entry point reaches. The project
+//!   is walked as a reference graph per language, from its entry points
+//!   through module and import declarations ([`RustGraph`], [`ScriptGraph`]).
+//!   Files in other languages are never reported.
+//! - **Markers**: `TODO`, `FIXME`, `todo!` and `unimplemented!`, with their
+//!   locations.
+//! - **Empty files**: files with nothing but whitespace and comments
+//!   (conventionally empty files such as `__init__.py` are left alone).
+//!
+//! With `cleanup_task` enabled the findings become one low-priority
+//! [`CLEANUP_TASK_ID`] task for the next run (see [`record_cleanup_task`]).
+
+use crate::models::{Task, TaskComplexity, TaskList};
+use crate::scope::normalize_path;
+use crate::validation::SKIPPED_DIRS;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+/// ID of the task created from a cleanup report.
+pub const CLEANUP_TASK_ID: &str = "T-CLEANUP";
+
+/// Priority of the cleanup task (lowest).
+const CLEANUP_TASK_PRIORITY: u32 = 5;
+
+/// Markers reported in the run's files.
+const MARKERS: &[&str] = &["TODO", "FIXME", "unimplemented!", "todo!"];
+
+/// Longest marker line quoted in the report.
+const MARKER_TEXT_MAX_CHARS: usize = 80;
+
+/// Most markers listed in the cleanup task's description.
+const TASK_MARKER_LIMIT: usize = 20;
+
+/// File names that are empty by convention.
+const CONVENTIONALLY_EMPTY: &[&str] = &["__init__.py", "py.typed", ".gitkeep", ".keep"];
+
+/// A marker left in a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Marker {
+    /// File, relative to the project
+    pub path: String,
+    /// 1-based line number
+    pub line: usize,
+    /// Which marker (`TODO`, `FIXME`, ...)
+    pub marker: &'static str,
+    /// The line, trimmed and shortened
+    pub text: String,
+}
+
+impl std::fmt::Display for Marker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{} {}", self.path, self.line, self.text)
+    }
+}
+
+/// What a run left behind that may need cleaning up.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CleanupReport {
+    /// Source files no entry point reaches
+    pub unreferenced: Vec<String>,
+    /// TODO/FIXME/unimplemented markers
+    pub markers: Vec<Marker>,
+    /// Files with only whitespace and comments
+    pub empty: Vec<String>,
+}
+
+impl CleanupReport {
+    /// Analyze the run's `files` (relative to `project_dir`).
+    ///
+    /// Files that no longer exist are ignored.
+    pub fn analyze(project_dir: &Path, files: &[String]) -> Self {
+        let manifest: BTreeSet<String> = files
+            .iter()
+            .map(|f| normalize_path(f))
+            .filter(|f| project_dir.join(f).is_file())
+            .collect();
+        let project_files = project_files(project_dir);
+
+        let mut report = Self::default();
+        let graphs: [&dyn ReferenceGraph; 2] = [&RustGraph, &ScriptGraph];
+        for graph in graphs {
+            if !manifest.iter().any(|f| graph.handles(f)) {
+                continue;
+            }
+            let reachable = reachable(graph, project_dir, &project_files);
+            report.unreferenced.extend(
+                manifest
+                    .iter()
+                    .filter(|f| graph.handles(f) && !graph.is_root(f) && !reachable.contains(*f))
+                    .cloned(),
+            );
+        }
+        report.unreferenced.sort();
+
+        for file in &manifest {
+            let Ok(content) = fs::read_to_string(project_dir.join(file)) else {
+                continue;
+            };
+            report.markers.extend(find_markers(file, &content));
+            if is_empty_file(file, &content) {
+                report.empty.push(file.clone());
+            }
+        }
+        report
+    }
+
+    /// Analyze the files recorded by a task list's tasks.
+    pub fn for_tasks(project_dir: &Path, list: &TaskList) -> Self {
+        let files: Vec<String> = list
+            .get_all_tasks()
+            .into_iter()
+            .flat_map(|t| t.files_created.iter().chain(&t.files_modified))
+            .cloned()
+            .collect();
+        Self::analyze(project_dir, &files)
+    }
+
+    /// Whether nothing needs cleaning up.
+    pub fn is_empty(&self) -> bool {
+        self.unreferenced.is_empty() && self.markers.is_empty() && self.empty.is_empty()
+    }
+
+    /// One low-priority task resolving every finding, or `None` when there are none.
+    pub fn to_task(&self) -> Option<Task> {
+        if self.is_empty() {
+            return None;
+        }
+        let mut description =
+            String::from("Remove what earlier tasks left behind, keeping the project building:");
+        if !self.unreferenced.is_empty() {
+            description.push_str(&format!(
+                "\n- Delete or wire up files nothing references: {}",
+                self.unreferenced.join(", ")
+            ));
+        }
+        if !self.markers.is_empty() {
+            description.push_str("\n- Resolve or remove these markers:");
+            for marker in self.markers.iter().take(TASK_MARKER_LIMIT) {
+                description.push_str(&format!("\n  - {}", marker));
+            }
+            if self.markers.len() > TASK_MARKER_LIMIT {
+                description.push_str(&format!(
+                    "\n  - ... and {} more",
+                    self.markers.len() - TASK_MARKER_LIMIT
+                ));
+            }
+        }
+        if !self.empty.is_empty() {
+            description.push_str(&format!(
+                "\n- Fill in or delete empty files: {}",
+                self.empty.join(", ")
+            ));
+        }
+
+        let mut task = Task::new(
+            CLEANUP_TASK_ID,
+            "Clean up leftover scaffolding and dead code",
+            description,
+            CLEANUP_TASK_PRIORITY,
+        )
+        .with_complexity(TaskComplexity::Low)
+        .with_acceptance_criterion("No unreferenced files, markers or empty files listed above remain")
+        .with_acceptance_criterion("The project builds and its tests pass");
+        let files: BTreeSet<&String> = self
+            .unreferenced
+            .iter()
+            .chain(self.markers.iter().map(|m| &m.path))
+            .chain(&self.empty)
+            .collect();
+        task.files_modified = files.into_iter().cloned().collect();
+        Some(task)
+    }
+}
+
+/// Add or refresh the cleanup task for `report`, returning its ID.
+///
+/// A pending cleanup task is replaced with the current findings; one that
+/// already ran (completed, blocked or skipped) is left alone so a run never
+/// queues the same cleanup twice.
+pub fn record_cleanup_task(list: &mut TaskList, report: &CleanupReport) -> Option<String> {
+    let task = report.to_task()?;
+    match list.get_task_mut(CLEANUP_TASK_ID) {
+        Some(existing) if existing.is_pending() => {
+            existing.description = task.description;
+            existing.files_modified = task.files_modified;
+        }
+        Some(_) => return None,
+        None => list.add_task(task),
+    }
+    list.updated_at = Some(crate::timefmt::now());
+    Some(CLEANUP_TASK_ID.to_string())
+}
+
+/// How one language's files reference each other.
+pub trait ReferenceGraph {
+    /// Whether this graph covers the file.
+    fn handles(&self, path: &str) -> bool;
+
+    /// Whether the file is an entry point (reachable by definition).
+    fn is_root(&self, path: &str) -> bool;
+
+    /// Files `path` references, among `files` (all project files).
+    fn references(&self, path: &str, content: &str, files: &BTreeSet<String>) -> Vec<String>;
+}
+
+/// Rust: crate roots, then `mod` declarations.
+///
+/// `use` items only name modules that a `mod` declaration already brought
+/// in, so only `mod name;` (and its `#[path = "..."]` attribute) adds files.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RustGraph;
+
+impl ReferenceGraph for RustGraph {
+    fn handles(&self, path: &str) -> bool {
+        path.ends_with(".rs")
+    }
+
+    fn is_root(&self, path: &str) -> bool {
+        let parts: Vec<&str> = path.split('/').collect();
+        let name = parts[parts.len() - 1];
+        let parent = parts.len().checked_sub(2).map(|i| parts[i]);
+        let grandparent = parts.len().checked_sub(3).map(|i| parts[i]);
+        (parent == Some("src") && matches!(name, "main.rs" | "lib.rs"))
+            || name == "build.rs"
+            || matches!(parent, Some("tests" | "examples" | "benches"))
+            || (parent == Some("bin") && grandparent == Some("src"))
+            || (name == "main.rs" && grandparent == Some("bin"))
+    }
+
+    fn references(&self, path: &str, content: &str, files: &BTreeSet<String>) -> Vec<String> {
+        let dir = parent_dir(path);
+        let name = path.rsplit('/').next().unwrap_or(path);
+        // Submodules of `a/foo.rs` live in `a/foo/`; of roots and `mod.rs` in `a/`
+        let module_dir = if self.is_root(path) || name == "mod.rs" {
+            dir.to_string()
+        } else {
+            join(dir, name.trim_end_matches(".rs"))
+        };
+
+        let mut found = Vec::new();
+        let mut path_attr: Option<String> = None;
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+            if let Some(value) = line
+                .strip_prefix("#[path")
+                .and_then(|rest| rest.split('"').nth(1))
+            {
+                path_attr = Some(value.to_string());
+                continue;
+            }
+            if let Some(module) = mod_declaration(line) {
+                let candidates = match path_attr.take() {
+                    Some(custom) => vec![join(dir, &custom)],
+                    None => vec![
+                        join(&module_dir, &format!("{}.rs", module)),
+                        join(&module_dir, &format!("{}/mod.rs", module)),
+                    ],
+                };
+                found.extend(candidates.into_iter().filter_map(|c| resolve(&c, files)));
+            } else if !line.starts_with("#[") {
+                path_attr = None;
+            }
+        }
+        found
+    }
+}
+
+/// The module name of a `mod name;` declaration (any visibility).
+fn mod_declaration(line: &str) -> Option<&str> {
+    let rest = if line.starts_with("pub") {
+        let after = line.strip_prefix("pub")?;
+        let after = match after.trim_start().strip_prefix('(') {
+            Some(inner) => inner.split_once(')')?.1,
+            None => after,
+        };
+        after.trim_start()
+    } else {
+        line
+    };
+    let name = rest.strip_prefix("mod ")?.trim().strip_suffix(';')?.trim();
+    let name = name.strip_prefix("r#").unwrap_or(name);
+    (!name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_')).then_some(name)
+}
+
+/// JavaScript and TypeScript: entry points, then relative imports.
+///
+/// Entry points are test and config files, declaration files, files under
+/// `bin/` or `scripts/`, `index`/`main`/`app`/`server`/`cli` at the project
+/// root or directly under `src/`, and whatever `package.json` names in
+/// `main`, `module`, `types` or `bin`. References are the relative specifiers
+/// of `import ... from`, `export ... from`, side-effect `import`, dynamic
+/// `import()` and `require()`; package imports are ignored.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScriptGraph;
+
+/// Extensions [`ScriptGraph`] covers, in resolution order.
+const SCRIPT_EXTENSIONS: &[&str] = &["ts", "tsx", "mts", "cts", "js", "jsx", "mjs", "cjs"];
+
+/// Base names of script entry points.
+const SCRIPT_ENTRY_NAMES: &[&str] = &["index", "main", "app", "server", "cli"];
+
+impl ReferenceGraph for ScriptGraph {
+    fn handles(&self, path: &str) -> bool {
+        extension(path).is_some_and(|ext| SCRIPT_EXTENSIONS.contains(&ext))
+    }
+
+    fn is_root(&self, path: &str) -> bool {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        let dir = parent_dir(path);
+        let stem = name.split('.').next().unwrap_or(name);
+        let dirs: Vec<&str> = dir.split('/').collect();
+        name.contains(".test.")
+            || name.contains(".spec.")
+            || name.contains(".config.")
+            || name.ends_with(".d.ts")
+            || dirs.iter().any(|d| matches!(*d, "__tests__" | "test" | "tests" | "bin" | "scripts"))
+            || (matches!(dir, "" | "src") && SCRIPT_ENTRY_NAMES.contains(&stem))
+    }
+
+    fn references(&self, path: &str, content: &str, files: &BTreeSet<String>) -> Vec<String> {
+        let dir = parent_dir(path);
+        import_specifiers(content)
+            .into_iter()
+            .filter(|spec| spec.starts_with("./") || spec.starts_with("../"))
+            .filter_map(|spec| resolve_script(&join(dir, spec), files))
+            .collect()
+    }
+}
+
+/// Specifiers of the imports, re-exports and requires in a script.
+fn import_specifiers(content: &str) -> Vec<&str> {
+    let mut specs = Vec::new();
+    for keyword in ["from", "import", "require"] {
+        let mut rest = content;
+        while let Some(at) = rest.find(keyword) {
+            let before = rest[..at].chars().next_back();
+            let after = &rest[at + keyword.len()..];
+            rest = after;
+            if before.is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '.' || c == '$') {
+                continue;
+            }
+            let after = after.trim_start();
+            let after = after.strip_prefix('(').map_or(after, str::trim_start);
+            let Some(quote) = after.chars().next().filter(|c| matches!(c, '\'' | '"' | '`')) else {
+                continue;
+            };
+            if let Some(end) = after[1..].find(quote) {
+                specs.push(&after[1..1 + end]);
+            }
+        }
+    }
+    specs
+}
+
+/// The script file an import of `base` loads.
+///
+/// TypeScript imports often name the compiled `.js` file; the `.ts` source
+/// is tried for those too.
+fn resolve_script(base: &str, files: &BTreeSet<String>) -> Option<String> {
+    let base = normalize(base)?;
+    if files.contains(&base) {
+        return Some(base);
+    }
+    if let Some(stem) = [".js", ".jsx", ".mjs", ".cjs"]
+        .iter()
+        .find_map(|ext| base.strip_suffix(ext))
+    {
+        for ext in ["ts", "tsx", "mts", "cts"] {
+            if let Some(found) = resolve(&format!("{}.{}", stem, ext), files) {
+                return Some(found);
+            }
+        }
+    }
+    SCRIPT_EXTENSIONS
+        .iter()
+        .map(|ext| format!("{}.{}", base, ext))
+        .chain(SCRIPT_EXTENSIONS.iter().map(|ext| join(&base, &format!("index.{}", ext))))
+        .find_map(|candidate| resolve(&candidate, files))
+}
+
+/// Files reachable from `graph`'s entry points.
+fn reachable(graph: &dyn ReferenceGraph, project_dir: &Path, files: &BTreeSet<String>) -> BTreeSet<String> {
+    let mut stack: Vec<String> = files
+        .iter()
+        .filter(|f| graph.handles(f) && graph.is_root(f))
+        .cloned()
+        .collect();
+    stack.extend(package_entry_points(project_dir, files));
+
+    let mut seen = BTreeSet::new();
+    while let Some(file) = stack.pop() {
+        if !seen.insert(file.clone()) {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(project_dir.join(&file)) else {
+            continue;
+        };
+        if graph.handles(&file) {
+            stack.extend(graph.references(&file, &content, files));
+        }
+    }
+    seen
+}
+
+/// Files `package.json` names as entry points.
+fn package_entry_points(project_dir: &Path, files: &BTreeSet<String>) -> Vec<String> {
+    let Ok(content) = fs::read_to_string(project_dir.join("package.json")) else {
+        return Vec::new();
+    };
+    let Ok(package) = serde_json::from_str::<serde_json::Value>(&content) else {
+        return Vec::new();
+    };
+    let mut entries = Vec::new();
+    for field in ["main", "module", "types", "bin"] {
+        match &package[field] {
+            serde_json::Value::String(path) => entries.push(path.clone()),
+            serde_json::Value::Object(map) => {
+                entries.extend(map.values().filter_map(|v| v.as_str().map(String::from)))
+            }
+            _ => {}
+        }
+    }
+    entries
+        .iter()
+        .filter_map(|entry| resolve_script(&normalize_path(entry), files))
+        .collect()
+}
+
+/// Every file in the project, relative and with forward slashes.
+fn project_files(project_dir: &Path) -> BTreeSet<String> {
+    fn walk(root: &Path, dir: &Path, files: &mut BTreeSet<String>) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            if path.is_dir() {
+                if !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_str()) {
+                    walk(root, &path, files);
+                }
+            } else if let Ok(rel) = path.strip_prefix(root) {
+                files.insert(rel.to_string_lossy().replace('\\', "/"));
+            }
+        }
+    }
+    let mut files = BTreeSet::new();
+    walk(project_dir, project_dir, &mut files);
+    files
+}
+
+/// Markers in one file.
+fn find_markers(path: &str, content: &str) -> Vec<Marker> {
+    let mut markers = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let found = MARKERS.iter().find(|marker| {
+            line.match_indices(**marker).any(|(at, _)| {
+                let before = line[..at].chars().next_back();
+                let after = line[at + marker.len()..].chars().next();
+                let word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+                !word(before) && (marker.ends_with('!') || !word(after))
+            })
+        });
+        if let Some(marker) = found {
+            let text = line.trim();
+            let text = if text.chars().count() > MARKER_TEXT_MAX_CHARS {
+                format!("{}...", text.chars().take(MARKER_TEXT_MAX_CHARS).collect::<String>())
+            } else {
+                text.to_string()
+            };
+            markers.push(Marker { path: path.to_string(), line: i + 1, marker: *marker, text });
+        }
+    }
+    markers
+}
+
+/// Whether a file has nothing but whitespace and comments.
+fn is_empty_file(path: &str, content: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    if CONVENTIONALLY_EMPTY.contains(&name) {
+        return false;
+    }
+    let comments: &[&str] = match extension(path) {
+        Some("rs" | "go" | "java" | "c" | "h" | "cpp" | "cs" | "kt" | "swift") => &["//", "/*", "*"],
+        Some(ext) if SCRIPT_EXTENSIONS.contains(&ext) => &["//", "/*", "*"],
+        Some("py" | "sh" | "rb" | "toml" | "yaml" | "yml") => &["#"],
+        _ => &[],
+    };
+    content
+        .lines()
+        .map(str::trim)
+        .all(|line| line.is_empty() || comments.iter().any(|c| line.starts_with(c)))
+}
+
+fn extension(path: &str) -> Option<&str> {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    name.rsplit_once('.').map(|(_, ext)| ext)
+}
+
+fn parent_dir(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(dir, _)| dir)
+}
+
+fn join(dir: &str, rel: &str) -> String {
+    if dir.is_empty() {
+        rel.to_string()
+    } else {
+        format!("{}/{}", dir, rel)
+    }
+}
+
+/// Resolve `.` and `..` segments; `None` when the path leaves the project.
+fn normalize(path: &str) -> Option<String> {
+    let mut parts: Vec<&str> = Vec::new();
+    for part in path.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            part => parts.push(part),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+/// `candidate`, normalized, when it is a project file.
+fn resolve(candidate: &str, files: &BTreeSet<String>) -> Option<String> {
+    normalize(candidate).filter(|path| files.contains(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/cleanup").join(name)
+    }
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_rust_graph_follows_mod_declarations() {
+        let dir = fixture("rust");
+        let files = strings(&[
+            "src/main.rs",
+            "src/lib.rs",
+            "src/parser.rs",
+            "src/parser/lexer.rs",
+            "src/eval/mod.rs",
+            "src/eval/ops.rs",
+            "src/platform_unix.rs",
+            "src/utils.rs",
+            "src/old/helpers.rs",
+            "tests/common/mod.rs",
+            "tests/cli.rs",
+        ]);
+        let report = CleanupReport::analyze(&dir, &files);
+        assert_eq!(report.unreferenced, vec!["src/old/helpers.rs", "src/utils.rs"]);
+    }
+
+    #[test]
+    fn test_script_graph_follows_relative_imports() {
+        let dir = fixture("ts");
+        let files = strings(&[
+            "src/index.ts",
+            "src/routes/users.ts",
+            "src/routes/index.ts",
+            "src/db.ts",
+            "src/lazy.ts",
+            "src/legacy.js",
+            "src/unused.ts",
+            "bin/serve.js",
+            "src/app.test.ts",
+        ]);
+        let report = CleanupReport::analyze(&dir, &files);
+        assert_eq!(report.unreferenced, vec!["src/legacy.js", "src/unused.ts"]);
+    }
+
+    #[test]
+    fn test_markers_and_empty_files() {
+        let dir = fixture("rust");
+        let report = CleanupReport::analyze(
+            &dir,
+            &strings(&["src/eval/ops.rs", "src/utils.rs", "src/parser/lexer.rs", "missing.rs"]),
+        );
+        let found: Vec<(&str, usize, &str)> = report
+            .markers
+            .iter()
+            .map(|m| (m.path.as_str(), m.line, m.marker))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("src/eval/ops.rs", 1, "TODO"),
+                ("src/eval/ops.rs", 4, "unimplemented!"),
+                ("src/eval/ops.rs", 8, "FIXME"),
+            ]
+        );
+        assert_eq!(report.markers[0].text, "// TODO: support exponents");
+        assert_eq!(report.empty, vec!["src/utils.rs"]);
+    }
+
+    #[test]
+    fn test_marker_words_must_stand_alone() {
+        let markers = find_markers("a.rs", "let todos = 1;\n// TODOS\nlet x = todo!();\n// TODO later");
+        let lines: Vec<usize> = markers.iter().map(|m| m.line).collect();
+        assert_eq!(lines, vec![3, 4]);
+        assert!(!is_empty_file("a/__init__.py", ""));
+        assert!(is_empty_file("a.py", "# nothing yet\n\n"));
+        assert!(!is_empty_file("a.py", "x = 1\n"));
+        assert!(!is_empty_file("Cargo.toml", "[package]\n"));
+    }
+
+    #[test]
+    fn test_mod_declarations() {
+        assert_eq!(mod_declaration("mod parser;"), Some("parser"));
+        assert_eq!(mod_declaration("pub(crate) mod eval;"), Some("eval"));
+        assert_eq!(mod_declaration("pub mod r#type;"), Some("type"));
+        assert_eq!(mod_declaration("mod tests {"), None);
+        assert_eq!(mod_declaration("model;"), None);
+    }
+
+    #[test]
+    fn test_cleanup_task_is_recorded_once() {
+        let report = CleanupReport {
+            unreferenced: strings(&["src/utils.rs"]),
+            markers: vec![Marker {
+                path: "src/main.rs".into(),
+                line: 3,
+                marker: "TODO",
+                text: "// TODO: flags".into(),
+            }],
+            empty: Vec::new(),
+        };
+        let mut list = TaskList::new("calc", "rust");
+        assert_eq!(record_cleanup_task(&mut list, &report).as_deref(), Some(CLEANUP_TASK_ID));
+        let task = list.get_task(CLEANUP_TASK_ID).unwrap();
+        assert_eq!(task.priority, CLEANUP_TASK_PRIORITY);
+        assert!(task.description.contains("src/utils.rs"));
+        assert!(task.description.contains("src/main.rs:3 // TODO: flags"));
+        assert_eq!(task.files_modified, vec!["src/main.rs", "src/utils.rs"]);
+
+        // Refreshed while pending, never re-queued once it ran
+        assert!(record_cleanup_task(&mut list, &report).is_some());
+        assert_eq!(list.get_all_tasks().len(), 1);
+        list.complete_task(CLEANUP_TASK_ID, None).unwrap();
+        assert!(record_cleanup_task(&mut list, &report).is_none());
+        assert!(record_cleanup_task(&mut TaskList::new("x", "rust"), &CleanupReport::default()).is_none());
+    }
+}
@@ -0,0 +1,662 @@
+//! Console capability detection.
+//!
+//! Decides once per process whether output may use color, Unicode symbols and
+//! in-place redraws, so legacy consoles and redirected output get readable text
+//! instead of raw escape sequences or mojibake:
+//!
+//! - On Windows, virtual terminal (VT) processing is switched on when the
+//!   console supports it. A console without it, or one that refuses to switch
+//!   it on (locked-down CI consoles), is treated as legacy: no color, no
+//!   in-place redraws and ASCII symbols.
+//! - When stdout is not a terminal (piped, redirected to a file, CI logs),
+//!   progress is printed as periodic plain lines and color is off. When stdout
+//!   carries JSON events instead, the same checks are made on stderr, where
+//!   the human-readable output goes ([`ConsoleStream`]).
+//! - `NO_COLOR` and `TERM=dumb` turn color off; `RALPH_COLOR` and
+//!   `RALPH_UNICODE` force either choice.
+//!
+//! The decision itself is [`ConsoleCapabilities::decide`], a pure function of
+//! [`ConsoleEnv`]; only [`ConsoleEnv::detect`] looks at the real process.
+//! [`init`] and [`capabilities`] set the `colored` override, and [`palette`]
+//! sets a provisional automatic one before either has run, so text painted
+//! before the configuration is loaded never falls back to `colored`'s own
+//! detection, which does not know about VT processing. `ralph config` prints the decision and why
+//! ([`ConsoleCapabilities::explain`]).
+//!
+//! Which colors are used when color is on is a separate choice: a [`Palette`]
+//! maps the output's semantic styles (success, failure, warning, dim, accent,
+//! info, heading) to colors, with presets for dark and light terminals,
+//! colorblind readers and no color at all (`RALPH_PALETTE`). No state is told
+//! apart by color alone: every style is paired with its own glyph or word.
+
+use crate::models::{ConsoleSetting, PalettePreset};
+use colored::{Color, ColoredString, Colorize};
+use std::io::IsTerminal;
+use std::sync::OnceLock;
+
+/// The stream human-readable output is written to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConsoleStream {
+    /// Standard output (the usual case)
+    #[default]
+    Stdout,
+    /// Standard error, when stdout carries JSON events
+    Stderr,
+}
+
+impl ConsoleStream {
+    /// Whether the stream is a terminal.
+    pub fn is_terminal(self) -> bool {
+        match self {
+            Self::Stdout => std::io::stdout().is_terminal(),
+            Self::Stderr => std::io::stderr().is_terminal(),
+        }
+    }
+}
+
+/// What is known about the console before deciding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConsoleEnv {
+    /// The output stream is a terminal
+    pub is_tty: bool,
+    /// The terminal understands ANSI/VT escape sequences
+    pub vt_supported: bool,
+    /// `NO_COLOR` is set to a non-empty value
+    pub no_color: bool,
+    /// `TERM=dumb`
+    pub dumb_term: bool,
+    /// Color override from configuration
+    pub color: ConsoleSetting,
+    /// Unicode override from configuration
+    pub unicode: ConsoleSetting,
+}
+
+/// What output may use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConsoleCapabilities {
+    /// ANSI colors
+    pub color: bool,
+    /// Unicode symbols and block-drawing bars
+    pub unicode: bool,
+    /// Carriage-return and cursor-movement redraws of progress lines
+    pub redraw: bool,
+}
+
+impl ConsoleCapabilities {
+    /// Everything on: a modern terminal.
+    pub const FULL: Self = Self {
+        color: true,
+        unicode: true,
+        redraw: true,
+    };
+
+    /// Everything off: safe anywhere.
+    pub const PLAIN: Self = Self {
+        color: false,
+        unicode: false,
+        redraw: false,
+    };
+
+    /// Decide capabilities from the environment.
+    ///
+    /// Overrides win; otherwise color and redraws need a terminal with VT
+    /// support, and Unicode is only avoided on a legacy console (a terminal
+    /// without VT support). Redirected output keeps Unicode, since files and
+    /// pipes carry UTF-8 fine.
+    pub fn decide(env: &ConsoleEnv) -> Self {
+        let ansi_terminal = env.is_tty && env.vt_supported && !env.dumb_term;
+        let color = match env.color {
+            ConsoleSetting::Always => true,
+            ConsoleSetting::Never => false,
+            ConsoleSetting::Auto => ansi_terminal && !env.no_color,
+        };
+        let unicode = match env.unicode {
+            ConsoleSetting::Always => true,
+            ConsoleSetting::Never => false,
+            ConsoleSetting::Auto => !env.is_tty || env.vt_supported,
+        };
+        Self {
+            color,
+            unicode,
+            redraw: ansi_terminal,
+        }
+    }
+
+    /// One line saying what was decided for `env` and why, e.g.
+    /// "color off (the console rejected ANSI escape sequences), ASCII symbols,
+    /// plain progress lines".
+    pub fn explain(&self, env: &ConsoleEnv) -> String {
+        let color = if self.color {
+            "color on".to_string()
+        } else {
+            let reason = if env.color == ConsoleSetting::Never {
+                "RALPH_COLOR=never"
+            } else if !env.is_tty {
+                "output is not a terminal"
+            } else if env.dumb_term {
+                "TERM=dumb"
+            } else if !env.vt_supported {
+                "the console rejected ANSI escape sequences"
+            } else {
+                "NO_COLOR is set"
+            };
+            format!("color off ({})", reason)
+        };
+        format!(
+            "{}, {} symbols, {}",
+            color,
+            if self.unicode { "Unicode" } else { "ASCII" },
+            if self.redraw { "live progress" } else { "plain progress lines" }
+        )
+    }
+
+    /// Symbols to draw with.
+    pub fn glyphs(&self) -> &'static Glyphs {
+        if self.unicode {
+            &Glyphs::UNICODE
+        } else {
+            &Glyphs::ASCII
+        }
+    }
+}
+
+/// Symbols used by the output, in Unicode and ASCII variants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Glyphs {
+    /// Completed step
+    pub success: &'static str,
+    /// Failed step
+    pub failure: &'static str,
+    /// Warning
+    pub warning: &'static str,
+    /// Informational note
+    pub info: &'static str,
+    /// Next item or task start
+    pub arrow: &'static str,
+    /// Tool response
+    pub back_arrow: &'static str,
+    /// Status line
+    pub bullet: &'static str,
+    /// List item and horizontal rules
+    pub dash: &'static str,
+    /// Iteration marker
+    pub pending: &'static str,
+    /// Phase header
+    pub phase: &'static str,
+    /// Diff summary
+    pub plus_minus: &'static str,
+    /// Column separator
+    pub separator: &'static str,
+    /// Truncated text
+    pub ellipsis: &'static str,
+    /// Filled part of a progress bar
+    pub bar_filled: &'static str,
+    /// Empty part of a progress bar
+    pub bar_empty: &'static str,
+    /// Tool call (verbose)
+    pub tool: &'static str,
+    /// Model text (verbose)
+    pub thought: &'static str,
+    /// Debug line
+    pub debug: &'static str,
+    /// Spinner frames for the task in progress
+    pub spinner: &'static [&'static str],
+}
+
+impl Glyphs {
+    /// Symbols for terminals that render Unicode.
+    pub const UNICODE: Self = Self {
+        success: "✓",
+        failure: "✗",
+        warning: "⚠",
+        info: "ℹ",
+        arrow: "→",
+        back_arrow: "←",
+        bullet: "•",
+        dash: "─",
+        pending: "○",
+        phase: "▶",
+        plus_minus: "±",
+        separator: "│",
+        ellipsis: "…",
+        bar_filled: "█",
+        bar_empty: "░",
+        tool: "🔧",
+        thought: "💭",
+        debug: "🐛",
+        spinner: &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"],
+    };
+
+    /// Plain ASCII fallbacks for legacy consoles.
+    pub const ASCII: Self = Self {
+        success: "+",
+        failure: "x",
+        warning: "!",
+        info: "i",
+        arrow: "->",
+        back_arrow: "<-",
+        bullet: "*",
+        dash: "-",
+        pending: "o",
+        phase: ">",
+        plus_minus: "+/-",
+        separator: "|",
+        ellipsis: "...",
+        bar_filled: "#",
+        bar_empty: "-",
+        tool: "$",
+        thought: "~",
+        debug: "#",
+        spinner: &["|", "/", "-", "\\"],
+    };
+}
+
+/// How one semantic style is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StyleSpec {
+    /// Foreground color (`None` = the terminal's own)
+    pub color: Option<Color>,
+    /// Bold
+    pub bold: bool,
+    /// Faint, relative to the terminal's foreground
+    pub dimmed: bool,
+}
+
+impl StyleSpec {
+    const fn color(color: Color) -> Self {
+        Self { color: Some(color), bold: false, dimmed: false }
+    }
+
+    const fn bold(color: Option<Color>) -> Self {
+        Self { color, bold: true, dimmed: false }
+    }
+
+    const PLAIN: Self = Self { color: None, bold: false, dimmed: false };
+
+    const FAINT: Self = Self { color: None, bold: false, dimmed: true };
+
+    /// Apply this style to `text`.
+    pub fn paint(&self, text: impl AsRef<str>) -> ColoredString {
+        let mut painted = match self.color {
+            Some(color) => text.as_ref().color(color),
+            None => text.as_ref().normal(),
+        };
+        if self.bold {
+            painted = painted.bold();
+        }
+        if self.dimmed {
+            painted = painted.dimmed();
+        }
+        painted
+    }
+}
+
+/// Semantic styles of the output, resolved to colors.
+///
+/// Output code asks for a meaning (`palette.success(..)`), never a color, so
+/// switching presets restyles everything at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    /// Completed steps, passed tests, additions
+    pub success: StyleSpec,
+    /// Failed steps, errors, removals
+    pub failure: StyleSpec,
+    /// Warnings and things needing attention
+    pub warning: StyleSpec,
+    /// Secondary detail: bullets, rules, tool output
+    pub dim: StyleSpec,
+    /// Task IDs, phase markers, JSON keys
+    pub accent: StyleSpec,
+    /// Tool calls, informational notes
+    pub info: StyleSpec,
+    /// Headings and names
+    pub heading: StyleSpec,
+}
+
+impl Palette {
+    /// Green, red and yellow on a dark background.
+    pub const DEFAULT: Self = Self {
+        success: StyleSpec::color(Color::BrightGreen),
+        failure: StyleSpec::color(Color::BrightRed),
+        warning: StyleSpec::color(Color::BrightYellow),
+        dim: StyleSpec::color(Color::BrightBlack),
+        accent: StyleSpec::color(Color::Cyan),
+        info: StyleSpec::color(Color::BrightBlue),
+        heading: StyleSpec::bold(Some(Color::BrightWhite)),
+    };
+
+    /// For light backgrounds: no bright white or yellow, and dimming by
+    /// faintness instead of gray.
+    pub const LIGHT: Self = Self {
+        success: StyleSpec::color(Color::Green),
+        failure: StyleSpec::color(Color::Red),
+        warning: StyleSpec::color(Color::Magenta),
+        dim: StyleSpec::FAINT,
+        accent: StyleSpec::color(Color::Blue),
+        info: StyleSpec::color(Color::Blue),
+        heading: StyleSpec::bold(None),
+    };
+
+    /// Okabe-Ito colors, which stay distinct with red-green and blue-yellow
+    /// color vision deficiencies: blue for success, orange for failure.
+    pub const COLORBLIND: Self = Self {
+        success: StyleSpec::color(Color::TrueColor { r: 86, g: 180, b: 233 }),
+        failure: StyleSpec {
+            color: Some(Color::TrueColor { r: 230, g: 159, b: 0 }),
+            bold: true,
+            dimmed: false,
+        },
+        warning: StyleSpec::color(Color::TrueColor { r: 240, g: 228, b: 66 }),
+        dim: StyleSpec::color(Color::BrightBlack),
+        accent: StyleSpec::color(Color::TrueColor { r: 204, g: 121, b: 167 }),
+        info: StyleSpec::color(Color::White),
+        heading: StyleSpec::bold(Some(Color::BrightWhite)),
+    };
+
+    /// No color; failures, warnings and headings are bold.
+    pub const MONO: Self = Self {
+        success: StyleSpec::PLAIN,
+        failure: StyleSpec::bold(None),
+        warning: StyleSpec::bold(None),
+        dim: StyleSpec::PLAIN,
+        accent: StyleSpec::PLAIN,
+        info: StyleSpec::PLAIN,
+        heading: StyleSpec::bold(None),
+    };
+
+    /// The palette of a preset.
+    pub fn preset(preset: PalettePreset) -> &'static Self {
+        match preset {
+            PalettePreset::Default => &Self::DEFAULT,
+            PalettePreset::Light => &Self::LIGHT,
+            PalettePreset::Colorblind => &Self::COLORBLIND,
+            PalettePreset::Mono => &Self::MONO,
+        }
+    }
+
+    /// Style text as a success.
+    pub fn success(&self, text: impl AsRef<str>) -> ColoredString {
+        self.success.paint(text)
+    }
+
+    /// Style text as a failure.
+    pub fn failure(&self, text: impl AsRef<str>) -> ColoredString {
+        self.failure.paint(text)
+    }
+
+    /// Style text as a warning.
+    pub fn warning(&self, text: impl AsRef<str>) -> ColoredString {
+        self.warning.paint(text)
+    }
+
+    /// Style text as secondary detail.
+    pub fn dim(&self, text: impl AsRef<str>) -> ColoredString {
+        self.dim.paint(text)
+    }
+
+    /// Style text as an identifier to pick out.
+    pub fn accent(&self, text: impl AsRef<str>) -> ColoredString {
+        self.accent.paint(text)
+    }
+
+    /// Style text as informational.
+    pub fn info(&self, text: impl AsRef<str>) -> ColoredString {
+        self.info.paint(text)
+    }
+
+    /// Style text as a heading.
+    pub fn heading(&self, text: impl AsRef<str>) -> ColoredString {
+        self.heading.paint(text)
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+static CAPABILITIES: OnceLock<ConsoleCapabilities> = OnceLock::new();
+
+/// Detect and apply console capabilities for this process.
+///
+/// The first call wins: it enables VT processing on Windows, decides the
+/// capabilities and applies the global color override, so escape sequences
+/// are only written when the decision allows them. Later calls return the
+/// same result and leave the override alone.
+pub fn init(color: ConsoleSetting, unicode: ConsoleSetting) -> ConsoleCapabilities {
+    init_on(ConsoleStream::Stdout, color, unicode)
+}
+
+/// Like [`init`], for human-readable output written to `stream`.
+pub fn init_on(
+    stream: ConsoleStream,
+    color: ConsoleSetting,
+    unicode: ConsoleSetting,
+) -> ConsoleCapabilities {
+    *CAPABILITIES.get_or_init(|| {
+        let capabilities = ConsoleCapabilities::decide(&ConsoleEnv::detect_on(stream, color, unicode));
+        colored::control::set_override(capabilities.color);
+        capabilities
+    })
+}
+
+/// Capabilities of this process's console.
+///
+/// Detects with automatic settings if [`init`] has not been called, and then
+/// sets the global color override the same way.
+pub fn capabilities() -> ConsoleCapabilities {
+    *CAPABILITIES.get_or_init(|| {
+        let capabilities = detect(ConsoleSetting::Auto, ConsoleSetting::Auto);
+        colored::control::set_override(capabilities.color);
+        capabilities
+    })
+}
+
+static PALETTE: OnceLock<&'static Palette> = OnceLock::new();
+
+/// Choose the palette for this process's output.
+///
+/// The first call wins, like [`init`]; later calls return the same palette.
+pub fn init_palette(preset: PalettePreset) -> &'static Palette {
+    PALETTE.get_or_init(|| Palette::preset(preset))
+}
+
+/// Palette of this process's output.
+///
+/// Reads `RALPH_PALETTE` if [`init_palette`] has not been called, so messages
+/// printed before the configuration loads use the same palette, colored only
+/// where automatic detection allows it until [`init`] applies the settings.
+pub fn palette() -> &'static Palette {
+    static PROVISIONAL: OnceLock<()> = OnceLock::new();
+    PROVISIONAL.get_or_init(|| {
+        if CAPABILITIES.get().is_none() {
+            colored::control::set_override(detect(ConsoleSetting::Auto, ConsoleSetting::Auto).color);
+        }
+    });
+    PALETTE.get_or_init(|| {
+        let preset = std::env::var("RALPH_PALETTE")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or_default();
+        Palette::preset(preset)
+    })
+}
+
+impl ConsoleEnv {
+    /// Inspect stdout and the environment. On Windows this switches VT
+    /// processing on; a console that refuses counts as unsupported.
+    pub fn detect(color: ConsoleSetting, unicode: ConsoleSetting) -> Self {
+        Self::detect_on(ConsoleStream::Stdout, color, unicode)
+    }
+
+    /// Inspect `stream` and the environment, like [`ConsoleEnv::detect`].
+    pub fn detect_on(stream: ConsoleStream, color: ConsoleSetting, unicode: ConsoleSetting) -> Self {
+        let is_tty = stream.is_terminal();
+        Self {
+            is_tty,
+            vt_supported: is_tty && enable_vt_processing(),
+            no_color: std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()),
+            dumb_term: std::env::var("TERM").is_ok_and(|t| t == "dumb"),
+            color,
+            unicode,
+        }
+    }
+}
+
+/// Inspect stdout and the environment and decide capabilities.
+pub fn detect(color: ConsoleSetting, unicode: ConsoleSetting) -> ConsoleCapabilities {
+    ConsoleCapabilities::decide(&ConsoleEnv::detect(color, unicode))
+}
+
+/// Turn on VT processing for the console; false on legacy Windows consoles
+/// and on consoles where switching it on fails.
+#[cfg(windows)]
+fn enable_vt_processing() -> bool {
+    colored::control::set_virtual_terminal(true).is_ok()
+}
+
+/// Unix terminals understand escape sequences natively.
+#[cfg(not(windows))]
+fn enable_vt_processing() -> bool {
+    true
+}
+
+/// Serializes tests that set the global color override.
+///
+/// Makes the one-time console decisions first, so [`capabilities`] and
+/// [`palette`] cannot reset the override while the guard is held.
+#[cfg(test)]
+pub(crate) fn lock_color_override() -> std::sync::MutexGuard<'static, ()> {
+    static COLOR_OVERRIDE: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    capabilities();
+    palette();
+    COLOR_OVERRIDE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(is_tty: bool, vt_supported: bool, no_color: bool) -> ConsoleEnv {
+        ConsoleEnv {
+            is_tty,
+            vt_supported,
+            no_color,
+            dumb_term: false,
+            color: ConsoleSetting::Auto,
+            unicode: ConsoleSetting::Auto,
+        }
+    }
+
+    fn caps(color: bool, unicode: bool, redraw: bool) -> ConsoleCapabilities {
+        ConsoleCapabilities { color, unicode, redraw }
+    }
+
+    #[test]
+    fn test_automatic_decision_matrix() {
+        // (tty, vt, NO_COLOR) -> (color, unicode, redraw)
+        let cases = [
+            ((true, true, false), caps(true, true, true)),
+            ((true, true, true), caps(false, true, true)),
+            // Legacy console: no VT processing
+            ((true, false, false), caps(false, false, false)),
+            ((true, false, true), caps(false, false, false)),
+            // Redirected output: plain lines, Unicode kept
+            ((false, true, false), caps(false, true, false)),
+            ((false, false, false), caps(false, true, false)),
+            ((false, true, true), caps(false, true, false)),
+            ((false, false, true), caps(false, true, false)),
+        ];
+        for ((tty, vt, no_color), expected) in cases {
+            assert_eq!(
+                ConsoleCapabilities::decide(&env(tty, vt, no_color)),
+                expected,
+                "tty={} vt={} no_color={}",
+                tty,
+                vt,
+                no_color
+            );
+        }
+    }
+
+    #[test]
+    fn test_overrides_win_over_detection() {
+        let legacy = ConsoleEnv {
+            color: ConsoleSetting::Always,
+            unicode: ConsoleSetting::Always,
+            ..env(true, false, true)
+        };
+        // Forcing color and Unicode does not make redraws safe
+        assert_eq!(ConsoleCapabilities::decide(&legacy), caps(true, true, false));
+
+        let modern = ConsoleEnv {
+            color: ConsoleSetting::Never,
+            unicode: ConsoleSetting::Never,
+            ..env(true, true, false)
+        };
+        assert_eq!(ConsoleCapabilities::decide(&modern), caps(false, false, true));
+
+        let piped = ConsoleEnv {
+            color: ConsoleSetting::Always,
+            ..env(false, false, false)
+        };
+        assert_eq!(ConsoleCapabilities::decide(&piped), caps(true, true, false));
+    }
+
+    #[test]
+    fn test_dumb_terminal_gets_no_color_or_redraws() {
+        let dumb = ConsoleEnv {
+            dumb_term: true,
+            ..env(true, true, false)
+        };
+        assert_eq!(ConsoleCapabilities::decide(&dumb), caps(false, true, false));
+    }
+
+    #[test]
+    fn test_explain_names_why_color_is_off() {
+        // A Windows console that refused VT processing
+        let legacy = env(true, false, false);
+        assert_eq!(
+            ConsoleCapabilities::decide(&legacy).explain(&legacy),
+            "color off (the console rejected ANSI escape sequences), ASCII symbols, plain progress lines"
+        );
+
+        let piped = env(false, true, false);
+        assert_eq!(
+            ConsoleCapabilities::decide(&piped).explain(&piped),
+            "color off (output is not a terminal), Unicode symbols, plain progress lines"
+        );
+        let modern = env(true, true, false);
+        assert_eq!(ConsoleCapabilities::decide(&modern).explain(&modern), "color on, Unicode symbols, live progress");
+        let no_color = env(true, true, true);
+        assert!(ConsoleCapabilities::decide(&no_color).explain(&no_color).contains("NO_COLOR"));
+    }
+
+    #[test]
+    fn test_ascii_glyphs_are_ascii() {
+        let ascii = ConsoleCapabilities::PLAIN.glyphs();
+        let all = [
+            ascii.success,
+            ascii.failure,
+            ascii.warning,
+            ascii.info,
+            ascii.arrow,
+            ascii.back_arrow,
+            ascii.bullet,
+            ascii.dash,
+            ascii.pending,
+            ascii.phase,
+            ascii.plus_minus,
+            ascii.separator,
+            ascii.ellipsis,
+            ascii.bar_filled,
+            ascii.bar_empty,
+            ascii.tool,
+            ascii.thought,
+            ascii.debug,
+        ];
+        assert!(all.iter().chain(ascii.spinner).all(|g| g.is_ascii() && !g.is_empty()));
+        assert_eq!(ConsoleCapabilities::FULL.glyphs().success, "✓");
+    }
+}
@@ -0,0 +1,302 @@
+//! Recovery from context-window overflows.
+//!
+//! A long task accumulates tool exchanges until the conversation no longer
+//! fits the model's context window, and the provider rejects the call with a
+//! length error. [`ContextTrimModel`] wraps a model so that such a call is
+//! retried once with a trimmed conversation before it fails: the oldest tool
+//! exchanges are dropped, while the kickoff message (the task), the system
+//! instruction (the design summary), text turns and the most recent
+//! exchanges (the latest errors) are kept.
+//!
+//! This is not a general retry: any other failure, and an overflow on the
+//! retried call, is returned unchanged.
+
+use crate::output::RalphOutput;
+use adk_rust::{Content, Llm, LlmRequest, LlmResponseStream, Part, Result as AdkResult};
+use async_trait::async_trait;
+use futures::StreamExt;
+use std::sync::Arc;
+
+/// Most recent tool exchanges kept when the context is trimmed.
+pub const KEEP_RECENT_EXCHANGES: usize = 4;
+
+/// Whether `message` is a provider's "the request does not fit the context window" error.
+pub fn is_context_overflow(message: &str) -> bool {
+    let message = message.to_lowercase();
+    message.contains("context_length_exceeded")
+        || message.contains("maximum context length")
+        || message.contains("prompt is too long")
+        || (message.contains("input token count") && message.contains("exceeds the maximum"))
+        || (message.contains("context window") && message.contains("exceed"))
+}
+
+/// A conversation with its oldest tool exchanges removed.
+#[derive(Debug, Clone)]
+pub struct TrimmedContext {
+    /// The contents to send instead
+    pub contents: Vec<Content>,
+    /// How many tool exchanges were removed
+    pub dropped: usize,
+}
+
+/// `contents` without the tool exchanges older than the last `keep_recent`,
+/// or `None` when there is nothing to drop.
+///
+/// An exchange is a model turn with function calls together with the turn
+/// that answers it, so a call is never kept without its response or the other
+/// way round. The first content (the kickoff message) and text-only turns are
+/// always kept; a note about the removal is appended to the first content.
+pub fn trim_contents(contents: &[Content], keep_recent: usize) -> Option<TrimmedContext> {
+    let (first, rest) = contents.split_first()?;
+
+    // Split the conversation after the kickoff into exchanges and text turns
+    let mut units = Vec::new();
+    let mut i = 0;
+    while i < rest.len() {
+        let is_call = has_function_call(&rest[i]);
+        let end = if is_call && i + 1 < rest.len() && rest[i + 1].role != "model" { i + 2 } else { i + 1 };
+        units.push((i..end, is_call));
+        i = end;
+    }
+
+    let exchanges = units.iter().filter(|(_, is_call)| *is_call).count();
+    let dropped = exchanges.saturating_sub(keep_recent);
+    if dropped == 0 {
+        return None;
+    }
+
+    let mut kickoff = first.clone();
+    kickoff.parts.push(Part::Text {
+        text: format!(
+            "[ralph] {} earlier tool exchange(s) were removed to fit the model's context window. \
+             Re-read any file you need before changing it.",
+            dropped
+        ),
+    });
+    let mut trimmed = vec![kickoff];
+    let mut seen = 0;
+    for (range, is_call) in units {
+        if is_call {
+            seen += 1;
+            if seen <= dropped {
+                continue;
+            }
+        }
+        trimmed.extend_from_slice(&rest[range]);
+    }
+    Some(TrimmedContext { contents: trimmed, dropped })
+}
+
+fn has_function_call(content: &Content) -> bool {
+    content.parts.iter().any(|part| matches!(part, Part::FunctionCall { .. }))
+}
+
+/// A model whose calls are retried once with a trimmed context when they
+/// overflow the context window.
+pub struct ContextTrimModel {
+    inner: Arc<dyn Llm>,
+    output: Option<RalphOutput>,
+}
+
+impl ContextTrimModel {
+    /// Retry overflowing calls to `inner`, reporting each retry on `output`.
+    pub fn new(inner: Arc<dyn Llm>, output: Option<RalphOutput>) -> Self {
+        Self { inner, output }
+    }
+
+    /// `model` with overflow recovery, or unchanged when `enabled` is false.
+    pub fn wrap(model: Arc<dyn Llm>, enabled: bool, output: Option<RalphOutput>) -> Arc<dyn Llm> {
+        if enabled { Arc::new(Self::new(model, output)) } else { model }
+    }
+}
+
+impl std::fmt::Debug for ContextTrimModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContextTrimModel").field("model", &self.inner.name()).finish()
+    }
+}
+
+#[async_trait]
+impl Llm for ContextTrimModel {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    async fn generate_content(
+        &self,
+        request: LlmRequest,
+        stream: bool,
+    ) -> AdkResult<LlmResponseStream> {
+        let mut retry = request.clone();
+        // Providers reject an oversized request either outright or as the first streamed item
+        let failure = match self.inner.generate_content(request, stream).await {
+            Ok(mut responses) => match responses.next().await {
+                Some(Err(e)) if is_context_overflow(&e.to_string()) => e,
+                Some(first) => {
+                    return Ok(Box::pin(futures::stream::once(async move { first }).chain(responses)));
+                }
+                None => return Ok(responses),
+            },
+            Err(e) if is_context_overflow(&e.to_string()) => e,
+            Err(e) => return Err(e),
+        };
+
+        let Some(trimmed) = trim_contents(&retry.contents, KEEP_RECENT_EXCHANGES) else {
+            return Err(failure);
+        };
+        tracing::warn!(
+            model = self.inner.name(),
+            dropped = trimmed.dropped,
+            error = %failure,
+            "Context window exceeded; retrying with a trimmed context"
+        );
+        if let Some(output) = &self.output {
+            output.clear_line();
+            output.status(&format!(
+                "Context window exceeded: trimmed context and retried ({} earlier tool exchange(s) dropped)",
+                trimmed.dropped
+            ));
+        }
+        retry.contents = trimmed.contents;
+        self.inner.generate_content(retry, stream).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adk_rust::{AdkError, LlmResponse};
+    use std::sync::Mutex;
+
+    fn text(role: &str, text: &str) -> Content {
+        Content::new(role).with_text(text)
+    }
+
+    fn call(name: &str) -> Content {
+        Content {
+            role: "model".to_string(),
+            parts: vec![Part::FunctionCall { name: name.to_string(), args: serde_json::json!({}), id: None }],
+        }
+    }
+
+    /// Kickoff, then `exchanges` call/response pairs named `call-N`/`result-N`.
+    fn conversation(exchanges: usize) -> Vec<Content> {
+        let mut contents = vec![text("user", "Implement T-001")];
+        for n in 1..=exchanges {
+            contents.push(call(&format!("call-{}", n)));
+            contents.push(text("function", &format!("result-{}", n)));
+        }
+        contents
+    }
+
+    fn labels(contents: &[Content]) -> Vec<String> {
+        contents
+            .iter()
+            .skip(1)
+            .map(|c| match &c.parts[0] {
+                Part::FunctionCall { name, .. } => name.clone(),
+                Part::Text { text } => text.clone(),
+                _ => String::new(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_context_overflow_messages_are_recognised() {
+        for message in [
+            r#"HTTP 400: {"error":{"message":"This model's maximum context length is 128000 tokens.","code":"context_length_exceeded"}}"#,
+            r#"HTTP 400: {"type":"error","error":{"type":"invalid_request_error","message":"prompt is too long: 210403 tokens > 200000 maximum"}}"#,
+            "The input token count (1200000) exceeds the maximum number of tokens allowed (1048576).",
+            "Model error: request exceeds the model's context window",
+        ] {
+            assert!(is_context_overflow(message), "{}", message);
+        }
+        assert!(!is_context_overflow("max_tokens too large"));
+        assert!(!is_context_overflow("HTTP 529: overloaded"));
+    }
+
+    #[test]
+    fn test_oldest_exchanges_are_dropped_in_pairs() {
+        let mut contents = conversation(6);
+        contents.insert(3, text("model", "Reading the parser first"));
+
+        let trimmed = trim_contents(&contents, 2).unwrap();
+        assert_eq!(trimmed.dropped, 4);
+        assert_eq!(
+            labels(&trimmed.contents),
+            ["Reading the parser first", "call-5", "result-5", "call-6", "result-6"]
+        );
+        let kickoff = &trimmed.contents[0];
+        assert_eq!(kickoff.parts.len(), 2);
+        assert!(matches!(&kickoff.parts[1], Part::Text { text } if text.contains("4 earlier tool exchange(s)")));
+        // The original is untouched
+        assert_eq!(contents[0].parts.len(), 1);
+    }
+
+    #[test]
+    fn test_nothing_to_trim() {
+        assert!(trim_contents(&[], 2).is_none());
+        assert!(trim_contents(&conversation(2), 2).is_none());
+        // A trailing call still waiting for its response counts as the latest exchange
+        let mut contents = conversation(2);
+        contents.push(call("call-3"));
+        let trimmed = trim_contents(&contents, 2).unwrap();
+        assert_eq!(labels(&trimmed.contents), ["call-2", "result-2", "call-3"]);
+    }
+
+    /// Rejects requests with more than `limit` contents as too long.
+    struct SmallWindowModel {
+        limit: usize,
+        sizes: Mutex<Vec<usize>>,
+    }
+
+    #[async_trait]
+    impl Llm for SmallWindowModel {
+        fn name(&self) -> &str {
+            "small-window"
+        }
+
+        async fn generate_content(
+            &self,
+            request: LlmRequest,
+            _stream: bool,
+        ) -> AdkResult<LlmResponseStream> {
+            self.sizes.lock().unwrap().push(request.contents.len());
+            if request.contents.len() > self.limit {
+                return Err(AdkError::Model("prompt is too long: 250000 tokens > 200000 maximum".into()));
+            }
+            let response = LlmResponse::new(Content::new("model").with_text("done"));
+            Ok(Box::pin(futures::stream::once(async move { Ok(response) })))
+        }
+    }
+
+    async fn call_with(model: &dyn Llm, contents: Vec<Content>) -> AdkResult<Vec<AdkResult<LlmResponse>>> {
+        let stream = model.generate_content(LlmRequest::new("small-window", contents), true).await?;
+        Ok(stream.collect().await)
+    }
+
+    #[tokio::test]
+    async fn test_overflow_is_retried_once_with_a_trimmed_context() {
+        let inner = Arc::new(SmallWindowModel { limit: 12, sizes: Mutex::new(Vec::new()) });
+        let model = ContextTrimModel::wrap(inner.clone(), true, None);
+
+        let items = call_with(model.as_ref(), conversation(8)).await.unwrap();
+        assert_eq!(items.len(), 1);
+        assert!(items[0].is_ok());
+        assert_eq!(*inner.sizes.lock().unwrap(), [17, 1 + 2 * KEEP_RECENT_EXCHANGES]);
+    }
+
+    #[tokio::test]
+    async fn test_overflow_is_returned_when_trimming_does_not_help() {
+        let inner = Arc::new(SmallWindowModel { limit: 3, sizes: Mutex::new(Vec::new()) });
+        let model = ContextTrimModel::wrap(inner.clone(), true, None);
+        let err = call_with(model.as_ref(), conversation(8)).await.unwrap_err();
+        assert!(is_context_overflow(&err.to_string()));
+        assert_eq!(inner.sizes.lock().unwrap().len(), 2);
+
+        let inner = Arc::new(SmallWindowModel { limit: 12, sizes: Mutex::new(Vec::new()) });
+        let model = ContextTrimModel::wrap(inner.clone(), false, None);
+        assert!(call_with(model.as_ref(), conversation(8)).await.is_err());
+        assert_eq!(inner.sizes.lock().unwrap().len(), 1);
+    }
+}
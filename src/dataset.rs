@@ -0,0 +1,471 @@
+//! Fine-tuning dataset export from successful runs.
+//!
+//! Off by default. With `RALPH_DATASET_EXPORT` (or `--export-dataset <dir>`)
+//! set, the loop records each task's model turns while it runs. At the end of
+//! the run, every task that ended Done becomes one JSONL line of a dataset
+//! shard: the task as the prompt, the model's text, tool calls and tool
+//! results as the response, the final contents of the task's files, and the
+//! run's provenance.
+//!
+//! ```json
+//! {"format":"ralph-dataset/v1","run_id":"...","project":"calc","language":"rust",
+//!  "task_id":"T-001","prompt":"...","messages":[{"role":"assistant","content":"..."},
+//!  {"role":"tool_call","name":"file","content":{...}},
+//!  {"role":"tool","name":"file","content":{...}}],
+//!  "files":[{"path":"src/main.rs","content":"..."}],"provenance":{...}}
+//! ```
+//!
+//! Secrets are scrubbed from every string with the same machinery as command
+//! output ([`ExecEnv::scrub`]): values of the execution environment and of
+//! secret-looking process variables (API keys, tokens) become `***`.
+//!
+//! The shard goes to a local directory, or to an `s3://` / `gs://` bucket when
+//! built with the `dataset-upload` feature. Uploads use the `aws` / `gcloud`
+//! CLIs, so credentials come from their standard chains (environment, profile,
+//! instance or workload identity). Failed uploads are retried and reported;
+//! they never fail the run.
+
+use crate::exec_env::{ExecEnv, is_secret_key};
+use crate::models::{Task, TaskList, TaskStatus};
+use crate::provenance::Provenance;
+use crate::{RalphError, Result};
+use adk_rust::Part;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Format tag written on every shard line.
+pub const DATASET_FORMAT: &str = "ralph-dataset/v1";
+
+/// Largest file included in a record; bigger files are left out.
+pub const MAX_FILE_BYTES: u64 = 64 * 1024;
+
+/// Upload attempts before giving up on a bucket.
+#[cfg_attr(not(feature = "dataset-upload"), allow(dead_code))]
+const UPLOAD_ATTEMPTS: u32 = 3;
+
+/// Where dataset shards are written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DatasetTarget {
+    /// A local directory
+    Dir(PathBuf),
+    /// An `s3://bucket/prefix` URL
+    S3(String),
+    /// A `gs://bucket/prefix` URL
+    Gcs(String),
+}
+
+impl DatasetTarget {
+    /// Parse a bucket URL, or treat anything else as a directory.
+    pub fn parse(target: &str) -> Self {
+        let target = target.trim();
+        if target.starts_with("s3://") {
+            DatasetTarget::S3(target.trim_end_matches('/').to_string())
+        } else if target.starts_with("gs://") {
+            DatasetTarget::Gcs(target.trim_end_matches('/').to_string())
+        } else {
+            DatasetTarget::Dir(PathBuf::from(target))
+        }
+    }
+}
+
+impl std::fmt::Display for DatasetTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DatasetTarget::Dir(dir) => write!(f, "{}", dir.display()),
+            DatasetTarget::S3(url) | DatasetTarget::Gcs(url) => write!(f, "{}", url),
+        }
+    }
+}
+
+/// One turn of a task's conversation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatasetMessage {
+    /// `assistant` (model text), `tool_call` (model call) or `tool` (tool result)
+    pub role: String,
+    /// Tool name for calls and results
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Text, call arguments or tool response
+    pub content: Value,
+}
+
+/// Final contents of a file the task created or modified.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatasetFile {
+    pub path: String,
+    pub content: String,
+}
+
+/// One shard line: a completed task with its conversation and files.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatasetRecord {
+    pub format: String,
+    pub run_id: String,
+    pub project: String,
+    pub language: String,
+    pub task_id: String,
+    /// The task as it was given to the model
+    pub prompt: String,
+    pub messages: Vec<DatasetMessage>,
+    pub files: Vec<DatasetFile>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+}
+
+/// Collects each task's model turns while the loop runs.
+#[derive(Debug, Default)]
+pub struct DatasetRecorder {
+    turns: BTreeMap<String, Vec<DatasetMessage>>,
+}
+
+impl DatasetRecorder {
+    /// Create an empty recorder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a content part produced while `task_id` was in progress.
+    pub fn record(&mut self, task_id: &str, part: &Part) {
+        match part {
+            Part::Text { text } if !text.trim().is_empty() => {
+                self.push(task_id, "assistant", None, Value::String(text.clone()));
+            }
+            Part::FunctionCall { name, args, .. } => {
+                self.push(task_id, "tool_call", Some(name), args.clone());
+            }
+            Part::FunctionResponse { function_response, .. } => {
+                self.record_response(task_id, &function_response.name, &function_response.response);
+            }
+            _ => {}
+        }
+    }
+
+    /// Record a tool's response for `task_id`.
+    pub fn record_response(&mut self, task_id: &str, tool: &str, response: &Value) {
+        self.push(task_id, "tool", Some(tool), response.clone());
+    }
+
+    fn push(&mut self, task_id: &str, role: &str, name: Option<&str>, content: Value) {
+        self.turns.entry(task_id.to_string()).or_default().push(DatasetMessage {
+            role: role.to_string(),
+            name: name.map(String::from),
+            content,
+        });
+    }
+
+    /// Turns recorded for `task_id`.
+    pub fn messages(&self, task_id: &str) -> &[DatasetMessage] {
+        self.turns.get(task_id).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Build the shard records: one per task that ended Done with recorded turns.
+    pub fn records(
+        &self,
+        list: &TaskList,
+        project_path: &Path,
+        redactor: &Redactor,
+    ) -> Vec<DatasetRecord> {
+        let run_id = list
+            .meta
+            .as_ref()
+            .map(|meta| meta.run_id.clone())
+            .unwrap_or_else(|| Provenance::new().run_id);
+
+        list.get_all_tasks()
+            .into_iter()
+            .filter(|task| task.status == TaskStatus::Completed)
+            .filter(|task| !self.messages(&task.id).is_empty())
+            .map(|task| {
+                let mut record = DatasetRecord {
+                    format: DATASET_FORMAT.to_string(),
+                    run_id: run_id.clone(),
+                    project: list.project.clone(),
+                    language: list.language.clone(),
+                    task_id: task.id.clone(),
+                    prompt: task_prompt(task),
+                    messages: self.messages(&task.id).to_vec(),
+                    files: task_files(task, project_path),
+                    provenance: list.meta.clone(),
+                };
+                redactor.redact_record(&mut record);
+                record
+            })
+            .collect()
+    }
+}
+
+/// Scrubs secrets from dataset records.
+#[derive(Debug)]
+pub struct Redactor {
+    secrets: ExecEnv,
+}
+
+impl Redactor {
+    /// Scrub the execution environment's secrets, secret-looking process
+    /// variables, and API keys read from a command or file.
+    pub fn new(exec_env: &ExecEnv) -> Self {
+        let mut vars = exec_env.vars();
+        vars.extend(std::env::vars().filter(|(key, _)| is_secret_key(key)));
+        for (i, key) in crate::models::config::resolved_secrets().into_iter().enumerate() {
+            vars.insert(format!("RALPH_RESOLVED_API_KEY_{}", i), key);
+        }
+        Self {
+            secrets: ExecEnv::new(vars),
+        }
+    }
+
+    /// Scrub exactly the given variables' secret values.
+    pub fn with_vars(vars: BTreeMap<String, String>) -> Self {
+        Self {
+            secrets: ExecEnv::new(vars),
+        }
+    }
+
+    /// Replace secret values in `text`.
+    pub fn scrub(&self, text: &str) -> String {
+        self.secrets.scrub(text)
+    }
+
+    /// Scrub every string in a record.
+    pub fn redact_record(&self, record: &mut DatasetRecord) {
+        record.prompt = self.scrub(&record.prompt);
+        for message in &mut record.messages {
+            self.redact_value(&mut message.content);
+        }
+        for file in &mut record.files {
+            file.content = self.scrub(&file.content);
+        }
+    }
+
+    fn redact_value(&self, value: &mut Value) {
+        match value {
+            Value::String(text) => *text = self.scrub(text),
+            Value::Array(items) => items.iter_mut().for_each(|item| self.redact_value(item)),
+            Value::Object(map) => map.values_mut().for_each(|item| self.redact_value(item)),
+            _ => {}
+        }
+    }
+}
+
+/// Render records as a JSONL shard.
+pub fn shard_jsonl(records: &[DatasetRecord]) -> Result<String> {
+    let mut shard = String::new();
+    for record in records {
+        shard.push_str(&serde_json::to_string(record)?);
+        shard.push('\n');
+    }
+    Ok(shard)
+}
+
+/// Write a shard named `<run_id>.jsonl` to `target` and return where it went.
+pub async fn export_shard(target: &DatasetTarget, run_id: &str, shard: &str) -> Result<String> {
+    let name = format!("{}.jsonl", run_id);
+    match target {
+        DatasetTarget::Dir(dir) => {
+            let path = dir.join(&name);
+            std::fs::create_dir_all(dir)
+                .and_then(|_| std::fs::write(&path, shard))
+                .map_err(|e| RalphError::file(path.display().to_string(), e.to_string()))?;
+            Ok(path.display().to_string())
+        }
+        DatasetTarget::S3(url) | DatasetTarget::Gcs(url) => {
+            upload(target, &format!("{}/{}", url, name), shard).await
+        }
+    }
+}
+
+/// Upload a shard with the provider's CLI, retrying with backoff.
+#[cfg(feature = "dataset-upload")]
+async fn upload(target: &DatasetTarget, url: &str, shard: &str) -> Result<String> {
+    let file = tempfile_path();
+    std::fs::write(&file, shard)
+        .map_err(|e| RalphError::file(file.display().to_string(), e.to_string()))?;
+    let (program, service) = match target {
+        DatasetTarget::S3(_) => ("aws", "s3"),
+        _ => ("gcloud", "storage"),
+    };
+    let args = [service.to_string(), "cp".to_string(), file.display().to_string(), url.to_string()];
+
+    let mut last_error = String::new();
+    for attempt in 1..=UPLOAD_ATTEMPTS {
+        match tokio::process::Command::new(program).args(&args).output().await {
+            Ok(output) if output.status.success() => {
+                let _ = std::fs::remove_file(&file);
+                return Ok(url.to_string());
+            }
+            Ok(output) => last_error = String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            Err(e) => last_error = format!("could not run {}: {}", program, e),
+        }
+        tracing::warn!(attempt, error = %last_error, "Dataset upload failed");
+        if attempt < UPLOAD_ATTEMPTS {
+            tokio::time::sleep(std::time::Duration::from_secs(1 << attempt)).await;
+        }
+    }
+    let _ = std::fs::remove_file(&file);
+    Err(RalphError::Tool {
+        tool: program.to_string(),
+        message: format!(
+            "upload to {} failed after {} attempts: {}",
+            url, UPLOAD_ATTEMPTS, last_error
+        ),
+    })
+}
+
+#[cfg(feature = "dataset-upload")]
+fn tempfile_path() -> PathBuf {
+    std::env::temp_dir().join(format!("ralph-dataset-{}.jsonl", uuid::Uuid::new_v4()))
+}
+
+#[cfg(not(feature = "dataset-upload"))]
+async fn upload(_target: &DatasetTarget, url: &str, _shard: &str) -> Result<String> {
+    Err(RalphError::config(format!(
+        "cannot upload to {}: ralph was built without dataset upload support; \
+         rebuild with `--features dataset-upload` or use --export-dataset <dir>",
+        url
+    )))
+}
+
+/// The task as the model was asked to implement it.
+fn task_prompt(task: &Task) -> String {
+    let mut prompt = format!("{}: {}\n\n{}", task.id, task.title, task.description);
+    if !task.acceptance_criteria.is_empty() {
+        prompt.push_str("\n\nAcceptance criteria:");
+        for criterion in &task.acceptance_criteria {
+            prompt.push_str("\n- ");
+            prompt.push_str(criterion);
+        }
+    }
+    prompt
+}
+
+/// Final text contents of the task's files; missing, large and binary files are skipped.
+fn task_files(task: &Task, project_path: &Path) -> Vec<DatasetFile> {
+    let mut paths: Vec<&String> = task.files_created.iter().chain(&task.files_modified).collect();
+    paths.sort();
+    paths.dedup();
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let full = project_path.join(path);
+            if std::fs::metadata(&full).ok()?.len() > MAX_FILE_BYTES {
+                return None;
+            }
+            let bytes = std::fs::read(&full).ok()?;
+            if crate::diff::is_binary(&bytes) {
+                return None;
+            }
+            Some(DatasetFile {
+                path: path.clone(),
+                content: String::from_utf8(bytes).ok()?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn plan() -> TaskList {
+        let mut list = TaskList::new("calc", "rust");
+        let mut done = Task::new("T-001", "Add parser", "Parse expressions", 1);
+        done.status = TaskStatus::Completed;
+        done.files_created = vec!["src/parser.rs".to_string(), "logo.png".to_string()];
+        done.acceptance_criteria = vec!["WHEN given 1+2, THE parser SHALL return 3".to_string()];
+        list.add_task(done);
+        let mut blocked = Task::new("T-002", "Add REPL", "Interactive loop", 2);
+        blocked.status = TaskStatus::Blocked;
+        list.add_task(blocked);
+        list
+    }
+
+    #[test]
+    fn test_target_parse() {
+        assert_eq!(
+            DatasetTarget::parse("s3://runs/ralph/"),
+            DatasetTarget::S3("s3://runs/ralph".to_string())
+        );
+        assert_eq!(DatasetTarget::parse("gs://runs"), DatasetTarget::Gcs("gs://runs".to_string()));
+        assert_eq!(
+            DatasetTarget::parse("./datasets"),
+            DatasetTarget::Dir(PathBuf::from("./datasets"))
+        );
+    }
+
+    #[test]
+    fn test_shard_has_only_done_tasks_and_is_redacted() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        let source = "const KEY: &str = \"sk-live-1234\";\n";
+        std::fs::write(dir.path().join("src/parser.rs"), source).unwrap();
+        std::fs::write(dir.path().join("logo.png"), [0x89, b'P', b'N', b'G', 0, 0]).unwrap();
+
+        let mut recorder = DatasetRecorder::new();
+        let text = "Writing the parser with sk-live-1234".to_string();
+        recorder.record("T-001", &Part::Text { text });
+        recorder.record(
+            "T-001",
+            &Part::FunctionCall {
+                name: "file".to_string(),
+                args: json!({"operation": "write", "content": ["sk-live-1234"]}),
+                id: None,
+            },
+        );
+        recorder.record_response("T-001", "file", &json!({"success": true}));
+        recorder.record("T-002", &Part::Text { text: "Starting the REPL".to_string() });
+
+        let redactor = Redactor::with_vars(BTreeMap::from([(
+            "PAYMENT_API_KEY".to_string(),
+            "sk-live-1234".to_string(),
+        )]));
+        let records = recorder.records(&plan(), dir.path(), &redactor);
+        assert_eq!(records.len(), 1);
+
+        let shard = shard_jsonl(&records).unwrap();
+        assert_eq!(shard.lines().count(), 1);
+        assert!(!shard.contains("sk-live-1234"));
+
+        let record: DatasetRecord = serde_json::from_str(shard.lines().next().unwrap()).unwrap();
+        assert_eq!(record.format, DATASET_FORMAT);
+        assert_eq!(record.task_id, "T-001");
+        assert!(record.prompt.starts_with("T-001: Add parser\n\nParse expressions"));
+        assert!(record.prompt.ends_with("- WHEN given 1+2, THE parser SHALL return 3"));
+        let roles: Vec<&str> = record.messages.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(roles, vec!["assistant", "tool_call", "tool"]);
+        assert_eq!(record.messages[0].content, json!("Writing the parser with ***"));
+        assert_eq!(record.messages[1].content["content"][0], "***");
+        assert_eq!(
+            record.files,
+            vec![DatasetFile {
+                path: "src/parser.rs".to_string(),
+                content: "const KEY: &str = \"***\";\n".to_string(),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_to_directory() {
+        let dir = TempDir::new().unwrap();
+        let target = DatasetTarget::Dir(dir.path().join("datasets"));
+        let written = export_shard(&target, "run-1", "{}\n").await.unwrap();
+        assert!(written.ends_with("run-1.jsonl"));
+        assert_eq!(std::fs::read_to_string(written).unwrap(), "{}\n");
+    }
+
+    /// Needs `aws` with credentials and RALPH_TEST_DATASET_BUCKET=s3://...
+    #[cfg(feature = "dataset-upload")]
+    #[tokio::test]
+    #[ignore]
+    async fn test_upload_to_bucket() {
+        let bucket =
+            std::env::var("RALPH_TEST_DATASET_BUCKET").expect("RALPH_TEST_DATASET_BUCKET");
+        let target = DatasetTarget::parse(&bucket);
+        let run_id = format!("ralph-test-{}", uuid::Uuid::new_v4());
+        let url = export_shard(&target, &run_id, "{}\n").await.unwrap();
+        assert!(url.ends_with(&format!("{}.jsonl", run_id)));
+    }
+}
@@ -0,0 +1,116 @@
+//! Offline demo: the full pipeline on an embedded hello-world recording.
+//!
+//! `ralph demo` runs the PRD, Architect and Ralph Loop agents against the
+//! [`replay`](crate::replay) provider, so it needs no API key and no network.
+//! The agents, tools and checks are the real ones: the loop writes the
+//! project, runs `cargo test`, commits, and completes the task through the
+//! completion gates. The result is a buildable Rust project with its
+//! prd.md, design.md, tasks.json and progress.json.
+
+use crate::agents::CompletionStatus;
+use crate::models::{DebugLevel, ModelConfig, RalphConfig, VerificationCadence};
+use crate::orchestrator::RalphOrchestrator;
+use crate::{RalphError, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Project description given to the (replayed) PRD agent.
+pub const DEMO_PROMPT: &str = include_str!("../demo/prompt.txt");
+
+/// What the demo produced.
+#[derive(Debug, Clone)]
+pub struct DemoReport {
+    /// Generated project directory
+    pub project_dir: PathBuf,
+    /// How the implementation phase ended
+    pub status: CompletionStatus,
+}
+
+impl DemoReport {
+    /// Generated files worth opening, relative to the project directory.
+    pub fn files(&self) -> Vec<&'static str> {
+        ["prd.md", "design.md", "tasks.json", "progress.json", "Cargo.toml", "src/main.rs"]
+            .into_iter()
+            .filter(|file| self.project_dir.join(file).exists())
+            .collect()
+    }
+}
+
+/// Configuration for a demo run in `dir`: every agent replays its recording.
+pub fn demo_config(dir: &Path) -> RalphConfig {
+    let mut config = RalphConfig {
+        project_path: dir.display().to_string(),
+        debug_level: DebugLevel::Normal,
+        max_iterations: 5,
+        verification_cadence: VerificationCadence::Off,
+        dependency_advice: false,
+        offline: true,
+        ..RalphConfig::default()
+    };
+    config.agents.prd_model = ModelConfig::new("replay", "demo-prd");
+    config.agents.architect_model = ModelConfig::new("replay", "demo-architect");
+    config.agents.ralph_model = ModelConfig::new("replay", "demo-loop");
+    config
+}
+
+/// Run the demo pipeline into `dir` (created if missing).
+///
+/// `dir` is made a git repository so the loop can commit; a throwaway
+/// identity is set only when git has none configured.
+pub async fn run(dir: &Path) -> Result<DemoReport> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| RalphError::file(dir.display().to_string(), e.to_string()))?;
+    init_git(dir)?;
+
+    let mut orchestrator = RalphOrchestrator::new(demo_config(dir))?;
+    let status = orchestrator.run(DEMO_PROMPT.trim()).await?;
+    Ok(DemoReport {
+        project_dir: orchestrator.project_path().clone(),
+        status,
+    })
+}
+
+/// Initialise a git repository in `dir` with a usable commit identity.
+fn init_git(dir: &Path) -> Result<()> {
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .map_err(|e| RalphError::Internal(format!("Failed to run git: {}", e)))
+    };
+
+    let init = git(&["init", "-q"])?;
+    if !init.status.success() {
+        return Err(RalphError::Internal(format!(
+            "git init failed: {}",
+            String::from_utf8_lossy(&init.stderr).trim()
+        )));
+    }
+    for (key, value) in [("user.name", "Ralph Demo"), ("user.email", "demo@ralph.invalid")] {
+        if !git(&["config", key])?.status.success() {
+            git(&["config", key, value])?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_demo_config_needs_no_keys() {
+        let config = demo_config(Path::new("/tmp/ralph-demo"));
+        assert!(config.validate().is_ok());
+        for model in [
+            &config.agents.prd_model,
+            &config.agents.architect_model,
+            &config.agents.ralph_model,
+        ] {
+            assert_eq!(model.provider, "replay");
+            assert!(crate::replay::ReplayModel::load(&model.model_name).is_ok());
+        }
+        assert!(!DEMO_PROMPT.trim().is_empty());
+    }
+}
@@ -0,0 +1,1211 @@
+//! Upgrade advice for dependencies in generated manifests.
+//!
+//! Models tend to pin dependencies at whatever was current at their training
+//! cutoff, often a major version or two behind. Once the agent first writes a
+//! `Cargo.toml` or `package.json`, the [`DependencyAdvisor`] looks up each direct
+//! dependency in its registry index and:
+//!
+//! - bumps the requirement in place when a newer semver-compatible (patch or
+//!   minor) release exists
+//! - reports a newer major release as [`DependencyAdvice::latest_major`], which
+//!   becomes a low-priority upgrade task linking to the release history
+//!
+//! Registry lookups go through [`HttpRegistry`], which caches responses under
+//! [`REGISTRY_CACHE_FILE`], spaces out requests, and in offline mode answers
+//! from the cache only.
+
+use crate::models::{Task, TaskComplexity, TaskList};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Registry cache location, relative to the project.
+pub const REGISTRY_CACHE_FILE: &str = ".ralph/registry-cache.json";
+
+/// How long cached registry responses are used before refetching.
+const CACHE_TTL_SECS: i64 = 24 * 60 * 60;
+
+/// Minimum spacing between registry requests.
+const MIN_REQUEST_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Prefix for upgrade task IDs.
+const UPGRADE_TASK_PREFIX: &str = "UPGRADE-";
+
+/// Priority given to major-version upgrade tasks (lowest).
+const UPGRADE_TASK_PRIORITY: u32 = 5;
+
+// =============================================================================
+// Versions and requirements
+// =============================================================================
+
+/// A semantic version (`major.minor.patch[-pre]`, build metadata ignored).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    /// Pre-release identifiers (e.g. `beta.2`)
+    pub pre: Option<String>,
+}
+
+impl Version {
+    /// Create a release version.
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+            pre: None,
+        }
+    }
+
+    /// Parse a version, allowing missing minor/patch components and a `v` prefix.
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::parse_with_precision(s).map(|(v, _)| v)
+    }
+
+    /// Parse a version and report how many numeric components were given.
+    fn parse_with_precision(s: &str) -> Option<(Self, usize)> {
+        let s = s.trim();
+        let s = s.strip_prefix('v').unwrap_or(s);
+        let s = s.split('+').next().unwrap_or(s);
+        let (core, pre) = match s.split_once('-') {
+            Some((core, pre)) if !pre.is_empty() => (core, Some(pre.to_string())),
+            Some(_) => return None,
+            None => (s, None),
+        };
+
+        let parts: Vec<&str> = core.split('.').collect();
+        if parts.is_empty() || parts.len() > 3 {
+            return None;
+        }
+        let mut numbers = [0u64; 3];
+        for (i, part) in parts.iter().enumerate() {
+            if part.is_empty() || !part.chars().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            numbers[i] = part.parse().ok()?;
+        }
+        if pre.is_some() && parts.len() < 3 {
+            return None;
+        }
+
+        Some((
+            Self {
+                major: numbers[0],
+                minor: numbers[1],
+                patch: numbers[2],
+                pre,
+            },
+            parts.len(),
+        ))
+    }
+
+    /// Check if this is a pre-release.
+    pub fn is_prerelease(&self) -> bool {
+        self.pre.is_some()
+    }
+
+    /// Same major.minor.patch, ignoring pre-release.
+    fn same_triple(&self, other: &Version) -> bool {
+        (self.major, self.minor, self.patch) == (other.major, other.minor, other.patch)
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(ref pre) = self.pre {
+            write!(f, "-{}", pre)?;
+        }
+        Ok(())
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (&self.pre, &other.pre) {
+                (None, None) => Ordering::Equal,
+                // A release sorts after its pre-releases
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(a), Some(b)) => compare_prerelease(a, b),
+            })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Compare pre-release identifiers per semver precedence rules.
+fn compare_prerelease(a: &str, b: &str) -> Ordering {
+    let mut left = a.split('.');
+    let mut right = b.split('.');
+    loop {
+        match (left.next(), right.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) => {
+                let ord = match (x.parse::<u64>(), y.parse::<u64>()) {
+                    (Ok(x), Ok(y)) => x.cmp(&y),
+                    // Numeric identifiers sort before alphanumeric ones
+                    (Ok(_), Err(_)) => Ordering::Less,
+                    (Err(_), Ok(_)) => Ordering::Greater,
+                    (Err(_), Err(_)) => x.cmp(y),
+                };
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+            }
+        }
+    }
+}
+
+/// Package ecosystem of a manifest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Ecosystem {
+    /// Rust crates (`Cargo.toml`, crates.io)
+    Cargo,
+    /// JavaScript packages (`package.json`, npm)
+    Npm,
+}
+
+impl Ecosystem {
+    /// Manifest file name.
+    pub fn manifest(&self) -> &'static str {
+        match self {
+            Ecosystem::Cargo => "Cargo.toml",
+            Ecosystem::Npm => "package.json",
+        }
+    }
+
+    /// Ecosystem of a manifest path, if it is one we advise on.
+    pub fn from_manifest(path: &Path) -> Option<Self> {
+        match path.file_name()?.to_str()? {
+            "Cargo.toml" => Some(Ecosystem::Cargo),
+            "package.json" => Some(Ecosystem::Npm),
+            _ => None,
+        }
+    }
+
+    /// Page listing a package's releases.
+    pub fn changelog_url(&self, name: &str) -> String {
+        match self {
+            Ecosystem::Cargo => format!("https://crates.io/crates/{}/versions", name),
+            Ecosystem::Npm => format!("https://www.npmjs.com/package/{}?activeTab=versions", name),
+        }
+    }
+}
+
+impl fmt::Display for Ecosystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ecosystem::Cargo => write!(f, "cargo"),
+            Ecosystem::Npm => write!(f, "npm"),
+        }
+    }
+}
+
+/// Operator of a version requirement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequirementKind {
+    /// `^1.2.3` (Cargo's default for a bare version)
+    Caret,
+    /// `~1.2.3`
+    Tilde,
+    /// `=1.2.3` (npm's default for a bare version)
+    Exact,
+}
+
+/// A single-version requirement as written in a manifest.
+///
+/// Ranges (`>=`, `<`, `||`, wildcards) are not advised on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Requirement {
+    pub kind: RequirementKind,
+    /// Operator text as written (e.g. `^`, `~`, `=`, or empty)
+    pub operator: String,
+    pub base: Version,
+    /// Number of version components written (1-3)
+    pub precision: usize,
+}
+
+impl Requirement {
+    /// Parse a requirement, or `None` if it is a range we do not advise on.
+    pub fn parse(spec: &str, ecosystem: Ecosystem) -> Option<Self> {
+        let spec = spec.trim();
+        let (kind, operator, rest) = if let Some(rest) = spec.strip_prefix('^') {
+            (RequirementKind::Caret, "^", rest)
+        } else if let Some(rest) = spec.strip_prefix('~') {
+            (RequirementKind::Tilde, "~", rest)
+        } else if let Some(rest) = spec.strip_prefix('=') {
+            (RequirementKind::Exact, "=", rest)
+        } else if spec.starts_with(|c: char| c.is_ascii_digit() || c == 'v') {
+            let kind = match ecosystem {
+                Ecosystem::Cargo => RequirementKind::Caret,
+                Ecosystem::Npm => RequirementKind::Exact,
+            };
+            (kind, "", spec)
+        } else {
+            return None;
+        };
+
+        let (base, precision) = Version::parse_with_precision(rest)?;
+        Some(Self {
+            kind,
+            operator: operator.to_string(),
+            base,
+            precision,
+        })
+    }
+
+    /// Whether `version` satisfies the requirement.
+    ///
+    /// Caret keeps the major for `1.x`, the minor for `0.y`, and pins the patch
+    /// of a full `0.0.z`. Tilde keeps the major and minor, and exact allows only
+    /// the written version. Fewer written components widen the range.
+    pub fn is_compatible(&self, version: &Version) -> bool {
+        let base = &self.base;
+        if version < base {
+            return false;
+        }
+        if version.is_prerelease() && !(base.is_prerelease() && base.same_triple(version)) {
+            return false;
+        }
+        let same_major = version.major == base.major;
+        let same_minor = same_major && version.minor == base.minor;
+        match self.kind {
+            RequirementKind::Exact => match self.precision {
+                1 => same_major,
+                2 => same_minor,
+                _ => version == base,
+            },
+            RequirementKind::Tilde => {
+                if self.precision == 1 {
+                    same_major
+                } else {
+                    same_minor
+                }
+            }
+            RequirementKind::Caret => {
+                if base.major > 0 || self.precision == 1 {
+                    same_major
+                } else if base.minor > 0 || self.precision == 2 {
+                    same_minor
+                } else {
+                    same_minor && version.patch == base.patch
+                }
+            }
+        }
+    }
+
+    /// Rewrite the requirement for a new base, keeping its operator and precision.
+    ///
+    /// Precision is widened when the written components cannot express the
+    /// new version (e.g. `1.2` bumped to `1.2.5` becomes `1.2.5`).
+    pub fn render(&self, version: &Version) -> String {
+        let precision = if version.patch != 0 || version.is_prerelease() {
+            3
+        } else if version.minor != 0 {
+            self.precision.max(2)
+        } else {
+            self.precision
+        };
+        let text = match precision {
+            1 => format!("{}", version.major),
+            2 => format!("{}.{}", version.major, version.minor),
+            _ => version.to_string(),
+        };
+        format!("{}{}", self.operator, text)
+    }
+}
+
+/// What to do about one dependency given the versions in its registry.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct UpgradeDecision {
+    /// Newest compatible release newer than the requirement, to bump to
+    pub bump: Option<Version>,
+    /// Newest release outside the compatible range, for an upgrade task
+    pub major_gap: Option<Version>,
+}
+
+/// Decide on a bump and/or major upgrade for a requirement.
+///
+/// Pre-releases are ignored unless the requirement itself is one.
+pub fn decide(requirement: &Requirement, available: &[Version]) -> UpgradeDecision {
+    let base = &requirement.base;
+    let candidates: Vec<&Version> = available
+        .iter()
+        .filter(|v| !v.is_prerelease() || base.is_prerelease())
+        .collect();
+
+    let bump = candidates
+        .iter()
+        .filter(|v| **v > base && requirement.is_compatible(v))
+        .max()
+        .map(|v| (*v).clone())
+        .filter(|v| requirement.render(v) != requirement.render(base));
+
+    let major_gap = candidates
+        .iter()
+        .filter(|v| !v.is_prerelease())
+        .max()
+        .filter(|latest| **latest > base && !requirement.is_compatible(latest))
+        .map(|v| (*v).clone());
+
+    UpgradeDecision { bump, major_gap }
+}
+
+// =============================================================================
+// Manifests
+// =============================================================================
+
+/// A direct dependency found in a manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestDependency {
+    /// Key in the manifest
+    pub name: String,
+    /// Package name in the registry (differs for renamed Cargo dependencies)
+    pub package: String,
+    /// Requirement as written
+    pub spec: String,
+    /// Zero-based line holding the requirement (Cargo only)
+    pub line: Option<usize>,
+}
+
+/// Direct dependencies of a `Cargo.toml`.
+///
+/// Covers `[dependencies]`, `[dev-dependencies]`, `[build-dependencies]` and
+/// their `[dependencies.name]` table forms. Path and git dependencies without
+/// a version are skipped.
+pub fn parse_cargo_dependencies(manifest: &str) -> Vec<ManifestDependency> {
+    const SECTIONS: &[&str] = &["dependencies", "dev-dependencies", "build-dependencies"];
+
+    let mut deps = Vec::new();
+    let mut in_deps = false;
+    // `[dependencies.name]`: (name, package, version line)
+    let mut table: Option<(String, Option<String>, Option<(String, usize)>)> = None;
+
+    let flush = |table: &mut Option<(String, Option<String>, Option<(String, usize)>)>,
+                 deps: &mut Vec<ManifestDependency>| {
+        if let Some((name, package, Some((spec, line)))) = table.take() {
+            deps.push(ManifestDependency {
+                package: package.unwrap_or_else(|| name.clone()),
+                name,
+                spec,
+                line: Some(line),
+            });
+        }
+    };
+
+    for (i, raw) in manifest.lines().enumerate() {
+        let line = raw.split('#').next().unwrap_or("").trim();
+        if line.starts_with('[') {
+            flush(&mut table, &mut deps);
+            let header = line.trim_matches(|c| c == '[' || c == ']').trim();
+            in_deps = SECTIONS.contains(&header);
+            table = SECTIONS.iter().find_map(|s| {
+                header
+                    .strip_prefix(&format!("{}.", s))
+                    .map(|name| (name.trim_matches('"').to_string(), None, None))
+            });
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim().trim_matches('"');
+        let value = value.trim();
+
+        if let Some((_, package, version)) = table.as_mut() {
+            match key {
+                "version" => *version = quoted(value).map(|v| (v, i)),
+                "package" => *package = quoted(value),
+                _ => {}
+            }
+            continue;
+        }
+        if !in_deps {
+            continue;
+        }
+
+        if let Some(spec) = quoted(value) {
+            deps.push(ManifestDependency {
+                name: key.to_string(),
+                package: key.to_string(),
+                spec,
+                line: Some(i),
+            });
+        } else if value.starts_with('{') {
+            let Some(spec) = inline_field(value, "version") else {
+                continue;
+            };
+            deps.push(ManifestDependency {
+                name: key.to_string(),
+                package: inline_field(value, "package").unwrap_or_else(|| key.to_string()),
+                spec,
+                line: Some(i),
+            });
+        }
+    }
+    flush(&mut table, &mut deps);
+    deps
+}
+
+/// Direct dependencies of a `package.json`.
+pub fn parse_npm_dependencies(manifest: &str) -> Vec<ManifestDependency> {
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(manifest) else {
+        return Vec::new();
+    };
+    let mut deps = Vec::new();
+    for section in ["dependencies", "devDependencies"] {
+        if let Some(map) = json[section].as_object() {
+            for (name, spec) in map {
+                if let Some(spec) = spec.as_str() {
+                    deps.push(ManifestDependency {
+                        name: name.clone(),
+                        package: name.clone(),
+                        spec: spec.to_string(),
+                        line: None,
+                    });
+                }
+            }
+        }
+    }
+    deps
+}
+
+/// Parse a manifest's direct dependencies.
+pub fn parse_dependencies(manifest: &str, ecosystem: Ecosystem) -> Vec<ManifestDependency> {
+    match ecosystem {
+        Ecosystem::Cargo => parse_cargo_dependencies(manifest),
+        Ecosystem::Npm => parse_npm_dependencies(manifest),
+    }
+}
+
+/// Replace a dependency's requirement in the manifest text, keeping its layout.
+pub fn rewrite_requirement(
+    manifest: &str,
+    ecosystem: Ecosystem,
+    dep: &ManifestDependency,
+    new_spec: &str,
+) -> String {
+    let old = format!("\"{}\"", dep.spec);
+    let new = format!("\"{}\"", new_spec);
+    match (ecosystem, dep.line) {
+        (Ecosystem::Cargo, Some(target)) => {
+            let mut out: Vec<String> = Vec::new();
+            for (i, line) in manifest.lines().enumerate() {
+                if i == target {
+                    // The version is the last quoted value matching the old spec on the line
+                    match line.rfind(&old) {
+                        Some(at) => out.push(format!("{}{}{}", &line[..at], new, &line[at + old.len()..])),
+                        None => out.push(line.to_string()),
+                    }
+                } else {
+                    out.push(line.to_string());
+                }
+            }
+            let mut text = out.join("\n");
+            if manifest.ends_with('\n') {
+                text.push('\n');
+            }
+            text
+        }
+        _ => {
+            // `"name": "spec"` with any whitespace around the colon
+            let key = format!("\"{}\"", dep.name);
+            let mut search_from = 0;
+            while let Some(found) = manifest[search_from..].find(&key) {
+                let key_end = search_from + found + key.len();
+                let rest = &manifest[key_end..];
+                let after_colon = rest.trim_start().strip_prefix(':').map(str::trim_start);
+                if let Some(value) = after_colon {
+                    if value.starts_with(&old) {
+                        let value_at = manifest.len() - value.len();
+                        return format!(
+                            "{}{}{}",
+                            &manifest[..value_at],
+                            new,
+                            &manifest[value_at + old.len()..]
+                        );
+                    }
+                }
+                search_from = key_end;
+            }
+            manifest.to_string()
+        }
+    }
+}
+
+/// Contents of a `"quoted"` TOML string value.
+fn quoted(value: &str) -> Option<String> {
+    let value = value.trim().trim_end_matches(',');
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .map(String::from)
+}
+
+/// A string field of an inline TOML table (`{ version = "1", ... }`).
+fn inline_field(table: &str, field: &str) -> Option<String> {
+    let inner = table.trim().trim_start_matches('{').trim_end_matches('}');
+    inner.split(',').find_map(|pair| {
+        let (key, value) = pair.split_once('=')?;
+        (key.trim() == field).then(|| quoted(value)).flatten()
+    })
+}
+
+// =============================================================================
+// Registry access
+// =============================================================================
+
+/// Source of published versions for a package.
+pub trait RegistryIndex {
+    /// Published, non-yanked, non-deprecated versions of a package.
+    fn versions(&self, ecosystem: Ecosystem, package: &str) -> Result<Vec<Version>, String>;
+}
+
+/// Cached registry responses keyed by `ecosystem:package`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RegistryCache {
+    #[serde(default)]
+    entries: HashMap<String, CachedVersions>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedVersions {
+    /// Unix timestamp of the fetch
+    fetched_at: i64,
+    versions: Vec<String>,
+}
+
+/// Registry index over HTTP (crates.io sparse index, npm registry) via `curl`.
+///
+/// Responses are cached on disk; requests are spaced by a minimum interval.
+/// Offline, only cached entries are used, however old.
+#[derive(Debug)]
+pub struct HttpRegistry {
+    cache_path: PathBuf,
+    offline: bool,
+    cache: Mutex<RegistryCache>,
+    last_request: Mutex<Option<Instant>>,
+}
+
+impl HttpRegistry {
+    /// Create a registry client caching under the project directory.
+    pub fn new(project_path: &Path, offline: bool) -> Self {
+        let cache_path = project_path.join(REGISTRY_CACHE_FILE);
+        let cache = std::fs::read_to_string(&cache_path)
+            .ok()
+            .and_then(|c| serde_json::from_str(&c).ok())
+            .unwrap_or_default();
+        Self {
+            cache_path,
+            offline,
+            cache: Mutex::new(cache),
+            last_request: Mutex::new(None),
+        }
+    }
+
+    /// Cached versions, if present and (unless offline) still fresh.
+    fn cached(&self, key: &str) -> Option<Vec<Version>> {
+        let cache = self.cache.lock().unwrap();
+        let entry = cache.entries.get(key)?;
+        let age = chrono::Utc::now().timestamp() - entry.fetched_at;
+        if !self.offline && age > CACHE_TTL_SECS {
+            return None;
+        }
+        Some(entry.versions.iter().filter_map(|v| Version::parse(v)).collect())
+    }
+
+    fn store(&self, key: String, versions: &[Version]) {
+        let mut cache = self.cache.lock().unwrap();
+        cache.entries.insert(
+            key,
+            CachedVersions {
+                fetched_at: chrono::Utc::now().timestamp(),
+                versions: versions.iter().map(Version::to_string).collect(),
+            },
+        );
+        if let Some(parent) = self.cache_path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Ok(content) = serde_json::to_string(&*cache) {
+            if let Err(e) = std::fs::write(&self.cache_path, content) {
+                tracing::debug!(error = %e, "Could not write registry cache");
+            }
+        }
+    }
+
+    /// Wait until the minimum interval since the last request has passed.
+    fn throttle(&self) {
+        let mut last = self.last_request.lock().unwrap();
+        if let Some(at) = *last {
+            let elapsed = at.elapsed();
+            if elapsed < MIN_REQUEST_INTERVAL {
+                std::thread::sleep(MIN_REQUEST_INTERVAL - elapsed);
+            }
+        }
+        *last = Some(Instant::now());
+    }
+
+    fn fetch(&self, url: &str) -> Result<String, String> {
+        self.throttle();
+        let output = Command::new("curl")
+            .args(["-sSfL", "--max-time", "10", url])
+            .output()
+            .map_err(|e| format!("Failed to run curl: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "Registry request failed for {}: {}",
+                url,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+}
+
+impl RegistryIndex for HttpRegistry {
+    fn versions(&self, ecosystem: Ecosystem, package: &str) -> Result<Vec<Version>, String> {
+        let key = format!("{}:{}", ecosystem, package);
+        if let Some(versions) = self.cached(&key) {
+            return Ok(versions);
+        }
+        if self.offline {
+            return Err(format!("Offline and no cached versions for {}", package));
+        }
+
+        let versions = match ecosystem {
+            Ecosystem::Cargo => {
+                let url = sparse_index_url(package)
+                    .ok_or_else(|| format!("Invalid crate name: {}", package))?;
+                parse_sparse_index(&self.fetch(&url)?)
+            }
+            Ecosystem::Npm => parse_npm_registry(&self.fetch(&format!(
+                "https://registry.npmjs.org/{}",
+                package.replace('/', "%2F")
+            ))?)?,
+        };
+        self.store(key, &versions);
+        Ok(versions)
+    }
+}
+
+/// URL of a crate in the crates.io sparse index, or `None` if `name` is not a
+/// valid crate name (ASCII letters, digits, `-` and `_`).
+pub fn sparse_index_url(name: &str) -> Option<String> {
+    let valid = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+    if name.is_empty() || !name.chars().all(valid) {
+        return None;
+    }
+    let name = name.to_ascii_lowercase();
+    let path = match name.len() {
+        1 => format!("1/{}", name),
+        2 => format!("2/{}", name),
+        3 => format!("3/{}/{}", &name[..1], name),
+        _ => format!("{}/{}/{}", &name[..2], &name[2..4], name),
+    };
+    Some(format!("https://index.crates.io/{}", path))
+}
+
+/// Non-yanked versions from a sparse index file (one JSON record per line).
+pub fn parse_sparse_index(body: &str) -> Vec<Version> {
+    body.lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter(|record| !record["yanked"].as_bool().unwrap_or(false))
+        .filter_map(|record| record["vers"].as_str().and_then(Version::parse))
+        .collect()
+}
+
+/// Non-deprecated versions from an npm registry document.
+pub fn parse_npm_registry(body: &str) -> Result<Vec<Version>, String> {
+    let json: serde_json::Value =
+        serde_json::from_str(body).map_err(|e| format!("Invalid npm registry response: {}", e))?;
+    Ok(json["versions"]
+        .as_object()
+        .map(|versions| {
+            versions
+                .iter()
+                .filter(|(_, meta)| meta["deprecated"].is_null())
+                .filter_map(|(v, _)| Version::parse(v))
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+// =============================================================================
+// Advice
+// =============================================================================
+
+/// Upgrade advice for one dependency.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DependencyAdvice {
+    pub ecosystem: Ecosystem,
+    /// Dependency key in the manifest
+    pub name: String,
+    /// Requirement as originally written
+    pub requirement: String,
+    /// New requirement written to the manifest (compatible bump)
+    #[serde(default)]
+    pub bumped_to: Option<String>,
+    /// Newer incompatible release
+    #[serde(default)]
+    pub latest_major: Option<String>,
+    /// Where to read about the releases in between
+    pub changelog_url: String,
+}
+
+/// Outcome of advising on one manifest.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdviceReport {
+    /// Manifest that was checked
+    pub manifest: PathBuf,
+    /// Dependencies with a bump or a major gap
+    pub advice: Vec<DependencyAdvice>,
+    /// Dependencies that could not be checked, with the reason
+    pub skipped: Vec<(String, String)>,
+}
+
+impl AdviceReport {
+    /// Dependencies that were bumped in place.
+    pub fn bumped(&self) -> impl Iterator<Item = &DependencyAdvice> {
+        self.advice.iter().filter(|a| a.bumped_to.is_some())
+    }
+
+    /// Dependencies with a newer major version.
+    pub fn major_gaps(&self) -> impl Iterator<Item = &DependencyAdvice> {
+        self.advice.iter().filter(|a| a.latest_major.is_some())
+    }
+}
+
+/// Checks a manifest's dependencies against a registry.
+pub struct DependencyAdvisor<R: RegistryIndex> {
+    registry: R,
+}
+
+impl<R: RegistryIndex> DependencyAdvisor<R> {
+    /// Create an advisor over a registry index.
+    pub fn new(registry: R) -> Self {
+        Self { registry }
+    }
+
+    /// Advise on a manifest, bumping compatible versions in place.
+    pub fn advise(&self, manifest_path: &Path) -> Result<AdviceReport, String> {
+        let ecosystem = Ecosystem::from_manifest(manifest_path)
+            .ok_or_else(|| format!("Not a supported manifest: {}", manifest_path.display()))?;
+        let original = std::fs::read_to_string(manifest_path)
+            .map_err(|e| format!("Failed to read '{}': {}", manifest_path.display(), e))?;
+
+        let mut manifest = original.clone();
+        let mut report = AdviceReport {
+            manifest: manifest_path.to_path_buf(),
+            ..Default::default()
+        };
+
+        for dep in parse_dependencies(&original, ecosystem) {
+            let Some(requirement) = Requirement::parse(&dep.spec, ecosystem) else {
+                report.skipped.push((dep.name, format!("unsupported requirement '{}'", dep.spec)));
+                continue;
+            };
+            let available = match self.registry.versions(ecosystem, &dep.package) {
+                Ok(versions) => versions,
+                Err(e) => {
+                    report.skipped.push((dep.name, e));
+                    continue;
+                }
+            };
+
+            let decision = decide(&requirement, &available);
+            if decision == UpgradeDecision::default() {
+                continue;
+            }
+
+            let bumped_to = decision.bump.as_ref().map(|v| requirement.render(v));
+            if let Some(ref new_spec) = bumped_to {
+                manifest = rewrite_requirement(&manifest, ecosystem, &dep, new_spec);
+            }
+            report.advice.push(DependencyAdvice {
+                ecosystem,
+                changelog_url: ecosystem.changelog_url(&dep.package),
+                name: dep.name,
+                requirement: dep.spec,
+                bumped_to,
+                latest_major: decision.major_gap.map(|v| v.to_string()),
+            });
+        }
+
+        if manifest != original {
+            std::fs::write(manifest_path, &manifest)
+                .map_err(|e| format!("Failed to update '{}': {}", manifest_path.display(), e))?;
+        }
+        Ok(report)
+    }
+}
+
+/// Add a low-priority upgrade task per major-version gap.
+///
+/// Returns the IDs of the new tasks. Gaps that already have a task are skipped.
+pub fn record_upgrade_tasks(list: &mut TaskList, report: &AdviceReport) -> Vec<String> {
+    let mut next = list
+        .get_all_tasks()
+        .iter()
+        .filter_map(|t| t.id.strip_prefix(UPGRADE_TASK_PREFIX)?.parse::<usize>().ok())
+        .max()
+        .unwrap_or(0);
+
+    let mut created = Vec::new();
+    for advice in report.major_gaps() {
+        let latest = advice.latest_major.as_deref().unwrap_or_default();
+        let title = format!("Upgrade {} to {}", advice.name, latest);
+        if list.get_all_tasks().iter().any(|t| t.title == title) {
+            continue;
+        }
+
+        next += 1;
+        let id = format!("{}{:03}", UPGRADE_TASK_PREFIX, next);
+        let description = format!(
+            "{} is pinned to '{}' but {} is available. Review the release history at {} \
+             and migrate any breaking API changes.",
+            advice.name,
+            advice.bumped_to.as_deref().unwrap_or(&advice.requirement),
+            latest,
+            advice.changelog_url
+        );
+        let task = Task::new(&id, title, description, UPGRADE_TASK_PRIORITY)
+            .with_complexity(TaskComplexity::Low)
+            .with_acceptance_criterion(format!(
+                "The project builds and its tests pass with {} {}",
+                advice.name, latest
+            ));
+        list.add_task(task);
+        created.push(id);
+    }
+    if !created.is_empty() {
+        list.updated_at = Some(crate::timefmt::now());
+    }
+    created
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn v(s: &str) -> Version {
+        Version::parse(s).unwrap()
+    }
+
+    fn req(spec: &str) -> Requirement {
+        Requirement::parse(spec, Ecosystem::Cargo).unwrap()
+    }
+
+    fn versions(list: &[&str]) -> Vec<Version> {
+        list.iter().map(|s| v(s)).collect()
+    }
+
+    #[test]
+    fn test_version_parsing() {
+        assert_eq!(v("1.2.3"), Version::new(1, 2, 3));
+        assert_eq!(v("v2"), Version::new(2, 0, 0));
+        assert_eq!(v("0.3"), Version::new(0, 3, 0));
+        assert_eq!(v("1.0.0+build.5"), Version::new(1, 0, 0));
+        assert_eq!(v("1.0.0-beta.2").pre.as_deref(), Some("beta.2"));
+        assert_eq!(v("1.0.0-beta.2").to_string(), "1.0.0-beta.2");
+        for bad in ["", "1.", "1.2.3.4", "a.b", "1.x", "1.0-rc1", "1.0.0-", "*"] {
+            assert!(Version::parse(bad).is_none(), "{} should not parse", bad);
+        }
+    }
+
+    #[test]
+    fn test_version_ordering() {
+        let mut list = versions(&[
+            "1.0.0", "1.0.0-rc.1", "1.0.0-alpha", "1.0.0-alpha.1", "1.0.0-alpha.beta",
+            "1.0.0-beta", "1.0.0-beta.2", "1.0.0-beta.11", "0.9.9", "1.10.0", "1.2.0",
+        ]);
+        list.sort();
+        let sorted: Vec<String> = list.iter().map(Version::to_string).collect();
+        assert_eq!(
+            sorted,
+            vec![
+                "0.9.9", "1.0.0-alpha", "1.0.0-alpha.1", "1.0.0-alpha.beta", "1.0.0-beta",
+                "1.0.0-beta.2", "1.0.0-beta.11", "1.0.0-rc.1", "1.0.0", "1.2.0", "1.10.0",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_requirement_parsing() {
+        let caret = req("1.2");
+        assert_eq!(caret.kind, RequirementKind::Caret);
+        assert_eq!(caret.operator, "");
+        assert_eq!(caret.precision, 2);
+
+        let npm_bare = Requirement::parse("1.2.3", Ecosystem::Npm).unwrap();
+        assert_eq!(npm_bare.kind, RequirementKind::Exact);
+        assert_eq!(Requirement::parse("~1.2.3", Ecosystem::Npm).unwrap().kind, RequirementKind::Tilde);
+        assert_eq!(Requirement::parse("^4.17.0", Ecosystem::Npm).unwrap().operator, "^");
+        assert_eq!(req("=0.5.1").kind, RequirementKind::Exact);
+
+        for range in [">=1.0", "*", "1.x", "^1 || ^2", "latest", "git+https://x", "workspace:*"] {
+            assert!(Requirement::parse(range, Ecosystem::Npm).is_none(), "{}", range);
+        }
+    }
+
+    #[test]
+    fn test_caret_compatibility_edges() {
+        assert!(req("1.2.3").is_compatible(&v("1.9.0")));
+        assert!(!req("1.2.3").is_compatible(&v("2.0.0")));
+        assert!(!req("1.2.3").is_compatible(&v("1.2.2")));
+
+        // 0.y: minor is the breaking component
+        assert!(req("0.3.1").is_compatible(&v("0.3.9")));
+        assert!(!req("0.3.1").is_compatible(&v("0.4.0")));
+
+        // 0.0.z: patch is breaking
+        assert!(!req("0.0.3").is_compatible(&v("0.0.4")));
+        assert!(req("0.0.3").is_compatible(&v("0.0.3")));
+
+        // Fewer components widen the range
+        assert!(req("0").is_compatible(&v("0.9.0")));
+        assert!(req("0.0").is_compatible(&v("0.0.7")));
+        assert!(!req("0.0").is_compatible(&v("0.1.0")));
+
+        // Pre-releases only match the same version's pre-releases
+        assert!(!req("1.2.3").is_compatible(&v("1.3.0-beta.1")));
+        assert!(req("1.0.0-beta.1").is_compatible(&v("1.0.0-beta.3")));
+        assert!(!req("1.0.0-beta.1").is_compatible(&v("1.1.0-alpha")));
+    }
+
+    #[test]
+    fn test_exact_compatibility() {
+        assert!(req("=0.5.1").is_compatible(&v("0.5.1")));
+        assert!(!req("=0.5.1").is_compatible(&v("0.5.9")));
+        assert!(!req("=1.2.3").is_compatible(&v("1.9.0")));
+
+        let npm_pin = Requirement::parse("4.17.21", Ecosystem::Npm).unwrap();
+        assert!(!npm_pin.is_compatible(&v("4.17.22")));
+
+        // Partial exact versions match every release they name
+        assert!(req("=1.2").is_compatible(&v("1.2.7")));
+        assert!(!req("=1.2").is_compatible(&v("1.3.0")));
+        assert!(req("=1").is_compatible(&v("1.8.0")));
+
+        // A pin only moves by a major-gap task
+        let pinned = decide(&req("=1.2.3"), &versions(&["1.2.3", "1.2.4", "1.5.0"]));
+        assert_eq!(pinned.bump, None);
+        assert_eq!(pinned.major_gap, Some(v("1.5.0")));
+    }
+
+    #[test]
+    fn test_tilde_compatibility() {
+        assert!(req("~1.2.3").is_compatible(&v("1.2.9")));
+        assert!(!req("~1.2.3").is_compatible(&v("1.3.0")));
+        assert!(!req("~1.2.3").is_compatible(&v("1.2.2")));
+        assert!(req("~1.2").is_compatible(&v("1.2.0")));
+        assert!(!req("~1.2").is_compatible(&v("1.3.0")));
+        assert!(req("~1").is_compatible(&v("1.9.0")));
+        assert!(!req("~1").is_compatible(&v("2.0.0")));
+
+        // Unlike caret, 0.0.z is not pinned
+        assert!(req("~0.0.3").is_compatible(&v("0.0.4")));
+        assert!(!req("~0.4.1").is_compatible(&v("0.5.0")));
+    }
+
+    #[test]
+    fn test_render_keeps_operator_and_precision() {
+        assert_eq!(req("1.2").render(&v("1.4.0")), "1.4");
+        assert_eq!(req("1.2").render(&v("1.2.5")), "1.2.5");
+        assert_eq!(req("^1").render(&v("1.0.0")), "^1");
+        assert_eq!(req("1").render(&v("1.3.0")), "1.3");
+        assert_eq!(req("~0.4.1").render(&v("0.4.7")), "~0.4.7");
+    }
+
+    #[test]
+    fn test_decide_bump_and_major_gap() {
+        let available = versions(&["1.0.0", "1.2.0", "1.4.2", "2.0.0", "2.1.0", "3.0.0-beta.1"]);
+
+        let both = decide(&req("1.2"), &available);
+        assert_eq!(both.bump, Some(v("1.4.2")));
+        assert_eq!(both.major_gap, Some(v("2.1.0")));
+
+        let up_to_date = decide(&req("2.1"), &available);
+        assert_eq!(up_to_date, UpgradeDecision::default());
+
+        // "1" already covers every 1.x release, so only the gap remains
+        let wide = decide(&req("1"), &versions(&["1.0.0", "1.9.0", "2.0.0"]));
+        assert_eq!(wide.bump, Some(v("1.9.0")));
+        let wide_same = decide(&req("1"), &versions(&["1.0.0", "2.0.0"]));
+        assert_eq!(wide_same.bump, None);
+        assert_eq!(wide_same.major_gap, Some(v("2.0.0")));
+
+        // 0.x minors are majors
+        let zero = decide(&req("0.11"), &versions(&["0.11.0", "0.11.3", "0.12.1"]));
+        assert_eq!(zero.bump, Some(v("0.11.3")));
+        assert_eq!(zero.major_gap, Some(v("0.12.1")));
+
+        // Newer than anything published (e.g. a yanked release): no advice
+        assert_eq!(decide(&req("5.0"), &available), UpgradeDecision::default());
+
+        // Pre-release requirements follow pre-releases of the same version
+        let beta = decide(&req("3.0.0-beta.1"), &versions(&["3.0.0-beta.1", "3.0.0-beta.4", "2.1.0"]));
+        assert_eq!(beta.bump, Some(v("3.0.0-beta.4")));
+        assert_eq!(beta.major_gap, None);
+    }
+
+    #[test]
+    fn test_parse_cargo_dependencies() {
+        let manifest = r#"[package]
+name = "calc"
+version = "0.1.0"
+
+[dependencies]
+serde = { version = "1.0", features = ["derive"] }
+clap = "3.2"  # old
+local = { path = "../local" }
+"tokio" = "1"
+rand_core = { package = "rand", version = "0.7" }
+
+[dev-dependencies]
+tempfile = "3"
+
+[dependencies.reqwest]
+version = "0.11"
+default-features = false
+"#;
+        let deps = parse_cargo_dependencies(manifest);
+        let names: Vec<(&str, &str, &str)> = deps
+            .iter()
+            .map(|d| (d.name.as_str(), d.package.as_str(), d.spec.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("serde", "serde", "1.0"),
+                ("clap", "clap", "3.2"),
+                ("tokio", "tokio", "1"),
+                ("rand_core", "rand", "0.7"),
+                ("tempfile", "tempfile", "3"),
+                ("reqwest", "reqwest", "0.11"),
+            ]
+        );
+        assert_eq!(deps[5].line, Some(17));
+    }
+
+    #[test]
+    fn test_rewrite_requirement_preserves_layout() {
+        let cargo = "[package]\nversion = \"1.0\"\n\n[dependencies]\nserde = { version = \"1.0\", features = [\"derive\"] }\n";
+        let dep = &parse_cargo_dependencies(cargo)[0];
+        let updated = rewrite_requirement(cargo, Ecosystem::Cargo, dep, "1.0.210");
+        assert_eq!(
+            updated,
+            "[package]\nversion = \"1.0\"\n\n[dependencies]\nserde = { version = \"1.0.210\", features = [\"derive\"] }\n"
+        );
+
+        let npm = "{\n  \"version\": \"4.17.1\",\n  \"dependencies\": {\n    \"express\" : \"^4.17.1\"\n  }\n}\n";
+        let dep = &parse_npm_dependencies(npm)[0];
+        let updated = rewrite_requirement(npm, Ecosystem::Npm, dep, "^4.21.2");
+        assert!(updated.contains("\"version\": \"4.17.1\""));
+        assert!(updated.contains("\"express\" : \"^4.21.2\""));
+    }
+
+    #[test]
+    fn test_registry_response_parsing() {
+        let sparse = concat!(
+            r#"{"name":"clap","vers":"3.2.25","yanked":false}"#, "\n",
+            r#"{"name":"clap","vers":"4.0.0","yanked":true}"#, "\n",
+            r#"{"name":"clap","vers":"4.5.4","yanked":false}"#, "\n",
+        );
+        assert_eq!(parse_sparse_index(sparse), versions(&["3.2.25", "4.5.4"]));
+
+        let npm = r#"{"versions":{"1.0.0":{},"1.1.0":{"deprecated":"security issue"},"2.0.0":{}}}"#;
+        let mut parsed = parse_npm_registry(npm).unwrap();
+        parsed.sort();
+        assert_eq!(parsed, versions(&["1.0.0", "2.0.0"]));
+
+        assert_eq!(sparse_index_url("a").unwrap(), "https://index.crates.io/1/a");
+        assert_eq!(sparse_index_url("cc").unwrap(), "https://index.crates.io/2/cc");
+        assert_eq!(sparse_index_url("syn").unwrap(), "https://index.crates.io/3/s/syn");
+        assert_eq!(sparse_index_url("Serde").unwrap(), "https://index.crates.io/se/rd/serde");
+        assert_eq!(sparse_index_url("sé"), None);
+        assert_eq!(sparse_index_url("ünïcode"), None);
+        assert_eq!(sparse_index_url(""), None);
+    }
+
+    struct FakeRegistry {
+        calls: Cell<usize>,
+    }
+
+    impl RegistryIndex for FakeRegistry {
+        fn versions(&self, _ecosystem: Ecosystem, package: &str) -> Result<Vec<Version>, String> {
+            self.calls.set(self.calls.get() + 1);
+            match package {
+                "serde" => Ok(versions(&["1.0.100", "1.0.210"])),
+                "clap" => Ok(versions(&["3.2.0", "3.2.25", "4.5.4"])),
+                _ => Err("not found".to_string()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_advise_bumps_manifest_and_records_upgrade_tasks() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = dir.path().join("Cargo.toml");
+        std::fs::write(
+            &manifest,
+            "[dependencies]\nserde = \"1.0.100\"\nclap = \"3.2\"\nmystery = \"0.1\"\nlocal = { path = \"x\" }\n",
+        )
+        .unwrap();
+
+        let advisor = DependencyAdvisor::new(FakeRegistry { calls: Cell::new(0) });
+        let report = advisor.advise(&manifest).unwrap();
+
+        let written = std::fs::read_to_string(&manifest).unwrap();
+        assert!(written.contains("serde = \"1.0.210\""));
+        assert!(written.contains("clap = \"3.2.25\""));
+        assert!(written.contains("local = { path = \"x\" }"));
+
+        assert_eq!(report.bumped().count(), 2);
+        let gaps: Vec<_> = report.major_gaps().collect();
+        assert_eq!(gaps.len(), 1);
+        assert_eq!(gaps[0].name, "clap");
+        assert_eq!(gaps[0].latest_major.as_deref(), Some("4.5.4"));
+        assert_eq!(gaps[0].changelog_url, "https://crates.io/crates/clap/versions");
+        assert_eq!(report.skipped, vec![("mystery".to_string(), "not found".to_string())]);
+
+        let mut list = TaskList::new("calc", "rust");
+        assert_eq!(record_upgrade_tasks(&mut list, &report), vec!["UPGRADE-001"]);
+        let task = list.get_task("UPGRADE-001").unwrap();
+        assert_eq!(task.priority, UPGRADE_TASK_PRIORITY);
+        assert!(task.description.contains("https://crates.io/crates/clap/versions"));
+        assert!(task.description.contains("'3.2.25'"));
+
+        // Re-advising does not duplicate tasks
+        assert!(record_upgrade_tasks(&mut list, &report).is_empty());
+    }
+
+    #[test]
+    fn test_offline_registry_uses_cache_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let online = HttpRegistry::new(dir.path(), false);
+        online.store("cargo:serde".to_string(), &versions(&["1.0.1"]));
+
+        let offline = HttpRegistry::new(dir.path(), true);
+        assert_eq!(offline.versions(Ecosystem::Cargo, "serde").unwrap(), versions(&["1.0.1"]));
+        assert!(offline.versions(Ecosystem::Cargo, "clap").unwrap_err().contains("Offline"));
+    }
+}
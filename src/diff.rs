@@ -0,0 +1,593 @@
+//! Line diffs rendered for people.
+//!
+//! Overwrite prompts, prompt drift and reports all need to show what changed
+//! in a file. [`unified_diff`] computes hunks with context, [`render_diff`]
+//! turns them into colored terminal output, plain unified text or an HTML
+//! fragment, and [`apply_patch`] replays hunks onto the old content.
+//!
+//! Text is compared after CRLF normalization. Binary content (a NUL byte or
+//! invalid UTF-8) is reported as differing without a body, very long diffs
+//! keep their first and last lines with the middle elided, and
+//! [`detect_renames`] pairs deleted and added files by content similarity.
+
+use std::borrow::Cow;
+
+/// Lines of context around each change.
+pub const DEFAULT_CONTEXT: usize = 3;
+
+/// Rendered lines beyond which the middle of a diff is elided.
+pub const DEFAULT_MAX_LINES: usize = 200;
+
+/// Similarity at or above which a deleted and an added file count as a rename.
+pub const RENAME_THRESHOLD: f64 = 0.5;
+
+/// Largest comparison table (changed old lines × changed new lines) diffed
+/// line by line; beyond it the changed region is replaced wholesale.
+const MAX_TABLE_CELLS: usize = 4_000_000;
+
+/// How far into the content to look for a NUL byte.
+const BINARY_SNIFF_BYTES: usize = 8000;
+
+/// Output format of [`render_diff`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffFormat {
+    /// Unified diff with ANSI colors
+    Terminal,
+    /// Unified diff without colors
+    Plain,
+    /// `<pre class="diff">` fragment with one `<span>` per line
+    Html,
+}
+
+impl DiffFormat {
+    /// [`DiffFormat::Terminal`] when the console shows color, [`DiffFormat::Plain`] otherwise.
+    pub fn for_console() -> Self {
+        if colored::control::SHOULD_COLORIZE.should_colorize() {
+            DiffFormat::Terminal
+        } else {
+            DiffFormat::Plain
+        }
+    }
+}
+
+/// Rendering limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiffOptions {
+    /// Lines of context around each change
+    pub context: usize,
+    /// Rendered lines beyond which the middle is elided
+    pub max_lines: usize,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            context: DEFAULT_CONTEXT,
+            max_lines: DEFAULT_MAX_LINES,
+        }
+    }
+}
+
+/// One line of a hunk, including its trailing newline if it has one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine {
+    /// Present in both versions
+    Context(String),
+    /// Only in the old version
+    Removed(String),
+    /// Only in the new version
+    Added(String),
+}
+
+/// A run of changes with surrounding context.
+///
+/// Starts are 1-based as in unified diffs; an empty side starts at the line
+/// before it (0 at the top of the file).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// First old line covered
+    pub old_start: usize,
+    /// Old lines covered
+    pub old_len: usize,
+    /// First new line covered
+    pub new_start: usize,
+    /// New lines covered
+    pub new_len: usize,
+    /// Context, removed and added lines in order
+    pub lines: Vec<DiffLine>,
+}
+
+/// A deleted file that reappears under another path.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rename {
+    /// Path of the deleted file
+    pub from: String,
+    /// Path of the added file
+    pub to: String,
+    /// Share of lines the two versions have in common (0.0 to 1.0)
+    pub similarity: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Replace CRLF line endings with LF.
+pub fn normalize_line_endings(text: &str) -> Cow<'_, str> {
+    if text.contains("\r\n") {
+        Cow::Owned(text.replace("\r\n", "\n"))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Whether `content` looks binary: a NUL byte near the start, or invalid UTF-8.
+pub fn is_binary(content: &[u8]) -> bool {
+    content[..content.len().min(BINARY_SNIFF_BYTES)].contains(&0)
+        || std::str::from_utf8(content).is_err()
+}
+
+/// Hunks turning `old` into `new`, with `context` lines around each change.
+pub fn unified_diff(old: &str, new: &str, context: usize) -> Vec<Hunk> {
+    let old = normalize_line_endings(old);
+    let new = normalize_line_endings(new);
+    let a: Vec<&str> = old.split_inclusive('\n').collect();
+    let b: Vec<&str> = new.split_inclusive('\n').collect();
+    let ops = line_ops(&a, &b);
+
+    // Position in each file before op i
+    let mut positions = Vec::with_capacity(ops.len() + 1);
+    let (mut ai, mut bi) = (0, 0);
+    for (op, _) in &ops {
+        positions.push((ai, bi));
+        match op {
+            Op::Equal => {
+                ai += 1;
+                bi += 1;
+            }
+            Op::Delete => ai += 1,
+            Op::Insert => bi += 1,
+        }
+    }
+    positions.push((ai, bi));
+
+    let changes: Vec<usize> = (0..ops.len()).filter(|&i| ops[i].0 != Op::Equal).collect();
+    let mut hunks = Vec::new();
+    let mut first = 0;
+    while first < changes.len() {
+        // Changes closer than two contexts apart share a hunk
+        let mut last = first;
+        while last + 1 < changes.len() && changes[last + 1] - changes[last] - 1 <= 2 * context {
+            last += 1;
+        }
+        let start = changes[first].saturating_sub(context);
+        let end = (changes[last] + context + 1).min(ops.len());
+        let ((old_from, new_from), (old_to, new_to)) = (positions[start], positions[end]);
+        let lines = ops[start..end]
+            .iter()
+            .map(|&(op, line)| match op {
+                Op::Equal => DiffLine::Context(line.to_string()),
+                Op::Delete => DiffLine::Removed(line.to_string()),
+                Op::Insert => DiffLine::Added(line.to_string()),
+            })
+            .collect();
+        hunks.push(Hunk {
+            old_start: if old_to > old_from { old_from + 1 } else { old_from },
+            old_len: old_to - old_from,
+            new_start: if new_to > new_from { new_from + 1 } else { new_from },
+            new_len: new_to - new_from,
+            lines,
+        });
+        first = last + 1;
+    }
+    hunks
+}
+
+/// Every line of `old` and `new` in order, without line terminators.
+pub fn line_diff(old: &str, new: &str) -> Vec<DiffLine> {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    line_ops(&a, &b)
+        .into_iter()
+        .map(|(op, line)| match op {
+            Op::Equal => DiffLine::Context(line.to_string()),
+            Op::Delete => DiffLine::Removed(line.to_string()),
+            Op::Insert => DiffLine::Added(line.to_string()),
+        })
+        .collect()
+}
+
+/// Apply `hunks` to `old`, checking that context and removed lines match.
+pub fn apply_patch(old: &str, hunks: &[Hunk]) -> Result<String, String> {
+    let old = normalize_line_endings(old);
+    let lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let mut out = String::with_capacity(old.len());
+    let mut cursor = 0;
+    for hunk in hunks {
+        let start = if hunk.old_len == 0 {
+            hunk.old_start
+        } else {
+            hunk.old_start.saturating_sub(1)
+        };
+        if start < cursor || start > lines.len() {
+            return Err(format!("Hunk at old line {} is out of order", hunk.old_start));
+        }
+        lines[cursor..start].iter().for_each(|line| out.push_str(line));
+        cursor = start;
+        for line in &hunk.lines {
+            match line {
+                DiffLine::Context(text) | DiffLine::Removed(text) => {
+                    if lines.get(cursor) != Some(&text.as_str()) {
+                        return Err(format!("Hunk does not match old line {}", cursor + 1));
+                    }
+                    cursor += 1;
+                    if matches!(line, DiffLine::Context(_)) {
+                        out.push_str(text);
+                    }
+                }
+                DiffLine::Added(text) => out.push_str(text),
+            }
+        }
+    }
+    lines[cursor..].iter().for_each(|line| out.push_str(line));
+    Ok(out)
+}
+
+/// Render the changes from `old` to `new`; empty when they are equal.
+pub fn render_diff(old: &str, new: &str, format: DiffFormat) -> String {
+    render_diff_with(old, new, format, DiffOptions::default())
+}
+
+/// [`render_diff`] with explicit context and length limits.
+pub fn render_diff_with(
+    old: &str,
+    new: &str,
+    format: DiffFormat,
+    options: DiffOptions,
+) -> String {
+    if old.contains('\0') || new.contains('\0') {
+        return render_rows(&[Row::Meta("Binary content differs".to_string())], format);
+    }
+    let hunks = unified_diff(old, new, options.context);
+    if hunks.is_empty() {
+        return String::new();
+    }
+    render_rows(&elide(hunk_rows(&hunks), options.max_lines), format)
+}
+
+/// Render a whole-file change with `---`/`+++` headers, a rename note when
+/// the paths differ, and binary detection.
+pub fn render_file_diff(
+    old_path: &str,
+    new_path: &str,
+    old: &[u8],
+    new: &[u8],
+    format: DiffFormat,
+) -> String {
+    let mut rows = Vec::new();
+    if old_path != new_path {
+        rows.push(Row::Meta(format!("rename from {}", old_path)));
+        rows.push(Row::Meta(format!("rename to {}", new_path)));
+    }
+    if is_binary(old) || is_binary(new) {
+        if old != new {
+            rows.push(Row::Meta(format!(
+                "Binary files a/{} and b/{} differ",
+                old_path, new_path
+            )));
+        }
+        return render_rows(&rows, format);
+    }
+    let (old, new) = (String::from_utf8_lossy(old), String::from_utf8_lossy(new));
+    let hunks = unified_diff(&old, &new, DEFAULT_CONTEXT);
+    if !hunks.is_empty() {
+        rows.push(Row::Meta(format!("--- a/{}", old_path)));
+        rows.push(Row::Meta(format!("+++ b/{}", new_path)));
+        rows.extend(elide(hunk_rows(&hunks), DEFAULT_MAX_LINES));
+    }
+    render_rows(&rows, format)
+}
+
+/// Share of lines `old` and `new` have in common, from 0.0 to 1.0.
+pub fn similarity(old: &str, new: &str) -> f64 {
+    let old = normalize_line_endings(old);
+    let new = normalize_line_endings(new);
+    let a: Vec<&str> = old.split_inclusive('\n').collect();
+    let b: Vec<&str> = new.split_inclusive('\n').collect();
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    let common = line_ops(&a, &b).iter().filter(|(op, _)| *op == Op::Equal).count();
+    2.0 * common as f64 / (a.len() + b.len()) as f64
+}
+
+/// Pair deleted and added files `(path, content)` whose content is at least
+/// `threshold` similar, best matches first; each file is used once.
+pub fn detect_renames(
+    deleted: &[(&str, &str)],
+    added: &[(&str, &str)],
+    threshold: f64,
+) -> Vec<Rename> {
+    let mut candidates = Vec::new();
+    for (d, (from, old)) in deleted.iter().enumerate() {
+        for (a, (to, new)) in added.iter().enumerate() {
+            let score = similarity(old, new);
+            if score >= threshold {
+                candidates.push((score, d, a));
+            }
+        }
+    }
+    candidates.sort_by(|x, y| y.0.total_cmp(&x.0).then(x.1.cmp(&y.1)).then(x.2.cmp(&y.2)));
+
+    let mut used_deleted = vec![false; deleted.len()];
+    let mut used_added = vec![false; added.len()];
+    let mut renames = Vec::new();
+    for (score, d, a) in candidates {
+        if used_deleted[d] || used_added[a] {
+            continue;
+        }
+        used_deleted[d] = true;
+        used_added[a] = true;
+        renames.push(Rename {
+            from: deleted[d].0.to_string(),
+            to: added[a].0.to_string(),
+            similarity: score,
+        });
+    }
+    renames
+}
+
+/// Edit script from `a` to `b`: common prefix and suffix, and a longest
+/// common subsequence of what is between them.
+fn line_ops<'a>(a: &[&'a str], b: &[&'a str]) -> Vec<(Op, &'a str)> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (mid_a, mid_b) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut ops: Vec<(Op, &'a str)> = a[..prefix].iter().map(|&l| (Op::Equal, l)).collect();
+    if mid_a.len().saturating_mul(mid_b.len()) > MAX_TABLE_CELLS {
+        ops.extend(mid_a.iter().map(|&l| (Op::Delete, l)));
+        ops.extend(mid_b.iter().map(|&l| (Op::Insert, l)));
+    } else {
+        let mut lcs = vec![vec![0usize; mid_b.len() + 1]; mid_a.len() + 1];
+        for i in (0..mid_a.len()).rev() {
+            for j in (0..mid_b.len()).rev() {
+                lcs[i][j] = if mid_a[i] == mid_b[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < mid_a.len() && j < mid_b.len() {
+            if mid_a[i] == mid_b[j] {
+                ops.push((Op::Equal, mid_a[i]));
+                i += 1;
+                j += 1;
+            } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+                ops.push((Op::Delete, mid_a[i]));
+                i += 1;
+            } else {
+                ops.push((Op::Insert, mid_b[j]));
+                j += 1;
+            }
+        }
+        ops.extend(mid_a[i..].iter().map(|&l| (Op::Delete, l)));
+        ops.extend(mid_b[j..].iter().map(|&l| (Op::Insert, l)));
+    }
+    ops.extend(a[a.len() - suffix..].iter().map(|&l| (Op::Equal, l)));
+    ops
+}
+
+/// A rendered line before formatting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Row {
+    Header(String),
+    Context(String),
+    Removed(String),
+    Added(String),
+    Meta(String),
+    Elided(usize),
+}
+
+fn hunk_rows(hunks: &[Hunk]) -> Vec<Row> {
+    let mut rows = Vec::new();
+    for hunk in hunks {
+        rows.push(Row::Header(format!(
+            "@@ -{},{} +{},{} @@",
+            hunk.old_start, hunk.old_len, hunk.new_start, hunk.new_len
+        )));
+        for line in &hunk.lines {
+            let (DiffLine::Context(text) | DiffLine::Removed(text) | DiffLine::Added(text)) = line;
+            let body = text.strip_suffix('\n').unwrap_or(text).to_string();
+            rows.push(match line {
+                DiffLine::Context(_) => Row::Context(body),
+                DiffLine::Removed(_) => Row::Removed(body),
+                DiffLine::Added(_) => Row::Added(body),
+            });
+            if !text.ends_with('\n') {
+                rows.push(Row::Meta("\\ No newline at end of file".to_string()));
+            }
+        }
+    }
+    rows
+}
+
+/// Keep the first and last `max_lines / 2` rows and note how many were dropped.
+fn elide(mut rows: Vec<Row>, max_lines: usize) -> Vec<Row> {
+    if rows.len() <= max_lines {
+        return rows;
+    }
+    let head = max_lines / 2;
+    let tail = max_lines - head;
+    let dropped = rows.len() - head - tail;
+    let rest = rows.split_off(head + dropped);
+    rows.truncate(head);
+    rows.push(Row::Elided(dropped));
+    rows.extend(rest);
+    rows
+}
+
+fn render_rows(rows: &[Row], format: DiffFormat) -> String {
+    let mut out = String::new();
+    if format == DiffFormat::Html {
+        out.push_str("<pre class=\"diff\">\n");
+    }
+    for row in rows {
+        let (class, color, text) = match row {
+            Row::Header(text) => ("diff-hunk", "36", Cow::Borrowed(text.as_str())),
+            Row::Context(text) => ("diff-ctx", "", Cow::Owned(format!(" {}", text))),
+            Row::Removed(text) => ("diff-del", "31", Cow::Owned(format!("-{}", text))),
+            Row::Added(text) => ("diff-add", "32", Cow::Owned(format!("+{}", text))),
+            Row::Meta(text) => ("diff-meta", "2", Cow::Borrowed(text.as_str())),
+            Row::Elided(n) => {
+                ("diff-elided", "2", Cow::Owned(format!("... {} lines elided ...", n)))
+            }
+        };
+        match format {
+            DiffFormat::Plain => out.push_str(&text),
+            DiffFormat::Terminal if color.is_empty() => out.push_str(&text),
+            DiffFormat::Terminal => out.push_str(&format!("\x1b[{}m{}\x1b[0m", color, text)),
+            DiffFormat::Html => {
+                out.push_str(&format!("<span class=\"{}\">{}</span>", class, escape_html(&text)))
+            }
+        }
+        out.push('\n');
+    }
+    if format == DiffFormat::Html {
+        out.push_str("</pre>\n");
+    }
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD: &str = "fn main() {\n    let x = 1;\n    println!(\"{}\", x);\n}\n";
+    const NEW: &str = "fn main() {\n    let x = 2;\n    println!(\"{}\", x);\n}\n";
+
+    #[test]
+    fn test_plain_golden() {
+        let expected = "\
+@@ -1,4 +1,4 @@
+ fn main() {
+-    let x = 1;
++    let x = 2;
+     println!(\"{}\", x);
+ }
+";
+        assert_eq!(render_diff(OLD, NEW, DiffFormat::Plain), expected);
+        assert_eq!(render_diff(OLD, OLD, DiffFormat::Plain), "");
+    }
+
+    #[test]
+    fn test_terminal_golden() {
+        let expected = "\
+\x1b[36m@@ -1,2 +1,2 @@\x1b[0m
+\x1b[31m-a\x1b[0m
+\x1b[32m+b\x1b[0m
+ c
+";
+        assert_eq!(render_diff("a\nc\n", "b\nc\n", DiffFormat::Terminal), expected);
+    }
+
+    #[test]
+    fn test_html_golden() {
+        let expected = "\
+<pre class=\"diff\">
+<span class=\"diff-hunk\">@@ -1,1 +1,1 @@</span>
+<span class=\"diff-del\">-if a &lt; b &amp;&amp; c</span>
+<span class=\"diff-add\">+if a &gt; &quot;b&quot;</span>
+<span class=\"diff-meta\">\\ No newline at end of file</span>
+</pre>
+";
+        assert_eq!(render_diff("if a < b && c\n", "if a > \"b\"", DiffFormat::Html), expected);
+    }
+
+    #[test]
+    fn test_hunks_split_beyond_context() {
+        let old: String = (1..=20).map(|i| format!("{}\n", i)).collect();
+        let new: String = (1..=20)
+            .map(|i| match i {
+                2 => "two\n".to_string(),
+                19 => "nineteen\n".to_string(),
+                i => format!("{}\n", i),
+            })
+            .collect();
+        let hunks = unified_diff(&old, &new, 3);
+        assert_eq!(hunks.len(), 2);
+        assert_eq!((hunks[0].old_start, hunks[0].old_len), (1, 5));
+        assert_eq!((hunks[1].old_start, hunks[1].old_len), (16, 5));
+        assert_eq!(apply_patch(&old, &hunks).unwrap(), new);
+        assert_eq!(unified_diff(&old, &new, 10).len(), 1);
+    }
+
+    #[test]
+    fn test_insert_at_top_and_crlf() {
+        let hunks = unified_diff("b\r\nc\r\n", "a\nb\nc\n", 0);
+        assert_eq!(hunks.len(), 1);
+        assert_eq!((hunks[0].old_start, hunks[0].old_len), (0, 0));
+        assert_eq!((hunks[0].new_start, hunks[0].new_len), (1, 1));
+        assert_eq!(apply_patch("b\nc\n", &hunks).unwrap(), "a\nb\nc\n");
+        assert!(apply_patch("x\ny\n", &unified_diff("b\nc\n", "b\nd\n", 1)).is_err());
+    }
+
+    #[test]
+    fn test_long_diffs_elide_the_middle() {
+        let new: String = (0..500).map(|i| format!("{}\n", i)).collect();
+        let options = DiffOptions { context: 3, max_lines: 10 };
+        let rendered = render_diff_with("", &new, DiffFormat::Plain, options);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines.len(), 11);
+        assert_eq!(lines[0], "@@ -0,0 +1,500 @@");
+        assert_eq!(lines[5], "... 491 lines elided ...");
+        assert_eq!(lines[10], "+499");
+    }
+
+    #[test]
+    fn test_binary_and_renames() {
+        let (old_png, new_png) = (b"\x89PNG\0\x01", b"\x89PNG\0\x02");
+        let rendered = render_file_diff("a.png", "a.png", old_png, new_png, DiffFormat::Plain);
+        assert_eq!(rendered, "Binary files a/a.png and b/a.png differ\n");
+        assert!(is_binary(&[0xff, 0xfe]));
+        assert!(!is_binary("héllo".as_bytes()));
+
+        let same = OLD.as_bytes();
+        let renamed = render_file_diff("src/a.rs", "src/b.rs", same, same, DiffFormat::Plain);
+        assert_eq!(renamed, "rename from src/a.rs\nrename to src/b.rs\n");
+
+        let renames = detect_renames(
+            &[("src/old.rs", OLD), ("src/gone.rs", "unrelated\n")],
+            &[("src/other.rs", "x\ny\n"), ("src/new.rs", NEW)],
+            RENAME_THRESHOLD,
+        );
+        assert_eq!(renames.len(), 1);
+        assert_eq!(renames[0].from, "src/old.rs");
+        assert_eq!(renames[0].to, "src/new.rs");
+        assert!((renames[0].similarity - 0.75).abs() < 1e-9);
+    }
+}
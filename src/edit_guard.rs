@@ -0,0 +1,557 @@
+//! Steering the agent from whole-file rewrites to targeted edits.
+//!
+//! Models tend to rewrite a 900-line file to change one function, which is
+//! slow, costs tokens and risks dropping code they did not mean to touch. The
+//! [`EditGuard`] sits in the file tool: a `write` that replaces an existing
+//! source file of at least [`EditGuard::min_lines`] lines is refused softly
+//! unless it changes more than [`EditGuard::rewrite_ratio`] of the file. The
+//! refusal carries the file's outline (items with their line ranges) and asks
+//! for the `edit` operation on the part that changes.
+//!
+//! Outlines are extracted line by line, without a parser, for Rust,
+//! TypeScript/JavaScript, Python and Go. Files in other languages, and
+//! languages left out of [`EditGuard::languages`], are never refused.
+
+use crate::language::Language;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// Languages an outline can be extracted for.
+pub const OUTLINE_LANGUAGES: &[Language] = &[
+    Language::Rust,
+    Language::TypeScript,
+    Language::JavaScript,
+    Language::Python,
+    Language::Go,
+];
+
+/// Default size, in lines, from which rewrites are questioned.
+pub const DEFAULT_MIN_LINES: usize = 300;
+
+/// Default share of changed lines above which a write counts as a rewrite.
+pub const DEFAULT_REWRITE_RATIO: f64 = 0.5;
+
+/// Most outline items included in a refusal.
+const OUTLINE_MAX_ITEMS: usize = 80;
+
+/// Parse the guarded languages: `off`, `all`, or a comma-separated list.
+pub fn parse_languages(value: &str) -> Result<Vec<Language>, String> {
+    let value = value.trim().to_lowercase();
+    match value.as_str() {
+        "" | "off" | "none" | "false" => return Ok(Vec::new()),
+        "all" | "on" | "true" => return Ok(OUTLINE_LANGUAGES.to_vec()),
+        _ => {}
+    }
+
+    let mut languages = Vec::new();
+    for name in value.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+        let language = Language::parse(name);
+        if !OUTLINE_LANGUAGES.contains(&language) {
+            return Err(format!("No outline support for '{}'", name));
+        }
+        if !languages.contains(&language) {
+            languages.push(language);
+        }
+    }
+    Ok(languages)
+}
+
+// =============================================================================
+// Outline
+// =============================================================================
+
+/// One item of a file's outline (1-based, inclusive line range).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OutlineItem {
+    /// `fn`, `struct`, `impl`, `class`, `def`, `func`, `type`, ...
+    pub kind: &'static str,
+    pub name: String,
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+/// Outline of `source`, or `None` when the language has no outline support.
+pub fn outline(language: Language, source: &str) -> Option<Vec<OutlineItem>> {
+    let lines: Vec<&str> = source.lines().collect();
+    match language {
+        Language::Rust => Some(brace_outline(&lines, false, rust_item)),
+        Language::TypeScript | Language::JavaScript => Some(brace_outline(&lines, true, js_item)),
+        Language::Go => Some(brace_outline(&lines, false, go_item)),
+        Language::Python => Some(python_outline(&lines)),
+        _ => None,
+    }
+}
+
+/// Outline of a brace-delimited language.
+///
+/// `detect` sees each trimmed line with the brace depth at its start and
+/// names the item the line opens, if any. An item ends where its braces
+/// close, or on its own line when it ends with `;` before any brace opens.
+fn brace_outline(
+    lines: &[&str],
+    single_quoted_strings: bool,
+    detect: fn(&str, usize) -> Option<(&'static str, String)>,
+) -> Vec<OutlineItem> {
+    let braces: Vec<Vec<char>> = lines
+        .iter()
+        .map(|line| braces_in(line, single_quoted_strings))
+        .collect();
+    let mut items = Vec::new();
+    let mut depth = 0usize;
+    for (i, line) in lines.iter().enumerate() {
+        if let Some((kind, name)) = detect(line.trim(), depth) {
+            items.push(OutlineItem {
+                kind,
+                name,
+                start_line: i + 1,
+                end_line: block_end(lines, &braces, i, depth) + 1,
+            });
+        }
+        for brace in &braces[i] {
+            match brace {
+                '{' => depth += 1,
+                _ => depth = depth.saturating_sub(1),
+            }
+        }
+    }
+    items
+}
+
+/// Index of the line that closes the block opened at or after line `start`.
+fn block_end(lines: &[&str], braces: &[Vec<char>], start: usize, depth: usize) -> usize {
+    let mut current = depth;
+    let mut opened = false;
+    for (j, line_braces) in braces.iter().enumerate().skip(start) {
+        for brace in line_braces {
+            if *brace == '{' {
+                current += 1;
+                opened = true;
+            } else {
+                current = current.saturating_sub(1);
+                if opened && current == depth {
+                    return j;
+                }
+            }
+        }
+        if !opened && lines[j].trim_end().ends_with(';') {
+            return j;
+        }
+    }
+    lines.len().saturating_sub(1)
+}
+
+/// Braces of a line in order, skipping string and char literals and `//`
+/// comments. Without `single_quoted_strings`, a `'` opens a literal only when
+/// it looks like a char (`'x'`, `'\n'`), not a Rust lifetime.
+fn braces_in(line: &str, single_quoted_strings: bool) -> Vec<char> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut braces = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match quote {
+            Some(_) if c == '\\' => i += 1,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None => match c {
+                '"' | '`' => quote = Some(c),
+                '\'' if single_quoted_strings || next == Some('\\') || chars.get(i + 2) == Some(&'\'') => {
+                    quote = Some(c)
+                }
+                '/' if next == Some('/') => break,
+                '{' | '}' => braces.push(c),
+                _ => {}
+            },
+        }
+        i += 1;
+    }
+    braces
+}
+
+/// Leading identifier of `s`.
+fn ident(s: &str) -> Option<String> {
+    let name: String = s
+        .trim_start()
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '$')
+        .collect();
+    (!name.is_empty()).then_some(name)
+}
+
+/// Strip any of `prefixes` from the front of `line`, repeatedly.
+fn strip_modifiers<'a>(mut line: &'a str, prefixes: &[&str]) -> &'a str {
+    while let Some(rest) = prefixes.iter().find_map(|p| line.strip_prefix(p)) {
+        line = rest.trim_start();
+    }
+    line
+}
+
+fn rust_item(line: &str, depth: usize) -> Option<(&'static str, String)> {
+    let rest = strip_modifiers(
+        line,
+        &["pub(crate) ", "pub(super) ", "pub ", "async ", "const ", "unsafe ", "extern \"C\" "],
+    );
+    if let Some(rest) = rest.strip_prefix("fn ") {
+        return Some(("fn", ident(rest)?));
+    }
+    if depth > 0 {
+        return None;
+    }
+    for kind in ["struct", "enum", "trait", "mod", "union"] {
+        if let Some(rest) = rest.strip_prefix(kind).and_then(|r| r.strip_prefix(' ')) {
+            return Some((kind, ident(rest)?));
+        }
+    }
+    if let Some(rest) = rest.strip_prefix("macro_rules! ") {
+        return Some(("macro", ident(rest)?));
+    }
+    if rest.starts_with("impl ") || rest.starts_with("impl<") {
+        let header = rest.trim_end_matches('{').trim();
+        return Some(("impl", header.strip_prefix("impl").unwrap_or(header).trim().to_string()));
+    }
+    None
+}
+
+fn js_item(line: &str, depth: usize) -> Option<(&'static str, String)> {
+    let rest = strip_modifiers(line, &["export ", "default ", "declare ", "abstract ", "async "]);
+    if depth == 0 {
+        if let Some(rest) = rest.strip_prefix("function") {
+            return Some(("function", ident(rest.trim_start_matches('*'))?));
+        }
+        for kind in ["class", "interface", "enum", "type"] {
+            if let Some(rest) = rest.strip_prefix(kind).and_then(|r| r.strip_prefix(' ')) {
+                return Some((kind, ident(rest)?));
+            }
+        }
+        for binding in ["const ", "let ", "var "] {
+            if let Some(rest) = rest.strip_prefix(binding) {
+                let value = rest.split_once('=')?.1.trim_start();
+                let is_function = value.starts_with("function")
+                    || value.starts_with("async")
+                    || (value.starts_with('(') && line.contains("=>"));
+                if !is_function {
+                    return None;
+                }
+                return Some(("function", ident(rest)?));
+            }
+        }
+        return None;
+    }
+    // Class methods: `name(args) {`, after any modifiers
+    if depth == 1 && line.ends_with('{') {
+        let rest = strip_modifiers(
+            rest,
+            &["public ", "private ", "protected ", "static ", "readonly ", "get ", "set ", "async "],
+        );
+        let name = ident(rest)?;
+        let after = rest[name.len()..].trim_start();
+        let keyword = ["if", "for", "while", "switch", "catch", "return", "function", "else"];
+        if (after.starts_with('(') || after.starts_with('<')) && !keyword.contains(&name.as_str()) {
+            return Some(("method", name));
+        }
+    }
+    None
+}
+
+fn go_item(line: &str, depth: usize) -> Option<(&'static str, String)> {
+    if depth > 0 {
+        return None;
+    }
+    if let Some(rest) = line.strip_prefix("func ") {
+        // Methods: `func (r *Receiver) Name(`
+        let rest = match rest.strip_prefix('(') {
+            Some(receiver) => receiver.split_once(')')?.1,
+            None => rest,
+        };
+        return Some(("func", ident(rest)?));
+    }
+    let rest = line.strip_prefix("type ")?;
+    Some(("type", ident(rest)?))
+}
+
+fn python_outline(lines: &[&str]) -> Vec<OutlineItem> {
+    let indent_of = |line: &str| {
+        line.chars()
+            .take_while(|c| c.is_whitespace())
+            .map(|c| if c == '\t' { 4 } else { 1 })
+            .sum::<usize>()
+    };
+    let is_code = |line: &str| {
+        let trimmed = line.trim();
+        !trimmed.is_empty() && !trimmed.starts_with('#')
+    };
+
+    let mut items = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let indent = indent_of(line);
+        // Top-level items and methods of top-level classes
+        if indent > 4 {
+            continue;
+        }
+        let trimmed = line.trim_start();
+        let (kind, rest) = if let Some(rest) = trimmed.strip_prefix("class ") {
+            ("class", rest)
+        } else if let Some(rest) = trimmed.strip_prefix("def ").or_else(|| trimmed.strip_prefix("async def ")) {
+            ("def", rest)
+        } else {
+            continue;
+        };
+        let Some(name) = ident(rest) else { continue };
+
+        let mut end = i;
+        for (j, next) in lines.iter().enumerate().skip(i + 1) {
+            if !is_code(next) {
+                continue;
+            }
+            if indent_of(next) <= indent {
+                break;
+            }
+            end = j;
+        }
+        items.push(OutlineItem {
+            kind,
+            name,
+            start_line: i + 1,
+            end_line: end + 1,
+        });
+    }
+    items
+}
+
+// =============================================================================
+// Policy
+// =============================================================================
+
+/// Share of lines a write changes, from 0.0 (identical) to 1.0 (nothing in common).
+pub fn change_ratio(old: &str, new: &str) -> f64 {
+    1.0 - crate::diff::similarity(old, new)
+}
+
+/// When a `write` over an existing file is refused in favor of `edit`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditGuard {
+    /// Smallest existing file, in lines, whose rewrites are questioned (0 = never)
+    pub min_lines: usize,
+    /// Writes changing more than this share of the file are genuine rewrites
+    pub rewrite_ratio: f64,
+    /// Languages the guard applies to
+    pub languages: Vec<Language>,
+}
+
+impl Default for EditGuard {
+    fn default() -> Self {
+        Self {
+            min_lines: DEFAULT_MIN_LINES,
+            rewrite_ratio: DEFAULT_REWRITE_RATIO,
+            languages: OUTLINE_LANGUAGES.to_vec(),
+        }
+    }
+}
+
+impl EditGuard {
+    /// Whether the guard can refuse anything.
+    pub fn is_enabled(&self) -> bool {
+        self.min_lines > 0 && !self.languages.is_empty()
+    }
+
+    /// The refusal for writing `new` over `old` at `rel_path`, or `None` to
+    /// let the write through.
+    pub fn check(&self, rel_path: &str, old: &str, new: &str) -> Option<Value> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let language = crate::smoke::language_of(rel_path).filter(|l| self.languages.contains(l))?;
+        let lines = old.lines().count();
+        if lines < self.min_lines {
+            return None;
+        }
+        let ratio = change_ratio(old, new);
+        if ratio > self.rewrite_ratio {
+            return None;
+        }
+        let items = outline(language, old)?;
+        Some(refusal(rel_path, lines, ratio, &items))
+    }
+}
+
+/// Soft refusal of a write: what the file holds, and how to edit it instead.
+pub fn refusal(rel_path: &str, lines: usize, ratio: f64, items: &[OutlineItem]) -> Value {
+    let mut response = json!({
+        "success": false,
+        "operation": "write",
+        "path": rel_path,
+        "refused": "use_edit",
+        "message": format!(
+            "{} has {} lines and this write changes only {:.0}% of them. Do not rewrite the \
+             whole file: use the 'edit' operation with 'old_text' (the exact lines to replace, \
+             unique in the file) and 'new_text'. The outline below shows where each item is.",
+            rel_path,
+            lines,
+            ratio * 100.0
+        ),
+        "lines": lines,
+        "change_ratio": (ratio * 100.0).round() / 100.0,
+        "outline": items.iter().take(OUTLINE_MAX_ITEMS).collect::<Vec<_>>(),
+    });
+    if items.len() > OUTLINE_MAX_ITEMS {
+        response["outline_truncated"] = json!(items.len() - OUTLINE_MAX_ITEMS);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(items: &[OutlineItem]) -> Vec<(&str, &str, usize, usize)> {
+        items
+            .iter()
+            .map(|i| (i.kind, i.name.as_str(), i.start_line, i.end_line))
+            .collect()
+    }
+
+    #[test]
+    fn test_rust_outline() {
+        let source = r#"use std::fmt;
+
+pub struct Parser {
+    input: String,
+}
+
+impl Parser {
+    pub fn new(input: &str) -> Self {
+        let open = '{';
+        Self { input: format!("{}{{", input) }
+    }
+
+    async fn parse(&self) {}
+}
+
+pub(crate) enum Token { Num, Op }
+mod lexer;
+"#;
+        assert_eq!(
+            names(&outline(Language::Rust, source).unwrap()),
+            vec![
+                ("struct", "Parser", 3, 5),
+                ("impl", "Parser", 7, 14),
+                ("fn", "new", 8, 11),
+                ("fn", "parse", 13, 13),
+                ("enum", "Token", 16, 16),
+                ("mod", "lexer", 17, 17),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_typescript_outline() {
+        let source = r#"import { x } from "./x";
+
+export interface Options {
+  verbose: boolean;
+}
+
+export async function run(opts: Options): Promise<void> {
+  if (opts.verbose) {
+    console.log("{");
+  }
+}
+
+export class Calculator {
+  private total = 0;
+
+  add(n: number): this {
+    this.total += n;
+    return this;
+  }
+}
+
+const square = (n: number) => {
+  return n * n;
+};
+"#;
+        assert_eq!(
+            names(&outline(Language::TypeScript, source).unwrap()),
+            vec![
+                ("interface", "Options", 3, 5),
+                ("function", "run", 7, 11),
+                ("class", "Calculator", 13, 20),
+                ("method", "add", 16, 19),
+                ("function", "square", 22, 24),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_python_outline() {
+        let source = "import os\n\nclass Store:\n    def __init__(self):\n        self.items = []\n\n    # helpers\n    async def load(self, path):\n        return path\n\n\ndef main():\n    Store()\n";
+        assert_eq!(
+            names(&outline(Language::Python, source).unwrap()),
+            vec![
+                ("class", "Store", 3, 9),
+                ("def", "__init__", 4, 5),
+                ("def", "load", 8, 9),
+                ("def", "main", 12, 13),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_go_outline() {
+        let source = "package main\n\ntype Server struct {\n\tport int\n}\n\nfunc (s *Server) Start() error {\n\treturn nil\n}\n\nfunc main() {\n\tfmt.Println(\"}\")\n}\n";
+        assert_eq!(
+            names(&outline(Language::Go, source).unwrap()),
+            vec![("type", "Server", 3, 5), ("func", "Start", 7, 9), ("func", "main", 11, 13)]
+        );
+        assert!(outline(Language::Java, "class A {}").is_none());
+    }
+
+    #[test]
+    fn test_change_ratio() {
+        let old: String = (0..10).map(|i| format!("line {}\n", i)).collect();
+        assert_eq!(change_ratio(&old, &old), 0.0);
+        let one_changed = old.replace("line 3\n", "line three\n");
+        assert!((change_ratio(&old, &one_changed) - 0.1).abs() < 1e-9);
+        assert_eq!(change_ratio(&old, "something else\n"), 1.0);
+    }
+
+    #[test]
+    fn test_guard_refuses_small_changes_to_large_files() {
+        let guard = EditGuard {
+            min_lines: 20,
+            ..EditGuard::default()
+        };
+        let old: String = (0..30).map(|i| format!("fn f{}() {{}}\n", i)).collect();
+        let tweak = old.replace("fn f7() {}", "fn f7() { todo!() }");
+
+        let refusal = guard.check("src/lib.rs", &old, &tweak).unwrap();
+        assert_eq!(refusal["success"], false);
+        assert_eq!(refusal["refused"], "use_edit");
+        assert_eq!(refusal["lines"], 30);
+        assert_eq!(refusal["change_ratio"], 0.03);
+        assert_eq!(refusal["outline"][7], json!({"kind": "fn", "name": "f7", "start_line": 8, "end_line": 8}));
+        assert!(refusal["message"].as_str().unwrap().contains("'edit' operation"));
+
+        // Genuine rewrites, small files, unsupported or disabled languages pass
+        let rewrite: String = (0..30).map(|i| format!("fn g{}() {{}}\n", i)).collect();
+        assert!(guard.check("src/lib.rs", &old, &rewrite).is_none());
+        assert!(guard.check("src/lib.rs", &old[..100], &tweak[..100]).is_none());
+        assert!(guard.check("notes.txt", &old, &tweak).is_none());
+        let python_only = EditGuard {
+            languages: vec![Language::Python],
+            ..guard.clone()
+        };
+        assert!(python_only.check("src/lib.rs", &old, &tweak).is_none());
+        assert!(!EditGuard { min_lines: 0, ..guard }.is_enabled());
+    }
+
+    #[test]
+    fn test_parse_languages() {
+        assert!(parse_languages("off").unwrap().is_empty());
+        assert_eq!(parse_languages("all").unwrap(), OUTLINE_LANGUAGES.to_vec());
+        assert_eq!(parse_languages("rust, py").unwrap(), vec![Language::Rust, Language::Python]);
+        assert!(parse_languages("java").is_err());
+    }
+}
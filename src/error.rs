@@ -1,6 +1,7 @@
 //! Error types for the Ralph multi-agent system.
 
 use crate::models::config::ValidationError;
+use crate::provider_error::{ProviderError, ProviderErrorKind};
 use thiserror::Error;
 
 /// Result type alias for Ralph operations.
@@ -53,6 +54,14 @@ pub enum RalphError {
     #[error("Agent error ({agent}): {message}")]
     Agent { agent: String, message: String },
 
+    /// The model returned nothing but whitespace
+    #[error("Empty response from {agent}: {detail}")]
+    EmptyResponse { agent: String, detail: String },
+
+    /// A model call did not finish within its time limit and was cancelled
+    #[error("{agent} model call cancelled after {}: no complete response within the time limit", crate::timefmt::duration(*elapsed))]
+    Timeout { agent: String, elapsed: std::time::Duration },
+
     /// Tool execution error
     #[error("Tool error ({tool}): {message}")]
     Tool { tool: String, message: String },
@@ -65,6 +74,10 @@ pub enum RalphError {
     #[error("All remaining tasks are blocked: {reason}")]
     AllTasksBlocked { reason: String },
 
+    /// The project did not build right after its setup task
+    #[error("Project scaffold does not build after {task_id}: {output}")]
+    ScaffoldBuild { task_id: String, output: String },
+
     /// Serialization/deserialization error
     #[error("Serialization error: {0}")]
     Serialization(String),
@@ -104,6 +117,16 @@ impl RalphError {
         }
     }
 
+    /// Error for a failed agent stream: [`RalphError::Timeout`] when the model
+    /// call was cancelled by its time limit, else [`RalphError::Agent`].
+    pub fn agent_stream(agent: impl Into<String>, msg: impl Into<String>) -> Self {
+        let (agent, message) = (agent.into(), msg.into());
+        match crate::model_timeout::elapsed_from_message(&message) {
+            Some(elapsed) => RalphError::Timeout { agent, elapsed },
+            None => RalphError::Agent { agent, message },
+        }
+    }
+
     /// Create a tool error.
     pub fn tool(tool: impl Into<String>, msg: impl Into<String>) -> Self {
         RalphError::Tool {
@@ -111,6 +134,67 @@ impl RalphError {
             message: msg.into(),
         }
     }
+
+    /// The provider failure behind a model or agent error, when it is recognised.
+    pub fn provider_error(&self) -> Option<ProviderError> {
+        match self {
+            RalphError::Model { provider, message } => ProviderError::decode(provider, message),
+            RalphError::Agent { message, .. } => ProviderError::decode_any(message),
+            _ => None,
+        }
+    }
+
+    /// Whether the failed operation may succeed if simply retried later.
+    ///
+    /// Recognised provider failures decide for themselves (a 429 for
+    /// exhausted quota is final); otherwise a 429 or 5xx status is retryable
+    /// and any other failure is not.
+    pub fn is_retryable(&self) -> bool {
+        if matches!(self, RalphError::EmptyResponse { .. } | RalphError::Timeout { .. }) {
+            return true;
+        }
+        if let Some(provider_error) = self.provider_error() {
+            return provider_error.retryable;
+        }
+        match self {
+            RalphError::Model { message, .. } | RalphError::Agent { message, .. } => {
+                crate::provider_error::http_status(message).is_some_and(|status| status == 429 || status >= 500)
+            }
+            _ => false,
+        }
+    }
+
+    /// Message for the user, with the next step for recognised provider failures.
+    pub fn user_message(&self) -> String {
+        match self.provider_error() {
+            Some(provider_error) => provider_error.to_string(),
+            None => self.to_string(),
+        }
+    }
+
+    /// Process exit code for this error.
+    ///
+    /// - `2`: configuration, including an unknown model or rejected API key
+    /// - `3`: the provider refused the work (quota exhausted, safety block)
+    /// - `4`: the project scaffold does not build after its setup task
+    /// - `75`: temporary provider failure, empty response or model call timeout,
+    ///   retry later (`EX_TEMPFAIL`)
+    /// - `1`: anything else
+    pub fn exit_code(&self) -> i32 {
+        if let Some(provider_error) = self.provider_error() {
+            return match provider_error.kind {
+                ProviderErrorKind::ModelNotFound | ProviderErrorKind::Authentication => 2,
+                ProviderErrorKind::QuotaExhausted | ProviderErrorKind::SafetyBlocked => 3,
+                ProviderErrorKind::Overloaded | ProviderErrorKind::RateLimited => 75,
+            };
+        }
+        match self {
+            RalphError::Configuration(_) | RalphError::ConfigValidation(_) => 2,
+            RalphError::ScaffoldBuild { .. } => 4,
+            RalphError::EmptyResponse { .. } | RalphError::Timeout { .. } => 75,
+            _ => 1,
+        }
+    }
 }
 
 impl From<std::io::Error> for RalphError {
@@ -150,4 +234,47 @@ mod tests {
         let err = RalphError::agent("PRD", "Failed to generate");
         assert!(matches!(err, RalphError::Agent { .. }));
     }
+
+    #[test]
+    fn test_provider_failures_map_to_exit_codes() {
+        let overloaded = RalphError::agent(
+            "ralph-loop",
+            r#"HTTP 529: {"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#,
+        );
+        assert!(overloaded.is_retryable());
+        assert_eq!(overloaded.exit_code(), 75);
+        assert!(overloaded.user_message().starts_with("Anthropic is overloaded"));
+
+        let quota = RalphError::model(
+            "openai",
+            r#"{"error":{"message":"You exceeded your current quota","code":"insufficient_quota"}}"#,
+        );
+        assert!(!quota.is_retryable());
+        assert_eq!(quota.exit_code(), 3);
+
+        assert_eq!(RalphError::config("bad provider").exit_code(), 2);
+        let opaque = RalphError::agent("prd", "connection reset");
+        assert_eq!(opaque.exit_code(), 1);
+        assert_eq!(opaque.user_message(), opaque.to_string());
+
+        let empty = RalphError::EmptyResponse {
+            agent: "architect".to_string(),
+            detail: "finish reason MaxTokens".to_string(),
+        };
+        assert!(empty.is_retryable());
+        assert_eq!(empty.exit_code(), 75);
+    }
+
+    #[test]
+    fn test_unrecognised_http_statuses_are_classified() {
+        let retryable = ["Model error: HTTP 503: Service Unavailable", "HTTP 429 Too Many Requests", "HTTP 500"];
+        for message in retryable {
+            assert!(RalphError::agent("architect", message).is_retryable(), "{}", message);
+        }
+        let fatal = ["HTTP 401: Unauthorized", "HTTP 400: Bad Request", "connection reset"];
+        for message in fatal {
+            assert!(!RalphError::model("gemini", message).is_retryable(), "{}", message);
+        }
+        assert!(!RalphError::Task("HTTP 503".to_string()).is_retryable());
+    }
 }
@@ -0,0 +1,158 @@
+//! Completion gates: the loop's own evidence that a task is done.
+//!
+//! The developer model says when it thinks a task is finished, but the task
+//! list only changes on evidence Ralph gathered itself. A [`CompletionGates`]
+//! is shared by the task and test tools of one loop. The task tool starts it
+//! when a task goes in progress, the test tool records every test run, and a
+//! `complete` (or `update_status` to completed) is only written to
+//! `tasks.json` when the gates pass. Otherwise the task stays in progress,
+//! and the tool response tells the model why. That response is the
+//! corrective message the model sees next in the session.
+//!
+//! Gates:
+//!
+//! - **Tests**: the test suite ran after the task started, and the latest run
+//!   passed (`verify_completion`, on by default).
+
+use serde::Serialize;
+use std::sync::Mutex;
+
+/// Outcome of the gates for one completion claim.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GateVerdict {
+    /// Task whose completion was claimed
+    pub task_id: String,
+    /// Whether every gate passed
+    pub passed: bool,
+    /// Why the claim was rejected, one entry per failed gate
+    pub failures: Vec<String>,
+}
+
+impl GateVerdict {
+    /// Message returned to the model when the claim is rejected.
+    pub fn corrective_message(&self) -> String {
+        format!(
+            "{}: {}; the task is not complete. Run the tests with the `test` tool, fix any \
+             failures, commit, and call complete again.",
+            self.task_id,
+            self.failures.join("; ")
+        )
+    }
+}
+
+#[derive(Debug, Default)]
+struct GateState {
+    /// Task the evidence belongs to
+    task_id: Option<String>,
+    /// Result of the latest test run since the task started
+    last_test_run: Option<bool>,
+}
+
+/// Evidence gathered during the current task.
+#[derive(Debug, Default)]
+pub struct CompletionGates {
+    /// Require a passing test run before completion
+    require_tests: bool,
+    state: Mutex<GateState>,
+}
+
+impl CompletionGates {
+    /// Create gates; with `require_tests` off every claim passes.
+    pub fn new(require_tests: bool) -> Self {
+        Self {
+            require_tests,
+            state: Mutex::new(GateState::default()),
+        }
+    }
+
+    /// Start collecting evidence for a task, discarding earlier evidence.
+    pub fn begin(&self, task_id: impl Into<String>) {
+        *self.state.lock().unwrap() = GateState {
+            task_id: Some(task_id.into()),
+            last_test_run: None,
+        };
+    }
+
+    /// Forget all evidence (the task completed).
+    pub fn clear(&self) {
+        *self.state.lock().unwrap() = GateState::default();
+    }
+
+    /// Task evidence is being collected for, if any.
+    pub fn task_id(&self) -> Option<String> {
+        self.state.lock().unwrap().task_id.clone()
+    }
+
+    /// Record the verdict of a test run.
+    pub fn record_test_run(&self, passed: bool) {
+        self.state.lock().unwrap().last_test_run = Some(passed);
+    }
+
+    /// Decide whether `task_id` may be marked completed.
+    pub fn evaluate(&self, task_id: &str) -> GateVerdict {
+        let state = self.state.lock().unwrap();
+        let mut failures = Vec::new();
+        if self.require_tests {
+            let other_task = state.task_id.as_deref().filter(|id| *id != task_id);
+            match (other_task, state.last_test_run) {
+                (Some(other), _) => failures.push(format!(
+                    "tests have not been run for {} (the evidence collected is for {})",
+                    task_id, other
+                )),
+                (None, None) => {
+                    failures.push("tests have not been run since the task started".to_string())
+                }
+                (None, Some(false)) => {
+                    failures.push("tests have not passed (the last test run failed)".to_string())
+                }
+                (None, Some(true)) => {}
+            }
+        }
+        GateVerdict {
+            task_id: task_id.to_string(),
+            passed: failures.is_empty(),
+            failures,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tests_gate_needs_a_passing_run_since_the_task_started() {
+        let gates = CompletionGates::new(true);
+        gates.begin("T-001");
+        let verdict = gates.evaluate("T-001");
+        assert!(!verdict.passed);
+        assert_eq!(verdict.failures, vec!["tests have not been run since the task started"]);
+
+        gates.record_test_run(false);
+        let verdict = gates.evaluate("T-001");
+        assert!(!verdict.passed);
+        assert!(verdict.corrective_message().starts_with(
+            "T-001: tests have not passed (the last test run failed); the task is not complete."
+        ));
+
+        gates.record_test_run(true);
+        assert!(gates.evaluate("T-001").passed);
+
+        // A passing run for one task is no evidence for another
+        let verdict = gates.evaluate("T-002");
+        assert!(!verdict.passed);
+        assert!(verdict.failures[0].contains("evidence collected is for T-001"));
+
+        // Starting the next task discards the evidence
+        gates.begin("T-002");
+        assert!(!gates.evaluate("T-002").passed);
+    }
+
+    #[test]
+    fn test_gates_off_accept_every_claim() {
+        let gates = CompletionGates::new(false);
+        assert!(gates.evaluate("T-001").passed);
+        gates.record_test_run(false);
+        assert!(gates.evaluate("T-001").passed);
+    }
+}
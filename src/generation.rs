@@ -0,0 +1,123 @@
+//! Per-agent sampling settings.
+//!
+//! [`ModelConfig::temperature`](crate::models::ModelConfig::temperature) and
+//! [`ModelConfig::max_tokens`](crate::models::ModelConfig::max_tokens) default
+//! to 0.7 and 4096 and can be overridden per agent. [`GenerationModel`] wraps
+//! a model so that every request carries them in its generation config,
+//! which each provider client maps to its own parameters (`temperature` and
+//! `max_tokens` for Anthropic and OpenAI, `generationConfig` for Gemini).
+//!
+//! Values a request already carries are replaced; other generation settings,
+//! such as a response schema, are kept.
+
+use crate::models::ModelConfig;
+use adk_rust::{GenerateContentConfig, Llm, LlmRequest, LlmResponseStream, Result as AdkResult};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// A model whose requests use a configured temperature and response length.
+pub struct GenerationModel {
+    inner: Arc<dyn Llm>,
+    temperature: f32,
+    max_tokens: usize,
+}
+
+impl GenerationModel {
+    /// `model` with the temperature and max tokens of `config`.
+    pub fn wrap(model: Arc<dyn Llm>, config: &ModelConfig) -> Arc<dyn Llm> {
+        Arc::new(Self {
+            inner: model,
+            temperature: config.temperature,
+            max_tokens: config.max_tokens,
+        })
+    }
+
+    fn apply(&self, request: &mut LlmRequest) {
+        let generation = request.config.get_or_insert_with(GenerateContentConfig::default);
+        generation.temperature = Some(self.temperature);
+        generation.max_output_tokens = Some(i32::try_from(self.max_tokens).unwrap_or(i32::MAX));
+    }
+}
+
+impl std::fmt::Debug for GenerationModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GenerationModel")
+            .field("model", &self.inner.name())
+            .field("temperature", &self.temperature)
+            .field("max_tokens", &self.max_tokens)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl Llm for GenerationModel {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    async fn generate_content(
+        &self,
+        mut request: LlmRequest,
+        stream: bool,
+    ) -> AdkResult<LlmResponseStream> {
+        self.apply(&mut request);
+        self.inner.generate_content(request, stream).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adk_rust::{Content, LlmResponse};
+    use std::sync::Mutex;
+
+    /// Records the generation config of every request it receives.
+    #[derive(Default)]
+    struct RecordingModel {
+        seen: Mutex<Vec<Option<GenerateContentConfig>>>,
+    }
+
+    #[async_trait]
+    impl Llm for RecordingModel {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        async fn generate_content(
+            &self,
+            request: LlmRequest,
+            _stream: bool,
+        ) -> AdkResult<LlmResponseStream> {
+            self.seen.lock().unwrap().push(request.config);
+            let reply = LlmResponse::new(Content::new("model").with_text("ok"));
+            Ok(Box::pin(futures::stream::once(async move { Ok(reply) })))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_configured_values_reach_the_request() {
+        let inner = Arc::new(RecordingModel::default());
+        let config = ModelConfig::default().with_temperature(0.2).with_max_tokens(8192);
+        let model = GenerationModel::wrap(inner.clone(), &config);
+
+        let mut request = LlmRequest::new("recording", Vec::new());
+        request.config = Some(GenerateContentConfig { temperature: Some(0.9), ..Default::default() });
+        model.generate_content(request, false).await.unwrap();
+
+        let seen = inner.seen.lock().unwrap();
+        let generation = seen[0].as_ref().unwrap();
+        assert_eq!(generation.temperature, Some(0.2));
+        assert_eq!(generation.max_output_tokens, Some(8192));
+    }
+
+    #[tokio::test]
+    async fn test_defaults_are_sent_when_not_overridden() {
+        let inner = Arc::new(RecordingModel::default());
+        let model = GenerationModel::wrap(inner.clone(), &ModelConfig::default());
+        model.generate_content(LlmRequest::new("recording", Vec::new()), false).await.unwrap();
+        let seen = inner.seen.lock().unwrap();
+        let generation = seen[0].as_ref().unwrap();
+        assert_eq!(generation.temperature, Some(0.7));
+        assert_eq!(generation.max_output_tokens, Some(4096));
+    }
+}
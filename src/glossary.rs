@@ -0,0 +1,677 @@
+//! Project glossary: one name per concept, across every task.
+//!
+//! Over a long run the developer agent drifts between "order", "purchase" and
+//! "transaction" for the same thing, and the public API ends up incoherent.
+//! After the architect phase the project's domain terms are collected into a
+//! [`Glossary`]:
+//!
+//! - from the `glossary` section the architect emits (terms, definitions and
+//!   synonyms to avoid), or
+//! - when it left that out, mechanically from the design's component names and
+//!   the PRD's user stories and acceptance criteria, by word frequency.
+//!
+//! The glossary is saved to `.ralph/glossary.md` and added to the developer
+//! prompt. After each completed task, [`GlossaryCheck`] scans the public
+//! identifiers declared in the task's files for near-synonyms of glossary
+//! terms (a listed synonym, or a spelling variant within a small edit
+//! distance) and records each one as a note on the task and a `naming`
+//! warning.
+
+use crate::language::Language;
+use crate::models::{DesignDocument, PrdDocument, TaskList};
+use crate::warnings::{Warning, WarningCategory};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Where the glossary is kept, relative to the project root.
+pub const GLOSSARY_FILE: &str = ".ralph/glossary.md";
+
+/// Most terms kept by the frequency-based fallback.
+const FALLBACK_MAX_TERMS: usize = 20;
+
+/// Lowest weighted count for a word to become a fallback term.
+const FALLBACK_MIN_SCORE: usize = 3;
+
+/// Words that say nothing about the domain.
+const STOPWORDS: &[&str] = &[
+    "about", "able", "after", "all", "allow", "allows", "also", "and", "any", "are", "because", "been",
+    "before", "being", "but", "can", "cannot", "correct", "correctly", "data", "does", "each", "error",
+    "errors", "every", "feature", "for", "from", "given", "has", "have", "into", "its", "least", "list",
+    "make", "more", "most", "must", "new", "not", "one", "only", "other", "out", "over", "same", "see",
+    "shall", "should", "show", "shows", "so", "some", "such", "support", "supports", "system", "than",
+    "that", "the", "their", "them", "then", "there", "these", "they", "this", "those", "through",
+    "too", "use", "used", "user", "users", "uses", "using", "valid", "value", "values", "via", "want",
+    "was", "were", "what", "when", "where", "which", "while", "will", "with", "within", "without",
+    "would", "you", "your",
+];
+
+/// One concept and the name to use for it.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct GlossaryTerm {
+    /// Canonical name, lowercase (e.g. `order`)
+    pub term: String,
+    /// What the term means in this project
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub definition: String,
+    /// Synonyms not to use for it (e.g. `purchase`, `transaction`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub avoid: Vec<String>,
+}
+
+impl GlossaryTerm {
+    /// A term without definition or synonyms.
+    pub fn new(term: impl Into<String>) -> Self {
+        Self {
+            term: term.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Set what the term means.
+    pub fn with_definition(mut self, definition: impl Into<String>) -> Self {
+        self.definition = definition.into();
+        self
+    }
+
+    /// Add synonyms not to use for the term.
+    pub fn with_avoid(mut self, words: &[&str]) -> Self {
+        self.avoid.extend(words.iter().map(|w| w.to_string()));
+        self
+    }
+}
+
+/// The project's domain terms.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Glossary {
+    pub terms: Vec<GlossaryTerm>,
+}
+
+impl Glossary {
+    /// A glossary of `terms`, canonicalized: lowercase, trimmed, one entry per
+    /// stem (the first wins), and no term listed as its own synonym.
+    pub fn new(terms: Vec<GlossaryTerm>) -> Self {
+        let mut seen = BTreeSet::new();
+        let terms = terms
+            .into_iter()
+            .filter_map(|t| {
+                let term = t.term.trim().to_lowercase();
+                if term.is_empty() || !seen.insert(stem(&term)) {
+                    return None;
+                }
+                let mut avoid: Vec<String> = Vec::new();
+                for word in t.avoid.iter().map(|w| w.trim().to_lowercase()) {
+                    if !word.is_empty() && stem(&word) != stem(&term) && !avoid.contains(&word) {
+                        avoid.push(word);
+                    }
+                }
+                Some(GlossaryTerm {
+                    term,
+                    definition: t.definition.trim().to_string(),
+                    avoid,
+                })
+            })
+            .collect();
+        Self { terms }
+    }
+
+    /// Whether there are no terms.
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Path of the glossary in `project_path`.
+    pub fn path(project_path: &Path) -> PathBuf {
+        project_path.join(GLOSSARY_FILE)
+    }
+
+    /// The project's saved glossary, if it has one with terms.
+    pub fn load(project_path: &Path) -> Option<Self> {
+        let content = std::fs::read_to_string(Self::path(project_path)).ok()?;
+        Some(Self::parse_markdown(&content)).filter(|g| !g.is_empty())
+    }
+
+    /// Write the glossary to `.ralph/glossary.md`.
+    pub fn save(&self, project_path: &Path) -> Result<(), String> {
+        let path = Self::path(project_path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        std::fs::write(&path, self.to_markdown())
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Markdown form: one `- **term**: definition (not: a, b)` bullet per term.
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::from("# Glossary\n\nUse these names for these concepts in code, tests and docs.\n\n");
+        md.push_str(&self.to_markdown_bullets());
+        md
+    }
+
+    /// Parse [`to_markdown`](Self::to_markdown) output (or any list of such bullets).
+    pub fn parse_markdown(content: &str) -> Self {
+        Self::new(content.lines().filter_map(parse_bullet).collect())
+    }
+
+    /// Section appended to the developer instruction.
+    pub fn prompt_section(&self) -> String {
+        format!(
+            "\n\n## Glossary\n\nName things with these terms in identifiers, tests and messages. \
+             Do not introduce the synonyms in parentheses for the same concept.\n\n{}",
+            self.to_markdown_bullets()
+        )
+    }
+
+    /// The terms as bullets, without heading.
+    pub fn to_markdown_bullets(&self) -> String {
+        let mut md = String::new();
+        for term in &self.terms {
+            md.push_str(&format!("- **{}**", term.term));
+            if !term.definition.is_empty() {
+                md.push_str(&format!(": {}", term.definition));
+            }
+            if !term.avoid.is_empty() {
+                md.push_str(&format!(" (not: {})", term.avoid.join(", ")));
+            }
+            md.push('\n');
+        }
+        md
+    }
+}
+
+/// One `- **term**: definition (not: a, b)` bullet.
+fn parse_bullet(line: &str) -> Option<GlossaryTerm> {
+    let rest = line.trim().strip_prefix("- **")?;
+    let (term, mut rest) = rest.split_once("**")?;
+    let mut avoid = Vec::new();
+    if let Some(open) = rest.rfind(" (not: ").filter(|_| rest.ends_with(')')) {
+        avoid = rest[open + 7..rest.len() - 1].split(',').map(|w| w.trim().to_string()).collect();
+        rest = &rest[..open];
+    }
+    Some(GlossaryTerm {
+        term: term.to_string(),
+        definition: rest.trim_start_matches(':').trim().to_string(),
+        avoid,
+    })
+}
+
+// =============================================================================
+// Extraction
+// =============================================================================
+
+/// The glossary for a new plan: the architect's terms when it gave any,
+/// otherwise [`fallback`].
+pub fn extract(design: &DesignDocument, prd: Option<&PrdDocument>) -> Glossary {
+    if !design.glossary.is_empty() {
+        return Glossary::new(design.glossary.clone());
+    }
+    fallback(design, prd)
+}
+
+/// Frequency-based glossary from component names (weight 3), user story
+/// titles (2), and story descriptions, acceptance criteria and component
+/// purposes (1). Words are canonicalized to their stem; the term is the
+/// stem itself when it occurs as a word, else the stem's most frequent form.
+pub fn fallback(design: &DesignDocument, prd: Option<&PrdDocument>) -> Glossary {
+    let mut texts: Vec<(&str, usize)> = Vec::new();
+    for component in &design.components {
+        texts.push((component.name.as_str(), 3));
+        texts.push((component.purpose.as_str(), 1));
+    }
+    for story in prd.map(|p| p.user_stories.as_slice()).unwrap_or_default() {
+        texts.push((story.title.as_str(), 2));
+        texts.push((story.description.as_str(), 1));
+        for criterion in &story.acceptance_criteria {
+            texts.push((criterion.criterion.as_str(), 1));
+        }
+    }
+
+    // stem -> (score, form -> count)
+    let mut counts: BTreeMap<String, (usize, BTreeMap<String, usize>)> = BTreeMap::new();
+    for (text, weight) in texts {
+        for word in split_words(text) {
+            if word.len() < 3 || STOPWORDS.contains(&word.as_str()) || word.chars().any(|c| c.is_ascii_digit()) {
+                continue;
+            }
+            let entry = counts.entry(stem(&word)).or_default();
+            entry.0 += weight;
+            *entry.1.entry(word).or_default() += 1;
+        }
+    }
+
+    let mut ranked: Vec<(usize, String)> = counts
+        .into_iter()
+        .filter(|(_, (score, _))| *score >= FALLBACK_MIN_SCORE)
+        .map(|(stem, (score, forms))| {
+            if forms.contains_key(&stem) {
+                return (score, stem);
+            }
+            let form = forms
+                .into_iter()
+                .max_by(|a, b| a.1.cmp(&b.1).then(b.0.len().cmp(&a.0.len())))
+                .map(|(form, _)| form)
+                .unwrap_or(stem);
+            (score, form)
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    Glossary::new(
+        ranked
+            .into_iter()
+            .take(FALLBACK_MAX_TERMS)
+            .map(|(_, term)| GlossaryTerm::new(term))
+            .collect(),
+    )
+}
+
+/// Lowercase words of `text`, splitting identifiers on case changes,
+/// underscores and hyphens (`PurchaseOrder_id` -> purchase, order, id).
+pub fn split_words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    for token in text.split(|c: char| !c.is_alphanumeric()) {
+        let chars: Vec<char> = token.chars().collect();
+        let mut start = 0;
+        for i in 1..chars.len() {
+            let lower_to_upper = chars[i - 1].is_lowercase() && chars[i].is_uppercase();
+            // The last capital of an acronym starts the next word: HTTPServer -> http, server
+            let acronym_end = chars[i - 1].is_uppercase()
+                && chars[i].is_uppercase()
+                && chars.get(i + 1).is_some_and(|c| c.is_lowercase());
+            if lower_to_upper || acronym_end {
+                words.push(chars[start..i].iter().collect::<String>().to_lowercase());
+                start = i;
+            }
+        }
+        if start < chars.len() {
+            words.push(chars[start..].iter().collect::<String>().to_lowercase());
+        }
+    }
+    words
+}
+
+/// Crude English stem: strips plural endings (`orders`, `categories`, `boxes`).
+pub fn stem(word: &str) -> String {
+    let word = word.to_lowercase();
+    if let Some(base) = word.strip_suffix("ies").filter(|b| b.len() >= 2) {
+        return format!("{}y", base);
+    }
+    for suffix in ["sses", "shes", "ches", "xes", "zes"] {
+        if word.ends_with(suffix) {
+            return word[..word.len() - 2].to_string();
+        }
+    }
+    match word.strip_suffix('s') {
+        // Not `status`, `address` or `analysis`
+        Some(base) if base.len() >= 3 && !base.ends_with(['s', 'u', 'i']) => base.to_string(),
+        _ => word,
+    }
+}
+
+/// Levenshtein distance between two short words.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+// =============================================================================
+// Identifier scan
+// =============================================================================
+
+/// Public identifiers declared in `source`, line by line:
+///
+/// - Rust: `pub fn/struct/enum/trait/type/const/static/mod`
+/// - TypeScript/JavaScript: `export function/class/interface/type/enum/const/let`
+/// - Python: top-level `def`/`class` not starting with `_`
+/// - Go: top-level `func`, methods and `type` with a capitalized name
+///
+/// Other languages have none.
+pub fn public_identifiers(language: Language, source: &str) -> Vec<String> {
+    let mut identifiers = Vec::new();
+    for line in source.lines() {
+        let found = match language {
+            Language::Rust => rust_public(line.trim_start()),
+            Language::TypeScript | Language::JavaScript => js_exported(line),
+            Language::Python => python_public(line),
+            Language::Go => go_exported(line),
+            _ => None,
+        };
+        if let Some(name) = found.filter(|n| !identifiers.contains(n)) {
+            identifiers.push(name);
+        }
+    }
+    identifiers
+}
+
+fn leading_ident(s: &str) -> Option<String> {
+    let name: String = s
+        .trim_start()
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '$')
+        .collect();
+    (!name.is_empty()).then_some(name)
+}
+
+fn strip_words<'a>(mut line: &'a str, words: &[&str]) -> &'a str {
+    while let Some(rest) = words.iter().find_map(|w| line.strip_prefix(w)) {
+        line = rest.trim_start();
+    }
+    line
+}
+
+fn rust_public(line: &str) -> Option<String> {
+    let rest = line.strip_prefix("pub ")?;
+    let rest = strip_words(rest, &["async ", "unsafe ", "extern \"C\" "]);
+    ["fn ", "const fn ", "struct ", "enum ", "trait ", "type ", "const ", "static ", "mod ", "union "]
+        .iter()
+        .find_map(|kind| rest.strip_prefix(kind))
+        .and_then(leading_ident)
+}
+
+fn js_exported(line: &str) -> Option<String> {
+    let rest = line.strip_prefix("export ")?;
+    let rest = strip_words(rest, &["default ", "declare ", "abstract ", "async "]);
+    ["function* ", "function ", "class ", "interface ", "type ", "enum ", "const ", "let ", "var "]
+        .iter()
+        .find_map(|kind| rest.strip_prefix(kind))
+        .and_then(leading_ident)
+}
+
+fn python_public(line: &str) -> Option<String> {
+    let rest = ["def ", "async def ", "class "].iter().find_map(|kind| line.strip_prefix(kind))?;
+    leading_ident(rest).filter(|name| !name.starts_with('_'))
+}
+
+fn go_exported(line: &str) -> Option<String> {
+    let rest = if let Some(rest) = line.strip_prefix("func ") {
+        match rest.strip_prefix('(') {
+            Some(receiver) => receiver.split_once(')')?.1,
+            None => rest,
+        }
+    } else {
+        line.strip_prefix("type ")?
+    };
+    leading_ident(rest).filter(|name| name.starts_with(|c: char| c.is_uppercase()))
+}
+
+// =============================================================================
+// Drift check
+// =============================================================================
+
+/// An identifier using a near-synonym of a glossary term.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamingDrift {
+    /// File declaring the identifier
+    pub file: String,
+    /// The public identifier
+    pub identifier: String,
+    /// The word in it that drifts
+    pub word: String,
+    /// The glossary term for the concept
+    pub term: String,
+}
+
+impl NamingDrift {
+    /// The drift as a `naming` warning for `task_id`.
+    pub fn to_warning(&self, task_id: &str) -> Warning {
+        Warning::new(WarningCategory::Naming, self.to_string())
+            .with_task(task_id)
+            .with_file(&self.file)
+    }
+}
+
+impl fmt::Display for NamingDrift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`{}` uses '{}' where the glossary says '{}'",
+            self.identifier, self.word, self.term
+        )
+    }
+}
+
+impl Glossary {
+    /// The glossary term `word` drifts from, if any: a listed synonym, or a
+    /// spelling variant (same first letter, edit distance 1 from 5 letters,
+    /// 2 from 7) that is not simply a longer or shorter form of the term.
+    pub fn drift_of(&self, word: &str) -> Option<&GlossaryTerm> {
+        let word = stem(word);
+        if self.terms.iter().any(|t| stem(&t.term) == word) {
+            return None;
+        }
+        if let Some(term) = self.terms.iter().find(|t| t.avoid.iter().any(|a| stem(a) == word)) {
+            return Some(term);
+        }
+        self.terms.iter().find(|t| {
+            let term = stem(&t.term);
+            let shorter = word.len().min(term.len());
+            let allowed = match shorter {
+                0..=4 => 0,
+                5 | 6 => 1,
+                _ => 2,
+            };
+            allowed > 0
+                && word.chars().next() == term.chars().next()
+                && !word.starts_with(&term)
+                && !term.starts_with(&word)
+                && edit_distance(&word, &term) <= allowed
+        })
+    }
+
+    /// Drifting identifiers among `identifiers` declared in `file`.
+    pub fn check_identifiers(&self, file: &str, identifiers: &[String]) -> Vec<NamingDrift> {
+        let mut drifts = Vec::new();
+        for identifier in identifiers {
+            for word in split_words(identifier) {
+                if let Some(term) = self.drift_of(&word) {
+                    drifts.push(NamingDrift {
+                        file: file.to_string(),
+                        identifier: identifier.clone(),
+                        word,
+                        term: term.term.clone(),
+                    });
+                    break;
+                }
+            }
+        }
+        drifts
+    }
+}
+
+/// Post-task naming check of the files a completed task declared.
+#[derive(Debug, Clone)]
+pub struct GlossaryCheck {
+    project_path: PathBuf,
+    tasks_path: PathBuf,
+}
+
+impl GlossaryCheck {
+    pub fn new(project_path: impl Into<PathBuf>, tasks_path: impl Into<PathBuf>) -> Self {
+        Self {
+            project_path: project_path.into(),
+            tasks_path: tasks_path.into(),
+        }
+    }
+
+    /// Scan the public identifiers of `task_id`'s created and modified files
+    /// against the project glossary. Each drift is added to the task's notes
+    /// in tasks.json; the caller reports them as warnings. Without a glossary
+    /// nothing is checked.
+    pub fn on_task_completed(&self, task_id: &str) -> Result<Vec<NamingDrift>, String> {
+        let Some(glossary) = Glossary::load(&self.project_path) else {
+            return Ok(Vec::new());
+        };
+        let mut list = TaskList::load(&self.tasks_path)?;
+        let Some(task) = list.get_task(task_id) else {
+            return Ok(Vec::new());
+        };
+
+        let mut drifts = Vec::new();
+        for file in task.files_created.iter().chain(&task.files_modified) {
+            let Some(language) = crate::smoke::language_of(file) else {
+                continue;
+            };
+            let Ok(source) = std::fs::read_to_string(self.project_path.join(file)) else {
+                continue;
+            };
+            drifts.extend(glossary.check_identifiers(file, &public_identifiers(language, &source)));
+        }
+
+        if !drifts.is_empty() {
+            if let Some(task) = list.get_task_mut(task_id) {
+                for drift in &drifts {
+                    task.add_note(&format!("Naming: {} in {}", drift, drift.file));
+                }
+            }
+            list.save(&self.tasks_path)?;
+        }
+        Ok(drifts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Component, Task, UserStory};
+
+    fn glossary() -> Glossary {
+        Glossary::new(vec![
+            GlossaryTerm::new("Order")
+                .with_definition("A customer's request to buy items")
+                .with_avoid(&["purchase", "transaction", "orders"]),
+            GlossaryTerm::new("customer"),
+            GlossaryTerm::new("catalog"),
+        ])
+    }
+
+    #[test]
+    fn test_markdown_round_trip_and_canonical_terms() {
+        let glossary = glossary();
+        assert_eq!(glossary.terms[0].term, "order");
+        // A term is never its own synonym
+        assert_eq!(glossary.terms[0].avoid, vec!["purchase", "transaction"]);
+
+        let markdown = glossary.to_markdown();
+        assert!(markdown.contains("- **order**: A customer's request to buy items (not: purchase, transaction)\n"));
+        assert!(markdown.contains("- **customer**\n"));
+        assert_eq!(Glossary::parse_markdown(&markdown), glossary);
+        assert!(glossary.prompt_section().contains("## Glossary"));
+    }
+
+    #[test]
+    fn test_fallback_extracts_frequent_domain_nouns() {
+        let mut design = DesignDocument::new("shop", "An online shop");
+        design.components.push(Component::new("OrderService", "Creates orders for customers"));
+        design.components.push(Component::new("Catalog", "Lists products"));
+
+        let mut prd = PrdDocument::new("shop", "An online shop");
+        let mut story = UserStory::new("US-001", "Place an order", "As a customer I want to order products", 1);
+        story.add_criterion_text("The order lists each product with its price");
+        story.add_criterion_text("The customer sees the order total");
+        prd.add_user_story(story);
+
+        let glossary = fallback(&design, Some(&prd));
+        let terms: Vec<&str> = glossary.terms.iter().map(|t| t.term.as_str()).collect();
+        assert_eq!(terms[0], "order");
+        assert!(terms.contains(&"customer"));
+        assert!(terms.contains(&"catalog"));
+        assert!(terms.contains(&"product"));
+        // Stopwords and one-off words are left out
+        assert!(!terms.contains(&"user") && !terms.contains(&"the") && !terms.contains(&"price"));
+
+        // The architect's own glossary wins
+        design.glossary = vec![GlossaryTerm::new("basket")];
+        assert_eq!(extract(&design, Some(&prd)).terms, vec![GlossaryTerm::new("basket")]);
+    }
+
+    #[test]
+    fn test_split_words_and_stem() {
+        assert_eq!(split_words("PurchaseOrder_id"), vec!["purchase", "order", "id"]);
+        assert_eq!(split_words("HTTPServer"), vec!["http", "server"]);
+        assert_eq!(split_words("create-order"), vec!["create", "order"]);
+        assert_eq!(stem("orders"), "order");
+        assert_eq!(stem("categories"), "category");
+        assert_eq!(stem("boxes"), "box");
+        assert_eq!(stem("status"), "status");
+        assert_eq!(stem("address"), "address");
+    }
+
+    #[test]
+    fn test_public_identifiers_per_language() {
+        let rust = "pub struct PurchaseService;\nfn helper() {}\n    pub async fn create_order() {}\npub(crate) fn hidden() {}\n";
+        assert_eq!(public_identifiers(Language::Rust, rust), vec!["PurchaseService", "create_order"]);
+
+        let ts = "export class TransactionStore {}\nexport const listOrders = () => [];\nfunction local() {}\n";
+        assert_eq!(public_identifiers(Language::TypeScript, ts), vec!["TransactionStore", "listOrders"]);
+
+        let py = "class Catalogue:\n    def method(self): pass\ndef _private(): pass\nasync def fetch_customer(): pass\n";
+        assert_eq!(public_identifiers(Language::Python, py), vec!["Catalogue", "fetch_customer"]);
+
+        let go = "type Purchase struct{}\nfunc (p *Purchase) Total() int {}\nfunc helper() {}\n";
+        assert_eq!(public_identifiers(Language::Go, go), vec!["Purchase", "Total"]);
+
+        assert!(public_identifiers(Language::Java, "public class A {}").is_empty());
+    }
+
+    #[test]
+    fn test_drift_detection() {
+        let glossary = glossary();
+        assert_eq!(glossary.drift_of("purchases").map(|t| t.term.as_str()), Some("order"));
+        assert_eq!(glossary.drift_of("costumer").map(|t| t.term.as_str()), Some("customer"));
+        assert_eq!(glossary.drift_of("catalogue"), None, "longer form of the term");
+        assert_eq!(glossary.drift_of("orders"), None);
+        assert_eq!(glossary.drift_of("border"), None);
+        assert_eq!(glossary.drift_of("ordre"), None, "a transposition is two edits");
+
+        let drifts = glossary.check_identifiers(
+            "src/shop.rs",
+            &["PurchaseService".to_string(), "create_order".to_string()],
+        );
+        assert_eq!(drifts.len(), 1);
+        assert_eq!(drifts[0].to_string(), "`PurchaseService` uses 'purchase' where the glossary says 'order'");
+    }
+
+    #[test]
+    fn test_check_adds_task_notes_and_warnings() {
+        let dir = tempfile::tempdir().unwrap();
+        glossary().save(dir.path()).unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/shop.rs"), "pub fn record_transaction() {}\npub fn add_order() {}\n").unwrap();
+
+        let mut task = Task::new("T-002", "Shop", "Shop logic", 1);
+        task.files_created = vec!["src/shop.rs".to_string()];
+        let mut list = TaskList::new("shop", "rust");
+        list.add_task(task);
+        let tasks_path = dir.path().join("tasks.json");
+        list.save(&tasks_path).unwrap();
+
+        let check = GlossaryCheck::new(dir.path(), &tasks_path);
+        let drifts = check.on_task_completed("T-002").unwrap();
+        assert_eq!(drifts.len(), 1);
+        assert_eq!(drifts[0].term, "order");
+
+        let saved = TaskList::load(&tasks_path).unwrap();
+        let notes = &saved.get_task("T-002").unwrap().notes;
+        assert!(notes.contains("Naming: `record_transaction` uses 'transaction'"), "{}", notes);
+
+        let warning = drifts[0].to_warning("T-002");
+        assert_eq!(warning.category, WarningCategory::Naming);
+        assert_eq!(warning.task.as_deref(), Some("T-002"));
+        assert_eq!(warning.file.as_deref(), Some("src/shop.rs"));
+
+        // No glossary, no check
+        let empty = tempfile::tempdir().unwrap();
+        assert!(GlossaryCheck::new(empty.path(), &tasks_path).on_task_completed("T-002").unwrap().is_empty());
+    }
+}
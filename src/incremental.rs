@@ -0,0 +1,411 @@
+//! Incremental re-runs: re-execute only the tasks whose inputs changed.
+//!
+//! When a task completes, [`Fingerprinter`] records a [`TaskFingerprint`]:
+//!
+//! - `inputs`: a hash of the task's description, acceptance criteria, the
+//!   design.md sections of the components it implements ([`design_excerpt`]),
+//!   and the files its dependencies produced (their recorded hashes), and
+//! - `files`: the content hash of every file the task declares, as it left them.
+//!
+//! `ralph resume --phase implementation --incremental` builds an
+//! [`IncrementalPlan`] from the completed tasks:
+//!
+//! - a task whose `inputs` hash no longer matches was changed and re-runs;
+//! - a task whose files no longer match what it left was edited by a person,
+//!   and re-runs only if the user confirms, so hand fixes are not overwritten;
+//! - everything that depends on a re-run task re-runs too
+//!   ([`IncrementalPlan::invalidated`]);
+//! - everything else stays completed and is skipped.
+//!
+//! A file written by several tasks is checked against the task that
+//! completed last. Tasks completed before fingerprints existed are kept and
+//! reported.
+
+use crate::archive::sha256_hex;
+use crate::models::{Task, TaskFingerprint, TaskList, TaskStatus};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Sections of `design_markdown` describing components implemented in `task`'s files.
+///
+/// Components are the `### Name` blocks under `## Components`; a block belongs
+/// to the task when its `**File**:` is one of the task's declared files.
+pub fn design_excerpt(design_markdown: &str, task: &Task) -> String {
+    let files: BTreeSet<&str> = declared_files(task).into_iter().collect();
+    let mut excerpt = String::new();
+    let mut in_components = false;
+    let mut block = String::new();
+    let flush = |block: &mut String, excerpt: &mut String| {
+        let matches = block.lines().any(|line| {
+            line.strip_prefix("**File**:")
+                .map(|path| path.trim().trim_matches('`'))
+                .is_some_and(|path| files.contains(path))
+        });
+        if matches {
+            excerpt.push_str(block);
+        }
+        block.clear();
+    };
+
+    for line in design_markdown.lines() {
+        if line.starts_with("## ") {
+            flush(&mut block, &mut excerpt);
+            in_components = line.starts_with("## Components");
+            continue;
+        }
+        if !in_components {
+            continue;
+        }
+        if line.starts_with("### ") {
+            flush(&mut block, &mut excerpt);
+        }
+        block.push_str(line);
+        block.push('\n');
+    }
+    flush(&mut block, &mut excerpt);
+    excerpt
+}
+
+/// Files a task declares it creates or modifies, without duplicates.
+fn declared_files(task: &Task) -> Vec<&str> {
+    let mut files: Vec<&str> = task
+        .files_created
+        .iter()
+        .chain(&task.files_modified)
+        .map(String::as_str)
+        .collect();
+    files.sort_unstable();
+    files.dedup();
+    files
+}
+
+/// Hash of everything `task` was built from.
+///
+/// Input files are the files its dependencies produced, by the hashes they
+/// recorded, so a dependency that re-runs with a different result changes it.
+pub fn input_hash(list: &TaskList, task: &Task, design_markdown: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(format!("description:{}\n", task.description));
+    for criterion in &task.acceptance_criteria {
+        hasher.update(format!("criterion:{}\n", criterion));
+    }
+    hasher.update(format!("design:\n{}\n", design_excerpt(design_markdown, task)));
+
+    let mut dependencies: Vec<&String> = task.dependencies.iter().collect();
+    dependencies.sort();
+    for id in dependencies {
+        let files = list
+            .get_task(id)
+            .and_then(|dep| dep.fingerprint.as_ref())
+            .map(|f| &f.files);
+        for (path, hash) in files.into_iter().flatten() {
+            hasher.update(format!("input:{}:{}={}\n", id, path, hash));
+        }
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Content hash of each of `task`'s declared files that exists under `project_root`.
+pub fn file_hashes(project_root: &Path, task: &Task) -> BTreeMap<String, String> {
+    declared_files(task)
+        .into_iter()
+        .filter_map(|path| {
+            let content = fs::read(project_root.join(path)).ok()?;
+            Some((path.to_string(), sha256_hex(&content)))
+        })
+        .collect()
+}
+
+/// Records fingerprints of completed tasks.
+#[derive(Debug, Clone)]
+pub struct Fingerprinter {
+    project_root: PathBuf,
+    design_path: PathBuf,
+}
+
+impl Fingerprinter {
+    /// Fingerprint tasks of the project at `project_root` against its design file.
+    pub fn new(project_root: impl Into<PathBuf>, design_path: impl Into<PathBuf>) -> Self {
+        Self {
+            project_root: project_root.into(),
+            design_path: design_path.into(),
+        }
+    }
+
+    /// Fingerprint of task `task_id` as it stands now.
+    pub fn fingerprint(&self, list: &TaskList, task_id: &str) -> Option<TaskFingerprint> {
+        let task = list.get_task(task_id)?;
+        let design = fs::read_to_string(&self.design_path).unwrap_or_default();
+        Some(TaskFingerprint {
+            inputs: input_hash(list, task, &design),
+            files: file_hashes(&self.project_root, task),
+            completed_at: crate::timefmt::now(),
+        })
+    }
+
+    /// Store the fingerprint of task `task_id` on it.
+    pub fn record(&self, list: &mut TaskList, task_id: &str) {
+        let fingerprint = self.fingerprint(list, task_id);
+        if let Some(task) = list.get_task_mut(task_id) {
+            task.fingerprint = fingerprint;
+        }
+    }
+}
+
+/// What an incremental re-run will do with each completed task.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IncrementalPlan {
+    /// Completed tasks whose inputs and files are unchanged (skipped)
+    pub unchanged: Vec<String>,
+    /// Completed tasks whose inputs changed (re-run)
+    pub changed: Vec<String>,
+    /// Completed tasks whose files were edited since, with the edited files
+    /// (re-run only when confirmed)
+    pub edited: BTreeMap<String, Vec<String>>,
+    /// Completed tasks without a fingerprint (kept)
+    pub unrecorded: Vec<String>,
+}
+
+impl IncrementalPlan {
+    /// Compare every completed task with the project as it is now.
+    pub fn compute(list: &TaskList, project_root: &Path, design_markdown: &str) -> Self {
+        let mut plan = Self::default();
+
+        // Which task last wrote each file, and what it left there
+        let mut recorded: Vec<(usize, &Task, &TaskFingerprint)> = list
+            .get_all_tasks()
+            .into_iter()
+            .enumerate()
+            .filter(|(_, t)| t.status == TaskStatus::Completed)
+            .filter_map(|(i, t)| t.fingerprint.as_ref().map(|f| (i, t, f)))
+            .collect();
+        recorded.sort_by(|a, b| a.2.completed_at.cmp(&b.2.completed_at).then(a.0.cmp(&b.0)));
+        let mut last_writer: BTreeMap<&str, (&str, &str)> = BTreeMap::new();
+        for (_, task, fingerprint) in &recorded {
+            for (path, hash) in &fingerprint.files {
+                last_writer.insert(path.as_str(), (task.id.as_str(), hash.as_str()));
+            }
+        }
+
+        for task in list.get_all_tasks() {
+            if task.status != TaskStatus::Completed {
+                continue;
+            }
+            let Some(fingerprint) = &task.fingerprint else {
+                plan.unrecorded.push(task.id.clone());
+                continue;
+            };
+            if input_hash(list, task, design_markdown) != fingerprint.inputs {
+                plan.changed.push(task.id.clone());
+                continue;
+            }
+            let edited: Vec<String> = last_writer
+                .iter()
+                .filter(|(_, (writer, _))| *writer == task.id)
+                .filter(|(path, (_, hash))| {
+                    let current = fs::read(project_root.join(path)).ok().map(|c| sha256_hex(&c));
+                    current.as_deref() != Some(*hash)
+                })
+                .map(|(path, _)| path.to_string())
+                .collect();
+            if edited.is_empty() {
+                plan.unchanged.push(task.id.clone());
+            } else {
+                plan.edited.insert(task.id.clone(), edited);
+            }
+        }
+        plan
+    }
+
+    /// Tasks to re-run: changed tasks, the edited tasks in `rerun_edited`, and
+    /// every task that depends on one of them, directly or not. In list order.
+    pub fn invalidated(&self, list: &TaskList, rerun_edited: &[String]) -> Vec<String> {
+        let mut invalid: BTreeSet<String> = self.changed.iter().cloned().collect();
+        invalid.extend(
+            rerun_edited
+                .iter()
+                .filter(|id| self.edited.contains_key(*id))
+                .cloned(),
+        );
+
+        // Propagate to dependents until nothing new is added
+        let tasks = list.get_all_tasks();
+        loop {
+            let before = invalid.len();
+            for task in &tasks {
+                if task.dependencies.iter().any(|dep| invalid.contains(dep)) {
+                    invalid.insert(task.id.clone());
+                }
+            }
+            if invalid.len() == before {
+                break;
+            }
+        }
+
+        tasks
+            .iter()
+            .filter(|t| invalid.contains(&t.id) && t.status == TaskStatus::Completed)
+            .map(|t| t.id.clone())
+            .collect()
+    }
+}
+
+/// Return tasks to the queue for re-execution.
+pub fn reset(list: &mut TaskList, ids: &[String]) {
+    for id in ids {
+        if let Some(task) = list.get_task_mut(id) {
+            task.status = TaskStatus::Pending;
+            task.fingerprint = None;
+            task.add_note("Re-run: inputs changed since completion");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const DESIGN: &str = "# System Design: calc\n\n## Components\n\n\
+        ### Parser\n\n**Purpose**: Parse input\n\n**File**: `src/parser.rs`\n\n\
+        ### Evaluator\n\n**Purpose**: Evaluate\n\n**File**: `src/eval.rs`\n\n\
+        ## Technology Stack\n\n- **Language**: rust\n";
+
+    fn task(id: &str, file: &str, deps: &[&str]) -> Task {
+        let mut task = Task::new(id, id, format!("Implement {}", file), 1);
+        task.files_created.push(file.to_string());
+        task.dependencies = deps.iter().map(|d| d.to_string()).collect();
+        task
+    }
+
+    /// Three tasks completed in order, T-2 and T-3 depending on T-1.
+    fn completed_project(root: &Path) -> (TaskList, Fingerprinter) {
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("design.md"), DESIGN).unwrap();
+        let mut list = TaskList::new("calc", "rust");
+        list.add_task(task("T-1", "src/parser.rs", &[]));
+        list.add_task(task("T-2", "src/eval.rs", &["T-1"]));
+        list.add_task(task("T-3", "src/main.rs", &["T-2"]));
+        let fingerprinter = Fingerprinter::new(root, root.join("design.md"));
+        for id in ["T-1", "T-2", "T-3"] {
+            let file = &list.get_task(id).unwrap().files_created[0];
+            fs::write(root.join(file), format!("// {}", id)).unwrap();
+            list.complete_task(id, None).unwrap();
+            fingerprinter.record(&mut list, id);
+        }
+        (list, fingerprinter)
+    }
+
+    #[test]
+    fn test_design_excerpt_is_the_task_components_section() {
+        let excerpt = design_excerpt(DESIGN, &task("T-1", "src/eval.rs", &[]));
+        assert_eq!(
+            excerpt,
+            "### Evaluator\n\n**Purpose**: Evaluate\n\n**File**: `src/eval.rs`\n\n"
+        );
+        assert_eq!(design_excerpt(DESIGN, &task("T-9", "README.md", &[])), "");
+    }
+
+    #[test]
+    fn test_fingerprint_covers_each_input() {
+        let dir = tempdir().unwrap();
+        let (list, _) = completed_project(dir.path());
+        let t2 = list.get_task("T-2").unwrap();
+        let base = input_hash(&list, t2, DESIGN);
+        assert_eq!(base, t2.fingerprint.as_ref().unwrap().inputs);
+        assert_eq!(
+            t2.fingerprint.as_ref().unwrap().files["src/eval.rs"],
+            sha256_hex(b"// T-2")
+        );
+
+        let mut edited = t2.clone();
+        edited.description.push('!');
+        assert_ne!(input_hash(&list, &edited, DESIGN), base);
+
+        let criterion = t2.clone().with_acceptance_criterion("WHEN 1+1 THE System SHALL show 2");
+        assert_ne!(input_hash(&list, &criterion, DESIGN), base);
+
+        let redesigned = DESIGN.replace("**Purpose**: Evaluate", "**Purpose**: Evaluate lazily");
+        assert_ne!(input_hash(&list, t2, &redesigned), base);
+        // Another component's section is not an input
+        let other = DESIGN.replace("Parse input", "Parse input and tokens");
+        assert_eq!(input_hash(&list, t2, &other), base);
+
+        // A dependency that produced something different is
+        let mut rebuilt = list.clone();
+        let t1 = rebuilt.get_task_mut("T-1").unwrap().fingerprint.as_mut().unwrap();
+        t1.files.insert("src/parser.rs".to_string(), sha256_hex(b"// new"));
+        assert_ne!(input_hash(&rebuilt, t2, DESIGN), base);
+    }
+
+    #[test]
+    fn test_unchanged_project_reruns_nothing() {
+        let dir = tempdir().unwrap();
+        let (list, _) = completed_project(dir.path());
+        let plan = IncrementalPlan::compute(&list, dir.path(), DESIGN);
+        assert_eq!(plan.unchanged, vec!["T-1", "T-2", "T-3"]);
+        assert!(plan.invalidated(&list, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_changed_task_invalidates_its_dependents() {
+        let dir = tempdir().unwrap();
+        let (mut list, _) = completed_project(dir.path());
+        list.get_task_mut("T-2").unwrap().description = "Implement a stack evaluator".into();
+
+        let plan = IncrementalPlan::compute(&list, dir.path(), DESIGN);
+        assert_eq!(plan.changed, vec!["T-2"]);
+        assert_eq!(plan.unchanged, vec!["T-1", "T-3"]);
+        let rerun = plan.invalidated(&list, &[]);
+        assert_eq!(rerun, vec!["T-2", "T-3"]);
+
+        reset(&mut list, &rerun);
+        assert_eq!(list.get_task("T-1").unwrap().status, TaskStatus::Completed);
+        assert_eq!(list.get_task("T-3").unwrap().status, TaskStatus::Pending);
+        assert!(list.get_task("T-3").unwrap().fingerprint.is_none());
+    }
+
+    #[test]
+    fn test_hand_edited_files_rerun_only_when_confirmed() {
+        let dir = tempdir().unwrap();
+        let (list, _) = completed_project(dir.path());
+        fs::write(dir.path().join("src/parser.rs"), "// fixed by hand").unwrap();
+
+        let plan = IncrementalPlan::compute(&list, dir.path(), DESIGN);
+        assert_eq!(plan.edited["T-1"], vec!["src/parser.rs"]);
+        assert!(plan.changed.is_empty());
+
+        // Declined: the hand fix is kept and nothing re-runs
+        assert!(plan.invalidated(&list, &[]).is_empty());
+        // Confirmed: the task and everything built on it re-run
+        let confirmed = plan.invalidated(&list, &["T-1".to_string()]);
+        assert_eq!(confirmed, vec!["T-1", "T-2", "T-3"]);
+    }
+
+    #[test]
+    fn test_files_rewritten_by_a_later_task_belong_to_it() {
+        let dir = tempdir().unwrap();
+        let (mut list, fingerprinter) = completed_project(dir.path());
+        let mut t4 = task("T-4", "src/parser.rs", &["T-3"]);
+        t4.files_created.clear();
+        t4.files_modified.push("src/parser.rs".to_string());
+        list.add_task(t4);
+        fs::write(dir.path().join("src/parser.rs"), "// T-4").unwrap();
+        list.complete_task("T-4", None).unwrap();
+        fingerprinter.record(&mut list, "T-4");
+
+        let plan = IncrementalPlan::compute(&list, dir.path(), DESIGN);
+        assert!(plan.edited.is_empty(), "{:?}", plan.edited);
+
+        let mut legacy = list.clone();
+        legacy.get_task_mut("T-1").unwrap().fingerprint = None;
+        let plan = IncrementalPlan::compute(&legacy, dir.path(), DESIGN);
+        assert_eq!(plan.unrecorded, vec!["T-1"]);
+    }
+}
@@ -10,7 +10,9 @@
 //! - 2.2: THE Orchestrator_Agent SHALL NOT use hardcoded intent classification or fixed rules
 //! - 2.3: THE Orchestrator_Agent SHALL have access to all required tools
 
-use crate::models::{ModelConfig, RalphConfig};
+use crate::exec_env::ExecEnv;
+use crate::model_timeout::TimeoutModel;
+use crate::models::{create_model, ModelConfig, RalphConfig};
 use crate::tools::{
     AddFeatureTool, FileTool, GetTimeTool, GitTool, ProgressTool, RunPipelineTool,
     RunProjectTool, TaskTool, WebSearchTool,
@@ -230,8 +232,9 @@ impl OrchestratorAgentBuilder {
     pub async fn build(self) -> Result<OrchestratorAgent> {
         let model = match self.model {
             Some(m) => m,
-            None => create_model_from_config(&self.model_config).await?,
+            None => create_model(&self.model_config).await?,
         };
+        let model = TimeoutModel::wrap(model, self.model_config.model_call_timeout());
 
         let project_path = self.project_path.clone();
 
@@ -288,7 +291,12 @@ fn create_tools(
 
     // Task management tool
     let tasks_path = project_path.join(&ralph_config.tasks_path);
-    tools.push(Arc::new(TaskTool::new(tasks_path)));
+    let exec_env = Arc::new(
+        ExecEnv::new(ralph_config.execution_env.clone())
+            .with_limits(ralph_config.resource_limits())
+            .with_isolation(ralph_config.artifact_isolation()),
+    );
+    tools.push(Arc::new(TaskTool::new(tasks_path).with_exec_env(exec_env.clone())));
 
     // Progress tracking tool
     let progress_path = project_path.join(&ralph_config.progress_path);
@@ -298,7 +306,7 @@ fn create_tools(
     )));
 
     // Run project tool - for executing generated projects
-    tools.push(Arc::new(RunProjectTool::new(project_path.clone())));
+    tools.push(Arc::new(RunProjectTool::new(project_path.clone()).with_exec_env(exec_env)));
 
     // Time tool - for general queries
     tools.push(Arc::new(GetTimeTool::new()));
@@ -309,66 +317,6 @@ fn create_tools(
     Ok(tools)
 }
 
-/// Create an LLM model from configuration.
-async fn create_model_from_config(config: &ModelConfig) -> Result<Arc<dyn Llm>> {
-    use std::env;
-
-    let model: Arc<dyn Llm> = match config.provider.to_lowercase().as_str() {
-        "anthropic" => {
-            use adk_rust::model::anthropic::{AnthropicClient, AnthropicConfig};
-
-            let api_key = env::var("ANTHROPIC_API_KEY").map_err(|_| {
-                RalphError::Configuration("ANTHROPIC_API_KEY environment variable not set".into())
-            })?;
-            let anthropic_config = AnthropicConfig::new(api_key, &config.model_name);
-            let client = AnthropicClient::new(anthropic_config).map_err(|e| RalphError::Model {
-                provider: "anthropic".into(),
-                message: e.to_string(),
-            })?;
-            Arc::new(client)
-        }
-        "openai" => {
-            use adk_rust::model::openai::{OpenAIClient, OpenAIConfig};
-
-            let api_key = env::var("OPENAI_API_KEY").map_err(|_| {
-                RalphError::Configuration("OPENAI_API_KEY environment variable not set".into())
-            })?;
-            let openai_config = OpenAIConfig::new(api_key, &config.model_name);
-            let client = OpenAIClient::new(openai_config).map_err(|e| RalphError::Model {
-                provider: "openai".into(),
-                message: e.to_string(),
-            })?;
-            Arc::new(client)
-        }
-        "gemini" => {
-            use adk_rust::model::GeminiModel;
-
-            let api_key = env::var("GEMINI_API_KEY")
-                .or_else(|_| env::var("GOOGLE_API_KEY"))
-                .map_err(|_| {
-                    RalphError::Configuration(
-                        "GEMINI_API_KEY or GOOGLE_API_KEY environment variable not set".into(),
-                    )
-                })?;
-            let client = GeminiModel::new(api_key, &config.model_name).map_err(|e| {
-                RalphError::Model {
-                    provider: "gemini".into(),
-                    message: e.to_string(),
-                }
-            })?;
-            Arc::new(client)
-        }
-        provider => {
-            return Err(RalphError::Configuration(format!(
-                "Unsupported model provider: {}. Supported: anthropic, openai, gemini",
-                provider
-            )));
-        }
-    };
-
-    Ok(model)
-}
-
 /// List of required tool names for the orchestrator.
 pub const REQUIRED_TOOLS: &[&str] = &[
     "run_pipeline",
@@ -13,13 +13,14 @@
 use crate::interactive::{OrchestratorAgent, OrchestratorAgentBuilder, Session};
 use crate::models::RalphConfig;
 use crate::output::RalphOutput;
+use crate::textfmt::truncate_display;
 use crate::{RalphError, Result};
 use adk_rust::{
     Agent, Artifacts, CallbackContext, Content, InvocationContext, Memory, Part, ReadonlyContext,
     RunConfig, Session as CoreSession, State,
 };
 use async_trait::async_trait;
-use colored::Colorize;
+use crate::console::palette;
 use futures::StreamExt;
 use std::collections::HashMap;
 use std::io::{self, BufRead, Write};
@@ -247,18 +248,18 @@ impl InteractiveRepl {
     /// Display the welcome message.
     async fn display_welcome(&self) {
         println!();
-        println!("{}", "Welcome to Ralph Interactive Mode!".bright_cyan().bold());
-        println!("{}", "─".repeat(40).bright_black());
+        println!("{}", palette().heading("Welcome to Ralph Interactive Mode!"));
+        println!("{}", palette().dim("─".repeat(40)));
         println!();
         println!("I'm your AI development assistant. I can help you:");
-        println!("  {} Create new projects from descriptions", "•".bright_blue());
-        println!("  {} Add features to existing projects", "•".bright_blue());
-        println!("  {} Run and test your code", "•".bright_blue());
-        println!("  {} Manage tasks and track progress", "•".bright_blue());
+        println!("  {} Create new projects from descriptions", palette().info("•"));
+        println!("  {} Add features to existing projects", palette().info("•"));
+        println!("  {} Run and test your code", palette().info("•"));
+        println!("  {} Manage tasks and track progress", palette().info("•"));
         println!();
         println!(
             "Type {} for available commands, or just start chatting!",
-            "help".cyan()
+            palette().accent("help")
         );
         println!();
 
@@ -267,7 +268,7 @@ impl InteractiveRepl {
         if !session.conversation_history.is_empty() {
             println!(
                 "{} Resumed session with {} previous messages",
-                "ℹ".bright_blue(),
+                palette().info("ℹ"),
                 session.conversation_history.len()
             );
             println!();
@@ -276,18 +277,18 @@ impl InteractiveRepl {
         // Show project context if available
         let ctx = &session.project_context;
         if ctx.has_prd || ctx.has_design || ctx.has_tasks {
-            println!("{}", "Project Status:".yellow());
+            println!("{}", palette().heading("Project Status:"));
             if ctx.has_prd {
-                println!("  {} PRD exists", "✓".green());
+                println!("  {} PRD exists", palette().success("✓"));
             }
             if ctx.has_design {
-                println!("  {} Design exists", "✓".green());
+                println!("  {} Design exists", palette().success("✓"));
             }
             if ctx.has_tasks {
-                println!("  {} Tasks exist", "✓".green());
+                println!("  {} Tasks exist", palette().success("✓"));
             }
             if let Some(ref lang) = ctx.language {
-                println!("  {} Language: {}", "•".bright_black(), lang.cyan());
+                println!("  {} Language: {}", palette().dim("•"), palette().accent(lang));
             }
             println!();
         }
@@ -296,14 +297,14 @@ impl InteractiveRepl {
     /// Display the goodbye message.
     fn display_goodbye(&self) {
         println!();
-        println!("{}", "Session saved. Goodbye!".bright_cyan());
+        println!("{}", palette().accent("Session saved. Goodbye!"));
         println!();
     }
 
     /// Read user input from stdin.
     fn read_input(&self) -> Result<String> {
         // Display prompt
-        print!("{}", DEFAULT_PROMPT.bright_green());
+        print!("{}", palette().success(DEFAULT_PROMPT));
         io::stdout().flush().map_err(|e| {
             RalphError::Internal(format!("Failed to flush stdout: {}", e))
         })?;
@@ -375,78 +376,78 @@ impl InteractiveRepl {
     /// Validates: Requirements 7.1
     fn display_help(&self) {
         println!();
-        println!("{}", "Ralph Interactive Mode - Help".bright_cyan().bold());
-        println!("{}", "═".repeat(50).bright_black());
+        println!("{}", palette().heading("Ralph Interactive Mode - Help"));
+        println!("{}", palette().dim("═".repeat(50)));
         println!();
         
         // Commands section
-        println!("{}", "Commands:".yellow().bold());
-        println!("  {}         Show this help message", "help".cyan());
-        println!("  {}         Exit the interactive session", "exit".cyan());
-        println!("  {}        Clear the terminal screen", "clear".cyan());
-        println!("  {}       Show project status and artifacts", "status".cyan());
-        println!("  {}      Show conversation history", "history".cyan());
+        println!("{}", palette().heading("Commands:"));
+        println!("  {}         Show this help message", palette().accent("help"));
+        println!("  {}         Exit the interactive session", palette().accent("exit"));
+        println!("  {}        Clear the terminal screen", palette().accent("clear"));
+        println!("  {}       Show project status and artifacts", palette().accent("status"));
+        println!("  {}      Show conversation history", palette().accent("history"));
         println!();
         
         // Project creation examples
-        println!("{}", "Creating Projects:".yellow().bold());
-        println!("  {} \"Create a CLI calculator in Rust\"", "•".bright_blue());
-        println!("  {} \"Build a REST API for a todo app in Python\"", "•".bright_blue());
-        println!("  {} \"Make a web scraper in Go\"", "•".bright_blue());
+        println!("{}", palette().heading("Creating Projects:"));
+        println!("  {} \"Create a CLI calculator in Rust\"", palette().info("•"));
+        println!("  {} \"Build a REST API for a todo app in Python\"", palette().info("•"));
+        println!("  {} \"Make a web scraper in Go\"", palette().info("•"));
         println!();
         
         // Feature addition examples
-        println!("{}", "Adding Features:".yellow().bold());
-        println!("  {} \"Add a history feature\"", "•".bright_blue());
-        println!("  {} \"Add user authentication\"", "•".bright_blue());
-        println!("  {} \"Add support for JSON output\"", "•".bright_blue());
+        println!("{}", palette().heading("Adding Features:"));
+        println!("  {} \"Add a history feature\"", palette().info("•"));
+        println!("  {} \"Add user authentication\"", palette().info("•"));
+        println!("  {} \"Add support for JSON output\"", palette().info("•"));
         println!();
         
         // Running and testing
-        println!("{}", "Running & Testing:".yellow().bold());
-        println!("  {} \"Run the project\"", "•".bright_blue());
-        println!("  {} \"Test it\"", "•".bright_blue());
-        println!("  {} \"Run with --help flag\"", "•".bright_blue());
+        println!("{}", palette().heading("Running & Testing:"));
+        println!("  {} \"Run the project\"", palette().info("•"));
+        println!("  {} \"Test it\"", palette().info("•"));
+        println!("  {} \"Run with --help flag\"", palette().info("•"));
         println!();
         
         // Task management
-        println!("{}", "Task Management:".yellow().bold());
-        println!("  {} \"What tasks are left?\"", "•".bright_blue());
-        println!("  {} \"Show me the next task\"", "•".bright_blue());
-        println!("  {} \"Mark task 1 as complete\"", "•".bright_blue());
+        println!("{}", palette().heading("Task Management:"));
+        println!("  {} \"What tasks are left?\"", palette().info("•"));
+        println!("  {} \"Show me the next task\"", palette().info("•"));
+        println!("  {} \"Mark task 1 as complete\"", palette().info("•"));
         println!();
         
         // File operations
-        println!("{}", "File Operations:".yellow().bold());
-        println!("  {} \"Show me the main.rs file\"", "•".bright_blue());
-        println!("  {} \"List all files in src/\"", "•".bright_blue());
-        println!("  {} \"Update the README\"", "•".bright_blue());
+        println!("{}", palette().heading("File Operations:"));
+        println!("  {} \"Show me the main.rs file\"", palette().info("•"));
+        println!("  {} \"List all files in src/\"", palette().info("•"));
+        println!("  {} \"Update the README\"", palette().info("•"));
         println!();
         
         // Git operations
-        println!("{}", "Version Control:".yellow().bold());
-        println!("  {} \"Show git status\"", "•".bright_blue());
-        println!("  {} \"Commit the changes\"", "•".bright_blue());
-        println!("  {} \"Show the diff\"", "•".bright_blue());
+        println!("{}", palette().heading("Version Control:"));
+        println!("  {} \"Show git status\"", palette().info("•"));
+        println!("  {} \"Commit the changes\"", palette().info("•"));
+        println!("  {} \"Show the diff\"", palette().info("•"));
         println!();
         
         // General queries
-        println!("{}", "General Queries:".yellow().bold());
-        println!("  {} \"What time is it?\"", "•".bright_blue());
-        println!("  {} \"What can you do?\"", "•".bright_blue());
-        println!("  {} \"How do I use this?\"", "•".bright_blue());
+        println!("{}", palette().heading("General Queries:"));
+        println!("  {} \"What time is it?\"", palette().info("•"));
+        println!("  {} \"What can you do?\"", palette().info("•"));
+        println!("  {} \"How do I use this?\"", palette().info("•"));
         println!();
         
         // Tips section
-        println!("{}", "Tips:".yellow().bold());
-        println!("  {} Just describe what you want in natural language", "💡".bright_black());
-        println!("  {} Ralph will figure out the best way to help", "💡".bright_black());
-        println!("  {} Use Ctrl+D or type 'exit' to quit", "💡".bright_black());
-        println!("  {} Your session is saved automatically", "💡".bright_black());
-        println!("  {} Use 'ralph chat --resume' to continue later", "💡".bright_black());
+        println!("{}", palette().heading("Tips:"));
+        println!("  {} Just describe what you want in natural language", palette().dim("💡"));
+        println!("  {} Ralph will figure out the best way to help", palette().dim("💡"));
+        println!("  {} Use Ctrl+D or type 'exit' to quit", palette().dim("💡"));
+        println!("  {} Your session is saved automatically", palette().dim("💡"));
+        println!("  {} Use 'ralph chat --resume' to continue later", palette().dim("💡"));
         println!();
         
-        println!("{}", "─".repeat(50).bright_black());
+        println!("{}", palette().dim("─".repeat(50)));
         println!();
     }
 
@@ -462,38 +463,38 @@ impl InteractiveRepl {
         let session = self.session.read().await;
         
         println!();
-        println!("{}", "Project Status".bright_cyan().bold());
-        println!("{}", "─".repeat(40).bright_black());
+        println!("{}", palette().heading("Project Status"));
+        println!("{}", palette().dim("─".repeat(40)));
         
         let ctx = &session.project_context;
-        println!("  Path: {}", ctx.project_path.display().to_string().cyan());
+        println!("  Path: {}", palette().accent(ctx.project_path.display().to_string()));
         
         if let Some(ref lang) = ctx.language {
-            println!("  Language: {}", lang.cyan());
+            println!("  Language: {}", palette().accent(lang));
         }
         
         println!();
-        println!("{}", "Artifacts:".yellow());
+        println!("{}", palette().heading("Artifacts:"));
         println!(
             "  PRD:    {}",
-            if ctx.has_prd { "✓".green() } else { "✗".red() }
+            if ctx.has_prd { palette().success("✓") } else { palette().failure("✗") }
         );
         println!(
             "  Design: {}",
-            if ctx.has_design { "✓".green() } else { "✗".red() }
+            if ctx.has_design { palette().success("✓") } else { palette().failure("✗") }
         );
         println!(
             "  Tasks:  {}",
-            if ctx.has_tasks { "✓".green() } else { "✗".red() }
+            if ctx.has_tasks { palette().success("✓") } else { palette().failure("✗") }
         );
         
         if let Some(ref phase) = ctx.current_phase {
             println!();
-            println!("  Current Phase: {}", phase.to_string().cyan());
+            println!("  Current Phase: {}", palette().accent(phase.to_string()));
         }
         
         println!();
-        println!("{}", "Session:".yellow());
+        println!("{}", palette().heading("Session:"));
         println!("  Messages: {}", session.conversation_history.len());
         println!("  Preferences: {}", session.user_preferences.len());
         println!();
@@ -504,27 +505,23 @@ impl InteractiveRepl {
         let session = self.session.read().await;
         
         println!();
-        println!("{}", "Conversation History".bright_cyan().bold());
-        println!("{}", "─".repeat(40).bright_black());
+        println!("{}", palette().heading("Conversation History"));
+        println!("{}", palette().dim("─".repeat(40)));
         
         if session.conversation_history.is_empty() {
             println!("  No messages yet.");
         } else {
             for (i, msg) in session.conversation_history.iter().enumerate() {
                 let role_display = if msg.role == "user" {
-                    "You".bright_green()
+                    palette().info("You")
                 } else {
-                    "Ralph".bright_cyan()
+                    palette().accent("Ralph")
                 };
                 
                 // Truncate long messages
-                let content = if msg.content.len() > 100 {
-                    format!("{}...", &msg.content[..100])
-                } else {
-                    msg.content.clone()
-                };
+                let content = truncate_display(&msg.content, 100);
                 
-                println!("  {}. {}: {}", i + 1, role_display, content.bright_black());
+                println!("  {}. {}: {}", i + 1, role_display, palette().dim(content));
             }
         }
         println!();
@@ -535,7 +532,7 @@ impl InteractiveRepl {
         let mut session = self.session.write().await;
         session.clear_history();
         println!();
-        println!("{} Conversation history cleared.", "✓".green());
+        println!("{} Conversation history cleared.", palette().success("✓"));
         println!();
     }
 
@@ -594,7 +591,7 @@ impl InteractiveRepl {
                                         // Print header on first chunk
                                         if first_chunk && !text.is_empty() {
                                             println!();
-                                            println!("{}", "Ralph:".bright_cyan().bold());
+                                            println!("{}", palette().heading("Ralph:"));
                                             first_chunk = false;
                                         }
                                         
@@ -660,7 +657,7 @@ impl InteractiveRepl {
     /// Display a response to the user.
     fn display_response(&self, response: &str) {
         println!();
-        println!("{}", "Ralph:".bright_cyan().bold());
+        println!("{}", palette().heading("Ralph:"));
         
         // Display response with proper formatting
         for line in response.lines() {
@@ -674,7 +671,7 @@ impl InteractiveRepl {
     ///
     /// Displays a visual indicator that Ralph is thinking/processing.
     fn show_typing_indicator(&self) {
-        print!("{}", "  ⏳ Thinking...".bright_black());
+        print!("{}", palette().dim("  ⏳ Thinking..."));
         let _ = io::stdout().flush();
     }
 
@@ -798,7 +795,7 @@ impl InteractiveReplBuilder {
         }
 
         // Create output handler
-        let output = RalphOutput::new(config.debug_level);
+        let output = RalphOutput::for_config(&config);
 
         // Build orchestrator agent
         let orchestrator = OrchestratorAgentBuilder::new()
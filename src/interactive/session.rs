@@ -8,6 +8,7 @@
 //! - History truncation for token limits
 
 use crate::{PipelinePhase, RalphError, Result};
+use crate::textfmt::truncate_display;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -100,21 +101,7 @@ impl ProjectContext {
 
     /// Detect the project language from common files.
     fn detect_language(&self) -> Option<String> {
-        let base = &self.project_path;
-        
-        if base.join("Cargo.toml").exists() {
-            Some("rust".to_string())
-        } else if base.join("go.mod").exists() {
-            Some("go".to_string())
-        } else if base.join("package.json").exists() {
-            Some("javascript".to_string())
-        } else if base.join("requirements.txt").exists() || base.join("pyproject.toml").exists() {
-            Some("python".to_string())
-        } else if base.join("pom.xml").exists() || base.join("build.gradle").exists() {
-            Some("java".to_string())
-        } else {
-            None
-        }
+        crate::language::detect_language(&self.project_path).map(|language| language.to_string())
     }
 
     /// Get a summary of the project state.
@@ -219,11 +206,7 @@ impl Session {
             if recent_count > 0 {
                 summary.push("Recent messages:".to_string());
                 for msg in self.conversation_history.iter().rev().take(recent_count).rev() {
-                    let preview = if msg.content.len() > 100 {
-                        format!("{}...", &msg.content[..100])
-                    } else {
-                        msg.content.clone()
-                    };
+                    let preview = truncate_display(&msg.content, 100);
                     summary.push(format!("  {}: {}", msg.role, preview));
                 }
             }
@@ -0,0 +1,423 @@
+//! Project language detection and per-language default commands.
+//!
+//! The test tool, the run tool, the scaffold check and the interactive session
+//! all need to know what language a project is in. They share [`detect_language`],
+//! which looks at the manifest files in the project root:
+//!
+//! | File | Language |
+//! |------|----------|
+//! | `Cargo.toml` | Rust |
+//! | `go.mod` | Go |
+//! | `tsconfig.json` | TypeScript |
+//! | `package.json` | JavaScript (TypeScript with a `tsconfig.json`) |
+//! | `pyproject.toml`, `setup.py`, `requirements.txt` | Python |
+//! | `pom.xml`, `build.gradle`, `build.gradle.kts` | Java |
+//!
+//! Before any of those exist, the design's declared language is used instead;
+//! [`reconcile`] picks between the two and warns when they disagree.
+//!
+//! A task may only touch part of a mixed project: Python scripts in a mostly
+//! Rust repository. An [`ExtensionMap`] maps the extensions of the files the
+//! task touched to languages, so its tests run with that language's command
+//! instead of the project's.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Languages Ralph can build, test, lint and format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    Rust,
+    Python,
+    TypeScript,
+    JavaScript,
+    Go,
+    Java,
+    Unknown,
+}
+
+impl std::fmt::Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Language::Rust => write!(f, "rust"),
+            Language::Python => write!(f, "python"),
+            Language::TypeScript => write!(f, "typescript"),
+            Language::JavaScript => write!(f, "javascript"),
+            Language::Go => write!(f, "go"),
+            Language::Java => write!(f, "java"),
+            Language::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+impl std::str::FromStr for Language {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.trim().to_lowercase().as_str() {
+            "rust" | "rs" => Language::Rust,
+            "python" | "py" => Language::Python,
+            "typescript" | "ts" => Language::TypeScript,
+            "javascript" | "js" | "node" | "nodejs" => Language::JavaScript,
+            "go" | "golang" => Language::Go,
+            "java" => Language::Java,
+            _ => Language::Unknown,
+        })
+    }
+}
+
+impl Language {
+    /// Parse a language from a string.
+    pub fn parse(s: &str) -> Self {
+        s.parse().unwrap_or(Language::Unknown)
+    }
+
+    /// Get the test command for this language.
+    pub fn test_command(&self) -> Option<(&str, Vec<&str>)> {
+        match self {
+            Language::Rust => Some(("cargo", vec!["test"])),
+            Language::Python => Some(("pytest", vec!["-v"])),
+            Language::TypeScript | Language::JavaScript => Some(("npx", vec!["vitest", "run"])),
+            Language::Go => Some(("go", vec!["test", "./..."])),
+            Language::Java => Some(("mvn", vec!["test"])),
+            Language::Unknown => None,
+        }
+    }
+
+    /// Get the command that checks the project builds, without running tests.
+    pub fn build_command(&self) -> Option<(&str, Vec<&str>)> {
+        match self {
+            Language::Rust => Some(("cargo", vec!["build"])),
+            Language::Python => {
+                Some(("python3", vec!["-m", "compileall", "-q", "-x", "/\\.", "."]))
+            }
+            Language::TypeScript => Some(("npx", vec!["tsc", "--noEmit"])),
+            Language::Go => Some(("go", vec!["build", "./..."])),
+            Language::Java => Some(("mvn", vec!["-q", "compile"])),
+            Language::JavaScript | Language::Unknown => None,
+        }
+    }
+
+    /// Get the lint command for this language.
+    pub fn lint_command(&self) -> Option<(&str, Vec<&str>)> {
+        match self {
+            Language::Rust => Some(("cargo", vec!["clippy", "--all-targets"])),
+            Language::Python => Some(("ruff", vec!["check", "."])),
+            Language::TypeScript | Language::JavaScript => Some(("npx", vec!["eslint", "."])),
+            Language::Go => Some(("go", vec!["vet", "./..."])),
+            Language::Java | Language::Unknown => None,
+        }
+    }
+
+    /// Get the command that formats the project in place.
+    pub fn format_command(&self) -> Option<(&str, Vec<&str>)> {
+        match self {
+            Language::Rust => Some(("cargo", vec!["fmt"])),
+            Language::Python => Some(("ruff", vec!["format", "."])),
+            Language::TypeScript | Language::JavaScript => {
+                Some(("npx", vec!["prettier", "--write", "."]))
+            }
+            Language::Go => Some(("gofmt", vec!["-w", "."])),
+            Language::Java | Language::Unknown => None,
+        }
+    }
+
+    /// Flag that sets the build tool's parallel job count (`cargo --jobs`, `go -p`, `mvn -T`).
+    ///
+    /// It goes right after the subcommand of [`test_command`](Self::test_command)
+    /// and [`build_command`](Self::build_command).
+    pub fn jobs_flag(&self) -> Option<&'static str> {
+        match self {
+            Language::Rust => Some("--jobs"),
+            Language::Go => Some("-p"),
+            Language::Java => Some("-T"),
+            Language::Python | Language::TypeScript | Language::JavaScript | Language::Unknown => None,
+        }
+    }
+
+    /// Get the test framework name for this language.
+    pub fn test_framework(&self) -> &str {
+        match self {
+            Language::Rust => "cargo test",
+            Language::Python => "pytest",
+            Language::TypeScript | Language::JavaScript => "vitest",
+            Language::Go => "go test",
+            Language::Java => "maven surefire",
+            Language::Unknown => "unknown",
+        }
+    }
+
+    /// Whether a project detected as `self` fits a design declaring `declared`.
+    ///
+    /// A TypeScript design starts out as a plain `package.json` project, so
+    /// JavaScript files satisfy it until the `tsconfig.json` is written.
+    fn satisfies(&self, declared: Language) -> bool {
+        *self == declared || (*self == Language::JavaScript && declared == Language::TypeScript)
+    }
+}
+
+/// Default file extensions of each language, without the dot.
+pub const DEFAULT_EXTENSIONS: &[(&str, Language)] = &[
+    ("rs", Language::Rust),
+    ("go", Language::Go),
+    ("py", Language::Python),
+    ("pyi", Language::Python),
+    ("ts", Language::TypeScript),
+    ("tsx", Language::TypeScript),
+    ("mts", Language::TypeScript),
+    ("cts", Language::TypeScript),
+    ("js", Language::JavaScript),
+    ("jsx", Language::JavaScript),
+    ("mjs", Language::JavaScript),
+    ("cjs", Language::JavaScript),
+    ("java", Language::Java),
+    ("kt", Language::Java),
+];
+
+/// File extension to language table, [`DEFAULT_EXTENSIONS`] plus overrides.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtensionMap {
+    languages: BTreeMap<String, Language>,
+}
+
+impl Default for ExtensionMap {
+    fn default() -> Self {
+        Self {
+            languages: DEFAULT_EXTENSIONS.iter().map(|(ext, lang)| (ext.to_string(), *lang)).collect(),
+        }
+    }
+}
+
+impl ExtensionMap {
+    /// The defaults with `overrides` on top; mapping to `Unknown` removes an extension.
+    pub fn with_overrides(overrides: &BTreeMap<String, Language>) -> Self {
+        let mut map = Self::default();
+        for (ext, language) in overrides {
+            map.set(ext, *language);
+        }
+        map
+    }
+
+    /// Map `ext` (with or without the dot, any case) to `language`.
+    pub fn set(&mut self, ext: &str, language: Language) {
+        let ext = normalize_extension(ext);
+        if language == Language::Unknown {
+            self.languages.remove(&ext);
+        } else {
+            self.languages.insert(ext, language);
+        }
+    }
+
+    /// Language of the file at `path`, by its extension.
+    pub fn language_of(&self, path: &str) -> Option<Language> {
+        let ext = Path::new(path).extension()?.to_str()?;
+        self.languages.get(&normalize_extension(ext)).copied()
+    }
+
+    /// Language whose commands fit a task that touched `files`.
+    ///
+    /// The language with the most touched files wins; `project` wins a tie and
+    /// is used when no file has a known extension (only docs or config touched).
+    pub fn task_language(&self, files: &[String], project: Language) -> Language {
+        let mut counts: Vec<(Language, usize)> = Vec::new();
+        for language in files.iter().filter_map(|file| self.language_of(file)) {
+            match counts.iter_mut().find(|(l, _)| *l == language) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((language, 1)),
+            }
+        }
+        let Some(most) = counts.iter().map(|(_, count)| *count).max() else {
+            return project;
+        };
+        if counts.iter().any(|(l, count)| *l == project && *count == most) {
+            return project;
+        }
+        counts.into_iter().find(|(_, count)| *count == most).map_or(project, |(l, _)| l)
+    }
+}
+
+/// Parse `ext=language` pairs separated by commas (`py=python,kt=java`).
+pub fn parse_extension_overrides(spec: &str) -> Result<BTreeMap<String, Language>, String> {
+    let mut overrides = BTreeMap::new();
+    for pair in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (ext, language) = pair
+            .split_once('=')
+            .ok_or_else(|| format!("'{}' is not ext=language", pair))?;
+        let ext = normalize_extension(ext);
+        if ext.is_empty() {
+            return Err(format!("'{}' has no extension", pair));
+        }
+        let language = match language.trim() {
+            "none" => Language::Unknown,
+            name => match Language::parse(name) {
+                Language::Unknown => return Err(format!("'{}' is not a known language", name)),
+                language => language,
+            },
+        };
+        overrides.insert(ext, language);
+    }
+    Ok(overrides)
+}
+
+fn normalize_extension(ext: &str) -> String {
+    ext.trim().trim_start_matches('.').to_lowercase()
+}
+
+/// Detect the language of the project at `project_path` from its manifest files.
+///
+/// Returns `None` when no known manifest exists yet.
+pub fn detect_language(project_path: &Path) -> Option<Language> {
+    let has = |file: &str| project_path.join(file).exists();
+
+    if has("Cargo.toml") {
+        Some(Language::Rust)
+    } else if has("go.mod") {
+        Some(Language::Go)
+    } else if has("tsconfig.json") {
+        Some(Language::TypeScript)
+    } else if has("package.json") {
+        Some(Language::JavaScript)
+    } else if has("pyproject.toml") || has("setup.py") || has("requirements.txt") {
+        Some(Language::Python)
+    } else if has("pom.xml") || has("build.gradle") || has("build.gradle.kts") {
+        Some(Language::Java)
+    } else {
+        None
+    }
+}
+
+/// Warning for a design language that the project files contradict, if any.
+///
+/// Unrecognised declared languages and projects without manifests never mismatch.
+pub fn language_mismatch(declared: &str, detected: Option<Language>) -> Option<String> {
+    let declared = Language::parse(declared);
+    let detected = detected?;
+    if declared == Language::Unknown || detected.satisfies(declared) {
+        return None;
+    }
+    Some(format!(
+        "Design declares {} but the project files are {}; using {}",
+        declared, detected, detected
+    ))
+}
+
+/// Language of the project at `project_path`, given the design's declared language.
+///
+/// Project files win once they exist; until then the declared language is used.
+/// A mismatch is logged as a warning.
+pub fn reconcile(declared: &str, project_path: &Path) -> Language {
+    let detected = detect_language(project_path);
+    if let Some(warning) = language_mismatch(declared, detected) {
+        tracing::warn!("{}", warning);
+        crate::warnings::record(crate::warnings::Warning::new(
+            crate::warnings::WarningCategory::Language,
+            warning,
+        ));
+    }
+    detected.unwrap_or_else(|| Language::parse(declared))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detect_language_from_manifests() {
+        let cases: [(&[&str], Option<Language>); 8] = [
+            (&["Cargo.toml"], Some(Language::Rust)),
+            (&["go.mod"], Some(Language::Go)),
+            (&["package.json"], Some(Language::JavaScript)),
+            (&["package.json", "tsconfig.json"], Some(Language::TypeScript)),
+            (&["pyproject.toml"], Some(Language::Python)),
+            (&["requirements.txt"], Some(Language::Python)),
+            (&["build.gradle.kts"], Some(Language::Java)),
+            (&["README.md"], None),
+        ];
+        for (files, expected) in cases {
+            let temp_dir = TempDir::new().unwrap();
+            for file in files {
+                std::fs::write(temp_dir.path().join(file), "").unwrap();
+            }
+            assert_eq!(detect_language(temp_dir.path()), expected, "{:?}", files);
+        }
+    }
+
+    #[test]
+    fn test_reconcile_with_design_language() {
+        let temp_dir = TempDir::new().unwrap();
+        assert_eq!(reconcile("Rust", temp_dir.path()), Language::Rust);
+
+        std::fs::write(temp_dir.path().join("package.json"), "{}").unwrap();
+        assert_eq!(language_mismatch("typescript", detect_language(temp_dir.path())), None);
+        assert_eq!(
+            language_mismatch("python", detect_language(temp_dir.path())).unwrap(),
+            "Design declares python but the project files are javascript; using javascript"
+        );
+        assert_eq!(reconcile("python", temp_dir.path()), Language::JavaScript);
+        assert_eq!(language_mismatch("cobol", Some(Language::Go)), None);
+    }
+
+    #[test]
+    fn test_default_commands() {
+        assert_eq!(Language::Rust.format_command(), Some(("cargo", vec!["fmt"])));
+        assert_eq!(Language::Go.lint_command(), Some(("go", vec!["vet", "./..."])));
+        assert_eq!(Language::JavaScript.build_command(), None);
+        for language in [Language::Rust, Language::Python, Language::Go, Language::Java] {
+            assert!(language.build_command().is_some());
+            assert!(language.test_command().is_some());
+        }
+        assert_eq!(Language::Unknown.test_command(), None);
+        assert_eq!(Language::Rust.jobs_flag(), Some("--jobs"));
+        assert_eq!(Language::Python.jobs_flag(), None);
+    }
+
+    fn files(paths: &[&str]) -> Vec<String> {
+        paths.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_extension_table() {
+        let map = ExtensionMap::default();
+        for (path, expected) in [
+            ("src/main.rs", Some(Language::Rust)),
+            ("cmd/server/main.go", Some(Language::Go)),
+            ("web/App.TSX", Some(Language::TypeScript)),
+            ("scripts/seed.py", Some(Language::Python)),
+            ("index.mjs", Some(Language::JavaScript)),
+            ("README.md", None),
+            ("Makefile", None),
+        ] {
+            assert_eq!(map.language_of(path), expected, "{}", path);
+        }
+
+        let overrides = parse_extension_overrides(".pyx=python, kt=none,TS=javascript").unwrap();
+        let map = ExtensionMap::with_overrides(&overrides);
+        assert_eq!(map.language_of("fast.pyx"), Some(Language::Python));
+        assert_eq!(map.language_of("App.kt"), None);
+        assert_eq!(map.language_of("app.ts"), Some(Language::JavaScript));
+
+        assert!(parse_extension_overrides("py").is_err());
+        assert!(parse_extension_overrides("py=cobol").is_err());
+        assert!(parse_extension_overrides("=rust").is_err());
+        assert_eq!(parse_extension_overrides("").unwrap(), BTreeMap::new());
+    }
+
+    #[test]
+    fn test_task_language_follows_touched_files() {
+        let map = ExtensionMap::default();
+        // Only Python touched in a Rust repository: pytest, not cargo test
+        assert_eq!(
+            map.task_language(&files(&["scripts/etl.py", "scripts/test_etl.py", "README.md"]), Language::Rust),
+            Language::Python
+        );
+        // Mixed: the project's language wins a tie, otherwise the most-touched one
+        assert_eq!(map.task_language(&files(&["a.py", "src/lib.rs"]), Language::Rust), Language::Rust);
+        assert_eq!(map.task_language(&files(&["a.ts", "b.ts", "c.go"]), Language::Rust), Language::TypeScript);
+        // Nothing with a known extension
+        assert_eq!(map.task_language(&files(&["docs/api.md"]), Language::Go), Language::Go);
+        assert_eq!(map.task_language(&[], Language::Go), Language::Go);
+    }
+}
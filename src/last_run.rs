@@ -0,0 +1,265 @@
+//! What changed since the previous run.
+//!
+//! Scheduled re-runs of the same project mostly repeat the same status. At the
+//! end of each run the loop saves a [`RunSnapshot`] (the [`TaskStats`] and every
+//! task's status) to [`LAST_RUN_FILE`], and compares it with the snapshot the
+//! previous run left:
+//!
+//! ```text
+//! Since last run: +3 completed, 1 newly failed (T-009), 2 new tasks
+//! ```
+//!
+//! A task is newly failed when it is blocked now and was not blocked before;
+//! it is new when the previous snapshot did not have it.
+//!
+//! The snapshot also keeps the run's [`Warning`]s, so the warnings section
+//! printed at the end of a run is available as JSON afterwards. It is the
+//! run's report for tools as well: it carries a `schema_version`
+//! ([`RUN_REPORT_SCHEMA_VERSION`]) and a `_meta` [`Provenance`] naming the
+//! Ralph version, the model and the hash of the run's configuration.
+
+use crate::models::{TaskList, TaskStats, TaskStatus};
+use crate::provenance::Provenance;
+use crate::warnings::Warning;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Snapshot file (relative to the project) written at the end of each run.
+pub const LAST_RUN_FILE: &str = ".ralph/last-run.json";
+
+/// Version of the snapshot's JSON shape; raised when a field changes meaning
+/// or goes away.
+pub const RUN_REPORT_SCHEMA_VERSION: u32 = 1;
+
+/// Task counts and statuses at the end of a run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunSnapshot {
+    /// Shape of this file (0 for snapshots saved before it was versioned)
+    #[serde(default)]
+    pub schema_version: u32,
+    /// Which Ralph version, run, model and configuration produced it
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Provenance>,
+    /// Task counts
+    pub stats: TaskStats,
+    /// Status of every task by ID
+    pub statuses: BTreeMap<String, TaskStatus>,
+    /// Warnings raised during the run
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<Warning>,
+}
+
+impl RunSnapshot {
+    /// Take a snapshot of a task list.
+    pub fn of(list: &TaskList) -> Self {
+        Self {
+            schema_version: RUN_REPORT_SCHEMA_VERSION,
+            meta: None,
+            stats: list.get_stats(),
+            statuses: list
+                .get_all_tasks()
+                .into_iter()
+                .map(|t| (t.id.clone(), t.status))
+                .collect(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Attach the warnings raised during the run.
+    pub fn with_warnings(mut self, warnings: Vec<Warning>) -> Self {
+        self.warnings = warnings;
+        self
+    }
+
+    /// Attach the run's provenance.
+    pub fn with_provenance(mut self, provenance: Provenance) -> Self {
+        self.meta = Some(provenance);
+        self
+    }
+
+    /// Path of the snapshot file in a project.
+    pub fn path(project_path: &Path) -> PathBuf {
+        project_path.join(LAST_RUN_FILE)
+    }
+
+    /// The snapshot the previous run saved, if any.
+    ///
+    /// A malformed file is ignored with a warning.
+    pub fn load(project_path: &Path) -> Option<Self> {
+        let content = fs::read_to_string(Self::path(project_path)).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(snapshot) => Some(snapshot),
+            Err(e) => {
+                tracing::warn!(error = %e, "Ignoring malformed last-run snapshot");
+                None
+            }
+        }
+    }
+
+    /// Save the snapshot for the next run to compare against.
+    pub fn save(&self, project_path: &Path) -> Result<(), String> {
+        let path = Self::path(project_path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create '{}': {}", parent.display(), e))?;
+        }
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize last-run snapshot: {}", e))?;
+        crate::persist::write_atomic(&path, content.as_bytes())
+            .map_err(|e| format!("Failed to write '{}': {}", path.display(), e))
+    }
+
+    /// How this snapshot differs from `previous`.
+    pub fn since(&self, previous: &RunSnapshot) -> RunDelta {
+        let mut newly_failed = Vec::new();
+        let mut new_tasks = Vec::new();
+        for (id, status) in &self.statuses {
+            let before = previous.statuses.get(id);
+            if before.is_none() {
+                new_tasks.push(id.clone());
+            }
+            if *status == TaskStatus::Blocked && before != Some(&TaskStatus::Blocked) {
+                newly_failed.push(id.clone());
+            }
+        }
+        RunDelta {
+            completed: self.stats.completed as i64 - previous.stats.completed as i64,
+            newly_failed,
+            new_tasks,
+        }
+    }
+}
+
+/// Changes between two runs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunDelta {
+    /// Change in the number of completed tasks
+    pub completed: i64,
+    /// Tasks blocked now that were not blocked before
+    pub newly_failed: Vec<String>,
+    /// Tasks the previous run did not have
+    pub new_tasks: Vec<String>,
+}
+
+impl RunDelta {
+    /// Whether nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.completed == 0 && self.newly_failed.is_empty() && self.new_tasks.is_empty()
+    }
+}
+
+impl std::fmt::Display for RunDelta {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return write!(f, "Since last run: no changes");
+        }
+        let mut parts = vec![format!("{:+} completed", self.completed)];
+        if !self.newly_failed.is_empty() {
+            parts.push(format!(
+                "{} newly failed ({})",
+                self.newly_failed.len(),
+                self.newly_failed.join(", ")
+            ));
+        }
+        match self.new_tasks.len() {
+            0 => {}
+            1 => parts.push("1 new task".to_string()),
+            n => parts.push(format!("{} new tasks", n)),
+        }
+        write!(f, "Since last run: {}", parts.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Task;
+    use crate::warnings::WarningCategory;
+    use tempfile::TempDir;
+
+    fn list(statuses: &[(&str, TaskStatus)]) -> TaskList {
+        let mut list = TaskList::new("calc", "rust");
+        for (i, (id, status)) in statuses.iter().enumerate() {
+            let mut task = Task::new(*id, format!("Task {}", id), "", i as u32 + 1);
+            task.status = *status;
+            list.add_task(task);
+        }
+        list
+    }
+
+    #[test]
+    fn test_delta_since_previous_run() {
+        use TaskStatus::*;
+        let previous = RunSnapshot::of(&list(&[
+            ("T-001", Completed),
+            ("T-002", Pending),
+            ("T-003", Pending),
+            ("T-004", Blocked),
+        ]));
+        let current = RunSnapshot::of(&list(&[
+            ("T-001", Completed),
+            ("T-002", Completed),
+            ("T-003", Blocked),
+            ("T-004", Blocked),
+            ("T-005", Completed),
+            ("T-006", Pending),
+        ]));
+
+        let delta = current.since(&previous);
+        assert_eq!(delta.completed, 2);
+        assert_eq!(delta.newly_failed, vec!["T-003"]);
+        assert_eq!(delta.new_tasks, vec!["T-005", "T-006"]);
+        assert_eq!(
+            delta.to_string(),
+            "Since last run: +2 completed, 1 newly failed (T-003), 2 new tasks"
+        );
+        assert_eq!(
+            previous.since(&previous).to_string(),
+            "Since last run: no changes"
+        );
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(RunSnapshot::load(temp_dir.path()).is_none());
+
+        let snapshot = RunSnapshot::of(&list(&[("T-001", TaskStatus::Completed)])).with_warnings(vec![
+            Warning::new(WarningCategory::Legacy, "legacy file_structure"),
+        ]);
+        snapshot.save(temp_dir.path()).unwrap();
+        assert_eq!(RunSnapshot::load(temp_dir.path()), Some(snapshot));
+
+        fs::write(RunSnapshot::path(temp_dir.path()), "not json").unwrap();
+        assert!(RunSnapshot::load(temp_dir.path()).is_none());
+    }
+
+    #[test]
+    fn test_report_shape_is_versioned_with_provenance() {
+        use crate::models::{ModelConfig, RalphConfig};
+        use crate::provenance::{config_hash, AgentProvenance};
+
+        let config = RalphConfig::default();
+        let provenance = Provenance::new()
+            .with_agent("ralph", AgentProvenance::from_config(&ModelConfig::default(), "Build it"))
+            .with_config(&config);
+        let snapshot = RunSnapshot::of(&list(&[("T-001", TaskStatus::Completed)])).with_provenance(provenance);
+
+        let json = serde_json::to_value(&snapshot).unwrap();
+        let mut keys: Vec<&str> = json.as_object().unwrap().keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        assert_eq!(keys, ["_meta", "schema_version", "stats", "statuses"]);
+        assert_eq!(json["schema_version"], RUN_REPORT_SCHEMA_VERSION);
+        assert_eq!(json["_meta"]["ralph_version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(json["_meta"]["config_sha256"], config_hash(&config));
+        assert_eq!(json["_meta"]["agents"]["ralph"]["model"], ModelConfig::default().model_name);
+        assert_eq!(json["statuses"]["T-001"], "completed");
+
+        // Snapshots from before versioning still load
+        let old = r#"{"stats":{"total":0,"completed":0,"in_progress":0,"pending":0,"blocked":0,"completion_rate":0.0},"statuses":{}}"#;
+        let old: RunSnapshot = serde_json::from_str(old).unwrap();
+        assert_eq!((old.schema_version, old.meta), (0, None));
+    }
+}
@@ -43,30 +43,81 @@
 //!            Completion Promise
 //! ```
 
+pub mod abort;
 pub mod agents;
+pub mod archive;
+pub mod capabilities;
+pub mod cleanup;
+pub mod console;
+pub mod context_trim;
+pub mod dataset;
+pub mod demo;
+pub mod dependencies;
+pub mod diff;
+pub mod edit_guard;
 pub mod error;
+pub mod exec_env;
+pub mod gates;
+pub mod generation;
+pub mod glossary;
+pub mod incremental;
 pub mod interactive;
+pub mod language;
+pub mod last_run;
+pub mod model_timeout;
 pub mod models;
 pub mod orchestrator;
 pub mod output;
+pub mod pause;
+pub mod persist;
+pub mod preflight;
+pub mod preview;
+pub mod progress;
+pub mod project_state;
+pub mod provider_error;
+pub mod provenance;
+pub mod replay;
+pub mod resources;
+pub mod retry;
+pub mod run_state;
+pub mod scaffold;
+pub mod schema;
+pub mod scope;
+pub mod smoke;
+pub mod story_cost;
 pub mod telemetry;
+pub mod template;
+pub mod textfmt;
+pub mod timefmt;
+pub mod tool_log;
 pub mod tools;
+pub mod validation;
+pub mod verification;
+pub mod warnings;
 
 // Re-export main types for convenience
 pub use error::{RalphError, Result};
 pub use models::{
     // Config types
     AgentModelConfig,
+    ConsoleSetting,
     DebugLevel,
+    MergeStrategy,
     ModelConfig,
+    PalettePreset,
     RalphConfig,
     RalphConfigBuilder,
+    SchedulingStrategy,
+    BuildConcurrency,
     TelemetryConfig,
     ValidationError,
+    VerificationCadence,
     MAX_ITERATIONS_LIMIT,
+    MAX_CONCURRENT_BUILDS_LIMIT,
     MAX_RETRIES_LIMIT,
     MAX_TOKENS_LIMIT,
     SUPPORTED_PROVIDERS,
+    Provider,
     // PRD types
     AcceptanceCriterion,
     PrdDocument,
@@ -77,10 +128,15 @@ pub use models::{
     DesignDocument,
     FileStructure,
     TechnologyStack,
+    TestInfrastructure,
     // Task types
+    BlockedTask,
+    CommitType,
+    DiffStats,
     Phase,
     Sprint,
     Task,
+    TaskFingerprint,
     TaskList,
     TaskStatus,
     // Progress types
@@ -94,13 +150,17 @@ pub use models::{
 pub use tools::{
     // Core tools
     FileTool, GitTool, ProgressTool, TaskTool, TestTool,
+    CommitAuthor,
     // Interactive mode tools
-    AddFeatureMode, AddFeatureTool, GetTimeTool, Language, RunPipelineTool, RunProjectTool,
-    SearchResult, WebSearchTool,
+    AddFeatureMode, AddFeatureTool, GetTimeTool, RunPipelineTool, RunProjectTool, SearchResult,
+    WebSearchTool,
 };
 
+// Re-export language detection
+pub use language::{Language, detect_language};
+
 // Re-export agents
-pub use agents::{ArchitectAgent, CompletionStatus, PrdAgent, PrdAgentBuilder, PrdResult, RalphLoopAgent, RalphLoopAgentBuilder};
+pub use agents::{ArchitectAgent, ArchitectAgentBuilder, CompletionStatus, CriterionVerdict, JudgeAgent, JudgeAgentBuilder, JudgeVerdict, PlanFormat, PrdAgent, PrdAgentBuilder, PrdResult, RalphLoopAgent, RalphLoopAgentBuilder};
 
 // Re-export orchestrator
 pub use orchestrator::{OrchestratorBuilder, OrchestratorState, PipelinePhase, RalphOrchestrator};
@@ -109,4 +169,96 @@ pub use orchestrator::{OrchestratorBuilder, OrchestratorState, PipelinePhase, Ra
 pub use interactive::{InteractiveRepl, InteractiveReplBuilder, Message, OrchestratorAgent, OrchestratorAgentBuilder, ProjectContext, Session, REQUIRED_TOOLS};
 
 // Re-export output
-pub use output::{RalphOutput, process_event_part};
+pub use output::{OutputSink, OutputWriter, RalphOutput, StdWriter, flush_output, process_event_part};
+pub use progress::{MultiProgressView, ProgressSnapshot};
+
+// Re-export console types
+pub use console::{ConsoleCapabilities, ConsoleEnv, Glyphs, Palette, StyleSpec};
+
+// Re-export verification
+pub use verification::{ContinuousVerifier, VerificationReport};
+
+// Re-export task abort
+pub use abort::{AbortOutcome, AbortReport, AbortRequest, AbortSignal, TaskAborter, WriteBackup};
+
+// Re-export per-task file budget
+pub use scope::{FileScope, ScopeDeviation, ScopeReport};
+
+// Re-export execution environment
+pub use exec_env::{ArtifactIsolation, ExecEnv};
+
+// Re-export scaffold build check
+pub use scaffold::ScaffoldCheck;
+
+// Re-export the per-write smoke gate
+pub use smoke::{SmokeFinding, SmokeGate};
+
+// Re-export the rewrite-to-edit policy
+pub use edit_guard::{EditGuard, OutlineItem};
+
+// Re-export the project glossary
+pub use glossary::{Glossary, GlossaryCheck, GlossaryTerm, NamingDrift};
+
+// Re-export the run's warning collector
+pub use warnings::{Warning, WarningCategory, WarningCollector};
+
+// Re-export completion gates
+pub use gates::{CompletionGates, GateVerdict};
+
+// Re-export provider error decoding
+pub use provider_error::{ProviderError, ProviderErrorKind};
+
+// Re-export model call retries
+pub use retry::RetryPolicy;
+
+// Re-export pause control
+pub use pause::{LoopState, PauseControl};
+
+// Re-export write-behind persistence
+pub use persist::{WriteBehind, WriteBehindPolicy};
+
+// Re-export project templates
+pub use template::ProjectTemplate;
+
+// Re-export diff rendering
+pub use diff::{DiffFormat, render_diff};
+
+// Re-export plan validation
+pub use validation::{ValidationCheck, ValidationReport, validate_project};
+
+// Re-export progress accounting
+pub use run_state::{PlanSummary, ProgressChange, RunState, TaskProgress};
+
+// Re-export the comparison with the previous run
+pub use last_run::{RunDelta, RunSnapshot};
+pub use project_state::{ProjectState, RalphBuild, VersionChange};
+
+// Re-export end-of-run cleanup advice
+pub use cleanup::{CleanupReport, Marker, record_cleanup_task};
+
+// Re-export the execution preview
+pub use preview::{PreviewStep, RunPreview, UnreachableTask, preview_run};
+
+// Re-export offline demo and replay provider
+pub use demo::DemoReport;
+pub use replay::ReplayModel;
+
+// Re-export dataset export
+pub use dataset::{DatasetRecord, DatasetRecorder, DatasetTarget};
+
+// Re-export build capabilities
+pub use capabilities::{Capabilities, capabilities};
+
+// Re-export run archive
+pub use archive::{ArchiveEntry, ArchiveManifest, RunArtifacts, collect_run_artifacts};
+#[cfg(feature = "archive")]
+pub use archive::export_run_archive;
+
+// Re-export artifact provenance
+pub use provenance::{AgentProvenance, Provenance};
+
+// Re-export artifact schemas
+pub use schema::{ArtifactSchema, DESIGN_SCHEMA_ID, SCHEMAS, TASKS_SCHEMA_ID};
+
+// Re-export dependency advice
+pub use dependencies::{AdviceReport, DependencyAdvice, DependencyAdvisor, HttpRegistry};
@@ -12,11 +12,50 @@
 //! RALPH_MODEL_PROVIDER=anthropic ralph "Build a REST API"
 //! ```
 
-use adk_ralph::{DebugLevel, InteractiveRepl, PipelinePhase, RalphConfig, RalphOrchestrator, RalphOutput, Result, TelemetryConfig};
+use adk_ralph::agents::judge_agent::{read_task_files, task_files};
+use adk_ralph::agents::prompt::{diff_lines, AssembledPrompt, PROMPT_AGENTS};
+use adk_ralph::output::flush_output;
+use adk_ralph::preflight::{self, ConfigSources};
+use adk_ralph::project_state;
+use adk_ralph::{AbortRequest, DebugLevel, DesignDocument, InteractiveRepl, OutputSink, PipelinePhase, RalphConfig, RalphOrchestrator, RalphOutput, Result, TaskList, TelemetryConfig};
 use clap::{Parser, Subcommand, ValueEnum};
+use adk_ralph::console::{self, palette, ConsoleEnv};
 use colored::Colorize;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tracing::info;
 
+/// Human-readable output goes to stderr while stdout carries JSON events
+/// (`--output json` or `RALPH_EVENT_LOG=-`).
+static HUMAN_ON_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Human-readable output is turned off (`--output json-only` or
+/// `RALPH_HUMAN_OUTPUT=false`); errors are still printed.
+static HUMAN_OFF: AtomicBool = AtomicBool::new(false);
+
+/// `println!` for human-readable output; writes to stderr in JSON mode.
+macro_rules! sayln {
+    ($($arg:tt)*) => {
+        if HUMAN_OFF.load(Ordering::Relaxed) {
+        } else if HUMAN_ON_STDERR.load(Ordering::Relaxed) {
+            eprintln!($($arg)*)
+        } else {
+            println!($($arg)*)
+        }
+    };
+}
+
+/// `print!` for human-readable output; writes to stderr in JSON mode.
+macro_rules! say {
+    ($($arg:tt)*) => {
+        if HUMAN_OFF.load(Ordering::Relaxed) {
+        } else if HUMAN_ON_STDERR.load(Ordering::Relaxed) {
+            eprint!($($arg)*)
+        } else {
+            print!($($arg)*)
+        }
+    };
+}
+
 /// Ralph - Multi-Agent Autonomous Development System
 #[derive(Parser, Debug)]
 #[command(name = "ralph")]
@@ -34,6 +73,29 @@ struct Cli {
     #[arg(short = 'p', long, global = true)]
     project_path: Option<String>,
 
+    /// Write completed tasks as a fine-tuning dataset shard to this directory
+    /// (overrides RALPH_DATASET_EXPORT)
+    #[arg(long, global = true, value_name = "DIR")]
+    export_dataset: Option<String>,
+
+    /// Output format: human-readable, JSON-line events on stdout with the
+    /// human-readable output on stderr, or the events alone with only errors
+    /// on stderr (overrides RALPH_EVENT_LOG and RALPH_HUMAN_OUTPUT)
+    #[arg(long, value_enum, global = true)]
+    output: Option<CliOutputFormat>,
+
+    /// Run a project pinned to a different ralph version and re-pin it to this one
+    #[arg(long, global = true)]
+    allow_version_change: bool,
+
+    /// Print this build's version, providers and optional features as JSON
+    #[arg(long)]
+    capabilities: bool,
+
+    /// Continue an interrupted run from tasks.json, keeping completed tasks
+    #[arg(long, conflicts_with = "prompt")]
+    resume: bool,
+
     /// Project description (when no subcommand is used)
     #[arg(trailing_var_arg = true)]
     prompt: Vec<String>,
@@ -63,6 +125,17 @@ impl From<CliDebugLevel> for DebugLevel {
     }
 }
 
+/// CLI output format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum CliOutputFormat {
+    /// Colored, human-readable progress
+    Human,
+    /// One JSON object per event on stdout
+    Json,
+    /// JSON events on stdout and no human-readable output besides errors
+    JsonOnly,
+}
+
 #[derive(Subcommand, Debug)]
 enum Commands {
     /// Run the full pipeline from prompt to completion
@@ -70,6 +143,20 @@ enum Commands {
         /// Project description
         #[arg(required = true)]
         prompt: Vec<String>,
+        /// Seed the project from a saved template first
+        #[arg(long)]
+        template: Option<String>,
+    },
+    /// Seed the project directory from a saved template
+    Init {
+        /// Template to seed from
+        #[arg(long)]
+        template: String,
+    },
+    /// Save the current project's design and scaffold as a reusable template
+    Template {
+        #[command(subcommand)]
+        action: TemplateCommand,
     },
     /// Resume from a specific phase
     Resume {
@@ -78,9 +165,24 @@ enum Commands {
         phase: String,
         /// Project description (required for requirements phase)
         prompt: Vec<String>,
+        /// Re-run only completed tasks whose inputs changed, and their dependents
+        #[arg(long)]
+        incremental: bool,
     },
     /// Show current status
     Status,
+    /// Check tasks.json against design.md and the PRD before running
+    Validate,
+    /// Show the execution order and the files each task declares, without calling a model
+    Preview,
+    /// Rank user stories by the cost of their remaining tasks, without calling a model
+    Scope {
+        /// Show the plan, iterations and cost without these stories (e.g. --without US-004)
+        #[arg(long, value_name = "STORY")]
+        without: Vec<String>,
+        #[command(subcommand)]
+        action: Option<ScopeCommand>,
+    },
     /// Validate configuration
     Config,
     /// Start interactive chat mode
@@ -93,6 +195,114 @@ enum Commands {
         #[arg(long)]
         auto_approve: bool,
     },
+    /// Inspect agent prompts without calling a model
+    Prompt {
+        #[command(subcommand)]
+        action: PromptCommand,
+    },
+    /// Control tasks of a running loop
+    Tasks {
+        #[command(subcommand)]
+        action: TasksCommand,
+    },
+    /// Show which run and models produced an artifact
+    Provenance {
+        /// Artifact to inspect (prd.md, design.md, tasks.json, ...)
+        file: std::path::PathBuf,
+    },
+    /// Generate design.md and tasks.json from the PRD
+    Generate {
+        /// PRD to plan from, or '-' for stdin (default: the project's prd.md)
+        #[arg(long)]
+        prd: Option<std::path::PathBuf>,
+        /// Print the plan to stdout instead of writing design.md and tasks.json
+        #[arg(long)]
+        stdout: bool,
+        /// With --stdout, print one JSON object with design_markdown and tasks
+        #[arg(long, requires = "stdout")]
+        json: bool,
+    },
+    /// Revise the design after tasks failed because the plan was wrong
+    Replan {
+        /// Failed task IDs to send back to the architect (default: all blocked tasks)
+        tasks: Vec<String>,
+    },
+    /// Work with the JSON Schemas of tasks.json and design JSON
+    Schema {
+        #[command(subcommand)]
+        action: SchemaCommand,
+    },
+    /// Run the whole pipeline offline on a recorded hello-world project (no API key)
+    Demo {
+        /// Directory to generate into (default: a new temporary directory)
+        #[arg(long)]
+        dir: Option<std::path::PathBuf>,
+    },
+    /// Bundle the run's artifacts into a .tar.gz with a manifest
+    Export {
+        /// Archive to write
+        #[arg(default_value = "ralph-run.tar.gz")]
+        out: std::path::PathBuf,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum TasksCommand {
+    /// Abort the task in progress; the loop rolls back its file writes and moves on
+    Abort {
+        /// Task ID to abort (e.g. T-009)
+        task_id: String,
+        /// Mark the task blocked instead of returning it to the queue
+        #[arg(long)]
+        block: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ScopeCommand {
+    /// Drop stories from scope: their remaining tasks become obsolete
+    Cut {
+        /// Story IDs to cut (e.g. US-004)
+        #[arg(required = true)]
+        stories: Vec<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum TemplateCommand {
+    /// Capture design.md and the scaffold files into a named template
+    Create {
+        /// Template name (letters, digits, '-' and '_')
+        name: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum SchemaCommand {
+    /// Write the artifact schemas to a directory
+    Export {
+        /// Directory to write the schema files into
+        #[arg(long, default_value = "schemas")]
+        dir: std::path::PathBuf,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum PromptCommand {
+    /// Show the full prompt an agent would receive right now
+    Show {
+        /// Agent to inspect (prd, architect, developer, judge)
+        agent: String,
+        /// Task whose code the judge reviews (judge only)
+        #[arg(long)]
+        task: Option<String>,
+        /// Project description (prd only)
+        #[arg(long)]
+        input: Option<String>,
+        /// Compare against the prompt recorded for the agent's last real run
+        #[arg(long)]
+        diff: bool,
+    },
 }
 
 /// Initialize telemetry based on configuration and debug level.
@@ -141,9 +351,10 @@ fn init_telemetry(config: &TelemetryConfig, debug_level: DebugLevel) -> std::res
 }
 
 fn _print_banner() {
-    println!(
+    sayln!(
         "{}",
-        r#"
+        palette().accent(
+            r#"
   ____       _       _     
  |  _ \ __ _| |_ __ | |__  
  | |_) / _` | | '_ \| '_ \ 
@@ -151,86 +362,117 @@ fn _print_banner() {
  |_| \_\__,_|_| .__/|_| |_|
               |_|          
 "#
-        .cyan()
+        )
     );
-    println!(
+    sayln!(
         "{}",
-        "Multi-Agent Autonomous Development System".bright_white()
+        palette().heading("Multi-Agent Autonomous Development System")
     );
-    println!();
+    sayln!();
 }
 
 fn print_config(config: &RalphConfig) {
-    println!("{}", "Configuration:".yellow().bold());
-    println!(
+    sayln!("{}", palette().heading("Configuration:"));
+    sayln!(
         "  PRD Agent:       {} ({}) {}",
-        config.agents.prd_model.provider.cyan(),
+        palette().accent(config.agents.prd_model.provider.as_str()),
         config.agents.prd_model.model_name,
         if config.agents.prd_model.thinking_enabled {
-            "[thinking]".green()
+            palette().success("[thinking]")
         } else {
             "".normal()
         }
     );
-    println!(
+    sayln!(
         "  Architect Agent: {} ({}) {}",
-        config.agents.architect_model.provider.cyan(),
+        palette().accent(config.agents.architect_model.provider.as_str()),
         config.agents.architect_model.model_name,
         if config.agents.architect_model.thinking_enabled {
-            "[thinking]".green()
+            palette().success("[thinking]")
         } else {
             "".normal()
         }
     );
-    println!(
+    sayln!(
         "  Ralph Loop:      {} ({})",
-        config.agents.ralph_model.provider.cyan(),
+        palette().accent(config.agents.ralph_model.provider.as_str()),
         config.agents.ralph_model.model_name
     );
-    println!("  Max Iterations:  {}", config.max_iterations);
-    println!("  Debug Level:     {}", config.debug_level.to_string().cyan());
-    println!("  Project Path:    {}", config.project_path);
-    println!();
+    sayln!("  Max Iterations:  {}", config.max_iterations);
+    sayln!(
+        "  Console:         {}",
+        console::capabilities().explain(&ConsoleEnv::detect_on(
+            OutputSink::from_event_log(config.event_log.as_deref()).console_stream(),
+            config.color,
+            config.unicode
+        ))
+    );
+    if config.resolved_concurrent_builds() > 1 || config.tool_jobs.is_some() {
+        sayln!(
+            "  Build Jobs:      {} per build tool, CPUs split between {} build(s)",
+            config.tool_jobs().map_or_else(|| "default".to_string(), |jobs| jobs.to_string()),
+            config.resolved_concurrent_builds()
+        );
+    }
+    sayln!("  Debug Level:     {}", palette().accent(config.debug_level.to_string()));
+    sayln!("  Project Path:    {}", config.project_path);
+    sayln!();
 }
 
 fn print_status(orchestrator: &RalphOrchestrator) {
-    println!("{}", "Pipeline Status:".yellow().bold());
-    println!("  Current Phase: {}", orchestrator.phase().to_string().cyan());
-    println!();
+    sayln!("{}", palette().heading("Pipeline Status:"));
+    sayln!("  Current Phase: {}", palette().accent(orchestrator.phase().to_string()));
+    sayln!();
 
     // Check for existing artifacts
-    println!("{}", "Artifacts:".yellow().bold());
+    sayln!("{}", palette().heading("Artifacts:"));
     
     let prd_status = if orchestrator.prd_exists() {
-        "✓ exists".green()
+        palette().success("✓ exists")
     } else {
-        "✗ not found".red()
+        palette().failure("✗ not found")
     };
-    println!("  PRD ({}): {}", orchestrator.config().prd_path, prd_status);
+    sayln!("  PRD ({}): {}", orchestrator.config().prd_path, prd_status);
 
     let design_status = if orchestrator.design_exists() {
-        "✓ exists".green()
+        palette().success("✓ exists")
     } else {
-        "✗ not found".red()
+        palette().failure("✗ not found")
     };
-    println!(
+    sayln!(
         "  Design ({}): {}",
         orchestrator.config().design_path,
         design_status
     );
 
     let tasks_status = if orchestrator.tasks_exist() {
-        "✓ exists".green()
+        palette().success("✓ exists")
     } else {
-        "✗ not found".red()
+        palette().failure("✗ not found")
     };
-    println!(
+    sayln!(
         "  Tasks ({}): {}",
         orchestrator.config().tasks_path,
         tasks_status
     );
 
-    println!();
+    let tasks_path = orchestrator.project_path().join(&orchestrator.config().tasks_path);
+    if let Ok(list) = adk_ralph::TaskList::load(&tasks_path) {
+        let flagged: Vec<_> = list
+            .get_all_tasks()
+            .into_iter()
+            .filter(|t| t.needs_review.is_some())
+            .collect();
+        if !flagged.is_empty() {
+            sayln!();
+            sayln!("{}", palette().heading("Needs Review:"));
+            for task in flagged {
+                sayln!("  {} {}", palette().accent(&task.id), task.needs_review.as_deref().unwrap_or_default());
+            }
+        }
+    }
+
+    sayln!();
 }
 
 fn parse_phase(phase_str: &str) -> Result<PipelinePhase> {
@@ -245,19 +487,418 @@ fn parse_phase(phase_str: &str) -> Result<PipelinePhase> {
     }
 }
 
+/// Assemble and print an agent's prompt using the same code paths as a real run.
+fn show_prompt(config: &RalphConfig, agent: &str, task_id: Option<&str>, input: Option<&str>, diff: bool) -> Result<()> {
+    use std::path::Path;
+
+    let project_path = Path::new(&config.project_path);
+    let prompt = match agent.to_lowercase().as_str() {
+        "prd" => AssembledPrompt::prd(input.unwrap_or("<project description>")),
+        "architect" => {
+            let prd_path = project_path.join(&config.prd_path);
+            let prd = std::fs::read_to_string(&prd_path).map_err(|e| {
+                adk_ralph::RalphError::file(prd_path.display().to_string(), format!("Failed to read PRD: {}", e))
+            })?;
+            AssembledPrompt::architect(&prd)
+        }
+        "developer" | "ralph" | "loop" => {
+            let design = DesignDocument::load_markdown(project_path.join(&config.design_path)).ok();
+            AssembledPrompt::developer(design.as_ref(), project_path, &config.completion_promise)
+        }
+        "judge" => {
+            let id = task_id.ok_or_else(|| {
+                adk_ralph::RalphError::Configuration("The judge prompt needs --task <ID>".into())
+            })?;
+            let tasks = TaskList::load(project_path.join(&config.tasks_path)).map_err(adk_ralph::RalphError::Task)?;
+            let task = tasks
+                .get_task(id)
+                .ok_or_else(|| adk_ralph::RalphError::Task(format!("Task not found: {}", id)))?;
+            let files = read_task_files(project_path, &task_files(task));
+            AssembledPrompt::judge(task, &files)
+        }
+        other => {
+            return Err(adk_ralph::RalphError::Configuration(format!(
+                "Unknown agent: {}. Valid agents: {}",
+                other,
+                PROMPT_AGENTS.join(", ")
+            )));
+        }
+    };
+
+    if !diff {
+        say!("{}", prompt.annotated());
+        return Ok(());
+    }
+
+    match AssembledPrompt::load_recorded(project_path, &prompt.agent) {
+        Some(recorded) => {
+            for line in diff_lines(&recorded, &prompt.render()) {
+                if line.starts_with('+') {
+                    sayln!("{}", palette().success(line));
+                } else if line.starts_with('-') {
+                    sayln!("{}", palette().failure(line));
+                } else {
+                    sayln!("{}", palette().dim(line));
+                }
+            }
+        }
+        None => sayln!(
+            "{} No recorded prompt for '{}' (expected at {})",
+            palette().info("ℹ"),
+            prompt.agent,
+            AssembledPrompt::journal_path(project_path, &prompt.agent).display()
+        ),
+    }
+    Ok(())
+}
+
+/// Rank stories by remaining cost, show a what-if without some, or cut them.
+fn show_scope(config: &RalphConfig, without: &[String], action: Option<ScopeCommand>) -> Result<()> {
+    use adk_ralph::story_cost::{self, CostModel};
+
+    let project_path = std::path::Path::new(&config.project_path);
+    let tasks_path = project_path.join(&config.tasks_path);
+    let mut tasks = TaskList::load(&tasks_path).map_err(adk_ralph::RalphError::Task)?;
+    let model = CostModel::calibrated(&tasks, config.cost_per_iteration);
+
+    if let Some(ScopeCommand::Cut { stories }) = action {
+        let cut = story_cost::cut_stories(&mut tasks, &stories).map_err(adk_ralph::RalphError::Task)?;
+        tasks.save(&tasks_path).map_err(adk_ralph::RalphError::Task)?;
+        sayln!(
+            "{} Cut {}: {} task(s) marked obsolete ({})",
+            palette().success("✓"),
+            stories.join(", "),
+            cut.len(),
+            cut.join(", ")
+        );
+        return Ok(());
+    }
+
+    if !without.is_empty() {
+        say!("{}", story_cost::what_if(&tasks, &model, without));
+        return Ok(());
+    }
+
+    let titles: std::collections::BTreeMap<String, String> = adk_ralph::PrdDocument::load_markdown(project_path.join(&config.prd_path))
+        .map(|prd| prd.user_stories.into_iter().map(|s| (s.id, s.title)).collect())
+        .unwrap_or_default();
+    let costs = story_cost::story_costs(&tasks, &model, &titles);
+    let total = story_cost::what_if(&tasks, &model, &[]).before;
+    sayln!("{}", palette().success("Remaining work by user story:").bold());
+    say!("{}", story_cost::render_story_costs(&costs, &total));
+    if config.cost_per_iteration.is_none() {
+        sayln!("Set RALPH_COST_PER_ITERATION to include a cost estimate.");
+    }
+    Ok(())
+}
+
+/// Print the provenance header of a generated artifact.
+fn show_provenance(file: &std::path::Path) -> Result<()> {
+    let provenance = adk_ralph::provenance::read_provenance(file)
+        .map_err(|e| adk_ralph::RalphError::file(file.display().to_string(), e))?;
+    let Some(provenance) = provenance else {
+        sayln!("{} {} has no provenance header", palette().warning("!"), file.display());
+        return Ok(());
+    };
+
+    sayln!("{} {}", palette().success("Provenance:").bold(), file.display());
+    match &provenance.ralph_git_sha {
+        Some(sha) => sayln!("  Ralph version: {} ({})", provenance.ralph_version, sha),
+        None => sayln!("  Ralph version: {}", provenance.ralph_version),
+    }
+    sayln!("  Run ID:        {}", provenance.run_id);
+    sayln!("  Generated at:  {}", provenance.generated_at);
+    if let Some(hash) = &provenance.config_sha256 {
+        sayln!("  Config:        {}", &hash[..12.min(hash.len())]);
+    }
+    for (agent, details) in &provenance.agents {
+        sayln!(
+            "  {:<10} {}/{} (temperature {}, instruction {})",
+            palette().accent(agent),
+            details.provider,
+            details.model,
+            details.temperature,
+            &details.instruction_sha256[..12.min(details.instruction_sha256.len())]
+        );
+    }
+    Ok(())
+}
+
+/// Cross-check the plan and print the report; fails if any check failed.
+fn validate_plan(config: &RalphConfig) -> Result<()> {
+    let project_path = std::path::Path::new(&config.project_path);
+    let prd_path = project_path.join(&config.prd_path);
+    let report = adk_ralph::validation::validate_project_with_prd(
+        &project_path.join(&config.design_path),
+        &project_path.join(&config.tasks_path),
+        prd_path.exists().then_some(prd_path.as_path()),
+    )?;
+    sayln!("{}", report);
+    if report.passed() {
+        Ok(())
+    } else {
+        Err(adk_ralph::RalphError::Task(format!(
+            "Plan validation failed: {} check(s) found problems",
+            report.failures().len()
+        )))
+    }
+}
+
+/// Run the architect on a PRD. With `stdout`, the plan is printed instead of
+/// saved, so `ralph generate --prd - --stdout` is a PRD-to-plan filter.
+async fn generate_plan(config: &RalphConfig, prd: Option<&std::path::Path>, stdout: bool, json: bool) -> Result<()> {
+    use adk_ralph::{ArchitectAgent, PlanFormat};
+    use std::io::Read as _;
+
+    let prd_content = match prd {
+        Some(path) if path.as_os_str() == "-" => {
+            let mut content = String::new();
+            std::io::stdin()
+                .read_to_string(&mut content)
+                .map_err(|e| adk_ralph::RalphError::Prd(format!("Failed to read PRD from stdin: {}", e)))?;
+            Some(content)
+        }
+        Some(path) => Some(std::fs::read_to_string(path).map_err(|e| {
+            adk_ralph::RalphError::file(path.display().to_string(), format!("Failed to read PRD: {}", e))
+        })?),
+        None => None,
+    };
+
+    let mut builder = ArchitectAgent::builder()
+        .model_config(config.agents.architect_model.clone())
+        .prd_path(&config.prd_path)
+        .design_path(&config.design_path)
+        .tasks_path(&config.tasks_path)
+        .project_path(&config.project_path)
+        .structure_limits(config.structure_limits())
+        .coverage_strict(config.coverage_strict)
+        .allow_empty_plan(config.allow_empty_plan)
+        .dry_run(stdout)
+        .output_to_stdout(stdout)
+        .plan_format(if json { PlanFormat::Json } else { PlanFormat::Text });
+    if let Some(prd) = prd_content {
+        builder = builder.prd_content(prd);
+    }
+    let (_, tasks) = builder.build().await?.generate().await?;
+
+    if !stdout {
+        sayln!(
+            "{} Saved design to {}, {} task(s) to {}",
+            palette().success("✓"),
+            config.design_path,
+            tasks.get_all_tasks().len(),
+            config.tasks_path
+        );
+    }
+    Ok(())
+}
+
+/// Write the run archive, or explain how to enable it.
+#[cfg(feature = "archive")]
+fn export_archive(config: &RalphConfig, out: &std::path::Path) -> Result<()> {
+    let project_path = std::path::Path::new(&config.project_path);
+    let manifest = adk_ralph::archive::export_artifacts(project_path, &adk_ralph::RunArtifacts::from_config(config), out)?;
+    sayln!(
+        "{} Wrote {} ({} files)",
+        palette().success("✓"),
+        out.display(),
+        manifest.entries.len()
+    );
+    if let Some(ref prd) = manifest.prd {
+        sayln!("  PRD sha256: {}", prd.sha256);
+    }
+    for missing in &manifest.missing {
+        sayln!("  {} {} not found, skipped", palette().warning("!"), missing);
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "archive"))]
+fn export_archive(_config: &RalphConfig, _out: &std::path::Path) -> Result<()> {
+    Err(adk_ralph::RalphError::config(
+        "ralph was built without archive support; rebuild with `--features archive`",
+    ))
+}
+
+/// Save the project's design and scaffold as template `name`.
+fn create_template(config: &RalphConfig, name: &str) -> Result<()> {
+    let project_path = std::path::Path::new(&config.project_path);
+    let design_path = project_path.join(&config.design_path);
+    let template = adk_ralph::ProjectTemplate::capture(name, project_path, &design_path)?;
+    let path = template.save(&adk_ralph::template::templates_dir()?)?;
+    sayln!("{} Saved template '{}' to {}", palette().success("✓"), name, path.display());
+    sayln!(
+        "  {} scaffold files, {} directories",
+        template.files.len(),
+        template.directories.len()
+    );
+    for file in &template.files {
+        sayln!("  {}", file.path);
+    }
+    Ok(())
+}
+
+/// Seed the project directory from template `name`.
+/// Compare this binary with the version the project is pinned to, record the
+/// run in `.ralph/state.json`, and (when enabled) mention a newer release.
+fn check_project_version(config: &RalphConfig, output: &RalphOutput, allow_change: bool) -> Result<()> {
+    let project_path = std::path::Path::new(&config.project_path);
+    if let Some(warning) =
+        project_state::check_and_record(project_path, config.version_strict, allow_change)?
+    {
+        output.warn(&warning);
+    }
+    if config.update_check {
+        let registry = adk_ralph::dependencies::HttpRegistry::new(project_path, config.offline);
+        let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+        if let Some(notice) = project_state::update_notice(project_path, &today, &registry) {
+            output.status(&notice);
+        }
+    }
+    output.flush();
+    Ok(())
+}
+
+fn seed_template(config: &RalphConfig, name: &str) -> Result<()> {
+    let project_path = std::path::Path::new(&config.project_path);
+    let (template, warnings) =
+        adk_ralph::ProjectTemplate::load(&adk_ralph::template::templates_dir()?, name)?;
+    for warning in &warnings {
+        sayln!("{} {}", palette().warning("!"), warning);
+    }
+
+    // Show what would change before replacing a file that already exists
+    let mut overwrite = Vec::new();
+    for (file, existing) in template.conflicts(project_path) {
+        sayln!("{} {} already exists:", palette().warning("?"), file.path);
+        say!(
+            "{}",
+            adk_ralph::diff::render_file_diff(
+                &file.path,
+                &file.path,
+                &existing,
+                file.content.as_bytes(),
+                adk_ralph::DiffFormat::for_console(),
+            )
+        );
+        if confirm(&format!("Overwrite {} with the template's version?", file.path))? {
+            overwrite.push(file.path.clone());
+        }
+    }
+
+    let written = template.seed_overwriting(project_path, &overwrite)?;
+    sayln!(
+        "{} Seeded {} from template '{}' ({} files)",
+        palette().success("✓"),
+        project_path.display(),
+        name,
+        written.len()
+    );
+    for file in &written {
+        sayln!("  {}", file);
+    }
+    Ok(())
+}
+
+/// Ask a yes/no question on stdin; anything but "y"/"yes" is no.
+fn confirm(question: &str) -> Result<bool> {
+    use std::io::{BufRead, Write};
+
+    say!("{} [y/N] ", question);
+    std::io::stdout().flush()?;
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Return changed tasks and their dependents to the queue; ask before
+/// re-running tasks whose files were edited by hand.
+fn prepare_incremental(config: &RalphConfig) -> Result<()> {
+    use adk_ralph::incremental::{self, IncrementalPlan};
+
+    let project_path = std::path::Path::new(&config.project_path);
+    let tasks_path = project_path.join(&config.tasks_path);
+    let mut list =
+        adk_ralph::TaskList::load(&tasks_path).map_err(adk_ralph::RalphError::Task)?;
+    let design =
+        std::fs::read_to_string(project_path.join(&config.design_path)).unwrap_or_default();
+    let plan = IncrementalPlan::compute(&list, project_path, &design);
+
+    let mut confirmed = Vec::new();
+    for (task_id, files) in &plan.edited {
+        let question = format!(
+            "{} {} was edited by hand ({}). Re-run it and its dependents?",
+            palette().warning("?"),
+            task_id,
+            files.join(", ")
+        );
+        if confirm(&question)? {
+            confirmed.push(task_id.clone());
+        }
+    }
+
+    let rerun = plan.invalidated(&list, &confirmed);
+    sayln!(
+        "{} Incremental: {} completed task(s) skipped, {} to re-run{}",
+        palette().success("✓"),
+        list.get_stats().completed - rerun.len(),
+        rerun.len(),
+        if rerun.is_empty() { String::new() } else { format!(" ({})", rerun.join(", ")) }
+    );
+    if !plan.unrecorded.is_empty() {
+        sayln!(
+            "  {} kept without a fingerprint (completed before incremental runs): {}",
+            palette().warning("!"),
+            plan.unrecorded.join(", ")
+        );
+    }
+    if !rerun.is_empty() {
+        incremental::reset(&mut list, &rerun);
+        list.save(&tasks_path).map_err(adk_ralph::RalphError::Task)?;
+    }
+    Ok(())
+}
+
 async fn run_pipeline(config: RalphConfig, prompt: &str) -> Result<()> {
     let mut orchestrator = RalphOrchestrator::new(config)?;
 
-    println!("{}", "Starting Ralph Pipeline...".green().bold());
-    println!();
+    sayln!("{}", palette().success("Starting Ralph Pipeline...").bold());
+    sayln!();
 
     // Run the full pipeline
     let status = orchestrator.run(prompt).await?;
 
     // Print final status
-    println!();
-    println!("{}", "Pipeline Complete!".green().bold());
-    println!("{}", status);
+    sayln!();
+    sayln!("{}", palette().success("Pipeline Complete!").bold());
+    sayln!("{}", status);
+
+    Ok(())
+}
+
+async fn run_demo(dir: Option<std::path::PathBuf>) -> Result<()> {
+    let dir = dir.unwrap_or_else(|| {
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        std::env::temp_dir().join(format!("ralph-demo-{}", &id[..8]))
+    });
+
+    sayln!("{}", palette().success("Starting Ralph demo (offline, replaying recorded model responses)...").bold());
+    sayln!("{} {}", palette().success("Prompt:").bold(), adk_ralph::demo::DEMO_PROMPT.trim());
+    sayln!();
+
+    let report = adk_ralph::demo::run(&dir).await?;
+
+    sayln!();
+    sayln!("{}", palette().success("Demo Complete!").bold());
+    sayln!("{}", report.status);
+    sayln!();
+    sayln!("Generated project: {}", palette().accent(report.project_dir.display().to_string()));
+    for file in report.files() {
+        sayln!("  {}", file);
+    }
+    sayln!();
+    sayln!("Try it: cd {} && cargo run", report.project_dir.display());
 
     Ok(())
 }
@@ -268,20 +909,31 @@ async fn resume_pipeline(config: RalphConfig, phase: PipelinePhase, prompt: &str
     // Skip to the specified phase
     orchestrator.skip_to_phase(phase)?;
 
-    println!(
+    sayln!(
         "{} {}",
-        "Resuming from phase:".green().bold(),
-        phase.to_string().cyan()
+        palette().success("Resuming from phase:").bold(),
+        palette().accent(phase.to_string())
     );
-    println!();
+    sayln!();
 
     // Resume the pipeline
     let status = orchestrator.resume(prompt).await?;
 
     // Print final status
-    println!();
-    println!("{}", "Pipeline Complete!".green().bold());
-    println!("{}", status);
+    sayln!();
+    sayln!("{}", palette().success("Pipeline Complete!").bold());
+    sayln!("{}", status);
+
+    Ok(())
+}
+
+async fn resume_checkpoint(config: RalphConfig) -> Result<()> {
+    let mut orchestrator = RalphOrchestrator::new(config)?;
+    let status = orchestrator.resume_from_checkpoint().await?;
+
+    sayln!();
+    sayln!("{}", palette().success("Pipeline Complete!").bold());
+    sayln!("{}", status);
 
     Ok(())
 }
@@ -326,147 +978,299 @@ async fn run_interactive_chat(config: RalphConfig, resume: bool, auto_approve: b
 #[tokio::main]
 async fn main() -> Result<()> {
     // Load .env file from current directory
-    match dotenvy::dotenv() {
+    let env_file = match dotenvy::dotenv() {
         Ok(path) => {
             // .env loaded successfully
             eprintln!("Loaded config from: {}", path.display());
+            Some(path)
         }
         Err(_) => {
             // No .env file found - that's okay, will use env vars or defaults
+            None
         }
-    }
+    };
 
     // Parse command line arguments
     let cli = Cli::parse();
+    if cli.capabilities {
+        let caps = serde_json::to_string_pretty(&adk_ralph::capabilities())?;
+        println!("{}", caps);
+        return Ok(());
+    }
 
-    // Load configuration
-    let mut config = match RalphConfig::from_env() {
-        Ok(c) => c,
-        Err(e) => {
-            let cwd = std::env::current_dir()
-                .map(|p| p.display().to_string())
-                .unwrap_or_else(|_| ".".to_string());
-            
-            eprintln!("{}: {}", "Configuration Error".red().bold(), e);
-            eprintln!();
-            eprintln!("Create a {} file at:", ".env".cyan());
-            eprintln!("  {}", format!("{}/.env", cwd).yellow());
-            eprintln!();
-            eprintln!("With contents:");
-            eprintln!("  # Required: API key for your provider");
-            eprintln!("  GEMINI_API_KEY=your-api-key");
-            eprintln!("  # or ANTHROPIC_API_KEY=your-api-key");
-            eprintln!("  # or OPENAI_API_KEY=your-api-key");
-            eprintln!();
-            eprintln!("  # Optional: customize project output location");
-            eprintln!("  RALPH_PROJECT_PATH=/path/to/project");
-            eprintln!();
-            eprintln!("See {} for all options.", "adk-ralph/.env.example".cyan());
-            std::process::exit(1);
-        }
-    };
+    // Load configuration; problems are reported together below
+    let mut env_errors = Vec::new();
+    let mut config = RalphConfig::read_env(&mut env_errors);
+    let mut sources = ConfigSources::from_environment();
+    if let Some(path) = env_file {
+        sources = sources.with_env_file(path);
+    }
 
     // Override from CLI if provided
     if let Some(debug_level) = cli.debug {
         config.debug_level = debug_level.into();
+        sources = sources.with_flag("debug_level", "--debug");
     }
     if let Some(ref path) = cli.project_path {
         config.project_path = path.clone();
+        sources = sources.with_flag("project_path", "--project-path");
+    }
+    if let Some(ref dir) = cli.export_dataset {
+        config.dataset_export = Some(dir.clone());
+        sources = sources.with_flag("dataset_export", "--export-dataset");
+    }
+    match cli.output {
+        Some(format @ (CliOutputFormat::Json | CliOutputFormat::JsonOnly)) => {
+            config.event_log = Some("-".to_string());
+            config.human_output = format == CliOutputFormat::Json;
+            sources = sources.with_flag("event_log", "--output").with_flag("human_output", "--output");
+        }
+        Some(CliOutputFormat::Human) => {
+            if config.event_log.as_deref() == Some("-") {
+                config.event_log = None;
+                sources = sources.with_flag("event_log", "--output");
+            }
+            config.human_output = true;
+            sources = sources.with_flag("human_output", "--output");
+        }
+        None => {}
+    }
+    let sink = OutputSink::from_event_log(config.event_log.as_deref());
+    HUMAN_ON_STDERR.store(sink == OutputSink::JsonlStdout, Ordering::Relaxed);
+    HUMAN_OFF.store(!config.human_output, Ordering::Relaxed);
+
+    // Check the whole configuration before any model is called
+    let needs_models = matches!(
+        cli.command,
+        None | Some(Commands::Run { .. })
+            | Some(Commands::Resume { .. })
+            | Some(Commands::Chat { .. })
+            | Some(Commands::Replan { .. })
+            | Some(Commands::Generate { .. })
+            | Some(Commands::Config)
+    );
+    // Key commands may block for a while; keep them off the runtime's workers
+    let report = tokio::task::block_in_place(|| preflight::run_checks(&config, &env_errors, &sources, needs_models));
+    if let Err(e) = report.clone().into_result() {
+        RalphOutput::for_config(&config).error(&e.user_message());
+        eprintln!();
+        eprintln!("See {} for all options.", palette().accent("adk-ralph/.env.example"));
+        flush_output();
+        std::process::exit(e.exit_code());
     }
 
     // Initialize telemetry
     if let Err(e) = init_telemetry(&config.telemetry, config.debug_level) {
-        eprintln!("{}: {}", "Telemetry Warning".yellow(), e);
+        eprintln!("{}: {}", palette().warning("Telemetry Warning"), e);
         eprintln!("Continuing without full telemetry support...");
     }
 
     // Create output handler for banner (respects debug level)
-    let output = RalphOutput::new(config.debug_level);
+    let output = RalphOutput::for_config(&config);
 
     // Print banner (only at normal and above)
     output.banner();
+    output.flush();
 
     // Handle commands
-    match cli.command {
-        Some(Commands::Run { prompt }) => {
-            let prompt_str = prompt.join(" ");
-            if prompt_str.is_empty() {
-                eprintln!("{}", "Error: Project description is required".red());
-                std::process::exit(1);
+    let result: Result<()> = async {
+        let runs_pipeline = matches!(
+            cli.command,
+            None | Some(Commands::Run { .. })
+                | Some(Commands::Resume { .. })
+                | Some(Commands::Chat { .. })
+                | Some(Commands::Replan { .. })
+        );
+        if runs_pipeline {
+            check_project_version(&config, &output, cli.allow_version_change)?;
+        }
+
+        match cli.command {
+            Some(Commands::Run { prompt, template }) => {
+                let prompt_str = prompt.join(" ");
+                if prompt_str.is_empty() {
+                    eprintln!("{}", palette().failure("Error: Project description is required"));
+                    flush_output();
+                    std::process::exit(1);
+                }
+
+                print_config(&config);
+                info!("Starting Ralph with prompt: {}", prompt_str);
+                sayln!("{} {}", palette().success("Project:").bold(), prompt_str);
+                sayln!();
+
+                if let Some(ref name) = template {
+                    seed_template(&config, name)?;
+                    sayln!();
+                }
+
+                run_pipeline(config, &prompt_str).await?;
             }
 
-            print_config(&config);
-            info!("Starting Ralph with prompt: {}", prompt_str);
-            println!("{} {}", "Project:".green().bold(), prompt_str);
-            println!();
+            Some(Commands::Resume { phase, prompt, incremental }) => {
+                let phase = parse_phase(&phase)?;
+                let prompt_str = prompt.join(" ");
 
-            run_pipeline(config, &prompt_str).await?;
-        }
+                // Prompt is required for requirements phase
+                if phase == PipelinePhase::Requirements && prompt_str.is_empty() {
+                    eprintln!(
+                        "{}",
+                        palette().failure("Error: Project description is required for requirements phase")
+                    );
+                    flush_output();
+                    std::process::exit(1);
+                }
+
+                print_config(&config);
+                if incremental {
+                    prepare_incremental(&config)?;
+                }
+                resume_pipeline(config, phase, &prompt_str).await?;
+            }
 
-        Some(Commands::Resume { phase, prompt }) => {
-            let phase = parse_phase(&phase)?;
-            let prompt_str = prompt.join(" ");
+            Some(Commands::Init { template }) => {
+                seed_template(&config, &template)?;
+            }
 
-            // Prompt is required for requirements phase
-            if phase == PipelinePhase::Requirements && prompt_str.is_empty() {
-                eprintln!(
-                    "{}",
-                    "Error: Project description is required for requirements phase".red()
+            Some(Commands::Template { action: TemplateCommand::Create { name } }) => {
+                create_template(&config, &name)?;
+            }
+
+            Some(Commands::Status) => {
+                let orchestrator = RalphOrchestrator::new(config)?;
+                print_status(&orchestrator);
+            }
+
+            Some(Commands::Validate) => {
+                validate_plan(&config)?;
+            }
+
+            Some(Commands::Preview) => {
+                let tasks_path = std::path::Path::new(&config.project_path).join(&config.tasks_path);
+                let tasks = TaskList::load(&tasks_path).map_err(adk_ralph::RalphError::Task)?;
+                output.run_preview(&adk_ralph::preview_run(&tasks, config.scheduling));
+                output.flush();
+            }
+
+            Some(Commands::Scope { without, action }) => {
+                show_scope(&config, &without, action)?;
+            }
+
+            Some(Commands::Config) => {
+                print_config(&config);
+                sayln!("{}", palette().success(report.to_string()));
+            }
+
+            Some(Commands::Chat { resume, auto_approve }) => {
+                run_interactive_chat(config, resume, auto_approve).await?;
+            }
+
+            Some(Commands::Prompt { action: PromptCommand::Show { agent, task, input, diff } }) => {
+                show_prompt(&config, &agent, task.as_deref(), input.as_deref(), diff)?;
+            }
+
+            Some(Commands::Tasks { action: TasksCommand::Abort { task_id, block } }) => {
+                let project_path = std::path::Path::new(&config.project_path);
+                AbortRequest::new(&task_id, block)
+                    .submit(project_path)
+                    .map_err(adk_ralph::RalphError::Task)?;
+                sayln!(
+                    "{} Abort requested for {}; the running loop applies it at the next safe point",
+                    palette().success("✓"),
+                    task_id
                 );
-                std::process::exit(1);
             }
 
-            print_config(&config);
-            resume_pipeline(config, phase, &prompt_str).await?;
-        }
+            Some(Commands::Provenance { file }) => {
+                show_provenance(&file)?;
+            }
 
-        Some(Commands::Status) => {
-            let orchestrator = RalphOrchestrator::new(config)?;
-            print_status(&orchestrator);
-        }
+            Some(Commands::Generate { prd, stdout, json }) => {
+                generate_plan(&config, prd.as_deref(), stdout, json).await?;
+            }
 
-        Some(Commands::Config) => {
-            print_config(&config);
-            println!("{}", "Configuration is valid!".green());
-        }
+            Some(Commands::Replan { tasks }) => {
+                let mut orchestrator = RalphOrchestrator::new(config)?;
+                orchestrator.run_replan(&tasks).await?;
+            }
 
-        Some(Commands::Chat { resume, auto_approve }) => {
-            run_interactive_chat(config, resume, auto_approve).await?;
-        }
+            Some(Commands::Schema { action: SchemaCommand::Export { dir } }) => {
+                for path in adk_ralph::schema::export(&dir)? {
+                    sayln!("{} Wrote {}", palette().success("✓"), path.display());
+                }
+            }
 
-        None => {
-            // No subcommand - use prompt directly
-            let prompt_str = cli.prompt.join(" ");
-            if prompt_str.is_empty() {
-                eprintln!("{}", "Usage: ralph <project description>".yellow());
-                eprintln!();
-                eprintln!("Examples:");
-                eprintln!("  ralph \"Create a CLI calculator in Rust\"");
-                eprintln!("  ralph \"Build a REST API for a todo app in Python\"");
-                eprintln!();
-                eprintln!("Commands:");
-                eprintln!("  ralph run <prompt>     Run the full pipeline");
-                eprintln!("  ralph resume [--phase] Resume from a specific phase");
-                eprintln!("  ralph chat             Start interactive chat mode");
-                eprintln!("  ralph status           Show current status");
-                eprintln!("  ralph config           Validate configuration");
-                eprintln!();
-                eprintln!("Chat Options:");
-                eprintln!("  ralph chat --resume       Resume previous session");
-                eprintln!("  ralph chat --auto-approve Skip change confirmations");
-                std::process::exit(1);
+            Some(Commands::Demo { dir }) => {
+                run_demo(dir).await?;
             }
 
-            print_config(&config);
-            info!("Starting Ralph with prompt: {}", prompt_str);
-            println!("{} {}", "Project:".green().bold(), prompt_str);
-            println!();
+            Some(Commands::Export { out }) => {
+                export_archive(&config, &out)?;
+            }
+
+            None if cli.resume => {
+                print_config(&config);
+                resume_checkpoint(config).await?;
+            }
+
+            None => {
+                // No subcommand - use prompt directly
+                let prompt_str = cli.prompt.join(" ");
+                if prompt_str.is_empty() {
+                    eprintln!("{}", palette().warning("Usage: ralph <project description>"));
+                    eprintln!();
+                    eprintln!("Examples:");
+                    eprintln!("  ralph \"Create a CLI calculator in Rust\"");
+                    eprintln!("  ralph \"Build a REST API for a todo app in Python\"");
+                    eprintln!();
+                    eprintln!("Commands:");
+                    eprintln!("  ralph run <prompt>     Run the full pipeline");
+                    eprintln!("  ralph run --template <name> <prompt>  Start from a saved template");
+                    eprintln!("  ralph --resume         Continue an interrupted run from tasks.json");
+                    eprintln!("  ralph resume [--phase] Resume from a specific phase");
+                    eprintln!("  ralph init --template <name>  Seed the project from a template");
+                    eprintln!("  ralph template create <name>  Save this project as a template");
+                    eprintln!("  ralph chat             Start interactive chat mode");
+                    eprintln!("  ralph status           Show current status");
+                    eprintln!("  ralph config           Validate configuration");
+                    eprintln!("  ralph validate         Check tasks.json against the design and PRD");
+                    eprintln!("  ralph scope [--without US-004]  Rank stories by remaining cost");
+                    eprintln!("  ralph scope cut <US-ID...>      Drop stories; their tasks become obsolete");
+                    eprintln!("  ralph prompt show <agent> [--task T] [--diff]  Inspect an agent's prompt");
+                    eprintln!("  ralph tasks abort <ID> [--block]  Abort the task in progress");
+                    eprintln!("  ralph replan [IDs...]      Revise the design from failed tasks");
+                    eprintln!("  ralph export [out.tar.gz]  Archive the run's artifacts");
+                    eprintln!("  ralph provenance <file>    Show which models produced an artifact");
+                    eprintln!("  ralph schema export [--dir schemas]  Write the tasks/design JSON Schemas");
+                    eprintln!("  ralph demo [--dir DIR]     Try the pipeline offline, no API key needed");
+                    eprintln!();
+                    eprintln!("Chat Options:");
+                    eprintln!("  ralph chat --resume       Resume previous session");
+                    eprintln!("  ralph chat --auto-approve Skip change confirmations");
+                    flush_output();
+                    std::process::exit(1);
+                }
 
-            run_pipeline(config, &prompt_str).await?;
+                print_config(&config);
+                info!("Starting Ralph with prompt: {}", prompt_str);
+                sayln!("{} {}", palette().success("Project:").bold(), prompt_str);
+                sayln!();
+
+                run_pipeline(config, &prompt_str).await?;
+            }
         }
+        Ok(())
+    }
+    .await;
+    if let Err(e) = result {
+        output.error(&e.user_message());
+        flush_output();
+        adk_rust::telemetry::shutdown_telemetry();
+        std::process::exit(e.exit_code());
     }
 
-    // Shutdown telemetry to flush any pending spans
+    // Drain queued output, then shut down telemetry to flush any pending spans
+    flush_output();
     adk_rust::telemetry::shutdown_telemetry();
 
     Ok(())
@@ -0,0 +1,184 @@
+//! Time limit for a single model call.
+//!
+//! A provider that stalls mid-response would otherwise hold the whole process:
+//! checks between operations never run while one call is pending.
+//! [`TimeoutModel`] wraps a model so that one call, from sending the request
+//! to the last streamed chunk, must finish within
+//! [`ModelConfig::model_call_timeout`](crate::models::ModelConfig::model_call_timeout).
+//!
+//! When the limit passes, the inner response stream is dropped, which cancels
+//! the HTTP request, and the call ends with a single error. Partial chunks
+//! already streamed are never followed by a final response. The runner only
+//! records final responses in the session, so the cancelled turn leaves
+//! nothing behind. Callers turn the error into [`RalphError::Timeout`] with
+//! [`RalphError::agent_stream`].
+//!
+//! [`RalphError::Timeout`]: crate::RalphError::Timeout
+//! [`RalphError::agent_stream`]: crate::RalphError::agent_stream
+
+use adk_rust::{AdkError, Llm, LlmRequest, LlmResponseStream, Result as AdkResult};
+use async_trait::async_trait;
+use futures::StreamExt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Start of the error message a cancelled call ends with.
+const TIMEOUT_MARKER: &str = "ralph: model call timed out after ";
+
+/// Error message for a call cancelled after `elapsed`.
+pub fn timeout_message(elapsed: Duration) -> String {
+    format!("{}{} ms", TIMEOUT_MARKER, elapsed.as_millis())
+}
+
+/// How long a call ran before it was cancelled, if `message` (possibly
+/// wrapped by the runner) is a timeout error.
+pub fn elapsed_from_message(message: &str) -> Option<Duration> {
+    let rest = &message[message.find(TIMEOUT_MARKER)? + TIMEOUT_MARKER.len()..];
+    let millis: String = rest.chars().take_while(char::is_ascii_digit).collect();
+    millis.parse().ok().map(Duration::from_millis)
+}
+
+/// A model whose calls are cancelled once they take longer than a limit.
+pub struct TimeoutModel {
+    inner: Arc<dyn Llm>,
+    limit: Duration,
+}
+
+impl TimeoutModel {
+    /// Limit every call to `inner` to `limit`.
+    pub fn new(inner: Arc<dyn Llm>, limit: Duration) -> Self {
+        Self { inner, limit }
+    }
+
+    /// `model` with its calls limited to `limit`, or unchanged for `None`.
+    pub fn wrap(model: Arc<dyn Llm>, limit: Option<Duration>) -> Arc<dyn Llm> {
+        match limit {
+            Some(limit) => Arc::new(Self::new(model, limit)),
+            None => model,
+        }
+    }
+}
+
+impl std::fmt::Debug for TimeoutModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TimeoutModel")
+            .field("model", &self.inner.name())
+            .field("limit", &self.limit)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl Llm for TimeoutModel {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    async fn generate_content(
+        &self,
+        request: LlmRequest,
+        stream: bool,
+    ) -> AdkResult<LlmResponseStream> {
+        let started = Instant::now();
+        let deadline = started + self.limit;
+        let model = self.inner.name().to_string();
+        let inner = match tokio::time::timeout_at(deadline, self.inner.generate_content(request, stream)).await {
+            Ok(result) => result?,
+            Err(_) => return Err(timed_out(&model, started)),
+        };
+
+        Ok(Box::pin(futures::stream::unfold(Some(inner), move |state| {
+            let model = model.clone();
+            async move {
+                let mut inner = state?;
+                match tokio::time::timeout_at(deadline, inner.next()).await {
+                    Ok(Some(item)) => Some((item, Some(inner))),
+                    Ok(None) => None,
+                    Err(_) => {
+                        // Dropping the stream cancels the request before anything else is read
+                        drop(inner);
+                        Some((Err(timed_out(&model, started)), None))
+                    }
+                }
+            }
+        })))
+    }
+}
+
+fn timed_out(model: &str, started: Instant) -> AdkError {
+    let elapsed = started.elapsed();
+    tracing::warn!(model, elapsed_ms = elapsed.as_millis() as u64, "Model call timed out; cancelled");
+    AdkError::Model(timeout_message(elapsed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adk_rust::{Content, LlmResponse};
+
+    /// Streams one chunk, then stalls (or finishes, when `stall` is false).
+    struct StallingModel {
+        stall: bool,
+    }
+
+    #[async_trait]
+    impl Llm for StallingModel {
+        fn name(&self) -> &str {
+            "stalling"
+        }
+
+        async fn generate_content(
+            &self,
+            _request: LlmRequest,
+            _stream: bool,
+        ) -> AdkResult<LlmResponseStream> {
+            let first = LlmResponse::new(Content::new("model").with_text("partial"));
+            let stall = self.stall;
+            Ok(Box::pin(
+                futures::stream::once(async move { Ok(first) }).chain(futures::stream::once(async move {
+                    if stall {
+                        futures::future::pending::<()>().await;
+                    }
+                    Ok(LlmResponse::new(Content::new("model").with_text("done")))
+                })),
+            ))
+        }
+    }
+
+    async fn collect(model: &dyn Llm) -> Vec<AdkResult<LlmResponse>> {
+        let stream = model.generate_content(LlmRequest::new("stalling", Vec::new()), true).await.unwrap();
+        stream.collect().await
+    }
+
+    #[test]
+    fn test_timeout_message_round_trip() {
+        let message = format!("Agent error: {}", timeout_message(Duration::from_secs(90)));
+        assert_eq!(elapsed_from_message(&message), Some(Duration::from_secs(90)));
+        assert_eq!(elapsed_from_message("Model error: overloaded"), None);
+
+        let err = crate::RalphError::agent_stream("prd", message);
+        assert!(matches!(err, crate::RalphError::Timeout { ref agent, .. } if agent == "prd"));
+        assert!(err.is_retryable());
+        assert_eq!(err.exit_code(), 75);
+        assert!(err.to_string().contains("cancelled after 1m 30s"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_stalled_stream_is_cancelled_after_the_limit() {
+        let model = TimeoutModel::new(Arc::new(StallingModel { stall: true }), Duration::from_millis(50));
+        let items = collect(&model).await;
+        assert_eq!(items.len(), 2);
+        assert!(items[0].is_ok());
+        let err = items[1].as_ref().unwrap_err().to_string();
+        assert!(elapsed_from_message(&err).is_some_and(|e| e >= Duration::from_millis(50)), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_calls_within_the_limit_are_untouched() {
+        let model = TimeoutModel::wrap(Arc::new(StallingModel { stall: false }), Some(Duration::from_secs(30)));
+        let items = collect(model.as_ref()).await;
+        assert_eq!(items.len(), 2);
+        assert!(items.iter().all(Result::is_ok));
+    }
+}
@@ -12,13 +12,23 @@
 //! Use `RalphConfig::from_env()` to load and validate configuration from environment
 //! variables, or use the builder pattern with `.build()` for programmatic configuration.
 
+use super::design::StructureLimits;
+use super::provider::Provider;
+use crate::edit_guard::EditGuard;
+use crate::exec_env::{self, ArtifactIsolation, EXEC_ENV_PREFIX};
+use crate::language::{ExtensionMap, Language};
+use crate::resources::ResourceLimits;
+use crate::tools::CommitAuthor;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::env;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
 use std::str::FromStr;
 
-/// Supported model providers.
-pub const SUPPORTED_PROVIDERS: &[&str] = &["openai", "anthropic", "gemini", "ollama"];
+/// Supported model providers (`replay` plays back a recording; see [`crate::replay`]).
+pub const SUPPORTED_PROVIDERS: &[&str] = &["openai", "anthropic", "gemini", "ollama", "replay"];
 
 /// Maximum allowed value for max_iterations to prevent runaway loops.
 pub const MAX_ITERATIONS_LIMIT: usize = 10000;
@@ -29,6 +39,9 @@ pub const MAX_TOKENS_LIMIT: usize = 1_000_000;
 /// Maximum allowed value for max_task_retries.
 pub const MAX_RETRIES_LIMIT: usize = 100;
 
+/// Most builds the CPUs are split between, whatever the CPU count.
+pub const MAX_CONCURRENT_BUILDS_LIMIT: usize = 16;
+
 /// Debug/output verbosity level for Ralph execution.
 ///
 /// Controls how much information is displayed during execution:
@@ -101,6 +114,283 @@ impl FromStr for DebugLevel {
     }
 }
 
+/// How often the full test suite is re-run during the implementation loop.
+///
+/// Per-task test runs can miss regressions in earlier work; a full-suite run
+/// at this cadence catches them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationCadence {
+    /// Never run the full suite outside of per-task tests
+    Off,
+    /// Run when a phase (or, for flat task lists, a priority tier) completes (default)
+    #[default]
+    PhaseBoundaries,
+    /// Run after every N completed tasks
+    EveryNTasks(usize),
+}
+
+impl std::fmt::Display for VerificationCadence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VerificationCadence::Off => write!(f, "off"),
+            VerificationCadence::PhaseBoundaries => write!(f, "phase"),
+            VerificationCadence::EveryNTasks(n) => write!(f, "every:{}", n),
+        }
+    }
+}
+
+impl FromStr for VerificationCadence {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_lowercase();
+        let count = lower.strip_prefix("every:").unwrap_or(&lower);
+        match lower.as_str() {
+            "off" | "none" | "disabled" => Ok(VerificationCadence::Off),
+            "phase" | "phases" | "phase_boundaries" => Ok(VerificationCadence::PhaseBoundaries),
+            _ => match count.parse::<usize>() {
+                Ok(n) if n > 0 => Ok(VerificationCadence::EveryNTasks(n)),
+                _ => Err(ValidationError::new(
+                    "verification_cadence",
+                    format!("Invalid verification cadence '{}'", s),
+                )
+                .with_suggestion("Valid values: off, phase, every:N (e.g. every:5)")),
+            },
+        }
+    }
+}
+
+/// How many builds the CPUs are split between when deriving the job count
+/// for build and test tools (see [`RalphConfig::tool_jobs_for`]).
+///
+/// The loop runs one task at a time, so this does not start tasks in
+/// parallel; it leaves room for other builds on the machine, such as other
+/// Ralph runs. `Auto` follows the machine: a fraction of the available CPUs,
+/// at least one. Either way the result is capped at the CPU count and at
+/// [`MAX_CONCURRENT_BUILDS_LIMIT`], so a large number cannot oversubscribe.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BuildConcurrency {
+    /// A fixed number of builds
+    Fixed(usize),
+    /// This fraction (0 < f <= 1) of the available CPUs
+    Auto(f32),
+}
+
+impl Default for BuildConcurrency {
+    fn default() -> Self {
+        BuildConcurrency::Fixed(1)
+    }
+}
+
+impl BuildConcurrency {
+    /// Number of builds to split `cpus` CPUs between.
+    pub fn resolve(&self, cpus: usize) -> usize {
+        let cpus = cpus.max(1);
+        let wanted = match *self {
+            BuildConcurrency::Fixed(n) => n,
+            BuildConcurrency::Auto(fraction) => (cpus as f32 * fraction).floor() as usize,
+        };
+        wanted.clamp(1, cpus.min(MAX_CONCURRENT_BUILDS_LIMIT))
+    }
+}
+
+impl std::fmt::Display for BuildConcurrency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuildConcurrency::Fixed(n) => write!(f, "{}", n),
+            BuildConcurrency::Auto(fraction) if *fraction >= 1.0 => write!(f, "auto"),
+            BuildConcurrency::Auto(fraction) => write!(f, "auto:{}", fraction),
+        }
+    }
+}
+
+impl FromStr for BuildConcurrency {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_lowercase();
+        let invalid = || {
+            ValidationError::new(
+                "concurrent_builds",
+                format!("Invalid concurrent builds '{}'", s),
+            )
+            .with_suggestion("Use a number like 2, auto (one per CPU) or auto:0.5 (half the CPUs)")
+        };
+        if lower == "auto" {
+            return Ok(BuildConcurrency::Auto(1.0));
+        }
+        if let Some(fraction) = lower.strip_prefix("auto:") {
+            return match fraction.parse::<f32>() {
+                Ok(f) if f > 0.0 && f <= 1.0 => Ok(BuildConcurrency::Auto(f)),
+                _ => Err(invalid()),
+            };
+        }
+        match lower.parse::<usize>() {
+            Ok(n) if n > 0 => Ok(BuildConcurrency::Fixed(n)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// How ready tasks are ordered when more than one could run next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchedulingStrategy {
+    /// Lowest priority number first (default)
+    #[default]
+    PriorityFirst,
+    /// Tasks heading the longest remaining dependency chain first, so risky
+    /// work and its failures surface early
+    CriticalPathFirst,
+    /// Lowest estimated complexity first
+    ShortestFirst,
+}
+
+impl std::fmt::Display for SchedulingStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchedulingStrategy::PriorityFirst => write!(f, "priority"),
+            SchedulingStrategy::CriticalPathFirst => write!(f, "critical_path"),
+            SchedulingStrategy::ShortestFirst => write!(f, "shortest"),
+        }
+    }
+}
+
+impl FromStr for SchedulingStrategy {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace('-', "_").as_str() {
+            "priority" | "priority_first" | "" => Ok(SchedulingStrategy::PriorityFirst),
+            "critical_path" | "critical_path_first" | "critical" => Ok(SchedulingStrategy::CriticalPathFirst),
+            "shortest" | "shortest_first" => Ok(SchedulingStrategy::ShortestFirst),
+            _ => Err(ValidationError::new("scheduling", format!("Invalid scheduling strategy '{}'", s))
+                .with_suggestion("Valid values: priority, critical_path, shortest")),
+        }
+    }
+}
+
+/// How a finished task's branch is merged back into the base branch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeStrategy {
+    /// Fast-forward when possible, otherwise a merge commit (default)
+    #[default]
+    FastForward,
+    /// One squashed commit per task on the base branch
+    Squash,
+}
+
+impl std::fmt::Display for MergeStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MergeStrategy::FastForward => write!(f, "ff"),
+            MergeStrategy::Squash => write!(f, "squash"),
+        }
+    }
+}
+
+impl FromStr for MergeStrategy {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace('-', "_").as_str() {
+            "ff" | "fast_forward" | "merge" | "" => Ok(MergeStrategy::FastForward),
+            "squash" => Ok(MergeStrategy::Squash),
+            _ => Err(ValidationError::new("task_merge", format!("Invalid merge strategy '{}'", s))
+                .with_suggestion("Valid values: ff, squash")),
+        }
+    }
+}
+
+/// Built-in color palette for terminal output (see [`crate::console::Palette`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PalettePreset {
+    /// Green, red and yellow for dark backgrounds (default)
+    #[default]
+    Default,
+    /// Darker colors and faint instead of gray dimming, for light backgrounds
+    Light,
+    /// Blue and orange instead of green and red
+    Colorblind,
+    /// No color, only bold
+    Mono,
+}
+
+impl std::fmt::Display for PalettePreset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PalettePreset::Default => write!(f, "default"),
+            PalettePreset::Light => write!(f, "light"),
+            PalettePreset::Colorblind => write!(f, "colorblind"),
+            PalettePreset::Mono => write!(f, "mono"),
+        }
+    }
+}
+
+impl FromStr for PalettePreset {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "default" | "dark" | "" => Ok(PalettePreset::Default),
+            "light" => Ok(PalettePreset::Light),
+            "colorblind" | "color-blind" | "cvd" => Ok(PalettePreset::Colorblind),
+            "mono" | "monochrome" => Ok(PalettePreset::Mono),
+            _ => Err(ValidationError::new("palette", format!("Invalid palette '{}'", s))
+                .with_suggestion("Valid values: default, light, colorblind, mono")),
+        }
+    }
+}
+
+/// CPUs available to this process (1 if unknown).
+pub fn available_cpus() -> usize {
+    std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+/// Override for a console capability that is otherwise detected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsoleSetting {
+    /// Decide from the terminal and environment (default)
+    #[default]
+    Auto,
+    /// Always on, even when redirected or on a legacy console
+    Always,
+    /// Always off
+    Never,
+}
+
+impl std::fmt::Display for ConsoleSetting {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConsoleSetting::Auto => write!(f, "auto"),
+            ConsoleSetting::Always => write!(f, "always"),
+            ConsoleSetting::Never => write!(f, "never"),
+        }
+    }
+}
+
+impl FromStr for ConsoleSetting {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "auto" | "" => Ok(ConsoleSetting::Auto),
+            "always" | "on" | "true" | "yes" => Ok(ConsoleSetting::Always),
+            "never" | "off" | "false" | "no" => Ok(ConsoleSetting::Never),
+            _ => Err(ValidationError::new(
+                "console_setting",
+                format!("Invalid console setting '{}'", s),
+            )
+            .with_suggestion("Valid values: auto, always, never")),
+        }
+    }
+}
+
 /// Validation error with context and suggestions.
 #[derive(Debug, Clone)]
 pub struct ValidationError {
@@ -141,28 +431,201 @@ impl ValidationError {
 
 impl std::error::Error for ValidationError {}
 
+/// `result`'s value, or `None` with its error recorded in `errors`.
+fn noted<T>(errors: &mut Vec<ValidationError>, result: Result<T, ValidationError>) -> Option<T> {
+    result.map_err(|e| errors.push(e)).ok()
+}
+
 /// Configuration for a single LLM model.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Serialize, Deserialize, PartialEq)]
 pub struct ModelConfig {
-    /// Model provider ("anthropic", "openai", "gemini", "ollama")
-    pub provider: String,
+    /// Model provider
+    pub provider: Provider,
     /// Specific model name (e.g., "claude-opus-4-5", "gpt-4o")
     pub model_name: String,
     /// Whether thinking/reasoning mode is enabled
     #[serde(default)]
     pub thinking_enabled: bool,
-    /// Maximum tokens for response
+    /// Maximum tokens for a response, sent with every request
     #[serde(default = "default_max_tokens")]
     pub max_tokens: usize,
-    /// Temperature for generation (0.0 - 1.0)
+    /// Sampling temperature (0.0 - 2.0), sent with every request
     #[serde(default = "default_temperature")]
     pub temperature: f32,
+    /// Environment variable to read the API key from, replacing the provider defaults
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_env: Option<String>,
+    /// API key passed directly; wins over any other source
+    #[serde(default, skip_serializing)]
+    pub api_key: Option<String>,
+    /// Command that prints the API key, e.g. `op read op://dev/gemini/key`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_command: Option<String>,
+    /// File holding the API key, e.g. a mounted secret
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_file: Option<PathBuf>,
+    /// Longest one model call may take, streaming included, in seconds (0 = no limit)
+    #[serde(default = "default_model_call_timeout_secs")]
+    pub model_call_timeout_secs: u64,
+    /// Server to send requests to instead of the provider's default (Ollama only for now)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+}
+
+impl std::fmt::Debug for ModelConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ModelConfig")
+            .field("provider", &self.provider)
+            .field("model_name", &self.model_name)
+            .field("thinking_enabled", &self.thinking_enabled)
+            .field("max_tokens", &self.max_tokens)
+            .field("temperature", &self.temperature)
+            .field("api_key_env", &self.api_key_env)
+            .field("api_key", &self.api_key.as_ref().map(|_| "<redacted>"))
+            .field("api_key_command", &self.api_key_command)
+            .field("api_key_file", &self.api_key_file)
+            .field("model_call_timeout_secs", &self.model_call_timeout_secs)
+            .field("base_url", &self.base_url)
+            .finish()
+    }
+}
+
+/// Environment variables checked for a provider's API key, in precedence order.
+///
+/// Gemini reads `GEMINI_API_KEY` first and falls back to `GOOGLE_API_KEY`.
+/// Ollama runs locally and needs no key.
+pub fn default_api_key_envs(provider: &Provider) -> &'static [&'static str] {
+    match provider {
+        Provider::Anthropic => &["ANTHROPIC_API_KEY"],
+        Provider::OpenAI => &["OPENAI_API_KEY"],
+        Provider::Gemini => &["GEMINI_API_KEY", "GOOGLE_API_KEY"],
+        Provider::Ollama | Provider::Replay | Provider::Unknown(_) => &[],
+    }
+}
+
+/// Longest an `api_key_command` may run.
+pub const API_KEY_COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Where a key that is not in the environment came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SecretSource {
+    Command(String),
+    File(PathBuf),
+}
+
+/// Keys read from a command or file by this process, by source. Environment
+/// keys are found by name when scrubbing; these have no name to find them
+/// by. Each source is read once per process, so a command that prompts
+/// (e.g. for a fingerprint) prompts once however many agents use it.
+static RESOLVED_SECRETS: Mutex<Vec<(SecretSource, String)>> = Mutex::new(Vec::new());
+
+/// API keys this process read from an `api_key_command` or `api_key_file`,
+/// for scrubbing from anything written out.
+pub fn resolved_secrets() -> Vec<String> {
+    let Ok(secrets) = RESOLVED_SECRETS.lock() else {
+        return Vec::new();
+    };
+    let mut keys: Vec<String> = Vec::new();
+    for (_, key) in secrets.iter() {
+        if !keys.contains(key) {
+            keys.push(key.clone());
+        }
+    }
+    keys
+}
+
+/// The key read from `source`, reading it with `read` the first time.
+///
+/// The lock is held while reading, so agents resolving the same source at
+/// once still run the command only once. Failures are not remembered.
+fn cached_secret(source: SecretSource, read: impl FnOnce() -> crate::Result<String>) -> crate::Result<String> {
+    let mut secrets = RESOLVED_SECRETS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((_, key)) = secrets.iter().find(|(known, _)| *known == source) {
+        return Ok(key.clone());
+    }
+    let key = read()?;
+    secrets.push((source, key.clone()));
+    Ok(key)
+}
+
+/// Run `command` through the shell and return its trimmed stdout.
+///
+/// Killed after `timeout`. The error never contains stdout, which may hold
+/// the key; the first line of stderr is included.
+fn run_secret_command(command: &str, timeout: Duration) -> crate::Result<String> {
+    use std::io::Read;
+    use std::process::{Command, Stdio};
+
+    let failed = |detail: String| {
+        crate::RalphError::Configuration(format!("API key command `{}` {}", command, detail))
+    };
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", command]);
+        shell
+    };
+    let mut child = shell
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| failed(format!("could not be started: {}", e)))?;
+
+    // Drain the pipes while waiting so a chatty command cannot block on them
+    let drain = |pipe: Option<Box<dyn Read + Send>>| {
+        std::thread::spawn(move || {
+            let mut text = String::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_string(&mut text);
+            }
+            text
+        })
+    };
+    let stdout = drain(child.stdout.take().map(|p| Box::new(p) as Box<dyn Read + Send>));
+    let stderr = drain(child.stderr.take().map(|p| Box::new(p) as Box<dyn Read + Send>));
+
+    let started = std::time::Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if started.elapsed() >= timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(failed(format!("timed out after {}", crate::timefmt::duration(timeout))));
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(20)),
+            Err(e) => return Err(failed(format!("could not be waited for: {}", e))),
+        }
+    };
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+
+    if !status.success() {
+        let reason = stderr.lines().find(|l| !l.trim().is_empty()).unwrap_or("no error output");
+        return Err(failed(format!("failed ({}): {}", status, reason.trim())));
+    }
+    let key = stdout.trim();
+    if key.is_empty() {
+        return Err(failed("printed nothing".to_string()));
+    }
+    Ok(key.to_string())
 }
 
+/// Where a local Ollama server listens unless `base_url` or `OLLAMA_HOST` says otherwise.
+pub const DEFAULT_OLLAMA_HOST: &str = "http://localhost:11434";
+
 fn default_max_tokens() -> usize {
     4096
 }
 
+fn default_model_call_timeout_secs() -> u64 {
+    600
+}
+
 fn default_temperature() -> f32 {
     0.7
 }
@@ -170,43 +633,203 @@ fn default_temperature() -> f32 {
 impl Default for ModelConfig {
     fn default() -> Self {
         Self {
-            provider: "gemini".to_string(),
+            provider: Provider::Gemini,
             model_name: "gemini-2.5-flash".to_string(),
             thinking_enabled: false,
             max_tokens: default_max_tokens(),
             temperature: default_temperature(),
+            api_key_env: None,
+            api_key: None,
+            api_key_command: None,
+            api_key_file: None,
+            model_call_timeout_secs: default_model_call_timeout_secs(),
+            base_url: None,
         }
     }
 }
 
 impl ModelConfig {
     /// Create a new model config.
+    ///
+    /// Any provider name is accepted; an unknown one is kept as
+    /// [`Provider::Unknown`] and rejected by [`ModelConfig::validate`].
     pub fn new(provider: impl Into<String>, model_name: impl Into<String>) -> Self {
+        Self::for_provider(Provider::from_name(&provider.into()), model_name)
+    }
+
+    /// Create a new model config for a parsed provider.
+    pub fn for_provider(provider: Provider, model_name: impl Into<String>) -> Self {
         Self {
-            provider: provider.into(),
+            provider,
             model_name: model_name.into(),
             ..Default::default()
         }
     }
 
+    /// Create a model config, rejecting an unknown provider or empty model name.
+    ///
+    /// [`ModelConfig::new`] accepts anything and a typo only surfaces when the
+    /// model is first created; this reports it at construction instead.
+    pub fn try_new(
+        provider: impl Into<String>,
+        model_name: impl Into<String>,
+    ) -> crate::Result<Self> {
+        let config = Self::new(provider, model_name);
+        config
+            .validate()
+            .map_err(|e| crate::RalphError::Configuration(e.to_string()))?;
+        Ok(config)
+    }
+
     /// Enable thinking mode.
     pub fn with_thinking(mut self) -> Self {
         self.thinking_enabled = true;
         self
     }
 
-    /// Set max tokens.
+    /// Cap each response at `tokens` instead of the provider default.
     pub fn with_max_tokens(mut self, tokens: usize) -> Self {
         self.max_tokens = tokens;
         self
     }
 
-    /// Set temperature.
+    /// Sample at `temp` instead of the provider default.
     pub fn with_temperature(mut self, temp: f32) -> Self {
         self.temperature = temp;
         self
     }
 
+    /// Read the API key from this environment variable only.
+    pub fn with_api_key_env(mut self, var: impl Into<String>) -> Self {
+        self.api_key_env = Some(var.into());
+        self
+    }
+
+    /// Use this API key instead of reading one from the environment.
+    pub fn with_api_key(mut self, key: impl Into<String>) -> Self {
+        self.api_key = Some(key.into());
+        self
+    }
+
+    /// Get the API key from this command's output (see [`API_KEY_COMMAND_TIMEOUT`]).
+    pub fn with_api_key_command(mut self, command: impl Into<String>) -> Self {
+        self.api_key_command = Some(command.into());
+        self
+    }
+
+    /// Read the API key from this file.
+    pub fn with_api_key_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.api_key_file = Some(path.into());
+        self
+    }
+
+    /// Give up on a model call that takes longer than `timeout` (zero = no limit).
+    pub fn with_model_call_timeout(mut self, timeout: Duration) -> Self {
+        self.model_call_timeout_secs = timeout.as_secs();
+        self
+    }
+
+    /// Send requests to this server, e.g. `http://gpu-box:11434` for a remote Ollama.
+    pub fn with_base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = Some(url.into());
+        self
+    }
+
+    /// The Ollama server to use: `base_url`, else `OLLAMA_HOST`, else
+    /// [`DEFAULT_OLLAMA_HOST`].
+    ///
+    /// `OLLAMA_HOST` is often set the way the Ollama server reads it, like
+    /// `0.0.0.0:11434`; a value without a scheme gets `http://`.
+    pub fn ollama_host(&self) -> String {
+        let host = self
+            .base_url
+            .clone()
+            .or_else(|| env::var("OLLAMA_HOST").ok())
+            .map(|h| h.trim().trim_end_matches('/').to_string())
+            .filter(|h| !h.is_empty())
+            .unwrap_or_else(|| DEFAULT_OLLAMA_HOST.to_string());
+        if host.contains("://") {
+            host
+        } else {
+            format!("http://{}", host)
+        }
+    }
+
+    /// Longest one model call may take, or `None` for no limit.
+    pub fn model_call_timeout(&self) -> Option<Duration> {
+        (self.model_call_timeout_secs > 0).then(|| Duration::from_secs(self.model_call_timeout_secs))
+    }
+
+    /// Resolve the API key for this model.
+    ///
+    /// Precedence, first match wins:
+    /// 1. `api_key`, when set
+    /// 2. `api_key_command`, when set: its trimmed stdout
+    /// 3. `api_key_file`, when set: its trimmed contents
+    /// 4. `api_key_env`, when set (no fallback if that variable is missing)
+    /// 5. the provider defaults from [`default_api_key_envs`], in order
+    ///
+    /// A command or file that fails is an error, not a reason to fall back.
+    /// Each command or file is read once per process; later calls get the
+    /// same key, which is also listed by [`resolved_secrets`]. The first read
+    /// of a command blocks for up to [`API_KEY_COMMAND_TIMEOUT`], so async
+    /// code calls this from a blocking task.
+    pub fn resolve_api_key(&self) -> crate::Result<String> {
+        if let Some(key) = self.api_key.as_deref().filter(|k| !k.trim().is_empty()) {
+            return Ok(key.to_string());
+        }
+
+        if let Some(command) = self.api_key_command.as_deref().filter(|c| !c.trim().is_empty()) {
+            return cached_secret(SecretSource::Command(command.to_string()), || {
+                run_secret_command(command, API_KEY_COMMAND_TIMEOUT)
+            });
+        }
+
+        if let Some(path) = &self.api_key_file {
+            return cached_secret(SecretSource::File(path.clone()), || {
+                let contents = std::fs::read_to_string(path).map_err(|e| {
+                    crate::RalphError::Configuration(format!(
+                        "Failed to read the {} API key file '{}': {}",
+                        self.provider,
+                        path.display(),
+                        e
+                    ))
+                })?;
+                let key = contents.trim();
+                if key.is_empty() {
+                    return Err(crate::RalphError::Configuration(format!(
+                        "The {} API key file '{}' is empty",
+                        self.provider,
+                        path.display()
+                    )));
+                }
+                Ok(key.to_string())
+            });
+        }
+
+        if let Some(var) = self.api_key_env.as_deref().filter(|v| !v.trim().is_empty()) {
+            return env::var(var)
+                .ok()
+                .filter(|k| !k.trim().is_empty())
+                .ok_or_else(|| {
+                    crate::RalphError::Configuration(format!(
+                        "{} environment variable not set (configured as the {} API key)",
+                        var, self.provider
+                    ))
+                });
+        }
+
+        let vars = default_api_key_envs(&self.provider);
+        vars.iter()
+            .find_map(|var| env::var(var).ok().filter(|k| !k.trim().is_empty()))
+            .ok_or_else(|| {
+                crate::RalphError::Configuration(format!(
+                    "{} environment variable not set",
+                    vars.join(" or ")
+                ))
+            })
+    }
+
     /// Validate the model config.
     ///
     /// Checks:
@@ -216,22 +839,12 @@ impl ModelConfig {
     /// - Temperature is within valid range (0.0-2.0)
     pub fn validate(&self) -> Result<(), ValidationError> {
         // Validate provider
-        if self.provider.is_empty() {
-            return Err(ValidationError::new("provider", "Model provider cannot be empty")
-                .with_suggestion(format!("Use one of: {:?}", SUPPORTED_PROVIDERS)));
-        }
-
-        let provider_lower = self.provider.to_lowercase();
-        if !SUPPORTED_PROVIDERS.contains(&provider_lower.as_str()) {
-            return Err(ValidationError::new(
-                "provider",
-                format!("Unsupported model provider '{}'", self.provider),
-            )
-            .with_suggestion(format!("Supported providers: {:?}", SUPPORTED_PROVIDERS)));
+        if let Provider::Unknown(name) = &self.provider {
+            return Err(Provider::unknown_error(name));
         }
 
         // Validate model name
-        if self.model_name.is_empty() {
+        if self.model_name.trim().is_empty() {
             return Err(ValidationError::new("model_name", "Model name cannot be empty")
                 .with_suggestion("Specify a valid model name like 'claude-sonnet-4-20250514' or 'gpt-4o'"));
         }
@@ -286,6 +899,17 @@ impl ModelConfig {
             .with_suggestion("Use a value between 0.0 and 2.0"));
         }
 
+        // Validate base URL
+        if let Some(url) = &self.base_url {
+            if !(url.starts_with("http://") || url.starts_with("https://")) {
+                return Err(ValidationError::new(
+                    "base_url",
+                    format!("Base URL '{}' is not an http(s) URL", url),
+                )
+                .with_suggestion("Use a URL like http://localhost:11434"));
+            }
+        }
+
         Ok(())
     }
 }
@@ -299,6 +923,13 @@ pub struct AgentModelConfig {
     pub architect_model: ModelConfig,
     /// Model config for Ralph Loop Agent (implementation)
     pub ralph_model: ModelConfig,
+    /// Model config for Judge Agent (acceptance-criteria review)
+    #[serde(default = "default_judge_model")]
+    pub judge_model: ModelConfig,
+}
+
+fn default_judge_model() -> ModelConfig {
+    ModelConfig::new("gemini", "gemini-2.5-flash")
 }
 
 impl Default for AgentModelConfig {
@@ -312,6 +943,8 @@ impl Default for AgentModelConfig {
             // Note: Gemini 3 models require thought_signature support for tool calls,
             // which adk-rust v0.3.2 does not yet handle. Use 2.5 Flash until upstream support lands.
             ralph_model: ModelConfig::new("gemini", "gemini-2.5-flash"),
+            // Judge Agent: a fast model is enough to review one task's files
+            judge_model: default_judge_model(),
         }
     }
 }
@@ -319,11 +952,27 @@ impl Default for AgentModelConfig {
 impl AgentModelConfig {
     /// Create from environment variables.
     pub fn from_env() -> Result<Self, ValidationError> {
+        let mut errors = Vec::new();
+        let config = Self::read_env(&mut errors);
+        if let Some(e) = errors.into_iter().next() {
+            return Err(e);
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Read the environment variables without validating the result.
+    ///
+    /// A value that cannot be parsed is recorded in `errors` and leaves the
+    /// setting at its default, so every bad variable can be reported at once.
+    pub fn read_env(errors: &mut Vec<ValidationError>) -> Self {
         let mut config = Self::default();
 
         // PRD Agent config
         if let Ok(provider) = env::var("RALPH_PRD_PROVIDER") {
-            config.prd_model.provider = provider;
+            if let Some(value) = noted(errors, provider.parse()) {
+                config.prd_model.provider = value;
+            }
         }
         if let Ok(model) = env::var("RALPH_PRD_MODEL") {
             config.prd_model.model_name = model;
@@ -331,10 +980,15 @@ impl AgentModelConfig {
         if let Ok(thinking) = env::var("RALPH_PRD_THINKING") {
             config.prd_model.thinking_enabled = thinking.to_lowercase() == "true";
         }
+        if let Ok(var) = env::var("RALPH_PRD_API_KEY_ENV") {
+            config.prd_model.api_key_env = Some(var);
+        }
 
         // Architect Agent config
         if let Ok(provider) = env::var("RALPH_ARCHITECT_PROVIDER") {
-            config.architect_model.provider = provider;
+            if let Some(value) = noted(errors, provider.parse()) {
+                config.architect_model.provider = value;
+            }
         }
         if let Ok(model) = env::var("RALPH_ARCHITECT_MODEL") {
             config.architect_model.model_name = model;
@@ -342,10 +996,15 @@ impl AgentModelConfig {
         if let Ok(thinking) = env::var("RALPH_ARCHITECT_THINKING") {
             config.architect_model.thinking_enabled = thinking.to_lowercase() == "true";
         }
+        if let Ok(var) = env::var("RALPH_ARCHITECT_API_KEY_ENV") {
+            config.architect_model.api_key_env = Some(var);
+        }
 
         // Ralph Loop Agent config
         if let Ok(provider) = env::var("RALPH_LOOP_PROVIDER") {
-            config.ralph_model.provider = provider;
+            if let Some(value) = noted(errors, provider.parse()) {
+                config.ralph_model.provider = value;
+            }
         }
         if let Ok(model) = env::var("RALPH_LOOP_MODEL") {
             config.ralph_model.model_name = model;
@@ -353,19 +1012,100 @@ impl AgentModelConfig {
         if let Ok(thinking) = env::var("RALPH_LOOP_THINKING") {
             config.ralph_model.thinking_enabled = thinking.to_lowercase() == "true";
         }
+        if let Ok(var) = env::var("RALPH_LOOP_API_KEY_ENV") {
+            config.ralph_model.api_key_env = Some(var);
+        }
+
+        // Judge Agent config
+        if let Ok(provider) = env::var("RALPH_JUDGE_PROVIDER") {
+            if let Some(value) = noted(errors, provider.parse()) {
+                config.judge_model.provider = value;
+            }
+        }
+        if let Ok(model) = env::var("RALPH_JUDGE_MODEL") {
+            config.judge_model.model_name = model;
+        }
+        if let Ok(var) = env::var("RALPH_JUDGE_API_KEY_ENV") {
+            config.judge_model.api_key_env = Some(var);
+        }
 
         // Also support legacy single-model config
         if let Ok(provider) = env::var("RALPH_MODEL_PROVIDER") {
-            config.prd_model.provider = provider.clone();
-            config.architect_model.provider = provider.clone();
-            config.ralph_model.provider = provider;
+            if let Some(provider) = noted(errors, provider.parse::<Provider>()) {
+                config.prd_model.provider = provider.clone();
+                config.architect_model.provider = provider.clone();
+                config.ralph_model.provider = provider.clone();
+                config.judge_model.provider = provider;
+            }
         }
         if let Ok(model) = env::var("RALPH_MODEL_NAME") {
             config.ralph_model.model_name = model;
         }
 
-        config.validate()?;
-        Ok(config)
+        // Secret sources per agent, for keys that must not sit in the environment
+        let prefixes = ["PRD", "ARCHITECT", "LOOP", "JUDGE"];
+        for (prefix, model) in prefixes.into_iter().zip(config.models_mut()) {
+            if let Ok(command) = env::var(format!("RALPH_{}_API_KEY_COMMAND", prefix)) {
+                model.api_key_command = Some(command.trim().to_string()).filter(|c| !c.is_empty());
+            }
+            if let Ok(path) = env::var(format!("RALPH_{}_API_KEY_FILE", prefix)) {
+                model.api_key_file = Some(path.trim()).filter(|p| !p.is_empty()).map(PathBuf::from);
+            }
+        }
+
+        // Server URL per agent, e.g. a remote Ollama box
+        for (prefix, model) in prefixes.into_iter().zip(config.models_mut()) {
+            if let Ok(url) = env::var(format!("RALPH_{}_BASE_URL", prefix)) {
+                model.base_url = Some(url.trim().to_string()).filter(|u| !u.is_empty());
+            }
+        }
+
+        // Sampling overrides per agent; unset keeps 0.7 and 4096 tokens
+        for (prefix, model) in prefixes.into_iter().zip(config.models_mut()) {
+            if let Ok(value) = env::var(format!("RALPH_{}_TEMPERATURE", prefix)) {
+                let temperature = value.trim().parse::<f32>().map_err(|_| {
+                    ValidationError::new("temperature", format!("Invalid value '{}'", value))
+                        .with_suggestion("Use a number between 0.0 and 2.0")
+                });
+                if let Some(temperature) = noted(errors, temperature) {
+                    model.temperature = temperature;
+                }
+            }
+            if let Ok(value) = env::var(format!("RALPH_{}_MAX_TOKENS", prefix)) {
+                let tokens = value.trim().parse::<usize>().map_err(|_| {
+                    ValidationError::new("max_tokens", format!("Invalid value '{}'", value))
+                        .with_suggestion("Use a whole number of tokens, e.g. 8192")
+                });
+                if let Some(tokens) = noted(errors, tokens) {
+                    model.max_tokens = tokens;
+                }
+            }
+        }
+
+        // Model call timeout: shared by every agent
+        if let Ok(secs) = env::var("RALPH_MODEL_CALL_TIMEOUT_SECS") {
+            let secs = secs.trim().parse::<u64>().map_err(|_| {
+                ValidationError::new("model_call_timeout_secs", format!("Invalid value '{}'", secs))
+                    .with_suggestion("Use a whole number of seconds, or 0 for no limit")
+            });
+            if let Some(secs) = noted(errors, secs) {
+                for model in config.models_mut() {
+                    model.model_call_timeout_secs = secs;
+                }
+            }
+        }
+
+        config
+    }
+
+    /// Every agent's model config, in PRD, architect, loop, judge order.
+    fn models_mut(&mut self) -> [&mut ModelConfig; 4] {
+        [
+            &mut self.prd_model,
+            &mut self.architect_model,
+            &mut self.ralph_model,
+            &mut self.judge_model,
+        ]
     }
 
     /// Validate all model configs.
@@ -390,6 +1130,13 @@ impl AgentModelConfig {
                 format!("agents.ralph_model.{}", e.field),
                 e.message,
             ).with_suggestion(e.suggestion.unwrap_or_default()))?;
+
+        self.judge_model
+            .validate()
+            .map_err(|e| ValidationError::new(
+                format!("agents.judge_model.{}", e.field),
+                e.message,
+            ).with_suggestion(e.suggestion.unwrap_or_default()))?;
         
         Ok(())
     }
@@ -446,6 +1193,13 @@ impl Default for TelemetryConfig {
 impl TelemetryConfig {
     /// Create from environment variables.
     pub fn from_env() -> Result<Self, ValidationError> {
+        let config = Self::read_env();
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Read the environment variables without validating the result.
+    pub fn read_env() -> Self {
         let mut config = Self::default();
 
         if let Ok(enabled) = env::var("RALPH_TELEMETRY_ENABLED") {
@@ -467,8 +1221,7 @@ impl TelemetryConfig {
             config.log_level = level;
         }
 
-        config.validate()?;
-        Ok(config)
+        config
     }
 
     /// Validate telemetry config.
@@ -549,6 +1302,156 @@ pub struct RalphConfig {
     /// Maximum retries for failed tasks
     #[serde(default = "default_max_retries")]
     pub max_task_retries: usize,
+    /// Extra test runs allowed after a failing run before tests count as failed
+    #[serde(default)]
+    pub flaky_test_retries: u32,
+    /// Consecutive green runs required to accept a pass once a run has failed
+    #[serde(default = "default_flaky_confirm_runs")]
+    pub flaky_confirm_runs: u32,
+    /// How often to re-run the full test suite during implementation
+    #[serde(default)]
+    pub verification_cadence: VerificationCadence,
+    /// Builds the CPUs are split between when deriving `tool_jobs`
+    #[serde(default, alias = "max_parallel_tasks")]
+    pub concurrent_builds: BuildConcurrency,
+    /// Order in which ready tasks are picked
+    #[serde(default)]
+    pub scheduling: SchedulingStrategy,
+    /// Job count passed to build tools (`cargo --jobs`, `go -p`, `mvn -T`); `None` = derived
+    #[serde(default)]
+    pub tool_jobs: Option<usize>,
+    /// Memory limit per build/test/run command in MiB; `None` = unlimited (unix only)
+    #[serde(default)]
+    pub max_rss_mb: Option<u64>,
+    /// CPU time limit per build/test/run command in seconds; `None` = unlimited (unix only)
+    #[serde(default)]
+    pub max_cpu_secs: Option<u64>,
+    /// Syntax-color tool call/response JSON at verbose output levels
+    #[serde(default)]
+    pub color_json: bool,
+    /// Require the judge agent to confirm acceptance criteria before a task completes
+    #[serde(default)]
+    pub judge_enabled: bool,
+    /// Refuse a task's completion until its tests have passed since it started
+    #[serde(default = "default_verify_completion")]
+    pub verify_completion: bool,
+    /// Show a live overall bar and current-task line instead of the single progress bar
+    #[serde(default)]
+    pub multi_progress: bool,
+    /// Colored output: detected from the terminal and `NO_COLOR`, or forced
+    #[serde(default)]
+    pub color: ConsoleSetting,
+    /// Unicode symbols and bars: detected from the console, or forced
+    #[serde(default)]
+    pub unicode: ConsoleSetting,
+    /// Colors used for success, failure, warnings and dimmed detail
+    #[serde(default)]
+    pub palette: PalettePreset,
+    /// Batch terminal writes and flush them periodically instead of per line
+    #[serde(default = "default_buffered_output")]
+    pub buffered_output: bool,
+    /// Also write progress events as JSON lines to this file, or to stdout for `-`
+    #[serde(default)]
+    pub event_log: Option<String>,
+    /// Print human-readable output; when off only errors are printed, so with
+    /// `event_log = "-"` stdout carries nothing but events
+    #[serde(default = "default_human_output")]
+    pub human_output: bool,
+    /// Check generated manifests against package registries for outdated dependencies
+    ///
+    /// Off by default because it queries crates.io/npm over the network.
+    #[serde(default)]
+    pub dependency_advice: bool,
+    /// Build the project right after its setup task and stop the run if it fails
+    #[serde(default = "default_scaffold_check")]
+    pub scaffold_check: bool,
+    /// Check each completed task's public identifiers against the project glossary
+    #[serde(default = "default_glossary_check")]
+    pub glossary_check: bool,
+    /// Retry a model call once with a trimmed conversation when it overflows the context window
+    #[serde(default = "default_trim_on_overflow")]
+    pub trim_on_overflow: bool,
+    /// Never contact package registries; use cached responses only
+    #[serde(default)]
+    pub offline: bool,
+    /// Deepest file path the architect may propose (top-level entries are depth 1)
+    #[serde(default = "default_structure_max_depth")]
+    pub structure_max_depth: usize,
+    /// Lowest average number of entries per directory in the proposed file tree
+    #[serde(default = "default_structure_min_fan_out")]
+    pub structure_min_fan_out: f32,
+    /// Re-ask the architect when its file tree breaks the limits instead of flattening it
+    #[serde(default)]
+    pub structure_strict: bool,
+    /// Fail design generation when a component's file is created by no task
+    #[serde(default)]
+    pub coverage_strict: bool,
+    /// Accept a design with no tasks (with a warning) instead of failing
+    #[serde(default)]
+    pub allow_empty_plan: bool,
+    /// Most files one task may write or delete (`None` = no cap)
+    #[serde(default)]
+    pub max_files_per_task: Option<usize>,
+    /// Pause before starting each task after the first (`None` = no pause)
+    #[serde(default)]
+    pub inter_task_delay: Option<Duration>,
+    /// Up to this much random extra pause on top of `inter_task_delay`
+    #[serde(default)]
+    pub inter_task_jitter: Option<Duration>,
+    /// Variables set only on the generated project's build/test/run commands
+    #[serde(default)]
+    pub execution_env: BTreeMap<String, String>,
+    /// Directory or `s3://`/`gs://` URL for fine-tuning dataset shards (`None` = off)
+    #[serde(default)]
+    pub dataset_export: Option<String>,
+    /// `Name <email>` to attribute the loop's commits to (`None` = the repo's identity)
+    #[serde(default)]
+    pub commit_author: Option<String>,
+    /// Implement each task on its own `ralph/<id>-<title>` branch, merged back on success
+    #[serde(default)]
+    pub task_branches: bool,
+    /// How task branches are merged back into the base branch
+    #[serde(default)]
+    pub task_merge: MergeStrategy,
+    /// Reset the project to where a task started when the task ends up blocked
+    #[serde(default)]
+    pub rollback_failed: bool,
+    /// Queue a low-priority task for the leftovers the end-of-run cleanup check finds
+    #[serde(default)]
+    pub cleanup_task: bool,
+    /// Give each task's test/run commands their own temp directory, removed afterwards
+    #[serde(default)]
+    pub isolate_test_artifacts: bool,
+    /// With `isolate_test_artifacts`, also give each task its own `CARGO_TARGET_DIR`
+    #[serde(default)]
+    pub isolate_target_dir: bool,
+    /// Languages whose source files get a quick syntax check after every write (empty = off)
+    #[serde(default)]
+    pub smoke_gate: Vec<Language>,
+    /// List every warning in the end-of-run warnings section, not just the counts
+    #[serde(default)]
+    pub expand_warnings: bool,
+    /// Refuse to run a project pinned to a different minor/major ralph version
+    #[serde(default)]
+    pub version_strict: bool,
+    /// Mention a newer ralph release on crates.io, at most once a day
+    #[serde(default)]
+    pub update_check: bool,
+    /// Existing files from this many lines get rewrites refused in favor of edits (0 = off)
+    #[serde(default = "default_edit_guard_lines")]
+    pub edit_guard_lines: usize,
+    /// Share of changed lines above which a write counts as a genuine rewrite
+    #[serde(default = "default_edit_guard_rewrite_ratio")]
+    pub edit_guard_rewrite_ratio: f64,
+    /// Languages the rewrite refusal applies to (empty = off)
+    #[serde(default = "default_edit_guard_languages")]
+    pub edit_guard_languages: Vec<Language>,
+    /// File extensions mapped to languages over the defaults, for picking a task's test command
+    #[serde(default)]
+    pub language_extensions: BTreeMap<String, Language>,
+    /// Model spend per loop iteration, for `ralph scope` estimates (`None` = unknown)
+    #[serde(default)]
+    pub cost_per_iteration: Option<f64>,
 }
 
 fn default_max_iterations() -> usize {
@@ -583,6 +1486,54 @@ fn default_max_retries() -> usize {
     3
 }
 
+fn default_flaky_confirm_runs() -> u32 {
+    1
+}
+
+fn default_verify_completion() -> bool {
+    true
+}
+
+fn default_buffered_output() -> bool {
+    true
+}
+
+fn default_human_output() -> bool {
+    true
+}
+
+fn default_scaffold_check() -> bool {
+    true
+}
+
+fn default_glossary_check() -> bool {
+    true
+}
+
+fn default_trim_on_overflow() -> bool {
+    true
+}
+
+fn default_structure_max_depth() -> usize {
+    StructureLimits::default().max_depth
+}
+
+fn default_structure_min_fan_out() -> f32 {
+    StructureLimits::default().min_average_fan_out
+}
+
+fn default_edit_guard_lines() -> usize {
+    crate::edit_guard::DEFAULT_MIN_LINES
+}
+
+fn default_edit_guard_rewrite_ratio() -> f64 {
+    crate::edit_guard::DEFAULT_REWRITE_RATIO
+}
+
+fn default_edit_guard_languages() -> Vec<Language> {
+    crate::edit_guard::OUTLINE_LANGUAGES.to_vec()
+}
+
 impl Default for RalphConfig {
     fn default() -> Self {
         Self {
@@ -597,6 +1548,55 @@ impl Default for RalphConfig {
             project_path: default_project_path(),
             completion_promise: default_completion_promise(),
             max_task_retries: default_max_retries(),
+            flaky_test_retries: 0,
+            flaky_confirm_runs: default_flaky_confirm_runs(),
+            verification_cadence: VerificationCadence::default(),
+            concurrent_builds: BuildConcurrency::default(),
+            scheduling: SchedulingStrategy::default(),
+            tool_jobs: None,
+            max_rss_mb: None,
+            max_cpu_secs: None,
+            color_json: false,
+            judge_enabled: false,
+            verify_completion: default_verify_completion(),
+            multi_progress: false,
+            color: ConsoleSetting::Auto,
+            unicode: ConsoleSetting::Auto,
+            palette: PalettePreset::Default,
+            buffered_output: default_buffered_output(),
+            event_log: None,
+            human_output: default_human_output(),
+            dependency_advice: false,
+            scaffold_check: default_scaffold_check(),
+            glossary_check: default_glossary_check(),
+            trim_on_overflow: default_trim_on_overflow(),
+            offline: false,
+            structure_max_depth: default_structure_max_depth(),
+            structure_min_fan_out: default_structure_min_fan_out(),
+            structure_strict: false,
+            coverage_strict: false,
+            allow_empty_plan: false,
+            max_files_per_task: None,
+            inter_task_delay: None,
+            inter_task_jitter: None,
+            execution_env: BTreeMap::new(),
+            dataset_export: None,
+            commit_author: None,
+            task_branches: false,
+            task_merge: MergeStrategy::default(),
+            rollback_failed: false,
+            cleanup_task: false,
+            isolate_test_artifacts: false,
+            isolate_target_dir: false,
+            smoke_gate: Vec::new(),
+            expand_warnings: false,
+            version_strict: false,
+            update_check: false,
+            edit_guard_lines: default_edit_guard_lines(),
+            edit_guard_rewrite_ratio: default_edit_guard_rewrite_ratio(),
+            edit_guard_languages: default_edit_guard_languages(),
+            language_extensions: BTreeMap::new(),
+            cost_per_iteration: None,
         }
     }
 }
@@ -607,6 +1607,73 @@ impl RalphConfig {
         RalphConfigBuilder::default()
     }
 
+    /// Limits on the architect's proposed file tree.
+    pub fn structure_limits(&self) -> StructureLimits {
+        StructureLimits {
+            max_depth: self.structure_max_depth,
+            min_average_fan_out: self.structure_min_fan_out,
+            strict: self.structure_strict,
+        }
+    }
+
+    /// When the file tool refuses a rewrite in favor of an edit.
+    pub fn edit_guard(&self) -> EditGuard {
+        EditGuard {
+            min_lines: self.edit_guard_lines,
+            rewrite_ratio: self.edit_guard_rewrite_ratio,
+            languages: self.edit_guard_languages.clone(),
+        }
+    }
+
+    /// Extension to language table for the files a task touches.
+    pub fn extension_map(&self) -> ExtensionMap {
+        ExtensionMap::with_overrides(&self.language_extensions)
+    }
+
+    /// Builds the CPUs of this machine are split between.
+    pub fn resolved_concurrent_builds(&self) -> usize {
+        self.concurrent_builds.resolve(available_cpus())
+    }
+
+    /// The identity to attribute the loop's commits to, if one is configured.
+    pub fn commit_author(&self) -> Result<Option<CommitAuthor>, String> {
+        self.commit_author.as_deref().map(CommitAuthor::parse).transpose()
+    }
+
+    /// Per-task directories for the build/test/run commands.
+    pub fn artifact_isolation(&self) -> ArtifactIsolation {
+        ArtifactIsolation {
+            scratch: self.isolate_test_artifacts,
+            target_dir: self.isolate_test_artifacts && self.isolate_target_dir,
+        }
+    }
+
+    /// Memory and CPU time limits for each build/test/run command.
+    pub fn resource_limits(&self) -> ResourceLimits {
+        ResourceLimits {
+            max_rss_mb: self.max_rss_mb,
+            max_cpu_secs: self.max_cpu_secs,
+        }
+    }
+
+    /// Job count for build and test tools on this machine.
+    pub fn tool_jobs(&self) -> Option<usize> {
+        self.tool_jobs_for(available_cpus())
+    }
+
+    /// Job count for build and test tools on a machine with `cpus` CPUs.
+    ///
+    /// An explicit `tool_jobs` wins. Otherwise, with `concurrent_builds` above
+    /// one, the CPUs are split between the builds so that builds times jobs
+    /// stays near the CPU count; with one build the tools pick their own.
+    pub fn tool_jobs_for(&self, cpus: usize) -> Option<usize> {
+        if self.tool_jobs.is_some() {
+            return self.tool_jobs;
+        }
+        let builds = self.concurrent_builds.resolve(cpus);
+        (builds > 1).then(|| (cpus / builds).max(1))
+    }
+
     /// Load configuration from environment variables with defaults.
     ///
     /// This method loads configuration from environment variables and validates
@@ -623,23 +1690,89 @@ impl RalphConfig {
     /// - `RALPH_PROJECT_PATH` - Base project directory (default: .)
     /// - `RALPH_COMPLETION_PROMISE` - Message on completion
     /// - `RALPH_MAX_TASK_RETRIES` - Max retries per task (default: 3)
+    /// - `RALPH_FLAKY_TEST_RETRIES` - Test re-runs allowed after a failure (default: 0)
+    /// - `RALPH_FLAKY_CONFIRM_RUNS` - Consecutive greens needed after a failure (default: 1)
+    /// - `RALPH_VERIFY_CADENCE` - Full-suite verification: off, phase, every:N (default: phase)
+    /// - `RALPH_CONCURRENT_BUILDS` - Builds the CPUs are split between for `RALPH_TOOL_JOBS`: N, auto or auto:F (default: 1)
+    /// - `RALPH_SCHEDULING` - Ready-task order: priority, critical_path, shortest (default: priority)
+    /// - `RALPH_TOOL_JOBS` - Jobs per build/test command (default: CPUs / concurrent builds)
+    /// - `RALPH_MAX_RSS_MB` - Memory limit per build/test/run command in MiB (default: none)
+    /// - `RALPH_MAX_CPU_SECS` - CPU time limit per build/test/run command (default: none)
+    /// - `RALPH_COLOR_JSON` - Syntax-color tool JSON at verbose levels (default: false)
+    /// - `RALPH_JUDGE_ENABLED` - Gate task completion on the acceptance-criteria judge (default: false)
+    /// - `RALPH_VERIFY_COMPLETION` - Refuse completion until the task's tests pass (default: true)
+    /// - `RALPH_MULTI_PROGRESS` - Two-line progress view: overall bar and current task (default: false)
+    /// - `RALPH_COLOR` - Colored output: auto, always, never (default: auto, honors `NO_COLOR`)
+    /// - `RALPH_UNICODE` - Unicode symbols and bars: auto, always, never (default: auto)
+    /// - `RALPH_PALETTE` - Output colors: default, light, colorblind, mono (default: default)
+    /// - `RALPH_BUFFERED_OUTPUT` - Batch terminal writes, flushing periodically (default: true)
+    /// - `RALPH_EVENT_LOG` - JSON-lines event file, or `-` for stdout (default: none)
+    /// - `RALPH_HUMAN_OUTPUT` - Print human-readable output besides errors (default: true)
+    /// - `RALPH_DEPENDENCY_ADVICE` - Check generated manifests for outdated dependencies (default: false)
+    /// - `RALPH_SCAFFOLD_CHECK` - Stop the run when the project does not build after its setup task (default: true)
+    /// - `RALPH_GLOSSARY_CHECK` - Warn when a task's public identifiers use near-synonyms of glossary terms (default: true)
+    /// - `RALPH_TRIM_ON_OVERFLOW` - Retry a call that overflows the context window with older tool exchanges dropped (default: true)
+    /// - `RALPH_OFFLINE` - Use cached registry responses only (default: false)
+    /// - `RALPH_STRUCTURE_MAX_DEPTH` - Deepest file path the architect may propose (default: 4)
+    /// - `RALPH_STRUCTURE_MIN_FAN_OUT` - Lowest average entries per directory (default: 1.5)
+    /// - `RALPH_STRUCTURE_STRICT` - Re-ask the architect instead of flattening (default: false)
+    /// - `RALPH_COVERAGE_STRICT` - Fail when a design component has no task creating it (default: false)
+    /// - `RALPH_ALLOW_EMPTY_PLAN` - Warn instead of failing when the architect returns no tasks (default: false)
+    /// - `RALPH_MAX_FILES_PER_TASK` - Most files one task may write (default: unset, no cap)
+    /// - `RALPH_INTER_TASK_DELAY_MS` - Pause between task starts in ms (default: unset, no pause)
+    /// - `RALPH_INTER_TASK_JITTER_MS` - Random extra pause of up to this many ms (default: unset)
+    /// - `RALPH_EXEC_ENV_<NAME>` - Sets `<NAME>` for the project's build/test/run commands only
+    /// - `RALPH_DATASET_EXPORT` - Directory or bucket URL for dataset shards (default: unset, off)
+    /// - `RALPH_COMMIT_AUTHOR` - `Name <email>` for the loop's commits (default: the repo's identity)
+    /// - `RALPH_TASK_BRANCHES` - Implement each task on its own git branch (default: false)
+    /// - `RALPH_TASK_MERGE` - Merge finished task branches: ff, squash (default: ff)
+    /// - `RALPH_ROLLBACK_FAILED` - Reset the project when a task is blocked (default: false)
+    /// - `RALPH_CLEANUP_TASK` - Queue a cleanup task for leftover files and markers (default: false)
+    /// - `RALPH_ISOLATE_TEST_ARTIFACTS` - A temp directory per task for its commands (default: false)
+    /// - `RALPH_ISOLATE_TARGET_DIR` - Also a `CARGO_TARGET_DIR` per task, no shared build cache (default: false)
+    /// - `RALPH_SMOKE_GATE` - Syntax-check files after each write: off, all or a language list (default: off)
+    /// - `RALPH_EXPAND_WARNINGS` - List every warning at the end of a run, not just counts (default: false)
+    /// - `RALPH_VERSION_STRICT` - Refuse to run a project pinned to another minor/major version (default: false)
+    /// - `RALPH_UPDATE_CHECK` - Mention newer ralph releases once a day (default: false)
+    /// - `RALPH_EDIT_GUARD` - Languages whose large files must be edited, not rewritten: off, all or a list (default: all)
+    /// - `RALPH_EDIT_GUARD_LINES` - File size from which rewrites are refused, 0 = off (default: 300)
+    /// - `RALPH_EDIT_GUARD_REWRITE_RATIO` - Changed share above which a rewrite is allowed (default: 0.5)
+    /// - `RALPH_LANGUAGE_EXTENSIONS` - Extra extension mappings for test command selection, e.g. `pyx=python,kt=none`
+    /// - `RALPH_COST_PER_ITERATION` - Model spend per loop iteration for `ralph scope` (default: none)
     pub fn from_env() -> Result<Self, ValidationError> {
-        let config = Self {
-            agents: AgentModelConfig::from_env()?,
-            telemetry: TelemetryConfig::from_env()?,
+        let mut errors = Vec::new();
+        let config = Self::read_env(&mut errors);
+        if let Some(e) = errors.into_iter().next() {
+            return Err(e);
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Read every variable listed on [`from_env`](Self::from_env) without
+    /// validating the result.
+    ///
+    /// A value that cannot be parsed is recorded in `errors` and leaves the
+    /// setting at its default, so every bad variable can be reported at once
+    /// (see [`crate::preflight`]).
+    pub fn read_env(errors: &mut Vec<ValidationError>) -> Self {
+        let mut config = Self {
+            agents: AgentModelConfig::read_env(errors),
+            telemetry: TelemetryConfig::read_env(),
             ..Default::default()
         };
 
         // Load other settings with validation
-        let mut config = config;
         if let Ok(iterations) = env::var("RALPH_MAX_ITERATIONS") {
-            config.max_iterations = iterations.parse().map_err(|e| {
+            if let Some(value) = noted(errors, iterations.parse().map_err(|e| {
                 ValidationError::new(
                     "max_iterations",
                     format!("Invalid RALPH_MAX_ITERATIONS '{}': {}", iterations, e),
                 )
                 .with_suggestion("Use a positive integer like 50 or 100")
-            })?;
+            })) {
+                config.max_iterations = value;
+            }
         }
 
         if let Ok(path) = env::var("RALPH_PRD_PATH") {
@@ -667,22 +1800,351 @@ impl RalphConfig {
         }
 
         if let Ok(retries) = env::var("RALPH_MAX_TASK_RETRIES") {
-            config.max_task_retries = retries.parse().map_err(|e| {
+            if let Some(value) = noted(errors, retries.parse().map_err(|e| {
                 ValidationError::new(
                     "max_task_retries",
                     format!("Invalid RALPH_MAX_TASK_RETRIES '{}': {}", retries, e),
                 )
                 .with_suggestion("Use a positive integer like 3 or 5")
-            })?;
+            })) {
+                config.max_task_retries = value;
+            }
+        }
+
+        if let Ok(retries) = env::var("RALPH_FLAKY_TEST_RETRIES") {
+            if let Some(value) = noted(errors, retries.parse().map_err(|e| {
+                ValidationError::new(
+                    "flaky_test_retries",
+                    format!("Invalid RALPH_FLAKY_TEST_RETRIES '{}': {}", retries, e),
+                )
+                .with_suggestion("Use a non-negative integer like 0 or 2")
+            })) {
+                config.flaky_test_retries = value;
+            }
+        }
+
+        if let Ok(runs) = env::var("RALPH_FLAKY_CONFIRM_RUNS") {
+            if let Some(value) = noted(errors, runs.parse().map_err(|e| {
+                ValidationError::new(
+                    "flaky_confirm_runs",
+                    format!("Invalid RALPH_FLAKY_CONFIRM_RUNS '{}': {}", runs, e),
+                )
+                .with_suggestion("Use a positive integer like 1 or 2")
+            })) {
+                config.flaky_confirm_runs = value;
+            }
+        }
+
+        if let Ok(cadence) = env::var("RALPH_VERIFY_CADENCE") {
+            if let Some(value) = noted(errors, cadence.parse()) {
+                config.verification_cadence = value;
+            }
+        }
+
+        if let Ok(builds) = env::var("RALPH_CONCURRENT_BUILDS") {
+            if let Some(value) = noted(errors, builds.parse()) {
+                config.concurrent_builds = value;
+            }
+        }
+
+        if let Ok(scheduling) = env::var("RALPH_SCHEDULING") {
+            if let Some(value) = noted(errors, scheduling.parse()) {
+                config.scheduling = value;
+            }
+        }
+
+        if let Ok(jobs) = env::var("RALPH_TOOL_JOBS") {
+            if let Some(value) = noted(errors, jobs.parse().map_err(|e| {
+                ValidationError::new("tool_jobs", format!("Invalid RALPH_TOOL_JOBS '{}': {}", jobs, e))
+                    .with_suggestion("Use a positive integer like 4, or unset it to derive from the CPU count")
+            })) {
+                config.tool_jobs = Some(value);
+            }
+        }
+
+        if let Ok(mb) = env::var("RALPH_MAX_RSS_MB") {
+            if let Some(value) = noted(errors, mb.parse().map_err(|e| {
+                ValidationError::new("max_rss_mb", format!("Invalid RALPH_MAX_RSS_MB '{}': {}", mb, e))
+                    .with_suggestion("Use a size in MiB like 2048, or unset it for no limit")
+            })) {
+                config.max_rss_mb = Some(value);
+            }
+        }
+
+        if let Ok(secs) = env::var("RALPH_MAX_CPU_SECS") {
+            if let Some(value) = noted(errors, secs.parse().map_err(|e| {
+                ValidationError::new("max_cpu_secs", format!("Invalid RALPH_MAX_CPU_SECS '{}': {}", secs, e))
+                    .with_suggestion("Use a number of seconds like 600, or unset it for no limit")
+            })) {
+                config.max_cpu_secs = Some(value);
+            }
+        }
+
+        if let Ok(color) = env::var("RALPH_COLOR_JSON") {
+            config.color_json = color.to_lowercase() == "true";
+        }
+
+        if let Ok(judge) = env::var("RALPH_JUDGE_ENABLED") {
+            config.judge_enabled = judge.to_lowercase() == "true";
+        }
+
+        if let Ok(verify) = env::var("RALPH_VERIFY_COMPLETION") {
+            config.verify_completion = verify.to_lowercase() == "true";
+        }
+
+        if let Ok(multi) = env::var("RALPH_MULTI_PROGRESS") {
+            config.multi_progress = multi.to_lowercase() == "true";
+        }
+
+        if let Ok(color) = env::var("RALPH_COLOR") {
+            if let Some(value) = noted(errors, color.parse().map_err(|e: ValidationError| ValidationError {
+                field: "color".to_string(),
+                ..e
+            })) {
+                config.color = value;
+            }
+        }
+
+        if let Ok(unicode) = env::var("RALPH_UNICODE") {
+            if let Some(value) = noted(errors, unicode.parse().map_err(|e: ValidationError| ValidationError {
+                field: "unicode".to_string(),
+                ..e
+            })) {
+                config.unicode = value;
+            }
+        }
+
+        if let Ok(palette) = env::var("RALPH_PALETTE") {
+            if let Some(value) = noted(errors, palette.parse()) {
+                config.palette = value;
+            }
+        }
+
+        if let Ok(buffered) = env::var("RALPH_BUFFERED_OUTPUT") {
+            config.buffered_output = buffered.to_lowercase() == "true";
+        }
+
+        if let Ok(event_log) = env::var("RALPH_EVENT_LOG") {
+            config.event_log = Some(event_log).filter(|path| !path.is_empty());
+        }
+
+        if let Ok(human) = env::var("RALPH_HUMAN_OUTPUT") {
+            config.human_output = human.to_lowercase() == "true";
+        }
+
+        if let Ok(advice) = env::var("RALPH_DEPENDENCY_ADVICE") {
+            config.dependency_advice = advice.to_lowercase() == "true";
+        }
+
+        if let Ok(check) = env::var("RALPH_SCAFFOLD_CHECK") {
+            config.scaffold_check = check.to_lowercase() == "true";
+        }
+
+        if let Ok(check) = env::var("RALPH_GLOSSARY_CHECK") {
+            config.glossary_check = check.to_lowercase() == "true";
+        }
+
+        if let Ok(trim) = env::var("RALPH_TRIM_ON_OVERFLOW") {
+            config.trim_on_overflow = trim.to_lowercase() == "true";
+        }
+
+        if let Ok(target) = env::var("RALPH_DATASET_EXPORT") {
+            if !target.trim().is_empty() {
+                config.dataset_export = Some(target.trim().to_string());
+            }
+        }
+
+        if let Ok(author) = env::var("RALPH_COMMIT_AUTHOR") {
+            if !author.trim().is_empty() {
+                config.commit_author = Some(author.trim().to_string());
+            }
+        }
+
+        if let Ok(branches) = env::var("RALPH_TASK_BRANCHES") {
+            config.task_branches = branches.to_lowercase() == "true";
+        }
+
+        if let Ok(merge) = env::var("RALPH_TASK_MERGE") {
+            if let Some(value) = noted(errors, merge.parse()) {
+                config.task_merge = value;
+            }
+        }
+
+        if let Ok(rollback) = env::var("RALPH_ROLLBACK_FAILED") {
+            config.rollback_failed = rollback.to_lowercase() == "true";
+        }
+
+        if let Ok(cleanup) = env::var("RALPH_CLEANUP_TASK") {
+            config.cleanup_task = cleanup.to_lowercase() == "true";
+        }
+
+        if let Ok(isolate) = env::var("RALPH_ISOLATE_TEST_ARTIFACTS") {
+            config.isolate_test_artifacts = isolate.to_lowercase() == "true";
+        }
+        if let Ok(isolate) = env::var("RALPH_ISOLATE_TARGET_DIR") {
+            config.isolate_target_dir = isolate.to_lowercase() == "true";
+        }
+
+        if let Ok(smoke) = env::var("RALPH_SMOKE_GATE") {
+            if let Some(value) = noted(errors, crate::smoke::parse_languages(&smoke).map_err(|e| {
+                ValidationError::new("smoke_gate", e)
+                    .with_suggestion("Use off, all, or a list such as rust,python (rust, typescript, javascript, python, go)")
+            })) {
+                config.smoke_gate = value;
+            }
+        }
+
+        if let Ok(expand) = env::var("RALPH_EXPAND_WARNINGS") {
+            config.expand_warnings = expand.to_lowercase() == "true";
+        }
+
+        if let Ok(strict) = env::var("RALPH_VERSION_STRICT") {
+            config.version_strict = strict.to_lowercase() == "true";
+        }
+
+        if let Ok(check) = env::var("RALPH_UPDATE_CHECK") {
+            config.update_check = check.to_lowercase() == "true";
+        }
+
+        if let Ok(guard) = env::var("RALPH_EDIT_GUARD") {
+            if let Some(value) = noted(errors, crate::edit_guard::parse_languages(&guard).map_err(|e| {
+                ValidationError::new("edit_guard_languages", e)
+                    .with_suggestion("Use off, all, or a list such as rust,python (rust, typescript, javascript, python, go)")
+            })) {
+                config.edit_guard_languages = value;
+            }
+        }
+
+        if let Ok(lines) = env::var("RALPH_EDIT_GUARD_LINES") {
+            if let Some(value) = noted(errors, lines.parse().map_err(|e| {
+                ValidationError::new(
+                    "edit_guard_lines",
+                    format!("Invalid RALPH_EDIT_GUARD_LINES '{}': {}", lines, e),
+                )
+                .with_suggestion("Use a line count like 300, or 0 to disable the check")
+            })) {
+                config.edit_guard_lines = value;
+            }
+        }
+
+        if let Ok(ratio) = env::var("RALPH_EDIT_GUARD_REWRITE_RATIO") {
+            if let Some(value) = noted(errors, ratio.parse().map_err(|e| {
+                ValidationError::new(
+                    "edit_guard_rewrite_ratio",
+                    format!("Invalid RALPH_EDIT_GUARD_REWRITE_RATIO '{}': {}", ratio, e),
+                )
+                .with_suggestion("Use a share between 0 and 1, like 0.5")
+            })) {
+                config.edit_guard_rewrite_ratio = value;
+            }
+        }
+
+        if let Ok(extensions) = env::var("RALPH_LANGUAGE_EXTENSIONS") {
+            if let Some(value) = noted(errors, crate::language::parse_extension_overrides(&extensions).map_err(|e| {
+                ValidationError::new("language_extensions", format!("Invalid RALPH_LANGUAGE_EXTENSIONS: {}", e))
+                    .with_suggestion("Use ext=language pairs such as pyx=python,kt=none (rust, typescript, javascript, python, go, java)")
+            })) {
+                config.language_extensions = value;
+            }
+        }
+
+        if let Ok(cost) = env::var("RALPH_COST_PER_ITERATION") {
+            if let Some(value) = noted(errors, cost.parse().map_err(|e| {
+                ValidationError::new(
+                    "cost_per_iteration",
+                    format!("Invalid RALPH_COST_PER_ITERATION '{}': {}", cost, e),
+                )
+                .with_suggestion("Use an amount like 0.35, or unset it")
+            })) {
+                config.cost_per_iteration = Some(value);
+            }
+        }
+
+        if let Ok(offline) = env::var("RALPH_OFFLINE") {
+            config.offline = offline.to_lowercase() == "true";
+        }
+
+        if let Ok(depth) = env::var("RALPH_STRUCTURE_MAX_DEPTH") {
+            if let Some(value) = noted(errors, depth.parse().map_err(|e| {
+                ValidationError::new(
+                    "structure_max_depth",
+                    format!("Invalid RALPH_STRUCTURE_MAX_DEPTH '{}': {}", depth, e),
+                )
+                .with_suggestion("Use a positive integer like 4")
+            })) {
+                config.structure_max_depth = value;
+            }
+        }
+
+        if let Ok(fan_out) = env::var("RALPH_STRUCTURE_MIN_FAN_OUT") {
+            if let Some(value) = noted(errors, fan_out.parse().map_err(|e| {
+                ValidationError::new(
+                    "structure_min_fan_out",
+                    format!("Invalid RALPH_STRUCTURE_MIN_FAN_OUT '{}': {}", fan_out, e),
+                )
+                .with_suggestion("Use a number like 1.5, or 0 to disable the check")
+            })) {
+                config.structure_min_fan_out = value;
+            }
+        }
+
+        if let Ok(strict) = env::var("RALPH_STRUCTURE_STRICT") {
+            config.structure_strict = strict.to_lowercase() == "true";
+        }
+
+        if let Ok(strict) = env::var("RALPH_COVERAGE_STRICT") {
+            config.coverage_strict = strict.to_lowercase() == "true";
+        }
+
+        if let Ok(allow) = env::var("RALPH_ALLOW_EMPTY_PLAN") {
+            config.allow_empty_plan = allow.to_lowercase() == "true";
+        }
+
+        if let Ok(max) = env::var("RALPH_MAX_FILES_PER_TASK") {
+            if let Some(value) = noted(errors, max.parse().map_err(|e| {
+                ValidationError::new(
+                    "max_files_per_task",
+                    format!("Invalid RALPH_MAX_FILES_PER_TASK '{}': {}", max, e),
+                )
+                .with_suggestion("Use a positive integer like 10, or unset it for no cap")
+            })) {
+                config.max_files_per_task = Some(value);
+            }
+        }
+
+        for (var, name, field) in [
+            ("RALPH_INTER_TASK_DELAY_MS", "inter_task_delay", &mut config.inter_task_delay),
+            ("RALPH_INTER_TASK_JITTER_MS", "inter_task_jitter", &mut config.inter_task_jitter),
+        ] {
+            if let Ok(ms) = env::var(var) {
+                let ms = ms.parse::<u64>().map_err(|e| {
+                    ValidationError::new(name, format!("Invalid {} '{}': {}", var, ms, e))
+                    .with_suggestion("Use milliseconds like 2000, or unset it for no pause")
+                });
+                if let Some(ms) = noted(errors, ms) {
+                    *field = Some(Duration::from_millis(ms));
+                }
+            }
+        }
+
+        // vars_os: one non-UTF-8 variable elsewhere must not abort startup
+        for (key, value) in env::vars_os() {
+            let (Some(key), Some(value)) = (key.to_str(), value.to_str()) else {
+                continue;
+            };
+            if let Some(name) = key.strip_prefix(EXEC_ENV_PREFIX) {
+                config.execution_env.insert(name.to_string(), value.to_string());
+            }
         }
 
         // Load debug level
         if let Ok(level) = env::var("RALPH_DEBUG_LEVEL") {
-            config.debug_level = level.parse()?;
+            if let Some(value) = noted(errors, level.parse()) {
+                config.debug_level = value;
+            }
         }
 
-        config.validate()?;
-        Ok(config)
+        config
     }
 
     /// Validate the configuration settings.
@@ -698,7 +2160,11 @@ impl RalphConfig {
         // Validate nested configs
         self.agents.validate()?;
         self.telemetry.validate()?;
+        self.validate_settings()
+    }
 
+    /// Validate the run settings, leaving out the agent models and telemetry.
+    pub fn validate_settings(&self) -> Result<(), ValidationError> {
         // Validate max_iterations
         if self.max_iterations == 0 {
             return Err(ValidationError::new(
@@ -739,10 +2205,123 @@ impl RalphConfig {
                     self.max_task_retries, MAX_RETRIES_LIMIT
                 ),
             )
-            .with_suggestion(format!(
-                "Use a value between 1 and {}",
-                MAX_RETRIES_LIMIT
-            )));
+            .with_suggestion(format!(
+                "Use a value between 1 and {}",
+                MAX_RETRIES_LIMIT
+            )));
+        }
+
+        // Validate flaky test handling
+        if self.flaky_test_retries as usize > MAX_RETRIES_LIMIT {
+            return Err(ValidationError::new(
+                "flaky_test_retries",
+                format!(
+                    "Flaky test retries {} exceeds limit of {}",
+                    self.flaky_test_retries, MAX_RETRIES_LIMIT
+                ),
+            )
+            .with_suggestion(format!(
+                "Use a value between 0 and {}",
+                MAX_RETRIES_LIMIT
+            )));
+        }
+
+        if self.flaky_confirm_runs == 0 {
+            return Err(ValidationError::new(
+                "flaky_confirm_runs",
+                "Flaky confirm runs must be greater than 0",
+            )
+            .with_suggestion("Set RALPH_FLAKY_CONFIRM_RUNS to at least 1 (default: 1)"));
+        }
+
+        if self.structure_max_depth == 0 {
+            return Err(ValidationError::new(
+                "structure_max_depth",
+                "Structure max depth must be greater than 0",
+            )
+            .with_suggestion("Set RALPH_STRUCTURE_MAX_DEPTH to at least 1 (default: 4)"));
+        }
+
+        if !self.structure_min_fan_out.is_finite() || self.structure_min_fan_out < 0.0 {
+            return Err(ValidationError::new(
+                "structure_min_fan_out",
+                format!("Structure min fan-out {} is out of range", self.structure_min_fan_out),
+            )
+            .with_suggestion("Use a non-negative number like 1.5, or 0 to disable the check"));
+        }
+
+        if !(0.0..=1.0).contains(&self.edit_guard_rewrite_ratio) {
+            return Err(ValidationError::new(
+                "edit_guard_rewrite_ratio",
+                format!("Edit guard rewrite ratio {} is out of range", self.edit_guard_rewrite_ratio),
+            )
+            .with_suggestion("Use a share between 0 and 1, like 0.5"));
+        }
+
+        if let Some(cost) = self.cost_per_iteration.filter(|c| !c.is_finite() || *c < 0.0) {
+            return Err(ValidationError::new(
+                "cost_per_iteration",
+                format!("Cost per iteration {} must be a non-negative amount", cost),
+            )
+            .with_suggestion("Use an amount like 0.35, or unset it"));
+        }
+
+        if self.max_files_per_task == Some(0) {
+            return Err(ValidationError::new(
+                "max_files_per_task",
+                "Max files per task must be greater than 0",
+            )
+            .with_suggestion("Set RALPH_MAX_FILES_PER_TASK to at least 1, or unset it for no cap"));
+        }
+
+        for name in self.execution_env.keys() {
+            if let Err(message) = exec_env::validate_name(name) {
+                return Err(ValidationError::new("execution_env", message)
+                    .with_suggestion("Name variables like RALPH_EXEC_ENV_DATABASE_URL"));
+            }
+        }
+
+        if let Err(message) = self.commit_author() {
+            return Err(ValidationError::new("commit_author", message)
+                .with_suggestion("Use a name and email like 'ralph-bot <ralph@company.com>'"));
+        }
+
+        if self.concurrent_builds == BuildConcurrency::Fixed(0) {
+            return Err(ValidationError::new(
+                "concurrent_builds",
+                "Concurrent builds must be at least 1",
+            )
+            .with_suggestion("Use a number like 2, or auto to follow the CPU count"));
+        }
+
+        if self.tool_jobs == Some(0) {
+            return Err(ValidationError::new("tool_jobs", "Tool jobs must be at least 1")
+                .with_suggestion("Set RALPH_TOOL_JOBS to at least 1, or unset it"));
+        }
+
+        if self.max_rss_mb == Some(0) {
+            return Err(ValidationError::new("max_rss_mb", "Memory limit must be at least 1 MiB")
+                .with_suggestion("Set RALPH_MAX_RSS_MB to a size like 2048, or unset it"));
+        }
+
+        if self.max_cpu_secs == Some(0) {
+            return Err(ValidationError::new("max_cpu_secs", "CPU time limit must be at least 1 second")
+                .with_suggestion("Set RALPH_MAX_CPU_SECS to a number like 600, or unset it"));
+        }
+
+        // Commands are only measured and stopped through unix process groups
+        if !cfg!(unix) && !self.resource_limits().is_unlimited() {
+            let field = if self.max_rss_mb.is_some() { "max_rss_mb" } else { "max_cpu_secs" };
+            return Err(ValidationError::new(field, "Resource limits are only enforced on unix systems")
+                .with_suggestion("Unset RALPH_MAX_RSS_MB and RALPH_MAX_CPU_SECS on this platform"));
+        }
+
+        if self.verification_cadence == VerificationCadence::EveryNTasks(0) {
+            return Err(ValidationError::new(
+                "verification_cadence",
+                "Verification cadence must run at least every 1 task",
+            )
+            .with_suggestion("Use every:N with N >= 1, or 'off' to disable"));
         }
 
         // Validate paths
@@ -892,6 +2471,283 @@ impl RalphConfigBuilder {
         self
     }
 
+    /// Set how many extra test runs are allowed after a failing run.
+    pub fn flaky_test_retries(mut self, retries: u32) -> Self {
+        self.config.flaky_test_retries = retries;
+        self
+    }
+
+    /// Set how many consecutive green runs confirm a pass after a failure.
+    pub fn flaky_confirm_runs(mut self, runs: u32) -> Self {
+        self.config.flaky_confirm_runs = runs;
+        self
+    }
+
+    /// Set the full-suite verification cadence.
+    pub fn verification_cadence(mut self, cadence: VerificationCadence) -> Self {
+        self.config.verification_cadence = cadence;
+        self
+    }
+
+    /// Set how many builds the CPUs are split between for `tool_jobs`.
+    pub fn concurrent_builds(mut self, builds: BuildConcurrency) -> Self {
+        self.config.concurrent_builds = builds;
+        self
+    }
+
+    /// Set the order in which ready tasks are picked.
+    pub fn scheduling(mut self, strategy: SchedulingStrategy) -> Self {
+        self.config.scheduling = strategy;
+        self
+    }
+
+    /// Set the job count passed to build and test tools.
+    pub fn tool_jobs(mut self, jobs: usize) -> Self {
+        self.config.tool_jobs = Some(jobs);
+        self
+    }
+
+    /// Set the memory and CPU time limits for each build/test/run command.
+    pub fn resource_limits(mut self, limits: ResourceLimits) -> Self {
+        self.config.max_rss_mb = limits.max_rss_mb;
+        self.config.max_cpu_secs = limits.max_cpu_secs;
+        self
+    }
+
+    /// Enable syntax-colored tool JSON at verbose output levels.
+    pub fn color_json(mut self, enabled: bool) -> Self {
+        self.config.color_json = enabled;
+        self
+    }
+
+    /// Gate task completion on the acceptance-criteria judge.
+    pub fn judge_enabled(mut self, enabled: bool) -> Self {
+        self.config.judge_enabled = enabled;
+        self
+    }
+
+    /// Refuse task completion until the task's tests have passed.
+    pub fn verify_completion(mut self, verify: bool) -> Self {
+        self.config.verify_completion = verify;
+        self
+    }
+
+    /// Show a live overall bar and current-task line.
+    pub fn multi_progress(mut self, enabled: bool) -> Self {
+        self.config.multi_progress = enabled;
+        self
+    }
+
+    /// Set whether output is colored (overrides terminal detection).
+    pub fn color(mut self, setting: ConsoleSetting) -> Self {
+        self.config.color = setting;
+        self
+    }
+
+    /// Set whether Unicode symbols are used (overrides console detection).
+    pub fn unicode(mut self, setting: ConsoleSetting) -> Self {
+        self.config.unicode = setting;
+        self
+    }
+
+    /// Set the output color palette.
+    pub fn palette(mut self, preset: PalettePreset) -> Self {
+        self.config.palette = preset;
+        self
+    }
+
+    /// Batch terminal writes instead of flushing every line.
+    pub fn buffered_output(mut self, enabled: bool) -> Self {
+        self.config.buffered_output = enabled;
+        self
+    }
+
+    /// Write progress events as JSON lines to `path` (`-` for stdout).
+    pub fn event_log(mut self, path: impl Into<String>) -> Self {
+        self.config.event_log = Some(path.into());
+        self
+    }
+
+    /// Print human-readable output besides errors.
+    pub fn human_output(mut self, enabled: bool) -> Self {
+        self.config.human_output = enabled;
+        self
+    }
+
+    /// Check generated manifests for outdated dependencies.
+    pub fn dependency_advice(mut self, enabled: bool) -> Self {
+        self.config.dependency_advice = enabled;
+        self
+    }
+
+    /// Build the project right after its setup task and stop the run if it fails.
+    pub fn scaffold_check(mut self, enabled: bool) -> Self {
+        self.config.scaffold_check = enabled;
+        self
+    }
+
+    /// Check each completed task's public identifiers against the project glossary.
+    pub fn glossary_check(mut self, enabled: bool) -> Self {
+        self.config.glossary_check = enabled;
+        self
+    }
+
+    /// Retry a model call once with a trimmed conversation when it overflows the context window.
+    pub fn trim_on_overflow(mut self, enabled: bool) -> Self {
+        self.config.trim_on_overflow = enabled;
+        self
+    }
+
+    /// Skip all package registry requests.
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.config.offline = offline;
+        self
+    }
+
+    /// Set the deepest file path the architect may propose.
+    pub fn structure_max_depth(mut self, depth: usize) -> Self {
+        self.config.structure_max_depth = depth;
+        self
+    }
+
+    /// Set the lowest average entries per directory in the proposed file tree.
+    pub fn structure_min_fan_out(mut self, fan_out: f32) -> Self {
+        self.config.structure_min_fan_out = fan_out;
+        self
+    }
+
+    /// Re-ask the architect instead of flattening a file tree that breaks the limits.
+    pub fn structure_strict(mut self, strict: bool) -> Self {
+        self.config.structure_strict = strict;
+        self
+    }
+
+    /// Fail design generation when a component has no task creating its file.
+    pub fn coverage_strict(mut self, strict: bool) -> Self {
+        self.config.coverage_strict = strict;
+        self
+    }
+
+    /// Accept an architect plan with no tasks, with a warning.
+    pub fn allow_empty_plan(mut self, allow: bool) -> Self {
+        self.config.allow_empty_plan = allow;
+        self
+    }
+
+    /// Cap the files one task may write or delete.
+    pub fn max_files_per_task(mut self, max: usize) -> Self {
+        self.config.max_files_per_task = Some(max);
+        self
+    }
+
+    /// Pause before starting each task after the first, plus up to `jitter` at random.
+    pub fn inter_task_delay(mut self, delay: Duration, jitter: Option<Duration>) -> Self {
+        self.config.inter_task_delay = Some(delay);
+        self.config.inter_task_jitter = jitter;
+        self
+    }
+
+    /// Set a variable for the generated project's build/test/run commands.
+    pub fn execution_env(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.config.execution_env.insert(name.into(), value.into());
+        self
+    }
+
+    /// Export completed tasks as a fine-tuning dataset shard to a directory or bucket URL.
+    pub fn dataset_export(mut self, target: impl Into<String>) -> Self {
+        self.config.dataset_export = Some(target.into());
+        self
+    }
+
+    /// Attribute the loop's commits to `author` (`Name <email>`).
+    pub fn commit_author(mut self, author: impl Into<String>) -> Self {
+        self.config.commit_author = Some(author.into());
+        self
+    }
+
+    /// Implement each task on its own branch and merge it back with `merge`.
+    pub fn task_branches(mut self, enabled: bool, merge: MergeStrategy) -> Self {
+        self.config.task_branches = enabled;
+        self.config.task_merge = merge;
+        self
+    }
+
+    /// Roll the project back to where a task started when it is blocked.
+    pub fn rollback_failed(mut self, enabled: bool) -> Self {
+        self.config.rollback_failed = enabled;
+        self
+    }
+
+    /// Queue a low-priority task for the leftovers the end-of-run cleanup check finds.
+    pub fn cleanup_task(mut self, enabled: bool) -> Self {
+        self.config.cleanup_task = enabled;
+        self
+    }
+
+    /// Run each task's test/run commands with their own temp directory,
+    /// removed when the task ends.
+    pub fn isolate_test_artifacts(mut self, enabled: bool) -> Self {
+        self.config.isolate_test_artifacts = enabled;
+        self
+    }
+
+    /// With `isolate_test_artifacts`, give each task its own build directory
+    /// too instead of sharing the build cache.
+    pub fn isolate_target_dir(mut self, enabled: bool) -> Self {
+        self.config.isolate_target_dir = enabled;
+        self
+    }
+
+    /// Syntax-check source files of `languages` right after each write.
+    pub fn smoke_gate(mut self, languages: Vec<Language>) -> Self {
+        self.config.smoke_gate = languages;
+        self
+    }
+
+    /// List every warning in the end-of-run warnings section, not just the counts.
+    pub fn expand_warnings(mut self, enabled: bool) -> Self {
+        self.config.expand_warnings = enabled;
+        self
+    }
+
+    /// Refuse to run a project pinned to a different minor/major ralph version.
+    pub fn version_strict(mut self, strict: bool) -> Self {
+        self.config.version_strict = strict;
+        self
+    }
+
+    /// Mention a newer ralph release on crates.io, at most once a day.
+    pub fn update_check(mut self, enabled: bool) -> Self {
+        self.config.update_check = enabled;
+        self
+    }
+
+    /// Refuse rewrites of existing files from `min_lines` lines (0 = off)
+    /// unless they change more than `rewrite_ratio` of the file.
+    pub fn edit_guard(mut self, min_lines: usize, rewrite_ratio: f64) -> Self {
+        self.config.edit_guard_lines = min_lines;
+        self.config.edit_guard_rewrite_ratio = rewrite_ratio;
+        self
+    }
+
+    /// Languages the rewrite refusal applies to.
+    pub fn edit_guard_languages(mut self, languages: Vec<Language>) -> Self {
+        self.config.edit_guard_languages = languages;
+        self
+    }
+
+    /// Set the model spend per loop iteration used by `ralph scope`.
+    pub fn cost_per_iteration(mut self, cost: f64) -> Self {
+        self.config.cost_per_iteration = Some(cost);
+        self
+    }
+
+    /// Map the file extension `ext` to `language` (`Unknown` removes it).
+    pub fn language_extension(mut self, ext: &str, language: Language) -> Self {
+        self.config.language_extensions.insert(ext.trim_start_matches('.').to_lowercase(), language);
+        self
+    }
+
     /// Set the debug/output verbosity level.
     pub fn debug_level(mut self, level: DebugLevel) -> Self {
         self.config.debug_level = level;
@@ -920,15 +2776,142 @@ mod tests {
         assert!(valid.validate().is_ok());
 
         let invalid_provider = ModelConfig::new("invalid", "model");
+        assert_eq!(invalid_provider.provider, Provider::Unknown("invalid".to_string()));
         let err = invalid_provider.validate().unwrap_err();
         assert_eq!(err.field, "provider");
         assert!(err.suggestion.is_some());
 
+        let aliased = ModelConfig::new("Claude", "claude-sonnet-4-20250514");
+        assert_eq!(aliased, ModelConfig::for_provider(Provider::Anthropic, "claude-sonnet-4-20250514"));
+        assert!(aliased.validate().is_ok());
+
         let empty_model = ModelConfig::new("anthropic", "");
         let err = empty_model.validate().unwrap_err();
         assert_eq!(err.field, "model_name");
     }
 
+    #[test]
+    fn test_model_config_try_new() {
+        let config = ModelConfig::try_new("OpenAI", "gpt-4o").unwrap();
+        assert_eq!(config, ModelConfig::new("OpenAI", "gpt-4o"));
+
+        match ModelConfig::try_new("antropic", "claude-sonnet-4-20250514") {
+            Err(crate::RalphError::Configuration(message)) => {
+                assert!(message.contains("antropic"));
+                assert!(message.contains("Did you mean 'anthropic'?"), "{}", message);
+                for provider in SUPPORTED_PROVIDERS {
+                    assert!(message.contains(provider), "missing {} in {}", provider, message);
+                }
+            }
+            other => panic!("expected a configuration error, got {:?}", other),
+        }
+
+        assert!(matches!(
+            ModelConfig::try_new("gemini", "  "),
+            Err(crate::RalphError::Configuration(_))
+        ));
+    }
+
+    #[test]
+    fn test_model_config_api_key_precedence() {
+        assert_eq!(default_api_key_envs(&Provider::Gemini), &["GEMINI_API_KEY", "GOOGLE_API_KEY"]);
+        assert!(default_api_key_envs(&Provider::Ollama).is_empty());
+
+        // A direct key wins over any environment variable
+        let config = ModelConfig::new("gemini", "gemini-2.5-flash")
+            .with_api_key_env("PATH")
+            .with_api_key("direct-key");
+        assert_eq!(config.resolve_api_key().unwrap(), "direct-key");
+        assert!(!format!("{:?}", config).contains("direct-key"));
+        assert!(!serde_json::to_string(&config).unwrap().contains("direct-key"));
+
+        // The override reads exactly the named variable
+        let config = ModelConfig::new("gemini", "gemini-2.5-flash").with_api_key_env("PATH");
+        assert_eq!(config.resolve_api_key().unwrap(), env::var("PATH").unwrap());
+
+        // ...and never falls back to the provider defaults
+        let config = ModelConfig::new("openai", "gpt-4o")
+            .with_api_key_env("RALPH_TEST_UNSET_API_KEY_VAR");
+        match config.resolve_api_key() {
+            Err(crate::RalphError::Configuration(message)) => {
+                assert!(message.contains("RALPH_TEST_UNSET_API_KEY_VAR"));
+            }
+            other => panic!("expected a configuration error, got {:?}", other),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_api_key_from_command_and_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("gemini.key");
+        std::fs::write(&file, "file-key-1234\n").unwrap();
+
+        // Command beats file, file beats the environment
+        let config = ModelConfig::new("gemini", "gemini-2.5-flash")
+            .with_api_key_env("PATH")
+            .with_api_key_file(&file)
+            .with_api_key_command("printf '  command-key-5678\\n'");
+        assert_eq!(config.resolve_api_key().unwrap(), "command-key-5678");
+        assert_eq!(config.clone().with_api_key("direct").resolve_api_key().unwrap(), "direct");
+        let config = ModelConfig { api_key_command: None, ..config };
+        assert_eq!(config.resolve_api_key().unwrap(), "file-key-1234");
+        assert!(resolved_secrets().contains(&"command-key-5678".to_string()));
+        assert!(resolved_secrets().contains(&"file-key-1234".to_string()));
+        assert!(!format!("{:?}", config).contains("file-key-1234"));
+
+        // Failures are configuration errors that never fall back or echo stdout
+        let failing = |command: &str| {
+            ModelConfig::new("gemini", "gemini-2.5-flash")
+                .with_api_key_env("PATH")
+                .with_api_key_command(command)
+                .resolve_api_key()
+                .unwrap_err()
+        };
+        let error = failing("echo leaked-key; echo 'vault: permission denied' >&2; exit 2");
+        assert!(matches!(error, crate::RalphError::Configuration(_)));
+        assert!(error.to_string().contains("vault: permission denied"), "{}", error);
+        assert!(!error.to_string().contains("leaked-key"), "{}", error);
+        assert!(failing("true").to_string().contains("printed nothing"));
+
+        let started = std::time::Instant::now();
+        let error = run_secret_command("sleep 5", Duration::from_millis(100)).unwrap_err();
+        assert!(error.to_string().contains("timed out"), "{}", error);
+        assert!(started.elapsed() < Duration::from_secs(4));
+
+        let missing = ModelConfig::default().with_api_key_file(dir.path().join("missing.key"));
+        assert!(missing.resolve_api_key().unwrap_err().to_string().contains("missing.key"));
+        let blank = dir.path().join("blank.key");
+        std::fs::write(&blank, " \n").unwrap();
+        let empty = ModelConfig::default().with_api_key_file(&blank);
+        assert!(empty.resolve_api_key().unwrap_err().to_string().contains("is empty"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_api_key_command_runs_once_per_process() {
+        let dir = tempfile::tempdir().unwrap();
+        let runs = dir.path().join("runs");
+        let command = format!("echo run >> '{}'; echo once-key-4242", runs.display());
+
+        // Every agent sharing the command gets the key from the first run
+        for provider in ["gemini", "anthropic", "openai"] {
+            let config = ModelConfig::new(provider, "model").with_api_key_command(&command);
+            assert_eq!(config.resolve_api_key().unwrap(), "once-key-4242");
+        }
+        assert_eq!(std::fs::read_to_string(&runs).unwrap().lines().count(), 1);
+        assert_eq!(resolved_secrets().iter().filter(|k| *k == "once-key-4242").count(), 1);
+    }
+
+    #[test]
+    fn test_model_call_timeout() {
+        assert_eq!(ModelConfig::default().model_call_timeout(), Some(Duration::from_secs(600)));
+        let config = ModelConfig::default().with_model_call_timeout(Duration::from_secs(90));
+        assert_eq!(config.model_call_timeout(), Some(Duration::from_secs(90)));
+        let unlimited = ModelConfig::default().with_model_call_timeout(Duration::ZERO);
+        assert_eq!(unlimited.model_call_timeout(), None);
+    }
+
     #[test]
     fn test_model_config_max_tokens_validation() {
         let mut config = ModelConfig::default();
@@ -1033,6 +3016,256 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_ralph_config_flaky_test_validation() {
+        let config = RalphConfig::default();
+        assert_eq!(config.flaky_test_retries, 0);
+        assert_eq!(config.flaky_confirm_runs, 1);
+
+        let result = RalphConfig::builder().flaky_confirm_runs(0).build();
+        assert_eq!(result.unwrap_err().field, "flaky_confirm_runs");
+
+        let result = RalphConfig::builder()
+            .flaky_test_retries(MAX_RETRIES_LIMIT as u32 + 1)
+            .build();
+        assert_eq!(result.unwrap_err().field, "flaky_test_retries");
+
+        let config = RalphConfig::builder()
+            .flaky_test_retries(2)
+            .flaky_confirm_runs(2)
+            .build()
+            .unwrap();
+        assert_eq!(config.flaky_test_retries, 2);
+        assert_eq!(config.flaky_confirm_runs, 2);
+    }
+
+    #[test]
+    fn test_verification_cadence_parsing() {
+        assert_eq!("off".parse::<VerificationCadence>().unwrap(), VerificationCadence::Off);
+        assert_eq!(
+            "phase".parse::<VerificationCadence>().unwrap(),
+            VerificationCadence::PhaseBoundaries
+        );
+        assert_eq!(
+            "every:5".parse::<VerificationCadence>().unwrap(),
+            VerificationCadence::EveryNTasks(5)
+        );
+        assert_eq!("3".parse::<VerificationCadence>().unwrap(), VerificationCadence::EveryNTasks(3));
+        assert!("every:0".parse::<VerificationCadence>().is_err());
+        assert!("sometimes".parse::<VerificationCadence>().is_err());
+        assert_eq!(VerificationCadence::EveryNTasks(4).to_string(), "every:4");
+        assert_eq!(RalphConfig::default().verification_cadence, VerificationCadence::PhaseBoundaries);
+
+        let result = RalphConfig::builder()
+            .verification_cadence(VerificationCadence::EveryNTasks(0))
+            .build();
+        assert_eq!(result.unwrap_err().field, "verification_cadence");
+    }
+
+    #[test]
+    fn test_build_concurrency() {
+        assert_eq!("auto".parse::<BuildConcurrency>().unwrap(), BuildConcurrency::Auto(1.0));
+        assert_eq!("auto:0.5".parse::<BuildConcurrency>().unwrap(), BuildConcurrency::Auto(0.5));
+        assert_eq!("3".parse::<BuildConcurrency>().unwrap(), BuildConcurrency::Fixed(3));
+        for bad in ["0", "auto:0", "auto:1.5", "many"] {
+            assert!(bad.parse::<BuildConcurrency>().is_err(), "{}", bad);
+        }
+        assert_eq!(BuildConcurrency::Auto(0.5).to_string(), "auto:0.5");
+
+        assert_eq!(BuildConcurrency::Auto(1.0).resolve(8), 8);
+        assert_eq!(BuildConcurrency::Auto(0.5).resolve(8), 4);
+        assert_eq!(BuildConcurrency::Auto(0.5).resolve(1), 1);
+        assert_eq!(BuildConcurrency::Auto(1.0).resolve(64), MAX_CONCURRENT_BUILDS_LIMIT);
+        assert_eq!(BuildConcurrency::Fixed(12).resolve(4), 4);
+
+        let config = RalphConfig::builder()
+            .concurrent_builds(BuildConcurrency::Fixed(4))
+            .build()
+            .unwrap();
+        assert_eq!(config.tool_jobs_for(16), Some(4));
+        assert_eq!(config.tool_jobs_for(2), Some(1));
+        assert_eq!(RalphConfig::default().tool_jobs_for(16), None);
+        let pinned = RalphConfig::builder().tool_jobs(3).build().unwrap();
+        assert_eq!(pinned.tool_jobs_for(16), Some(3));
+        assert_eq!(RalphConfig::builder().tool_jobs(0).build().unwrap_err().field, "tool_jobs");
+    }
+
+    #[test]
+    fn test_resource_limits_setting() {
+        assert!(RalphConfig::default().resource_limits().is_unlimited());
+        let limits = ResourceLimits { max_rss_mb: Some(2048), max_cpu_secs: Some(600) };
+        let built = RalphConfig::builder().resource_limits(limits).build();
+        if cfg!(unix) {
+            assert_eq!(built.unwrap().resource_limits(), limits);
+        } else {
+            // Not enforceable there, so refused rather than ignored
+            assert_eq!(built.unwrap_err().field, "max_rss_mb");
+        }
+
+        let no_memory = ResourceLimits { max_rss_mb: Some(0), max_cpu_secs: None };
+        let err = RalphConfig::builder().resource_limits(no_memory).build().unwrap_err();
+        assert_eq!(err.field, "max_rss_mb");
+    }
+
+    #[test]
+    fn test_language_extensions_setting() {
+        assert_eq!(RalphConfig::default().extension_map(), ExtensionMap::default());
+        let config = RalphConfig::builder()
+            .language_extension(".PYX", Language::Python)
+            .language_extension("rs", Language::Unknown)
+            .build()
+            .unwrap();
+        let map = config.extension_map();
+        assert_eq!(map.language_of("fast.pyx"), Some(Language::Python));
+        assert_eq!(map.language_of("src/lib.rs"), None);
+    }
+
+    #[test]
+    fn test_ollama_base_url() {
+        let remote = ModelConfig::new("ollama", "qwen2.5-coder").with_base_url("http://gpu-box:11434/");
+        assert_eq!(remote.ollama_host(), "http://gpu-box:11434");
+        assert!(remote.validate().is_ok());
+
+        let err = ModelConfig::new("ollama", "llama3.2").with_base_url("gpu-box:11434").validate().unwrap_err();
+        assert_eq!(err.field, "base_url");
+
+        // Not written when unset, read back when set
+        let json = serde_json::to_value(ModelConfig::new("ollama", "llama3.2")).unwrap();
+        assert!(json.get("base_url").is_none());
+        let parsed: ModelConfig = serde_json::from_value(serde_json::to_value(&remote).unwrap()).unwrap();
+        assert_eq!(parsed.base_url.as_deref(), Some("http://gpu-box:11434/"));
+    }
+
+    #[test]
+    fn test_cost_per_iteration_setting() {
+        assert_eq!(RalphConfig::default().cost_per_iteration, None);
+        let config = RalphConfig::builder().cost_per_iteration(0.35).build().unwrap();
+        assert_eq!(config.cost_per_iteration, Some(0.35));
+        let err = RalphConfig::builder().cost_per_iteration(-1.0).build().unwrap_err();
+        assert_eq!(err.field, "cost_per_iteration");
+    }
+
+    #[test]
+    fn test_smoke_gate_setting() {
+        assert!(RalphConfig::default().smoke_gate.is_empty());
+        let config = RalphConfig::builder()
+            .smoke_gate(vec![Language::Rust, Language::Python])
+            .build()
+            .unwrap();
+        assert_eq!(config.smoke_gate, vec![Language::Rust, Language::Python]);
+    }
+
+    #[test]
+    fn test_edit_guard_settings() {
+        let guard = RalphConfig::default().edit_guard();
+        assert_eq!(guard, crate::edit_guard::EditGuard::default());
+        assert!(guard.is_enabled());
+
+        let config = RalphConfig::builder()
+            .edit_guard(500, 0.7)
+            .edit_guard_languages(vec![Language::Go])
+            .build()
+            .unwrap();
+        let guard = config.edit_guard();
+        assert_eq!((guard.min_lines, guard.rewrite_ratio), (500, 0.7));
+        assert_eq!(guard.languages, vec![Language::Go]);
+
+        assert!(RalphConfig::builder().edit_guard(300, 1.5).build().is_err());
+    }
+
+    #[test]
+    fn test_commit_author() {
+        assert_eq!(RalphConfig::default().commit_author(), Ok(None));
+        let config = RalphConfig::builder()
+            .commit_author("ralph-bot <ralph@company.com>")
+            .build()
+            .unwrap();
+        assert_eq!(
+            config.commit_author().unwrap(),
+            Some(CommitAuthor::new("ralph-bot", "ralph@company.com"))
+        );
+        let result = RalphConfig::builder().commit_author("ralph-bot").build();
+        assert_eq!(result.unwrap_err().field, "commit_author");
+    }
+
+    #[test]
+    fn test_execution_env_names_are_validated() {
+        let config = RalphConfig::builder()
+            .execution_env("DATABASE_URL", "sqlite://test.db")
+            .build()
+            .unwrap();
+        assert_eq!(config.execution_env["DATABASE_URL"], "sqlite://test.db");
+
+        let result = RalphConfig::builder().execution_env("BAD-NAME", "x").build();
+        assert_eq!(result.unwrap_err().field, "execution_env");
+    }
+
+    #[test]
+    fn test_console_setting_parsing() {
+        assert_eq!("auto".parse::<ConsoleSetting>().unwrap(), ConsoleSetting::Auto);
+        assert_eq!("Always".parse::<ConsoleSetting>().unwrap(), ConsoleSetting::Always);
+        assert_eq!("off".parse::<ConsoleSetting>().unwrap(), ConsoleSetting::Never);
+        assert!("sometimes".parse::<ConsoleSetting>().is_err());
+        assert_eq!(ConsoleSetting::Never.to_string(), "never");
+
+        let config = RalphConfig::default();
+        assert_eq!((config.color, config.unicode), (ConsoleSetting::Auto, ConsoleSetting::Auto));
+        let config = RalphConfig::builder().unicode(ConsoleSetting::Never).build().unwrap();
+        assert_eq!(config.unicode, ConsoleSetting::Never);
+    }
+
+    #[test]
+    fn test_merge_strategy_parsing() {
+        assert_eq!("fast-forward".parse::<MergeStrategy>().unwrap(), MergeStrategy::FastForward);
+        assert_eq!("SQUASH".parse::<MergeStrategy>().unwrap(), MergeStrategy::Squash);
+        assert_eq!("rebase".parse::<MergeStrategy>().unwrap_err().field, "task_merge");
+        assert_eq!(MergeStrategy::Squash.to_string(), "squash");
+
+        let config = RalphConfig::default();
+        assert!(!config.task_branches);
+        assert_eq!(config.task_merge, MergeStrategy::FastForward);
+        let config = RalphConfig::builder()
+            .task_branches(true, MergeStrategy::Squash)
+            .build()
+            .unwrap();
+        assert!(config.task_branches);
+        assert_eq!(config.task_merge, MergeStrategy::Squash);
+        assert!(!config.rollback_failed);
+        assert!(RalphConfig::builder().rollback_failed(true).build().unwrap().rollback_failed);
+    }
+
+    #[test]
+    fn test_scheduling_strategy_parsing() {
+        assert_eq!("critical-path".parse::<SchedulingStrategy>().unwrap(), SchedulingStrategy::CriticalPathFirst);
+        assert_eq!("Shortest_First".parse::<SchedulingStrategy>().unwrap(), SchedulingStrategy::ShortestFirst);
+        assert_eq!("priority".parse::<SchedulingStrategy>().unwrap(), SchedulingStrategy::PriorityFirst);
+        let err = "random".parse::<SchedulingStrategy>().unwrap_err();
+        assert_eq!(err.field, "scheduling");
+        assert_eq!(SchedulingStrategy::CriticalPathFirst.to_string(), "critical_path");
+
+        assert_eq!(RalphConfig::default().scheduling, SchedulingStrategy::PriorityFirst);
+        let config = RalphConfig::builder()
+            .scheduling(SchedulingStrategy::ShortestFirst)
+            .build()
+            .unwrap();
+        assert_eq!(config.scheduling, SchedulingStrategy::ShortestFirst);
+    }
+
+    #[test]
+    fn test_palette_preset_parsing() {
+        assert_eq!("Colorblind".parse::<PalettePreset>().unwrap(), PalettePreset::Colorblind);
+        assert_eq!("monochrome".parse::<PalettePreset>().unwrap(), PalettePreset::Mono);
+        assert_eq!(" light ".parse::<PalettePreset>().unwrap(), PalettePreset::Light);
+        let err = "solarized".parse::<PalettePreset>().unwrap_err();
+        assert_eq!(err.field, "palette");
+        assert!(err.suggestion.unwrap().contains("colorblind"));
+        assert_eq!(PalettePreset::Mono.to_string(), "mono");
+
+        assert_eq!(RalphConfig::default().palette, PalettePreset::Default);
+        let config = RalphConfig::builder().palette(PalettePreset::Light).build().unwrap();
+        assert_eq!(config.palette, PalettePreset::Light);
+    }
+
     #[test]
     fn test_ralph_config_path_validation() {
         // Empty paths
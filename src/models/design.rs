@@ -3,10 +3,15 @@
 //! This module provides data models for system architecture and design documents,
 //! including component diagrams, interfaces, and file structure definitions.
 
+use crate::models::TaskList;
+use crate::glossary::{Glossary, GlossaryTerm};
+use crate::provenance::{self, Provenance};
+use crate::schema;
+use crate::scope::normalize_path;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// A component in the system architecture.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -118,6 +123,385 @@ impl FileStructure {
 
         result
     }
+
+    /// Parse a tree rendered by [`FileStructure::to_tree`] back into a structure.
+    ///
+    /// The first line is the root; names ending in `/` are directories.
+    pub fn parse_tree(text: &str) -> Option<Self> {
+        let mut lines = text.lines().filter(|l| !l.trim().is_empty());
+        let root = lines.next()?.trim().trim_end_matches('/');
+        // Open directories from the root down to the latest entry
+        let mut stack = vec![FileStructure::directory(root, "")];
+        for line in lines {
+            let mut rest = line;
+            let mut depth = 1;
+            while let Some(r) = rest.strip_prefix("│   ").or_else(|| rest.strip_prefix("    ")) {
+                rest = r;
+                depth += 1;
+            }
+            let Some(name) = rest.strip_prefix("├── ").or_else(|| rest.strip_prefix("└── "))
+            else {
+                continue;
+            };
+            while stack.len() > depth {
+                let done = stack.pop()?;
+                stack.last_mut()?.add_child(done);
+            }
+            let name = name.trim();
+            stack.push(match name.strip_suffix('/') {
+                Some(dir) => FileStructure::directory(dir, ""),
+                None => FileStructure::file(name, ""),
+            });
+        }
+        while stack.len() > 1 {
+            let done = stack.pop()?;
+            stack.last_mut()?.add_child(done);
+        }
+        stack.pop()
+    }
+
+    /// Paths of the files (not directories) relative to this root.
+    pub fn file_paths(&self) -> Vec<String> {
+        let mut paths = Vec::new();
+        for child in &self.children {
+            child.collect_file_paths("", &mut paths);
+        }
+        paths
+    }
+
+    fn collect_file_paths(&self, parent: &str, paths: &mut Vec<String>) {
+        let path = join_path(parent, &self.name);
+        if !self.is_directory {
+            paths.push(path);
+        }
+        for child in &self.children {
+            child.collect_file_paths(&path, paths);
+        }
+    }
+
+    /// Leaf paths (files and empty directories) relative to this root.
+    pub fn paths(&self) -> Vec<String> {
+        let mut paths = Vec::new();
+        for child in &self.children {
+            child.collect_paths("", &mut paths);
+        }
+        paths
+    }
+
+    fn collect_paths(&self, parent: &str, paths: &mut Vec<String>) {
+        let path = join_path(parent, &self.name);
+        if self.children.is_empty() {
+            paths.push(path);
+        } else {
+            for child in &self.children {
+                child.collect_paths(&path, paths);
+            }
+        }
+    }
+
+    /// Shape metrics of the tree below this root.
+    pub fn metrics(&self) -> StructureMetrics {
+        let mut metrics = StructureMetrics::default();
+        let mut fan_outs = vec![self.children.len()];
+        for child in &self.children {
+            child.collect_metrics("", 1, false, &mut metrics, &mut fan_outs);
+        }
+        let fan_outs: Vec<usize> = fan_outs.into_iter().filter(|n| *n > 0).collect();
+        if !fan_outs.is_empty() {
+            metrics.average_fan_out = fan_outs.iter().sum::<usize>() as f32 / fan_outs.len() as f32;
+        }
+        metrics.directories = fan_outs.len();
+        metrics
+    }
+
+    fn collect_metrics(
+        &self,
+        parent: &str,
+        depth: usize,
+        in_chain: bool,
+        metrics: &mut StructureMetrics,
+        fan_outs: &mut Vec<usize>,
+    ) {
+        let path = join_path(parent, &self.name);
+        metrics.max_depth = metrics.max_depth.max(depth);
+        if !self.is_directory {
+            return;
+        }
+        fan_outs.push(self.children.len());
+
+        // Report each maximal chain once, from its top directory
+        if self.is_single_child_dir() && !in_chain {
+            let mut chain = path.clone();
+            let mut node = self;
+            while node.is_single_child_dir() {
+                node = &node.children[0];
+                chain = join_path(&chain, &node.name);
+            }
+            metrics.single_child_chains.push(chain);
+        }
+
+        for child in &self.children {
+            child.collect_metrics(&path, depth + 1, self.is_single_child_dir(), metrics, fan_outs);
+        }
+    }
+
+    /// A directory whose only entry is another directory.
+    fn is_single_child_dir(&self) -> bool {
+        self.is_directory && self.children.len() == 1 && self.children[0].is_directory
+    }
+
+    /// Collapse chains of single-child directories below this root.
+    ///
+    /// A directory whose only entry is another directory takes over that
+    /// directory's contents, so `src/core/engine/mod.rs` becomes `src/mod.rs`
+    /// when `core` and `engine` hold nothing else. The root itself is never
+    /// collapsed. Returns `(from, to)` path prefixes for each collapsed chain;
+    /// see [`rename_path`].
+    pub fn flatten_single_child_chains(&mut self) -> Vec<(String, String)> {
+        let mut renames = Vec::new();
+        for child in &mut self.children {
+            child.flatten_into("", &mut renames);
+        }
+        renames
+    }
+
+    fn flatten_into(&mut self, parent: &str, renames: &mut Vec<(String, String)>) {
+        if !self.is_directory {
+            return;
+        }
+        let path = join_path(parent, &self.name);
+        let mut removed = Vec::new();
+        while self.is_single_child_dir() {
+            let only = self.children.remove(0);
+            removed.push(only.name);
+            self.children = only.children;
+        }
+        if !removed.is_empty() {
+            renames.push((format!("{}/{}", path, removed.join("/")), path.clone()));
+        }
+        for child in &mut self.children {
+            child.flatten_into(&path, renames);
+        }
+    }
+
+    /// Sort entries at every level: directories first, then by name.
+    pub fn sort_children(&mut self) {
+        self.children.sort_by(|a, b| {
+            b.is_directory.cmp(&a.is_directory).then_with(|| a.name.cmp(&b.name))
+        });
+        for child in &mut self.children {
+            child.sort_children();
+        }
+    }
+
+    /// Create the tree's directories and (empty) files below `root`.
+    ///
+    /// Entries are created in a stable order, directories first and then by
+    /// name at every level, whatever order the model listed them in, so two
+    /// runs of the same design scaffold identically. Existing files are left
+    /// untouched. Returns the paths created, in creation order.
+    pub fn materialize(&self, root: &Path) -> std::io::Result<Vec<PathBuf>> {
+        let mut sorted = self.clone();
+        sorted.sort_children();
+        let mut created = Vec::new();
+        for child in &sorted.children {
+            child.materialize_into(root, &mut created)?;
+        }
+        Ok(created)
+    }
+
+    fn materialize_into(&self, parent: &Path, created: &mut Vec<PathBuf>) -> std::io::Result<()> {
+        let path = parent.join(&self.name);
+        if self.is_directory {
+            if !path.is_dir() {
+                fs::create_dir_all(&path)?;
+                created.push(path.clone());
+            }
+            for child in &self.children {
+                child.materialize_into(&path, created)?;
+            }
+        } else if !path.exists() {
+            fs::write(&path, "")?;
+            created.push(path);
+        }
+        Ok(())
+    }
+}
+
+/// Lines of the `## <title>` section of a Markdown document, without the heading.
+fn markdown_section<'a>(content: &'a str, title: &str) -> Vec<&'a str> {
+    let heading = format!("## {}", title);
+    content
+        .lines()
+        .skip_while(|l| l.trim_end() != heading)
+        .skip(1)
+        .take_while(|l| !l.starts_with("## "))
+        .collect()
+}
+
+/// Parse the `### Name` blocks rendered under `## Components`.
+fn parse_components(lines: &[&str]) -> Vec<Component> {
+    let mut components: Vec<Component> = Vec::new();
+    let mut in_interface = false;
+    for line in lines {
+        if let Some(name) = line.strip_prefix("### ") {
+            components.push(Component::new(name.trim(), ""));
+            in_interface = false;
+            continue;
+        }
+        let Some(component) = components.last_mut() else {
+            continue;
+        };
+        if let Some(purpose) = line.strip_prefix("**Purpose**:") {
+            component.purpose = purpose.trim().to_string();
+        } else if line.starts_with("**Interface**:") {
+            in_interface = true;
+            continue;
+        } else if let Some(deps) = line.strip_prefix("**Dependencies**:") {
+            component.dependencies = split_list(deps);
+        } else if let Some(file) = line.strip_prefix("**File**:") {
+            let file = file.trim().trim_matches('`');
+            component.file_path = (!file.is_empty()).then(|| file.to_string());
+        } else if let Some(item) = line.strip_prefix("- ").filter(|_| in_interface) {
+            component.interface.push(item.trim().to_string());
+            continue;
+        } else if line.trim().is_empty() {
+            continue;
+        }
+        in_interface = false;
+    }
+    components
+}
+
+/// Parse the `- **Key**: value` list rendered under `## Technology Stack`.
+fn parse_technology_stack(lines: &[&str]) -> Option<TechnologyStack> {
+    let mut tech: Option<TechnologyStack> = None;
+    for line in lines {
+        let Some((key, value)) = line
+            .strip_prefix("- **")
+            .and_then(|rest| rest.split_once("**:"))
+        else {
+            continue;
+        };
+        let value = value.trim();
+        let tech = tech.get_or_insert_with(TechnologyStack::default);
+        match key {
+            "Language" => tech.language = value.to_string(),
+            "Testing" => tech.testing_framework = value.to_string(),
+            "Build Tool" => tech.build_tool = value.to_string(),
+            "Dependencies" => tech.dependencies = split_list(value),
+            other => {
+                tech.additional.insert(other.to_string(), value.to_string());
+            }
+        }
+    }
+    tech
+}
+
+fn split_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn join_path(parent: &str, name: &str) -> String {
+    if parent.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", parent, name)
+    }
+}
+
+/// Apply the first matching `(from, to)` prefix rename to a relative path.
+pub fn rename_path(path: &str, renames: &[(String, String)]) -> String {
+    for (from, to) in renames {
+        if path == from {
+            return to.clone();
+        }
+        if let Some(rest) = path.strip_prefix(from.as_str()).and_then(|r| r.strip_prefix('/')) {
+            return join_path(to, rest);
+        }
+    }
+    path.to_string()
+}
+
+/// Shape of a project file tree.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StructureMetrics {
+    /// Deepest entry, counting top-level entries as depth 1
+    pub max_depth: usize,
+    /// Mean entries per non-empty directory (including the root)
+    pub average_fan_out: f32,
+    /// Non-empty directories counted in the fan-out
+    pub directories: usize,
+    /// Paths of single-child directory chains, e.g. `src/core/engine`
+    pub single_child_chains: Vec<String>,
+}
+
+/// Limits on the file tree the architect may propose.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructureLimits {
+    /// Deepest allowed entry (top-level entries are depth 1)
+    pub max_depth: usize,
+    /// Lowest allowed average fan-out, checked from three directories up
+    pub min_average_fan_out: f32,
+    /// Re-ask the architect on a violation instead of flattening
+    pub strict: bool,
+}
+
+/// Fewer directories than this are too small for the fan-out check to mean anything.
+const MIN_DIRECTORIES_FOR_FAN_OUT: usize = 3;
+
+impl Default for StructureLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: 4,
+            min_average_fan_out: 1.5,
+            strict: false,
+        }
+    }
+}
+
+impl StructureLimits {
+    /// Describe how a tree breaks these limits, quoting the offending paths.
+    pub fn violations(&self, root: &FileStructure) -> Vec<String> {
+        let metrics = root.metrics();
+        let mut violations = Vec::new();
+
+        if metrics.max_depth > self.max_depth {
+            let deep: Vec<String> = root
+                .paths()
+                .into_iter()
+                .filter(|p| p.split('/').count() > self.max_depth)
+                .collect();
+            violations.push(format!(
+                "file tree is {} levels deep (limit {}): {}",
+                metrics.max_depth,
+                self.max_depth,
+                deep.join(", ")
+            ));
+        }
+
+        if metrics.directories >= MIN_DIRECTORIES_FOR_FAN_OUT
+            && metrics.average_fan_out < self.min_average_fan_out
+        {
+            violations.push(format!(
+                "directories hold {:.1} entries on average (minimum {:.1})",
+                metrics.average_fan_out, self.min_average_fan_out
+            ));
+        }
+
+        if !metrics.single_child_chains.is_empty() {
+            violations.push(format!(
+                "directories containing only another directory: {}",
+                metrics.single_child_chains.join(", ")
+            ));
+        }
+
+        violations
+    }
 }
 
 /// Technology stack information.
@@ -169,9 +553,74 @@ impl TechnologyStack {
     }
 }
 
+/// Test helpers shared by every test file, planned up front so later tasks
+/// import them instead of writing their own.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct TestInfrastructure {
+    /// Module holding the shared helpers (e.g. `tests/common/mod.rs`)
+    #[serde(default)]
+    pub helper_module: Option<String>,
+    /// Shared fixtures (setup and teardown helpers)
+    #[serde(default)]
+    pub fixtures: Vec<String>,
+    /// Factories building test data
+    #[serde(default)]
+    pub factories: Vec<String>,
+}
+
+impl TestInfrastructure {
+    /// Whether nothing is planned.
+    pub fn is_empty(&self) -> bool {
+        self.helper_module.is_none() && self.fixtures.is_empty() && self.factories.is_empty()
+    }
+
+    /// Markdown lines describing the helpers, one bullet per item.
+    fn to_markdown_lines(&self) -> String {
+        let mut md = String::new();
+        if let Some(module) = &self.helper_module {
+            md.push_str(&format!("**Helper Module**: `{}`\n\n", module));
+        }
+        for (title, items) in [("Fixtures", &self.fixtures), ("Factories", &self.factories)] {
+            if !items.is_empty() {
+                md.push_str(&format!("**{}**:\n", title));
+                for item in items {
+                    md.push_str(&format!("- {}\n", item));
+                }
+                md.push('\n');
+            }
+        }
+        md
+    }
+}
+
+/// Parse the `## Test Infrastructure` section rendered by [`DesignDocument::to_markdown`].
+fn parse_test_infrastructure(lines: &[&str]) -> Option<TestInfrastructure> {
+    let mut infra = TestInfrastructure::default();
+    let mut list: Option<&mut Vec<String>> = None;
+    for line in lines {
+        if let Some(module) = line.strip_prefix("**Helper Module**:") {
+            infra.helper_module = Some(module.trim().trim_matches('`').to_string());
+            list = None;
+        } else if line.starts_with("**Fixtures**:") {
+            list = Some(&mut infra.fixtures);
+        } else if line.starts_with("**Factories**:") {
+            list = Some(&mut infra.factories);
+        } else if let (Some(item), Some(items)) = (line.strip_prefix("- "), list.as_mut()) {
+            items.push(item.trim().to_string());
+        }
+    }
+    (!infra.is_empty()).then_some(infra)
+}
+
 /// Design document containing system architecture and design decisions.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct DesignDocument {
+    /// Schema the file follows; always the current one when written
+    #[serde(rename = "$schema", default = "schema_id", skip_deserializing)]
+    pub schema: String,
+    /// Which run and models produced this document
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Provenance>,
     /// Project name (should match PRD)
     pub project: String,
     /// Architecture overview description
@@ -188,9 +637,18 @@ pub struct DesignDocument {
     /// Technology stack
     #[serde(default)]
     pub technology_stack: Option<TechnologyStack>,
+    /// Shared test helpers, built by an early task
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test_infrastructure: Option<TestInfrastructure>,
+    /// Domain terms to name things by (see [`crate::glossary`])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub glossary: Vec<GlossaryTerm>,
     /// Design decisions and rationale
     #[serde(default)]
     pub design_decisions: Vec<String>,
+    /// Changes Ralph made to the design after the architect produced it
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changelog: Vec<String>,
     /// Document version
     #[serde(default = "default_version")]
     pub version: String,
@@ -206,19 +664,28 @@ fn default_version() -> String {
     "1.0".to_string()
 }
 
+fn schema_id() -> String {
+    schema::DESIGN_SCHEMA_ID.to_string()
+}
+
 impl DesignDocument {
     /// Create a new design document.
     pub fn new(project: impl Into<String>, overview: impl Into<String>) -> Self {
         Self {
+            schema: schema_id(),
+            meta: None,
             project: project.into(),
             overview: overview.into(),
             component_diagram: None,
             components: Vec::new(),
             file_structure: None,
             technology_stack: None,
+            test_infrastructure: None,
+            glossary: Vec::new(),
             design_decisions: Vec::new(),
+            changelog: Vec::new(),
             version: default_version(),
-            created_at: Some(chrono::Utc::now().to_rfc3339()),
+            created_at: Some(crate::timefmt::now()),
             updated_at: None,
         }
     }
@@ -256,8 +723,10 @@ impl DesignDocument {
         let mut in_overview = false;
         let mut in_diagram = false;
         let mut diagram_content = String::new();
+        let mut changelog = Vec::new();
+        let mut in_changelog = false;
 
-        for line in content.lines() {
+        for line in provenance::strip_markdown_header(content).lines() {
             // Extract project name from title
             if line.starts_with("# ") {
                 let title = line.trim_start_matches("# ");
@@ -283,6 +752,13 @@ impl DesignDocument {
             if line.starts_with("## ") {
                 in_overview = false;
                 in_diagram = false;
+                in_changelog = line.starts_with("## Changelog");
+                continue;
+            }
+            if in_changelog {
+                if let Some(entry) = line.strip_prefix("- ") {
+                    changelog.push(entry.to_string());
+                }
                 continue;
             }
 
@@ -317,6 +793,21 @@ impl DesignDocument {
 
         let mut design = DesignDocument::new(project, overview);
         design.component_diagram = diagram;
+        design.components = parse_components(&markdown_section(content, "Components"));
+        let tree: Vec<&str> = markdown_section(content, "File Structure")
+            .into_iter()
+            .skip_while(|l| !l.starts_with("```"))
+            .skip(1)
+            .take_while(|l| !l.starts_with("```"))
+            .collect();
+        design.file_structure = FileStructure::parse_tree(&tree.join("\n"));
+        design.technology_stack =
+            parse_technology_stack(&markdown_section(content, "Technology Stack"));
+        design.test_infrastructure =
+            parse_test_infrastructure(&markdown_section(content, "Test Infrastructure"));
+        design.glossary = Glossary::parse_markdown(&markdown_section(content, "Glossary").join("\n")).terms;
+        design.changelog = changelog;
+        design.meta = provenance::parse_markdown_header(content);
 
         Ok(design)
     }
@@ -327,7 +818,7 @@ impl DesignDocument {
         let content = serde_json::to_string_pretty(self)
             .map_err(|e| format!("Failed to serialize design: {}", e))?;
 
-        fs::write(path, content)
+        crate::persist::write_atomic(path, content.as_bytes())
             .map_err(|e| format!("Failed to write design file '{}': {}", path.display(), e))?;
 
         Ok(())
@@ -338,7 +829,7 @@ impl DesignDocument {
         let content = self.to_markdown();
         let path = path.as_ref();
 
-        fs::write(path, content)
+        crate::persist::write_atomic(path, content.as_bytes())
             .map_err(|e| format!("Failed to write design file '{}': {}", path.display(), e))?;
 
         Ok(())
@@ -346,7 +837,11 @@ impl DesignDocument {
 
     /// Convert design document to markdown format.
     pub fn to_markdown(&self) -> String {
-        let mut md = String::new();
+        let mut md = self
+            .meta
+            .as_ref()
+            .map(provenance::render_markdown_header)
+            .unwrap_or_default();
 
         md.push_str(&format!("# System Design: {}\n\n", self.project));
         md.push_str("## Architecture Overview\n\n");
@@ -414,6 +909,25 @@ impl DesignDocument {
                     tech.dependencies.join(", ")
                 ));
             }
+            if !tech.additional.is_empty() {
+                md.push_str("\n### Other\n\n");
+                let mut other: Vec<_> = tech.additional.iter().collect();
+                other.sort();
+                for (key, value) in other {
+                    md.push_str(&format!("- **{}**: {}\n", key, value));
+                }
+            }
+            md.push('\n');
+        }
+
+        if let Some(infra) = self.test_infrastructure.as_ref().filter(|i| !i.is_empty()) {
+            md.push_str("## Test Infrastructure\n\n");
+            md.push_str(&infra.to_markdown_lines());
+        }
+
+        if !self.glossary.is_empty() {
+            md.push_str("## Glossary\n\n");
+            md.push_str(&Glossary::new(self.glossary.clone()).to_markdown_bullets());
             md.push('\n');
         }
 
@@ -425,6 +939,14 @@ impl DesignDocument {
             md.push('\n');
         }
 
+        if !self.changelog.is_empty() {
+            md.push_str("## Changelog\n\n");
+            for entry in &self.changelog {
+                md.push_str(&format!("- {}\n", entry));
+            }
+            md.push('\n');
+        }
+
         md
     }
 
@@ -439,34 +961,56 @@ impl DesignDocument {
         Ok(())
     }
 
+    /// Components whose `file_path` no task creates.
+    ///
+    /// The architecture describes them but no task is scheduled to build them.
+    /// Components without a `file_path` cannot be checked and are skipped.
+    pub fn uncovered_components(&self, tasks: &TaskList) -> Vec<&Component> {
+        let created: HashSet<String> = tasks
+            .get_all_tasks()
+            .iter()
+            .flat_map(|task| task.files_created.iter())
+            .map(|path| normalize_path(path))
+            .collect();
+        self.components
+            .iter()
+            .filter(|component| {
+                component
+                    .file_path
+                    .as_deref()
+                    .is_some_and(|path| !created.contains(&normalize_path(path)))
+            })
+            .collect()
+    }
+
     /// Add a component to the design.
     pub fn add_component(&mut self, component: Component) {
         self.components.push(component);
-        self.updated_at = Some(chrono::Utc::now().to_rfc3339());
+        self.updated_at = Some(crate::timefmt::now());
     }
 
     /// Set the component diagram.
     pub fn set_diagram(&mut self, diagram: impl Into<String>) {
         self.component_diagram = Some(diagram.into());
-        self.updated_at = Some(chrono::Utc::now().to_rfc3339());
+        self.updated_at = Some(crate::timefmt::now());
     }
 
     /// Set the file structure.
     pub fn set_file_structure(&mut self, structure: FileStructure) {
         self.file_structure = Some(structure);
-        self.updated_at = Some(chrono::Utc::now().to_rfc3339());
+        self.updated_at = Some(crate::timefmt::now());
     }
 
     /// Set the technology stack.
     pub fn set_technology_stack(&mut self, tech: TechnologyStack) {
         self.technology_stack = Some(tech);
-        self.updated_at = Some(chrono::Utc::now().to_rfc3339());
+        self.updated_at = Some(crate::timefmt::now());
     }
 
     /// Add a design decision.
     pub fn add_design_decision(&mut self, decision: impl Into<String>) {
         self.design_decisions.push(decision.into());
-        self.updated_at = Some(chrono::Utc::now().to_rfc3339());
+        self.updated_at = Some(crate::timefmt::now());
     }
 
     /// Get a component by name.
@@ -499,6 +1043,26 @@ mod tests {
         assert_eq!(component.dependencies.len(), 1);
     }
 
+    #[test]
+    fn test_uncovered_components() {
+        use crate::models::Task;
+
+        let mut design = DesignDocument::new("calc", "A calculator");
+        design.add_component(Component::new("Parser", "Parses").with_file_path("./src/parser.rs"));
+        design.add_component(Component::new("Cache", "Caches results").with_file_path("src/cache.rs"));
+        design.add_component(Component::new("Docs", "No file"));
+
+        let mut tasks = TaskList::new("calc", "rust");
+        let mut parser = Task::new("T-001", "Parser", "Write the parser", 1);
+        parser.add_file_created("src/parser.rs");
+        parser.add_file_modified("src/cache.rs");
+        tasks.add_task(parser);
+
+        let uncovered: Vec<&str> =
+            design.uncovered_components(&tasks).iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(uncovered, vec!["Cache"]);
+    }
+
     #[test]
     fn test_file_structure_tree() {
         let mut root = FileStructure::directory("project", "Root directory");
@@ -513,6 +1077,235 @@ mod tests {
         assert!(tree.contains("main.rs"));
     }
 
+    /// Build a tree from relative file paths.
+    fn tree(paths: &[&str]) -> FileStructure {
+        let mut root = FileStructure::directory("project", "");
+        for path in paths {
+            let parts: Vec<&str> = path.split('/').collect();
+            let mut node = &mut root;
+            for (i, part) in parts.iter().enumerate() {
+                let idx = match node.children.iter().position(|c| c.name == *part) {
+                    Some(idx) => idx,
+                    None => {
+                        node.add_child(if i + 1 == parts.len() {
+                            FileStructure::file(*part, "")
+                        } else {
+                            FileStructure::directory(*part, "")
+                        });
+                        node.children.len() - 1
+                    }
+                };
+                node = &mut node.children[idx];
+            }
+        }
+        root
+    }
+
+    #[test]
+    fn test_materialize_order_is_stable() {
+        let listed =
+            tree(&["src/main.rs", "Cargo.toml", "README.md", "src/cli/args.rs", "src/app.rs"]);
+        let reordered =
+            tree(&["README.md", "src/app.rs", "src/cli/args.rs", "Cargo.toml", "src/main.rs"]);
+
+        let created = |root: &FileStructure| {
+            let dir = tempfile::tempdir().unwrap();
+            root.materialize(dir.path())
+                .unwrap()
+                .iter()
+                .map(|p| p.strip_prefix(dir.path()).unwrap().display().to_string())
+                .collect::<Vec<_>>()
+        };
+        let expected = vec![
+            "src",
+            "src/cli",
+            "src/cli/args.rs",
+            "src/app.rs",
+            "src/main.rs",
+            "Cargo.toml",
+            "README.md",
+        ];
+        assert_eq!(created(&listed), expected);
+        assert_eq!(created(&reordered), expected);
+
+        let mut sorted = reordered;
+        sorted.sort_children();
+        assert_eq!(
+            sorted.paths(),
+            vec!["src/cli/args.rs", "src/app.rs", "src/main.rs", "Cargo.toml", "README.md"]
+        );
+    }
+
+    #[test]
+    fn test_structure_metrics_on_flat_tree() {
+        let mut root = tree(&["Cargo.toml", "src/main.rs", "src/lib.rs", "tests/cli.rs", "tests/parse.rs"]);
+        let metrics = root.metrics();
+        assert_eq!(metrics.max_depth, 2);
+        assert_eq!(metrics.directories, 3);
+        assert!((metrics.average_fan_out - 7.0 / 3.0).abs() < 1e-5);
+        assert!(metrics.single_child_chains.is_empty());
+
+        assert!(StructureLimits::default().violations(&root).is_empty());
+        let before = root.clone();
+        assert!(root.flatten_single_child_chains().is_empty());
+        assert_eq!(root, before);
+    }
+
+    #[test]
+    fn test_structure_metrics_and_flattening_on_deep_chains() {
+        let mut root = tree(&[
+            "Cargo.toml",
+            "src/main.rs",
+            "src/core/engine/eval/mod.rs",
+            "src/io/reader.rs",
+            "docs/api/v1/index.md",
+        ]);
+        let metrics = root.metrics();
+        assert_eq!(metrics.max_depth, 5);
+        assert_eq!(metrics.single_child_chains, vec!["src/core/engine/eval", "docs/api/v1"]);
+
+        let violations = StructureLimits::default().violations(&root);
+        assert_eq!(violations.len(), 3);
+        assert!(violations[0].contains("src/core/engine/eval/mod.rs"));
+        assert!(violations[1].contains("average"));
+        assert!(violations[2].contains("docs/api/v1"));
+
+        let renames = root.flatten_single_child_chains();
+        assert_eq!(
+            renames,
+            vec![
+                ("src/core/engine/eval".to_string(), "src/core".to_string()),
+                ("docs/api/v1".to_string(), "docs".to_string()),
+            ]
+        );
+        assert_eq!(
+            root.paths(),
+            vec!["Cargo.toml", "src/main.rs", "src/core/mod.rs", "src/io/reader.rs", "docs/index.md"]
+        );
+        assert!(root.metrics().single_child_chains.is_empty());
+        assert!(StructureLimits::default().violations(&root).is_empty());
+
+        assert_eq!(rename_path("src/core/engine/eval/mod.rs", &renames), "src/core/mod.rs");
+        assert_eq!(rename_path("docs/api/v1", &renames), "docs");
+        assert_eq!(rename_path("src/core/engineering.rs", &renames), "src/core/engineering.rs");
+    }
+
+    #[test]
+    fn test_structure_flattening_of_single_path_tree() {
+        // Every directory holds exactly one entry
+        let mut root = tree(&["a/b/c/d/e/file.rs"]);
+        assert_eq!(root.metrics().max_depth, 6);
+        assert_eq!(root.metrics().single_child_chains, vec!["a/b/c/d/e"]);
+        let renames = root.flatten_single_child_chains();
+        assert_eq!(renames, vec![("a/b/c/d/e".to_string(), "a".to_string())]);
+        assert_eq!(root.paths(), vec!["a/file.rs"]);
+    }
+
+    #[test]
+    fn test_design_markdown_keeps_provenance() {
+        let mut design = DesignDocument::new("calc", "A calculator");
+        let meta = crate::provenance::Provenance::new();
+        design.meta = Some(meta.clone());
+
+        let markdown = design.to_markdown();
+        assert!(markdown.starts_with(crate::provenance::MARKDOWN_HEADER_START));
+        let parsed = DesignDocument::parse_markdown(&markdown).unwrap();
+        assert_eq!(parsed.project, "calc");
+        assert_eq!(parsed.meta, Some(meta));
+
+        let json = serde_json::to_value(&design).unwrap();
+        assert!(json.get("_meta").is_some());
+        let plain = serde_json::to_value(DesignDocument::new("calc", "x")).unwrap();
+        assert!(plain.get("_meta").is_none());
+    }
+
+    #[test]
+    fn test_design_markdown_changelog_round_trips() {
+        let mut design = DesignDocument::new("calc", "A calculator");
+        design.changelog.push("Flattened `src/a/` into `src/`".to_string());
+        design.changelog.push("Replanned after failures in T-003".to_string());
+
+        let parsed = DesignDocument::parse_markdown(&design.to_markdown()).unwrap();
+        assert_eq!(parsed.changelog, design.changelog);
+        assert_eq!(parsed.overview, "A calculator");
+    }
+
+    #[test]
+    fn test_design_markdown_sections_round_trip() {
+        let mut design = DesignDocument::new("calc", "A calculator");
+        let mut parser = Component::new("Parser", "Parses input");
+        parser.interface = vec!["parse(input: &str) -> Expr".to_string()];
+        parser.dependencies = vec!["Lexer".to_string(), "Ast".to_string()];
+        parser.file_path = Some("src/parser.rs".to_string());
+        design.add_component(parser);
+        design.add_component(Component::new("Cli", "Reads arguments"));
+
+        let mut root = FileStructure::directory("calc", "");
+        let mut src = FileStructure::directory("src", "");
+        let mut ops = FileStructure::directory("ops", "");
+        ops.add_child(FileStructure::file("add.rs", ""));
+        src.add_child(ops);
+        src.add_child(FileStructure::file("main.rs", ""));
+        root.add_child(src);
+        root.add_child(FileStructure::directory("docs", ""));
+        root.add_child(FileStructure::file("Cargo.toml", ""));
+        design.file_structure = Some(root);
+
+        let mut tech = TechnologyStack::new("rust").with_build_tool("cargo");
+        tech.add_dependency("clap");
+        tech.add_dependency("thiserror");
+        tech.additional.insert("framework".to_string(), "axum".to_string());
+        tech.additional.insert("database".to_string(), "PostgreSQL 16".to_string());
+        design.technology_stack = Some(tech);
+        assert!(design.to_markdown().contains(
+            "- **Dependencies**: clap, thiserror\n\n### Other\n\n- **database**: PostgreSQL 16\n- **framework**: axum\n"
+        ));
+
+        let parsed = DesignDocument::parse_markdown(&design.to_markdown()).unwrap();
+        assert_eq!(parsed.components, design.components);
+        assert_eq!(parsed.file_structure, design.file_structure);
+        assert_eq!(
+            parsed.file_structure.unwrap().file_paths(),
+            vec!["src/ops/add.rs", "src/main.rs", "Cargo.toml"]
+        );
+        assert_eq!(parsed.technology_stack, design.technology_stack);
+    }
+
+    #[test]
+    fn test_design_markdown_test_infrastructure_round_trips() {
+        let mut design = DesignDocument::new("calc", "A calculator");
+        design.test_infrastructure = Some(TestInfrastructure::default());
+        assert!(!design.to_markdown().contains("## Test Infrastructure"));
+
+        design.test_infrastructure = Some(TestInfrastructure {
+            helper_module: Some("tests/common/mod.rs".to_string()),
+            fixtures: vec!["temp_workspace() -> TempDir".to_string()],
+            factories: vec!["expr(src: &str) -> Expr".to_string(), "config()".to_string()],
+        });
+        design.design_decisions.push("Recursive descent".to_string());
+        let markdown = design.to_markdown();
+        assert!(markdown.contains("**Helper Module**: `tests/common/mod.rs`"));
+
+        let parsed = DesignDocument::parse_markdown(&markdown).unwrap();
+        assert_eq!(parsed.test_infrastructure, design.test_infrastructure);
+        assert_eq!(DesignDocument::parse_markdown("# calc\n").unwrap().test_infrastructure, None);
+    }
+
+    #[test]
+    fn test_design_markdown_glossary_round_trips() {
+        let mut design = DesignDocument::new("shop", "A shop");
+        design.glossary = vec![
+            GlossaryTerm::new("order").with_definition("A request to buy").with_avoid(&["purchase"]),
+            GlossaryTerm::new("customer"),
+        ];
+        design.design_decisions.push("Orders are immutable".to_string());
+        let markdown = design.to_markdown();
+        assert!(markdown.contains("## Glossary\n\n- **order**: A request to buy (not: purchase)\n- **customer**\n"));
+
+        let parsed = DesignDocument::parse_markdown(&markdown).unwrap();
+        assert_eq!(parsed.glossary, design.glossary);
+    }
+
     #[test]
     fn test_design_document_validation() {
         let design = DesignDocument::new("Test", "Test overview");
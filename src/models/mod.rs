@@ -11,15 +11,24 @@ pub mod config;
 pub mod design;
 pub mod prd;
 pub mod progress;
+pub mod provider;
 pub mod tasks;
 
 // Re-export public API
 pub use config::{
-    AgentModelConfig, DebugLevel, ModelConfig, RalphConfig, RalphConfigBuilder, TelemetryConfig,
-    ValidationError, MAX_ITERATIONS_LIMIT, MAX_RETRIES_LIMIT, MAX_TOKENS_LIMIT,
-    SUPPORTED_PROVIDERS,
+    AgentModelConfig, ConsoleSetting, DebugLevel, ModelConfig, PalettePreset, RalphConfig, RalphConfigBuilder, TelemetryConfig,
+    MergeStrategy, SchedulingStrategy, BuildConcurrency, ValidationError, VerificationCadence, MAX_ITERATIONS_LIMIT, MAX_CONCURRENT_BUILDS_LIMIT,
+    MAX_RETRIES_LIMIT, MAX_TOKENS_LIMIT, SUPPORTED_PROVIDERS, available_cpus, default_api_key_envs,
+};
+pub use design::{
+    Component, DesignDocument, FileStructure, StructureLimits, StructureMetrics, TechnologyStack,
+    TestInfrastructure,
 };
-pub use design::{Component, DesignDocument, FileStructure, TechnologyStack};
 pub use prd::{AcceptanceCriterion, PrdDocument, PrdStats, UserStory};
 pub use progress::{ProgressEntry, ProgressLog, ProgressSummary, TestResults};
-pub use tasks::{Phase, Sprint, Task, TaskComplexity, TaskList, TaskStatus};
+pub use provider::Provider;
+pub(crate) use provider::create_model;
+pub use tasks::{
+    BlockedTask, CommitType, DiffStats, Phase, Sprint, Task, TaskComplexity, TaskFingerprint, TaskList,
+    TaskStats, TaskStatus,
+};
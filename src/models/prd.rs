@@ -220,7 +220,7 @@ impl PrdDocument {
             language: None,
             user_stories: Vec::new(),
             version: default_version(),
-            created_at: Some(chrono::Utc::now().to_rfc3339()),
+            created_at: Some(crate::timefmt::now()),
             updated_at: None,
         }
     }
@@ -252,7 +252,7 @@ impl PrdDocument {
         // Basic markdown parsing - can be enhanced later
         let mut prd = PrdDocument::new("", "");
 
-        let lines: Vec<&str> = content.lines().collect();
+        let lines: Vec<&str> = crate::provenance::strip_markdown_header(content).lines().collect();
         let mut i = 0;
 
         while i < lines.len() {
@@ -369,7 +369,7 @@ impl PrdDocument {
         let content = serde_json::to_string_pretty(self)
             .map_err(|e| format!("Failed to serialize PRD: {}", e))?;
 
-        fs::write(path, content)
+        crate::persist::write_atomic(path, content.as_bytes())
             .map_err(|e| format!("Failed to write PRD file '{}': {}", path.display(), e))?;
 
         Ok(())
@@ -380,7 +380,7 @@ impl PrdDocument {
         let content = self.to_markdown();
         let path = path.as_ref();
 
-        fs::write(path, content)
+        crate::persist::write_atomic(path, content.as_bytes())
             .map_err(|e| format!("Failed to write PRD file '{}': {}", path.display(), e))?;
 
         Ok(())
@@ -453,7 +453,7 @@ impl PrdDocument {
     /// Add a user story to the PRD.
     pub fn add_user_story(&mut self, story: UserStory) {
         self.user_stories.push(story);
-        self.updated_at = Some(chrono::Utc::now().to_rfc3339());
+        self.updated_at = Some(crate::timefmt::now());
     }
 
     /// Get the next pending user story by priority.
@@ -479,7 +479,7 @@ impl PrdDocument {
         match self.get_story_mut(id) {
             Some(story) => {
                 story.mark_passing();
-                self.updated_at = Some(chrono::Utc::now().to_rfc3339());
+                self.updated_at = Some(crate::timefmt::now());
                 Ok(())
             }
             None => Err(format!("User story not found: {}", id)),
@@ -50,7 +50,11 @@ impl std::fmt::Display for TestResults {
             f,
             "{} passed, {} failed, {} skipped",
             self.passed, self.failed, self.skipped
-        )
+        )?;
+        if let Some(ms) = self.duration_ms {
+            write!(f, " in {}", crate::timefmt::duration_ms(ms))?;
+        }
+        Ok(())
     }
 }
 
@@ -99,7 +103,7 @@ impl ProgressEntry {
             task_id: task_id.into(),
             title: title.into(),
             iteration,
-            completed_at: chrono::Utc::now().to_rfc3339(),
+            completed_at: crate::timefmt::now(),
             approach: approach.into(),
             learnings: Vec::new(),
             gotchas: Vec::new(),
@@ -250,7 +254,7 @@ pub struct ProgressLog {
 impl ProgressLog {
     /// Create a new progress log.
     pub fn new(project: impl Into<String>) -> Self {
-        let now = chrono::Utc::now().to_rfc3339();
+        let now = crate::timefmt::now();
         Self {
             project: project.into(),
             started_at: now.clone(),
@@ -299,13 +303,13 @@ impl ProgressLog {
     /// This is the primary way to add entries - the log is append-only.
     pub fn append(&mut self, entry: ProgressEntry) {
         self.entries.push(entry);
-        self.last_updated = chrono::Utc::now().to_rfc3339();
+        self.last_updated = crate::timefmt::now();
     }
 
     /// Increment the iteration counter.
     pub fn increment_iteration(&mut self) {
         self.total_iterations += 1;
-        self.last_updated = chrono::Utc::now().to_rfc3339();
+        self.last_updated = crate::timefmt::now();
     }
 
     /// Update the summary statistics.
@@ -426,8 +430,11 @@ mod tests {
         assert!(!results.all_passed());
         assert_eq!(results.total(), 13);
 
-        let passing = TestResults::new(10, 0, 0);
+        let mut passing = TestResults::new(10, 0, 0);
         assert!(passing.all_passed());
+        assert_eq!(passing.to_string(), "10 passed, 0 failed, 0 skipped");
+        passing.duration_ms = Some(4_250);
+        assert_eq!(passing.to_string(), "10 passed, 0 failed, 0 skipped in 4s");
     }
 
     #[test]
@@ -0,0 +1,344 @@
+//! Model providers.
+//!
+//! [`Provider`] names the client a [`ModelConfig`](super::ModelConfig) is
+//! served by. Parsing is case-insensitive and accepts a few aliases
+//! (`claude`, `google`, `open-ai`); an unknown name is rejected with the
+//! closest known provider as a suggestion, so a typo such as `anthropc`
+//! fails when the configuration is read rather than when the first model is
+//! created.
+//!
+//! The string-based [`ModelConfig::new`](super::ModelConfig::new) keeps
+//! accepting any name for backward compatibility; an unrecognised one is
+//! kept as [`Provider::Unknown`] and reported by
+//! [`ModelConfig::validate`](super::ModelConfig::validate). Deserializing is
+//! just as lenient, so any provider that serializes reads back unchanged;
+//! the name is checked when the configuration is validated.
+//!
+//! `create_model` turns a config into the provider's client; every agent
+//! builds its model through it.
+
+use super::config::{ModelConfig, SUPPORTED_PROVIDERS, ValidationError};
+use crate::RalphError;
+use crate::generation::GenerationModel;
+use adk_rust::Llm;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::sync::Arc;
+
+/// A model provider.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Provider {
+    /// Anthropic (Claude)
+    Anthropic,
+    /// OpenAI
+    OpenAI,
+    /// Google Gemini
+    #[default]
+    Gemini,
+    /// A local Ollama server
+    Ollama,
+    /// Plays back a recording; see [`crate::replay`]
+    Replay,
+    /// A name that is not a known provider, kept as given
+    Unknown(String),
+}
+
+/// Accepted spellings besides the canonical names, with the provider they mean.
+const ALIASES: &[(&str, Provider)] = &[
+    ("claude", Provider::Anthropic),
+    ("open-ai", Provider::OpenAI),
+    ("open_ai", Provider::OpenAI),
+    ("google", Provider::Gemini),
+];
+
+impl Provider {
+    /// Every known provider, in [`SUPPORTED_PROVIDERS`] order.
+    pub const KNOWN: &'static [Provider] = &[
+        Provider::OpenAI,
+        Provider::Anthropic,
+        Provider::Gemini,
+        Provider::Ollama,
+        Provider::Replay,
+    ];
+
+    /// Canonical lowercase name (`anthropic`, `openai`, ...).
+    pub fn as_str(&self) -> &str {
+        match self {
+            Provider::Anthropic => "anthropic",
+            Provider::OpenAI => "openai",
+            Provider::Gemini => "gemini",
+            Provider::Ollama => "ollama",
+            Provider::Replay => "replay",
+            Provider::Unknown(name) => name,
+        }
+    }
+
+    /// Name for messages to the user (`Anthropic`, `OpenAI`, ...).
+    pub fn display_name(&self) -> &str {
+        match self {
+            Provider::Anthropic => "Anthropic",
+            Provider::OpenAI => "OpenAI",
+            Provider::Gemini => "Gemini",
+            Provider::Ollama => "Ollama",
+            Provider::Replay => "Replay",
+            Provider::Unknown(name) => name,
+        }
+    }
+
+    /// The provider a name or alias means, or [`Provider::Unknown`] with the name as given.
+    pub fn from_name(name: &str) -> Self {
+        let lower = name.trim().to_lowercase();
+        Self::KNOWN
+            .iter()
+            .find(|p| p.as_str() == lower)
+            .or_else(|| ALIASES.iter().find(|(alias, _)| *alias == lower).map(|(_, p)| p))
+            .cloned()
+            .unwrap_or_else(|| Provider::Unknown(name.to_string()))
+    }
+
+    /// Whether this is a known provider.
+    pub fn is_known(&self) -> bool {
+        !matches!(self, Provider::Unknown(_))
+    }
+
+    /// The known provider closest to a misspelled name, if any is close enough.
+    ///
+    /// Close enough means at most two edits away (one for names of four
+    /// letters or fewer), counting aliases.
+    pub fn suggest(name: &str) -> Option<Provider> {
+        let lower = name.trim().to_lowercase();
+        let max_distance = if lower.chars().count() <= 4 { 1 } else { 2 };
+        let candidates = Self::KNOWN
+            .iter()
+            .map(|p| (p.as_str(), p))
+            .chain(ALIASES.iter().map(|(alias, p)| (*alias, p)));
+        candidates
+            .map(|(spelling, provider)| (edit_distance(&lower, spelling), provider))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, provider)| provider.clone())
+    }
+
+    /// Error for a provider name that is empty or unknown.
+    pub(crate) fn unknown_error(name: &str) -> ValidationError {
+        let supported = format!("Supported providers: {:?}", SUPPORTED_PROVIDERS);
+        if name.trim().is_empty() {
+            return ValidationError::new("provider", "Model provider cannot be empty")
+                .with_suggestion(supported);
+        }
+        let suggestion = match Self::suggest(name) {
+            Some(provider) => format!("Did you mean '{}'? {}", provider, supported),
+            None => supported,
+        };
+        ValidationError::new("provider", format!("Unsupported model provider '{}'", name))
+            .with_suggestion(suggestion)
+    }
+}
+
+impl std::fmt::Display for Provider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Provider {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Self::from_name(s) {
+            Provider::Unknown(name) => Err(Self::unknown_error(&name)),
+            provider => Ok(provider),
+        }
+    }
+}
+
+impl PartialEq<str> for Provider {
+    fn eq(&self, other: &str) -> bool {
+        *self == Provider::from_name(other)
+    }
+}
+
+impl PartialEq<&str> for Provider {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+impl Serialize for Provider {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// Unknown names read back as [`Provider::Unknown`], like they were written;
+/// [`ModelConfig::validate`] and `create_model` reject them.
+impl<'de> Deserialize<'de> for Provider {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(Provider::from_name(&name))
+    }
+}
+
+/// Create the client for a model config, the one factory every agent uses.
+///
+/// The result carries the config's sampling settings (see
+/// [`GenerationModel`]); callers add their own wrappers, such as the call
+/// time limit, on top.
+pub(crate) async fn create_model(config: &ModelConfig) -> crate::Result<Arc<dyn Llm>> {
+    let model: Arc<dyn Llm> = match &config.provider {
+        Provider::Anthropic => {
+            use adk_rust::model::anthropic::{AnthropicClient, AnthropicConfig};
+
+            let api_key = resolve_api_key(config).await?;
+            let anthropic_config = AnthropicConfig::new(api_key, &config.model_name);
+            let client = AnthropicClient::new(anthropic_config).map_err(|e| RalphError::Model {
+                provider: "anthropic".into(),
+                message: e.to_string(),
+            })?;
+            Arc::new(client)
+        }
+        Provider::OpenAI => {
+            use adk_rust::model::openai::{OpenAIClient, OpenAIConfig};
+
+            let api_key = resolve_api_key(config).await?;
+            let openai_config = OpenAIConfig::new(api_key, &config.model_name);
+            let client = OpenAIClient::new(openai_config).map_err(|e| RalphError::Model {
+                provider: "openai".into(),
+                message: e.to_string(),
+            })?;
+            Arc::new(client)
+        }
+        Provider::Gemini => {
+            use adk_rust::model::GeminiModel;
+
+            let api_key = resolve_api_key(config).await?;
+            let client = GeminiModel::new(api_key, &config.model_name).map_err(|e| {
+                RalphError::Model {
+                    provider: "gemini".into(),
+                    message: e.to_string(),
+                }
+            })?;
+            Arc::new(client)
+        }
+        Provider::Replay => Arc::new(crate::replay::ReplayModel::load(&config.model_name)?),
+        Provider::Ollama => {
+            use adk_rust::model::ollama::{OllamaConfig, OllamaModel};
+
+            let ollama_config = OllamaConfig::with_host(config.ollama_host(), &config.model_name);
+            let client = OllamaModel::new(ollama_config).map_err(|e| RalphError::Model {
+                provider: "ollama".into(),
+                message: format!("{} ({})", e, config.ollama_host()),
+            })?;
+            Arc::new(client)
+        }
+        Provider::Unknown(name) => {
+            return Err(RalphError::Configuration(format!(
+                "Unsupported model provider: {}. Supported: {}",
+                name,
+                SUPPORTED_PROVIDERS.join(", ")
+            )));
+        }
+    };
+
+    Ok(GenerationModel::wrap(model, config))
+}
+
+/// [`ModelConfig::resolve_api_key`] on a blocking thread: a key command can
+/// take seconds (or wait for a prompt) and must not hold a runtime worker.
+async fn resolve_api_key(config: &ModelConfig) -> crate::Result<String> {
+    let config = config.clone();
+    tokio::task::spawn_blocking(move || config.resolve_api_key())
+        .await
+        .map_err(|e| RalphError::Internal(format!("API key lookup failed: {}", e)))?
+}
+
+/// Levenshtein distance between two strings, by characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_names_and_aliases() {
+        for (name, expected) in [
+            ("anthropic", Provider::Anthropic),
+            ("Anthropic", Provider::Anthropic),
+            ("claude", Provider::Anthropic),
+            ("OpenAI", Provider::OpenAI),
+            ("open-ai", Provider::OpenAI),
+            (" gemini ", Provider::Gemini),
+            ("google", Provider::Gemini),
+            ("ollama", Provider::Ollama),
+            ("replay", Provider::Replay),
+        ] {
+            assert_eq!(name.parse::<Provider>().unwrap(), expected, "{}", name);
+        }
+        let names: Vec<&str> = Provider::KNOWN.iter().map(Provider::as_str).collect();
+        assert_eq!(names, SUPPORTED_PROVIDERS);
+    }
+
+    #[test]
+    fn test_unknown_provider_suggests_closest() {
+        let err = "anthropc".parse::<Provider>().unwrap_err();
+        assert_eq!(err.field, "provider");
+        assert_eq!(err.message, "Unsupported model provider 'anthropc'");
+        assert_eq!(
+            err.suggestion.unwrap(),
+            "Did you mean 'anthropic'? Supported providers: \
+             [\"openai\", \"anthropic\", \"gemini\", \"ollama\", \"replay\"]"
+        );
+        assert_eq!(Provider::suggest("gemeni"), Some(Provider::Gemini));
+        assert_eq!(Provider::suggest("claud"), Some(Provider::Anthropic));
+        assert_eq!(Provider::suggest("mistral"), None);
+
+        let err = "mistral".parse::<Provider>().unwrap_err();
+        assert!(err.suggestion.unwrap().starts_with("Supported providers"));
+        assert_eq!("".parse::<Provider>().unwrap_err().message, "Model provider cannot be empty");
+    }
+
+    #[test]
+    fn test_unknown_names_are_kept_leniently() {
+        let provider = Provider::from_name("Mistral");
+        assert_eq!(provider, Provider::Unknown("Mistral".to_string()));
+        assert!(!provider.is_known());
+        assert_eq!(provider.to_string(), "Mistral");
+        assert_eq!(Provider::OpenAI.display_name(), "OpenAI");
+    }
+
+    #[test]
+    fn test_serde_and_string_comparison() {
+        assert_eq!(serde_json::to_string(&Provider::OpenAI).unwrap(), "\"openai\"");
+        let parsed: Provider = serde_json::from_str("\"Claude\"").unwrap();
+        assert_eq!(parsed, Provider::Anthropic);
+
+        // Every provider reads back as it was written, unknown ones included
+        let unknown = Provider::Unknown("Mistral".to_string());
+        for provider in Provider::KNOWN.iter().chain([&unknown]) {
+            let json = serde_json::to_string(provider).unwrap();
+            assert_eq!(&serde_json::from_str::<Provider>(&json).unwrap(), provider, "{}", json);
+        }
+
+        // A typo is reported when the config is validated
+        let config: ModelConfig =
+            serde_json::from_str(r#"{"provider": "opneai", "model_name": "gpt-4o"}"#).unwrap();
+        assert_eq!(config.provider, Provider::Unknown("opneai".to_string()));
+        let err = config.validate().unwrap_err();
+        assert!(err.suggestion.unwrap().contains("Did you mean 'openai'?"));
+
+        assert_eq!(Provider::Gemini, "gemini");
+        assert_eq!(Provider::Gemini, "GEMINI");
+        assert_ne!(Provider::Gemini, "openai");
+    }
+}
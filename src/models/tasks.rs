@@ -4,8 +4,13 @@
 //! including tasks with priorities, dependencies, status tracking,
 //! and organization into sprints and phases.
 
+use crate::models::{ModelConfig, SchedulingStrategy};
+use crate::provenance::Provenance;
+use crate::resources::ResourceUsage;
+use crate::schema;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 
@@ -20,10 +25,14 @@ pub enum TaskStatus {
     InProgress,
     /// Successfully completed
     Completed,
-    /// Blocked by dependencies or issues
+    /// Given up on: out of attempts, aborted with `--block`, or waiting on a
+    /// blocked dependency. There is no separate failed status; a blocked task
+    /// that was never attempted has `attempts == 0`.
     Blocked,
     /// Skipped (optional task not implemented)
     Skipped,
+    /// Dropped from scope with its user story (`ralph scope cut`)
+    Obsolete,
 }
 
 impl std::fmt::Display for TaskStatus {
@@ -34,6 +43,7 @@ impl std::fmt::Display for TaskStatus {
             TaskStatus::Completed => write!(f, "completed"),
             TaskStatus::Blocked => write!(f, "blocked"),
             TaskStatus::Skipped => write!(f, "skipped"),
+            TaskStatus::Obsolete => write!(f, "obsolete"),
         }
     }
 }
@@ -43,7 +53,7 @@ impl TaskStatus {
     pub fn is_terminal(&self) -> bool {
         matches!(
             self,
-            TaskStatus::Completed | TaskStatus::Blocked | TaskStatus::Skipped
+            TaskStatus::Completed | TaskStatus::Blocked | TaskStatus::Skipped | TaskStatus::Obsolete
         )
     }
 
@@ -66,6 +76,17 @@ pub enum TaskComplexity {
     High,
 }
 
+impl TaskComplexity {
+    /// Relative effort: 1 (low), 2 (medium) or 3 (high).
+    pub fn weight(&self) -> u32 {
+        match self {
+            TaskComplexity::Low => 1,
+            TaskComplexity::Medium => 2,
+            TaskComplexity::High => 3,
+        }
+    }
+}
+
 impl std::fmt::Display for TaskComplexity {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -76,6 +97,203 @@ impl std::fmt::Display for TaskComplexity {
     }
 }
 
+/// Conventional-commit type of a task's commit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CommitType {
+    /// A new feature
+    Feat,
+    /// A bug fix
+    Fix,
+    /// Tests only
+    Test,
+    /// Restructuring without behavior change
+    Refactor,
+    /// Documentation only
+    Docs,
+    /// Build, config and other maintenance
+    Chore,
+}
+
+impl CommitType {
+    /// Infer the type from the task's title and declared files.
+    ///
+    /// Checked in order: "fix"/"bug" in the title, test-only files,
+    /// docs-only files, "refactor"-like titles, then new files or a
+    /// feature verb (`feat`); anything else is a `chore`.
+    pub fn infer(task: &Task) -> Self {
+        let words: Vec<String> = task
+            .title
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_ascii_lowercase)
+            .collect();
+        let has_word = |candidates: &[&str]| words.iter().any(|w| candidates.contains(&w.as_str()));
+        let files: Vec<&str> = task
+            .files_created
+            .iter()
+            .chain(&task.files_modified)
+            .map(String::as_str)
+            .collect();
+
+        if has_word(&["fix", "fixes", "fixed", "bug", "bugs", "bugfix", "hotfix"]) {
+            CommitType::Fix
+        } else if files.iter().all(|f| is_test_path(f))
+            && (!files.is_empty() || has_word(&["test", "tests", "testing"]))
+        {
+            CommitType::Test
+        } else if !files.is_empty() && files.iter().all(|f| is_docs_path(f)) {
+            CommitType::Docs
+        } else if has_word(&["refactor", "restructure", "rename", "cleanup", "simplify"]) {
+            CommitType::Refactor
+        } else if !task.files_created.is_empty()
+            || has_word(&["add", "implement", "create", "support", "introduce", "build"])
+        {
+            CommitType::Feat
+        } else {
+            CommitType::Chore
+        }
+    }
+
+    /// Parse a lowercase type name such as `feat`.
+    pub fn parse(name: &str) -> Option<Self> {
+        serde_json::from_value(serde_json::Value::String(name.trim().to_ascii_lowercase())).ok()
+    }
+
+    /// Prefix `message` with this type, unless it already has a conventional prefix.
+    pub fn apply(self, message: &str) -> String {
+        if has_conventional_prefix(message) {
+            message.to_string()
+        } else {
+            format!("{}: {}", self, message)
+        }
+    }
+}
+
+impl std::fmt::Display for CommitType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            CommitType::Feat => "feat",
+            CommitType::Fix => "fix",
+            CommitType::Test => "test",
+            CommitType::Refactor => "refactor",
+            CommitType::Docs => "docs",
+            CommitType::Chore => "chore",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Whether `message` starts with `type:`, `type(scope):` or `type!:`.
+fn has_conventional_prefix(message: &str) -> bool {
+    let Some((head, _)) = message.split_once(':') else {
+        return false;
+    };
+    let head = head.strip_suffix('!').unwrap_or(head);
+    let kind = match head.split_once('(') {
+        Some((kind, scope)) if scope.ends_with(')') => kind,
+        Some(_) => return false,
+        None => head,
+    };
+    !kind.is_empty() && kind.chars().all(|c| c.is_ascii_lowercase())
+}
+
+fn is_test_path(path: &str) -> bool {
+    let path = path.to_ascii_lowercase();
+    let name = path.rsplit('/').next().unwrap_or(&path);
+    path.split('/').any(|part| matches!(part, "test" | "tests" | "__tests__" | "spec"))
+        || name.starts_with("test_")
+        || name.contains("_test.")
+        || name.contains(".test.")
+        || name.contains(".spec.")
+        || name.contains("_spec.")
+}
+
+fn is_docs_path(path: &str) -> bool {
+    let path = path.to_ascii_lowercase();
+    path.starts_with("docs/") || path.ends_with(".md") || path.ends_with(".rst")
+}
+
+/// Line and file footprint of a task's commit, as reported by `git --numstat`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiffStats {
+    /// Lines added
+    pub added: usize,
+    /// Lines removed
+    pub removed: usize,
+    /// Paths of changed files
+    #[serde(default)]
+    pub files: Vec<String>,
+}
+
+/// What a completed task was built from and what it produced.
+///
+/// Recorded on completion; `ralph resume --incremental` re-runs a task only
+/// when this no longer matches (see [`crate::incremental`]).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskFingerprint {
+    /// SHA-256 over the description, acceptance criteria, design excerpt and input files
+    pub inputs: String,
+    /// SHA-256 of each declared file as the task left it
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+    /// When the task completed
+    #[serde(default)]
+    pub completed_at: String,
+}
+
+impl DiffStats {
+    /// Parse the output of `git show --numstat` or `git diff --numstat`.
+    ///
+    /// Binary files are reported by git as `-` and count towards `files` only.
+    pub fn from_numstat(output: &str) -> Self {
+        let mut stats = Self::default();
+        for line in output.lines() {
+            let mut cols = line.splitn(3, '\t');
+            let (Some(added), Some(removed), Some(path)) = (cols.next(), cols.next(), cols.next())
+            else {
+                continue;
+            };
+            let path = path.trim();
+            if path.is_empty() {
+                continue;
+            }
+            stats.added += added.trim().parse::<usize>().unwrap_or(0);
+            stats.removed += removed.trim().parse::<usize>().unwrap_or(0);
+            stats.files.push(path.to_string());
+        }
+        stats
+    }
+
+    /// Number of files changed.
+    pub fn files_changed(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Fold another set of stats into this one, counting each file once.
+    pub fn merge(&mut self, other: &DiffStats) {
+        self.added += other.added;
+        self.removed += other.removed;
+        for file in &other.files {
+            if !self.files.contains(file) {
+                self.files.push(file.clone());
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for DiffStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} lines added, {} removed across {} files",
+            self.added,
+            self.removed,
+            self.files_changed()
+        )
+    }
+}
+
 /// A single task in the task list.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Task {
@@ -95,6 +313,12 @@ pub struct Task {
     /// Reference to the user story this task implements
     #[serde(default)]
     pub user_story_id: Option<String>,
+    /// Conditions the implementation must satisfy (EARS patterns from the PRD)
+    #[serde(default)]
+    pub acceptance_criteria: Vec<String>,
+    /// Criteria the latest acceptance review found unmet (`None` until reviewed)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unmet_criteria: Option<Vec<String>>,
     /// Estimated complexity
     #[serde(default)]
     pub estimated_complexity: TaskComplexity,
@@ -107,12 +331,51 @@ pub struct Task {
     /// Git commit hash when completed
     #[serde(default)]
     pub commit_hash: Option<String>,
+    /// Lines and files changed by the task's commit
+    #[serde(default)]
+    pub diff_stats: Option<DiffStats>,
     /// Number of attempts made on this task
     #[serde(default)]
     pub attempts: u32,
     /// Notes or learnings from implementation
     #[serde(default)]
     pub notes: String,
+    /// Why a person should review this task, when it was flagged
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub needs_review: Option<String>,
+    /// Variables for this task's build/test/run commands, over `execution_env`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Model used for this task instead of the run's loop model
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<ModelConfig>,
+    /// Inputs and outputs recorded on completion, for incremental re-runs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<TaskFingerprint>,
+    /// Conventional-commit type for this task's commit instead of the inferred one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit_type: Option<CommitType>,
+    /// Whether this task scaffolds the project, so the build is checked right after it
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub setup: bool,
+    /// Resources used by the task's build/test/run commands, recorded on completion
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resources: Option<ResourceUsage>,
+    /// When the latest attempt started (RFC 3339)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<String>,
+    /// When the latest attempt ended, completed or blocked (RFC 3339)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<String>,
+    /// Git branch the task was implemented on, kept for review when it fails
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// HEAD when the task first started, where a rollback returns to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_commit: Option<String>,
+    /// Commit holding the task's work before it was rolled back
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rolled_back: Option<String>,
 }
 
 impl Task {
@@ -131,15 +394,35 @@ impl Task {
             status: TaskStatus::Pending,
             dependencies: Vec::new(),
             user_story_id: None,
+            acceptance_criteria: Vec::new(),
+            unmet_criteria: None,
             estimated_complexity: TaskComplexity::Medium,
             files_created: Vec::new(),
             files_modified: Vec::new(),
             commit_hash: None,
+            diff_stats: None,
             attempts: 0,
             notes: String::new(),
+            needs_review: None,
+            env: BTreeMap::new(),
+            model: None,
+            fingerprint: None,
+            commit_type: None,
+            setup: false,
+            resources: None,
+            started_at: None,
+            completed_at: None,
+            branch: None,
+            start_commit: None,
+            rolled_back: None,
         }
     }
 
+    /// Conventional-commit type of this task: the override, or the inferred type.
+    pub fn conventional_type(&self) -> CommitType {
+        self.commit_type.unwrap_or_else(|| CommitType::infer(self))
+    }
+
     /// Add a dependency.
     pub fn add_dependency(&mut self, dep_id: impl Into<String>) {
         self.dependencies.push(dep_id.into());
@@ -151,12 +434,24 @@ impl Task {
         self
     }
 
+    /// Add an acceptance criterion.
+    pub fn with_acceptance_criterion(mut self, criterion: impl Into<String>) -> Self {
+        self.acceptance_criteria.push(criterion.into());
+        self
+    }
+
     /// Set the complexity.
     pub fn with_complexity(mut self, complexity: TaskComplexity) -> Self {
         self.estimated_complexity = complexity;
         self
     }
 
+    /// Implement this task with `model` instead of the run's loop model.
+    pub fn with_model(mut self, model: ModelConfig) -> Self {
+        self.model = Some(model);
+        self
+    }
+
     /// Check if this task is pending.
     pub fn is_pending(&self) -> bool {
         self.status == TaskStatus::Pending
@@ -174,22 +469,51 @@ impl Task {
 
     /// Mark this task as in progress.
     pub fn start(&mut self) {
-        self.status = TaskStatus::InProgress;
+        self.set_status(TaskStatus::InProgress);
         self.attempts += 1;
     }
 
     /// Mark this task as completed.
     pub fn complete(&mut self, commit_hash: Option<String>) {
-        self.status = TaskStatus::Completed;
+        self.set_status(TaskStatus::Completed);
         self.commit_hash = commit_hash;
     }
 
     /// Mark this task as blocked.
     pub fn block(&mut self, reason: &str) {
-        self.status = TaskStatus::Blocked;
+        self.set_status(TaskStatus::Blocked);
         self.add_note(&format!("Blocked: {}", reason));
     }
 
+    /// Change the status, timing the attempt: entering in progress records
+    /// `started_at`, and completing or blocking an attempt records `completed_at`.
+    pub fn set_status(&mut self, status: TaskStatus) {
+        match status {
+            TaskStatus::InProgress if self.status != TaskStatus::InProgress => {
+                self.started_at = Some(crate::timefmt::now());
+                self.completed_at = None;
+            }
+            TaskStatus::Completed | TaskStatus::Blocked if self.status == TaskStatus::InProgress => {
+                self.completed_at = Some(crate::timefmt::now());
+            }
+            _ => {}
+        }
+        self.status = status;
+    }
+
+    /// How long the latest attempt took, once it has ended.
+    pub fn duration(&self) -> Option<chrono::Duration> {
+        let started = crate::timefmt::parse_timestamp(self.started_at.as_deref()?)?;
+        let completed = crate::timefmt::parse_timestamp(self.completed_at.as_deref()?)?;
+        Some(completed - started).filter(|d| *d >= chrono::Duration::zero())
+    }
+
+    /// Flag this task for review by a person.
+    pub fn flag_for_review(&mut self, reason: &str) {
+        self.needs_review = Some(reason.to_string());
+        self.add_note(&format!("Flagged for review: {}", reason));
+    }
+
     /// Add a note to this task.
     pub fn add_note(&mut self, note: &str) {
         if !self.notes.is_empty() {
@@ -208,6 +532,20 @@ impl Task {
         self.files_modified.push(path.into());
     }
 
+    /// Short account of why this task failed, for replanning.
+    ///
+    /// Uses the latest note (where blocks and failed attempts are recorded),
+    /// falling back to the review flag or the status.
+    pub fn failure_summary(&self) -> String {
+        self.notes
+            .lines()
+            .rev()
+            .find(|line| !line.trim().is_empty())
+            .map(|line| line.trim().to_string())
+            .or_else(|| self.needs_review.clone())
+            .unwrap_or_else(|| format!("{} after {} attempt(s)", self.status, self.attempts))
+    }
+
     /// Convert to context string for LLM.
     pub fn to_context(&self) -> String {
         let deps = if self.dependencies.is_empty() {
@@ -216,10 +554,50 @@ impl Task {
             self.dependencies.join(", ")
         };
 
-        format!(
+        let mut context = format!(
             "**Task ID**: {}\n**Title**: {}\n**Description**: {}\n**Priority**: {}\n**Status**: {}\n**Dependencies**: {}\n**Complexity**: {}",
             self.id, self.title, self.description, self.priority, self.status, deps, self.estimated_complexity
-        )
+        );
+        if !self.acceptance_criteria.is_empty() {
+            context.push_str("\n**Acceptance Criteria**:");
+            for criterion in &self.acceptance_criteria {
+                // Checked off once a review has seen them
+                let check = match self.criterion_met(criterion) {
+                    Some(true) => "[x] ",
+                    Some(false) => "[ ] ",
+                    None => "",
+                };
+                context.push_str(&format!("\n- {}{}", check, criterion));
+            }
+        }
+        context
+    }
+
+    /// Whether the latest acceptance review found `criterion` met, or `None`
+    /// before any review.
+    pub fn criterion_met(&self, criterion: &str) -> Option<bool> {
+        self.unmet_criteria
+            .as_ref()
+            .map(|unmet| !unmet.iter().any(|c| c == criterion))
+    }
+
+    /// Move acceptance criteria that an older architect joined into `notes`
+    /// into [`Task::acceptance_criteria`], one per line.
+    ///
+    /// Only a task that was never attempted is changed: until then its notes
+    /// held nothing but the criteria.
+    fn migrate_criteria_from_notes(&mut self) {
+        if self.attempts > 0 || !self.acceptance_criteria.is_empty() {
+            return;
+        }
+        self.acceptance_criteria = self
+            .notes
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(String::from)
+            .collect();
+        self.notes.clear();
     }
 }
 
@@ -309,6 +687,12 @@ impl Phase {
 /// Complete task list for a project.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct TaskList {
+    /// Schema the file follows; always the current one when written
+    #[serde(rename = "$schema", default = "schema_id", skip_deserializing)]
+    pub schema: String,
+    /// Which run and models produced this task list
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Provenance>,
     /// Project name (should match PRD and Design)
     pub project: String,
     /// Target programming language
@@ -334,16 +718,26 @@ fn default_version() -> String {
     "1.0".to_string()
 }
 
+fn schema_id() -> String {
+    schema::TASKS_SCHEMA_ID.to_string()
+}
+
+/// Failed attempts after which [`TaskList::next_runnable`] passes a task over,
+/// matching the default `max_task_retries`.
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
 impl TaskList {
     /// Create a new task list.
     pub fn new(project: impl Into<String>, language: impl Into<String>) -> Self {
         Self {
+            schema: schema_id(),
+            meta: None,
             project: project.into(),
             language: language.into(),
             phases: Vec::new(),
             tasks: Vec::new(),
             version: default_version(),
-            created_at: Some(chrono::Utc::now().to_rfc3339()),
+            created_at: Some(crate::timefmt::now()),
             updated_at: None,
         }
     }
@@ -354,20 +748,60 @@ impl TaskList {
         let content = fs::read_to_string(path)
             .map_err(|e| format!("Failed to read tasks file '{}': {}", path.display(), e))?;
 
-        let tasks: TaskList = serde_json::from_str(&content)
+        let mut tasks: TaskList = serde_json::from_str(&content)
             .map_err(|e| format!("Failed to parse tasks JSON '{}': {}", path.display(), e))?;
+        if let Ok(raw) = serde_json::from_str::<serde_json::Value>(&content) {
+            tasks.migrate_legacy_criteria(&raw);
+        }
 
         tasks.validate()?;
         Ok(tasks)
     }
 
-    /// Save the task list to a JSON file.
+    /// Upgrade tasks written before tasks had `acceptance_criteria`, which
+    /// then went into `notes` (see [`Task::migrate_criteria_from_notes`]).
+    ///
+    /// Such tasks are recognized by the key missing from `raw`, the file as
+    /// read: it has been written for every task since.
+    fn migrate_legacy_criteria(&mut self, raw: &serde_json::Value) {
+        let sprint_tasks = raw["phases"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .flat_map(|phase| phase["sprints"].as_array().into_iter().flatten())
+            .flat_map(|sprint| sprint["tasks"].as_array().into_iter().flatten());
+        let legacy: HashSet<&str> = raw["tasks"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .chain(sprint_tasks)
+            .filter(|task| task.get("acceptance_criteria").is_none())
+            .filter_map(|task| task["id"].as_str())
+            .collect();
+        if legacy.is_empty() {
+            return;
+        }
+        for task in self.get_all_tasks_mut() {
+            if legacy.contains(task.id.as_str()) {
+                task.migrate_criteria_from_notes();
+            }
+        }
+    }
+
+    /// Save the task list to a JSON file (see [`TaskList::save_atomic`]).
     pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
-        let path = path.as_ref();
+        self.save_atomic(path.as_ref())
+    }
+
+    /// Save the task list by writing a temporary sibling file and renaming it
+    /// over `path`, so a reader or a killed run never sees a half-written
+    /// tasks.json: it holds either the previous or the new list.
+    pub fn save_atomic(&self, path: &Path) -> Result<(), String> {
         let content = serde_json::to_string_pretty(self)
             .map_err(|e| format!("Failed to serialize tasks: {}", e))?;
 
-        fs::write(path, content)
+        // Never buffered or torn: the loop and `ralph tasks` read it at any time
+        crate::persist::write_atomic(path, content.as_bytes())
             .map_err(|e| format!("Failed to write tasks file '{}': {}", path.display(), e))?;
 
         Ok(())
@@ -406,16 +840,63 @@ impl TaskList {
         Ok(())
     }
 
+    /// Check the dependency graph: every dependency names a task in the
+    /// list, and no task waits on itself through a chain of dependencies.
+    ///
+    /// Returns every problem found, unknown references first, then one entry
+    /// per group of tasks that depend on each other in a cycle.
+    pub fn validate_dependencies(&self) -> Result<(), Vec<String>> {
+        let tasks = self.get_all_tasks();
+        let ids: HashSet<&str> = tasks.iter().map(|t| t.id.as_str()).collect();
+        let mut problems = Vec::new();
+        for task in &tasks {
+            for dep in task.dependencies.iter().filter(|dep| !ids.contains(dep.as_str())) {
+                problems.push(format!("{} depends on unknown task {}", task.id, dep));
+            }
+        }
+
+        let (_, unsorted) = dependency_sort(&tasks);
+        for cycle in cycle_groups(&tasks, &unsorted) {
+            problems.push(format!("dependency cycle among {}", cycle.join(", ")));
+        }
+
+        if problems.is_empty() { Ok(()) } else { Err(problems) }
+    }
+
+    /// All tasks ordered so that every task comes after its dependencies,
+    /// lower priority numbers first and then IDs where the order is free.
+    ///
+    /// Statuses are not considered; unknown dependencies are ignored (see
+    /// [`TaskList::validate_dependencies`]). Fails with [`RalphError::Task`]
+    /// naming the tasks of each cycle when there is one.
+    ///
+    /// [`RalphError::Task`]: crate::RalphError::Task
+    pub fn topological_order(&self) -> crate::Result<Vec<&Task>> {
+        let tasks = self.get_all_tasks();
+        let (sorted, unsorted) = dependency_sort(&tasks);
+        if unsorted.is_empty() {
+            return Ok(sorted);
+        }
+        let cycles: Vec<String> = cycle_groups(&tasks, &unsorted)
+            .into_iter()
+            .map(|cycle| cycle.join(" <-> "))
+            .collect();
+        Err(crate::RalphError::Task(format!(
+            "No task order satisfies the dependencies; these tasks depend on each other: {}",
+            cycles.join("; ")
+        )))
+    }
+
     /// Add a phase.
     pub fn add_phase(&mut self, phase: Phase) {
         self.phases.push(phase);
-        self.updated_at = Some(chrono::Utc::now().to_rfc3339());
+        self.updated_at = Some(crate::timefmt::now());
     }
 
     /// Add a task to the flat list.
     pub fn add_task(&mut self, task: Task) {
         self.tasks.push(task);
-        self.updated_at = Some(chrono::Utc::now().to_rfc3339());
+        self.updated_at = Some(crate::timefmt::now());
     }
 
     /// Get all tasks (from both phases and flat list).
@@ -464,6 +945,41 @@ impl TaskList {
 
     /// Get the next task to work on based on priority and dependencies.
     pub fn get_next_task(&self) -> Option<&Task> {
+        self.next_ready(SchedulingStrategy::PriorityFirst)
+    }
+
+    /// The task to attempt next: the highest-priority pending task whose
+    /// dependencies are all completed, skipping tasks that already failed
+    /// [`DEFAULT_MAX_ATTEMPTS`] times.
+    ///
+    /// A failed attempt puts a task back to pending with its `attempts`
+    /// counted (or blocks it), so a task that keeps failing is not offered
+    /// forever. `None` when everything left is done, blocked or out of attempts.
+    pub fn next_runnable(&self) -> Option<&Task> {
+        self.next_runnable_within(DEFAULT_MAX_ATTEMPTS)
+    }
+
+    /// [`TaskList::next_runnable`] with a custom attempt limit.
+    pub fn next_runnable_within(&self, max_attempts: u32) -> Option<&Task> {
+        self.ready_tasks(SchedulingStrategy::PriorityFirst)
+            .into_iter()
+            .find(|t| t.attempts < max_attempts)
+    }
+
+    /// Mutable [`TaskList::next_runnable`], e.g. to start the task.
+    pub fn next_runnable_mut(&mut self) -> Option<&mut Task> {
+        let id = self.next_runnable()?.id.clone();
+        self.get_task_mut(&id)
+    }
+
+    /// Get the next task to work on, ordering ready tasks by `strategy`.
+    pub fn next_ready(&self, strategy: SchedulingStrategy) -> Option<&Task> {
+        self.ready_tasks(strategy).into_iter().next()
+    }
+
+    /// Pending tasks whose dependencies are all completed, best first by
+    /// `strategy`. Ties go to the lower priority number, then the lower ID.
+    pub fn ready_tasks(&self, strategy: SchedulingStrategy) -> Vec<&Task> {
         let completed_ids: HashSet<_> = self
             .get_all_tasks()
             .iter()
@@ -471,7 +987,8 @@ impl TaskList {
             .map(|t| t.id.as_str())
             .collect();
 
-        self.get_all_tasks()
+        let mut ready: Vec<&Task> = self
+            .get_all_tasks()
             .into_iter()
             .filter(|t| t.is_pending())
             .filter(|t| {
@@ -480,15 +997,145 @@ impl TaskList {
                     .iter()
                     .all(|dep| completed_ids.contains(dep.as_str()))
             })
-            .min_by_key(|t| t.priority)
+            .collect();
+
+        match strategy {
+            SchedulingStrategy::PriorityFirst => ready.sort_by_key(|t| (t.priority, t.id.as_str())),
+            SchedulingStrategy::CriticalPathFirst => {
+                let lengths = self.critical_path_lengths();
+                ready.sort_by_key(|t| {
+                    (Reverse(lengths.get(t.id.as_str()).copied().unwrap_or(0)), t.priority, t.id.as_str())
+                });
+            }
+            SchedulingStrategy::ShortestFirst => {
+                ready.sort_by_key(|t| (t.estimated_complexity.weight(), t.priority, t.id.as_str()))
+            }
+        }
+        ready
+    }
+
+    /// Pending tasks that cannot start yet, each with the dependencies it is
+    /// waiting on, in [`TaskList::ready_tasks`] order (priority, then ID).
+    ///
+    /// Tasks marked [`TaskStatus::Blocked`] are counted by
+    /// [`TaskList::get_stats`] instead; a dependency on one shows up here.
+    pub fn blocked(&self) -> Vec<BlockedTask> {
+        let statuses: HashMap<&str, TaskStatus> = self
+            .get_all_tasks()
+            .into_iter()
+            .map(|t| (t.id.as_str(), t.status))
+            .collect();
+
+        let mut blocked: Vec<BlockedTask> = self
+            .get_all_tasks()
+            .into_iter()
+            .filter(|t| t.is_pending())
+            .filter_map(|t| {
+                let reasons: Vec<String> = t
+                    .dependencies
+                    .iter()
+                    .filter_map(|dep| match statuses.get(dep.as_str()) {
+                        Some(TaskStatus::Completed) => None,
+                        Some(status) => Some(format!("{} is {}", dep, status)),
+                        None => Some(format!("{} does not exist", dep)),
+                    })
+                    .collect();
+                (!reasons.is_empty()).then(|| BlockedTask {
+                    id: t.id.clone(),
+                    priority: t.priority,
+                    reasons,
+                })
+            })
+            .collect();
+        blocked.sort_by(|a, b| (a.priority, &a.id).cmp(&(b.priority, &b.id)));
+        blocked
+    }
+
+    /// Length of the longest chain of unfinished work starting at each
+    /// unfinished task: its own complexity weight plus that of the heaviest
+    /// chain of tasks waiting on it. Finished and blocked tasks are left out;
+    /// a dependency cycle is cut where it closes.
+    pub fn critical_path_lengths(&self) -> HashMap<&str, u32> {
+        let unfinished: Vec<&Task> = self
+            .get_all_tasks()
+            .into_iter()
+            .filter(|t| t.status.is_workable())
+            .collect();
+
+        let mut dependents: HashMap<&str, Vec<&Task>> = HashMap::new();
+        for &task in &unfinished {
+            for dep in &task.dependencies {
+                dependents.entry(dep.as_str()).or_default().push(task);
+            }
+        }
+
+        let mut lengths = HashMap::new();
+        for &task in &unfinished {
+            chain_length(task, &dependents, &mut lengths, &mut HashSet::new());
+        }
+        lengths
+    }
+
+    /// Block every pending task that depends, directly or through other
+    /// tasks, on a blocked one: it can never start. Returns their IDs.
+    ///
+    /// Each gets a note naming the dependency; its `attempts` are untouched,
+    /// so it stays distinguishable from tasks that were tried and failed.
+    pub fn mark_blocked_tasks(&mut self) -> Vec<String> {
+        let mut newly_blocked = Vec::new();
+        loop {
+            let statuses: HashMap<String, TaskStatus> = self
+                .get_all_tasks()
+                .into_iter()
+                .map(|t| (t.id.clone(), t.status))
+                .collect();
+            let mut changed = false;
+            for task in self.get_all_tasks_mut() {
+                if !task.is_pending() {
+                    continue;
+                }
+                let blocked_dep = task
+                    .dependencies
+                    .iter()
+                    .find(|dep| statuses.get(dep.as_str()) == Some(&TaskStatus::Blocked));
+                if let Some(dep) = blocked_dep.cloned() {
+                    task.block(&format!("dependency {} is blocked", dep));
+                    newly_blocked.push(task.id.clone());
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        if !newly_blocked.is_empty() {
+            self.updated_at = Some(crate::timefmt::now());
+        }
+        newly_blocked
+    }
+
+    /// Put tasks left in progress by an interrupted run back to pending and
+    /// return their IDs. Their `attempts` stay counted.
+    pub fn reset_interrupted(&mut self) -> Vec<String> {
+        let mut reset = Vec::new();
+        for task in self.get_all_tasks_mut() {
+            if task.status == TaskStatus::InProgress {
+                task.status = TaskStatus::Pending;
+                reset.push(task.id.clone());
+            }
+        }
+        if !reset.is_empty() {
+            self.updated_at = Some(crate::timefmt::now());
+        }
+        reset
     }
 
     /// Update task status by ID.
     pub fn update_task_status(&mut self, id: &str, status: TaskStatus) -> Result<(), String> {
         match self.get_task_mut(id) {
             Some(task) => {
-                task.status = status;
-                self.updated_at = Some(chrono::Utc::now().to_rfc3339());
+                task.set_status(status);
+                self.updated_at = Some(crate::timefmt::now());
                 Ok(())
             }
             None => Err(format!("Task not found: {}", id)),
@@ -500,13 +1147,60 @@ impl TaskList {
         match self.get_task_mut(id) {
             Some(task) => {
                 task.complete(commit_hash);
-                self.updated_at = Some(chrono::Utc::now().to_rfc3339());
+                self.updated_at = Some(crate::timefmt::now());
+                Ok(())
+            }
+            None => Err(format!("Task not found: {}", id)),
+        }
+    }
+
+    /// Record the diff stats of a task's commit.
+    pub fn set_diff_stats(&mut self, id: &str, stats: DiffStats) -> Result<(), String> {
+        match self.get_task_mut(id) {
+            Some(task) => {
+                task.diff_stats = Some(stats);
+                self.updated_at = Some(crate::timefmt::now());
                 Ok(())
             }
             None => Err(format!("Task not found: {}", id)),
         }
     }
 
+    /// Aggregate diff stats across all tasks that recorded them.
+    pub fn total_diff_stats(&self) -> Option<DiffStats> {
+        let mut total: Option<DiffStats> = None;
+        for stats in self.get_all_tasks().iter().filter_map(|t| t.diff_stats.as_ref()) {
+            total.get_or_insert_with(DiffStats::default).merge(stats);
+        }
+        total
+    }
+
+    /// Average and highest attempts over completed tasks, with the task that took the most.
+    ///
+    /// Ties go to the task listed first; `(0.0, 0, "")` when nothing is completed.
+    pub fn attempt_stats(&self) -> (f64, u32, String) {
+        let completed: Vec<&Task> =
+            self.get_all_tasks().into_iter().filter(|t| t.is_completed()).collect();
+        if completed.is_empty() {
+            return (0.0, 0, String::new());
+        }
+        let total: u32 = completed.iter().map(|t| t.attempts).sum();
+        let worst = completed
+            .iter()
+            .fold(completed[0], |worst, t| if t.attempts > worst.attempts { t } else { worst });
+        (total as f64 / completed.len() as f64, worst.attempts, worst.id.clone())
+    }
+
+    /// Time spent in completed tasks' final attempts, and how many were timed
+    /// (tasks.json files from before timing was recorded have no times).
+    pub fn task_time(&self) -> (chrono::Duration, usize) {
+        self.get_all_tasks()
+            .into_iter()
+            .filter(|t| t.is_completed())
+            .filter_map(Task::duration)
+            .fold((chrono::Duration::zero(), 0), |(total, count), d| (total + d, count + 1))
+    }
+
     /// Get task statistics.
     pub fn get_stats(&self) -> TaskStats {
         let all_tasks = self.get_all_tasks();
@@ -544,10 +1238,170 @@ impl TaskList {
             .iter()
             .all(|t| t.status.is_terminal())
     }
+
+    /// Carry completed work over from an earlier plan into this one.
+    ///
+    /// A completed task in `previous` replaces the task with the same ID here,
+    /// or is appended to the flat list if the new plan dropped it, so finished
+    /// work is never redone. Returns the IDs of the preserved tasks.
+    pub fn merge_from(&mut self, previous: &TaskList) -> Vec<String> {
+        let mut preserved = Vec::new();
+        for done in previous.get_all_tasks().into_iter().filter(|t| t.is_completed()) {
+            match self.get_task_mut(&done.id) {
+                Some(task) => *task = done.clone(),
+                None => self.tasks.push(done.clone()),
+            }
+            preserved.push(done.id.clone());
+        }
+        if previous.created_at.is_some() {
+            self.created_at = previous.created_at.clone();
+        }
+        self.updated_at = Some(crate::timefmt::now());
+        preserved
+    }
+}
+
+/// Weighted length of the longest chain of unfinished tasks starting at `task`.
+fn chain_length<'a>(
+    task: &'a Task,
+    dependents: &HashMap<&str, Vec<&'a Task>>,
+    lengths: &mut HashMap<&'a str, u32>,
+    visiting: &mut HashSet<&'a str>,
+) -> u32 {
+    if let Some(&length) = lengths.get(task.id.as_str()) {
+        return length;
+    }
+    if !visiting.insert(task.id.as_str()) {
+        // Back on a cycle
+        return 0;
+    }
+    let longest_after = dependents
+        .get(task.id.as_str())
+        .into_iter()
+        .flatten()
+        .copied()
+        .map(|dependent| chain_length(dependent, dependents, lengths, visiting))
+        .max()
+        .unwrap_or(0);
+    visiting.remove(task.id.as_str());
+
+    let length = task.estimated_complexity.weight() + longest_after;
+    lengths.insert(task.id.as_str(), length);
+    length
+}
+
+/// Kahn's topological sort over `tasks`: the tasks whose dependencies can
+/// all be ordered before them, by priority then ID where there is a choice,
+/// and the IDs of those left over because they are on or behind a cycle.
+/// Unknown dependencies are ignored.
+fn dependency_sort<'a>(tasks: &[&'a Task]) -> (Vec<&'a Task>, Vec<&'a str>) {
+    let ids: HashSet<&str> = tasks.iter().map(|t| t.id.as_str()).collect();
+    let mut waiting_on: HashMap<&str, usize> = HashMap::new();
+    let mut dependents: HashMap<&str, Vec<&str>> = HashMap::new();
+    for task in tasks {
+        let known: HashSet<&str> = task
+            .dependencies
+            .iter()
+            .map(String::as_str)
+            .filter(|dep| ids.contains(dep))
+            .collect();
+        for &dep in &known {
+            dependents.entry(dep).or_default().push(task.id.as_str());
+        }
+        waiting_on.insert(task.id.as_str(), known.len());
+    }
+
+    let by_id: HashMap<&str, &'a Task> = tasks.iter().map(|&t| (t.id.as_str(), t)).collect();
+    let rank = |task: &'a Task| (task.priority, task.id.as_str());
+    let mut ready: std::collections::BTreeSet<(u32, &str)> = tasks
+        .iter()
+        .filter(|t| waiting_on[t.id.as_str()] == 0)
+        .map(|&t| rank(t))
+        .collect();
+    let mut sorted = Vec::with_capacity(tasks.len());
+    while let Some((_, id)) = ready.pop_first() {
+        let task = by_id[id];
+        sorted.push(task);
+        for &dependent in dependents.get(id).into_iter().flatten() {
+            let count = waiting_on.entry(dependent).or_default();
+            *count -= 1;
+            if *count == 0 {
+                ready.insert(rank(by_id[dependent]));
+            }
+        }
+    }
+
+    let done: HashSet<&str> = sorted.iter().map(|t| t.id.as_str()).collect();
+    let unsorted = tasks
+        .iter()
+        .map(|&t| t.id.as_str())
+        .filter(|id| !done.contains(id))
+        .collect();
+    (sorted, unsorted)
+}
+
+/// Split the tasks a topological sort left over into groups that depend on
+/// each other in a cycle, dropping those that only wait on a cycle.
+///
+/// Each group lists its IDs in order; groups are ordered by their first ID.
+fn cycle_groups(tasks: &[&Task], unsorted: &[&str]) -> Vec<Vec<String>> {
+    let left: HashSet<&str> = unsorted.iter().copied().collect();
+    let deps: HashMap<&str, Vec<&str>> = tasks
+        .iter()
+        .filter(|t| left.contains(t.id.as_str()))
+        .map(|t| {
+            let deps = t.dependencies.iter().map(String::as_str).filter(|d| left.contains(d)).collect();
+            (t.id.as_str(), deps)
+        })
+        .collect();
+
+    // Tasks reachable from `start` through dependencies among the leftovers
+    let reachable = |start: &str| {
+        let mut seen = HashSet::new();
+        let mut stack = deps.get(start).cloned().unwrap_or_default();
+        while let Some(id) = stack.pop() {
+            if seen.insert(id) {
+                stack.extend(deps.get(id).into_iter().flatten().copied());
+            }
+        }
+        seen
+    };
+    let reach: HashMap<&str, HashSet<&str>> = left.iter().map(|&id| (id, reachable(id))).collect();
+
+    let mut groups: Vec<Vec<String>> = Vec::new();
+    let mut grouped: HashSet<&str> = HashSet::new();
+    let mut ids: Vec<&str> = left.iter().copied().filter(|id| reach[id].contains(id)).collect();
+    ids.sort_unstable();
+    for &id in &ids {
+        if grouped.contains(id) {
+            continue;
+        }
+        // Members of one cycle reach each other
+        let group: Vec<&str> = ids
+            .iter()
+            .copied()
+            .filter(|&other| other == id || (reach[id].contains(other) && reach[other].contains(id)))
+            .collect();
+        grouped.extend(&group);
+        groups.push(group.into_iter().map(String::from).collect());
+    }
+    groups
+}
+
+/// A pending task whose dependencies are not all completed
+/// (see [`TaskList::blocked`]).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockedTask {
+    /// ID of the waiting task
+    pub id: String,
+    /// Its priority (lower = higher priority)
+    pub priority: u32,
+    /// One line per unmet dependency, e.g. "T-001 is in_progress"
+    pub reasons: Vec<String>,
 }
 
 /// Statistics about task completion.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TaskStats {
     /// Total number of tasks
     pub total: usize,
@@ -604,6 +1458,261 @@ mod tests {
         assert_eq!(task.commit_hash, Some("abc123".to_string()));
     }
 
+    #[test]
+    fn test_merge_from_preserves_completed_tasks() {
+        let mut previous = TaskList::new("calc", "rust");
+        let mut setup = Task::new("T-001", "Setup", "Set up", 1);
+        setup.complete(Some("abc123".to_string()));
+        previous.add_task(setup);
+        let mut cli = Task::new("T-002", "CLI", "Old CLI", 2);
+        cli.complete(None);
+        previous.add_task(cli);
+        let mut parser = Task::new("T-003", "Parser", "Parse", 2);
+        parser.block("design flaw");
+        previous.add_task(parser);
+
+        let mut replanned = TaskList::new("calc", "rust");
+        replanned.add_task(Task::new("T-001", "Setup", "Set up again", 1));
+        replanned.add_task(Task::new("T-003", "Parser", "Parse with a lexer", 2));
+        replanned.add_task(Task::new("T-004", "Lexer", "Tokenize", 2));
+
+        let preserved = replanned.merge_from(&previous);
+        assert_eq!(preserved, vec!["T-001", "T-002"]);
+        let setup = replanned.get_task("T-001").unwrap();
+        assert!(setup.is_completed());
+        assert_eq!(setup.commit_hash.as_deref(), Some("abc123"));
+        // Dropped by the new plan but done, so kept
+        assert!(replanned.get_task("T-002").unwrap().is_completed());
+        // Failed tasks take the new plan's version
+        let parser = replanned.get_task("T-003").unwrap();
+        assert!(parser.is_pending());
+        assert_eq!(parser.description, "Parse with a lexer");
+        assert_eq!(replanned.get_stats().total, 4);
+        assert_eq!(replanned.created_at, previous.created_at);
+    }
+
+    #[test]
+    fn test_failure_summary_prefers_latest_note() {
+        let mut task = Task::new("T-003", "Parser", "Parse", 2);
+        task.start();
+        assert_eq!(task.failure_summary(), "in_progress after 1 attempt(s)");
+        task.add_note("Tests failed: missing lexer");
+        task.block("parser needs a token stream the design never provides");
+        assert_eq!(
+            task.failure_summary(),
+            "Blocked: parser needs a token stream the design never provides"
+        );
+    }
+
+    #[test]
+    fn test_task_acceptance_criteria() {
+        let task = Task::new("TASK-001", "Test", "Desc", 1)
+            .with_acceptance_criterion("WHEN run, THE cli SHALL print help");
+        assert!(task.to_context().contains("**Acceptance Criteria**:\n- WHEN run, THE cli SHALL print help"));
+
+        // Task lists written before criteria were tracked still load
+        let json = r#"{"id":"TASK-002","title":"T","description":"D","priority":1,"status":"pending"}"#;
+        let old: Task = serde_json::from_str(json).unwrap();
+        assert!(old.acceptance_criteria.is_empty());
+        assert!(!old.to_context().contains("Acceptance Criteria"));
+    }
+
+    #[test]
+    fn test_reviewed_criteria_are_checked_off() {
+        let mut task = Task::new("TASK-001", "Test", "Desc", 1)
+            .with_acceptance_criterion("WHEN run, THE cli SHALL print help")
+            .with_acceptance_criterion("WHEN given -v, THE cli SHALL print its version");
+        assert_eq!(task.criterion_met("WHEN run, THE cli SHALL print help"), None);
+
+        task.unmet_criteria = Some(vec!["WHEN given -v, THE cli SHALL print its version".to_string()]);
+        assert_eq!(task.criterion_met("WHEN run, THE cli SHALL print help"), Some(true));
+        let context = task.to_context();
+        assert!(context.contains("\n- [x] WHEN run, THE cli SHALL print help"), "{}", context);
+        assert!(context.contains("\n- [ ] WHEN given -v, THE cli SHALL print its version"), "{}", context);
+
+        let loaded: Task = serde_json::from_str(&serde_json::to_string(&task).unwrap()).unwrap();
+        assert_eq!(loaded.unmet_criteria, task.unmet_criteria);
+    }
+
+    #[test]
+    fn test_legacy_task_list_criteria_move_out_of_notes() {
+        // Written by the first architect: criteria joined into notes, no
+        // acceptance_criteria key
+        let legacy = r#"{
+            "project": "cli",
+            "language": "rust",
+            "tasks": [
+                {"id": "T-001", "title": "Help", "description": "D", "priority": 1, "status": "pending",
+                 "notes": "WHEN run, THE cli SHALL print help\n\nWHEN given -v, THE cli SHALL print its version"},
+                {"id": "T-002", "title": "Parse", "description": "D", "priority": 2, "status": "blocked",
+                 "attempts": 2, "notes": "WHEN given a file, THE cli SHALL parse it\nTests failed twice"}
+            ],
+            "phases": [{"id": "p1", "name": "P", "sprints": [{"id": "s1", "name": "S", "tasks": [
+                {"id": "T-003", "title": "Lex", "description": "D", "priority": 1, "status": "pending",
+                 "notes": "WHEN lexing, THE lexer SHALL skip comments"}
+            ]}]}]
+        }"#;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tasks.json");
+        std::fs::write(&path, legacy).unwrap();
+        let mut list = TaskList::load(&path).unwrap();
+
+        let help = list.get_task("T-001").unwrap();
+        assert_eq!(
+            help.acceptance_criteria,
+            ["WHEN run, THE cli SHALL print help", "WHEN given -v, THE cli SHALL print its version"]
+        );
+        assert!(help.notes.is_empty());
+        assert_eq!(
+            list.get_task("T-003").unwrap().acceptance_criteria,
+            ["WHEN lexing, THE lexer SHALL skip comments"]
+        );
+        // Once attempted, notes mix criteria with progress and stay as they are
+        let parse = list.get_task("T-002").unwrap();
+        assert!(parse.acceptance_criteria.is_empty());
+        assert!(parse.notes.contains("Tests failed twice"));
+
+        // Saved in the current format, free-form notes are never taken for criteria
+        list.get_task_mut("T-001").unwrap().add_note("Remember the man page");
+        list.save(&path).unwrap();
+        let reloaded = TaskList::load(&path).unwrap();
+        let help = reloaded.get_task("T-001").unwrap();
+        assert_eq!(help.acceptance_criteria.len(), 2);
+        assert_eq!(help.notes, "Remember the man page");
+        let json = std::fs::read_to_string(&path).unwrap();
+        assert!(json.contains("\"acceptance_criteria\": []"), "{}", json);
+    }
+
+    #[test]
+    fn test_task_model_override_round_trips() {
+        let task = Task::new("TASK-001", "Parser", "Desc", 1)
+            .with_model(ModelConfig::new("anthropic", "claude-opus-4-5").with_api_key("secret"));
+        let json = serde_json::to_string(&task).unwrap();
+        assert!(!json.contains("secret"));
+        let loaded: Task = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.model.as_ref().unwrap().model_name, "claude-opus-4-5");
+        assert_eq!(loaded.model.unwrap().api_key, None);
+
+        // Tasks without an override stay as they were written
+        let plain = serde_json::to_string(&Task::new("TASK-002", "T", "D", 1)).unwrap();
+        assert!(!plain.contains("\"model\""));
+    }
+
+    #[test]
+    fn test_commit_type_inference() {
+        let task = |title: &str, created: &[&str], modified: &[&str]| {
+            let mut task = Task::new("T-001", title, "", 1);
+            task.files_created = created.iter().map(|f| f.to_string()).collect();
+            task.files_modified = modified.iter().map(|f| f.to_string()).collect();
+            task
+        };
+        let cases = [
+            (task("Fix off-by-one in lexer", &["tests/lexer.rs"], &[]), CommitType::Fix),
+            (task("Parser bug with unary minus", &[], &["src/parser.rs"]), CommitType::Fix),
+            (task("Cover the parser", &["tests/parser.rs"], &[]), CommitType::Test),
+            (task("Write tests", &[], &[]), CommitType::Test),
+            (task("Edge cases", &["src/calc.test.ts"], &["src/__tests__/a.ts"]), CommitType::Test),
+            (task("Usage guide", &["docs/usage.md"], &["README.md"]), CommitType::Docs),
+            (task("Refactor evaluator", &[], &["src/eval.rs"]), CommitType::Refactor),
+            (task("Evaluator", &["src/eval.rs"], &[]), CommitType::Feat),
+            (task("Implement division", &[], &["src/eval.rs"]), CommitType::Feat),
+            (task("Pin toolchain", &[], &["Cargo.toml"]), CommitType::Chore),
+        ];
+        for (task, expected) in cases {
+            assert_eq!(task.conventional_type(), expected, "{}", task.title);
+        }
+
+        let mut pinned = task("Fix typo", &[], &[]);
+        pinned.commit_type = CommitType::parse("Docs");
+        assert_eq!(pinned.conventional_type(), CommitType::Docs);
+        assert_eq!(CommitType::parse("wip"), None);
+    }
+
+    #[test]
+    fn test_commit_type_prefix() {
+        assert_eq!(CommitType::Feat.apply("Add parser"), "feat: Add parser");
+        assert_eq!(CommitType::Feat.apply("fix(parser): guard"), "fix(parser): guard");
+        assert_eq!(CommitType::Feat.apply("refactor!: drop v1"), "refactor!: drop v1");
+        assert_eq!(CommitType::Fix.apply("Note: trailing"), "fix: Note: trailing");
+        assert_eq!(CommitType::Chore.apply("T-001 done"), "chore: T-001 done");
+    }
+
+    #[test]
+    fn test_save_atomic_replaces_the_file_whole() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tasks.json");
+        let mut list = TaskList::new("Test", "rust");
+        list.add_task(Task::new("T-001", "First", "", 1));
+        list.save_atomic(&path).unwrap();
+
+        list.complete_task("T-001", None).unwrap();
+        list.save(&path).unwrap();
+        let saved = TaskList::load(&path).unwrap();
+        assert!(saved.get_task("T-001").unwrap().is_completed());
+
+        // Only tasks.json is left behind, no temporary sibling
+        let names: Vec<_> = std::fs::read_dir(dir.path()).unwrap().map(|e| e.unwrap().file_name()).collect();
+        assert_eq!(names, ["tasks.json"]);
+    }
+
+    #[test]
+    fn test_mark_blocked_tasks_follows_dependency_chains() {
+        let mut list = TaskList::new("Test", "rust");
+        list.add_task(Task::new("T-001", "Parser", "", 1));
+        for (id, dep) in [("T-002", "T-001"), ("T-003", "T-002"), ("T-004", "T-005")] {
+            let mut task = Task::new(id, id, "", 2);
+            task.add_dependency(dep);
+            list.add_task(task);
+        }
+        list.add_task(Task::new("T-005", "Docs", "", 3));
+        assert!(list.mark_blocked_tasks().is_empty());
+
+        list.get_task_mut("T-001").unwrap().start();
+        list.get_task_mut("T-001").unwrap().block("out of attempts");
+        assert_eq!(list.mark_blocked_tasks(), ["T-002", "T-003"]);
+        let never_tried = list.get_task("T-003").unwrap();
+        assert!(never_tried.is_blocked());
+        assert_eq!(never_tried.attempts, 0);
+        assert!(never_tried.notes.contains("dependency T-002 is blocked"));
+        assert_eq!(list.get_task("T-004").unwrap().status, TaskStatus::Pending);
+        assert_eq!(list.get_stats().blocked, 3);
+    }
+
+    #[test]
+    fn test_task_timing() {
+        let mut task = Task::new("T-001", "Timed", "", 1);
+        assert_eq!(task.duration(), None);
+        task.start();
+        assert!(task.started_at.is_some());
+        assert_eq!(task.duration(), None);
+
+        task.started_at = Some("2026-10-16T09:30:00.000Z".to_string());
+        task.complete(None);
+        task.completed_at = Some("2026-10-16T09:42:05.500Z".to_string());
+        assert_eq!(task.duration(), Some(chrono::Duration::milliseconds(725_500)));
+
+        // A new attempt starts the clock again
+        task.status = TaskStatus::Pending;
+        task.start();
+        assert_eq!(task.completed_at, None);
+        task.block("stuck");
+        assert!(task.duration().is_some());
+
+        // Old tasks.json entries have no times
+        let old = r#"{"id":"T-002","title":"Old","description":"","priority":1,"status":"completed"}"#;
+        let old: Task = serde_json::from_str(old).unwrap();
+        assert_eq!(old.duration(), None);
+
+        let mut list = TaskList::new("Test", "rust");
+        let mut timed = Task::new("T-003", "Timed", "", 1);
+        timed.status = TaskStatus::Completed;
+        timed.started_at = Some("2026-10-16T09:00:00Z".to_string());
+        timed.completed_at = Some("2026-10-16T09:01:00Z".to_string());
+        list.add_task(timed);
+        list.add_task(old);
+        assert_eq!(list.task_time(), (chrono::Duration::minutes(1), 1));
+    }
+
     #[test]
     fn test_task_list_next_task() {
         let mut list = TaskList::new("Test", "rust");
@@ -644,4 +1753,231 @@ mod tests {
         let next = list.get_next_task().unwrap();
         assert_eq!(next.id, "TASK-002");
     }
+
+    #[test]
+    fn test_next_runnable_walks_a_diamond() {
+        // T-001 feeds T-002 and T-003, which both feed T-004
+        let mut list = TaskList::new("Test", "rust");
+        list.add_task(Task::new("T-001", "Base", "", 2));
+        for (id, priority) in [("T-002", 3), ("T-003", 1)] {
+            let mut side = Task::new(id, id, "", priority);
+            side.add_dependency("T-001");
+            list.add_task(side);
+        }
+        let mut top = Task::new("T-004", "Top", "", 1);
+        top.add_dependency("T-002");
+        top.add_dependency("T-003");
+        list.add_task(top);
+
+        let next = |list: &TaskList| list.next_runnable().map(|t| t.id.clone());
+        assert_eq!(next(&list).as_deref(), Some("T-001"));
+        list.complete_task("T-001", None).unwrap();
+        // Both sides are ready; the higher priority goes first
+        assert_eq!(next(&list).as_deref(), Some("T-003"));
+        list.next_runnable_mut().unwrap().start();
+        assert_eq!(next(&list).as_deref(), Some("T-002"));
+        list.complete_task("T-003", None).unwrap();
+        // T-004 still waits for T-002
+        assert_eq!(next(&list).as_deref(), Some("T-002"));
+
+        // A task that failed too often is passed over, leaving nothing runnable
+        list.get_task_mut("T-002").unwrap().attempts = DEFAULT_MAX_ATTEMPTS;
+        assert_eq!(next(&list), None);
+        assert_eq!(list.next_runnable_within(DEFAULT_MAX_ATTEMPTS + 1).unwrap().id, "T-002");
+        list.get_task_mut("T-002").unwrap().attempts = 1;
+        list.complete_task("T-002", None).unwrap();
+        assert_eq!(next(&list).as_deref(), Some("T-004"));
+        list.complete_task("T-004", None).unwrap();
+        assert_eq!(next(&list), None);
+        assert!(list.next_runnable_mut().is_none());
+    }
+
+    #[test]
+    fn test_blocked_explains_unmet_dependencies() {
+        // T-001 feeds T-002 and T-003, which both feed T-004; equal
+        // priorities fall back to the ID whatever the file order
+        let mut list = TaskList::new("Test", "rust");
+        let mut top = Task::new("T-004", "Top", "", 1);
+        top.add_dependency("T-002");
+        top.add_dependency("T-003");
+        list.add_task(top);
+        for id in ["T-003", "T-002"] {
+            let mut side = Task::new(id, id, "", 2);
+            side.add_dependency("T-001");
+            list.add_task(side);
+        }
+        list.add_task(Task::new("T-001", "Base", "", 2));
+        list.add_task(Task::new("T-000", "Other", "", 2));
+        let mut orphan = Task::new("T-005", "Orphan", "", 3);
+        orphan.add_dependency("T-099");
+        list.add_task(orphan);
+
+        let ids = |list: &TaskList| -> Vec<String> { list.blocked().into_iter().map(|b| b.id).collect() };
+        assert_eq!(list.next_runnable().unwrap().id, "T-000");
+        assert_eq!(ids(&list), ["T-004", "T-002", "T-003", "T-005"]);
+        assert_eq!(list.blocked()[3].reasons, ["T-099 does not exist"]);
+
+        list.complete_task("T-000", None).unwrap();
+        list.complete_task("T-001", None).unwrap();
+        assert_eq!(list.next_runnable().unwrap().id, "T-002");
+        list.next_runnable_mut().unwrap().start();
+        list.update_task_status("T-003", TaskStatus::Blocked).unwrap();
+        let blocked = list.blocked();
+        assert_eq!(blocked[0].id, "T-004");
+        assert_eq!(blocked[0].reasons, ["T-002 is in_progress", "T-003 is blocked"]);
+        assert_eq!(list.next_runnable(), None);
+    }
+
+    #[test]
+    fn test_scheduling_strategies_order_ready_tasks() {
+        let mut list = TaskList::new("Test", "rust");
+        list.add_task(Task::new("T-001", "Polish", "", 1).with_complexity(TaskComplexity::Low));
+        list.add_task(Task::new("T-002", "Storage", "", 3));
+        let mut api = Task::new("T-003", "API", "", 3);
+        api.add_dependency("T-002");
+        list.add_task(api);
+        let mut sync = Task::new("T-004", "Sync", "", 3).with_complexity(TaskComplexity::High);
+        sync.add_dependency("T-003");
+        list.add_task(sync);
+        list.add_task(Task::new("T-005", "Auth", "", 2).with_complexity(TaskComplexity::High));
+
+        let lengths = list.critical_path_lengths();
+        assert_eq!(lengths["T-004"], 3);
+        assert_eq!(lengths["T-002"], 7);
+        assert_eq!(lengths["T-001"], 1);
+
+        let order = |strategy| -> Vec<String> {
+            list.ready_tasks(strategy).iter().map(|t| t.id.clone()).collect()
+        };
+        assert_eq!(order(SchedulingStrategy::PriorityFirst), ["T-001", "T-005", "T-002"]);
+        assert_eq!(order(SchedulingStrategy::CriticalPathFirst), ["T-002", "T-005", "T-001"]);
+        assert_eq!(order(SchedulingStrategy::ShortestFirst), ["T-001", "T-002", "T-005"]);
+        assert_eq!(list.get_next_task().unwrap().id, "T-001");
+
+        // Finished work no longer counts towards a chain
+        list.complete_task("T-002", None).unwrap();
+        assert_eq!(list.critical_path_lengths().get("T-002"), None);
+        assert_eq!(list.next_ready(SchedulingStrategy::CriticalPathFirst).unwrap().id, "T-003");
+    }
+
+    #[test]
+    fn test_validate_dependencies_reports_unknown_ids_and_cycles() {
+        let task = |id: &str, deps: &[&str]| {
+            let mut task = Task::new(id, id, "", 1);
+            for dep in deps {
+                task.add_dependency(*dep);
+            }
+            task
+        };
+
+        let mut list = TaskList::new("Test", "rust");
+        list.add_task(task("T-001", &[]));
+        list.add_task(task("T-002", &["T-001"]));
+        list.add_task(task("T-003", &["T-001", "T-002"]));
+        assert_eq!(list.validate_dependencies(), Ok(()));
+
+        // A self-dependency, a three-task cycle, a task only waiting on it,
+        // and a reference to a task that does not exist
+        list.add_task(task("T-004", &["T-004"]));
+        list.add_task(task("T-005", &["T-007"]));
+        list.add_task(task("T-006", &["T-005"]));
+        list.add_task(task("T-007", &["T-006", "T-003"]));
+        list.add_task(task("T-008", &["T-005", "T-099"]));
+        assert_eq!(
+            list.validate_dependencies().unwrap_err(),
+            vec![
+                "T-008 depends on unknown task T-099",
+                "dependency cycle among T-004",
+                "dependency cycle among T-005, T-006, T-007",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_topological_order_puts_dependencies_first() {
+        let mut list = TaskList::new("Test", "rust");
+        let mut api = Task::new("T-003", "API", "", 1);
+        api.add_dependency("T-004");
+        list.add_task(api);
+        list.add_task(Task::new("T-002", "Docs", "", 2));
+        list.add_task(Task::new("T-004", "Storage", "", 3));
+        list.add_task(Task::new("T-001", "Setup", "", 2));
+        let mut cli = Task::new("T-005", "CLI", "", 1);
+        cli.add_dependency("T-003");
+        cli.add_dependency("T-001");
+        list.add_task(cli);
+
+        let order: Vec<&str> = list.topological_order().unwrap().iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(order, ["T-001", "T-002", "T-004", "T-003", "T-005"]);
+
+        // Completed work keeps its place
+        list.complete_task("T-004", None).unwrap();
+        assert_eq!(list.topological_order().unwrap().len(), 5);
+
+        list.get_task_mut("T-004").unwrap().add_dependency("T-005");
+        let error = list.topological_order().unwrap_err();
+        assert!(matches!(error, crate::RalphError::Task(_)));
+        assert!(error.to_string().contains("T-003 <-> T-004 <-> T-005"), "{}", error);
+    }
+
+    #[test]
+    fn test_critical_path_lengths_survive_cycles() {
+        let mut list = TaskList::new("Test", "rust");
+        let mut a = Task::new("T-001", "A", "", 1);
+        a.add_dependency("T-002");
+        let mut b = Task::new("T-002", "B", "", 1);
+        b.add_dependency("T-001");
+        list.add_task(a);
+        list.add_task(b);
+
+        let lengths = list.critical_path_lengths();
+        assert_eq!(lengths.len(), 2);
+        assert!(list.next_ready(SchedulingStrategy::CriticalPathFirst).is_none());
+    }
+
+    #[test]
+    fn test_diff_stats_from_numstat() {
+        let output = "12\t3\tsrc/main.rs\n5\t0\tsrc/lib.rs\n-\t-\tassets/logo.png\n\n";
+        let stats = DiffStats::from_numstat(output);
+        assert_eq!(stats.added, 17);
+        assert_eq!(stats.removed, 3);
+        assert_eq!(stats.files, vec!["src/main.rs", "src/lib.rs", "assets/logo.png"]);
+        assert_eq!(stats.to_string(), "17 lines added, 3 removed across 3 files");
+    }
+
+    #[test]
+    fn test_total_diff_stats() {
+        let mut list = TaskList::new("Test", "rust");
+        list.add_task(Task::new("TASK-001", "First", "Desc", 1));
+        list.add_task(Task::new("TASK-002", "Second", "Desc", 1));
+        assert!(list.total_diff_stats().is_none());
+
+        list.set_diff_stats("TASK-001", DiffStats::from_numstat("10\t2\tsrc/main.rs")).unwrap();
+        list.set_diff_stats("TASK-002", DiffStats::from_numstat("4\t1\tsrc/main.rs\n3\t0\tsrc/cli.rs")).unwrap();
+        assert!(list.set_diff_stats("TASK-999", DiffStats::default()).is_err());
+
+        let total = list.total_diff_stats().unwrap();
+        assert_eq!(total.added, 17);
+        assert_eq!(total.removed, 3);
+        assert_eq!(total.files_changed(), 2);
+    }
+
+    #[test]
+    fn test_attempt_stats_cover_completed_tasks() {
+        let mut list = TaskList::new("Test", "rust");
+        assert_eq!(list.attempt_stats(), (0.0, 0, String::new()));
+
+        let tasks = [("T-001", 1, true), ("T-007", 5, true), ("T-008", 5, true), ("T-009", 9, false)];
+        for (id, attempts, done) in tasks {
+            let mut task = Task::new(id, id, "Desc", 1);
+            task.attempts = attempts;
+            if done {
+                task.complete(None);
+            }
+            list.add_task(task);
+        }
+        let (avg, max, worst) = list.attempt_stats();
+        assert!((avg - 11.0 / 3.0).abs() < 1e-9);
+        assert_eq!((max, worst.as_str()), (5, "T-007"));
+    }
 }
@@ -13,10 +13,12 @@
 use crate::agents::{ArchitectAgent, CompletionStatus, PrdAgent, RalphLoopAgent};
 use crate::models::{DesignDocument, PrdDocument, RalphConfig, TaskList};
 use crate::output::RalphOutput;
+use crate::pause::PauseControl;
 use crate::telemetry::{
     architect_design_span, log_completion, log_error, prd_generation_span, start_timing,
 };
 use crate::{RalphError, Result};
+use crate::warnings::{Warning, WarningCategory};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use tracing::{info, instrument, warn};
@@ -103,6 +105,8 @@ pub struct RalphOrchestrator {
     state: OrchestratorState,
     /// Output handler for human-readable progress
     output: RalphOutput,
+    /// Pauses the implementation loop between tasks
+    pause_control: PauseControl,
 }
 
 impl std::fmt::Debug for RalphOrchestrator {
@@ -121,7 +125,7 @@ impl RalphOrchestrator {
         config.validate()?;
 
         let project_path = PathBuf::from(&config.project_path);
-        let output = RalphOutput::new(config.debug_level);
+        let output = RalphOutput::for_config(&config);
 
         // Create project directory if it doesn't exist
         if !project_path.exists() {
@@ -140,6 +144,7 @@ impl RalphOrchestrator {
             project_path,
             state: OrchestratorState::default(),
             output,
+            pause_control: PauseControl::new(),
         })
     }
 
@@ -168,6 +173,13 @@ impl RalphOrchestrator {
         &self.project_path
     }
 
+    /// Get a handle that pauses and resumes the implementation loop.
+    ///
+    /// Clone it before awaiting the pipeline to pause from another task.
+    pub fn pause_control(&self) -> PauseControl {
+        self.pause_control.clone()
+    }
+
     /// Check if a PRD file already exists.
     pub fn prd_exists(&self) -> bool {
         self.project_path.join(&self.config.prd_path).exists()
@@ -294,10 +306,16 @@ impl RalphOrchestrator {
             .design_path(&self.config.design_path)
             .tasks_path(&self.config.tasks_path)
             .project_path(&self.project_path)
+            .structure_limits(self.config.structure_limits())
+            .coverage_strict(self.config.coverage_strict)
+            .allow_empty_plan(self.config.allow_empty_plan)
+            .output(self.output.clone())
             .build()
             .await?;
 
-        let (design, tasks) = architect.generate().await?;
+        // Show the reply as it streams in at Verbose
+        let output = &self.output;
+        let (design, tasks) = architect.generate_with_callback(|text| output.llm_text(text)).await?;
 
         self.output.status(&format!(
             "Saved design to {}, tasks to {}",
@@ -319,6 +337,78 @@ impl RalphOrchestrator {
     }
 
 
+    /// Send failed tasks back to the Architect Agent for a corrected design.
+    ///
+    /// `task_ids` selects the failed tasks; when empty, every blocked task is
+    /// used. Completed tasks are kept, and design.md and tasks.json are
+    /// rewritten with the revised plan.
+    #[instrument(skip(self), fields(phase = "design"))]
+    pub async fn run_replan(&mut self, task_ids: &[String]) -> Result<(DesignDocument, TaskList)> {
+        let tasks_path = self.project_path.join(&self.config.tasks_path);
+        let tasks = TaskList::load(&tasks_path).map_err(RalphError::Task)?;
+
+        let failures: Vec<(String, String)> = if task_ids.is_empty() {
+            tasks
+                .get_all_tasks()
+                .into_iter()
+                .filter(|t| t.is_blocked())
+                .map(|t| (t.id.clone(), t.failure_summary()))
+                .collect()
+        } else {
+            task_ids
+                .iter()
+                .map(|id| {
+                    tasks
+                        .get_task(id)
+                        .map(|t| (t.id.clone(), t.failure_summary()))
+                        .ok_or_else(|| RalphError::Task(format!("Task not found: {}", id)))
+                })
+                .collect::<Result<_>>()?
+        };
+        if failures.is_empty() {
+            return Err(RalphError::Task(
+                "No blocked tasks to replan from; name the failed tasks explicitly".to_string(),
+            ));
+        }
+
+        self.output.phase("Replanning Design");
+        for (id, summary) in &failures {
+            self.output.list_item(&format!("{}: {}", id, summary));
+        }
+
+        let architect = ArchitectAgent::builder()
+            .model_config(self.config.agents.architect_model.clone())
+            .prd_path(&self.config.prd_path)
+            .design_path(&self.config.design_path)
+            .tasks_path(&self.config.tasks_path)
+            .project_path(&self.project_path)
+            .structure_limits(self.config.structure_limits())
+            .coverage_strict(self.config.coverage_strict)
+            .allow_empty_plan(self.config.allow_empty_plan)
+            .output(self.output.clone())
+            .build()
+            .await?;
+
+        let (design, new_tasks) = architect.replan(&tasks, &failures).await?;
+
+        let stats = new_tasks.get_stats();
+        self.output.phase_complete(&format!(
+            "Revised design saved; {} of {} tasks already complete",
+            stats.completed, stats.total
+        ));
+        info!(
+            failed = failures.len(),
+            task_count = stats.total,
+            "Design replanned from failures"
+        );
+
+        self.state.design = Some(design.clone());
+        self.state.tasks = Some(new_tasks.clone());
+        self.state.phase = PipelinePhase::Implementation;
+
+        Ok((design, new_tasks))
+    }
+
     /// Run the implementation phase (Ralph Loop Agent).
     ///
     /// This phase:
@@ -337,10 +427,19 @@ impl RalphOrchestrator {
             ));
         }
 
+        // Project files and the design should agree on the language
+        if let Some(tech) = self.state.design.as_ref().and_then(|d| d.technology_stack.as_ref()) {
+            let detected = crate::language::detect_language(&self.project_path);
+            if let Some(warning) = crate::language::language_mismatch(&tech.language, detected) {
+                self.output.warn_about(Warning::new(WarningCategory::Language, warning));
+            }
+        }
+
         // Create and run the Ralph Loop Agent
         let ralph_loop = RalphLoopAgent::builder()
             .config(self.config.clone())
             .project_path(&self.project_path)
+            .pause_control(self.pause_control.clone())
             .build()
             .await?;
 
@@ -400,6 +499,7 @@ impl RalphOrchestrator {
     pub async fn run(&mut self, prompt: &str) -> Result<CompletionStatus> {
         info!(prompt = prompt, "Starting Ralph pipeline");
         let _timing = start_timing("full_pipeline");
+        let _flush = self.output.flush_on_drop();
 
         // Phase 1: Requirements
         self.output.phase("Phase 1: Requirements Generation");
@@ -457,6 +557,7 @@ impl RalphOrchestrator {
     /// This is useful for resuming after a failure or interruption.
     pub async fn resume(&mut self, prompt: &str) -> Result<CompletionStatus> {
         info!(phase = %self.state.phase, "Resuming pipeline");
+        let _flush = self.output.flush_on_drop();
 
         match self.state.phase {
             PipelinePhase::Requirements => self.run(prompt).await,
@@ -474,17 +575,8 @@ impl RalphOrchestrator {
             }
             PipelinePhase::Implementation => {
                 // Load design and tasks if not in state
-                if self.state.design.is_none() {
-                    let design_path = self.project_path.join(&self.config.design_path);
-                    let design = DesignDocument::load_markdown(&design_path)
-                        .map_err(RalphError::Design)?;
-                    self.state.design = Some(design);
-                }
-                if self.state.tasks.is_none() {
-                    let tasks_path = self.project_path.join(&self.config.tasks_path);
-                    let tasks = TaskList::load(&tasks_path)
-                        .map_err(RalphError::Task)?;
-                    self.state.tasks = Some(tasks);
+                if self.state.design.is_none() || self.state.tasks.is_none() {
+                    self.load_checkpoint()?;
                 }
                 
                 self.run_implementation_phase().await
@@ -498,6 +590,79 @@ impl RalphOrchestrator {
         }
     }
 
+    /// Continue an interrupted run from tasks.json (`ralph --resume`),
+    /// without regenerating the PRD or the design.
+    ///
+    /// Completed tasks stay completed and count in the final summary.
+    pub async fn resume_from_checkpoint(&mut self) -> Result<CompletionStatus> {
+        self.state.design = None;
+        self.state.tasks = None;
+        self.state.phase = PipelinePhase::Implementation;
+        self.resume("").await
+    }
+
+    /// Load design.md and tasks.json to continue implementing them.
+    ///
+    /// Tasks the interrupted run left in progress go back to pending (their
+    /// attempts stay counted) and tasks.json is saved. A PRD edited after
+    /// tasks.json was written is reported, since the plan may not match it.
+    fn load_checkpoint(&mut self) -> Result<()> {
+        let tasks_path = self.project_path.join(&self.config.tasks_path);
+        let design_path = self.project_path.join(&self.config.design_path);
+        if !tasks_path.exists() {
+            return Err(RalphError::Task(format!(
+                "Nothing to resume: {} does not exist",
+                tasks_path.display()
+            )));
+        }
+        if !design_path.exists() {
+            return Err(RalphError::Design(format!(
+                "Cannot resume: {} does not exist. Run `ralph resume --phase design` to plan again.",
+                design_path.display()
+            )));
+        }
+
+        let design = DesignDocument::load_markdown(&design_path).map_err(RalphError::Design)?;
+        let mut tasks = TaskList::load(&tasks_path).map_err(RalphError::Task)?;
+
+        let prd_path = self.project_path.join(&self.config.prd_path);
+        let modified = |path: &std::path::Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        let prd_is_newer = match (modified(&prd_path), modified(&tasks_path)) {
+            (Some(prd), Some(plan)) => prd > plan,
+            _ => false,
+        };
+        if prd_is_newer {
+            self.output.warn(&format!(
+                "{} changed after {} was written; the plan may not match it \
+                 (`ralph resume --phase design` plans again)",
+                self.config.prd_path, self.config.tasks_path
+            ));
+        }
+
+        let interrupted = tasks.reset_interrupted();
+        if !interrupted.is_empty() {
+            tasks.save(&tasks_path).map_err(RalphError::Task)?;
+        }
+        let stats = tasks.get_stats();
+        self.output.status(&format!(
+            "Resuming from {}: {}/{} tasks completed",
+            self.config.tasks_path, stats.completed, stats.total
+        ));
+        for id in &interrupted {
+            self.output.list_item(&format!("{} was interrupted and starts over", id));
+        }
+        info!(
+            completed = stats.completed,
+            total = stats.total,
+            interrupted = interrupted.len(),
+            "Loaded checkpoint"
+        );
+
+        self.state.design = Some(design);
+        self.state.tasks = Some(tasks);
+        Ok(())
+    }
+
     /// Skip to a specific phase.
     ///
     /// This is useful for testing or when you want to start from a specific phase
@@ -649,4 +814,39 @@ mod tests {
         // Cleanup
         let _ = std::fs::remove_dir_all(&tmp_dir);
     }
+
+    #[test]
+    fn test_load_checkpoint_restarts_interrupted_tasks() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = RalphConfig::default();
+        config.project_path = dir.path().to_string_lossy().to_string();
+        let tasks_path = dir.path().join(&config.tasks_path);
+        let mut orchestrator = RalphOrchestrator::new(config.clone()).unwrap();
+
+        let error = orchestrator.load_checkpoint().unwrap_err();
+        assert!(error.to_string().contains("Nothing to resume"), "{}", error);
+
+        let mut tasks = TaskList::new("demo", "rust");
+        tasks.add_task(crate::models::Task::new("T-001", "First", "", 1));
+        tasks.add_task(crate::models::Task::new("T-002", "Second", "", 2));
+        tasks.add_task(crate::models::Task::new("T-003", "Third", "", 3));
+        tasks.complete_task("T-001", None).unwrap();
+        tasks.get_task_mut("T-002").unwrap().start();
+        tasks.save(&tasks_path).unwrap();
+        let error = orchestrator.load_checkpoint().unwrap_err();
+        assert!(error.to_string().contains("design"), "{}", error);
+
+        DesignDocument::new("demo", "A demo.")
+            .save_markdown(dir.path().join(&config.design_path))
+            .unwrap();
+        orchestrator.load_checkpoint().unwrap();
+
+        let saved = TaskList::load(&tasks_path).unwrap();
+        let interrupted = saved.get_task("T-002").unwrap();
+        assert_eq!(interrupted.status, crate::models::TaskStatus::Pending);
+        assert_eq!(interrupted.attempts, 1);
+        assert!(saved.get_task("T-001").unwrap().is_completed());
+        assert_eq!(orchestrator.state.tasks.as_ref().unwrap().get_stats().completed, 1);
+        assert!(orchestrator.state.design.is_some());
+    }
 }
@@ -2,6 +2,8 @@
 //!
 //! This module provides debug-level-aware output through ADK callbacks.
 //! The callbacks are designed to be added to agents for clean, configurable logging.
+//! All output is written by one writer thread, so concurrent emitters never
+//! interleave mid-line (see the ordering notes below).
 //!
 //! ## Debug Levels
 //!
@@ -9,18 +11,502 @@
 //! - `Normal`: Human-readable progress (default) - shows task progress, phase changes
 //! - `Verbose`: Detailed output with tool calls and responses
 //! - `Debug`: Full debug output with all internal state
+//!
+//! ## Consoles
+//!
+//! What the terminal can show is decided once, when the first stdout handle is
+//! created (see [`crate::console`]). Legacy consoles get ASCII symbols and no
+//! color; redirected output gets progress as periodic plain lines instead of
+//! carriage-return redraws.
+//!
+//! ## Events
+//!
+//! With an [`OutputSink::Jsonl`] or [`OutputSink::JsonlStdout`] sink, phases,
+//! tasks, tool calls and results, errors and the final summary are also
+//! written as one JSON object per line, for CI and other programs to parse.
+//! Every line carries `ts` (RFC 3339, UTC) and `type`; task events carry
+//! `task_id`. Events are written at every debug level and flushed per line.
+//! With `human_output` off in the configuration, only errors are printed
+//! besides the events, so `--output json-only` leaves stdout to the events.
 
-use crate::models::DebugLevel;
+use crate::console::{self, ConsoleCapabilities, ConsoleStream, Glyphs, Palette};
+use crate::models::{DebugLevel, DiffStats, RalphConfig, TaskList};
+use crate::cleanup::CleanupReport;
+use crate::preview::RunPreview;
+use crate::progress::{MultiProgressView, ProgressSnapshot, STATUS_INTERVAL};
+use crate::run_state::TaskProgress;
+use crate::textfmt::truncate_display;
+use crate::timefmt;
+use crate::warnings::{self, Warning, WarningCategory};
 use adk_rust::Part;
 use colored::Colorize;
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+// =============================================================================
+// Output writer
+// =============================================================================
+//
+// Every `RalphOutput` method renders its complete text up front and sends it as
+// one message to a single writer thread, which is the only code that touches
+// the terminal. Ordering guarantees:
+//
+// - Messages sent through one handle (and its clones) from one task or thread
+//   are written in the order they were sent.
+// - Messages from different tasks interleave only at message boundaries: a
+//   message is never split, so lines are never mixed mid-way.
+// - `flush` returns only after everything sent before it has been written.
+//
+// The writer also owns the progress bookkeeping: the single-line progress bar
+// and the multi-line view are cleared before regular output and the bar is
+// redrawn after it, so messages never land on a progress line. Where redraws
+// are not possible (redirected output, legacy consoles) the bar is printed as
+// a plain line instead, at most once per interval and always when complete.
+//
+// With buffering on, regular output is flushed at most every
+// `FLUSH_INTERVAL` (and whenever the writer goes idle that long) rather than
+// after every message. Progress bars, spinners and `flush` still flush at
+// once, so animations keep moving and nothing is held back at exit.
+
+/// Longest buffered output waits before it is flushed.
+const FLUSH_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Destination of rendered output, owned by the writer thread.
+pub trait OutputWriter: Send + 'static {
+    /// Write text to standard output.
+    fn write_stdout(&mut self, text: &str);
+
+    /// Write text to standard error.
+    fn write_stderr(&mut self, text: &str);
+
+    /// Flush buffered text.
+    fn flush(&mut self) {}
+
+    /// Whether output goes to a terminal (enables in-place redraws).
+    fn is_terminal(&self) -> bool {
+        false
+    }
+}
+
+/// The process's stdout and stderr.
+///
+/// Stdout goes through a [`std::io::BufWriter`], so writes between flushes
+/// reach the terminal in as few syscalls as possible; stderr is unbuffered.
+#[derive(Debug)]
+pub struct StdWriter {
+    stdout: std::io::BufWriter<std::io::Stdout>,
+    /// Send regular output to stderr, leaving stdout to JSON events
+    to_stderr: bool,
+}
+
+impl Default for StdWriter {
+    fn default() -> Self {
+        Self {
+            stdout: std::io::BufWriter::new(std::io::stdout()),
+            to_stderr: false,
+        }
+    }
+}
+
+impl StdWriter {
+    /// Write everything to stderr, for when stdout carries JSON events.
+    pub fn stderr_only() -> Self {
+        Self {
+            to_stderr: true,
+            ..Self::default()
+        }
+    }
+}
+
+impl OutputWriter for StdWriter {
+    fn write_stdout(&mut self, text: &str) {
+        if self.to_stderr {
+            let _ = std::io::stderr().write_all(text.as_bytes());
+        } else {
+            let _ = self.stdout.write_all(text.as_bytes());
+        }
+    }
+
+    fn write_stderr(&mut self, text: &str) {
+        let _ = std::io::stderr().write_all(text.as_bytes());
+    }
+
+    fn flush(&mut self) {
+        let _ = self.stdout.flush();
+        let _ = std::io::stderr().flush();
+    }
+
+    fn is_terminal(&self) -> bool {
+        use std::io::IsTerminal;
+        if self.to_stderr {
+            std::io::stderr().is_terminal()
+        } else {
+            std::io::stdout().is_terminal()
+        }
+    }
+}
+
+/// A message to the writer thread.
+enum OutputMessage {
+    /// Complete text for stdout
+    Stdout(String),
+    /// Complete text for stderr
+    Stderr(String),
+    /// Draw the single-line progress bar; `done` ends it with a newline
+    ProgressLine { text: String, done: bool },
+    /// Remove the progress bar or multi-line view from the screen
+    ClearProgress,
+    /// Switch to the multi-line progress view, drawn in the given palette
+    EnableMultiProgress(&'static Palette),
+    /// Update the multi-line progress view
+    Progress(ProgressSnapshot),
+    /// Redraw the multi-line view if other output removed it
+    RefreshProgress,
+    /// Batch regular output and flush it periodically (or after every message)
+    SetBuffered(bool),
+    /// Acknowledge once everything before this message is written
+    Flush(mpsc::Sender<()>),
+}
+
+/// State owned by the writer thread.
+struct WriterState<W: OutputWriter> {
+    writer: W,
+    capabilities: ConsoleCapabilities,
+    /// Progress bar currently on the last line
+    progress_line: Option<String>,
+    /// Last progress printed as a plain line, when redraws are unavailable
+    plain_progress: Option<(String, Instant)>,
+    view: Option<MultiProgressView>,
+    /// Flush periodically instead of after every message
+    buffered: bool,
+    /// When the oldest unflushed output was written
+    unflushed: Option<Instant>,
+}
+
+impl<W: OutputWriter> WriterState<W> {
+    fn run(mut self, rx: mpsc::Receiver<OutputMessage>) {
+        loop {
+            let message = match self.unflushed {
+                // Wake up in time to flush buffered output even if nothing else arrives
+                Some(since) => {
+                    let wait = FLUSH_INTERVAL.saturating_sub(since.elapsed());
+                    match rx.recv_timeout(wait) {
+                        Ok(message) => message,
+                        Err(mpsc::RecvTimeoutError::Timeout) => {
+                            self.flush();
+                            continue;
+                        }
+                        Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                    }
+                }
+                None => match rx.recv() {
+                    Ok(message) => message,
+                    Err(_) => break,
+                },
+            };
+            self.handle(message);
+            self.flush_if_due();
+        }
+        self.flush();
+    }
+
+    fn write_stdout(&mut self, text: &str) {
+        self.writer.write_stdout(text);
+        self.unflushed.get_or_insert_with(Instant::now);
+    }
+
+    fn flush(&mut self) {
+        self.writer.flush();
+        self.unflushed = None;
+    }
+
+    /// Flush now unless buffering and the oldest output is still fresh.
+    fn flush_if_due(&mut self) {
+        if let Some(since) = self.unflushed {
+            if !self.buffered || since.elapsed() >= FLUSH_INTERVAL {
+                self.flush();
+            }
+        }
+    }
+
+    fn handle(&mut self, message: OutputMessage) {
+        match message {
+            OutputMessage::Stdout(text) => {
+                self.clear_progress(false);
+                self.write_stdout(&text);
+                self.redraw_progress_line();
+            }
+            OutputMessage::Stderr(text) => {
+                self.clear_progress(false);
+                self.flush();
+                self.writer.write_stderr(&text);
+                self.redraw_progress_line();
+                self.flush();
+            }
+            OutputMessage::ProgressLine { text, done } if !self.capabilities.redraw => {
+                self.plain_progress_line(text, done);
+            }
+            OutputMessage::ProgressLine { text, done } => {
+                self.write_stdout(&format!("\r{}", text));
+                if done {
+                    self.write_stdout("\n");
+                    self.progress_line = None;
+                } else {
+                    self.progress_line = Some(text);
+                }
+                self.flush();
+            }
+            OutputMessage::ClearProgress => self.clear_progress(true),
+            OutputMessage::EnableMultiProgress(palette) => {
+                if self.view.is_none() {
+                    let tty = self.writer.is_terminal() && self.capabilities.redraw;
+                    self.view = Some(
+                        MultiProgressView::with_tty(tty)
+                            .with_glyphs(self.capabilities.glyphs())
+                            .with_palette(palette),
+                    );
+                }
+            }
+            OutputMessage::Progress(snapshot) => {
+                if let Some(view) = self.view.as_mut() {
+                    let mut frame = Vec::new();
+                    view.update(&snapshot, &mut frame);
+                    self.write_stdout(&String::from_utf8_lossy(&frame));
+                    self.flush();
+                }
+            }
+            OutputMessage::RefreshProgress => {
+                if let Some(view) = self.view.as_mut() {
+                    let mut frame = Vec::new();
+                    view.refresh(&mut frame);
+                    if !frame.is_empty() {
+                        self.write_stdout(&String::from_utf8_lossy(&frame));
+                        self.flush();
+                    }
+                }
+            }
+            OutputMessage::SetBuffered(buffered) => self.buffered = buffered,
+            OutputMessage::Flush(ack) => {
+                self.flush();
+                let _ = ack.send(());
+            }
+        }
+    }
+
+    /// Take progress off the screen; `forget` drops the bar instead of redrawing it later.
+    fn clear_progress(&mut self, forget: bool) {
+        if self.progress_line.is_some() {
+            self.write_stdout(&format!("\r{}\r", " ".repeat(80)));
+            if forget {
+                self.progress_line = None;
+            }
+        }
+        if let Some(view) = self.view.as_mut() {
+            let mut frame = Vec::new();
+            view.finish(&mut frame);
+            if !frame.is_empty() {
+                self.write_stdout(&String::from_utf8_lossy(&frame));
+            }
+        }
+        if forget {
+            self.flush();
+        }
+    }
+
+    /// Print progress as its own line if it changed and the interval elapsed (or it is done).
+    fn plain_progress_line(&mut self, text: String, done: bool) {
+        let text = text.trim_end().to_string();
+        let now = Instant::now();
+        let due = match &self.plain_progress {
+            None => true,
+            Some((last, _)) if *last == text => false,
+            Some((_, at)) => done || now.duration_since(*at) >= STATUS_INTERVAL,
+        };
+        if due {
+            self.write_stdout(&format!("{}\n", text));
+            self.flush();
+            self.plain_progress = Some((text, now));
+        }
+        if done {
+            self.plain_progress = None;
+        }
+    }
+
+    /// Put the progress bar back on the last line after other output.
+    fn redraw_progress_line(&mut self) {
+        if let Some(text) = self.progress_line.clone() {
+            self.write_stdout(&text);
+        }
+    }
+}
+
+/// Sending side of a writer thread.
+#[derive(Debug, Clone)]
+struct OutputChannel {
+    tx: mpsc::Sender<OutputMessage>,
+}
+
+impl OutputChannel {
+    /// Start a writer thread that owns `writer` and draws within `capabilities`.
+    fn spawn<W: OutputWriter>(writer: W, capabilities: ConsoleCapabilities) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let state = WriterState {
+            writer,
+            capabilities,
+            progress_line: None,
+            plain_progress: None,
+            view: None,
+            buffered: false,
+            unflushed: None,
+        };
+        std::thread::Builder::new()
+            .name("ralph-output".to_string())
+            .spawn(move || state.run(rx))
+            .expect("failed to start output writer thread");
+        Self { tx }
+    }
+
+    fn send(&self, message: OutputMessage) {
+        // The writer only stops once every sender is gone, so this cannot fail in practice
+        let _ = self.tx.send(message);
+    }
+
+    fn flush(&self) {
+        let (ack_tx, ack_rx) = mpsc::channel();
+        self.send(OutputMessage::Flush(ack_tx));
+        let _ = ack_rx.recv();
+    }
+}
+
+/// Writer shared by every handle created with [`RalphOutput::new`].
+static STDOUT_CHANNEL: OnceLock<OutputChannel> = OnceLock::new();
+
+fn stdout_channel() -> &'static OutputChannel {
+    STDOUT_CHANNEL
+        .get_or_init(|| OutputChannel::spawn(StdWriter::default(), console::capabilities()))
+}
+
+/// Writer shared by every handle whose stdout carries JSON events.
+static STDERR_CHANNEL: OnceLock<OutputChannel> = OnceLock::new();
+
+fn stderr_channel() -> &'static OutputChannel {
+    STDERR_CHANNEL
+        .get_or_init(|| OutputChannel::spawn(StdWriter::stderr_only(), console::capabilities()))
+}
+
+/// Wait until all output sent to the terminal so far has been written.
+///
+/// Call before exiting the process, and before printing directly with
+/// `println!` after using a [`RalphOutput`].
+pub fn flush_output() {
+    for channel in [&STDOUT_CHANNEL, &STDERR_CHANNEL] {
+        if let Some(channel) = channel.get() {
+            channel.flush();
+        }
+    }
+}
+
+// =============================================================================
+// Events
+// =============================================================================
+
+/// Where machine-readable events go, besides the human-readable output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutputSink {
+    /// Human-readable output only
+    Console,
+    /// JSON lines appended to a file
+    Jsonl(PathBuf),
+    /// JSON lines on stdout; human-readable output moves to stderr
+    JsonlStdout,
+}
+
+impl OutputSink {
+    /// The sink for an `event_log` setting: a path, `-` for stdout, or none.
+    pub fn from_event_log(event_log: Option<&str>) -> Self {
+        match event_log {
+            None | Some("") => Self::Console,
+            Some("-") => Self::JsonlStdout,
+            Some(path) => Self::Jsonl(PathBuf::from(path)),
+        }
+    }
+
+    /// Where human-readable output goes alongside this sink.
+    pub fn console_stream(&self) -> ConsoleStream {
+        match self {
+            Self::JsonlStdout => ConsoleStream::Stderr,
+            Self::Console | Self::Jsonl(_) => ConsoleStream::Stdout,
+        }
+    }
+}
+
+/// Open destination of JSON events, shared by a handle and its clones.
+#[derive(Debug, Clone)]
+enum EventSink {
+    Stdout,
+    File(Arc<Mutex<std::fs::File>>),
+}
+
+impl EventSink {
+    /// Write one event as a single line and flush it, so a tailing reader
+    /// never sees half an event.
+    fn write(&self, event: &serde_json::Value) {
+        let mut line = event.to_string();
+        line.push('\n');
+        match self {
+            Self::Stdout => {
+                let mut stdout = std::io::stdout().lock();
+                let _ = stdout.write_all(line.as_bytes());
+                let _ = stdout.flush();
+            }
+            Self::File(file) => {
+                let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+                let _ = file.write_all(line.as_bytes());
+                let _ = file.flush();
+            }
+        }
+    }
+}
+
+/// Flushes its output handle when dropped, including on early `?` returns.
+#[derive(Debug)]
+pub struct FlushOnDrop(RalphOutput);
+
+impl Drop for FlushOnDrop {
+    fn drop(&mut self) {
+        self.0.flush();
+    }
+}
+
+// =============================================================================
+// RalphOutput
+// =============================================================================
 
 /// Output handler that respects debug levels.
 ///
 /// This struct provides methods to create ADK callbacks that output
-/// information based on the configured debug level.
+/// information based on the configured debug level. Handles are cheap to
+/// clone; all clones send to the same writer.
 #[derive(Debug, Clone)]
 pub struct RalphOutput {
     level: DebugLevel,
+    /// Syntax-color tool JSON at Verbose and above
+    color_json: bool,
+    /// Two-line view of the overall bar and current task, replacing the single progress bar
+    multi_progress: bool,
+    /// Symbols the console can show
+    glyphs: &'static Glyphs,
+    /// Styles for each kind of output
+    palette: &'static Palette,
+    channel: OutputChannel,
+    /// Destination of JSON events, if any
+    events: Option<EventSink>,
+    /// Print human-readable output; errors are printed either way
+    human: bool,
 }
 
 impl Default for RalphOutput {
@@ -31,8 +517,196 @@ impl Default for RalphOutput {
 
 impl RalphOutput {
     /// Create a new output handler with the specified debug level.
+    ///
+    /// Console capabilities and the palette are detected on first use unless
+    /// [`RalphOutput::for_config`] (or [`console::init`] and
+    /// [`console::init_palette`]) ran first.
     pub fn new(level: DebugLevel) -> Self {
-        Self { level }
+        Self {
+            level,
+            color_json: false,
+            multi_progress: false,
+            glyphs: console::capabilities().glyphs(),
+            palette: console::palette(),
+            channel: stdout_channel().clone(),
+            events: None,
+            human: true,
+        }
+    }
+
+    /// Create an output handler from configuration, applying its console
+    /// overrides (`color`, `unicode`, `palette`) before the first output.
+    ///
+    /// With `event_log` set, events are written there as well; if the log
+    /// cannot be opened the run continues with a warning. With `human_output`
+    /// off, only errors are printed.
+    pub fn for_config(config: &RalphConfig) -> Self {
+        let sink = OutputSink::from_event_log(config.event_log.as_deref());
+        console::init_on(sink.console_stream(), config.color, config.unicode);
+        console::init_palette(config.palette);
+        let mut output = Self::new(config.debug_level).with_color_json(config.color_json);
+        if sink == OutputSink::JsonlStdout {
+            output.channel = stderr_channel().clone();
+        }
+        output.human = config.human_output;
+        let output = output.buffered(config.buffered_output);
+        match output.clone().with_sink(sink) {
+            Ok(output) => output,
+            Err(e) => {
+                output.warn(&format!(
+                    "Cannot write events to {}: {}",
+                    config.event_log.as_deref().unwrap_or_default(),
+                    e
+                ));
+                output
+            }
+        }
+    }
+
+    /// Create an output handler with its own writer thread and destination.
+    pub fn with_writer<W: OutputWriter>(level: DebugLevel, writer: W) -> Self {
+        Self::with_console(level, writer, ConsoleCapabilities::FULL)
+    }
+
+    /// Create an output handler with its own writer, drawing within `capabilities`.
+    pub fn with_console<W: OutputWriter>(
+        level: DebugLevel,
+        writer: W,
+        capabilities: ConsoleCapabilities,
+    ) -> Self {
+        Self {
+            level,
+            color_json: false,
+            multi_progress: false,
+            glyphs: capabilities.glyphs(),
+            palette: &Palette::DEFAULT,
+            channel: OutputChannel::spawn(writer, capabilities),
+            events: None,
+            human: true,
+        }
+    }
+
+    /// Also write machine-readable events to `sink`.
+    ///
+    /// A [`OutputSink::Jsonl`] file is created if needed and appended to. For
+    /// [`OutputSink::JsonlStdout`], use [`RalphOutput::for_config`] (or a
+    /// writer that does not print to stdout) so human-readable output goes to
+    /// stderr instead of mixing with the events.
+    pub fn with_sink(mut self, sink: OutputSink) -> std::io::Result<Self> {
+        self.events = match sink {
+            OutputSink::Console => None,
+            OutputSink::JsonlStdout => Some(EventSink::Stdout),
+            OutputSink::Jsonl(path) => {
+                if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                    std::fs::create_dir_all(parent)?;
+                }
+                let file = std::fs::OpenOptions::new().create(true).append(true).open(&path)?;
+                Some(EventSink::File(Arc::new(Mutex::new(file))))
+            }
+        };
+        Ok(self)
+    }
+
+    /// Write one event to the sink, if any, with its timestamp and name first.
+    fn event(&self, name: &str, fields: serde_json::Value) {
+        let Some(events) = &self.events else {
+            return;
+        };
+        let mut event = serde_json::Map::new();
+        event.insert("ts".to_string(), timefmt::now().into());
+        event.insert("type".to_string(), name.into());
+        if let serde_json::Value::Object(fields) = fields {
+            event.extend(fields);
+        }
+        events.write(&serde_json::Value::Object(event));
+    }
+
+    /// Draw output in `palette` instead of the process's [`console::palette`].
+    ///
+    /// Call before [`RalphOutput::with_multi_progress`] so the progress view
+    /// uses the same palette.
+    pub fn with_palette(mut self, palette: &'static Palette) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    /// Enable syntax-colored JSON for tool calls and responses.
+    ///
+    /// Falls back to plain rendering when color is disabled (e.g. `NO_COLOR`).
+    pub fn with_color_json(mut self, enabled: bool) -> Self {
+        self.color_json = enabled;
+        self
+    }
+
+    /// Show an overall bar plus a spinner line for the task in progress
+    /// instead of the single-line progress bar (shown at Normal and above).
+    ///
+    /// Without a terminal, structured status lines are printed instead.
+    pub fn with_multi_progress(mut self, enabled: bool) -> Self {
+        self.multi_progress = enabled && self.level.is_normal() && self.human;
+        if self.multi_progress {
+            self.channel.send(OutputMessage::EnableMultiProgress(self.palette));
+        }
+        self
+    }
+
+    /// Batch regular output and flush it every few milliseconds instead of
+    /// after every message.
+    ///
+    /// Applies to the writer this handle shares with its clones (for
+    /// [`RalphOutput::new`], every stdout handle). Progress updates, errors
+    /// and [`RalphOutput::flush`] still flush immediately.
+    pub fn buffered(self, enabled: bool) -> Self {
+        self.channel.send(OutputMessage::SetBuffered(enabled));
+        self
+    }
+
+    /// Wait until everything sent through this handle has been written.
+    pub fn flush(&self) {
+        self.channel.flush();
+    }
+
+    /// Guard that flushes this handle when it goes out of scope.
+    pub fn flush_on_drop(&self) -> FlushOnDrop {
+        FlushOnDrop(self.clone())
+    }
+
+    /// Update task progress from the task store.
+    ///
+    /// Uses the two-line view when enabled, otherwise the single progress bar.
+    pub fn task_progress(&self, list: &TaskList) {
+        if self.multi_progress {
+            self.channel
+                .send(OutputMessage::Progress(ProgressSnapshot::from_task_list(list)));
+        } else {
+            let stats = list.get_stats();
+            self.progress_bar(stats.completed, stats.total);
+        }
+    }
+
+    /// Redraw the multi-line progress view after other output.
+    pub fn refresh_progress(&self) {
+        if self.multi_progress {
+            self.channel.send(OutputMessage::RefreshProgress);
+        }
+    }
+
+    /// Send complete text to stdout.
+    fn out(&self, text: String) {
+        if !self.human {
+            return;
+        }
+        self.channel.send(OutputMessage::Stdout(text));
+    }
+
+    /// Send one line to stdout.
+    fn line(&self, line: String) {
+        self.out(line + "\n");
+    }
+
+    /// Whether tool JSON should be syntax-colored right now.
+    fn use_color_json(&self) -> bool {
+        self.color_json && colored::control::SHOULD_COLORIZE.should_colorize()
     }
 
     /// Get the current debug level.
@@ -46,46 +720,49 @@ impl RalphOutput {
 
     /// Print a phase header (shown at Normal and above).
     pub fn phase(&self, name: &str) {
+        self.event("phase", serde_json::json!({ "name": name }));
         if self.level.is_normal() {
-            println!("\n{} {}", "▶".bright_cyan(), name.bright_white().bold());
+            self.line(format!("\n{} {}", self.palette.accent(self.glyphs.phase), self.palette.heading(name)));
         }
     }
 
     /// Print a status message within a phase (shown at Normal and above).
     pub fn status(&self, message: &str) {
         if self.level.is_normal() {
-            println!("  {} {}", "•".bright_black(), message);
+            self.line(format!("  {} {}", self.palette.dim(self.glyphs.bullet), message));
         }
     }
 
     /// Print a phase completion message (shown at Normal and above).
     pub fn phase_complete(&self, message: &str) {
         if self.level.is_normal() {
-            println!("  {} {}", "✓".bright_green(), message.green());
+            self.line(format!("  {} {}", self.palette.success(self.glyphs.success), self.palette.success(message)));
         }
     }
 
     /// Print a list item (shown at Normal and above).
     pub fn list_item(&self, message: &str) {
         if self.level.is_normal() {
-            println!("    {} {}", "─".bright_black(), message);
+            self.line(format!("    {} {}", self.palette.dim(self.glyphs.dash), message));
         }
     }
 
     /// Print a task start message (shown at Normal and above).
     pub fn task_start(&self, task_id: &str, title: &str) {
+        self.event("task_start", serde_json::json!({ "task_id": task_id, "title": title }));
         if self.level.is_normal() {
-            println!("  {} {} - {}", "→".bright_blue(), task_id.cyan(), title);
+            self.line(format!("  {} {} - {}", self.palette.info(self.glyphs.arrow), self.palette.accent(task_id), title));
         }
     }
 
     /// Print a task completion message (shown at Normal and above).
     pub fn task_complete(&self, task_id: &str, success: bool) {
+        self.event("task_complete", serde_json::json!({ "task_id": task_id, "success": success }));
         if self.level.is_normal() {
             if success {
-                println!("  {} {} completed", "✓".bright_green(), task_id.green());
+                self.line(format!("  {} {} completed", self.palette.success(self.glyphs.success), self.palette.success(task_id)));
             } else {
-                println!("  {} {} failed", "✗".bright_red(), task_id.red());
+                self.line(format!("  {} {} failed", self.palette.failure(self.glyphs.failure), self.palette.failure(task_id)));
             }
         }
     }
@@ -93,138 +770,135 @@ impl RalphOutput {
     /// Print iteration progress (shown at Normal and above).
     pub fn iteration(&self, current: u32, max: usize) {
         if self.level.is_normal() {
-            println!(
+            self.line(format!(
                 "  {} iteration {}/{}",
-                "○".bright_black(),
+                self.palette.dim(self.glyphs.pending),
                 current,
                 max
-            );
+            ));
         }
     }
 
     /// Print a progress bar for task completion (shown at Normal and above).
     ///
-    /// Displays: `[████████░░░░░░░░░░░░] 40% (4/10 tasks)`
+    /// Displays: `[████████░░░░░░░░░░░░] 40% (4/10 tasks)`, or `[########------------]` on
+    /// consoles without Unicode.
     pub fn progress_bar(&self, completed: usize, total: usize) {
-        if !self.level.is_normal() || total == 0 {
+        if !self.level.is_normal() || total == 0 || self.multi_progress || !self.human {
             return;
         }
 
-        let percentage = (completed * 100) / total;
+        // Clamped, so a stale count can never draw more than a full bar
+        let progress = TaskProgress { completed, total };
+        let percentage = progress.percentage();
         let bar_width = 30;
-        let filled = (completed * bar_width) / total;
+        let filled = progress.filled(bar_width);
         let empty = bar_width - filled;
 
         let bar = format!(
             "{}{}",
-            "█".repeat(filled).bright_green(),
-            "░".repeat(empty).bright_black()
-        );
-
-        // Use carriage return to update in place
-        print!(
-            "\r  [{}] {}% ({}/{} tasks)  ",
-            bar,
-            percentage,
-            completed,
-            total
+            self.palette.success(self.glyphs.bar_filled.repeat(filled)),
+            self.palette.dim(self.glyphs.bar_empty.repeat(empty))
         );
-        
-        // Flush to ensure it displays
-        use std::io::Write;
-        let _ = std::io::stdout().flush();
 
-        // Print newline when complete
-        if completed == total {
-            println!();
-        }
+        // Redrawn in place; ends with a newline when complete
+        self.channel.send(OutputMessage::ProgressLine {
+            text: format!(
+                "  [{}] {}% ({}/{} tasks)  ",
+                bar,
+                percentage,
+                progress.completed_clamped(),
+                total
+            ),
+            done: progress.is_done(),
+        });
     }
 
     /// Print a progress bar with a task name (shown at Normal and above).
     pub fn progress_bar_with_task(&self, completed: usize, total: usize, current_task: &str) {
-        if !self.level.is_normal() || total == 0 {
+        if !self.level.is_normal() || total == 0 || self.multi_progress || !self.human {
             return;
         }
 
-        let percentage = (completed * 100) / total;
+        let progress = TaskProgress { completed, total };
+        let percentage = progress.percentage();
         let bar_width = 20;
-        let filled = (completed * bar_width) / total;
+        let filled = progress.filled(bar_width);
         let empty = bar_width - filled;
 
         let bar = format!(
             "{}{}",
-            "█".repeat(filled).bright_green(),
-            "░".repeat(empty).bright_black()
+            self.palette.success(self.glyphs.bar_filled.repeat(filled)),
+            self.palette.dim(self.glyphs.bar_empty.repeat(empty))
         );
 
-        // Truncate task name if too long
-        let task_display = if current_task.len() > 30 {
-            format!("{}...", &current_task[..27])
-        } else {
-            current_task.to_string()
-        };
-
-        // Use carriage return to update in place
-        print!(
-            "\r  [{}] {}% │ {}  ",
-            bar,
-            percentage,
-            task_display.cyan()
-        );
-        
-        // Flush to ensure it displays
-        use std::io::Write;
-        let _ = std::io::stdout().flush();
+        let task_display = truncate_display(current_task, 30);
 
-        // Print newline when complete
-        if completed == total {
-            println!();
-        }
+        // Redrawn in place; ends with a newline when complete
+        self.channel.send(OutputMessage::ProgressLine {
+            text: format!("  [{}] {}% {} {}  ", bar, percentage, self.glyphs.separator, self.palette.accent(task_display)),
+            done: progress.is_done(),
+        });
     }
 
     /// Clear the current line (for progress bar updates).
     pub fn clear_line(&self) {
         if self.level.is_normal() {
-            print!("\r{}\r", " ".repeat(80));
-            use std::io::Write;
-            let _ = std::io::stdout().flush();
+            self.channel.send(OutputMessage::ClearProgress);
         }
     }
 
     /// Print a tool call (shown at Verbose and above).
     pub fn tool_call(&self, name: &str, args: &serde_json::Value) {
+        self.event("tool_call", serde_json::json!({ "tool": name, "args": args }));
         if self.level.is_verbose() {
-            println!(
-                "\n  {} {}",
-                "🔧".bright_blue(),
-                name.bright_white().bold()
-            );
-            if let Ok(pretty) = serde_json::to_string_pretty(args) {
+            let mut text = format!("\n  {} {}\n", self.palette.info(self.glyphs.tool), self.palette.heading(name));
+            if self.use_color_json() {
+                for line in colorize_json(args, self.palette).lines() {
+                    let _ = writeln!(text, "     {}", line);
+                }
+            } else if let Ok(pretty) = serde_json::to_string_pretty(args) {
                 for line in pretty.lines() {
-                    println!("     {}", line.bright_black());
+                    let _ = writeln!(text, "     {}", self.palette.dim(line));
                 }
             }
+            self.out(text);
         }
     }
 
     /// Print a tool response (shown at Verbose and above).
     pub fn tool_response(&self, name: &str, response: &serde_json::Value) {
-        if self.level.is_verbose() {
+        self.event("tool_result", serde_json::json!({ "tool": name, "response": response }));
+        let mut text = String::new();
+        if self.level.is_verbose() && self.use_color_json() {
+            let _ = writeln!(text, "     {} {} response:", self.palette.success(self.glyphs.back_arrow), self.palette.success(name));
+            let colored = colorize_json(response, self.palette);
+            let total = colored.lines().count();
+            for line in colored.lines().take(COLOR_JSON_MAX_LINES) {
+                let _ = writeln!(text, "       {}", line);
+            }
+            if total > COLOR_JSON_MAX_LINES {
+                let _ = writeln!(
+                    text,
+                    "       {}",
+                    self.palette.dim(format!("{} {} more lines", self.glyphs.ellipsis, total - COLOR_JSON_MAX_LINES))
+                );
+            }
+        } else if self.level.is_verbose() {
             let resp_str = serde_json::to_string(response).unwrap_or_default();
-            let display = if resp_str.len() > 300 {
-                format!("{}...", &resp_str[..300])
-            } else {
-                resp_str
-            };
-            println!("     {} {}", "←".green(), display.bright_black());
+            let display = truncate_display(&resp_str, 300);
+            let _ = writeln!(text, "     {} {}", self.palette.success(self.glyphs.back_arrow), self.palette.dim(display));
         } else if self.level.is_debug() {
-            println!("     {} {} response:", "←".green(), name.green());
+            let _ = writeln!(text, "     {} {} response:", self.palette.success(self.glyphs.back_arrow), self.palette.success(name));
             if let Ok(pretty) = serde_json::to_string_pretty(response) {
                 for line in pretty.lines() {
-                    println!("       {}", line.bright_black());
+                    let _ = writeln!(text, "       {}", self.palette.dim(line));
                 }
             }
         }
+        if !text.is_empty() {
+            self.out(text);
+        }
     }
 
     /// Print a concise tool result summary at Normal level.
@@ -236,6 +910,8 @@ impl RalphOutput {
             return; // Verbose already shows full response via tool_response()
         }
 
+        let mut text = String::new();
+
         match name {
             "test" => {
                 if let Some(results) = response.get("results") {
@@ -245,18 +921,20 @@ impl RalphOutput {
                     let all_passed = results.get("all_passed").and_then(|v| v.as_bool()).unwrap_or(false);
 
                     if all_passed {
-                        println!(
+                        let _ = writeln!(
+                            text,
                             "    {} Tests passed: {} passed{}",
-                            "✓".bright_green(),
-                            passed.to_string().green(),
+                            self.palette.success(self.glyphs.success),
+                            self.palette.success(passed.to_string()),
                             if skipped > 0 { format!(", {} skipped", skipped) } else { String::new() }
                         );
                     } else {
-                        println!(
+                        let _ = writeln!(
+                            text,
                             "    {} Tests failed: {} passed, {} failed{}",
-                            "✗".bright_red(),
+                            self.palette.failure(self.glyphs.failure),
                             passed,
-                            failed.to_string().red(),
+                            self.palette.failure(failed.to_string()),
                             if skipped > 0 { format!(", {} skipped", skipped) } else { String::new() }
                         );
                         // Show a snippet of stderr if tests failed
@@ -270,14 +948,14 @@ impl RalphOutput {
                                 .take(3)
                                 .collect();
                             for line in error_lines {
-                                let trimmed = if line.len() > 100 { &line[..100] } else { line };
-                                println!("      {} {}", "│".bright_red(), trimmed.bright_black());
+                                let trimmed = truncate_display(line, 100);
+                                let _ = writeln!(text, "      {} {}", self.palette.failure(self.glyphs.separator), self.palette.dim(trimmed));
                             }
                         }
                     }
                 } else if let Some(msg) = response.get("message").and_then(|v| v.as_str()) {
                     // Fallback: detect/check operations
-                    println!("    {} {}", "ℹ".bright_blue(), msg.bright_black());
+                    let _ = writeln!(text, "    {} {}", self.palette.info(self.glyphs.info), self.palette.dim(msg));
                 }
             }
             "git" => {
@@ -286,22 +964,24 @@ impl RalphOutput {
                         "commit" => {
                             if let Some(hash) = response.get("commit_hash").and_then(|v| v.as_str()) {
                                 let msg = response.get("message").and_then(|v| v.as_str()).unwrap_or("");
-                                let short_msg = if msg.len() > 50 { &msg[..50] } else { msg };
-                                println!(
+                                let short_msg = truncate_display(msg, 50);
+                                let _ = writeln!(
+                                    text,
                                     "    {} Committed {} \"{}\"",
-                                    "✓".bright_green(),
-                                    hash[..7.min(hash.len())].bright_black(),
+                                    self.palette.success(self.glyphs.success),
+                                    self.palette.dim(&hash[..7.min(hash.len())]),
                                     short_msg
                                 );
                             } else {
-                                println!("    {} Committed", "✓".bright_green());
+                                let _ = writeln!(text, "    {} Committed", self.palette.success(self.glyphs.success));
                             }
                         }
                         "add" => {
                             if let Some(files) = response.get("files").and_then(|v| v.as_array()) {
-                                println!(
+                                let _ = writeln!(
+                                    text,
                                     "    {} Staged {} file(s)",
-                                    "✓".bright_green(),
+                                    self.palette.success(self.glyphs.success),
                                     files.len()
                                 );
                             }
@@ -316,11 +996,12 @@ impl RalphOutput {
                 if let Some(false) = response.get("success").and_then(|v| v.as_bool()) {
                     if let Some(op) = response.get("operation").and_then(|v| v.as_str()) {
                         let path = response.get("path").and_then(|v| v.as_str()).unwrap_or("unknown");
-                        println!(
+                        let _ = writeln!(
+                            text,
                             "    {} Failed to {} {}",
-                            "✗".bright_red(),
+                            self.palette.failure(self.glyphs.failure),
                             op,
-                            path.bright_black()
+                            self.palette.dim(path)
                         );
                     }
                 }
@@ -329,14 +1010,14 @@ impl RalphOutput {
                 let success = response.get("success").and_then(|v| v.as_bool()).unwrap_or(false);
                 if let Some(cmd) = response.get("command").and_then(|v| v.as_str()) {
                     if success {
-                        println!("    {} `{}` succeeded", "✓".bright_green(), cmd.bright_black());
+                        let _ = writeln!(text, "    {} `{}` succeeded", self.palette.success(self.glyphs.success), self.palette.dim(cmd));
                     } else {
-                        println!("    {} `{}` failed", "✗".bright_red(), cmd.bright_black());
+                        let _ = writeln!(text, "    {} `{}` failed", self.palette.failure(self.glyphs.failure), self.palette.dim(cmd));
                         if let Some(stderr) = response.get("stderr").and_then(|v| v.as_str()) {
                             let last_lines: Vec<&str> = stderr.lines().rev().take(2).collect();
                             for line in last_lines.iter().rev() {
-                                let trimmed = if line.len() > 100 { &line[..100] } else { line };
-                                println!("      {} {}", "│".bright_red(), trimmed.bright_black());
+                                let trimmed = truncate_display(line, 100);
+                                let _ = writeln!(text, "      {} {}", self.palette.failure(self.glyphs.separator), self.palette.dim(trimmed));
                             }
                         }
                     }
@@ -347,68 +1028,134 @@ impl RalphOutput {
                 if let Some(task) = response.get("task") {
                     let id = task.get("id").and_then(|v| v.as_str()).unwrap_or("?");
                     let title = task.get("title").and_then(|v| v.as_str()).unwrap_or("");
-                    println!(
+                    let _ = writeln!(
+                        text,
                         "    {} Next: {} - {}",
-                        "→".bright_blue(),
-                        id.cyan(),
+                        self.palette.info(self.glyphs.arrow),
+                        self.palette.accent(id),
                         title
                     );
                 } else if let Some(true) = response.get("all_complete").and_then(|v| v.as_bool()) {
-                    println!("    {} All tasks complete", "✓".bright_green());
+                    let _ = writeln!(text, "    {} All tasks complete", self.palette.success(self.glyphs.success));
                 } else if let Some(blocked) = response.get("blocked_count").and_then(|v| v.as_u64()) {
-                    println!(
+                    let _ = writeln!(
+                        text,
                         "    {} {} task(s) blocked",
-                        "⚠".bright_yellow(),
+                        self.palette.warning(self.glyphs.warning),
                         blocked
                     );
                 }
             }
             _ => {}
         }
+        if !text.is_empty() {
+            self.out(text);
+        }
     }
 
     /// Print LLM text output (shown at Verbose and above).
     pub fn llm_text(&self, text: &str) {
         if self.level.is_verbose() && !text.trim().is_empty() {
-            println!("\n  {} {}", "💭".bright_magenta(), text.trim());
+            self.line(format!("\n  {} {}", self.palette.info(self.glyphs.thought), text.trim()));
         }
     }
 
+    /// Print a model part that is neither text nor a tool call.
+    ///
+    /// Code execution and inline data are shown at Verbose; part types Ralph
+    /// does not know are named at Debug, so no model output vanishes silently.
+    pub fn other_part(&self, part: &serde_json::Value) {
+        let kind = classify_part(part);
+        tracing::debug!(part = ?kind, "Model returned a non-text part");
+        if !self.level.is_verbose() {
+            return;
+        }
+        let mut text = String::new();
+        match kind {
+            OtherPart::CodeResult { outcome, output } => {
+                let arrow = self.palette.success(self.glyphs.back_arrow);
+                let _ = writeln!(text, "     {} code execution ({}):", arrow, outcome);
+                for line in output.lines().take(COLOR_JSON_MAX_LINES) {
+                    let _ = writeln!(text, "       {}", self.palette.dim(line));
+                }
+            }
+            OtherPart::ExecutableCode { language, code } => {
+                let _ = writeln!(text, "  {} running {}:", self.palette.accent(self.glyphs.arrow), language);
+                for line in code.lines().take(COLOR_JSON_MAX_LINES) {
+                    let _ = writeln!(text, "       {}", self.palette.dim(line));
+                }
+            }
+            OtherPart::InlineData { mime_type, bytes } => {
+                let _ = writeln!(
+                    text,
+                    "  {} inline data: {} ({} bytes)",
+                    self.glyphs.bullet, mime_type, bytes
+                );
+            }
+            OtherPart::Unknown(name) => {
+                self.debug("part", &format!("unhandled model part: {}", name));
+                return;
+            }
+        }
+        self.out(text);
+    }
+
     /// Print debug information (shown at Debug only).
     pub fn debug(&self, context: &str, message: &str) {
         if self.level.is_debug() {
-            println!(
+            self.line(format!(
                 "  {} [{}] {}",
-                "🐛".bright_yellow(),
-                context.bright_black(),
+                self.palette.warning(self.glyphs.debug),
+                self.palette.dim(context),
                 message
-            );
+            ));
         }
     }
 
     /// Print an error (always shown).
+    ///
+    /// Waits until the error is written so it is not lost if the process exits.
     pub fn error(&self, message: &str) {
-        eprintln!("{} {}", "✗ Error:".bright_red().bold(), message);
+        self.event("error", serde_json::json!({ "message": message }));
+        self.channel.send(OutputMessage::Stderr(format!(
+            "{} {}\n",
+            self.palette.failure(format!("{} Error:", self.glyphs.failure)).bold(),
+            message
+        )));
+        self.flush();
     }
 
-    /// Print a warning (shown at Normal and above).
+    /// Print a warning (shown at Normal and above) and record it for the
+    /// end-of-run summary.
     pub fn warn(&self, message: &str) {
+        self.warn_about(Warning::new(WarningCategory::Other, message));
+    }
+
+    /// Print a categorized warning (shown at Normal and above) and record it
+    /// for the end-of-run summary.
+    pub fn warn_about(&self, warning: Warning) {
         if self.level.is_normal() {
-            println!("{} {}", "⚠".bright_yellow(), message.yellow());
+            self.line(format!(
+                "{} {}",
+                self.palette.warning(self.glyphs.warning),
+                self.palette.warning(&warning.message)
+            ));
         }
+        warnings::record(warning);
     }
 
     /// Print success message (always shown).
     pub fn success(&self, message: &str) {
-        println!("{} {}", "✓".bright_green(), message.green());
+        self.line(format!("{} {}", self.palette.success(self.glyphs.success), self.palette.success(message)));
     }
 
     /// Print the startup banner (shown at Normal and above).
     pub fn banner(&self) {
         if self.level.is_normal() {
-            println!(
-                "{}",
-                r#"
+            self.out(format!(
+                "{}\n{}\n\n",
+                self.palette.accent(
+                    r#"
   ____       _       _     
  |  _ \ __ _| |_ __ | |__  
  | |_) / _` | | '_ \| '_ \ 
@@ -416,46 +1163,292 @@ impl RalphOutput {
  |_| \_\__,_|_| .__/|_| |_|
               |_|          
 "#
-                .cyan()
-            );
-            println!(
-                "{}",
-                "Multi-Agent Autonomous Development System".bright_white()
-            );
-            println!();
+                ),
+                self.palette.heading("Multi-Agent Autonomous Development System")
+            ));
         }
     }
 
     /// Print final summary (always shown except minimal only shows status).
-    pub fn summary(&self, iterations: u32, tasks_completed: usize, tasks_total: usize, success: bool) {
+    pub fn summary(
+        &self,
+        iterations: u32,
+        tasks_completed: usize,
+        tasks_total: usize,
+        elapsed: Duration,
+        success: bool,
+    ) {
+        self.event(
+            "summary",
+            serde_json::json!({
+                "iterations": iterations,
+                "tasks_completed": tasks_completed,
+                "tasks_total": tasks_total,
+                "elapsed_ms": elapsed.as_millis() as u64,
+                "success": success,
+            }),
+        );
+        let elapsed = timefmt::duration(elapsed);
         if self.level.is_minimal() {
             // Minimal: just the result
             if success {
-                println!("✓ Complete: {}/{} tasks", tasks_completed, tasks_total);
+                self.line(format!(
+                    "{} Complete: {}/{} tasks in {}",
+                    self.glyphs.success, tasks_completed, tasks_total, elapsed
+                ));
             } else {
-                println!("✗ Incomplete: {}/{} tasks in {} iterations", tasks_completed, tasks_total, iterations);
+                self.line(format!(
+                    "{} Incomplete: {}/{} tasks in {} iterations ({})",
+                    self.glyphs.failure, tasks_completed, tasks_total, iterations, elapsed
+                ));
             }
         } else {
             // Normal and above: formatted summary
-            println!();
-            println!("{}", "─".repeat(50).bright_black());
-            if success {
-                println!(
-                    "{} {} tasks completed in {} iterations",
-                    "✓".bright_green(),
-                    tasks_completed.to_string().green(),
-                    iterations
-                );
+            let rule = self.palette.dim(self.glyphs.dash.repeat(50));
+            let result = if success {
+                format!(
+                    "{} {} tasks completed in {} iterations ({})",
+                    self.palette.success(self.glyphs.success),
+                    self.palette.success(tasks_completed.to_string()),
+                    iterations,
+                    elapsed
+                )
             } else {
-                println!(
-                    "{} {}/{} tasks completed in {} iterations",
-                    "⚠".bright_yellow(),
+                format!(
+                    "{} {}/{} tasks completed in {} iterations ({})",
+                    self.palette.warning(self.glyphs.warning),
                     tasks_completed,
                     tasks_total,
-                    iterations
+                    iterations,
+                    elapsed
+                )
+            };
+            self.out(format!("\n{}\n{}\n{}\n", rule, result, rule));
+        }
+    }
+
+    /// Print the time spent inside tasks next to the run's wall-clock time,
+    /// e.g. "42m in 11 timed tasks of 50m wall clock" (shown at Normal and above).
+    pub fn timing_summary(&self, task_time: Duration, timed_tasks: usize, wall_clock: Duration) {
+        if self.level.is_normal() && timed_tasks > 0 {
+            self.line(format!(
+                "  {} {} in {} timed task(s) of {} wall clock",
+                self.palette.dim(self.glyphs.bullet),
+                timefmt::duration(task_time),
+                timed_tasks,
+                timefmt::duration(wall_clock)
+            ));
+        }
+    }
+
+    /// Print the code footprint of the run, e.g. "847 lines added across 12 files" (shown at Normal and above).
+    pub fn diff_summary(&self, stats: &DiffStats) {
+        if self.level.is_normal() {
+            self.line(format!(
+                "  {} {} lines added across {} files ({} removed)",
+                self.palette.dim(self.glyphs.plus_minus),
+                self.palette.success(stats.added.to_string()),
+                stats.files_changed(),
+                self.palette.failure(stats.removed.to_string())
+            ));
+        }
+    }
+
+    /// Print a run preview: each task in execution order with the files it
+    /// declares it creates (`+`) and modifies (`~`), then the tasks that would
+    /// never run (always shown).
+    pub fn run_preview(&self, preview: &RunPreview) {
+        let mut text = format!(
+            "{} Run preview: {} tasks, {} files",
+            self.palette.accent(self.glyphs.phase),
+            preview.steps.len(),
+            preview.files().len()
+        );
+        let mut finished = Vec::new();
+        if preview.already_done > 0 {
+            finished.push(format!("{} already done", preview.already_done));
+        }
+        if preview.blocked > 0 {
+            finished.push(format!("{} blocked", preview.blocked));
+        }
+        if !finished.is_empty() {
+            let _ = write!(text, " ({})", finished.join(", "));
+        }
+        text.push('\n');
+
+        for (i, step) in preview.steps.iter().enumerate() {
+            let _ = write!(text, "  {:>3}. {} {}", i + 1, self.palette.accent(step.task_id), step.title);
+            if step.resumed {
+                let _ = write!(text, " {}", self.palette.warning("(resumed)"));
+            } else if !step.dependencies.is_empty() {
+                let _ = write!(text, " {}", self.palette.dim(format!("(after {})", step.dependencies.join(", "))));
+            }
+            text.push('\n');
+            for file in &step.creates {
+                let _ = writeln!(text, "       {} {}", self.palette.success("+"), file);
+            }
+            for file in &step.modifies {
+                let _ = writeln!(text, "       {} {}", self.palette.warning("~"), file);
+            }
+            if step.creates.is_empty() && step.modifies.is_empty() {
+                let _ = writeln!(text, "       {}", self.palette.dim("no files declared"));
+            }
+        }
+
+        for task in &preview.unreachable {
+            let _ = writeln!(
+                text,
+                "  {} {} never runs: waits on {}",
+                self.palette.warning(self.glyphs.warning),
+                self.palette.warning(task.task_id),
+                task.waiting_on.join(", ")
+            );
+        }
+        self.out(text);
+    }
+
+    /// Print the warnings section: one count line per run, plus every warning
+    /// when `expand` is set or at Verbose and above (shown at Normal and above,
+    /// only when there are any).
+    pub fn warning_summary(&self, warnings: &[Warning], expand: bool) {
+        if !self.level.is_normal() || warnings.is_empty() {
+            return;
+        }
+        let mut text = format!(
+            "  {} {}\n",
+            self.palette.warning(self.glyphs.warning),
+            warnings::summary(warnings)
+        );
+        if expand || self.level.is_verbose() {
+            let dash = self.palette.dim(self.glyphs.dash);
+            for warning in warnings.iter().take(WARNINGS_SHOWN) {
+                let _ = writeln!(text, "    {} {}", dash, warning);
+            }
+            if warnings.len() > WARNINGS_SHOWN {
+                let _ = writeln!(
+                    text,
+                    "    {} {} {} more",
+                    dash,
+                    self.glyphs.ellipsis,
+                    warnings.len() - WARNINGS_SHOWN
                 );
             }
-            println!("{}", "─".repeat(50).bright_black());
+        } else {
+            let _ = writeln!(text, "    {} RALPH_EXPAND_WARNINGS=true lists them", self.palette.dim(self.glyphs.dash));
+        }
+        self.out(text);
+    }
+
+    /// Print the cleanup section: unreferenced files, markers and empty files
+    /// the run left behind (shown at Normal and above, only when there are any).
+    pub fn cleanup_report(&self, report: &CleanupReport) {
+        if !self.level.is_normal() || report.is_empty() {
+            return;
+        }
+        let plural = |n: usize, word: &str| format!("{} {}{}", n, word, if n == 1 { "" } else { "s" });
+        let mut text = format!(
+            "  {} Cleanup: {}, {}, {}\n",
+            self.palette.warning(self.glyphs.warning),
+            plural(report.unreferenced.len(), "unreferenced file"),
+            plural(report.markers.len(), "marker"),
+            plural(report.empty.len(), "empty file")
+        );
+        let dash = self.palette.dim(self.glyphs.dash);
+        for file in &report.unreferenced {
+            let _ = writeln!(text, "    {} unreferenced: {}", dash, file);
+        }
+        for marker in report.markers.iter().take(CLEANUP_MARKERS_SHOWN) {
+            let _ = writeln!(text, "    {} {}", dash, marker);
+        }
+        if report.markers.len() > CLEANUP_MARKERS_SHOWN {
+            let _ = writeln!(
+                text,
+                "    {} {} {} more markers",
+                dash,
+                self.glyphs.ellipsis,
+                report.markers.len() - CLEANUP_MARKERS_SHOWN
+            );
+        }
+        for file in &report.empty {
+            let _ = writeln!(text, "    {} empty: {}", dash, file);
+        }
+        self.out(text);
+    }
+
+    /// Print how hard completed tasks were, e.g. "avg 1.8 attempts/task (max 5 on T-007)" (shown at Normal and above).
+    pub fn attempt_summary(&self, average: f64, max: u32, worst_task_id: &str) {
+        if self.level.is_normal() {
+            self.line(format!(
+                "  {} avg {:.1} attempts/task (max {} on {})",
+                self.palette.dim(self.glyphs.bullet),
+                average,
+                max,
+                worst_task_id
+            ));
+        }
+    }
+}
+
+/// Most markers listed in the cleanup section.
+const CLEANUP_MARKERS_SHOWN: usize = 10;
+
+/// Most warnings listed when the warnings section is expanded.
+const WARNINGS_SHOWN: usize = 50;
+
+/// Maximum lines of colorized tool response JSON shown at Verbose.
+const COLOR_JSON_MAX_LINES: usize = 60;
+
+/// Render JSON pretty-printed with keys, strings, numbers, booleans and null
+/// in distinct styles from `palette`.
+///
+/// Layout matches `serde_json::to_string_pretty`, so with color disabled the
+/// output is identical to the plain rendering.
+pub fn colorize_json(value: &serde_json::Value, palette: &Palette) -> String {
+    let mut out = String::new();
+    write_colored_json(value, palette, 0, &mut out);
+    out
+}
+
+fn write_colored_json(value: &serde_json::Value, palette: &Palette, indent: usize, out: &mut String) {
+    use serde_json::Value;
+
+    let pad = "  ".repeat(indent + 1);
+    let close_pad = "  ".repeat(indent);
+    match value {
+        Value::Null => out.push_str(&palette.dim("null").to_string()),
+        Value::Bool(b) => out.push_str(&palette.info(b.to_string()).to_string()),
+        Value::Number(n) => out.push_str(&palette.warning(n.to_string()).to_string()),
+        Value::String(_) => out.push_str(&palette.success(value.to_string()).to_string()),
+        Value::Array(items) if items.is_empty() => out.push_str("[]"),
+        Value::Array(items) => {
+            out.push_str("[\n");
+            for (i, item) in items.iter().enumerate() {
+                out.push_str(&pad);
+                write_colored_json(item, palette, indent + 1, out);
+                if i + 1 < items.len() {
+                    out.push(',');
+                }
+                out.push('\n');
+            }
+            out.push_str(&close_pad);
+            out.push(']');
+        }
+        Value::Object(map) if map.is_empty() => out.push_str("{}"),
+        Value::Object(map) => {
+            out.push_str("{\n");
+            for (i, (key, item)) in map.iter().enumerate() {
+                out.push_str(&pad);
+                let key = Value::String(key.clone()).to_string();
+                out.push_str(&palette.accent(key).to_string());
+                out.push_str(": ");
+                write_colored_json(item, palette, indent + 1, out);
+                if i + 1 < map.len() {
+                    out.push(',');
+                }
+                out.push('\n');
+            }
+            out.push_str(&close_pad);
+            out.push('}');
         }
     }
 }
@@ -477,13 +1470,225 @@ pub fn process_event_part(output: &RalphOutput, part: &Part) {
         Part::Text { text } => {
             output.llm_text(text);
         }
-        _ => {}
+        // Inline data, code execution and any part types added later
+        other => {
+            output.other_part(&serde_json::to_value(other).unwrap_or_default());
+        }
+    }
+}
+
+/// A part that is neither text nor a tool call, classified by its JSON shape.
+#[derive(Debug, PartialEq)]
+enum OtherPart<'a> {
+    /// Output of code the provider ran
+    CodeResult { outcome: &'a str, output: &'a str },
+    /// Code the provider is about to run
+    ExecutableCode { language: &'a str, code: &'a str },
+    /// Raw bytes such as an image or file
+    InlineData { mime_type: &'a str, bytes: usize },
+    /// Anything else, named by its tag or keys
+    Unknown(String),
+}
+
+/// Classify a serialized part, whether or not its variant is tagged.
+fn classify_part(value: &serde_json::Value) -> OtherPart<'_> {
+    let (tag, inner) = match value.as_object() {
+        Some(map) if map.len() == 1 && map.values().all(|v| v.is_object()) => {
+            let (tag, inner) = map.iter().next().unwrap();
+            (Some(tag.as_str()), inner)
+        }
+        _ => (None, value),
+    };
+    let field = |names: &[&str]| names.iter().find_map(|n| inner.get(*n)?.as_str());
+
+    if let Some(output) = field(&["output"]) {
+        return OtherPart::CodeResult {
+            outcome: field(&["outcome"]).unwrap_or("unknown outcome"),
+            output,
+        };
+    }
+    if let Some(code) = field(&["code"]) {
+        return OtherPart::ExecutableCode {
+            language: field(&["language"]).unwrap_or("code"),
+            code,
+        };
+    }
+    if let Some(mime_type) = field(&["mime_type", "mimeType"]) {
+        let bytes = match inner.get("data") {
+            Some(serde_json::Value::Array(bytes)) => bytes.len(),
+            // Base64: four characters per three bytes
+            Some(serde_json::Value::String(encoded)) => {
+                encoded.trim_end_matches('=').len() * 3 / 4
+            }
+            _ => 0,
+        };
+        return OtherPart::InlineData { mime_type, bytes };
     }
+    OtherPart::Unknown(match (tag, inner.as_object()) {
+        (Some(tag), _) => tag.to_string(),
+        (None, Some(map)) => map.keys().cloned().collect::<Vec<_>>().join(", "),
+        (None, None) => inner.to_string(),
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::PalettePreset;
+    use crate::console::lock_color_override;
+    use std::sync::{Arc, Mutex};
+
+    /// Remove ANSI escape sequences.
+    fn strip_ansi(text: &str) -> String {
+        let mut plain = String::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|c| *c == 'm');
+            } else {
+                plain.push(c);
+            }
+        }
+        plain
+    }
+
+    /// Captures both streams into one buffer.
+    #[derive(Clone, Default)]
+    struct BufferWriter(Arc<Mutex<String>>);
+
+    impl OutputWriter for BufferWriter {
+        fn write_stdout(&mut self, text: &str) {
+            self.0.lock().unwrap().push_str(text);
+        }
+
+        fn write_stderr(&mut self, text: &str) {
+            self.0.lock().unwrap().push_str(text);
+        }
+    }
+
+    /// Records writes and flushes in order.
+    #[derive(Clone, Default)]
+    struct EventWriter(Arc<Mutex<Vec<String>>>);
+
+    impl EventWriter {
+        fn flushes(&self) -> usize {
+            self.0.lock().unwrap().iter().filter(|e| *e == "<flush>").count()
+        }
+    }
+
+    impl OutputWriter for EventWriter {
+        fn write_stdout(&mut self, text: &str) {
+            self.0.lock().unwrap().push(text.to_string());
+        }
+
+        fn write_stderr(&mut self, text: &str) {
+            self.0.lock().unwrap().push(text.to_string());
+        }
+
+        fn flush(&mut self) {
+            self.0.lock().unwrap().push("<flush>".to_string());
+        }
+    }
+
+    #[test]
+    fn test_other_parts_are_classified_by_shape() {
+        let tagged = serde_json::json!({
+            "code_execution_result": { "outcome": "OUTCOME_OK", "output": "42\n" }
+        });
+        assert_eq!(
+            classify_part(&tagged),
+            OtherPart::CodeResult { outcome: "OUTCOME_OK", output: "42\n" }
+        );
+        let untagged = serde_json::json!({ "language": "PYTHON", "code": "print(6 * 7)" });
+        assert_eq!(
+            classify_part(&untagged),
+            OtherPart::ExecutableCode { language: "PYTHON", code: "print(6 * 7)" }
+        );
+        let image =
+            serde_json::json!({ "InlineData": { "mime_type": "image/png", "data": [1, 2, 3] } });
+        assert_eq!(
+            classify_part(&image),
+            OtherPart::InlineData { mime_type: "image/png", bytes: 3 }
+        );
+        let future = serde_json::json!({ "thought_signature": { "sig": "abc" } });
+        assert_eq!(classify_part(&future), OtherPart::Unknown("thought_signature".to_string()));
+    }
+
+    #[test]
+    fn test_other_parts_render_at_verbose_and_debug_only() {
+        let result = serde_json::json!({ "outcome": "OUTCOME_OK", "output": "42" });
+        let unknown = serde_json::json!({ "thought_signature": { "sig": "abc" } });
+        let render = |level| {
+            let buffer = BufferWriter::default();
+            let output = RalphOutput::with_writer(level, buffer.clone());
+            output.other_part(&result);
+            output.other_part(&unknown);
+            output.flush();
+            buffer.0.lock().unwrap().clone()
+        };
+
+        assert_eq!(render(DebugLevel::Normal), "");
+        let verbose = render(DebugLevel::Verbose);
+        assert!(verbose.contains("code execution (OUTCOME_OK)") && verbose.contains("42"));
+        assert!(!verbose.contains("thought_signature"));
+        assert!(render(DebugLevel::Debug).contains("unhandled model part: thought_signature"));
+    }
+
+    #[test]
+    fn test_run_preview_lists_steps_and_files() {
+        use crate::models::{Task, TaskStatus};
+
+        let mut list = TaskList::new("calc", "rust");
+        let mut done = Task::new("T-001", "Setup", "", 1);
+        done.status = TaskStatus::Completed;
+        list.add_task(done);
+        let mut parser = Task::new("T-002", "Parser", "", 1);
+        parser.add_dependency("T-001");
+        parser.files_created.push("src/parser.rs".to_string());
+        parser.files_modified.push("src/main.rs".to_string());
+        list.add_task(parser);
+        list.add_task(Task::new("T-003", "Docs", "", 2));
+        let mut orphan = Task::new("T-004", "Plugins", "", 1);
+        orphan.add_dependency("T-099");
+        list.add_task(orphan);
+
+        let buffer = BufferWriter::default();
+        let output = RalphOutput::with_writer(DebugLevel::Minimal, buffer.clone());
+        output.run_preview(&crate::preview::preview_run(&list, Default::default()));
+        output.flush();
+        let text = buffer.0.lock().unwrap().clone();
+
+        assert!(text.contains("Run preview: 2 tasks, 2 files (1 already done)"), "{}", text);
+        assert!(text.contains("src/parser.rs") && text.contains("src/main.rs"));
+        assert!(text.contains("(after T-001)"));
+        assert!(text.contains("no files declared"));
+        assert!(text.contains("never runs: waits on T-099"));
+        assert!(text.find("Parser").unwrap() < text.find("Docs").unwrap());
+    }
+
+    #[test]
+    fn test_warning_summary_counts_and_expands() {
+        let warnings = vec![
+            Warning::new(WarningCategory::FilePath, "Stripped project folder prefix").with_file("calc/src/main.rs"),
+            Warning::new(WarningCategory::Coverage, "No task creates src/db.rs"),
+            Warning::new(WarningCategory::FilePath, "Stripped project folder prefix").with_task("T-002"),
+        ];
+        let render = |expand: bool| {
+            let buffer = BufferWriter::default();
+            let output = RalphOutput::with_writer(DebugLevel::Normal, buffer.clone());
+            output.warning_summary(&warnings, expand);
+            output.warning_summary(&[], true);
+            output.flush();
+            buffer.0.lock().unwrap().clone()
+        };
+
+        let collapsed = render(false);
+        assert!(collapsed.contains("3 warnings (2 file-path, 1 coverage)"), "{}", collapsed);
+        assert!(!collapsed.contains("calc/src/main.rs"));
+        let expanded = render(true);
+        assert!(expanded.contains("[file-path] calc/src/main.rs Stripped project folder prefix"), "{}", expanded);
+        assert!(expanded.contains("[file-path] T-002 Stripped"));
+    }
 
     #[test]
     fn test_debug_level_checks() {
@@ -517,4 +1722,305 @@ mod tests {
         let output = RalphOutput::default();
         assert_eq!(output.level(), DebugLevel::Normal);
     }
+
+    #[test]
+    fn test_colorize_json() {
+        let value = serde_json::json!({
+            "name": "tasks",
+            "count": 3,
+            "ok": true,
+            "missing": null,
+            "items": [1, "two\n", {}],
+            "empty": []
+        });
+
+        let _guard = lock_color_override();
+        colored::control::set_override(false);
+        assert_eq!(colorize_json(&value, &Palette::DEFAULT), serde_json::to_string_pretty(&value).unwrap());
+
+        colored::control::set_override(true);
+        let colored = colorize_json(&value, &Palette::DEFAULT);
+        assert!(colored.contains("\x1b["));
+        assert!(colored.contains("two\\n"));
+        colored::control::unset_override();
+    }
+
+    /// Render the same states through every preset, with color forced on.
+    fn render_in_presets() -> Vec<(PalettePreset, String)> {
+        let _guard = lock_color_override();
+        colored::control::set_override(true);
+        let rendered = [PalettePreset::Default, PalettePreset::Light, PalettePreset::Colorblind, PalettePreset::Mono]
+            .into_iter()
+            .map(|preset| {
+                let buffer = BufferWriter::default();
+                let output = RalphOutput::with_writer(DebugLevel::Normal, buffer.clone())
+                    .with_palette(Palette::preset(preset));
+                output.task_start("T-001", "Parser");
+                output.task_complete("T-001", true);
+                output.task_complete("T-002", false);
+                output.warn("slow tests");
+                output.status("Compiling");
+                output.progress_bar(1, 2);
+                output.flush();
+                let text = buffer.0.lock().unwrap().clone();
+                (preset, text)
+            })
+            .collect();
+        colored::control::unset_override();
+        rendered
+    }
+
+    #[test]
+    fn test_palette_presets_render_through_writer() {
+        for (preset, text) in render_in_presets() {
+            match preset {
+                // Bright green and red
+                PalettePreset::Default => {
+                    assert!(text.contains("\x1b[92m") && text.contains("\x1b[91m"), "{}", text);
+                }
+                // No bright black, which is unreadable on white
+                PalettePreset::Light => {
+                    assert!(text.contains("\x1b[32m") && !text.contains("90m"), "{}", text);
+                }
+                // Blue and orange instead of green and red
+                PalettePreset::Colorblind => {
+                    assert!(text.contains("38;2;86;180;233m") && text.contains("38;2;230;159;0m"), "{}", text);
+                    assert!(!text.contains("92m") && !text.contains("91m"), "{}", text);
+                }
+                // Bold at most
+                PalettePreset::Mono => {
+                    let rest = text.replace("\x1b[1m", "").replace("\x1b[0m", "");
+                    assert!(!rest.contains('\x1b'), "{:?}", text);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_palette_presets_never_rely_on_color_alone() {
+        let glyphs = &Glyphs::UNICODE;
+        assert_ne!(glyphs.success, glyphs.failure);
+        for (preset, text) in render_in_presets() {
+            let plain = strip_ansi(&text);
+            assert!(plain.contains(&format!("{} T-001 completed", glyphs.success)), "{}: {}", preset, plain);
+            assert!(plain.contains(&format!("{} T-002 failed", glyphs.failure)), "{}: {}", preset, plain);
+            assert!(plain.contains(&format!("{} slow tests", glyphs.warning)), "{}: {}", preset, plain);
+            assert!(plain.contains("50% (1/2 tasks)"), "{}: {}", preset, plain);
+        }
+    }
+
+    #[test]
+    fn test_concurrent_emitters_never_interleave_mid_line() {
+        const EMITTERS: usize = 16;
+        const LINES: usize = 200;
+
+        let buffer = BufferWriter::default();
+        let output = RalphOutput::with_writer(DebugLevel::Normal, buffer.clone());
+
+        let handles: Vec<_> = (0..EMITTERS)
+            .map(|emitter| {
+                let output = output.clone();
+                std::thread::spawn(move || {
+                    for n in 0..LINES {
+                        output.status(&format!("emitter-{} line-{}", emitter, n));
+                        if n % 50 == 0 {
+                            output.progress_bar(n, LINES);
+                        }
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        output.flush();
+
+        let text = buffer.0.lock().unwrap().clone();
+        let mut next = vec![0usize; EMITTERS];
+        for line in text.split(['\n', '\r']).filter(|l| l.contains("emitter-")) {
+            // Exactly one message per line, and it is complete
+            assert_eq!(line.matches("emitter-").count(), 1, "interleaved line: {:?}", line);
+            let tail = &line[line.find("emitter-").unwrap() + "emitter-".len()..];
+            let (emitter, n) = tail.split_once(" line-").expect("truncated line");
+            let (emitter, n): (usize, usize) = (emitter.parse().unwrap(), n.trim_end().parse().unwrap());
+            // Each emitter's lines arrive in the order it sent them
+            assert_eq!(n, next[emitter], "emitter {} out of order", emitter);
+            next[emitter] += 1;
+        }
+        assert_eq!(next, vec![LINES; EMITTERS]);
+    }
+
+    #[test]
+    fn test_progress_bar_is_cleared_and_redrawn_around_output() {
+        let buffer = BufferWriter::default();
+        let output = RalphOutput::with_writer(DebugLevel::Normal, buffer.clone());
+        output.progress_bar(1, 4);
+        output.status("working");
+        output.clear_line();
+        output.status("done");
+        output.flush();
+
+        let text = buffer.0.lock().unwrap().clone();
+        let clear = format!("\r{}\r", " ".repeat(80));
+        let parts: Vec<&str> = text.split(clear.as_str()).collect();
+        // Bar, cleared for "working" and redrawn, then cleared for good before "done"
+        assert_eq!(parts.len(), 3);
+        assert!(parts[0].starts_with('\r') && parts[0].contains("25% (1/4 tasks)"));
+        assert!(parts[1].contains("working\n") && parts[1].ends_with("(1/4 tasks)  "));
+        assert!(parts[2].ends_with("done\n") && !parts[2].contains("tasks)"));
+    }
+
+    #[test]
+    fn test_plain_console_prints_progress_as_periodic_lines() {
+        let buffer = BufferWriter::default();
+        let output =
+            RalphOutput::with_console(DebugLevel::Normal, buffer.clone(), ConsoleCapabilities::PLAIN);
+        output.progress_bar(1, 4);
+        output.progress_bar(1, 4);
+        output.status("working");
+        // Within the interval: skipped
+        output.progress_bar(2, 4);
+        output.task_complete("T-004", true);
+        // Completion is always printed
+        output.progress_bar(4, 4);
+        output.flush();
+
+        let text = buffer.0.lock().unwrap().clone();
+        assert!(!text.contains('\r'));
+        assert!(text.is_ascii());
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 4, "{:?}", lines);
+        assert!(lines[0].contains("#######") && lines[0].ends_with("25% (1/4 tasks)"));
+        assert!(lines[1].contains("working"));
+        assert!(lines[2].contains("T-004") && lines[2].contains('+'));
+        assert!(lines[3].ends_with("100% (4/4 tasks)"));
+    }
+
+    #[test]
+    fn test_buffered_output_batches_flushes_but_not_progress() {
+        let unbuffered = EventWriter::default();
+        let output = RalphOutput::with_writer(DebugLevel::Debug, unbuffered.clone()).buffered(false);
+        for n in 0..100 {
+            output.debug("loop", &format!("line {}", n));
+        }
+        output.flush();
+        assert!(unbuffered.flushes() >= 100);
+
+        let buffered = EventWriter::default();
+        let output = RalphOutput::with_writer(DebugLevel::Debug, buffered.clone()).buffered(true);
+        for n in 0..100 {
+            output.debug("loop", &format!("line {}", n));
+        }
+        output.progress_bar(1, 4);
+        output.flush();
+
+        let events = buffered.0.lock().unwrap().clone();
+        assert_eq!(events.iter().filter(|e| e.contains("line ")).count(), 100);
+        assert!(buffered.flushes() < 100, "{} flushes", buffered.flushes());
+        // The progress bar is flushed as soon as it is drawn
+        let bar = events.iter().position(|e| e.contains("(1/4 tasks)")).unwrap();
+        assert_eq!(events[bar + 1], "<flush>");
+    }
+
+    #[test]
+    fn test_buffered_output_is_flushed_when_idle() {
+        let buffered = EventWriter::default();
+        let output = RalphOutput::with_writer(DebugLevel::Normal, buffered.clone()).buffered(true);
+        output.status("waiting");
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while buffered.flushes() == 0 && Instant::now() < deadline {
+            std::thread::sleep(FLUSH_INTERVAL);
+        }
+        let events = buffered.0.lock().unwrap().clone();
+        assert_eq!(events.last().map(String::as_str), Some("<flush>"));
+        assert!(events.iter().any(|e| e.contains("waiting")));
+    }
+
+    #[test]
+    fn test_jsonl_sink_writes_one_event_per_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logs").join("events.jsonl");
+        let buffer = BufferWriter::default();
+        let output = RalphOutput::with_writer(DebugLevel::Minimal, buffer.clone())
+            .with_sink(OutputSink::Jsonl(path.clone()))
+            .unwrap();
+
+        output.phase("Phase 3: Implementation");
+        output.task_start("T-001", "Parser");
+        output.tool_call("run_tests", &serde_json::json!({ "filter": "parser" }));
+        output.tool_response("run_tests", &serde_json::json!({ "passed": 3 }));
+        output.task_complete("T-001", false);
+        output.error("model unavailable");
+        output.summary(2, 0, 1, Duration::from_millis(1500), false);
+        output.flush();
+
+        // Events are on disk as soon as each call returns, at any debug level
+        let events: Vec<serde_json::Value> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let names: Vec<&str> = events.iter().map(|e| e["type"].as_str().unwrap()).collect();
+        assert_eq!(
+            names,
+            ["phase", "task_start", "tool_call", "tool_result", "task_complete", "error", "summary"]
+        );
+        for event in &events {
+            assert!(timefmt::parse_timestamp(event["ts"].as_str().unwrap()).is_some(), "{}", event);
+        }
+        assert_eq!(events[0]["name"], "Phase 3: Implementation");
+        assert_eq!(events[1]["task_id"], "T-001");
+        assert_eq!(events[1]["title"], "Parser");
+        assert_eq!(events[2]["tool"], "run_tests");
+        assert_eq!(events[2]["args"]["filter"], "parser");
+        assert_eq!(events[3]["response"]["passed"], 3);
+        assert_eq!(events[4]["task_id"], "T-001");
+        assert_eq!(events[4]["success"], false);
+        assert_eq!(events[5]["message"], "model unavailable");
+        assert_eq!(events[6]["tasks_total"], 1);
+        assert_eq!(events[6]["elapsed_ms"], 1500);
+
+        // Human-readable output is unchanged
+        let text = buffer.0.lock().unwrap().clone();
+        assert!(text.contains("model unavailable") && text.contains("Incomplete: 0/1"));
+    }
+
+    #[test]
+    fn test_output_sink_from_event_log() {
+        assert_eq!(OutputSink::from_event_log(None), OutputSink::Console);
+        assert_eq!(OutputSink::from_event_log(Some("-")), OutputSink::JsonlStdout);
+        assert_eq!(
+            OutputSink::from_event_log(Some("events.jsonl")),
+            OutputSink::Jsonl(PathBuf::from("events.jsonl"))
+        );
+        // Progress and color are decided for the stream the human output uses
+        assert_eq!(OutputSink::JsonlStdout.console_stream(), ConsoleStream::Stderr);
+        assert_eq!(OutputSink::Console.console_stream(), ConsoleStream::Stdout);
+    }
+
+    #[test]
+    fn test_human_output_off_prints_only_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.jsonl");
+        let buffer = BufferWriter::default();
+        let mut output = RalphOutput::with_writer(DebugLevel::Verbose, buffer.clone())
+            .with_sink(OutputSink::Jsonl(path.clone()))
+            .unwrap();
+        output.human = false;
+        let output = output.with_multi_progress(true);
+        output.phase("Phase 3: Implementation");
+        output.task_start("T-001", "Parser");
+        output.progress_bar(1, 2);
+        output.tool_call("run_tests", &serde_json::json!({}));
+        output.summary(1, 1, 2, Duration::from_secs(3), false);
+        output.error("model unavailable");
+        output.flush();
+
+        // Only the error reaches the writer; everything else is a JSON event
+        let text = strip_ansi(&buffer.0.lock().unwrap());
+        assert_eq!(text.trim(), "✗ Error: model unavailable");
+        let events = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(events.lines().count(), 5);
+    }
 }
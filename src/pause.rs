@@ -0,0 +1,152 @@
+//! Pausing and resuming the implementation loop from code.
+//!
+//! A [`PauseControl`] is a cheap handle shared between the loop and whoever
+//! drives it (an orchestration service, a deploy hook). [`PauseControl::pause`]
+//! only sets a flag: the loop checks it when the agent starts its next task,
+//! so the task in progress always finishes first. tasks.json is saved on
+//! every status change, so once the buffered journals are flushed the files
+//! on disk are a checkpoint another process may read or copy. The loop then
+//! waits without calling the model; its session is kept, and
+//! [`PauseControl::resume`] lets it start the task it was about to begin.
+//!
+//! This is the loop's lifecycle ([`LoopState`]); progress accounting over
+//! tasks.json is [`crate::run_state::RunState`].
+
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// Where the implementation loop is in its lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LoopState {
+    /// Starting tasks as usual (also before the loop starts)
+    #[default]
+    Running,
+    /// Waiting before the next task until resumed
+    Paused,
+    /// The loop has ended; pause and resume no longer apply
+    Finished,
+}
+
+impl std::fmt::Display for LoopState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoopState::Running => write!(f, "running"),
+            LoopState::Paused => write!(f, "paused"),
+            LoopState::Finished => write!(f, "finished"),
+        }
+    }
+}
+
+/// Shared pause flag of one loop. Clones control the same loop.
+#[derive(Debug, Clone)]
+pub struct PauseControl {
+    state: Arc<watch::Sender<LoopState>>,
+}
+
+impl Default for PauseControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PauseControl {
+    /// A control in the [`LoopState::Running`] state.
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(watch::Sender::new(LoopState::Running)),
+        }
+    }
+
+    /// The current state.
+    pub fn state(&self) -> LoopState {
+        *self.state.borrow()
+    }
+
+    /// Ask the loop to stop before its next task. Returns whether this
+    /// changed the state (false when already paused or finished).
+    pub fn pause(&self) -> bool {
+        self.transition(LoopState::Running, LoopState::Paused)
+    }
+
+    /// Let a paused loop continue. Returns whether this changed the state.
+    pub fn resume(&self) -> bool {
+        self.transition(LoopState::Paused, LoopState::Running)
+    }
+
+    /// Mark the loop as ended, releasing anyone waiting on it.
+    pub fn finish(&self) {
+        self.state.send_replace(LoopState::Finished);
+    }
+
+    /// Back to running when a finished loop is run again.
+    pub(crate) fn reopen(&self) {
+        self.transition(LoopState::Finished, LoopState::Running);
+    }
+
+    /// Wait until the loop is no longer paused and return the state it left to.
+    pub async fn wait_while_paused(&self) -> LoopState {
+        let mut receiver = self.state.subscribe();
+        match receiver.wait_for(|state| *state != LoopState::Paused).await {
+            Ok(state) => *state,
+            // The sender lives in `self`, so it cannot be dropped while we wait
+            Err(_) => LoopState::Finished,
+        }
+    }
+
+    fn transition(&self, from: LoopState, to: LoopState) -> bool {
+        self.state.send_if_modified(|state| {
+            let changed = *state == from;
+            if changed {
+                *state = to;
+            }
+            changed
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_pause_and_resume_transitions() {
+        let control = PauseControl::new();
+        assert_eq!(control.state(), LoopState::Running);
+        assert!(!control.resume());
+        assert!(control.pause());
+        assert!(!control.pause());
+        assert_eq!(control.clone().state(), LoopState::Paused);
+        assert!(control.resume());
+        assert_eq!(control.state(), LoopState::Running);
+
+        control.finish();
+        assert!(!control.pause());
+        assert_eq!(control.state(), LoopState::Finished);
+        assert_eq!(LoopState::Finished.to_string(), "finished");
+    }
+
+    #[tokio::test]
+    async fn test_wait_returns_once_resumed() {
+        let control = PauseControl::new();
+        assert_eq!(control.wait_while_paused().await, LoopState::Running);
+
+        control.pause();
+        let waiter = tokio::spawn({
+            let control = control.clone();
+            async move { control.wait_while_paused().await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiter.is_finished());
+        control.resume();
+        assert_eq!(waiter.await.unwrap(), LoopState::Running);
+
+        control.pause();
+        let waiter = tokio::spawn({
+            let control = control.clone();
+            async move { control.wait_while_paused().await }
+        });
+        control.finish();
+        assert_eq!(waiter.await.unwrap(), LoopState::Finished);
+    }
+}
@@ -0,0 +1,390 @@
+//! Write-behind persistence for files that tolerate losing their newest entries.
+//!
+//! A burst of tool calls can rewrite progress.json dozens of times a minute,
+//! which is slow on network filesystems. [`WriteBehind`] keeps the latest
+//! content of each file in memory and writes it out when:
+//!
+//! - the oldest buffered write is [`WriteBehindPolicy::max_delay`] old
+//!   (checked on every write and by the timer from [`WriteBehind::spawn_timer`]),
+//! - more than [`WriteBehindPolicy::max_bytes`] are buffered,
+//! - the loop reaches a task boundary and calls [`WriteBehind::flush`],
+//! - or the buffer is dropped, which includes unwinding from a panic.
+//!
+//! A crash may lose the last few buffered writes. tasks.json is never
+//! buffered: it is the loop's source of truth and is saved synchronously
+//! with [`write_atomic`], so it is never older than a journal.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+
+/// Source of the current time, replaceable in tests.
+pub trait Clock: Send + Sync {
+    /// The current instant.
+    fn now(&self) -> Instant;
+}
+
+/// The system's monotonic clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// When buffered writes go to disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriteBehindPolicy {
+    /// Longest a write may stay buffered
+    pub max_delay: Duration,
+    /// Buffered bytes that force a flush
+    pub max_bytes: usize,
+}
+
+impl Default for WriteBehindPolicy {
+    fn default() -> Self {
+        Self {
+            max_delay: Duration::from_millis(500),
+            max_bytes: 256 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Buffer {
+    /// Latest content per file; a newer write replaces an older one
+    pending: BTreeMap<PathBuf, Vec<u8>>,
+    /// When the oldest pending write was buffered
+    since: Option<Instant>,
+}
+
+impl Buffer {
+    fn bytes(&self) -> usize {
+        self.pending.values().map(Vec::len).sum()
+    }
+}
+
+/// Buffers whole-file writes and flushes them on a deadline, a size threshold or demand.
+pub struct WriteBehind {
+    clock: Arc<dyn Clock>,
+    policy: WriteBehindPolicy,
+    buffer: Mutex<Buffer>,
+}
+
+impl std::fmt::Debug for WriteBehind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WriteBehind")
+            .field("policy", &self.policy)
+            .field("pending", &self.pending_paths())
+            .finish()
+    }
+}
+
+impl Default for WriteBehind {
+    fn default() -> Self {
+        Self::new(WriteBehindPolicy::default())
+    }
+}
+
+impl WriteBehind {
+    /// Create a buffer on the system clock.
+    pub fn new(policy: WriteBehindPolicy) -> Self {
+        Self::with_clock(policy, Arc::new(SystemClock))
+    }
+
+    /// Create a buffer on `clock`.
+    pub fn with_clock(policy: WriteBehindPolicy, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            clock,
+            policy,
+            buffer: Mutex::new(Buffer::default()),
+        }
+    }
+
+    /// Buffer `content` as the new content of `path`, flushing if a trigger is due.
+    ///
+    /// Returns whether a flush happened.
+    pub fn write(
+        &self,
+        path: impl Into<PathBuf>,
+        content: impl Into<Vec<u8>>,
+    ) -> Result<bool, String> {
+        let now = self.clock.now();
+        let due = {
+            let mut buffer = self.lock();
+            buffer.pending.insert(path.into(), content.into());
+            let since = *buffer.since.get_or_insert(now);
+            buffer.bytes() >= self.policy.max_bytes || now - since >= self.policy.max_delay
+        };
+        if due {
+            self.flush()?;
+        }
+        Ok(due)
+    }
+
+    /// Flush if the oldest buffered write has waited `max_delay`.
+    pub fn tick(&self) -> Result<bool, String> {
+        let due = self
+            .lock()
+            .since
+            .is_some_and(|since| self.clock.now() - since >= self.policy.max_delay);
+        if due {
+            self.flush()?;
+        }
+        Ok(due)
+    }
+
+    /// Write every buffered file now.
+    ///
+    /// Files that fail to write stay buffered for the next flush.
+    pub fn flush(&self) -> Result<(), String> {
+        let pending = {
+            let mut buffer = self.lock();
+            buffer.since = None;
+            std::mem::take(&mut buffer.pending)
+        };
+        let mut failed = BTreeMap::new();
+        let mut first_error = None;
+        for (path, content) in pending {
+            if let Some(parent) = path.parent() {
+                let _ = fs::create_dir_all(parent);
+            }
+            if let Err(e) = write_atomic(&path, &content) {
+                first_error.get_or_insert(format!("Failed to write '{}': {}", path.display(), e));
+                failed.insert(path, content);
+            }
+        }
+        if !failed.is_empty() {
+            let now = self.clock.now();
+            let mut buffer = self.lock();
+            buffer.since.get_or_insert(now);
+            for (path, content) in failed {
+                // A write buffered during the flush is newer; keep it
+                buffer.pending.entry(path).or_insert(content);
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+
+    /// Buffered content of `path`, if a write to it is pending.
+    pub fn pending(&self, path: &Path) -> Option<Vec<u8>> {
+        self.lock().pending.get(path).cloned()
+    }
+
+    /// Paths with pending writes.
+    pub fn pending_paths(&self) -> Vec<PathBuf> {
+        self.lock().pending.keys().cloned().collect()
+    }
+
+    /// Flush on a timer every `max_delay` until the returned guard is dropped.
+    ///
+    /// Must be called inside a Tokio runtime.
+    pub fn spawn_timer(self: &Arc<Self>) -> TimerGuard {
+        let weak: Weak<Self> = Arc::downgrade(self);
+        let period = self.policy.max_delay.max(Duration::from_millis(10));
+        TimerGuard(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                let Some(buffer) = weak.upgrade() else {
+                    break;
+                };
+                if let Err(e) = buffer.tick() {
+                    tracing::warn!(error = %e, "Buffered write failed");
+                }
+            }
+        }))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Buffer> {
+        // A panic while holding the lock leaves a valid buffer behind
+        self.buffer.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for WriteBehind {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            tracing::warn!(error = %e, "Buffered writes lost on shutdown");
+        }
+    }
+}
+
+/// Stops the flush timer when dropped.
+#[derive(Debug)]
+pub struct TimerGuard(tokio::task::JoinHandle<()>);
+
+impl Drop for TimerGuard {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Replace `path` with `content` so readers see the old or the new file, never a torn one.
+///
+/// The content is written and synced to a sibling temporary file, which is
+/// then renamed over `path`. Missing parent directories are created. Each
+/// call uses its own temporary file, so concurrent writers of the same path
+/// leave one complete version, never a mix.
+pub fn write_atomic(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    static NEXT_TEMP: AtomicU64 = AtomicU64::new(0);
+
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let temp = path.with_file_name(format!(
+        ".{}.tmp-{}-{}",
+        name,
+        std::process::id(),
+        NEXT_TEMP.fetch_add(1, Ordering::Relaxed)
+    ));
+    let result = write_synced(&temp, content).and_then(|_| fs::rename(&temp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+fn write_synced(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    let mut file = fs::File::create(path)?;
+    file.write_all(content)?;
+    file.sync_all()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    /// A clock that only moves when told to.
+    struct FakeClock(Mutex<Instant>);
+
+    impl FakeClock {
+        fn advance(&self, by: Duration) {
+            *self.0.lock().unwrap() += by;
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> Instant {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    fn buffer(max_bytes: usize) -> (WriteBehind, Arc<FakeClock>) {
+        let clock = Arc::new(FakeClock(Mutex::new(Instant::now())));
+        let policy = WriteBehindPolicy { max_delay: Duration::from_millis(500), max_bytes };
+        (WriteBehind::with_clock(policy, clock.clone()), clock)
+    }
+
+    #[test]
+    fn test_flushes_when_the_oldest_write_is_due() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("progress.json");
+        let (writes, clock) = buffer(1024);
+
+        assert!(!writes.write(&path, "1").unwrap());
+        clock.advance(Duration::from_millis(300));
+        assert!(!writes.write(&path, "2").unwrap());
+        assert!(!writes.tick().unwrap());
+        assert!(!path.exists());
+        assert_eq!(writes.pending(&path).as_deref(), Some(&b"2"[..]));
+
+        // The deadline runs from the first buffered write, not the latest
+        clock.advance(Duration::from_millis(200));
+        assert!(writes.tick().unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "2");
+        assert!(writes.pending_paths().is_empty());
+        assert!(!writes.tick().unwrap());
+    }
+
+    #[test]
+    fn test_flushes_on_size_and_on_demand() {
+        let dir = tempdir().unwrap();
+        let (a, b) = (dir.path().join("a.json"), dir.path().join("nested/b.json"));
+        let (writes, _clock) = buffer(8);
+
+        assert!(!writes.write(&a, "1234").unwrap());
+        assert!(writes.write(&b, "5678").unwrap());
+        assert_eq!(fs::read_to_string(&a).unwrap(), "1234");
+        assert_eq!(fs::read_to_string(&b).unwrap(), "5678");
+
+        writes.write(&a, "x").unwrap();
+        writes.flush().unwrap();
+        assert_eq!(fs::read_to_string(&a).unwrap(), "x");
+    }
+
+    #[test]
+    fn test_drop_flushes_even_when_unwinding() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("progress.json");
+        let result = std::panic::catch_unwind(|| {
+            let (writes, _clock) = buffer(1024);
+            writes.write(&path, "last entry").unwrap();
+            panic!("tool crashed");
+        });
+        assert!(result.is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "last entry");
+    }
+
+    #[test]
+    fn test_atomic_write_replaces_without_leftovers() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("tasks.json");
+        write_atomic(&path, b"old").unwrap();
+        write_atomic(&path, b"new").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        // Missing directories are created
+        let nested = dir.path().join(".ralph/design.md");
+        write_atomic(&nested, b"# Design").unwrap();
+        assert_eq!(fs::read_to_string(&nested).unwrap(), "# Design");
+    }
+
+    #[test]
+    fn test_interleaved_atomic_writes_keep_valid_json() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("tasks.json");
+        write_atomic(&path, br#"{"writer":0,"round":0}"#).unwrap();
+
+        let writers: Vec<_> = (1..=4)
+            .map(|writer| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    for round in 0..50 {
+                        // Large enough that a torn write would be caught
+                        let padding = "x".repeat(4096 * writer);
+                        let content = serde_json::json!({ "writer": writer, "round": round, "padding": padding });
+                        write_atomic(&path, content.to_string().as_bytes()).unwrap();
+                    }
+                })
+            })
+            .collect();
+        let reader = {
+            let path = path.clone();
+            std::thread::spawn(move || {
+                for _ in 0..200 {
+                    let content = fs::read_to_string(&path).unwrap();
+                    serde_json::from_str::<serde_json::Value>(&content).unwrap();
+                }
+            })
+        };
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        reader.join().unwrap();
+
+        let last: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(last["round"], 49);
+        let names: Vec<_> = fs::read_dir(dir.path()).unwrap().map(|e| e.unwrap().file_name()).collect();
+        assert_eq!(names, ["tasks.json"]);
+    }
+}
@@ -0,0 +1,480 @@
+//! Startup check of the whole configuration.
+//!
+//! Configuration problems used to surface one at a time, some only once a
+//! model client was built minutes into a run. [`run_checks`] evaluates the
+//! effective configuration up front with a list of named checks and
+//! collects everything they find into one [`PreflightReport`], each problem
+//! naming where the bad value came from (a flag, a `.env` file, an
+//! environment variable, or the default) and how to fix it. Nothing is sent
+//! to a model before the report passes.
+//!
+//! The checks reuse the validators behind [`RalphConfig::from_env`], so
+//! `ralph config` and a run agree on what is valid. Within one check the
+//! first failing rule is reported; the checks themselves are independent.
+
+use crate::error::RalphError;
+use crate::models::config::default_api_key_envs;
+use crate::models::{ModelConfig, RalphConfig, ValidationError};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// One named configuration check.
+#[derive(Debug, Clone, Copy)]
+pub struct ConfigCheck {
+    /// Short name shown next to each problem
+    pub name: &'static str,
+    /// Only needed by commands that call a model
+    pub needs_models: bool,
+    run: fn(&RalphConfig, &[ValidationError]) -> Vec<ValidationError>,
+}
+
+impl ConfigCheck {
+    /// Problems this check finds in `config`; `env_errors` are the
+    /// variables [`RalphConfig::read_env`] could not parse.
+    pub fn run(&self, config: &RalphConfig, env_errors: &[ValidationError]) -> Vec<ValidationError> {
+        (self.run)(config, env_errors)
+    }
+}
+
+/// Every check, in the order they run.
+pub const CONFIG_CHECKS: &[ConfigCheck] = &[
+    ConfigCheck { name: "environment", needs_models: false, run: check_environment },
+    ConfigCheck { name: "models", needs_models: false, run: check_models },
+    ConfigCheck { name: "settings", needs_models: false, run: check_settings },
+    ConfigCheck { name: "telemetry", needs_models: false, run: check_telemetry },
+    ConfigCheck { name: "project path", needs_models: false, run: check_project_path },
+    ConfigCheck { name: "api keys", needs_models: true, run: check_api_keys },
+];
+
+/// Environment variables whose values could not be parsed.
+pub fn check_environment(_config: &RalphConfig, env_errors: &[ValidationError]) -> Vec<ValidationError> {
+    env_errors.to_vec()
+}
+
+/// Each agent's model settings ([`ModelConfig::validate`]).
+pub fn check_models(config: &RalphConfig, _env_errors: &[ValidationError]) -> Vec<ValidationError> {
+    agent_models(config)
+        .into_iter()
+        .filter_map(|(agent, model)| {
+            model.validate().err().map(|e| ValidationError {
+                field: format!("agents.{}.{}", agent, e.field),
+                ..e
+            })
+        })
+        .collect()
+}
+
+/// Run settings ([`RalphConfig::validate_settings`]).
+pub fn check_settings(config: &RalphConfig, _env_errors: &[ValidationError]) -> Vec<ValidationError> {
+    config.validate_settings().err().into_iter().collect()
+}
+
+/// Telemetry settings ([`TelemetryConfig::validate`](crate::models::TelemetryConfig::validate)).
+pub fn check_telemetry(config: &RalphConfig, _env_errors: &[ValidationError]) -> Vec<ValidationError> {
+    config.telemetry.validate().err().into_iter().collect()
+}
+
+/// The project directory exists, or can be created.
+pub fn check_project_path(config: &RalphConfig, _env_errors: &[ValidationError]) -> Vec<ValidationError> {
+    let path = Path::new(&config.project_path);
+    if config.project_path.is_empty() {
+        // Reported by the settings check
+        return Vec::new();
+    }
+    let suggestion = "Point RALPH_PROJECT_PATH (or --project-path) at a directory, or at a path that can be created";
+    if path.exists() {
+        if path.is_dir() {
+            return Vec::new();
+        }
+        return vec![ValidationError::new(
+            "project_path",
+            format!("'{}' exists but is not a directory", path.display()),
+        )
+        .with_suggestion(suggestion)];
+    }
+    // A missing project directory is created, which needs a directory to create it in
+    let blocker = path
+        .ancestors()
+        .skip(1)
+        .map(|a| if a.as_os_str().is_empty() { Path::new(".") } else { a })
+        .find(|a| a.exists())
+        .filter(|a| !a.is_dir());
+    match blocker {
+        Some(file) => vec![ValidationError::new(
+            "project_path",
+            format!("'{}' cannot be created: '{}' is not a directory", path.display(), file.display()),
+        )
+        .with_suggestion(suggestion)],
+        None => Vec::new(),
+    }
+}
+
+/// Every agent that will run has an API key ([`ModelConfig::resolve_api_key`]).
+pub fn check_api_keys(config: &RalphConfig, _env_errors: &[ValidationError]) -> Vec<ValidationError> {
+    let mut seen = BTreeSet::new();
+    agent_models(config)
+        .into_iter()
+        .filter(|(agent, _)| *agent != "judge_model" || config.judge_enabled)
+        .filter(|(_, model)| {
+            model.api_key_env.is_some() || !default_api_key_envs(&model.provider).is_empty()
+        })
+        .filter_map(|(agent, model)| {
+            let message = match model.resolve_api_key().err()? {
+                RalphError::Configuration(message) => message,
+                e => e.to_string(),
+            };
+            // Agents sharing a provider share the missing key
+            seen.insert(message.clone()).then(|| {
+                ValidationError::new(format!("agents.{}.api_key", agent), message).with_suggestion(
+                    "Set the key in the environment or in a .env file in the working directory (see .env.example)",
+                )
+            })
+        })
+        .collect()
+}
+
+/// Each agent's model config with its field name under `agents`.
+fn agent_models(config: &RalphConfig) -> [(&'static str, &ModelConfig); 4] {
+    [
+        ("prd_model", &config.agents.prd_model),
+        ("architect_model", &config.agents.architect_model),
+        ("ralph_model", &config.agents.ralph_model),
+        ("judge_model", &config.agents.judge_model),
+    ]
+}
+
+/// Where a setting's value came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValueSource {
+    /// A command-line flag
+    Flag(String),
+    /// A variable set in a `.env` file
+    EnvFile { var: String, path: PathBuf },
+    /// An environment variable
+    Env(String),
+    /// Nothing set it
+    Default,
+}
+
+impl fmt::Display for ValueSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueSource::Flag(flag) => write!(f, "flag {}", flag),
+            ValueSource::EnvFile { var, path } => write!(f, "{} in {}", var, path.display()),
+            ValueSource::Env(var) => write!(f, "environment variable {}", var),
+            ValueSource::Default => write!(f, "default value"),
+        }
+    }
+}
+
+/// What set each setting: flags given, variables in the environment and in
+/// the loaded `.env` file.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigSources {
+    flags: BTreeMap<String, String>,
+    env: BTreeSet<String>,
+    env_file: Option<(PathBuf, BTreeSet<String>)>,
+}
+
+impl ConfigSources {
+    /// Sources with the names of the variables currently set.
+    pub fn from_environment() -> Self {
+        let env = std::env::vars_os().filter_map(|(key, _)| key.into_string().ok()).collect();
+        Self { env, ..Self::default() }
+    }
+
+    /// Note that `var` is set in the environment.
+    pub fn with_env_var(mut self, var: impl Into<String>) -> Self {
+        self.env.insert(var.into());
+        self
+    }
+
+    /// Note that the variables in the `.env` file at `path` were loaded.
+    pub fn with_env_file(mut self, path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let vars = dotenvy::from_path_iter(&path)
+            .map(|iter| iter.filter_map(|item| item.ok().map(|(key, _)| key)).collect())
+            .unwrap_or_default();
+        self.env.extend(vars.iter().cloned());
+        self.env_file = Some((path, vars));
+        self
+    }
+
+    /// Note that `flag` set the setting `field`.
+    pub fn with_flag(mut self, field: impl Into<String>, flag: impl Into<String>) -> Self {
+        self.flags.insert(field.into(), flag.into());
+        self
+    }
+
+    /// Where the value of `field` (as named in a [`ValidationError`]) came from.
+    pub fn source_of(&self, field: &str) -> ValueSource {
+        if let Some(flag) = self.flags.get(field) {
+            return ValueSource::Flag(flag.clone());
+        }
+        let Some(var) = env_vars_for(field).into_iter().find(|var| self.env.contains(var)) else {
+            return ValueSource::Default;
+        };
+        match &self.env_file {
+            Some((path, vars)) if vars.contains(&var) => ValueSource::EnvFile { var, path: path.clone() },
+            _ => ValueSource::Env(var),
+        }
+    }
+}
+
+/// Variables that can set `field`, most specific first.
+fn env_vars_for(field: &str) -> Vec<String> {
+    if let Some((agent, setting)) = field.strip_prefix("agents.").and_then(|rest| rest.split_once('.')) {
+        let prefix = match agent {
+            "prd_model" => "PRD",
+            "architect_model" => "ARCHITECT",
+            "ralph_model" => "LOOP",
+            "judge_model" => "JUDGE",
+            _ => return Vec::new(),
+        };
+        let own = |suffix: &str| format!("RALPH_{}_{}", prefix, suffix);
+        return match setting {
+            "provider" => vec![own("PROVIDER"), "RALPH_MODEL_PROVIDER".to_string()],
+            "model_name" => vec![own("MODEL"), "RALPH_MODEL_NAME".to_string()],
+            "thinking_enabled" => vec![own("THINKING")],
+            "api_key" => vec![
+                own("API_KEY_COMMAND"),
+                own("API_KEY_FILE"),
+                own("API_KEY_ENV"),
+                own("PROVIDER"),
+                "RALPH_MODEL_PROVIDER".to_string(),
+            ],
+            "model_call_timeout_secs" => vec!["RALPH_MODEL_CALL_TIMEOUT_SECS".to_string()],
+            "base_url" => vec![own("BASE_URL")],
+            "temperature" => vec![own("TEMPERATURE")],
+            "max_tokens" => vec![own("MAX_TOKENS")],
+            _ => Vec::new(),
+        };
+    }
+    let var = match field {
+        "verification_cadence" => "RALPH_VERIFY_CADENCE".to_string(),
+        "edit_guard_languages" => "RALPH_EDIT_GUARD".to_string(),
+        "inter_task_delay" => "RALPH_INTER_TASK_DELAY_MS".to_string(),
+        "inter_task_jitter" => "RALPH_INTER_TASK_JITTER_MS".to_string(),
+        "enabled" => "RALPH_TELEMETRY_ENABLED".to_string(),
+        _ => format!("RALPH_{}", field.to_uppercase()),
+    };
+    vec![var]
+}
+
+/// One problem found by a check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigProblem {
+    /// Name of the check that found it
+    pub check: &'static str,
+    /// The setting at fault
+    pub field: String,
+    /// What is wrong
+    pub message: String,
+    /// How to fix it
+    pub suggestion: Option<String>,
+    /// Where the bad value came from
+    pub source: ValueSource,
+}
+
+/// Everything the checks found.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PreflightReport {
+    /// Names of the checks that ran
+    pub checks: Vec<&'static str>,
+    /// Problems, in check order
+    pub problems: Vec<ConfigProblem>,
+}
+
+impl PreflightReport {
+    /// Whether no check found a problem.
+    pub fn passed(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// Problems found by the check named `check`.
+    pub fn problems_of(&self, check: &str) -> Vec<&ConfigProblem> {
+        self.problems.iter().filter(|p| p.check == check).collect()
+    }
+
+    /// `Ok` when the report passed, else the whole report as a configuration error.
+    pub fn into_result(self) -> crate::Result<()> {
+        if self.passed() {
+            Ok(())
+        } else {
+            Err(RalphError::Configuration(self.to_string()))
+        }
+    }
+}
+
+impl fmt::Display for PreflightReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.passed() {
+            return write!(f, "Configuration is valid ({} checks)", self.checks.len());
+        }
+        write!(f, "{} configuration problem(s); nothing was sent to a model", self.problems.len())?;
+        for problem in &self.problems {
+            write!(
+                f,
+                "\n  - [{}] {}: {} (from {})",
+                problem.check, problem.field, problem.message, problem.source
+            )?;
+            if let Some(suggestion) = problem.suggestion.as_deref().filter(|s| !s.is_empty()) {
+                write!(f, "\n    fix: {}", suggestion)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Run every check in [`CONFIG_CHECKS`], leaving out the model-only ones
+/// unless `needs_models`.
+pub fn run_checks(
+    config: &RalphConfig,
+    env_errors: &[ValidationError],
+    sources: &ConfigSources,
+    needs_models: bool,
+) -> PreflightReport {
+    let mut report = PreflightReport::default();
+    for check in CONFIG_CHECKS.iter().filter(|c| needs_models || !c.needs_models) {
+        report.checks.push(check.name);
+        report.problems.extend(check.run(config, env_errors).into_iter().map(|e| ConfigProblem {
+            check: check.name,
+            source: sources.source_of(&e.field),
+            field: e.field,
+            message: e.message,
+            suggestion: e.suggestion,
+        }));
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> RalphConfig {
+        let mut config = RalphConfig::default();
+        config.project_path = std::env::temp_dir().display().to_string();
+        config
+    }
+
+    #[test]
+    fn test_environment_check_reports_every_parse_error() {
+        let errors = [
+            ValidationError::new("max_iterations", "Invalid RALPH_MAX_ITERATIONS 'lots'"),
+            ValidationError::new("color", "Invalid color 'sometimes'"),
+        ];
+        assert_eq!(check_environment(&config(), &errors).len(), 2);
+        assert!(check_environment(&config(), &[]).is_empty());
+    }
+
+    #[test]
+    fn test_models_check_names_the_agent() {
+        let mut config = config();
+        assert!(check_models(&config, &[]).is_empty());
+        config.agents.prd_model.temperature = 3.0;
+        config.agents.judge_model.model_name = String::new();
+        let fields: Vec<_> = check_models(&config, &[]).into_iter().map(|e| e.field).collect();
+        assert_eq!(fields, ["agents.prd_model.temperature", "agents.judge_model.model_name"]);
+    }
+
+    #[test]
+    fn test_settings_and_telemetry_checks() {
+        let mut config = config();
+        assert!(check_settings(&config, &[]).is_empty());
+        assert!(check_telemetry(&config, &[]).is_empty());
+        config.max_iterations = 0;
+        config.telemetry.log_level = "loud".to_string();
+        assert_eq!(check_settings(&config, &[])[0].field, "max_iterations");
+        assert_eq!(check_telemetry(&config, &[])[0].field, "log_level");
+    }
+
+    #[test]
+    fn test_project_path_check() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("notes.txt");
+        std::fs::write(&file, "").unwrap();
+        let mut config = config();
+
+        config.project_path = dir.path().join("new/project").display().to_string();
+        assert!(check_project_path(&config, &[]).is_empty());
+
+        config.project_path = file.display().to_string();
+        assert!(check_project_path(&config, &[])[0].message.contains("is not a directory"));
+
+        config.project_path = file.join("project").display().to_string();
+        let problems = check_project_path(&config, &[]);
+        assert!(problems[0].message.contains("cannot be created"), "{}", problems[0]);
+    }
+
+    #[test]
+    fn test_api_keys_check_reports_each_missing_key_once() {
+        let mut config = config();
+        for model in [
+            &mut config.agents.prd_model,
+            &mut config.agents.architect_model,
+            &mut config.agents.ralph_model,
+        ] {
+            model.api_key_env = Some("RALPH_PREFLIGHT_TEST_UNSET_KEY".to_string());
+        }
+        config.agents.judge_model.api_key = Some("judge-key".to_string());
+        let problems = check_api_keys(&config, &[]);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].field, "agents.prd_model.api_key");
+        assert!(problems[0].message.contains("RALPH_PREFLIGHT_TEST_UNSET_KEY"));
+
+        for model in [&mut config.agents.prd_model, &mut config.agents.architect_model, &mut config.agents.ralph_model] {
+            model.api_key = Some("key".to_string());
+        }
+        assert!(check_api_keys(&config, &[]).is_empty());
+        // The judge only needs a key when it runs
+        config.agents.judge_model.api_key = None;
+        config.agents.judge_model.api_key_env = Some("RALPH_PREFLIGHT_TEST_UNSET_KEY".to_string());
+        assert!(check_api_keys(&config, &[]).is_empty());
+        config.judge_enabled = true;
+        assert_eq!(check_api_keys(&config, &[])[0].field, "agents.judge_model.api_key");
+    }
+
+    #[test]
+    fn test_sources_prefer_flags_then_env_file_then_env() {
+        let dir = tempfile::tempdir().unwrap();
+        let env_file = dir.path().join(".env");
+        std::fs::write(&env_file, "RALPH_MAX_ITERATIONS=0\n").unwrap();
+        let sources = ConfigSources::default()
+            .with_env_var("RALPH_LOOP_PROVIDER")
+            .with_env_var("RALPH_MODEL_PROVIDER")
+            .with_env_file(&env_file)
+            .with_flag("project_path", "--project-path");
+
+        assert_eq!(sources.source_of("project_path"), ValueSource::Flag("--project-path".to_string()));
+        assert_eq!(
+            sources.source_of("max_iterations"),
+            ValueSource::EnvFile { var: "RALPH_MAX_ITERATIONS".to_string(), path: env_file.clone() }
+        );
+        assert_eq!(sources.source_of("agents.ralph_model.provider"), ValueSource::Env("RALPH_LOOP_PROVIDER".to_string()));
+        assert_eq!(sources.source_of("agents.prd_model.provider"), ValueSource::Env("RALPH_MODEL_PROVIDER".to_string()));
+        assert_eq!(sources.source_of("agents.prd_model.temperature"), ValueSource::Default);
+        let sources = ConfigSources::default().with_env_var("RALPH_ARCHITECT_TEMPERATURE");
+        assert_eq!(
+            sources.source_of("agents.architect_model.temperature"),
+            ValueSource::Env("RALPH_ARCHITECT_TEMPERATURE".to_string())
+        );
+        assert_eq!(sources.source_of("max_task_retries"), ValueSource::Default);
+    }
+
+    #[test]
+    fn test_model_checks_only_run_when_needed() {
+        let mut config = config();
+        config.agents.prd_model.api_key_env = Some("RALPH_PREFLIGHT_TEST_UNSET_KEY".to_string());
+        config.agents.architect_model.api_key = Some("key".to_string());
+        config.agents.ralph_model.api_key = Some("key".to_string());
+        let report = run_checks(&config, &[], &ConfigSources::default(), false);
+        assert!(report.passed(), "{}", report);
+        assert!(!report.checks.contains(&"api keys"));
+        assert!(report.to_string().starts_with("Configuration is valid"));
+
+        let report = run_checks(&config, &[], &ConfigSources::default(), true);
+        assert_eq!(report.problems_of("api keys").len(), 1);
+    }
+}
@@ -0,0 +1,204 @@
+//! Preview of the execution phase, without calling a model.
+//!
+//! [`preview_run`] replays the loop's task selection on a copy of
+//! `tasks.json`: it repeatedly takes [`TaskList::next_ready`] under the
+//! configured [`SchedulingStrategy`] and pretends that task completed. The result lists, in execution order, each task with
+//! the files it declares it will create or modify. Tasks the loop would never
+//! reach (their dependencies are blocked, skipped, missing or circular) are
+//! listed separately with what they are waiting on.
+//!
+//! The footprint is what the architect declared in `files_to_create` and
+//! `files_to_modify`; the developer agent may touch other files as well.
+
+use crate::models::{SchedulingStrategy, Task, TaskList, TaskStatus};
+use std::collections::BTreeSet;
+
+/// One task in the previewed execution order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreviewStep {
+    /// Task ID
+    pub task_id: String,
+    /// Task title
+    pub title: String,
+    /// Dependencies, all of which run earlier
+    pub dependencies: Vec<String>,
+    /// Files the task declares it creates
+    pub creates: Vec<String>,
+    /// Files the task declares it modifies
+    pub modifies: Vec<String>,
+    /// Whether the task was interrupted and is picked up again first
+    pub resumed: bool,
+}
+
+impl PreviewStep {
+    fn of(task: &Task, resumed: bool) -> Self {
+        Self {
+            task_id: task.id.clone(),
+            title: task.title.clone(),
+            dependencies: task.dependencies.clone(),
+            creates: task.files_created.clone(),
+            modifies: task.files_modified.clone(),
+            resumed,
+        }
+    }
+}
+
+/// A task the loop would never start.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnreachableTask {
+    /// Task ID
+    pub task_id: String,
+    /// Dependencies that never complete
+    pub waiting_on: Vec<String>,
+}
+
+/// What a run over a task list would do, in order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunPreview {
+    /// Tasks in the order the loop would run them
+    pub steps: Vec<PreviewStep>,
+    /// Pending tasks the loop would never reach
+    pub unreachable: Vec<UnreachableTask>,
+    /// Tasks already completed, skipped or obsolete, which do not run again
+    pub already_done: usize,
+    /// Blocked tasks, which do not run again
+    pub blocked: usize,
+}
+
+impl RunPreview {
+    /// Every distinct file the run declares it touches, sorted.
+    pub fn files(&self) -> BTreeSet<&str> {
+        self.steps
+            .iter()
+            .flat_map(|s| s.creates.iter().chain(&s.modifies))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Whether there is nothing to run.
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+/// Preview the order a run would execute `tasks` in and what each task touches.
+///
+/// Tasks already in progress were interrupted and come first; the rest follow
+/// the loop's own selection (dependencies completed, then `strategy`).
+pub fn preview_run(tasks: &TaskList, strategy: SchedulingStrategy) -> RunPreview {
+    let mut list = tasks.clone();
+    let mut preview = RunPreview::default();
+
+    for task in tasks.get_all_tasks() {
+        match task.status {
+            TaskStatus::Completed | TaskStatus::Skipped | TaskStatus::Obsolete => preview.already_done += 1,
+            TaskStatus::Blocked => preview.blocked += 1,
+            TaskStatus::InProgress => {
+                preview.steps.push(PreviewStep::of(task, true));
+                if let Some(task) = list.get_task_mut(&task.id) {
+                    task.status = TaskStatus::Completed;
+                }
+            }
+            TaskStatus::Pending => {}
+        }
+    }
+
+    while let Some(task) = list.next_ready(strategy) {
+        let id = task.id.clone();
+        preview.steps.push(PreviewStep::of(task, false));
+        if let Some(task) = list.get_task_mut(&id) {
+            task.status = TaskStatus::Completed;
+        }
+    }
+
+    for task in list.get_all_tasks().into_iter().filter(|t| t.is_pending()) {
+        let waiting_on = task
+            .dependencies
+            .iter()
+            .filter(|dep| !list.get_task(dep).is_some_and(|d| d.is_completed()))
+            .cloned()
+            .collect();
+        preview.unreachable.push(UnreachableTask {
+            task_id: task.id.clone(),
+            waiting_on,
+        });
+    }
+
+    preview
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: &str, priority: u32, deps: &[&str], creates: &[&str], modifies: &[&str]) -> Task {
+        let mut task = Task::new(id, format!("Task {}", id), "", priority);
+        for dep in deps {
+            task.add_dependency(*dep);
+        }
+        task.files_created = creates.iter().map(|f| f.to_string()).collect();
+        task.files_modified = modifies.iter().map(|f| f.to_string()).collect();
+        task
+    }
+
+    #[test]
+    fn test_preview_follows_dependencies_then_priority() {
+        let mut list = TaskList::new("calc", "rust");
+        list.add_task(task("T-003", 1, &["T-002"], &["src/eval.rs"], &["src/main.rs"]));
+        list.add_task(task("T-001", 2, &[], &["Cargo.toml", "src/main.rs"], &[]));
+        list.add_task(task("T-002", 3, &["T-001"], &["src/parser.rs"], &["src/main.rs"]));
+        list.add_task(task("T-004", 1, &[], &["README.md"], &[]));
+
+        let preview = preview_run(&list, SchedulingStrategy::PriorityFirst);
+        let order: Vec<&str> = preview.steps.iter().map(|s| s.task_id.as_str()).collect();
+        assert_eq!(order, vec!["T-004", "T-001", "T-002", "T-003"]);
+        assert_eq!(preview.steps[3].creates, vec!["src/eval.rs"]);
+        assert_eq!(preview.steps[3].modifies, vec!["src/main.rs"]);
+        assert_eq!(
+            preview.files().into_iter().collect::<Vec<_>>(),
+            vec!["Cargo.toml", "README.md", "src/eval.rs", "src/main.rs", "src/parser.rs"]
+        );
+        assert!(preview.unreachable.is_empty());
+
+        // The task list itself is untouched
+        assert!(list.get_all_tasks().iter().all(|t| t.is_pending()));
+
+        // The long T-001 -> T-002 -> T-003 chain goes before the lone T-004
+        let preview = preview_run(&list, SchedulingStrategy::CriticalPathFirst);
+        let order: Vec<&str> = preview.steps.iter().map(|s| s.task_id.as_str()).collect();
+        assert_eq!(order, vec!["T-001", "T-002", "T-003", "T-004"]);
+    }
+
+    #[test]
+    fn test_preview_skips_finished_and_reports_unreachable_tasks() {
+        let mut list = TaskList::new("calc", "rust");
+        let mut done = task("T-001", 1, &[], &["Cargo.toml"], &[]);
+        done.status = TaskStatus::Completed;
+        let mut blocked = task("T-002", 1, &["T-001"], &["src/db.rs"], &[]);
+        blocked.status = TaskStatus::Blocked;
+        let mut interrupted = task("T-003", 3, &["T-001"], &["src/cli.rs"], &[]);
+        interrupted.status = TaskStatus::InProgress;
+        list.add_task(done);
+        list.add_task(blocked);
+        list.add_task(interrupted);
+        list.add_task(task("T-004", 1, &["T-002", "T-003"], &[], &["src/cli.rs"]));
+        list.add_task(task("T-005", 2, &["T-003"], &["tests/cli.rs"], &[]));
+        list.add_task(task("T-006", 1, &["T-007"], &[], &[]));
+        list.add_task(task("T-007", 1, &["T-006"], &[], &[]));
+
+        let preview = preview_run(&list, SchedulingStrategy::PriorityFirst);
+        let order: Vec<&str> = preview.steps.iter().map(|s| s.task_id.as_str()).collect();
+        assert_eq!(order, vec!["T-003", "T-005"]);
+        assert!(preview.steps[0].resumed);
+        assert_eq!(preview.already_done, 1);
+        assert_eq!(preview.blocked, 1);
+        assert_eq!(
+            preview.unreachable,
+            vec![
+                UnreachableTask { task_id: "T-004".into(), waiting_on: vec!["T-002".into()] },
+                UnreachableTask { task_id: "T-006".into(), waiting_on: vec!["T-007".into()] },
+                UnreachableTask { task_id: "T-007".into(), waiting_on: vec!["T-006".into()] },
+            ]
+        );
+    }
+}
@@ -0,0 +1,320 @@
+//! Live two-line progress for the task in progress.
+//!
+//! When `multi_progress` is enabled, [`MultiProgressView`] draws an overall
+//! bar plus a spinner line for the task being worked on, redrawn in place from
+//! the task store (`tasks.json`) by the output writer. The loop works on one
+//! task at a time, so there is one task line at most. When stdout is not a
+//! terminal it prints a structured status line instead, at most once per
+//! interval or whenever the current task changes. Symbols come from
+//! [`Glyphs`], so legacy consoles get an ASCII bar and spinner.
+
+use crate::console::{Glyphs, Palette};
+use crate::models::{TaskList, TaskStatus};
+use crate::run_state::TaskProgress;
+use crate::textfmt::truncate_display;
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+/// Minimum time between structured status lines on non-TTY output.
+pub(crate) const STATUS_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Width of the overall progress bar.
+const BAR_WIDTH: usize = 30;
+
+/// Longest task title shown on a spinner line.
+const MAX_TITLE_CHARS: usize = 50;
+
+/// The task currently being worked on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveTask {
+    /// Task ID
+    pub id: String,
+    /// Task title
+    pub title: String,
+}
+
+/// Point-in-time view of the task store.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProgressSnapshot {
+    /// Tasks completed
+    pub completed: usize,
+    /// Total tasks, skipped ones excluded
+    pub total: usize,
+    /// Task in progress, if any
+    pub current: Option<ActiveTask>,
+}
+
+impl ProgressSnapshot {
+    /// Take a snapshot of a task list.
+    pub fn from_task_list(list: &TaskList) -> Self {
+        let progress = TaskProgress::of(list);
+        let current = list
+            .get_all_tasks()
+            .into_iter()
+            .find(|t| t.status == TaskStatus::InProgress)
+            .map(|t| ActiveTask {
+                id: t.id.clone(),
+                title: t.title.clone(),
+            });
+        Self {
+            completed: progress.completed,
+            total: progress.total,
+            current,
+        }
+    }
+
+    /// Lines drawn on a terminal: the overall bar, then the current task.
+    pub fn render_lines(&self, tick: usize, glyphs: &Glyphs, palette: &Palette) -> Vec<String> {
+        let progress = TaskProgress {
+            completed: self.completed,
+            total: self.total,
+        };
+        let filled = progress.filled(BAR_WIDTH);
+        let percentage = progress.percentage();
+
+        let mut lines = vec![format!(
+            "  [{}{}] {}% ({}/{} tasks)",
+            palette.success(glyphs.bar_filled.repeat(filled)),
+            palette.dim(glyphs.bar_empty.repeat(BAR_WIDTH - filled)),
+            percentage,
+            self.completed,
+            self.total
+        )];
+
+        if let Some(task) = &self.current {
+            let frame = glyphs.spinner[tick % glyphs.spinner.len()];
+            let title = truncate_display(&task.title, MAX_TITLE_CHARS);
+            lines.push(format!("    {} {} {}", palette.info(frame), palette.accent(&task.id), title));
+        }
+        lines
+    }
+
+    /// Structured single-line status for logs and non-TTY output.
+    pub fn status_line(&self) -> String {
+        format!(
+            "progress completed={} total={} task={}",
+            self.completed,
+            self.total,
+            self.current.as_ref().map_or("none", |t| t.id.as_str())
+        )
+    }
+}
+
+/// Two-line live progress view.
+///
+/// The view only renders; it is owned by the output writer (see
+/// [`crate::output`]), which passes in the stream to draw on so frames never
+/// interleave with other output.
+#[derive(Debug)]
+pub struct MultiProgressView {
+    /// Redraw in place (terminal) or print status lines
+    tty: bool,
+    /// Lines drawn by the last terminal update
+    drawn_lines: usize,
+    /// Redraw counter driving the spinners
+    tick: usize,
+    /// When the last status line was printed (non-TTY)
+    last_status: Option<Instant>,
+    /// Current task ID at the last status line (non-TTY)
+    last_task: Option<String>,
+    /// Most recent snapshot, redrawn after other output
+    last_snapshot: Option<ProgressSnapshot>,
+    /// Bar and spinner symbols
+    glyphs: &'static Glyphs,
+    /// Bar and spinner styles
+    palette: &'static Palette,
+}
+
+impl MultiProgressView {
+    /// Create a view, choosing in-place drawing and symbols from the console's capabilities.
+    pub fn new() -> Self {
+        let capabilities = crate::console::capabilities();
+        Self::with_tty(capabilities.redraw).with_glyphs(capabilities.glyphs())
+    }
+
+    /// Create a view with explicit terminal handling.
+    pub fn with_tty(tty: bool) -> Self {
+        Self {
+            tty,
+            drawn_lines: 0,
+            tick: 0,
+            last_status: None,
+            last_task: None,
+            last_snapshot: None,
+            glyphs: &Glyphs::UNICODE,
+            palette: &Palette::DEFAULT,
+        }
+    }
+
+    /// Draw with these symbols (Unicode by default).
+    pub fn with_glyphs(mut self, glyphs: &'static Glyphs) -> Self {
+        self.glyphs = glyphs;
+        self
+    }
+
+    /// Draw in this palette ([`Palette::DEFAULT`] by default).
+    pub fn with_palette(mut self, palette: &'static Palette) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    /// Whether the view redraws in place.
+    pub fn is_tty(&self) -> bool {
+        self.tty
+    }
+
+    /// Update the view from a snapshot.
+    pub fn update(&mut self, snapshot: &ProgressSnapshot, out: &mut dyn Write) {
+        self.last_snapshot = Some(snapshot.clone());
+        if self.tty {
+            self.redraw(snapshot, out);
+        } else if let Some(line) = self.next_status_line(snapshot, Instant::now()) {
+            let _ = writeln!(out, "{}", line);
+        }
+    }
+
+    /// Draw the last snapshot again if it was removed for other output.
+    pub fn refresh(&mut self, out: &mut dyn Write) {
+        if !self.tty || self.drawn_lines > 0 {
+            return;
+        }
+        if let Some(snapshot) = self.last_snapshot.clone() {
+            self.redraw(&snapshot, out);
+        }
+    }
+
+    /// Remove the drawn lines so regular output continues below cleanly.
+    pub fn finish(&mut self, out: &mut dyn Write) {
+        if !self.tty || self.drawn_lines == 0 {
+            return;
+        }
+        let _ = write!(out, "\x1b[{}A", self.drawn_lines);
+        for _ in 0..self.drawn_lines {
+            let _ = writeln!(out, "\x1b[2K");
+        }
+        let _ = write!(out, "\x1b[{}A", self.drawn_lines);
+        self.drawn_lines = 0;
+    }
+
+    /// Move the cursor back over the previous frame and draw the new one.
+    fn redraw(&mut self, snapshot: &ProgressSnapshot, out: &mut dyn Write) {
+        self.tick = self.tick.wrapping_add(1);
+        let lines = snapshot.render_lines(self.tick, self.glyphs, self.palette);
+
+        if self.drawn_lines > 0 {
+            let _ = write!(out, "\x1b[{}A", self.drawn_lines);
+        }
+        for line in &lines {
+            let _ = writeln!(out, "\x1b[2K{}", line);
+        }
+        // Clear the task line left over when no task is in progress
+        let stale = self.drawn_lines.saturating_sub(lines.len());
+        for _ in 0..stale {
+            let _ = writeln!(out, "\x1b[2K");
+        }
+        if stale > 0 {
+            let _ = write!(out, "\x1b[{}A", stale);
+        }
+        self.drawn_lines = lines.len();
+    }
+
+    /// Status line to print now, if the interval elapsed or the current task changed.
+    fn next_status_line(&mut self, snapshot: &ProgressSnapshot, now: Instant) -> Option<String> {
+        let task = snapshot.current.as_ref().map(|t| t.id.clone());
+        let due = self
+            .last_status
+            .is_none_or(|last| now.duration_since(last) >= STATUS_INTERVAL);
+        if !due && task == self.last_task {
+            return None;
+        }
+        self.last_status = Some(now);
+        self.last_task = task;
+        Some(snapshot.status_line())
+    }
+}
+
+impl Default for MultiProgressView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Task;
+
+    fn snapshot() -> ProgressSnapshot {
+        let mut list = TaskList::new("calc", "rust");
+        let mut done = Task::new("T-001", "Setup", "Set up", 1);
+        done.complete(None);
+        list.add_task(done);
+        let mut parser = Task::new("T-002", "Parser", "Parse", 2);
+        parser.start();
+        list.add_task(parser);
+        list.add_task(Task::new("T-003", "Evaluator", "Evaluate", 2));
+        list.add_task(Task::new("T-004", "Docs", "Write docs", 4));
+        ProgressSnapshot::from_task_list(&list)
+    }
+
+    #[test]
+    fn test_snapshot_and_render() {
+        let snap = snapshot();
+        assert_eq!(snap.completed, 1);
+        assert_eq!(snap.total, 4);
+        assert_eq!(snap.current.as_ref().map(|t| t.id.as_str()), Some("T-002"));
+
+        // The mono palette adds no color codes
+        let lines = snap.render_lines(0, &Glyphs::UNICODE, &Palette::MONO);
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("25% (1/4 tasks)"));
+        assert!(lines[1].contains("T-002 Parser"));
+        // The spinner advances with the tick
+        assert_ne!(lines[1], snap.render_lines(1, &Glyphs::UNICODE, &Palette::MONO)[1]);
+
+        let ascii = snap.render_lines(0, &Glyphs::ASCII, &Palette::MONO);
+        assert!(ascii.iter().all(|line| line.is_ascii()));
+        assert!(ascii[0].starts_with("  [#######"));
+
+        let idle = ProgressSnapshot { current: None, ..snap };
+        assert_eq!(idle.render_lines(0, &Glyphs::UNICODE, &Palette::MONO).len(), 1);
+    }
+
+    #[test]
+    fn test_status_lines_throttled_unless_task_changes() {
+        let mut view = MultiProgressView::with_tty(false);
+        let snap = snapshot();
+        let start = Instant::now();
+
+        assert_eq!(
+            view.next_status_line(&snap, start).as_deref(),
+            Some("progress completed=1 total=4 task=T-002")
+        );
+        assert!(view.next_status_line(&snap, start + Duration::from_secs(1)).is_none());
+
+        let mut changed = snap.clone();
+        changed.current = None;
+        assert!(view.next_status_line(&changed, start + Duration::from_secs(2)).is_some());
+        assert!(view.next_status_line(&changed, start + STATUS_INTERVAL * 2).is_some());
+    }
+
+    #[test]
+    fn test_terminal_frames_are_replaced_in_place() {
+        let mut view = MultiProgressView::with_tty(true);
+        let mut out = Vec::new();
+        view.update(&snapshot(), &mut out);
+        assert_eq!(view.drawn_lines, 2);
+
+        // Nothing to refresh while the frame is still on screen
+        let drawn = out.len();
+        view.refresh(&mut out);
+        assert_eq!(out.len(), drawn);
+
+        view.finish(&mut out);
+        assert_eq!(view.drawn_lines, 0);
+        assert!(String::from_utf8_lossy(&out[drawn..]).starts_with("\x1b[2A"));
+
+        view.refresh(&mut out);
+        assert_eq!(view.drawn_lines, 2);
+    }
+}
@@ -0,0 +1,356 @@
+//! Which ralph build a project is pinned to.
+//!
+//! Task schemas and prompts change between ralph releases, so a project shared
+//! by a team can behave differently depending on who runs it. Every run
+//! records the exact ralph version (and git commit for source builds) in
+//! [`PROJECT_STATE_FILE`]. The first run pins the project to its version. At
+//! startup the running binary is compared with the pin:
+//!
+//! - patch differences pass silently
+//! - a minor or major difference is a warning, or an error in strict mode
+//!   (`RALPH_VERSION_STRICT`) unless `--allow-version-change` is passed
+//! - `--allow-version-change` re-pins the project to the running version
+//!
+//! With `RALPH_UPDATE_CHECK=true`, newer releases on crates.io are mentioned
+//! at most once a day. Lookups go through the dependency advisor's cached
+//! [`HttpRegistry`](crate::dependencies::HttpRegistry), so offline runs only
+//! ever read the cache.
+
+use crate::dependencies::{Ecosystem, RegistryIndex, Version};
+use crate::{RalphError, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// State file (relative to the project).
+pub const PROJECT_STATE_FILE: &str = ".ralph/state.json";
+
+/// Name of ralph on crates.io.
+const CRATE_NAME: &str = "adk-ralph";
+
+/// Commit the running binary was built from, when built from a git checkout.
+pub fn git_sha() -> Option<&'static str> {
+    option_env!("RALPH_GIT_SHA").filter(|sha| !sha.is_empty())
+}
+
+/// A ralph version and, for source builds, its commit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RalphBuild {
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_sha: Option<String>,
+}
+
+impl RalphBuild {
+    /// The running binary.
+    pub fn current() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            git_sha: git_sha().map(str::to_string),
+        }
+    }
+}
+
+impl fmt::Display for RalphBuild {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.git_sha {
+            Some(sha) => write!(f, "{} ({})", self.version, sha),
+            None => write!(f, "{}", self.version),
+        }
+    }
+}
+
+/// How far apart two ralph versions are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionChange {
+    /// Same version
+    Same,
+    /// Only the patch number (or pre-release) differs
+    Patch,
+    /// The minor number differs
+    Minor,
+    /// The major number differs
+    Major,
+    /// One of the versions could not be parsed
+    Unknown,
+}
+
+impl VersionChange {
+    /// Compare a recorded version with the running one (either direction).
+    pub fn between(recorded: &str, current: &str) -> Self {
+        let (Some(recorded), Some(current)) = (Version::parse(recorded), Version::parse(current)) else {
+            return if recorded.trim() == current.trim() {
+                VersionChange::Same
+            } else {
+                VersionChange::Unknown
+            };
+        };
+        if recorded.major != current.major {
+            VersionChange::Major
+        } else if recorded.minor != current.minor {
+            VersionChange::Minor
+        } else if recorded != current {
+            VersionChange::Patch
+        } else {
+            VersionChange::Same
+        }
+    }
+
+    /// Whether task schemas and prompts may differ across the change.
+    pub fn is_significant(&self) -> bool {
+        matches!(self, VersionChange::Minor | VersionChange::Major | VersionChange::Unknown)
+    }
+}
+
+impl fmt::Display for VersionChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            VersionChange::Same => "same version",
+            VersionChange::Patch => "patch version change",
+            VersionChange::Minor => "minor version change",
+            VersionChange::Major => "major version change",
+            VersionChange::Unknown => "unrecognized version",
+        })
+    }
+}
+
+/// The once-a-day update check.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpdateCheck {
+    /// Day (`YYYY-MM-DD`, UTC) a newer release was last mentioned
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notified_on: Option<String>,
+}
+
+/// Contents of [`PROJECT_STATE_FILE`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProjectState {
+    /// Version the project is pinned to (its first run, or the last re-pin)
+    pub pinned: RalphBuild,
+    /// Version of the most recent run
+    pub last_run: RalphBuild,
+    #[serde(default)]
+    pub update_check: UpdateCheck,
+}
+
+impl ProjectState {
+    /// State for a project first run with the current binary.
+    pub fn new() -> Self {
+        Self {
+            pinned: RalphBuild::current(),
+            last_run: RalphBuild::current(),
+            update_check: UpdateCheck::default(),
+        }
+    }
+
+    /// Path of the state file in a project.
+    pub fn path(project_path: &Path) -> PathBuf {
+        project_path.join(PROJECT_STATE_FILE)
+    }
+
+    /// The project's recorded state, if any.
+    ///
+    /// A malformed file is ignored with a warning.
+    pub fn load(project_path: &Path) -> Option<Self> {
+        let content = fs::read_to_string(Self::path(project_path)).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(state) => Some(state),
+            Err(e) => {
+                tracing::warn!(error = %e, "Ignoring malformed project state");
+                None
+            }
+        }
+    }
+
+    /// Write the state file.
+    pub fn save(&self, project_path: &Path) -> Result<()> {
+        let path = Self::path(project_path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| RalphError::file(parent.display().to_string(), e.to_string()))?;
+        }
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| RalphError::config(format!("Failed to serialize project state: {}", e)))?;
+        crate::persist::write_atomic(&path, content.as_bytes())
+            .map_err(|e| RalphError::file(path.display().to_string(), e.to_string()))
+    }
+
+    /// How the running binary differs from the pinned version.
+    pub fn version_change(&self) -> VersionChange {
+        VersionChange::between(&self.pinned.version, env!("CARGO_PKG_VERSION"))
+    }
+}
+
+impl Default for ProjectState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Compare the running binary with the project's pin and record this run.
+///
+/// Returns a warning when the versions differ by a minor or major version.
+/// In `strict` mode that difference is an error unless `allow_change` is set.
+/// `allow_change` re-pins the project to the running version.
+pub fn check_and_record(project_path: &Path, strict: bool, allow_change: bool) -> Result<Option<String>> {
+    let mut state = ProjectState::load(project_path).unwrap_or_default();
+    let change = state.version_change();
+    let mut warning = None;
+    if change.is_significant() {
+        let message = format!(
+            "This project is pinned to ralph {}; this is ralph {} ({}). Task schemas and prompts \
+             may differ between them",
+            state.pinned,
+            RalphBuild::current(),
+            change
+        );
+        if strict && !allow_change {
+            return Err(RalphError::config(format!(
+                "{}. Pass --allow-version-change to run anyway and re-pin the project",
+                message
+            )));
+        }
+        if !allow_change {
+            warning = Some(format!("{}. Pass --allow-version-change to re-pin it", message));
+        }
+    }
+
+    if allow_change {
+        state.pinned = RalphBuild::current();
+    }
+    state.last_run = RalphBuild::current();
+    state.save(project_path)?;
+    Ok(warning)
+}
+
+/// Newest non-prerelease ralph on crates.io, per `registry`.
+pub fn latest_release(registry: &impl RegistryIndex) -> Option<Version> {
+    registry
+        .versions(Ecosystem::Cargo, CRATE_NAME)
+        .ok()?
+        .into_iter()
+        .filter(|v| !v.is_prerelease())
+        .max()
+}
+
+/// A notice about a newer release, at most once per day per project.
+///
+/// Registry failures (including being offline without a cached answer) are
+/// silent: the check is advisory and must never stop a run.
+pub fn update_notice(project_path: &Path, today: &str, registry: &impl RegistryIndex) -> Option<String> {
+    let mut state = ProjectState::load(project_path).unwrap_or_default();
+    if state.update_check.notified_on.as_deref() == Some(today) {
+        return None;
+    }
+    let current = Version::parse(env!("CARGO_PKG_VERSION"))?;
+    let latest = latest_release(registry).filter(|latest| *latest > current)?;
+
+    state.update_check.notified_on = Some(today.to_string());
+    if let Err(e) = state.save(project_path) {
+        tracing::debug!(error = %e, "Could not record the update notice");
+    }
+    Some(format!(
+        "ralph {} is available (this is {}): cargo install {}",
+        latest,
+        current,
+        CRATE_NAME
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const CURRENT: &str = env!("CARGO_PKG_VERSION");
+
+    fn pin(dir: &Path, version: &str) {
+        let mut state = ProjectState::new();
+        state.pinned.version = version.to_string();
+        state.save(dir).unwrap();
+    }
+
+    /// A version one minor release away from the running one.
+    fn other_minor() -> String {
+        let current = Version::parse(CURRENT).unwrap();
+        format!("{}.{}.0", current.major, current.minor + 1)
+    }
+
+    struct FixedRegistry(Vec<&'static str>);
+
+    impl RegistryIndex for FixedRegistry {
+        fn versions(&self, _: Ecosystem, _: &str) -> std::result::Result<Vec<Version>, String> {
+            if self.0.is_empty() {
+                return Err("Offline and no cached versions".to_string());
+            }
+            Ok(self.0.iter().filter_map(|v| Version::parse(v)).collect())
+        }
+    }
+
+    #[test]
+    fn test_version_change_levels() {
+        assert_eq!(VersionChange::between("0.3.2", "0.3.2"), VersionChange::Same);
+        assert_eq!(VersionChange::between("0.3.1", "0.3.2"), VersionChange::Patch);
+        assert_eq!(VersionChange::between("0.3.2", "0.3.2-beta.1"), VersionChange::Patch);
+        assert_eq!(VersionChange::between("0.4.0", "0.3.2"), VersionChange::Minor);
+        assert_eq!(VersionChange::between("1.0.0", "0.3.2"), VersionChange::Major);
+        assert_eq!(VersionChange::between("custom", "0.3.2"), VersionChange::Unknown);
+        assert!(!VersionChange::Patch.is_significant());
+        assert!(VersionChange::Minor.is_significant() && VersionChange::Major.is_significant());
+    }
+
+    #[test]
+    fn test_first_run_pins_the_project() {
+        let dir = TempDir::new().unwrap();
+        assert!(ProjectState::load(dir.path()).is_none());
+
+        assert_eq!(check_and_record(dir.path(), true, false).unwrap(), None);
+        let state = ProjectState::load(dir.path()).unwrap();
+        assert_eq!(state.pinned, RalphBuild::current());
+        assert_eq!(state.last_run, RalphBuild::current());
+        assert_eq!(state.version_change(), VersionChange::Same);
+    }
+
+    #[test]
+    fn test_minor_change_warns_and_records_the_run() {
+        let dir = TempDir::new().unwrap();
+        pin(dir.path(), &other_minor());
+
+        let warning = check_and_record(dir.path(), false, false).unwrap().unwrap();
+        assert!(warning.contains(&other_minor()) && warning.contains(CURRENT), "{}", warning);
+        assert!(warning.contains("minor version change"));
+        let state = ProjectState::load(dir.path()).unwrap();
+        assert_eq!(state.pinned.version, other_minor());
+        assert_eq!(state.last_run.version, CURRENT);
+    }
+
+    #[test]
+    fn test_strict_mode_requires_allow_version_change() {
+        let dir = TempDir::new().unwrap();
+        pin(dir.path(), &other_minor());
+
+        let err = check_and_record(dir.path(), true, false).unwrap_err();
+        assert!(err.to_string().contains("--allow-version-change"), "{}", err);
+        assert_eq!(ProjectState::load(dir.path()).unwrap().pinned.version, other_minor());
+
+        assert_eq!(check_and_record(dir.path(), true, true).unwrap(), None);
+        assert_eq!(ProjectState::load(dir.path()).unwrap().pinned, RalphBuild::current());
+        assert_eq!(check_and_record(dir.path(), true, false).unwrap(), None);
+    }
+
+    #[test]
+    fn test_update_notice_once_per_day() {
+        let dir = TempDir::new().unwrap();
+        let newer = FixedRegistry(vec!["0.1.0", "999.0.0", "1000.0.0-beta.1"]);
+
+        let notice = update_notice(dir.path(), "2026-10-16", &newer).unwrap();
+        assert!(notice.contains("ralph 999.0.0 is available"), "{}", notice);
+        assert!(update_notice(dir.path(), "2026-10-16", &newer).is_none());
+        assert!(update_notice(dir.path(), "2026-10-17", &newer).is_some());
+
+        let fresh = TempDir::new().unwrap();
+        assert!(update_notice(fresh.path(), "2026-10-16", &FixedRegistry(vec![CURRENT])).is_none());
+        assert!(update_notice(fresh.path(), "2026-10-16", &FixedRegistry(Vec::new())).is_none());
+    }
+}
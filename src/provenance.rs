@@ -0,0 +1,291 @@
+//! Provenance headers for generated artifacts.
+//!
+//! Every artifact an agent writes records which Ralph version, run and model
+//! produced it. Markdown files (`prd.md`, `design.md`) start with an HTML
+//! comment holding the record as JSON; JSON files (`tasks.json`, design JSON)
+//! carry it as a top-level `_meta` object.
+//!
+//! Re-saving replaces the existing header rather than adding another, and
+//! [`Provenance::merged_with`] keeps agent entries written by earlier phases,
+//! so `design.md` names both the PRD and architect models. The instruction
+//! hash changes whenever an agent's prompt does; the run report also records
+//! a hash of the run's configuration ([`Provenance::with_config`]).
+
+use crate::models::{ModelConfig, RalphConfig};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Opening of the Markdown provenance comment.
+pub const MARKDOWN_HEADER_START: &str = "<!-- ralph-provenance";
+
+/// Close of the Markdown provenance comment.
+const MARKDOWN_HEADER_END: &str = "-->";
+
+/// Key of the provenance object in JSON artifacts.
+pub const JSON_META_KEY: &str = "_meta";
+
+/// The model behind one agent.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentProvenance {
+    pub provider: String,
+    pub model: String,
+    pub temperature: f32,
+    /// SHA-256 of the agent's instruction prompt
+    pub instruction_sha256: String,
+}
+
+impl AgentProvenance {
+    /// Describe an agent configured from a model config.
+    pub fn from_config(config: &ModelConfig, instruction: &str) -> Self {
+        Self {
+            provider: config.provider.to_string(),
+            model: config.model_name.clone(),
+            temperature: config.temperature,
+            instruction_sha256: instruction_hash(instruction),
+        }
+    }
+
+    /// Describe an agent given a ready-made model (provider unknown).
+    pub fn from_model_name(model: &str, config: &ModelConfig, instruction: &str) -> Self {
+        Self {
+            provider: "custom".to_string(),
+            model: model.to_string(),
+            ..Self::from_config(config, instruction)
+        }
+    }
+}
+
+/// Who and what produced an artifact.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Provenance {
+    pub ralph_version: String,
+    /// Commit the binary was built from, when built from a git checkout
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ralph_git_sha: Option<String>,
+    pub run_id: String,
+    /// When the artifact was last written (RFC 3339)
+    pub generated_at: String,
+    /// SHA-256 of the run's settings, when recorded (see [`config_hash`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_sha256: Option<String>,
+    /// Agent name → model details
+    #[serde(default)]
+    pub agents: BTreeMap<String, AgentProvenance>,
+}
+
+impl Provenance {
+    /// Provenance for the current process's run.
+    pub fn new() -> Self {
+        Self {
+            ralph_version: env!("CARGO_PKG_VERSION").to_string(),
+            ralph_git_sha: crate::project_state::git_sha().map(str::to_string),
+            run_id: run_id().to_string(),
+            generated_at: crate::timefmt::now(),
+            config_sha256: None,
+            agents: BTreeMap::new(),
+        }
+    }
+
+    /// Add or replace an agent entry.
+    pub fn with_agent(mut self, agent: impl Into<String>, details: AgentProvenance) -> Self {
+        self.agents.insert(agent.into(), details);
+        self
+    }
+
+    /// Record the hash of the run's configuration.
+    pub fn with_config(mut self, config: &RalphConfig) -> Self {
+        self.config_sha256 = Some(config_hash(config));
+        self
+    }
+
+    /// Carry over agent entries from an earlier record that this one lacks.
+    pub fn merged_with(mut self, earlier: Option<&Provenance>) -> Self {
+        if let Some(earlier) = earlier {
+            for (agent, details) in &earlier.agents {
+                self.agents
+                    .entry(agent.clone())
+                    .or_insert_with(|| details.clone());
+            }
+        }
+        self
+    }
+}
+
+impl Default for Provenance {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// ID shared by every artifact written in this process (`RALPH_RUN_ID` overrides).
+pub fn run_id() -> &'static str {
+    static RUN_ID: OnceLock<String> = OnceLock::new();
+    RUN_ID.get_or_init(|| {
+        std::env::var("RALPH_RUN_ID")
+            .ok()
+            .filter(|id| !id.trim().is_empty())
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string())
+    })
+}
+
+/// SHA-256 of an instruction prompt.
+pub fn instruction_hash(instruction: &str) -> String {
+    crate::archive::sha256_hex(instruction.as_bytes())
+}
+
+/// SHA-256 of a configuration's settings as JSON, with keys in a fixed order.
+///
+/// API keys are not serialized and so do not change the hash; two runs with
+/// the same hash ran with the same settings.
+pub fn config_hash(config: &RalphConfig) -> String {
+    let json = serde_json::to_value(config).map(|value| value.to_string()).unwrap_or_default();
+    crate::archive::sha256_hex(json.as_bytes())
+}
+
+/// Render the Markdown header comment.
+pub fn render_markdown_header(provenance: &Provenance) -> String {
+    let json = serde_json::to_string_pretty(provenance).unwrap_or_else(|_| "{}".to_string());
+    format!("{}\n{}\n{}\n\n", MARKDOWN_HEADER_START, json, MARKDOWN_HEADER_END)
+}
+
+/// Markdown content with its provenance header set, replacing any existing one.
+pub fn with_markdown_header(content: &str, provenance: &Provenance) -> String {
+    format!("{}{}", render_markdown_header(provenance), strip_markdown_header(content))
+}
+
+/// Markdown content without its provenance header.
+pub fn strip_markdown_header(content: &str) -> &str {
+    match split_markdown_header(content) {
+        Some((_, body)) => body,
+        None => content,
+    }
+}
+
+/// Provenance from a Markdown header, if present and well-formed.
+pub fn parse_markdown_header(content: &str) -> Option<Provenance> {
+    let (json, _) = split_markdown_header(content)?;
+    serde_json::from_str(json).ok()
+}
+
+/// Split leading header JSON from the rest of the document.
+fn split_markdown_header(content: &str) -> Option<(&str, &str)> {
+    let rest = content.trim_start().strip_prefix(MARKDOWN_HEADER_START)?;
+    let end = rest.find(MARKDOWN_HEADER_END)?;
+    let body = rest[end + MARKDOWN_HEADER_END.len()..].trim_start_matches(['\r', '\n']);
+    Some((rest[..end].trim(), body))
+}
+
+/// Set the `_meta` object of a JSON artifact.
+pub fn set_json_meta(value: &mut serde_json::Value, provenance: &Provenance) {
+    if let Some(object) = value.as_object_mut() {
+        object.insert(
+            JSON_META_KEY.to_string(),
+            serde_json::to_value(provenance).unwrap_or_default(),
+        );
+    }
+}
+
+/// Provenance from a JSON artifact's `_meta` object.
+pub fn parse_json_meta(value: &serde_json::Value) -> Option<Provenance> {
+    serde_json::from_value(value.get(JSON_META_KEY)?.clone()).ok()
+}
+
+/// Read the provenance recorded in an artifact (Markdown or JSON).
+pub fn read_provenance(path: &Path) -> Result<Option<Provenance>, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+    let is_json = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    if is_json {
+        let value: serde_json::Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse '{}': {}", path.display(), e))?;
+        Ok(parse_json_meta(&value))
+    } else {
+        Ok(parse_markdown_header(&content))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Provenance {
+        let mut provenance = Provenance::new().with_agent(
+            "architect",
+            AgentProvenance::from_config(&ModelConfig::new("Gemini", "gemini-3-pro-preview"), "Design it"),
+        );
+        provenance.generated_at = "2026-10-01T12:00:00+00:00".to_string();
+        provenance
+    }
+
+    #[test]
+    fn test_markdown_header_round_trip_and_replace() {
+        let provenance = sample();
+        let doc = with_markdown_header("# Design: calc\n\nBody\n", &provenance);
+        assert!(doc.starts_with(MARKDOWN_HEADER_START));
+        assert_eq!(parse_markdown_header(&doc), Some(provenance.clone()));
+        assert_eq!(strip_markdown_header(&doc), "# Design: calc\n\nBody\n");
+
+        // Re-saving replaces the header instead of stacking another
+        let mut updated = provenance.clone();
+        updated.generated_at = "2026-01-01T00:00:00Z".to_string();
+        let resaved = with_markdown_header(&doc, &updated);
+        assert_eq!(resaved.matches(MARKDOWN_HEADER_START).count(), 1);
+        assert_eq!(parse_markdown_header(&resaved), Some(updated));
+
+        assert_eq!(parse_markdown_header("# No header"), None);
+        assert_eq!(strip_markdown_header("# No header"), "# No header");
+    }
+
+    #[test]
+    fn test_json_meta_round_trip() {
+        let mut value = serde_json::json!({ "project": "calc" });
+        set_json_meta(&mut value, &sample());
+        assert_eq!(parse_json_meta(&value), Some(sample()));
+        set_json_meta(&mut value, &sample());
+        assert_eq!(value.as_object().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_merge_keeps_earlier_agents_and_instruction_hash_tracks_prompt() {
+        let prd = Provenance::new()
+            .with_agent("prd", AgentProvenance::from_config(&ModelConfig::default(), "Write a PRD"));
+        let merged = sample().merged_with(Some(&prd));
+        assert_eq!(merged.agents.keys().collect::<Vec<_>>(), vec!["architect", "prd"]);
+        assert_eq!(merged.agents["architect"].provider, "gemini");
+        assert_eq!(merged.run_id, run_id());
+
+        assert_eq!(instruction_hash("Design it"), merged.agents["architect"].instruction_sha256);
+        assert_ne!(instruction_hash("Design it!"), instruction_hash("Design it"));
+    }
+
+    #[test]
+    fn test_config_hash_tracks_settings() {
+        let config = RalphConfig::default();
+        let provenance = Provenance::new().with_config(&config);
+        assert_eq!(provenance.config_sha256.as_deref(), Some(config_hash(&config).as_str()));
+        assert_eq!(config_hash(&config).len(), 64);
+
+        let mut changed = config.clone();
+        changed.max_iterations += 1;
+        assert_ne!(config_hash(&changed), config_hash(&config));
+        let mut keyed = config.clone();
+        keyed.agents.ralph_model.api_key = Some("sk-test".to_string());
+        assert_eq!(config_hash(&keyed), config_hash(&config));
+    }
+
+    #[test]
+    fn test_read_provenance_dispatches_on_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let md = dir.path().join("design.md");
+        std::fs::write(&md, with_markdown_header("# Design", &sample())).unwrap();
+        assert_eq!(read_provenance(&md).unwrap(), Some(sample()));
+
+        let json = dir.path().join("tasks.json");
+        std::fs::write(&json, r#"{"project":"calc"}"#).unwrap();
+        assert_eq!(read_provenance(&json).unwrap(), None);
+    }
+}
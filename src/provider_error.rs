@@ -0,0 +1,321 @@
+//! Provider error decoding: opaque model failures into actionable errors.
+//!
+//! Model clients report a failed request as a string that carries the
+//! provider's HTTP status and JSON error body. [`ProviderError::decode`]
+//! reads that body for the failures a user can do something about (an
+//! overloaded or rate-limited provider, exhausted quota, a safety block, an
+//! unknown model, a rejected API key) and says what to do next. Anything it
+//! does not recognise is left to the original message.
+//!
+//! The decoders follow the documented error shapes of each provider and are
+//! tested against captured payloads in `tests/fixtures/provider_errors/`.
+
+use crate::models::Provider;
+use crate::models::config::default_api_key_envs;
+use serde_json::Value;
+
+/// What went wrong, independent of the provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProviderErrorKind {
+    /// The provider is temporarily overloaded
+    Overloaded,
+    /// Too many requests in a short time
+    RateLimited,
+    /// The account has no quota or credit left
+    QuotaExhausted,
+    /// The provider refused to produce the response
+    SafetyBlocked,
+    /// The configured model does not exist or is not available
+    ModelNotFound,
+    /// The API key was rejected
+    Authentication,
+}
+
+impl ProviderErrorKind {
+    /// Whether the same request can succeed later without any change.
+    pub fn is_retryable(self) -> bool {
+        matches!(self, ProviderErrorKind::Overloaded | ProviderErrorKind::RateLimited)
+    }
+}
+
+/// A decoded provider failure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderError {
+    /// Provider that failed (`anthropic`, `openai`, `gemini`)
+    pub provider: String,
+    /// What went wrong
+    pub kind: ProviderErrorKind,
+    /// Whether retrying the same request may succeed
+    pub retryable: bool,
+    /// What the user should do about it
+    pub user_action: String,
+    /// The provider's own explanation, when it gave one
+    pub detail: Option<String>,
+}
+
+impl ProviderError {
+    fn new(provider: &str, kind: ProviderErrorKind, detail: Option<&str>) -> Self {
+        Self {
+            provider: provider.to_string(),
+            kind,
+            retryable: kind.is_retryable(),
+            user_action: default_action(provider, kind),
+            detail: detail.map(str::trim).filter(|d| !d.is_empty()).map(String::from),
+        }
+    }
+
+    /// Decode a failure reported by `provider`'s client.
+    pub fn decode(provider: &str, message: &str) -> Option<Self> {
+        let body = json_body(message);
+        match Provider::from_name(provider) {
+            Provider::Anthropic => decode_anthropic(message, body.as_ref()),
+            Provider::OpenAI => decode_openai(message, body.as_ref()),
+            Provider::Gemini => decode_gemini(message, body.as_ref()),
+            Provider::Ollama | Provider::Replay | Provider::Unknown(_) => None,
+        }
+    }
+
+    /// Decode a failure whose provider is not known, trying each decoder.
+    pub fn decode_any(message: &str) -> Option<Self> {
+        ["anthropic", "openai", "gemini"]
+            .into_iter()
+            .find_map(|provider| Self::decode(provider, message))
+    }
+
+    /// The decoded failure for display, or `message` as given when it is not recognised.
+    ///
+    /// `provider`'s decoder is tried first, then the others: a task may run on
+    /// a different provider than the agent's default.
+    pub fn describe(provider: &str, message: &str) -> String {
+        Self::decode(provider, message)
+            .or_else(|| Self::decode_any(message))
+            .map(|e| e.to_string())
+            .unwrap_or_else(|| message.to_string())
+    }
+}
+
+impl std::fmt::Display for ProviderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = display_name(&self.provider);
+        match self.kind {
+            ProviderErrorKind::Overloaded => write!(f, "{} is overloaded", name)?,
+            ProviderErrorKind::RateLimited => write!(f, "{} rate-limited the request", name)?,
+            ProviderErrorKind::QuotaExhausted => write!(f, "{} quota is exhausted", name)?,
+            ProviderErrorKind::SafetyBlocked => {
+                write!(f, "{} blocked the response for safety reasons", name)?
+            }
+            ProviderErrorKind::ModelNotFound => write!(f, "{} does not have that model", name)?,
+            ProviderErrorKind::Authentication => write!(f, "{} rejected the API key", name)?,
+        }
+        if let Some(detail) = &self.detail {
+            write!(f, " ({})", detail)?;
+        }
+        write!(f, " — {}", self.user_action)
+    }
+}
+
+fn display_name(provider: &str) -> String {
+    Provider::from_name(provider).display_name().to_string()
+}
+
+fn default_action(provider: &str, kind: ProviderErrorKind) -> String {
+    let name = display_name(provider);
+    match kind {
+        ProviderErrorKind::Overloaded => "wait a few minutes, then resume the run".to_string(),
+        ProviderErrorKind::RateLimited => {
+            "wait for the rate limit to reset, then resume the run".to_string()
+        }
+        ProviderErrorKind::QuotaExhausted => format!(
+            "add credit or raise the usage limit in your {} account, or switch provider",
+            name
+        ),
+        ProviderErrorKind::SafetyBlocked => {
+            "consider rephrasing the part of the PRD or prompt that triggered it".to_string()
+        }
+        ProviderErrorKind::ModelNotFound => format!(
+            "check the configured model name (RALPH_*_MODEL) against {}'s model list",
+            name
+        ),
+        ProviderErrorKind::Authentication => match default_api_key_envs(&Provider::from_name(provider)).first() {
+            Some(var) => format!("check {} (or the model's api_key_env)", var),
+            None => "check the configured API key".to_string(),
+        },
+    }
+}
+
+/// The first JSON object in a client error message.
+fn json_body(message: &str) -> Option<Value> {
+    let start = message.find('{')?;
+    serde_json::Deserializer::from_str(&message[start..])
+        .into_iter::<Value>()
+        .next()?
+        .ok()
+}
+
+/// An HTTP error status mentioned in the message, e.g. the `529` of `HTTP 529:`.
+pub(crate) fn http_status(message: &str) -> Option<u16> {
+    let head = message.split('{').next().unwrap_or(message);
+    head.split(|c: char| !c.is_ascii_digit())
+        .filter_map(|word| word.parse::<u16>().ok())
+        .find(|status| (400..600).contains(status))
+}
+
+fn str_at<'a>(value: Option<&'a Value>, pointer: &str) -> Option<&'a str> {
+    value?.pointer(pointer)?.as_str()
+}
+
+fn decode_anthropic(message: &str, body: Option<&Value>) -> Option<ProviderError> {
+    let detail = str_at(body, "/error/message");
+    let kind = match str_at(body, "/error/type") {
+        Some("overloaded_error") => ProviderErrorKind::Overloaded,
+        Some("rate_limit_error") => ProviderErrorKind::RateLimited,
+        Some("authentication_error") => ProviderErrorKind::Authentication,
+        Some("not_found_error") if detail.is_some_and(|d| d.starts_with("model")) => {
+            ProviderErrorKind::ModelNotFound
+        }
+        Some(_) => return None,
+        None if http_status(message) == Some(529) => ProviderErrorKind::Overloaded,
+        None => return None,
+    };
+    Some(ProviderError::new("anthropic", kind, detail))
+}
+
+fn decode_openai(message: &str, body: Option<&Value>) -> Option<ProviderError> {
+    let detail = str_at(body, "/error/message");
+    let code = str_at(body, "/error/code").or_else(|| str_at(body, "/error/type"));
+    let kind = match code {
+        Some("insufficient_quota") => ProviderErrorKind::QuotaExhausted,
+        Some("rate_limit_exceeded") => ProviderErrorKind::RateLimited,
+        Some("model_not_found") => ProviderErrorKind::ModelNotFound,
+        Some("invalid_api_key") => ProviderErrorKind::Authentication,
+        Some("content_policy_violation") | Some("content_filter") => {
+            ProviderErrorKind::SafetyBlocked
+        }
+        _ if http_status(message) == Some(503) && message.contains("overloaded") => {
+            ProviderErrorKind::Overloaded
+        }
+        _ => return None,
+    };
+    Some(ProviderError::new("openai", kind, detail))
+}
+
+fn decode_gemini(_message: &str, body: Option<&Value>) -> Option<ProviderError> {
+    let body = body?;
+
+    // A blocked prompt or candidate is a successful response without content
+    let blocked = [
+        ("/promptFeedback/blockReason", "/promptFeedback/safetyRatings"),
+        ("/candidates/0/finishReason", "/candidates/0/safetyRatings"),
+    ]
+    .into_iter()
+    .find(|(reason, _)| str_at(Some(body), reason) == Some("SAFETY"));
+    if let Some((_, ratings)) = blocked {
+        let categories = flagged_categories(body.pointer(ratings));
+        let mut error = ProviderError::new(
+            "gemini",
+            ProviderErrorKind::SafetyBlocked,
+            (!categories.is_empty()).then(|| categories.join(", ")).as_deref(),
+        );
+        if !categories.is_empty() {
+            error.user_action =
+                format!("consider rephrasing the PRD section about {}", categories.join(" and "));
+        }
+        return Some(error);
+    }
+
+    let detail = str_at(Some(body), "/error/message");
+    let key_invalid = body
+        .pointer("/error/details")
+        .and_then(Value::as_array)
+        .is_some_and(|details| {
+            details.iter().any(|d| d["reason"].as_str() == Some("API_KEY_INVALID"))
+        });
+    let kind = match str_at(Some(body), "/error/status") {
+        _ if key_invalid => ProviderErrorKind::Authentication,
+        Some("NOT_FOUND") if detail.is_some_and(|d| d.contains("models/")) => {
+            ProviderErrorKind::ModelNotFound
+        }
+        Some("RESOURCE_EXHAUSTED") => ProviderErrorKind::RateLimited,
+        Some("UNAVAILABLE") => ProviderErrorKind::Overloaded,
+        Some("UNAUTHENTICATED") | Some("PERMISSION_DENIED") => ProviderErrorKind::Authentication,
+        _ => return None,
+    };
+    Some(ProviderError::new("gemini", kind, detail))
+}
+
+/// Safety categories rated likely enough to block, e.g. `dangerous content`.
+fn flagged_categories(ratings: Option<&Value>) -> Vec<String> {
+    ratings
+        .and_then(Value::as_array)
+        .map(|ratings| {
+            ratings
+                .iter()
+                .filter(|r| {
+                    r["blocked"].as_bool() == Some(true)
+                        || matches!(r["probability"].as_str(), Some("HIGH" | "MEDIUM"))
+                })
+                .filter_map(|r| r["category"].as_str())
+                .map(|c| c.trim_start_matches("HARM_CATEGORY_").replace('_', " ").to_lowercase())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> String {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/provider_errors")
+            .join(name);
+        std::fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn test_captured_payloads_decode() {
+        let cases = [
+            ("anthropic", "anthropic_overloaded.txt", ProviderErrorKind::Overloaded, true),
+            ("anthropic", "anthropic_model_not_found.txt", ProviderErrorKind::ModelNotFound, false),
+            ("openai", "openai_insufficient_quota.txt", ProviderErrorKind::QuotaExhausted, false),
+            ("openai", "openai_model_not_found.txt", ProviderErrorKind::ModelNotFound, false),
+            ("gemini", "gemini_safety.txt", ProviderErrorKind::SafetyBlocked, false),
+            ("gemini", "gemini_model_not_found.txt", ProviderErrorKind::ModelNotFound, false),
+        ];
+        for (provider, file, kind, retryable) in cases {
+            let message = fixture(file);
+            let error = ProviderError::decode(provider, &message)
+                .unwrap_or_else(|| panic!("{} did not decode", file));
+            assert_eq!((error.kind, error.retryable), (kind, retryable), "{}", file);
+            assert_eq!(ProviderError::decode_any(&message).unwrap().kind, kind, "{}", file);
+        }
+    }
+
+    #[test]
+    fn test_messages_say_what_to_do() {
+        let safety = ProviderError::decode("gemini", &fixture("gemini_safety.txt")).unwrap();
+        assert_eq!(
+            safety.to_string(),
+            "Gemini blocked the response for safety reasons (dangerous content) — consider \
+             rephrasing the PRD section about dangerous content"
+        );
+
+        let quota =
+            ProviderError::decode("openai", &fixture("openai_insufficient_quota.txt")).unwrap();
+        assert!(quota.to_string().starts_with("OpenAI quota is exhausted (You exceeded"));
+        assert!(quota.user_action.contains("OpenAI account"));
+
+        let model =
+            ProviderError::decode("anthropic", &fixture("anthropic_model_not_found.txt")).unwrap();
+        assert!(model.to_string().contains("(model: claude-opus-9)"));
+    }
+
+    #[test]
+    fn test_unrecognised_failures_keep_their_message() {
+        let message = r#"HTTP 400: {"type":"error","error":{"type":"invalid_request_error","message":"max_tokens too large"}}"#;
+        assert_eq!(ProviderError::decode("anthropic", message), None);
+        assert_eq!(ProviderError::describe("anthropic", message), message);
+        assert_eq!(ProviderError::decode_any("connection reset by peer"), None);
+        assert_eq!(ProviderError::decode("ollama", &fixture("anthropic_overloaded.txt")), None);
+    }
+}
@@ -0,0 +1,189 @@
+//! Replay provider: plays back recorded model responses instead of calling a model.
+//!
+//! Select it with provider `replay`. The model name is either one of the
+//! recordings embedded in the binary ([`RECORDINGS`], used by `ralph demo`) or
+//! the path of a recording file, so no API key or network is needed.
+//!
+//! A recording is a JSON array of turns, returned in order whatever the
+//! request contains:
+//!
+//! ```json
+//! [
+//!   { "calls": [{ "name": "tasks", "args": { "operation": "get_next" } }] },
+//!   { "text": "All tasks completed successfully!" }
+//! ]
+//! ```
+//!
+//! Once the turns run out the model answers with a short text, which ends
+//! the agent's turn.
+
+use crate::{RalphError, Result};
+use adk_rust::{
+    Content, Llm, LlmRequest, LlmResponse, LlmResponseStream, Part, Result as AdkResult,
+};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// Recordings embedded in the binary, by model name.
+pub const RECORDINGS: &[(&str, &str)] = &[
+    ("demo-prd", include_str!("../demo/prd.json")),
+    ("demo-architect", include_str!("../demo/architect.json")),
+    ("demo-loop", include_str!("../demo/loop.json")),
+];
+
+/// Text returned once a recording is exhausted.
+const END_OF_RECORDING: &str = "End of recording.";
+
+/// One recorded model response.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum ReplayTurn {
+    /// Plain text (e.g. structured JSON output)
+    Text { text: String },
+    /// Tool calls, executed by the agent before the next turn
+    Calls { calls: Vec<ReplayCall> },
+}
+
+/// A recorded tool call.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ReplayCall {
+    /// Tool name
+    pub name: String,
+    /// Tool arguments
+    #[serde(default)]
+    pub args: Value,
+}
+
+impl ReplayTurn {
+    /// The turn as model content.
+    fn to_content(&self) -> Content {
+        let parts = match self {
+            ReplayTurn::Text { text } => vec![Part::Text { text: text.clone() }],
+            ReplayTurn::Calls { calls } => calls
+                .iter()
+                .map(|call| Part::FunctionCall {
+                    name: call.name.clone(),
+                    args: call.args.clone(),
+                    id: None,
+                })
+                .collect(),
+        };
+        Content {
+            role: "model".to_string(),
+            parts,
+        }
+    }
+}
+
+/// A model that replays a recording.
+pub struct ReplayModel {
+    name: String,
+    turns: Mutex<VecDeque<ReplayTurn>>,
+}
+
+impl std::fmt::Debug for ReplayModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReplayModel")
+            .field("name", &self.name)
+            .field("remaining", &self.remaining())
+            .finish()
+    }
+}
+
+impl ReplayModel {
+    /// Parse a recording.
+    pub fn from_json(name: impl Into<String>, json: &str) -> Result<Self> {
+        let name = name.into();
+        let turns: VecDeque<ReplayTurn> = serde_json::from_str(json).map_err(|e| {
+            RalphError::Configuration(format!("Invalid replay recording '{}': {}", name, e))
+        })?;
+        Ok(Self {
+            name,
+            turns: Mutex::new(turns),
+        })
+    }
+
+    /// Load an embedded recording by name, or a recording file by path.
+    pub fn load(name: &str) -> Result<Self> {
+        if let Some((_, json)) = RECORDINGS.iter().find(|(n, _)| *n == name) {
+            return Self::from_json(name, json);
+        }
+        let json = std::fs::read_to_string(name).map_err(|e| {
+            RalphError::Configuration(format!(
+                "Replay recording '{}' is neither embedded ({}) nor a readable file: {}",
+                name,
+                RECORDINGS.iter().map(|(n, _)| *n).collect::<Vec<_>>().join(", "),
+                e
+            ))
+        })?;
+        Self::from_json(name, &json)
+    }
+
+    /// Turns not yet played.
+    pub fn remaining(&self) -> usize {
+        self.turns.lock().unwrap().len()
+    }
+
+    /// Take the next turn, or the end-of-recording text.
+    fn next_turn(&self) -> ReplayTurn {
+        self.turns.lock().unwrap().pop_front().unwrap_or_else(|| ReplayTurn::Text {
+            text: END_OF_RECORDING.to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl Llm for ReplayModel {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn generate_content(
+        &self,
+        _request: LlmRequest,
+        _stream: bool,
+    ) -> AdkResult<LlmResponseStream> {
+        let response = LlmResponse::new(self.next_turn().to_content());
+        Ok(Box::pin(futures::stream::once(async move { Ok(response) })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_turns_play_in_order_then_end() {
+        let model = ReplayModel::from_json(
+            "t",
+            r#"[{"calls": [{"name": "tasks", "args": {"operation": "list"}}]}, {"text": "done"}]"#,
+        )
+        .unwrap();
+        assert_eq!(model.remaining(), 2);
+        assert_eq!(
+            model.next_turn(),
+            ReplayTurn::Calls {
+                calls: vec![ReplayCall {
+                    name: "tasks".to_string(),
+                    args: serde_json::json!({"operation": "list"}),
+                }],
+            }
+        );
+        assert_eq!(model.next_turn(), ReplayTurn::Text { text: "done".to_string() });
+        assert_eq!(
+            model.next_turn(),
+            ReplayTurn::Text { text: END_OF_RECORDING.to_string() }
+        );
+    }
+
+    #[test]
+    fn test_embedded_recordings_parse() {
+        for (name, _) in RECORDINGS {
+            assert!(ReplayModel::load(name).unwrap().remaining() > 0, "{} is empty", name);
+        }
+        assert!(ReplayModel::load("no-such-recording.json").is_err());
+    }
+}
@@ -0,0 +1,538 @@
+//! Resource usage of the generated project's commands.
+//!
+//! Build, test and run commands are measured while they run: wall time,
+//! user and system CPU time, and peak resident memory. The usage goes into the
+//! tool's response and adds up into the task's record, so a task whose tests
+//! take 40 minutes of CPU or 6 GB of memory is visible before it is a problem.
+//!
+//! Optional [`ResourceLimits`] (`RALPH_MAX_RSS_MB`, `RALPH_MAX_CPU_SECS`) stop
+//! a command that goes over them. The command runs in its own process group,
+//! which is killed as a whole, and the breach is reported as a
+//! [`LimitBreach`] the agent is told about in plain words.
+//!
+//! Platform support:
+//!
+//! - Linux: usage from `wait4`, limits enforced while running by sampling the
+//!   process group in `/proc`
+//! - Other unix: usage from `wait4`; limits are checked when the command ends
+//! - Windows: wall time only; a timed-out command's process tree is killed
+//!   with `taskkill /T`. Limits cannot be enforced, so they are refused (by
+//!   config validation, and by [`run_measured`] with
+//!   [`io::ErrorKind::Unsupported`]) rather than silently ignored
+//! - Elsewhere: wall time only; a timed-out command is killed but its own
+//!   children may outlive it
+
+use serde::{Deserialize, Serialize};
+use std::io::{self, Read};
+use std::process::{Child, ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+
+/// How often a running command is checked against its timeout and limits.
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Resources used by one command, or added up over a task's commands.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    /// Elapsed time in milliseconds
+    pub wall_ms: u64,
+    /// CPU time in user mode, in milliseconds (`None` where not measured)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_cpu_ms: Option<u64>,
+    /// CPU time in the kernel, in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_cpu_ms: Option<u64>,
+    /// Largest resident memory, in KiB
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peak_rss_kb: Option<u64>,
+}
+
+impl ResourceUsage {
+    /// User plus system CPU time, when measured.
+    pub fn cpu_ms(&self) -> Option<u64> {
+        match (self.user_cpu_ms, self.system_cpu_ms) {
+            (None, None) => None,
+            (user, system) => Some(user.unwrap_or(0) + system.unwrap_or(0)),
+        }
+    }
+
+    /// Add another command's usage: times add up, the peak is the larger one.
+    pub fn add(&mut self, other: &ResourceUsage) {
+        fn sum(a: Option<u64>, b: Option<u64>) -> Option<u64> {
+            match (a, b) {
+                (None, None) => None,
+                (a, b) => Some(a.unwrap_or(0) + b.unwrap_or(0)),
+            }
+        }
+        self.wall_ms += other.wall_ms;
+        self.user_cpu_ms = sum(self.user_cpu_ms, other.user_cpu_ms);
+        self.system_cpu_ms = sum(self.system_cpu_ms, other.system_cpu_ms);
+        self.peak_rss_kb = self.peak_rss_kb.max(other.peak_rss_kb);
+    }
+}
+
+impl std::fmt::Display for ResourceUsage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.1}s wall", self.wall_ms as f64 / 1000.0)?;
+        if let Some(cpu) = self.cpu_ms() {
+            write!(f, ", {:.1}s CPU", cpu as f64 / 1000.0)?;
+        }
+        if let Some(kb) = self.peak_rss_kb {
+            write!(f, ", {} peak memory", format_mb(kb / 1024))?;
+        }
+        Ok(())
+    }
+}
+
+/// Limits on one command's resources; `None` means unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceLimits {
+    /// Resident memory of the command's process group, in MiB
+    pub max_rss_mb: Option<u64>,
+    /// CPU time of the command's process group, in seconds
+    pub max_cpu_secs: Option<u64>,
+}
+
+impl ResourceLimits {
+    /// Whether no limit is set.
+    pub fn is_unlimited(&self) -> bool {
+        self.max_rss_mb.is_none() && self.max_cpu_secs.is_none()
+    }
+
+    /// The limit `usage` goes over, memory first.
+    pub fn breach(&self, usage: &ResourceUsage) -> Option<LimitBreach> {
+        if let (Some(limit_mb), Some(kb)) = (self.max_rss_mb, usage.peak_rss_kb) {
+            if kb > limit_mb * 1024 {
+                return Some(LimitBreach::Memory { limit_mb, peak_mb: kb / 1024 });
+            }
+        }
+        if let (Some(limit_secs), Some(ms)) = (self.max_cpu_secs, usage.cpu_ms()) {
+            if ms > limit_secs * 1000 {
+                return Some(LimitBreach::Cpu { limit_secs, used_secs: ms / 1000 });
+            }
+        }
+        None
+    }
+}
+
+/// A command that went over one of its [`ResourceLimits`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LimitBreach {
+    /// Resident memory went over `limit_mb`
+    Memory { limit_mb: u64, peak_mb: u64 },
+    /// CPU time went over `limit_secs`
+    Cpu { limit_secs: u64, used_secs: u64 },
+}
+
+impl LimitBreach {
+    /// Short name of the failure, for structured responses and logs.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Memory { .. } => "memory_limit",
+            Self::Cpu { .. } => "cpu_limit",
+        }
+    }
+
+    /// Explanation for the agent, naming what ran (`"Your tests"`, `"The build"`).
+    pub fn describe(&self, what: &str) -> String {
+        match self {
+            Self::Memory { limit_mb, peak_mb } => format!(
+                "{} exceeded the {} memory limit (reached {}); the command was stopped",
+                what,
+                format_mb(*limit_mb),
+                format_mb(*peak_mb)
+            ),
+            Self::Cpu { limit_secs, used_secs } => format!(
+                "{} exceeded the {}s CPU time limit (used {}s); the command was stopped",
+                what, limit_secs, used_secs
+            ),
+        }
+    }
+}
+
+/// `mb` as MB, or as GB from 1 GB up.
+fn format_mb(mb: u64) -> String {
+    if mb < 1024 {
+        format!("{} MB", mb)
+    } else if mb % 1024 == 0 {
+        format!("{} GB", mb / 1024)
+    } else {
+        format!("{:.1} GB", mb as f64 / 1024.0)
+    }
+}
+
+/// Output of a measured command.
+#[derive(Debug)]
+pub struct MeasuredOutput {
+    /// How the command ended
+    pub status: ExitStatus,
+    /// Captured standard output
+    pub stdout: Vec<u8>,
+    /// Captured standard error
+    pub stderr: Vec<u8>,
+    /// What the command used
+    pub usage: ResourceUsage,
+    /// The limit it went over, if any (the command was stopped)
+    pub breach: Option<LimitBreach>,
+}
+
+/// Run `command` to completion, capturing its output and measuring it.
+///
+/// Like [`tokio::process::Command::output`], with stdin closed. A command that
+/// runs longer than `timeout` is killed and fails with
+/// [`io::ErrorKind::TimedOut`]; one that goes over `limits` is killed and
+/// returned with its [`LimitBreach`]. Outside unix, limits cannot be enforced
+/// and fail with [`io::ErrorKind::Unsupported`] before the command starts.
+pub async fn run_measured(
+    command: &mut tokio::process::Command,
+    limits: ResourceLimits,
+    timeout: Duration,
+) -> io::Result<MeasuredOutput> {
+    if !cfg!(unix) && !limits.is_unlimited() {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "resource limits are only enforced on unix; unset RALPH_MAX_RSS_MB and RALPH_MAX_CPU_SECS",
+        ));
+    }
+    let command = command.as_std_mut();
+    command.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        // Its own group, so a breach kills the tool's children too
+        command.process_group(0);
+    }
+
+    let started = Instant::now();
+    let mut child = command.spawn()?;
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+
+    tokio::task::spawn_blocking(move || {
+        let stdout = std::thread::spawn(move || read_pipe(stdout));
+        let stderr = std::thread::spawn(move || read_pipe(stderr));
+        let waited = platform::wait(child, limits, timeout, started)?;
+        let stdout = stdout.join().unwrap_or_default();
+        let stderr = stderr.join().unwrap_or_default();
+
+        let mut usage = waited.usage;
+        usage.wall_ms = started.elapsed().as_millis() as u64;
+        let breach = match waited.stop {
+            Some(Stop::TimedOut) => {
+                return Err(io::Error::new(io::ErrorKind::TimedOut, "command timed out"));
+            }
+            Some(Stop::Breach(breach)) => Some(breach),
+            // Over a limit between two samples, or where limits are only checked at the end
+            None => limits.breach(&usage),
+        };
+        if let Some(breach) = &breach {
+            tracing::warn!(kind = breach.kind(), %usage, "Command went over its resource limit");
+        }
+        Ok(MeasuredOutput { status: waited.status, stdout, stderr, usage, breach })
+    })
+    .await
+    .map_err(io::Error::other)?
+}
+
+fn read_pipe(pipe: Option<impl Read>) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    if let Some(mut pipe) = pipe {
+        let _ = pipe.read_to_end(&mut bytes);
+    }
+    bytes
+}
+
+/// Why a command was stopped before it ended by itself.
+enum Stop {
+    TimedOut,
+    Breach(LimitBreach),
+}
+
+/// A reaped command.
+struct Waited {
+    status: ExitStatus,
+    usage: ResourceUsage,
+    stop: Option<Stop>,
+}
+
+#[cfg(unix)]
+mod platform {
+    use super::{Child, ExitStatus, Instant, LimitBreach, ResourceLimits, ResourceUsage, Stop, Waited};
+    use super::{SAMPLE_INTERVAL, io};
+    use std::os::unix::process::ExitStatusExt;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    /// Reap `child` with `wait4` while a monitor thread enforces the timeout and limits.
+    pub(super) fn wait(
+        child: Child,
+        limits: ResourceLimits,
+        timeout: Duration,
+        started: Instant,
+    ) -> io::Result<Waited> {
+        let pid = child.id() as libc::pid_t;
+        let done = Arc::new(AtomicBool::new(false));
+        let stop = Arc::new(Mutex::new(None));
+
+        let monitor = {
+            let (done, stop) = (done.clone(), stop.clone());
+            std::thread::spawn(move || {
+                while !done.load(Ordering::SeqCst) {
+                    let reason = if started.elapsed() >= timeout {
+                        Some(Stop::TimedOut)
+                    } else if limits.is_unlimited() {
+                        None
+                    } else {
+                        sample_group(pid).and_then(|usage| limits.breach(&usage)).map(Stop::Breach)
+                    };
+                    if let Some(reason) = reason {
+                        *stop.lock().unwrap() = Some(reason);
+                        // SAFETY: plain syscall without pointers; the child is its group's leader
+                        unsafe { libc::kill(-pid, libc::SIGKILL) };
+                        return;
+                    }
+                    std::thread::sleep(SAMPLE_INTERVAL);
+                }
+            })
+        };
+
+        let mut status = 0;
+        // SAFETY: an all-zero rusage is a valid value for wait4 to fill in
+        let mut rusage: libc::rusage = unsafe { std::mem::zeroed() };
+        loop {
+            // SAFETY: `pid` is our unreaped child and both out-pointers are valid
+            let reaped = unsafe { libc::wait4(pid, &mut status, 0, &mut rusage) };
+            if reaped == pid {
+                break;
+            }
+            let err = io::Error::last_os_error();
+            if err.kind() != io::ErrorKind::Interrupted {
+                done.store(true, Ordering::SeqCst);
+                let _ = monitor.join();
+                return Err(err);
+            }
+        }
+        done.store(true, Ordering::SeqCst);
+        let _ = monitor.join();
+        drop(child);
+
+        let usage = ResourceUsage {
+            wall_ms: 0,
+            user_cpu_ms: Some(timeval_ms(rusage.ru_utime)),
+            system_cpu_ms: Some(timeval_ms(rusage.ru_stime)),
+            peak_rss_kb: Some(max_rss_kb(rusage.ru_maxrss)),
+        };
+        let stop = stop.lock().unwrap().take();
+        // A sampled breach may have seen more memory than the reaped process tree reports
+        let usage = match &stop {
+            Some(Stop::Breach(LimitBreach::Memory { peak_mb, .. })) => ResourceUsage {
+                peak_rss_kb: usage.peak_rss_kb.max(Some(peak_mb * 1024)),
+                ..usage
+            },
+            _ => usage,
+        };
+        Ok(Waited { status: ExitStatus::from_raw(status), usage, stop })
+    }
+
+    fn timeval_ms(time: libc::timeval) -> u64 {
+        (time.tv_sec as u64) * 1000 + (time.tv_usec as u64) / 1000
+    }
+
+    /// `ru_maxrss` in KiB (macOS reports bytes).
+    fn max_rss_kb(max_rss: libc::c_long) -> u64 {
+        if cfg!(target_os = "macos") { max_rss as u64 / 1024 } else { max_rss as u64 }
+    }
+
+    /// Current memory and CPU of every process in group `pgid`.
+    #[cfg(target_os = "linux")]
+    fn sample_group(pgid: libc::pid_t) -> Option<ResourceUsage> {
+        // SAFETY: sysconf has no preconditions
+        let (page_kb, ticks) = unsafe {
+            (libc::sysconf(libc::_SC_PAGESIZE) as u64 / 1024, libc::sysconf(libc::_SC_CLK_TCK) as u64)
+        };
+        let mut usage = ResourceUsage { wall_ms: 0, user_cpu_ms: Some(0), system_cpu_ms: Some(0), peak_rss_kb: Some(0) };
+        for entry in std::fs::read_dir("/proc").ok()?.flatten() {
+            let name = entry.file_name();
+            if !name.to_string_lossy().bytes().all(|b| b.is_ascii_digit()) {
+                continue;
+            }
+            let Ok(stat) = std::fs::read_to_string(entry.path().join("stat")) else {
+                continue;
+            };
+            // Fields after the parenthesised command name, starting at field 3 (state)
+            let Some((_, rest)) = stat.rsplit_once(')') else {
+                continue;
+            };
+            let fields: Vec<&str> = rest.split_whitespace().collect();
+            let field = |n: usize| fields.get(n - 3).and_then(|f| f.parse::<u64>().ok());
+            if field(5) != Some(pgid as u64) {
+                continue;
+            }
+            let to_ms = |t: Option<u64>| t.unwrap_or(0) * 1000 / ticks.max(1);
+            usage.user_cpu_ms = usage.user_cpu_ms.map(|ms| ms + to_ms(field(14)));
+            usage.system_cpu_ms = usage.system_cpu_ms.map(|ms| ms + to_ms(field(15)));
+            usage.peak_rss_kb = usage.peak_rss_kb.map(|kb| kb + field(24).unwrap_or(0) * page_kb);
+        }
+        Some(usage)
+    }
+
+    /// No live sampling here; limits are checked once the command ends.
+    #[cfg(not(target_os = "linux"))]
+    fn sample_group(_pgid: libc::pid_t) -> Option<ResourceUsage> {
+        None
+    }
+}
+
+#[cfg(not(unix))]
+mod platform {
+    use super::{Child, Instant, ResourceLimits, ResourceUsage, SAMPLE_INTERVAL, Stop, Waited, io};
+    use std::time::Duration;
+
+    /// Poll `child` for its exit, killing its process tree at the timeout;
+    /// only wall time is measured.
+    pub(super) fn wait(
+        mut child: Child,
+        _limits: ResourceLimits,
+        timeout: Duration,
+        started: Instant,
+    ) -> io::Result<Waited> {
+        loop {
+            if let Some(status) = child.try_wait()? {
+                return Ok(Waited { status, usage: ResourceUsage::default(), stop: None });
+            }
+            if started.elapsed() >= timeout {
+                kill_tree(&mut child);
+                let status = child.wait()?;
+                return Ok(Waited { status, usage: ResourceUsage::default(), stop: Some(Stop::TimedOut) });
+            }
+            std::thread::sleep(SAMPLE_INTERVAL);
+        }
+    }
+
+    /// Kill `child` with its descendants where the platform has a way to find
+    /// them (`taskkill /T` on Windows); elsewhere only `child` itself.
+    fn kill_tree(child: &mut Child) {
+        #[cfg(windows)]
+        {
+            let _ = std::process::Command::new("taskkill")
+                .args(["/T", "/F", "/PID", &child.id().to_string()])
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .status();
+        }
+        let _ = child.kill();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_adds_up_per_task() {
+        let mut total = ResourceUsage::default();
+        total.add(&ResourceUsage { wall_ms: 1500, user_cpu_ms: Some(1000), system_cpu_ms: Some(200), peak_rss_kb: Some(4096) });
+        total.add(&ResourceUsage { wall_ms: 500, user_cpu_ms: Some(300), system_cpu_ms: None, peak_rss_kb: Some(2048) });
+        assert_eq!(total.wall_ms, 2000);
+        assert_eq!(total.cpu_ms(), Some(1500));
+        assert_eq!(total.peak_rss_kb, Some(4096));
+        assert_eq!(total.to_string(), "2.0s wall, 1.5s CPU, 4 MB peak memory");
+        assert_eq!(ResourceUsage { wall_ms: 300, ..Default::default() }.to_string(), "0.3s wall");
+    }
+
+    #[test]
+    fn test_limit_breaches() {
+        let limits = ResourceLimits { max_rss_mb: Some(2048), max_cpu_secs: Some(60) };
+        let usage = |kb, ms| ResourceUsage { wall_ms: 0, user_cpu_ms: Some(ms), system_cpu_ms: None, peak_rss_kb: Some(kb) };
+        assert_eq!(limits.breach(&usage(1024, 1000)), None);
+        assert_eq!(
+            limits.breach(&usage(3 * 1024 * 1024, 90_000)),
+            Some(LimitBreach::Memory { limit_mb: 2048, peak_mb: 3072 })
+        );
+        assert_eq!(limits.breach(&usage(1024, 90_000)), Some(LimitBreach::Cpu { limit_secs: 60, used_secs: 90 }));
+        assert!(ResourceLimits::default().is_unlimited());
+        assert_eq!(ResourceLimits::default().breach(&usage(3 * 1024 * 1024, 90_000)), None);
+
+        let memory = LimitBreach::Memory { limit_mb: 2048, peak_mb: 2150 };
+        assert_eq!(memory.kind(), "memory_limit");
+        assert_eq!(
+            memory.describe("Your tests"),
+            "Your tests exceeded the 2 GB memory limit (reached 2.1 GB); the command was stopped"
+        );
+        let cpu = LimitBreach::Cpu { limit_secs: 60, used_secs: 61 };
+        assert_eq!(
+            cpu.describe("The build"),
+            "The build exceeded the 60s CPU time limit (used 61s); the command was stopped"
+        );
+        assert_eq!(serde_json::to_value(cpu).unwrap()["kind"], "cpu");
+    }
+
+    /// Environment variable that turns [`memory_hog`] into a helper process.
+    const HOG_MB: &str = "RALPH_TEST_MEMORY_HOG_MB";
+
+    /// Helper process for the tests below: holds `RALPH_TEST_MEMORY_HOG_MB`
+    /// MiB of touched memory for `RALPH_TEST_MEMORY_HOG_SECS` seconds.
+    #[test]
+    #[ignore = "helper process, started by the resource tests"]
+    fn memory_hog() {
+        let Some(mb) = std::env::var(HOG_MB).ok().and_then(|v| v.parse::<usize>().ok()) else {
+            return;
+        };
+        let mut memory = vec![0u8; mb * 1024 * 1024];
+        for byte in memory.iter_mut().step_by(4096) {
+            *byte = 1;
+        }
+        let secs = std::env::var("RALPH_TEST_MEMORY_HOG_SECS").ok().and_then(|v| v.parse().ok()).unwrap_or(0);
+        std::thread::sleep(Duration::from_secs(secs));
+        std::hint::black_box(&memory);
+    }
+
+    fn hog(mb: u64, secs: u64) -> tokio::process::Command {
+        let mut command = tokio::process::Command::new(std::env::current_exe().unwrap());
+        command
+            .args(["--exact", "resources::tests::memory_hog", "--ignored", "--nocapture", "--test-threads=1"])
+            .env(HOG_MB, mb.to_string())
+            .env("RALPH_TEST_MEMORY_HOG_SECS", secs.to_string());
+        command
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_command_usage_is_measured() {
+        let output = run_measured(&mut hog(64, 0), ResourceLimits::default(), Duration::from_secs(60)).await.unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        assert!(output.usage.peak_rss_kb.unwrap() >= 64 * 1024, "{:?}", output.usage);
+        assert!(output.usage.cpu_ms().is_some());
+        assert!(output.breach.is_none());
+
+        let mut command = tokio::process::Command::new("sh");
+        command.args(["-c", "echo out; echo err >&2; exit 3"]);
+        let output = run_measured(&mut command, ResourceLimits::default(), Duration::from_secs(60)).await.unwrap();
+        assert_eq!(output.status.code(), Some(3));
+        assert_eq!(output.stdout, b"out\n");
+        assert_eq!(output.stderr, b"err\n");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_slow_command_times_out() {
+        let mut command = tokio::process::Command::new("sleep");
+        command.arg("30");
+        let started = Instant::now();
+        let err = run_measured(&mut command, ResourceLimits::default(), Duration::from_millis(200)).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_memory_limit_stops_the_command() {
+        let limits = ResourceLimits { max_rss_mb: Some(128), max_cpu_secs: None };
+        let started = Instant::now();
+        let output = run_measured(&mut hog(512, 30), limits, Duration::from_secs(60)).await.unwrap();
+        assert!(started.elapsed() < Duration::from_secs(20), "the command was not stopped");
+        assert!(!output.status.success());
+        assert!(matches!(output.breach, Some(LimitBreach::Memory { limit_mb: 128, peak_mb }) if peak_mb > 128));
+    }
+}
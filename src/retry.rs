@@ -0,0 +1,135 @@
+//! Retrying model calls that failed for a transient reason.
+//!
+//! A provider that answers 429 or 503 mid-run would otherwise end the whole
+//! pipeline. [`RetryPolicy`] says how often a call is attempted and how long
+//! to wait in between: the wait doubles from `initial_delay` up to
+//! `max_delay`, and with `jitter` a random part of each wait is dropped so
+//! that parallel runs hitting the same limit do not retry in lockstep.
+//!
+//! The architect sends its message again; the build loop resumes its runner,
+//! picking up from the task store where the failed call left off.
+//!
+//! Which failures are worth retrying is decided by
+//! [`RalphError::is_retryable`](crate::RalphError::is_retryable): rate
+//! limits, overload, other 5xx responses, timeouts and empty replies are;
+//! rejected keys, bad requests and exhausted quota are not.
+
+use std::time::Duration;
+
+/// How many times a model call is attempted, and the waits between attempts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts in total, including the first (1 = never retry)
+    pub max_attempts: u32,
+    /// Wait before the first retry
+    pub initial_delay: Duration,
+    /// Longest wait between two attempts
+    pub max_delay: Duration,
+    /// Wait a random time between half and all of each backoff step
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay: Duration::from_secs(2),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that makes one attempt only.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Set the total number of attempts (at least 1).
+    pub fn with_max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// Set the first wait and the longest one.
+    pub fn with_delays(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_delay = initial;
+        self.max_delay = max.max(initial);
+        self
+    }
+
+    /// Turn jitter on or off.
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Whether another attempt follows failed attempt number `attempt` (1-based).
+    pub fn allows_retry(&self, attempt: u32) -> bool {
+        attempt < self.max_attempts
+    }
+
+    /// Backoff step after failed attempt number `attempt` (1-based), before jitter.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 1u32 << attempt.saturating_sub(1).min(16);
+        self.initial_delay.saturating_mul(factor).min(self.max_delay)
+    }
+
+    /// Wait after failed attempt number `attempt`, with `sample` scaling the
+    /// jitter into the upper half of the backoff step.
+    pub fn delay(&self, attempt: u32, sample: u64) -> Duration {
+        let backoff = self.backoff(attempt);
+        if !self.jitter {
+            return backoff;
+        }
+        let half = backoff / 2;
+        let spread_ms = (backoff - half).as_millis() as u64;
+        half + Duration::from_millis(sample % (spread_ms + 1))
+    }
+
+    /// [`RetryPolicy::delay`] with a random sample.
+    pub fn random_delay(&self, attempt: u32) -> Duration {
+        self.delay(attempt, uuid::Uuid::new_v4().as_u64_pair().0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(s: u64) -> Duration {
+        Duration::from_secs(s)
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_cap() {
+        let policy = RetryPolicy::default().with_delays(secs(2), secs(10)).with_jitter(false);
+        let waits: Vec<Duration> = (1..=5).map(|attempt| policy.delay(attempt, 12345)).collect();
+        assert_eq!(waits, [secs(2), secs(4), secs(8), secs(10), secs(10)]);
+        assert_eq!(policy.backoff(u32::MAX), secs(10));
+    }
+
+    #[test]
+    fn test_jitter_stays_in_the_upper_half() {
+        let policy = RetryPolicy::default().with_delays(secs(4), secs(60));
+        for sample in [0, 1, 999, 2000, u64::MAX] {
+            let wait = policy.delay(2, sample);
+            assert!(wait >= secs(4) && wait <= secs(8), "{:?}", wait);
+        }
+        assert_eq!(policy.delay(2, 0), secs(4));
+        assert_eq!(policy.delay(2, 4000), secs(8));
+    }
+
+    #[test]
+    fn test_attempt_budget() {
+        let policy = RetryPolicy::default().with_max_attempts(3);
+        assert!(policy.allows_retry(1) && policy.allows_retry(2));
+        assert!(!policy.allows_retry(3));
+        assert!(!RetryPolicy::none().allows_retry(1));
+        assert_eq!(RetryPolicy::default().with_max_attempts(0).max_attempts, 1);
+    }
+}
@@ -0,0 +1,333 @@
+//! Progress accounting for a run whose task set changes.
+//!
+//! Tasks are added while the loop runs (regression tasks from full-suite
+//! verification, upgrade tasks from dependency advice) and can be skipped,
+//! removed or reopened. [`RunState`] recounts progress from the task list on
+//! every change instead of fixing the total at the start, reports each change
+//! so the output can annotate it, and tells originally planned tasks apart
+//! from added ones for the summary.
+//!
+//! Skipped tasks are left out of the total: they are neither work to do nor
+//! work done. Every function here is pure over a [`TaskList`].
+
+use crate::models::{TaskList, TaskStatus};
+use std::collections::BTreeSet;
+
+/// Completed tasks out of the tasks that count toward completion.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TaskProgress {
+    /// Completed tasks
+    pub completed: usize,
+    /// Tasks that count toward completion (all but skipped and obsolete)
+    pub total: usize,
+}
+
+impl TaskProgress {
+    /// Count a task list.
+    pub fn of(list: &TaskList) -> Self {
+        let counted: Vec<_> = list
+            .get_all_tasks()
+            .into_iter()
+            .filter(|t| !matches!(t.status, TaskStatus::Skipped | TaskStatus::Obsolete))
+            .collect();
+        Self {
+            completed: counted.iter().filter(|t| t.status == TaskStatus::Completed).count(),
+            total: counted.len(),
+        }
+    }
+
+    /// Completed tasks, never more than the total.
+    pub fn completed_clamped(&self) -> usize {
+        self.completed.min(self.total)
+    }
+
+    /// Whole percentage complete, between 0 and 100.
+    pub fn percentage(&self) -> usize {
+        if self.total == 0 {
+            return 0;
+        }
+        self.completed_clamped() * 100 / self.total
+    }
+
+    /// Filled cells of a bar `width` cells wide, between 0 and `width`.
+    pub fn filled(&self, width: usize) -> usize {
+        if self.total == 0 {
+            return 0;
+        }
+        self.completed_clamped() * width / self.total
+    }
+
+    /// Whether every counted task is complete.
+    pub fn is_done(&self) -> bool {
+        self.total > 0 && self.completed >= self.total
+    }
+}
+
+/// How the task set changed between two refreshes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProgressChange {
+    /// Counted tasks that appeared
+    pub added: Vec<String>,
+    /// Counted tasks that disappeared (removed or skipped)
+    pub removed: Vec<String>,
+    /// Tasks that were complete and no longer are
+    pub reopened: Vec<String>,
+}
+
+impl ProgressChange {
+    /// Whether nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.reopened.is_empty()
+    }
+
+    /// Note shown next to the progress bar, e.g. "+2 tasks added by dependency advice".
+    pub fn annotation(&self, cause: &str) -> Option<String> {
+        let mut parts = Vec::new();
+        if !self.added.is_empty() {
+            parts.push(format!("+{} added", count_tasks(self.added.len())));
+        }
+        if !self.removed.is_empty() {
+            parts.push(format!("-{} removed", count_tasks(self.removed.len())));
+        }
+        if !self.reopened.is_empty() {
+            parts.push(format!(
+                "{} reopened ({})",
+                count_tasks(self.reopened.len()),
+                self.reopened.join(", ")
+            ));
+        }
+        if parts.is_empty() {
+            return None;
+        }
+        Some(format!("{} by {}", parts.join(", "), cause))
+    }
+}
+
+/// `1 task` / `3 tasks`.
+fn count_tasks(n: usize) -> String {
+    format!("{} task{}", n, if n == 1 { "" } else { "s" })
+}
+
+/// Planned versus added tasks at the end of a run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PlanSummary {
+    /// Counted tasks that were in the list when the run started
+    pub planned: usize,
+    /// ...of which completed
+    pub planned_completed: usize,
+    /// Counted tasks added during the run
+    pub added: usize,
+    /// ...of which completed
+    pub added_completed: usize,
+    /// Planned tasks no longer counted (removed or skipped)
+    pub dropped: usize,
+}
+
+impl std::fmt::Display for PlanSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{} planned tasks complete", self.planned_completed, self.planned)?;
+        if self.added > 0 {
+            write!(f, ", {}/{} added during the run", self.added_completed, self.added)?;
+        }
+        if self.dropped > 0 {
+            write!(f, ", {} dropped", count_tasks(self.dropped))?;
+        }
+        Ok(())
+    }
+}
+
+/// Progress of one run over a changing task list.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunState {
+    /// Counted task IDs when the run started
+    planned: BTreeSet<String>,
+    /// Counted task IDs at the last refresh
+    known: BTreeSet<String>,
+    /// Completed task IDs at the last refresh
+    completed: BTreeSet<String>,
+    progress: TaskProgress,
+}
+
+/// Counted and completed task IDs of a list.
+fn task_ids(list: &TaskList) -> (BTreeSet<String>, BTreeSet<String>) {
+    let mut counted = BTreeSet::new();
+    let mut completed = BTreeSet::new();
+    for task in list.get_all_tasks() {
+        if matches!(task.status, TaskStatus::Skipped | TaskStatus::Obsolete) {
+            continue;
+        }
+        counted.insert(task.id.clone());
+        if task.status == TaskStatus::Completed {
+            completed.insert(task.id.clone());
+        }
+    }
+    (counted, completed)
+}
+
+impl RunState {
+    /// Start accounting from the task list at the beginning of a run.
+    pub fn new(list: &TaskList) -> Self {
+        let (counted, completed) = task_ids(list);
+        Self {
+            planned: counted.clone(),
+            known: counted,
+            completed,
+            progress: TaskProgress::of(list),
+        }
+    }
+
+    /// Progress at the last refresh.
+    pub fn progress(&self) -> TaskProgress {
+        self.progress
+    }
+
+    /// Recount from `list` and report what changed since the last refresh.
+    pub fn refresh(&mut self, list: &TaskList) -> ProgressChange {
+        let (counted, completed) = task_ids(list);
+        let change = ProgressChange {
+            added: counted.difference(&self.known).cloned().collect(),
+            removed: self.known.difference(&counted).cloned().collect(),
+            reopened: self
+                .completed
+                .iter()
+                .filter(|id| counted.contains(*id) && !completed.contains(*id))
+                .cloned()
+                .collect(),
+        };
+        self.known = counted;
+        self.completed = completed;
+        self.progress = TaskProgress::of(list);
+        change
+    }
+
+    /// Planned versus added tasks in `list`.
+    pub fn summary(&self, list: &TaskList) -> PlanSummary {
+        let (counted, completed) = task_ids(list);
+        let planned = counted.intersection(&self.planned).count();
+        let added = counted.difference(&self.planned).count();
+        let planned_completed = completed.intersection(&self.planned).count();
+        PlanSummary {
+            planned,
+            planned_completed,
+            added,
+            added_completed: completed.len() - planned_completed,
+            dropped: self.planned.len() - planned,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Task;
+
+    fn list(tasks: &[(&str, TaskStatus)]) -> TaskList {
+        let mut list = TaskList::new("demo", "rust");
+        for (id, status) in tasks {
+            let mut task = Task::new(*id, *id, "", 1);
+            task.status = *status;
+            list.add_task(task);
+        }
+        list
+    }
+
+    #[test]
+    fn test_progress_excludes_skipped_tasks() {
+        let progress = TaskProgress::of(&list(&[
+            ("T-1", TaskStatus::Completed),
+            ("T-2", TaskStatus::Skipped),
+            ("T-3", TaskStatus::Pending),
+        ]));
+        assert_eq!(progress, TaskProgress { completed: 1, total: 2 });
+        assert_eq!(progress.percentage(), 50);
+        assert!(!progress.is_done());
+    }
+
+    #[test]
+    fn test_progress_never_exceeds_the_total() {
+        let progress = TaskProgress { completed: 5, total: 3 };
+        assert_eq!(progress.percentage(), 100);
+        assert_eq!(progress.filled(30), 30);
+        assert_eq!(TaskProgress::default().percentage(), 0);
+        assert_eq!(TaskProgress::default().filled(30), 0);
+    }
+
+    #[test]
+    fn test_added_tasks_are_annotated_and_counted_separately() {
+        let mut state = RunState::new(&list(&[
+            ("T-1", TaskStatus::Completed),
+            ("T-2", TaskStatus::Pending),
+        ]));
+        let grown = list(&[
+            ("T-1", TaskStatus::Completed),
+            ("T-2", TaskStatus::Completed),
+            ("T-3", TaskStatus::Pending),
+            ("T-4", TaskStatus::Completed),
+        ]);
+        let change = state.refresh(&grown);
+        assert_eq!(change.added, vec!["T-3", "T-4"]);
+        assert_eq!(
+            change.annotation("full-suite verification").unwrap(),
+            "+2 tasks added by full-suite verification"
+        );
+        assert_eq!(state.progress(), TaskProgress { completed: 3, total: 4 });
+
+        let summary = state.summary(&grown);
+        assert_eq!(
+            summary,
+            PlanSummary {
+                planned: 2,
+                planned_completed: 2,
+                added: 2,
+                added_completed: 1,
+                dropped: 0,
+            }
+        );
+        assert_eq!(summary.to_string(), "2/2 planned tasks complete, 1/2 added during the run");
+    }
+
+    #[test]
+    fn test_removed_and_skipped_tasks_leave_the_total() {
+        let mut state = RunState::new(&list(&[
+            ("T-1", TaskStatus::Pending),
+            ("T-2", TaskStatus::Pending),
+            ("T-3", TaskStatus::Pending),
+        ]));
+        let change = state.refresh(&list(&[
+            ("T-1", TaskStatus::Completed),
+            ("T-2", TaskStatus::Skipped),
+        ]));
+        assert_eq!(change.removed, vec!["T-2", "T-3"]);
+        assert_eq!(change.annotation("a replan").unwrap(), "-2 tasks removed by a replan");
+        assert_eq!(state.progress(), TaskProgress { completed: 1, total: 1 });
+        assert!(state.progress().is_done());
+        assert_eq!(
+            state.summary(&list(&[("T-1", TaskStatus::Completed)])).to_string(),
+            "1/1 planned tasks complete, 2 tasks dropped"
+        );
+    }
+
+    #[test]
+    fn test_reopened_tasks_are_reported_not_silently_lost() {
+        let mut state = RunState::new(&list(&[
+            ("T-1", TaskStatus::Completed),
+            ("T-2", TaskStatus::Pending),
+        ]));
+        let change = state.refresh(&list(&[
+            ("T-1", TaskStatus::Pending),
+            ("T-2", TaskStatus::Pending),
+        ]));
+        assert_eq!(change.reopened, vec!["T-1"]);
+        assert_eq!(change.annotation("task abort").unwrap(), "1 task reopened (T-1) by task abort");
+        assert_eq!(state.progress().completed, 0);
+    }
+
+    #[test]
+    fn test_unchanged_list_has_no_annotation() {
+        let tasks = list(&[("T-1", TaskStatus::InProgress)]);
+        let mut state = RunState::new(&tasks);
+        let change = state.refresh(&tasks);
+        assert!(change.is_empty());
+        assert_eq!(change.annotation("tasks.json edits"), None);
+    }
+}
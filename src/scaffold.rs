@@ -0,0 +1,196 @@
+//! Build check right after the project-setup task.
+//!
+//! Every later task builds on the scaffold the setup task creates. When that
+//! scaffold does not compile, each following task fails the same way and the
+//! loop spends its iterations rediscovering it. So as soon as the setup task
+//! completes, the language's build command runs, whatever the task itself
+//! reported, and a failure stops the run with [`RalphError::ScaffoldBuild`].
+//!
+//! The setup task is the first task of the plan, or any task with `"setup": true`.
+
+use crate::exec_env::ExecEnv;
+use crate::models::TaskList;
+use crate::language::Language;
+use crate::tools::TestTool;
+use crate::{RalphError, Result};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::process::Command;
+
+/// How long the build may take before the check gives up.
+pub const BUILD_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Lines of build output kept in the error, from the end.
+const OUTPUT_TAIL_LINES: usize = 40;
+
+/// Builds the project after its setup task completes.
+#[derive(Debug)]
+pub struct ScaffoldCheck {
+    project_path: PathBuf,
+    tasks_path: PathBuf,
+    language: Option<Language>,
+    jobs: Option<usize>,
+    timeout: Duration,
+    /// Variables for the build command
+    exec_env: Arc<ExecEnv>,
+}
+
+impl ScaffoldCheck {
+    /// Create a check for the given project and tasks file.
+    pub fn new(project_path: impl Into<PathBuf>, tasks_path: impl Into<PathBuf>) -> Self {
+        Self {
+            project_path: project_path.into(),
+            tasks_path: tasks_path.into(),
+            language: None,
+            jobs: None,
+            timeout: BUILD_TIMEOUT,
+            exec_env: Arc::new(ExecEnv::default()),
+        }
+    }
+
+    /// Set the environment variables injected into the build command.
+    pub fn with_exec_env(mut self, exec_env: Arc<ExecEnv>) -> Self {
+        self.exec_env = exec_env;
+        self
+    }
+
+    /// Set a language override (skips auto-detection).
+    pub fn with_language(mut self, language: Language) -> Self {
+        self.language = Some(language);
+        self
+    }
+
+    /// Limit the build tool's parallel jobs (see [`Language::jobs_flag`]).
+    pub fn with_jobs(mut self, jobs: Option<usize>) -> Self {
+        self.jobs = jobs;
+        self
+    }
+
+    /// Set how long the build may take.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Notify the check that a task completed.
+    ///
+    /// Builds the project if `task_id` is the setup task. Returns `true` when
+    /// the build ran and passed, `false` when no build was due or the language
+    /// has nothing to build.
+    pub async fn on_task_completed(&self, task_id: &str) -> Result<bool> {
+        let list = TaskList::load(&self.tasks_path).map_err(RalphError::Task)?;
+        if !is_setup_task(&list, task_id) {
+            return Ok(false);
+        }
+        self.build(task_id).await
+    }
+
+    /// Run the build command now, attributing a failure to `task_id`.
+    pub async fn build(&self, task_id: &str) -> Result<bool> {
+        let mut tool = TestTool::new(&self.project_path);
+        if let Some(language) = self.language {
+            tool = tool.with_language(language);
+        }
+        let language = tool.detect_language();
+
+        let Some((cmd, mut args)) = language.build_command() else {
+            tracing::info!(%language, "No build command; scaffold check skipped");
+            return Ok(false);
+        };
+        if language == Language::TypeScript && !self.project_path.join("node_modules").exists() {
+            tracing::info!("Dependencies not installed yet; scaffold check skipped");
+            return Ok(false);
+        }
+
+        let jobs = self.jobs.map(|n| n.to_string());
+        if let (Some(flag), Some(jobs)) = (language.jobs_flag(), jobs.as_deref()) {
+            args.splice(1..1, [flag, jobs]);
+        }
+
+        let mut command = Command::new(cmd);
+        command.args(&args).current_dir(&self.project_path);
+
+        let tool_error = |message: String| RalphError::Tool {
+            tool: "build".to_string(),
+            message,
+        };
+        let output = self.exec_env.run(&mut command, self.timeout).await.map_err(|e| {
+            if e.kind() == std::io::ErrorKind::TimedOut {
+                tool_error(format!(
+                    "{} {} timed out after {}",
+                    cmd,
+                    args.join(" "),
+                    crate::timefmt::duration(self.timeout)
+                ))
+            } else {
+                tool_error(format!("Failed to run {}: {}", cmd, e))
+            }
+        })?;
+
+        if output.status.success() && output.breach.is_none() {
+            return Ok(true);
+        }
+
+        let mut combined = format!(
+            "{}\n{}",
+            self.exec_env.decode(&output.stdout, "stdout"),
+            self.exec_env.decode(&output.stderr, "stderr")
+        );
+        if let Some(breach) = output.breach {
+            combined.push_str(&format!("\n{}", breach.describe("The build")));
+        }
+        Err(RalphError::ScaffoldBuild {
+            task_id: task_id.to_string(),
+            output: format!(
+                "{} {} failed:\n{}",
+                cmd,
+                args.join(" "),
+                tail(&combined)
+            ),
+        })
+    }
+}
+
+/// Check whether `task_id` is the plan's setup task: the first task, or one flagged `setup`.
+pub fn is_setup_task(list: &TaskList, task_id: &str) -> bool {
+    let tasks = list.get_all_tasks();
+    tasks.first().is_some_and(|t| t.id == task_id)
+        || tasks.iter().any(|t| t.id == task_id && t.setup)
+}
+
+/// The last [`OUTPUT_TAIL_LINES`] non-empty lines of `output`.
+fn tail(output: &str) -> String {
+    let lines: Vec<&str> = output.lines().filter(|l| !l.trim().is_empty()).collect();
+    lines[lines.len().saturating_sub(OUTPUT_TAIL_LINES)..].join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Task;
+
+    #[test]
+    fn test_setup_task_is_first_or_flagged() {
+        let mut list = TaskList::new("Test", "rust");
+        list.add_task(Task::new("TASK-001", "Set up project", "Desc", 1));
+        list.add_task(Task::new("TASK-002", "Core logic", "Desc", 2));
+        let mut workspace = Task::new("TASK-003", "Add workspace crate", "Desc", 2);
+        workspace.setup = true;
+        list.add_task(workspace);
+
+        assert!(is_setup_task(&list, "TASK-001"));
+        assert!(!is_setup_task(&list, "TASK-002"));
+        assert!(is_setup_task(&list, "TASK-003"));
+        assert!(!is_setup_task(&list, "TASK-404"));
+    }
+
+    #[test]
+    fn test_tail_keeps_last_lines() {
+        let output: String = (1..=100).map(|i| format!("line {}\n\n", i)).collect();
+        let kept = tail(&output);
+        assert_eq!(kept.lines().count(), OUTPUT_TAIL_LINES);
+        assert!(kept.starts_with("line 61\n"));
+        assert!(kept.ends_with("line 100"));
+    }
+}
@@ -0,0 +1,351 @@
+//! JSON Schemas for Ralph's JSON artifacts.
+//!
+//! Editor plugins and dashboards can validate `tasks.json` and `design.json`
+//! without linking this crate. The schemas live in `schemas/` at the
+//! repository root and are embedded in the binary; `ralph schema export`
+//! writes them out. Every emitted artifact carries a `$schema` member naming
+//! the versioned identifier (`$id`) of the schema it follows.
+//!
+//! The schemas are written by hand. Tests validate fully populated artifacts
+//! and the example outputs against them with [`validate`], and every object in
+//! the schemas forbids unknown members, so a field added to the serde types
+//! without a schema update fails the build.
+
+use crate::{RalphError, Result};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Version of the artifact layout, bumped when a schema changes incompatibly.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// `$id` of the `tasks.json` schema.
+pub const TASKS_SCHEMA_ID: &str = "urn:adk-ralph:schema:tasks:v1";
+
+/// `$id` of the design JSON schema.
+pub const DESIGN_SCHEMA_ID: &str = "urn:adk-ralph:schema:design:v1";
+
+/// A schema embedded in the binary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArtifactSchema {
+    /// File name when exported
+    pub file_name: &'static str,
+    /// Versioned identifier, also written as `$schema` in artifacts
+    pub id: &'static str,
+    /// Schema document
+    pub source: &'static str,
+}
+
+impl ArtifactSchema {
+    /// Parse the schema document.
+    pub fn document(&self) -> Value {
+        serde_json::from_str(self.source).expect("embedded schema is valid JSON")
+    }
+}
+
+/// Schema for `tasks.json` ([`crate::TaskList`]).
+pub const TASKS_SCHEMA: ArtifactSchema = ArtifactSchema {
+    file_name: "tasks.schema.json",
+    id: TASKS_SCHEMA_ID,
+    source: include_str!("../schemas/tasks.schema.json"),
+};
+
+/// Schema for design JSON ([`crate::DesignDocument`]).
+pub const DESIGN_SCHEMA: ArtifactSchema = ArtifactSchema {
+    file_name: "design.schema.json",
+    id: DESIGN_SCHEMA_ID,
+    source: include_str!("../schemas/design.schema.json"),
+};
+
+/// Every published schema.
+pub const SCHEMAS: &[ArtifactSchema] = &[TASKS_SCHEMA, DESIGN_SCHEMA];
+
+/// Write every schema into `dir`, creating it if needed.
+pub fn export(dir: &Path) -> Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| RalphError::file(dir.display().to_string(), e.to_string()))?;
+    SCHEMAS
+        .iter()
+        .map(|schema| {
+            let path = dir.join(schema.file_name);
+            std::fs::write(&path, schema.source)
+                .map_err(|e| RalphError::file(path.display().to_string(), e.to_string()))?;
+            Ok(path)
+        })
+        .collect()
+}
+
+/// Check `instance` against `schema`, returning one message per violation.
+///
+/// Supports the keywords the published schemas use: `type`, `enum`,
+/// `properties`, `required`, `additionalProperties`, `items`, `minimum`,
+/// `anyOf` and local `$ref`s (`#/$defs/...`). Other keywords are ignored.
+pub fn validate(schema: &Value, instance: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    check(schema, schema, instance, "$", &mut errors);
+    errors
+}
+
+fn check(root: &Value, schema: &Value, instance: &Value, at: &str, errors: &mut Vec<String>) {
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        match resolve(root, reference) {
+            Some(target) => check(root, target, instance, at, errors),
+            None => errors.push(format!("{}: unresolved $ref '{}'", at, reference)),
+        }
+    }
+
+    if let Some(expected) = schema.get("type") {
+        let allowed: Vec<&str> = match expected {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !allowed.iter().any(|name| has_type(instance, name)) {
+            errors.push(format!(
+                "{}: expected {}, found {}",
+                at,
+                allowed.join(" or "),
+                type_name(instance)
+            ));
+            return;
+        }
+    }
+
+    if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        if !values.contains(instance) {
+            let allowed = Value::Array(values.clone());
+            errors.push(format!("{}: {} is not one of {}", at, instance, allowed));
+        }
+    }
+
+    if let Some(minimum) = schema.get("minimum").and_then(Value::as_f64) {
+        if instance.as_f64().is_some_and(|n| n < minimum) {
+            errors.push(format!("{}: {} is below the minimum {}", at, instance, minimum));
+        }
+    }
+
+    if let Some(options) = schema.get("anyOf").and_then(Value::as_array) {
+        if !options.iter().any(|option| validate_at(root, option, instance)) {
+            errors.push(format!("{}: matches none of the allowed shapes", at));
+        }
+    }
+
+    if let (Some(items), Some(elements)) = (schema.get("items"), instance.as_array()) {
+        for (i, element) in elements.iter().enumerate() {
+            check(root, items, element, &format!("{}[{}]", at, i), errors);
+        }
+    }
+
+    if let Some(object) = instance.as_object() {
+        for name in schema.get("required").and_then(Value::as_array).into_iter().flatten() {
+            if let Some(name) = name.as_str() {
+                if !object.contains_key(name) {
+                    errors.push(format!("{}: missing required member '{}'", at, name));
+                }
+            }
+        }
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for (name, value) in object {
+            let path = format!("{}.{}", at, name);
+            match (properties.and_then(|p| p.get(name)), schema.get("additionalProperties")) {
+                (Some(property), _) => check(root, property, value, &path, errors),
+                (None, Some(Value::Bool(false))) => {
+                    errors.push(format!("{}: member not allowed by the schema", path))
+                }
+                (None, Some(additional)) if additional.is_object() => {
+                    check(root, additional, value, &path, errors)
+                }
+                (None, _) => {}
+            }
+        }
+    }
+}
+
+/// Whether `instance` matches `schema` (used for `anyOf` branches).
+fn validate_at(root: &Value, schema: &Value, instance: &Value) -> bool {
+    let mut errors = Vec::new();
+    check(root, schema, instance, "$", &mut errors);
+    errors.is_empty()
+}
+
+fn resolve<'a>(root: &'a Value, reference: &str) -> Option<&'a Value> {
+    root.pointer(reference.strip_prefix('#')?)
+}
+
+fn has_type(instance: &Value, name: &str) -> bool {
+    match name {
+        "null" => instance.is_null(),
+        "boolean" => instance.is_boolean(),
+        "string" => instance.is_string(),
+        "number" => instance.is_number(),
+        "integer" => instance.is_u64() || instance.is_i64(),
+        "array" => instance.is_array(),
+        "object" => instance.is_object(),
+        _ => false,
+    }
+}
+
+fn type_name(instance: &Value) -> &'static str {
+    match instance {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        Component, DesignDocument, DiffStats, FileStructure, Phase, Sprint, Task, TaskList,
+        TaskStatus, TechnologyStack,
+    };
+    use crate::provenance::{AgentProvenance, Provenance};
+
+    fn provenance() -> Provenance {
+        Provenance::new().with_agent(
+            "architect",
+            AgentProvenance {
+                provider: "anthropic".to_string(),
+                model: "model".to_string(),
+                temperature: 0.2,
+                instruction_sha256: "abc123".to_string(),
+            },
+        )
+    }
+
+    /// A task list with every optional field set.
+    fn full_task_list() -> TaskList {
+        let mut task = Task::new("T-001", "Parser", "Parse input", 1)
+            .with_user_story("US-001")
+            .with_acceptance_criterion("WHEN input is empty THEN return an error");
+        task.add_dependency("T-000");
+        task.add_file_created("src/parser.rs");
+        task.add_file_modified("src/lib.rs");
+        task.start();
+        task.complete(Some("abc123".to_string()));
+        task.diff_stats = Some(DiffStats {
+            added: 10,
+            removed: 2,
+            files: vec!["src/parser.rs".to_string()],
+        });
+        task.flag_for_review("touched many files");
+        task.env.insert("DATABASE_URL".to_string(), "sqlite://test.db".to_string());
+        task.model = Some(crate::models::ModelConfig::new("anthropic", "claude-opus-4-5"));
+
+        let mut sprint = Sprint::new("sprint-1", "Core");
+        sprint.add_task(task);
+        let mut phase = Phase::new("phase-1", "Foundation");
+        phase.add_sprint(sprint);
+
+        let mut list = TaskList::new("calc", "rust");
+        list.meta = Some(provenance());
+        list.add_phase(phase);
+        list.add_task(Task::new("T-000", "Setup", "Create the project", 1));
+        list
+    }
+
+    /// A design document with every optional field set.
+    fn full_design() -> DesignDocument {
+        let mut design = DesignDocument::new("calc", "A calculator");
+        design.meta = Some(provenance());
+        design.component_diagram = Some("graph TD; A-->B".to_string());
+        let mut component = Component::new("Parser", "Parses input").with_file_path("src/parser.rs");
+        component.add_interface("parse(&str) -> Expr");
+        component.add_dependency("Lexer");
+        design.components.push(component);
+        let mut root = FileStructure::directory("calc", "Project root");
+        root.children.push(FileStructure::file("Cargo.toml", "Manifest"));
+        design.file_structure = Some(root);
+        let mut stack = TechnologyStack::new("rust")
+            .with_testing("cargo test")
+            .with_build_tool("cargo");
+        stack.add_dependency("clap");
+        stack.additional.insert("cli".to_string(), "clap".to_string());
+        design.technology_stack = Some(stack);
+        design.test_infrastructure = Some(crate::models::TestInfrastructure {
+            helper_module: Some("tests/common/mod.rs".to_string()),
+            fixtures: vec!["temp_workspace()".to_string()],
+            factories: vec!["expr(&str) -> Expr".to_string()],
+        });
+        design.glossary = vec![crate::glossary::GlossaryTerm::new("expression").with_avoid(&["formula"])];
+        design.design_decisions.push("Recursive descent parser".to_string());
+        design.changelog.push("Flattened src/a/b".to_string());
+        design.updated_at = Some(crate::timefmt::now());
+        design
+    }
+
+    fn assert_valid(schema: &ArtifactSchema, instance: &Value) {
+        let errors = validate(&schema.document(), instance);
+        assert!(errors.is_empty(), "{} violations: {:#?}", schema.file_name, errors);
+    }
+
+    #[test]
+    fn test_embedded_schemas_match_their_ids() {
+        for schema in SCHEMAS {
+            let document = schema.document();
+            assert_eq!(document["$id"], schema.id);
+            assert!(schema.id.ends_with(&format!(":v{}", SCHEMA_VERSION)));
+        }
+    }
+
+    #[test]
+    fn test_artifacts_validate_against_exported_schemas() {
+        let dir = tempfile::tempdir().unwrap();
+        let written = export(&dir.path().join("schemas")).unwrap();
+        assert_eq!(written.len(), SCHEMAS.len());
+        let exported = |name: &str| -> Value {
+            let path = dir.path().join("schemas").join(name);
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+        };
+
+        let tasks = serde_json::to_value(full_task_list()).unwrap();
+        assert_eq!(tasks["$schema"], TASKS_SCHEMA_ID);
+        assert!(validate(&exported("tasks.schema.json"), &tasks).is_empty());
+        assert_valid(&TASKS_SCHEMA, &tasks);
+
+        let design = serde_json::to_value(full_design()).unwrap();
+        assert_eq!(design["$schema"], DESIGN_SCHEMA_ID);
+        assert!(validate(&exported("design.schema.json"), &design).is_empty());
+        assert_valid(&DESIGN_SCHEMA, &design);
+    }
+
+    #[test]
+    fn test_example_outputs_validate() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        for example in [
+            "examples/outputs/cli-rust/tasks.json",
+            "examples/outputs/ratelimit/tasks.json",
+            "examples/outputs/web-api-python/tasks.json",
+            "hello-world/tasks.json",
+        ] {
+            let content = std::fs::read_to_string(root.join(example)).unwrap();
+            let errors = validate(&TASKS_SCHEMA.document(), &serde_json::from_str(&content).unwrap());
+            assert!(errors.is_empty(), "{}: {:#?}", example, errors);
+        }
+    }
+
+    #[test]
+    fn test_violations_are_reported() {
+        let mut tasks = serde_json::to_value(full_task_list()).unwrap();
+        tasks["tasks"][0]["status"] = Value::from("done");
+        tasks["tasks"][0]["surprise"] = Value::from(true);
+        tasks["phases"][0]["sprints"][0]["tasks"][0]["priority"] = Value::from("high");
+        tasks.as_object_mut().unwrap().remove("language");
+
+        let errors = validate(&TASKS_SCHEMA.document(), &tasks);
+        assert!(errors.iter().any(|e| e.starts_with("$.tasks[0].status:")), "{:#?}", errors);
+        assert!(errors.contains(&"$.tasks[0].surprise: member not allowed by the schema".to_string()));
+        assert!(errors.iter().any(|e| e.contains("priority: expected integer, found string")));
+        assert!(errors.contains(&"$: missing required member 'language'".to_string()));
+
+        // Round-tripping keeps the current identifier, whatever the file said
+        let mut old = serde_json::to_value(full_task_list()).unwrap();
+        old["$schema"] = Value::from("urn:adk-ralph:schema:tasks:v0");
+        let list: TaskList = serde_json::from_value(old).unwrap();
+        assert_eq!(list.schema, TASKS_SCHEMA_ID);
+        assert_eq!(list.tasks[0].status, TaskStatus::Pending);
+    }
+}
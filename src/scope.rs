@@ -0,0 +1,294 @@
+//! Per-task file budget and scope checks.
+//!
+//! A [`FileScope`] is shared by the task and file tools of one loop. The task
+//! tool starts it when a task goes in progress, with the files the architect
+//! declared for the task (`files_to_create` / `files_to_modify`). Every write
+//! or delete through the file tool is then admitted against it:
+//!
+//! - With `max_files_per_task` set, writing one file more than the cap is
+//!   refused with a tool error and the task is flagged for review.
+//! - When the task completes, the files it touched are compared with the
+//!   declared ones; a large deviation is reported even under the cap.
+//! - Files refused over the cap are reported once per task, as a count with
+//!   the reason ([`ScopeReport::skipped_summary`]), so a missing file does
+//!   not look like a bug.
+
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+
+/// Undeclared files tolerated before a deviation counts as large.
+const MIN_LARGE_DEVIATION: usize = 3;
+
+/// Files a task touched compared with those it declared.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScopeDeviation {
+    /// Touched but not declared
+    pub undeclared: Vec<String>,
+    /// Declared but never touched
+    pub untouched: Vec<String>,
+    /// Number of files declared
+    pub declared: usize,
+}
+
+impl ScopeDeviation {
+    /// Compare touched files with declared ones.
+    pub fn compare(declared: &[String], touched: &[String]) -> Self {
+        let declared_set: BTreeSet<String> = declared.iter().map(|p| normalize_path(p)).collect();
+        let touched_set: BTreeSet<String> = touched.iter().map(|p| normalize_path(p)).collect();
+        Self {
+            undeclared: touched_set.difference(&declared_set).cloned().collect(),
+            untouched: declared_set.difference(&touched_set).cloned().collect(),
+            declared: declared_set.len(),
+        }
+    }
+
+    /// Whether the task wandered well outside what it declared.
+    ///
+    /// Large means at least three undeclared files, and more undeclared files
+    /// than declared ones.
+    pub fn is_large(&self) -> bool {
+        self.undeclared.len() >= MIN_LARGE_DEVIATION && self.undeclared.len() > self.declared
+    }
+
+    /// One-line description for notes and warnings.
+    pub fn summary(&self) -> String {
+        format!(
+            "touched {} undeclared file(s) ({}) against {} declared",
+            self.undeclared.len(),
+            self.undeclared.join(", "),
+            self.declared
+        )
+    }
+}
+
+/// What a finished task did to the file budget.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScopeReport {
+    /// Task the report is for
+    pub task_id: String,
+    /// Files written or deleted, in first-touch order
+    pub touched: Vec<String>,
+    /// Per-task cap in force
+    pub max_files: Option<usize>,
+    /// Whether a write was refused for exceeding the cap
+    pub over_cap: bool,
+    /// Files refused for exceeding the cap, in first-refusal order
+    pub skipped: Vec<String>,
+    /// Comparison with the declared files
+    pub deviation: ScopeDeviation,
+}
+
+impl ScopeReport {
+    /// Reason to flag the task for review, if any.
+    pub fn review_reason(&self) -> Option<String> {
+        match (self.over_cap, self.max_files) {
+            (true, Some(max)) => Some(format!(
+                "tried to write more than {} file(s) (max_files_per_task); further writes were refused",
+                max
+            )),
+            _ => None,
+        }
+    }
+
+    /// One line for the files refused, e.g. `skipped 3 files (over the
+    /// 5-file limit per task)`, or `None` when nothing was refused.
+    pub fn skipped_summary(&self) -> Option<String> {
+        if self.skipped.is_empty() {
+            return None;
+        }
+        let files = if self.skipped.len() == 1 { "file" } else { "files" };
+        let limit = self.max_files.map_or_else(String::new, |max| format!("{}-file ", max));
+        Some(format!(
+            "skipped {} {} (over the {}limit per task)",
+            self.skipped.len(),
+            files,
+            limit
+        ))
+    }
+}
+
+#[derive(Debug, Default)]
+struct ScopeState {
+    task_id: Option<String>,
+    declared: Vec<String>,
+    touched: Vec<String>,
+    over_cap: bool,
+    skipped: Vec<String>,
+}
+
+/// Tracks the files written during the current task.
+#[derive(Debug, Default)]
+pub struct FileScope {
+    /// Most files one task may write (`None` = no cap)
+    max_files: Option<usize>,
+    state: Mutex<ScopeState>,
+}
+
+impl FileScope {
+    /// Create a scope with an optional per-task cap.
+    pub fn new(max_files: Option<usize>) -> Self {
+        Self {
+            max_files,
+            state: Mutex::new(ScopeState::default()),
+        }
+    }
+
+    /// Per-task cap, if any.
+    pub fn max_files(&self) -> Option<usize> {
+        self.max_files
+    }
+
+    /// Start tracking a task with the files it declared.
+    pub fn begin(&self, task_id: impl Into<String>, declared: Vec<String>) {
+        *self.state.lock().unwrap() = ScopeState {
+            task_id: Some(task_id.into()),
+            declared,
+            ..Default::default()
+        };
+    }
+
+    /// Stop tracking (the task finished or was abandoned).
+    pub fn clear(&self) {
+        *self.state.lock().unwrap() = ScopeState::default();
+    }
+
+    /// Task being tracked, if any.
+    pub fn task_id(&self) -> Option<String> {
+        self.state.lock().unwrap().task_id.clone()
+    }
+
+    /// Files touched so far, in first-touch order.
+    pub fn touched(&self) -> Vec<String> {
+        self.state.lock().unwrap().touched.clone()
+    }
+
+    /// Admit a write or delete of `path`, or explain why it is refused.
+    ///
+    /// Files already touched by the task are always admitted, so it can keep
+    /// editing what it has. Outside a task nothing is tracked.
+    pub fn admit(&self, path: &str) -> Result<(), String> {
+        let path = normalize_path(path);
+        let mut state = self.state.lock().unwrap();
+        let Some(task_id) = state.task_id.clone() else {
+            return Ok(());
+        };
+        if state.touched.contains(&path) {
+            return Ok(());
+        }
+        if let Some(max) = self.max_files {
+            if state.touched.len() >= max {
+                state.over_cap = true;
+                if !state.skipped.contains(&path) {
+                    state.skipped.push(path.clone());
+                }
+                return Err(format!(
+                    "Task {} has already written {} file(s), the limit per task ({}): {}. \
+                     Refusing to write '{}'. Finish the task within these files; \
+                     it has been flagged for review.",
+                    task_id,
+                    state.touched.len(),
+                    max,
+                    state.touched.join(", "),
+                    path
+                ));
+            }
+        }
+        state.touched.push(path);
+        Ok(())
+    }
+
+    /// Summarize the current task, if one is tracked.
+    pub fn report(&self) -> Option<ScopeReport> {
+        let state = self.state.lock().unwrap();
+        let task_id = state.task_id.clone()?;
+        Some(ScopeReport {
+            task_id,
+            touched: state.touched.clone(),
+            max_files: self.max_files,
+            over_cap: state.over_cap,
+            skipped: state.skipped.clone(),
+            deviation: ScopeDeviation::compare(&state.declared, &state.touched),
+        })
+    }
+}
+
+/// Path as compared across tools: forward slashes, no leading `./`.
+pub(crate) fn normalize_path(path: &str) -> String {
+    let path = path.trim().replace('\\', "/");
+    path.strip_prefix("./").unwrap_or(&path).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_cap_refuses_new_files_but_allows_rewrites() {
+        let scope = FileScope::new(Some(2));
+        assert!(scope.admit("untracked.rs").is_ok());
+        assert!(scope.touched().is_empty());
+
+        scope.begin("T-001", strings(&["src/main.rs"]));
+        assert!(scope.admit("./src/main.rs").is_ok());
+        assert!(scope.admit("src/lib.rs").is_ok());
+        assert!(scope.admit("src/main.rs").is_ok());
+
+        let err = scope.admit("src/extra.rs").unwrap_err();
+        assert!(err.contains("T-001"));
+        assert!(err.contains("src/main.rs, src/lib.rs"));
+        assert!(err.contains("'src/extra.rs'"));
+        assert!(scope.admit("src/lib.rs").is_ok());
+
+        assert!(scope.admit("src/more.rs").is_err());
+        assert!(scope.admit("src/extra.rs").is_err());
+
+        let report = scope.report().unwrap();
+        assert!(report.over_cap);
+        assert_eq!(report.touched, strings(&["src/main.rs", "src/lib.rs"]));
+        assert!(report.review_reason().unwrap().contains("more than 2 file(s)"));
+        assert_eq!(report.skipped, strings(&["src/extra.rs", "src/more.rs"]));
+        assert_eq!(
+            report.skipped_summary().as_deref(),
+            Some("skipped 2 files (over the 2-file limit per task)")
+        );
+
+        scope.clear();
+        assert!(scope.report().is_none());
+    }
+
+    #[test]
+    fn test_no_cap_tracks_without_refusing() {
+        let scope = FileScope::new(None);
+        scope.begin("T-002", Vec::new());
+        for i in 0..50 {
+            assert!(scope.admit(&format!("src/f{}.rs", i)).is_ok());
+        }
+        let report = scope.report().unwrap();
+        assert!(!report.over_cap);
+        assert_eq!(report.review_reason(), None);
+        assert_eq!(report.skipped_summary(), None);
+        assert!(report.deviation.is_large());
+    }
+
+    #[test]
+    fn test_deviation_against_declared_files() {
+        let declared = strings(&["src/parser.rs", "tests/parser.rs"]);
+
+        let within = ScopeDeviation::compare(&declared, &strings(&["src/parser.rs", "src/lib.rs"]));
+        assert_eq!(within.undeclared, strings(&["src/lib.rs"]));
+        assert_eq!(within.untouched, strings(&["tests/parser.rs"]));
+        assert!(!within.is_large());
+
+        let sprawl = ScopeDeviation::compare(
+            &declared,
+            &strings(&["src/parser.rs", "src/a.rs", "src/b.rs", "README.md"]),
+        );
+        assert_eq!(sprawl.undeclared.len(), 3);
+        assert!(sprawl.is_large());
+        assert!(sprawl.summary().contains("3 undeclared file(s) (README.md, src/a.rs, src/b.rs) against 2 declared"));
+    }
+}
@@ -0,0 +1,523 @@
+//! Quick syntax check after every file write (the smoke gate).
+//!
+//! Waiting for a full build until the agent has finished all its writes finds
+//! errors late and in bulk. The smoke gate instead runs a fast, syntax-level
+//! check of each source file shortly after it is written:
+//!
+//! | Language | Check |
+//! |----------|-------|
+//! | Rust | `cargo check -p <package>` for the package that contains the file |
+//! | TypeScript | `tsc --noEmit` on the file (once dependencies are installed) |
+//! | JavaScript | `node --check` on the file |
+//! | Python | `python3 -m py_compile` on the file |
+//! | Go | `gofmt -e -l` on the file |
+//!
+//! Checks run in the background, so a write returns at once. Writes to the same
+//! file within [`DEBOUNCE`] of each other collapse into one check of the last
+//! version. Errors are held until the file tool's next response, which carries
+//! them as a `smoke_check` notification so the agent corrects course before
+//! moving on. A check whose tool is not installed is skipped silently.
+//!
+//! The gate is off by default and enabled per language (`RALPH_SMOKE_GATE`).
+
+use crate::exec_env::ExecEnv;
+use crate::language::Language;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Quiet period after a write before its file is checked.
+pub const DEBOUNCE: Duration = Duration::from_millis(400);
+
+/// How long one check may take before it is abandoned.
+pub const SMOKE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Languages that have a fast check.
+pub const SMOKE_LANGUAGES: &[Language] = &[
+    Language::Rust,
+    Language::TypeScript,
+    Language::JavaScript,
+    Language::Python,
+    Language::Go,
+];
+
+/// Lines of check output kept per finding, from the end.
+const OUTPUT_TAIL_LINES: usize = 30;
+
+/// Parse the gated languages: `off`, `all`, or a comma-separated list.
+pub fn parse_languages(value: &str) -> Result<Vec<Language>, String> {
+    let value = value.trim().to_lowercase();
+    match value.as_str() {
+        "" | "off" | "none" | "false" => return Ok(Vec::new()),
+        "all" | "on" | "true" => return Ok(SMOKE_LANGUAGES.to_vec()),
+        _ => {}
+    }
+
+    let mut languages = Vec::new();
+    for name in value.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+        let language = Language::parse(name);
+        if !SMOKE_LANGUAGES.contains(&language) {
+            return Err(format!("No smoke check for '{}'", name));
+        }
+        if !languages.contains(&language) {
+            languages.push(language);
+        }
+    }
+    Ok(languages)
+}
+
+/// Language of a source file, from its extension.
+pub fn language_of(rel_path: &str) -> Option<Language> {
+    let extension = Path::new(rel_path).extension()?.to_str()?;
+    match extension {
+        "rs" => Some(Language::Rust),
+        "ts" | "tsx" | "mts" | "cts" => Some(Language::TypeScript),
+        "js" | "mjs" | "cjs" => Some(Language::JavaScript),
+        "py" => Some(Language::Python),
+        "go" => Some(Language::Go),
+        _ => None,
+    }
+}
+
+/// The fast check of one file, run from the project root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmokeCommand {
+    /// Program to run
+    pub program: String,
+    /// Its arguments
+    pub args: Vec<String>,
+}
+
+impl SmokeCommand {
+    fn new(program: &str, args: &[&str]) -> Self {
+        Self {
+            program: program.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+
+    /// The check for `rel_path`, if the file is source with a fast check.
+    ///
+    /// Rust checks are scoped to the package that contains the file; a file
+    /// outside any package is not checked. TypeScript waits until the
+    /// project's own `tsc` is installed rather than letting `npx` download one.
+    pub fn for_file(project_path: &Path, rel_path: &str) -> Option<Self> {
+        let command = match language_of(rel_path)? {
+            Language::Rust => {
+                let (manifest, package) = cargo_package(project_path, rel_path)?;
+                Self::new(
+                    "cargo",
+                    &["check", "--quiet", "--message-format", "short", "--manifest-path", &manifest, "-p", &package],
+                )
+            }
+            Language::TypeScript => {
+                if !project_path.join("node_modules/.bin/tsc").exists() {
+                    return None;
+                }
+                Self::new("npx", &["tsc", "--noEmit", "--skipLibCheck", "--pretty", "false", rel_path])
+            }
+            Language::JavaScript => Self::new("node", &["--check", rel_path]),
+            Language::Python => Self::new("python3", &["-m", "py_compile", rel_path]),
+            Language::Go => Self::new("gofmt", &["-e", "-l", rel_path]),
+            Language::Java | Language::Unknown => return None,
+        };
+        Some(command)
+    }
+}
+
+impl std::fmt::Display for SmokeCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.program, self.args.join(" "))
+    }
+}
+
+/// Manifest (relative to the project) and name of the Cargo package containing `rel_path`.
+///
+/// The nearest `Cargo.toml` with a `[package]` name, walking up from the file
+/// but never above the project root.
+pub fn cargo_package(project_path: &Path, rel_path: &str) -> Option<(String, String)> {
+    let mut dir = Path::new(rel_path).parent();
+    while let Some(current) = dir {
+        let manifest = current.join("Cargo.toml");
+        if let Some(name) = std::fs::read_to_string(project_path.join(&manifest))
+            .ok()
+            .and_then(|text| cargo_package_name(&text))
+        {
+            return Some((manifest.to_string_lossy().replace('\\', "/"), name));
+        }
+        dir = current.parent();
+    }
+    None
+}
+
+/// The `name` in a manifest's `[package]` table.
+fn cargo_package_name(manifest: &str) -> Option<String> {
+    let mut in_package = false;
+    for raw in manifest.lines() {
+        let line = raw.split('#').next().unwrap_or("").trim();
+        if line.starts_with('[') {
+            in_package = line == "[package]";
+            continue;
+        }
+        if !in_package {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            if key.trim() == "name" {
+                return Some(value.trim().trim_matches('"').to_string()).filter(|name| !name.is_empty());
+            }
+        }
+    }
+    None
+}
+
+/// Collapses rapid writes to one file into a single check of its last version.
+#[derive(Debug, Default)]
+pub struct Debouncer {
+    /// Ticket of the latest write to each file
+    latest: HashMap<String, u64>,
+    next: u64,
+}
+
+impl Debouncer {
+    /// Record a write to `path` and return its ticket.
+    pub fn write(&mut self, path: &str) -> u64 {
+        self.next += 1;
+        self.latest.insert(path.to_string(), self.next);
+        self.next
+    }
+
+    /// Whether `ticket` is still the latest write to `path`.
+    pub fn is_latest(&self, path: &str, ticket: u64) -> bool {
+        self.latest.get(path) == Some(&ticket)
+    }
+}
+
+/// Syntax errors found in a file right after it was written.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SmokeFinding {
+    /// File, relative to the project
+    pub path: String,
+    /// The check that failed
+    pub command: String,
+    /// The end of its output
+    pub errors: String,
+}
+
+#[derive(Debug, Default)]
+struct SmokeState {
+    debouncer: Debouncer,
+    /// Errors not yet handed to the agent, at most one per file
+    findings: Vec<SmokeFinding>,
+    /// Checks scheduled but not finished
+    pending: usize,
+    /// Checks that ran to completion
+    checks: usize,
+    /// Checks that found errors
+    catches: usize,
+}
+
+/// Checks each written source file in the background.
+#[derive(Debug)]
+pub struct SmokeGate {
+    project_path: PathBuf,
+    languages: Vec<Language>,
+    debounce: Duration,
+    timeout: Duration,
+    /// Variables for the check commands
+    exec_env: Arc<ExecEnv>,
+    state: Arc<Mutex<SmokeState>>,
+}
+
+impl SmokeGate {
+    /// Create a gate checking files of `languages` (none = off).
+    pub fn new(project_path: impl Into<PathBuf>, languages: Vec<Language>) -> Self {
+        Self {
+            project_path: project_path.into(),
+            languages,
+            debounce: DEBOUNCE,
+            timeout: SMOKE_TIMEOUT,
+            exec_env: Arc::new(ExecEnv::default()),
+            state: Arc::new(Mutex::new(SmokeState::default())),
+        }
+    }
+
+    /// Set the environment variables injected into the check commands.
+    pub fn with_exec_env(mut self, exec_env: Arc<ExecEnv>) -> Self {
+        self.exec_env = exec_env;
+        self
+    }
+
+    /// Set the quiet period after a write before its file is checked.
+    pub fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Whether any language is gated.
+    pub fn is_enabled(&self) -> bool {
+        !self.languages.is_empty()
+    }
+
+    /// The check a write to `rel_path` would trigger, if any.
+    pub fn command_for(&self, rel_path: &str) -> Option<SmokeCommand> {
+        let language = language_of(rel_path)?;
+        if !self.languages.contains(&language) {
+            return None;
+        }
+        SmokeCommand::for_file(&self.project_path, rel_path)
+    }
+
+    /// Schedule a check of `rel_path`, just written. Returns at once.
+    ///
+    /// Outside a Tokio runtime nothing is checked.
+    pub fn on_write(&self, rel_path: &str) {
+        let Some(command) = self.command_for(rel_path) else {
+            return;
+        };
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+
+        let path = rel_path.to_string();
+        let ticket = {
+            let mut state = self.state.lock().unwrap();
+            state.pending += 1;
+            state.debouncer.write(&path)
+        };
+        let state = self.state.clone();
+        let project_path = self.project_path.clone();
+        let exec_env = self.exec_env.clone();
+        let (debounce, timeout) = (self.debounce, self.timeout);
+
+        runtime.spawn(async move {
+            tokio::time::sleep(debounce).await;
+            let latest = state.lock().unwrap().debouncer.is_latest(&path, ticket);
+            let outcome = if latest {
+                Some(run_check(&command, &project_path, &exec_env, timeout).await)
+            } else {
+                None
+            };
+
+            let mut state = state.lock().unwrap();
+            state.pending -= 1;
+            // Superseded by a later write, before or while running
+            let Some(errors) = outcome.filter(|_| state.debouncer.is_latest(&path, ticket)) else {
+                return;
+            };
+            state.checks += 1;
+            state.findings.retain(|finding| finding.path != path);
+            if let Some(errors) = errors {
+                tracing::debug!(path = %path, command = %command, "Smoke check found errors");
+                state.catches += 1;
+                state.findings.push(SmokeFinding {
+                    path,
+                    command: command.to_string(),
+                    errors,
+                });
+            }
+        });
+    }
+
+    /// Errors found since the last call, oldest first.
+    pub fn take_findings(&self) -> Vec<SmokeFinding> {
+        std::mem::take(&mut self.state.lock().unwrap().findings)
+    }
+
+    /// Wait until every scheduled check has finished.
+    pub async fn settle(&self) {
+        loop {
+            let pending = self.state.lock().unwrap().pending;
+            if pending == 0 {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    }
+
+    /// Checks that ran to completion.
+    pub fn checks(&self) -> usize {
+        self.state.lock().unwrap().checks
+    }
+
+    /// Checks that found errors.
+    pub fn catches(&self) -> usize {
+        self.state.lock().unwrap().catches
+    }
+}
+
+/// Run one check: `Some(output)` when it reported errors.
+///
+/// A check that cannot start or times out counts as clean; the gate is advice,
+/// and the full build still runs later.
+async fn run_check(
+    command: &SmokeCommand,
+    project_path: &Path,
+    exec_env: &ExecEnv,
+    timeout: Duration,
+) -> Option<String> {
+    let mut child = tokio::process::Command::new(&command.program);
+    child
+        .args(&command.args)
+        .current_dir(project_path)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    exec_env.apply(&mut child);
+
+    let output = match tokio::time::timeout(timeout, child.output()).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => {
+            tracing::debug!(command = %command, error = %e, "Smoke check could not run");
+            return None;
+        }
+        Err(_) => {
+            tracing::debug!(command = %command, "Smoke check timed out");
+            return None;
+        }
+    };
+    if output.status.success() {
+        return None;
+    }
+
+    let combined = format!(
+        "{}\n{}",
+        exec_env.decode(&output.stdout, "stdout"),
+        exec_env.decode(&output.stderr, "stderr")
+    );
+    Some(tail(&combined))
+}
+
+/// The last [`OUTPUT_TAIL_LINES`] non-empty lines of `output`.
+fn tail(output: &str) -> String {
+    let lines: Vec<&str> = output.lines().filter(|l| !l.trim().is_empty()).collect();
+    lines[lines.len().saturating_sub(OUTPUT_TAIL_LINES)..].join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, rel: &str, content: &str) {
+        let path = root.join(rel);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_parse_languages() {
+        assert!(parse_languages("off").unwrap().is_empty());
+        assert!(parse_languages("").unwrap().is_empty());
+        assert_eq!(parse_languages("all").unwrap(), SMOKE_LANGUAGES.to_vec());
+        assert_eq!(
+            parse_languages("Rust, py,rust").unwrap(),
+            vec![Language::Rust, Language::Python]
+        );
+        assert!(parse_languages("rust,java").unwrap_err().contains("java"));
+        assert!(parse_languages("cobol").is_err());
+    }
+
+    #[test]
+    fn test_debouncer_keeps_only_the_latest_write_per_file() {
+        let mut debouncer = Debouncer::default();
+        let first = debouncer.write("src/main.rs");
+        let other = debouncer.write("src/lib.rs");
+        let second = debouncer.write("src/main.rs");
+
+        assert!(!debouncer.is_latest("src/main.rs", first));
+        assert!(debouncer.is_latest("src/main.rs", second));
+        assert!(debouncer.is_latest("src/lib.rs", other));
+        assert!(!debouncer.is_latest("src/unknown.rs", first));
+    }
+
+    #[test]
+    fn test_fast_check_per_language() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+
+        let check = |rel: &str| SmokeCommand::for_file(root, rel).map(|c| c.to_string());
+        assert_eq!(check("app/main.py").unwrap(), "python3 -m py_compile app/main.py");
+        assert_eq!(check("src/index.mjs").unwrap(), "node --check src/index.mjs");
+        assert_eq!(check("cmd/server.go").unwrap(), "gofmt -e -l cmd/server.go");
+        assert_eq!(check("README.md"), None);
+        assert_eq!(check("src/Main.java"), None);
+
+        // tsc only once the project's own compiler is installed
+        assert_eq!(check("src/app.ts"), None);
+        write(root, "node_modules/.bin/tsc", "");
+        assert_eq!(
+            check("src/app.ts").unwrap(),
+            "npx tsc --noEmit --skipLibCheck --pretty false src/app.ts"
+        );
+    }
+
+    #[test]
+    fn test_rust_check_is_scoped_to_the_containing_package() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, "Cargo.toml", "[workspace]\nmembers = [\"crates/*\"]\n");
+        write(
+            root,
+            "crates/core/Cargo.toml",
+            "# core library\n[package]\nname = \"calc-core\" # the engine\nversion = \"0.1.0\"\n\n[dependencies]\nname = \"not-this\"\n",
+        );
+
+        assert_eq!(
+            cargo_package(root, "crates/core/src/parser/mod.rs"),
+            Some(("crates/core/Cargo.toml".to_string(), "calc-core".to_string()))
+        );
+        let command = SmokeCommand::for_file(root, "crates/core/src/lib.rs").unwrap();
+        assert_eq!(command.program, "cargo");
+        assert!(command.to_string().ends_with("--manifest-path crates/core/Cargo.toml -p calc-core"));
+
+        // The workspace root has no package, and nothing above the project counts
+        assert_eq!(cargo_package(root, "src/main.rs"), None);
+        assert_eq!(SmokeCommand::for_file(root, "build.rs"), None);
+    }
+
+    #[test]
+    fn test_gate_only_checks_enabled_languages() {
+        let gate = SmokeGate::new("/tmp/project", vec![Language::Python]);
+        assert!(gate.is_enabled());
+        assert!(gate.command_for("main.py").is_some());
+        assert!(gate.command_for("index.js").is_none());
+        assert!(!SmokeGate::new("/tmp/project", Vec::new()).is_enabled());
+    }
+
+    #[tokio::test]
+    async fn test_gate_debounces_and_reports_python_syntax_errors() {
+        if std::process::Command::new("python3").arg("--version").output().is_err() {
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let gate = SmokeGate::new(root, vec![Language::Python]).with_debounce(Duration::from_millis(50));
+
+        // Three quick writes: only the last version is checked
+        write(root, "app.py", "print('ok')\n");
+        gate.on_write("app.py");
+        write(root, "app.py", "def broken(:\n");
+        gate.on_write("app.py");
+        gate.on_write("app.py");
+        gate.settle().await;
+
+        assert_eq!(gate.checks(), 1);
+        assert_eq!(gate.catches(), 1);
+        let findings = gate.take_findings();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].path, "app.py");
+        assert!(findings[0].errors.contains("SyntaxError"), "{}", findings[0].errors);
+        assert!(gate.take_findings().is_empty());
+
+        // Fixed: checked again, nothing to report
+        write(root, "app.py", "def fixed():\n    return 1\n");
+        gate.on_write("app.py");
+        gate.settle().await;
+        assert_eq!(gate.checks(), 2);
+        assert_eq!(gate.catches(), 1);
+        assert!(gate.take_findings().is_empty());
+    }
+}
@@ -0,0 +1,373 @@
+//! What each user story costs, and what cutting one would save.
+//!
+//! `ralph scope` answers "what would it cost to drop feature X?" from
+//! `tasks.json` alone, without calling a model. Each story's remaining tasks
+//! (pending or in progress) are summed by their complexity weight (1 low,
+//! 2 medium, 3 high) and turned into loop iterations and money by a
+//! [`CostModel`]:
+//!
+//! - Iterations per weight point come from the completed tasks' attempts
+//!   when there are any, and [`DEFAULT_ITERATIONS_PER_WEIGHT`] otherwise.
+//! - Money is iterations times `RALPH_COST_PER_ITERATION`, when set.
+//!
+//! [`what_if`] shows the plan without some stories; [`cut_stories`] marks
+//! their remaining tasks [`TaskStatus::Obsolete`]. A cut that would leave a
+//! task of another story waiting on a removed task is refused.
+
+use crate::models::{Task, TaskList, TaskStatus};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+
+/// Loop iterations per complexity weight point before any task has completed.
+pub const DEFAULT_ITERATIONS_PER_WEIGHT: f64 = 2.0;
+
+/// Label of the tasks that name no user story.
+const NO_STORY: &str = "(no story)";
+
+/// Turns complexity weight into loop iterations and money.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CostModel {
+    /// Loop iterations one weight point takes
+    pub iterations_per_weight: f64,
+    /// Model spend per loop iteration, when known
+    pub cost_per_iteration: Option<f64>,
+}
+
+impl Default for CostModel {
+    fn default() -> Self {
+        Self {
+            iterations_per_weight: DEFAULT_ITERATIONS_PER_WEIGHT,
+            cost_per_iteration: None,
+        }
+    }
+}
+
+impl CostModel {
+    /// A model calibrated on `list`'s completed tasks: their attempts per weight point.
+    pub fn calibrated(list: &TaskList, cost_per_iteration: Option<f64>) -> Self {
+        let (attempts, weight) = list
+            .get_all_tasks()
+            .into_iter()
+            .filter(|t| t.is_completed() && t.attempts > 0)
+            .fold((0u32, 0u32), |(a, w), t| (a + t.attempts, w + t.estimated_complexity.weight()));
+        let iterations_per_weight = if weight > 0 {
+            attempts as f64 / weight as f64
+        } else {
+            DEFAULT_ITERATIONS_PER_WEIGHT
+        };
+        Self { iterations_per_weight, cost_per_iteration }
+    }
+
+    /// Estimate for tasks of total `weight`.
+    pub fn estimate(&self, tasks: usize, weight: u32) -> Estimate {
+        let iterations = (weight as f64 * self.iterations_per_weight).ceil() as u32;
+        Estimate {
+            tasks,
+            weight,
+            iterations,
+            cost: self.cost_per_iteration.map(|cost| cost * iterations as f64),
+        }
+    }
+}
+
+/// Size of some remaining work.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    /// Number of tasks
+    pub tasks: usize,
+    /// Summed complexity weight
+    pub weight: u32,
+    /// Expected loop iterations
+    pub iterations: u32,
+    /// Expected model spend, when the cost per iteration is known
+    pub cost: Option<f64>,
+}
+
+impl std::fmt::Display for Estimate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} task(s), weight {}, ~{} iteration(s)",
+            self.tasks, self.weight, self.iterations
+        )?;
+        if let Some(cost) = self.cost {
+            write!(f, ", ~${:.2}", cost)?;
+        }
+        Ok(())
+    }
+}
+
+/// Remaining work of one user story.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoryCost {
+    /// Story ID, or `None` for tasks that name no story
+    pub story_id: Option<String>,
+    /// Story title from the PRD, when known
+    pub title: Option<String>,
+    /// IDs of the story's remaining tasks
+    pub tasks: Vec<String>,
+    /// Size of those tasks
+    pub estimate: Estimate,
+}
+
+/// Whether `task` still has to run.
+fn is_remaining(task: &Task) -> bool {
+    task.status.is_workable()
+}
+
+/// Remaining work per user story, most expensive first.
+///
+/// `titles` maps story IDs to their PRD titles. Stories with no remaining
+/// task are left out; ties are ordered by story ID.
+pub fn story_costs(list: &TaskList, model: &CostModel, titles: &BTreeMap<String, String>) -> Vec<StoryCost> {
+    let mut by_story: BTreeMap<Option<&str>, Vec<&Task>> = BTreeMap::new();
+    for task in list.get_all_tasks().into_iter().filter(|t| is_remaining(t)) {
+        by_story.entry(task.user_story_id.as_deref()).or_default().push(task);
+    }
+
+    let mut costs: Vec<StoryCost> = by_story
+        .into_iter()
+        .map(|(story_id, tasks)| {
+            let weight: u32 = tasks.iter().map(|t| t.estimated_complexity.weight()).sum();
+            StoryCost {
+                story_id: story_id.map(String::from),
+                title: story_id.and_then(|id| titles.get(id).cloned()),
+                tasks: tasks.iter().map(|t| t.id.clone()).collect(),
+                estimate: model.estimate(tasks.len(), weight),
+            }
+        })
+        .collect();
+    costs.sort_by(|a, b| b.estimate.weight.cmp(&a.estimate.weight).then_with(|| a.story_id.cmp(&b.story_id)));
+    costs
+}
+
+/// Render [`story_costs`] as a ranked table.
+pub fn render_story_costs(costs: &[StoryCost], total: &Estimate) -> String {
+    let mut out = String::new();
+    if costs.is_empty() {
+        out.push_str("No remaining tasks.\n");
+        return out;
+    }
+    for (rank, story) in costs.iter().enumerate() {
+        let id = story.story_id.as_deref().unwrap_or(NO_STORY);
+        let _ = write!(out, "{:>2}. {}", rank + 1, id);
+        if let Some(title) = &story.title {
+            let _ = write!(out, " {}", title);
+        }
+        let _ = writeln!(out, "\n    {} ({})", story.estimate, story.tasks.join(", "));
+    }
+    let _ = writeln!(out, "\nTotal remaining: {}", total);
+    out
+}
+
+/// The plan without some user stories.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WhatIf {
+    /// Stories cut
+    pub cut: Vec<String>,
+    /// Remaining tasks that would be dropped
+    pub removed: Vec<String>,
+    /// Remaining tasks that would still run
+    pub kept: Vec<String>,
+    /// Kept tasks that depend on a removed one, as (task, dependency)
+    pub stranded: Vec<(String, String)>,
+    /// Remaining work now
+    pub before: Estimate,
+    /// Remaining work without the stories
+    pub after: Estimate,
+}
+
+impl WhatIf {
+    /// Whether the cut leaves no task waiting on a removed one.
+    pub fn is_clean(&self) -> bool {
+        self.stranded.is_empty()
+    }
+}
+
+impl std::fmt::Display for WhatIf {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Without {}:", self.cut.join(", "))?;
+        writeln!(f, "  Now:     {}", self.before)?;
+        writeln!(f, "  Without: {}", self.after)?;
+        let saved_iterations = self.before.iterations.saturating_sub(self.after.iterations);
+        write!(f, "  Saves:   {} task(s), ~{} iteration(s)", self.removed.len(), saved_iterations)?;
+        if let (Some(before), Some(after)) = (self.before.cost, self.after.cost) {
+            write!(f, ", ~${:.2}", before - after)?;
+        }
+        writeln!(f)?;
+        if !self.removed.is_empty() {
+            writeln!(f, "  Dropped: {}", self.removed.join(", "))?;
+        }
+        if !self.kept.is_empty() {
+            writeln!(f, "  Still to run: {}", self.kept.join(", "))?;
+        }
+        for (task, dependency) in &self.stranded {
+            writeln!(f, "  ! {} depends on {}, which would be dropped", task, dependency)?;
+        }
+        Ok(())
+    }
+}
+
+/// What the remaining plan would look like without the stories in `cut`.
+pub fn what_if(list: &TaskList, model: &CostModel, cut: &[String]) -> WhatIf {
+    let cut_set: BTreeSet<&str> = cut.iter().map(String::as_str).collect();
+    let in_cut = |task: &Task| task.user_story_id.as_deref().is_some_and(|id| cut_set.contains(id));
+    let remaining: Vec<&Task> = list.get_all_tasks().into_iter().filter(|t| is_remaining(t)).collect();
+
+    let (removed, kept): (Vec<&Task>, Vec<&Task>) = remaining.iter().copied().partition(|t| in_cut(t));
+    let removed_ids: BTreeSet<&str> = removed.iter().map(|t| t.id.as_str()).collect();
+    let stranded = kept
+        .iter()
+        .flat_map(|t| {
+            t.dependencies
+                .iter()
+                .filter(|dep| removed_ids.contains(dep.as_str()))
+                .map(|dep| (t.id.clone(), dep.clone()))
+        })
+        .collect();
+
+    let weight = |tasks: &[&Task]| -> u32 { tasks.iter().map(|t| t.estimated_complexity.weight()).sum() };
+    WhatIf {
+        cut: cut.to_vec(),
+        removed: removed.iter().map(|t| t.id.clone()).collect(),
+        kept: kept.iter().map(|t| t.id.clone()).collect(),
+        stranded,
+        before: model.estimate(remaining.len(), weight(&remaining)),
+        after: model.estimate(kept.len(), weight(&kept)),
+    }
+}
+
+/// Mark the remaining tasks of the stories in `cut` obsolete.
+///
+/// Returns the IDs of the tasks marked. Nothing changes when a story has no
+/// task at all, or when a task outside the cut depends on one being removed.
+pub fn cut_stories(list: &mut TaskList, cut: &[String]) -> Result<Vec<String>, String> {
+    for story in cut {
+        if !list.get_all_tasks().iter().any(|t| t.user_story_id.as_deref() == Some(story.as_str())) {
+            return Err(format!("No task implements {}", story));
+        }
+    }
+    let plan = what_if(list, &CostModel::default(), cut);
+    if !plan.is_clean() {
+        let waiting: Vec<String> = plan.stranded.iter().map(|(task, dep)| format!("{} on {}", task, dep)).collect();
+        return Err(format!(
+            "Cutting {} would leave tasks waiting on dropped work ({}); cut their stories too",
+            cut.join(", "),
+            waiting.join(", ")
+        ));
+    }
+    for id in &plan.removed {
+        list.update_task_status(id, TaskStatus::Obsolete)?;
+        if let Some(task) = list.get_task_mut(id) {
+            task.add_note("Obsolete: its user story was cut from scope");
+        }
+    }
+    Ok(plan.removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TaskComplexity;
+
+    fn task(id: &str, story: &str, complexity: TaskComplexity, deps: &[&str]) -> Task {
+        let mut task = Task::new(id, format!("Task {}", id), "", 1)
+            .with_user_story(story)
+            .with_complexity(complexity);
+        for dep in deps {
+            task.add_dependency(*dep);
+        }
+        task
+    }
+
+    /// US-001: T-1 (done), T-2 high; US-002: T-3 medium, T-4 low; US-003: T-5 medium
+    fn fixture() -> TaskList {
+        let mut list = TaskList::new("shop", "rust");
+        let mut done = task("T-1", "US-001", TaskComplexity::Medium, &[]);
+        done.status = TaskStatus::Completed;
+        done.attempts = 6;
+        list.add_task(done);
+        list.add_task(task("T-2", "US-001", TaskComplexity::High, &["T-1"]));
+        list.add_task(task("T-3", "US-002", TaskComplexity::Medium, &["T-1"]));
+        list.add_task(task("T-4", "US-002", TaskComplexity::Low, &["T-3"]));
+        list.add_task(task("T-5", "US-003", TaskComplexity::Medium, &["T-2"]));
+        list
+    }
+
+    #[test]
+    fn test_cost_model_is_calibrated_on_completed_tasks() {
+        let list = fixture();
+        let model = CostModel::calibrated(&list, Some(0.5));
+        assert_eq!(model.iterations_per_weight, 3.0);
+        let estimate = model.estimate(2, 3);
+        assert_eq!(estimate.iterations, 9);
+        assert_eq!(estimate.cost, Some(4.5));
+        assert_eq!(estimate.to_string(), "2 task(s), weight 3, ~9 iteration(s), ~$4.50");
+
+        let fresh = CostModel::calibrated(&TaskList::new("shop", "rust"), None);
+        assert_eq!(fresh, CostModel::default());
+        assert_eq!(fresh.estimate(1, 3).to_string(), "1 task(s), weight 3, ~6 iteration(s)");
+    }
+
+    #[test]
+    fn test_story_costs_rank_remaining_work() {
+        let mut list = fixture();
+        list.add_task(Task::new("T-6", "Write README", "", 3).with_complexity(TaskComplexity::Low));
+        let titles = BTreeMap::from([("US-002".to_string(), "Checkout".to_string())]);
+        let costs = story_costs(&list, &CostModel::default(), &titles);
+
+        let summary: Vec<(Option<&str>, u32, usize)> = costs
+            .iter()
+            .map(|c| (c.story_id.as_deref(), c.estimate.weight, c.tasks.len()))
+            .collect();
+        // US-001 only counts its remaining high task; US-002 and US-001 tie at 3
+        assert_eq!(
+            summary,
+            [(Some("US-001"), 3, 1), (Some("US-002"), 3, 2), (Some("US-003"), 2, 1), (None, 1, 1)]
+        );
+        assert_eq!(costs[1].title.as_deref(), Some("Checkout"));
+
+        let rendered = render_story_costs(&costs, &CostModel::default().estimate(5, 9));
+        assert!(rendered.contains(" 2. US-002 Checkout\n    2 task(s), weight 3, ~6 iteration(s) (T-3, T-4)"), "{}", rendered);
+        assert!(rendered.contains(" 4. (no story)"), "{}", rendered);
+        assert!(rendered.ends_with("Total remaining: 5 task(s), weight 9, ~18 iteration(s)\n"), "{}", rendered);
+    }
+
+    #[test]
+    fn test_what_if_without_a_story() {
+        let list = fixture();
+        let model = CostModel { iterations_per_weight: 2.0, cost_per_iteration: Some(1.0) };
+        let plan = what_if(&list, &model, &["US-002".to_string()]);
+        assert_eq!(plan.removed, ["T-3", "T-4"]);
+        assert_eq!(plan.kept, ["T-2", "T-5"]);
+        assert!(plan.is_clean());
+        assert_eq!((plan.before.weight, plan.before.iterations), (8, 16));
+        assert_eq!((plan.after.weight, plan.after.iterations, plan.after.cost), (5, 10, Some(10.0)));
+        assert!(plan.to_string().contains("Saves:   2 task(s), ~6 iteration(s), ~$6.00"), "{}", plan);
+
+        // US-003 depends on US-001's remaining task
+        let plan = what_if(&list, &model, &["US-001".to_string()]);
+        assert_eq!(plan.stranded, [("T-5".to_string(), "T-2".to_string())]);
+        assert!(plan.to_string().contains("! T-5 depends on T-2, which would be dropped"));
+    }
+
+    #[test]
+    fn test_cut_marks_remaining_tasks_obsolete() {
+        let mut list = fixture();
+        assert!(cut_stories(&mut list, &["US-009".to_string()]).unwrap_err().contains("No task implements US-009"));
+        let err = cut_stories(&mut list, &["US-001".to_string()]).unwrap_err();
+        assert!(err.contains("T-5 on T-2"), "{}", err);
+        assert_eq!(list.get_task("T-2").unwrap().status, TaskStatus::Pending);
+
+        let cut = cut_stories(&mut list, &["US-001".to_string(), "US-003".to_string()]).unwrap();
+        assert_eq!(cut, ["T-2", "T-5"]);
+        assert_eq!(list.get_task("T-1").unwrap().status, TaskStatus::Completed);
+        assert_eq!(list.get_task("T-2").unwrap().status, TaskStatus::Obsolete);
+        assert!(list.get_task("T-5").unwrap().notes.contains("user story was cut"));
+        assert_eq!(list.ready_tasks(Default::default()).len(), 1);
+
+        list.complete_task("T-3", None).unwrap();
+        list.complete_task("T-4", None).unwrap();
+        assert!(list.is_complete());
+    }
+}
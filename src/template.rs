@@ -0,0 +1,514 @@
+//! Project templates: a finished project's design and scaffold as a starter.
+//!
+//! `ralph template create <name>` captures the design of the project in the
+//! current directory and its scaffold files (see [`is_scaffold_file`]) into
+//! one JSON file under [`templates_dir`]. Business logic is never captured:
+//! only manifests, CI and tool configuration, and the directory skeleton.
+//!
+//! `ralph init --template <name>` and `ralph run --template <name>` seed a new
+//! project from it. Seeding also writes the template to [`SEEDED_TEMPLATE`] in
+//! the project, from where the architect picks up the template's design as
+//! warm-start context ([`warm_start_context`]).
+//!
+//! Templates carry a format version. Older formats are upgraded on load with
+//! a warning; a template from a newer format is refused rather than misread.
+
+use crate::models::DesignDocument;
+use crate::{RalphError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Template format written by this version of Ralph.
+pub const TEMPLATE_FORMAT_VERSION: u32 = 1;
+
+/// Where a seeded project keeps the template it started from.
+pub const SEEDED_TEMPLATE: &str = ".ralph/template.json";
+
+/// File names captured wherever they appear: manifests and tool configuration.
+const SCAFFOLD_FILES: &[&str] = &[
+    "Cargo.toml",
+    "rust-toolchain.toml",
+    "rustfmt.toml",
+    ".rustfmt.toml",
+    "clippy.toml",
+    "package.json",
+    "tsconfig.json",
+    "vitest.config.ts",
+    ".eslintrc.json",
+    ".prettierrc",
+    "pyproject.toml",
+    "requirements.txt",
+    "requirements-dev.txt",
+    "setup.cfg",
+    "go.mod",
+    "Makefile",
+    "Dockerfile",
+    ".dockerignore",
+    ".gitignore",
+    ".editorconfig",
+    ".gitlab-ci.yml",
+];
+
+/// Directories whose files are CI configuration, captured whole.
+const CI_DIRS: &[&str] = &[".github/workflows"];
+
+/// Directories never captured: VCS, Ralph state, build output and dependencies.
+const SKIPPED_DIRS: &[&str] = &[
+    ".git",
+    ".ralph",
+    "target",
+    "node_modules",
+    "dist",
+    "build",
+    "__pycache__",
+    ".venv",
+    "venv",
+    ".pytest_cache",
+];
+
+/// Scaffold files larger than this are left out.
+const MAX_FILE_BYTES: u64 = 64 * 1024;
+
+/// A captured scaffold file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemplateFile {
+    /// Path relative to the project root
+    pub path: String,
+    /// File content
+    pub content: String,
+}
+
+/// A reusable project starter.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectTemplate {
+    /// Template format (see [`TEMPLATE_FORMAT_VERSION`])
+    #[serde(default)]
+    pub format_version: u32,
+    /// Ralph version that created the template
+    #[serde(default)]
+    pub ralph_version: String,
+    /// Template name
+    pub name: String,
+    /// When the template was created
+    #[serde(default)]
+    pub created_at: String,
+    /// Design of the project the template was taken from
+    pub design: DesignDocument,
+    /// Directory skeleton, relative to the project root
+    #[serde(default)]
+    pub directories: Vec<String>,
+    /// Scaffold files
+    #[serde(default)]
+    pub files: Vec<TemplateFile>,
+}
+
+/// Whether `path` (relative, `/`-separated) is a scaffold file worth capturing.
+pub fn is_scaffold_file(path: &str) -> bool {
+    if path.split('/').any(|part| SKIPPED_DIRS.contains(&part)) {
+        return false;
+    }
+    let in_ci_dir = |dir: &&str| path.strip_prefix(*dir).is_some_and(|rest| rest.starts_with('/'));
+    if CI_DIRS.iter().any(in_ci_dir) {
+        return true;
+    }
+    let name = path.rsplit('/').next().unwrap_or(path);
+    SCAFFOLD_FILES.contains(&name)
+}
+
+/// Check a template name: letters, digits, `-` and `_`.
+pub fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(RalphError::config(format!(
+            "Invalid template name '{}': use letters, digits, '-' and '_'",
+            name
+        )))
+    }
+}
+
+/// Directory templates are stored in.
+///
+/// `RALPH_TEMPLATES_DIR` if set, else `ralph/templates` under
+/// `XDG_CONFIG_HOME` or `~/.config`.
+pub fn templates_dir() -> Result<PathBuf> {
+    if let Ok(dir) = std::env::var("RALPH_TEMPLATES_DIR") {
+        return Ok(PathBuf::from(dir));
+    }
+    let config_home = std::env::var("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|_| std::env::var("HOME").map(|home| Path::new(&home).join(".config")))
+        .map_err(|_| {
+            RalphError::config("Cannot locate the templates directory: set RALPH_TEMPLATES_DIR")
+        })?;
+    Ok(config_home.join("ralph").join("templates"))
+}
+
+impl ProjectTemplate {
+    /// Capture the design at `design_path` and the scaffold of `project_dir`.
+    ///
+    /// The design is read as JSON when the path ends in `.json`, else as Markdown.
+    pub fn capture(name: &str, project_dir: &Path, design_path: &Path) -> Result<Self> {
+        validate_name(name)?;
+        let design = if design_path.extension().is_some_and(|ext| ext == "json") {
+            DesignDocument::load(design_path)
+        } else {
+            DesignDocument::load_markdown(design_path)
+        }
+        .map_err(RalphError::Design)?;
+
+        let mut directories = Vec::new();
+        let mut files = Vec::new();
+        collect(project_dir, "", &mut directories, &mut files)?;
+        directories.sort();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        Ok(Self {
+            format_version: TEMPLATE_FORMAT_VERSION,
+            ralph_version: env!("CARGO_PKG_VERSION").to_string(),
+            name: name.to_string(),
+            created_at: crate::timefmt::now(),
+            design,
+            directories,
+            files,
+        })
+    }
+
+    /// Parse a template, upgrading older formats.
+    ///
+    /// Returns the template and warnings about how it was read.
+    pub fn from_json(json: &str) -> Result<(Self, Vec<String>)> {
+        let value: Value = serde_json::from_str(json)
+            .map_err(|e| RalphError::config(format!("Invalid template: {}", e)))?;
+        let format = value["format_version"].as_u64().unwrap_or(0);
+        let created_by = value["ralph_version"].as_str().unwrap_or("an unknown version");
+        if format > u64::from(TEMPLATE_FORMAT_VERSION) {
+            return Err(RalphError::config(format!(
+                "Template was created by ralph {} in format {}; this ralph ({}) reads up to \
+                 format {}. Upgrade ralph to use it",
+                created_by,
+                format,
+                env!("CARGO_PKG_VERSION"),
+                TEMPLATE_FORMAT_VERSION
+            )));
+        }
+
+        let mut warnings = Vec::new();
+        if format < u64::from(TEMPLATE_FORMAT_VERSION) {
+            warnings.push(format!(
+                "Template was created by ralph {} (format {}); upgraded to format {}. \
+                 Recreate it to keep its directory skeleton",
+                created_by, format, TEMPLATE_FORMAT_VERSION
+            ));
+        } else if created_by != env!("CARGO_PKG_VERSION") {
+            warnings.push(format!(
+                "Template was created by ralph {}; this is ralph {}",
+                created_by,
+                env!("CARGO_PKG_VERSION")
+            ));
+        }
+
+        let mut template: Self = serde_json::from_value(value)
+            .map_err(|e| RalphError::config(format!("Invalid template: {}", e)))?;
+        template.format_version = TEMPLATE_FORMAT_VERSION;
+        Ok((template, warnings))
+    }
+
+    /// Path of template `name` in `dir`.
+    pub fn path_in(dir: &Path, name: &str) -> PathBuf {
+        dir.join(format!("{}.json", name))
+    }
+
+    /// Write the template into `dir` as `<name>.json`.
+    pub fn save(&self, dir: &Path) -> Result<PathBuf> {
+        fs::create_dir_all(dir)
+            .map_err(|e| RalphError::file(dir.display().to_string(), e.to_string()))?;
+        let path = Self::path_in(dir, &self.name);
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&path, json)
+            .map_err(|e| RalphError::file(path.display().to_string(), e.to_string()))?;
+        Ok(path)
+    }
+
+    /// Read template `name` from `dir`.
+    pub fn load(dir: &Path, name: &str) -> Result<(Self, Vec<String>)> {
+        validate_name(name)?;
+        let path = Self::path_in(dir, name);
+        let json = fs::read_to_string(&path).map_err(|e| {
+            RalphError::config(format!("Template '{}' not found in {}: {}", name, dir.display(), e))
+        })?;
+        Self::from_json(&json)
+    }
+
+    /// Seed `project_dir` with the skeleton and scaffold files.
+    ///
+    /// Existing files are kept. Returns the files written, relative to
+    /// `project_dir`. The template itself is stored at [`SEEDED_TEMPLATE`].
+    pub fn seed(&self, project_dir: &Path) -> Result<Vec<String>> {
+        self.seed_overwriting(project_dir, &[])
+    }
+
+    /// Template files whose target already exists with different content,
+    /// paired with the existing content.
+    pub fn conflicts(&self, project_dir: &Path) -> Vec<(&TemplateFile, Vec<u8>)> {
+        self.files
+            .iter()
+            .filter_map(|file| {
+                let existing = fs::read(safe_join(project_dir, &file.path).ok()?).ok()?;
+                (existing != file.content.as_bytes()).then_some((file, existing))
+            })
+            .collect()
+    }
+
+    /// Like [`ProjectTemplate::seed`], but replaces the existing files listed in `overwrite`.
+    pub fn seed_overwriting(
+        &self,
+        project_dir: &Path,
+        overwrite: &[String],
+    ) -> Result<Vec<String>> {
+        let io = |path: &Path, e: std::io::Error| {
+            RalphError::file(path.display().to_string(), e.to_string())
+        };
+        for dir in &self.directories {
+            let path = safe_join(project_dir, dir)?;
+            fs::create_dir_all(&path).map_err(|e| io(&path, e))?;
+        }
+
+        let mut written = Vec::new();
+        for file in &self.files {
+            let path = safe_join(project_dir, &file.path)?;
+            if path.exists() && !overwrite.contains(&file.path) {
+                continue;
+            }
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| io(parent, e))?;
+            }
+            fs::write(&path, &file.content).map_err(|e| io(&path, e))?;
+            written.push(file.path.clone());
+        }
+
+        let seeded = project_dir.join(SEEDED_TEMPLATE);
+        if let Some(parent) = seeded.parent() {
+            fs::create_dir_all(parent).map_err(|e| io(parent, e))?;
+        }
+        fs::write(&seeded, serde_json::to_string_pretty(self)?).map_err(|e| io(&seeded, e))?;
+        Ok(written)
+    }
+}
+
+/// Join a template path onto the project, refusing absolute paths and `..`.
+fn safe_join(project_dir: &Path, relative: &str) -> Result<PathBuf> {
+    let path = Path::new(relative);
+    let escapes = path.is_absolute()
+        || path.components().any(|c| matches!(c, std::path::Component::ParentDir));
+    if escapes {
+        return Err(RalphError::config(format!(
+            "Template path '{}' is outside the project",
+            relative
+        )));
+    }
+    Ok(project_dir.join(path))
+}
+
+/// Walk `dir`, collecting the skeleton and scaffold files below `prefix`.
+fn collect(
+    dir: &Path,
+    prefix: &str,
+    directories: &mut Vec<String>,
+    files: &mut Vec<TemplateFile>,
+) -> Result<()> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| RalphError::file(dir.display().to_string(), e.to_string()))?;
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let relative = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{}/{}", prefix, name)
+        };
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            if SKIPPED_DIRS.contains(&name.as_str()) {
+                continue;
+            }
+            directories.push(relative.clone());
+            collect(&entry.path(), &relative, directories, files)?;
+        } else if file_type.is_file() && is_scaffold_file(&relative) {
+            let small = entry.metadata().map(|m| m.len() <= MAX_FILE_BYTES).unwrap_or(false);
+            // Binary files are not scaffold
+            if let (true, Ok(content)) = (small, fs::read_to_string(entry.path())) {
+                files.push(TemplateFile { path: relative, content });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Architect context from the template a project was seeded from, if any.
+pub fn warm_start_context(project_dir: &Path) -> Option<String> {
+    let json = fs::read_to_string(project_dir.join(SEEDED_TEMPLATE)).ok()?;
+    let (template, _) = ProjectTemplate::from_json(&json).ok()?;
+    let mut context = format!(
+        "\n\n## Starter Template\n\nThis project was seeded from the template '{}'. Reuse its \
+         technology stack, structure and conventions where they fit the PRD above, and drop \
+         what does not. Its scaffold files are already in place: {}.\n\n",
+        template.name,
+        if template.files.is_empty() {
+            "none".to_string()
+        } else {
+            template.files.iter().map(|f| f.path.as_str()).collect::<Vec<_>>().join(", ")
+        }
+    );
+    context.push_str(&template.design.to_markdown());
+    Some(context)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    /// A finished Rust API project.
+    fn finished_project(root: &Path) {
+        let mut design = DesignDocument::new("todo-api", "REST API for todos");
+        design.set_technology_stack(crate::models::TechnologyStack::new("rust"));
+        design.save_markdown(root.join("design.md")).unwrap();
+        write(root, "Cargo.toml", "[package]\nname = \"todo-api\"\n");
+        write(root, "src/main.rs", "fn main() {}\n");
+        write(root, "src/routes/todos.rs", "// business logic\n");
+        write(root, ".github/workflows/ci.yml", "on: push\n");
+        write(root, "target/debug/Cargo.toml", "build output\n");
+        write(root, "tasks.json", "{}");
+        write(root, ".ralph/prompts/architect.md", "journal");
+    }
+
+    #[test]
+    fn test_scaffold_file_selection() {
+        let captured = [
+            "Cargo.toml",
+            "crates/core/Cargo.toml",
+            ".github/workflows/ci.yml",
+            ".gitignore",
+        ];
+        for path in captured {
+            assert!(is_scaffold_file(path), "{}", path);
+        }
+        let left_out = [
+            "src/main.rs",
+            "tasks.json",
+            "design.md",
+            "target/Cargo.toml",
+            ".github/CODEOWNERS",
+        ];
+        for path in left_out {
+            assert!(!is_scaffold_file(path), "{}", path);
+        }
+        assert!(validate_name("rest-api_2").is_ok());
+        assert!(validate_name("../evil").is_err());
+    }
+
+    #[test]
+    fn test_template_packs_and_seeds_scaffold_only() {
+        let project = tempdir().unwrap();
+        finished_project(project.path());
+        let template =
+            ProjectTemplate::capture("rest-api", project.path(), &project.path().join("design.md"))
+                .unwrap();
+        let paths: Vec<&str> = template.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec![".github/workflows/ci.yml", "Cargo.toml"]);
+        assert!(template.directories.contains(&"src/routes".to_string()));
+        let skipped = |d: &String| d.starts_with("target") || d.starts_with(".ralph");
+        assert!(!template.directories.iter().any(skipped));
+
+        let store = tempdir().unwrap();
+        let saved = template.save(store.path()).unwrap();
+        assert_eq!(saved, store.path().join("rest-api.json"));
+        let (loaded, warnings) = ProjectTemplate::load(store.path(), "rest-api").unwrap();
+        assert_eq!(loaded, template);
+        assert!(warnings.is_empty());
+
+        let fresh = tempdir().unwrap();
+        write(fresh.path(), ".gitignore", "keep me\n");
+        let written = loaded.seed(fresh.path()).unwrap();
+        assert_eq!(written, vec![".github/workflows/ci.yml", "Cargo.toml"]);
+        assert!(fresh.path().join("src/routes").is_dir());
+        assert!(!fresh.path().join("src/main.rs").exists());
+        assert_eq!(fs::read_to_string(fresh.path().join(".gitignore")).unwrap(), "keep me\n");
+
+        // Differing files are replaced only when the caller asks
+        assert!(loaded.conflicts(fresh.path()).is_empty());
+        write(fresh.path(), "Cargo.toml", "[package]\nname = \"mine\"\n");
+        let conflicts = loaded.conflicts(fresh.path());
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].0.path, "Cargo.toml");
+        assert_eq!(conflicts[0].1, b"[package]\nname = \"mine\"\n");
+        assert!(loaded.seed(fresh.path()).unwrap().is_empty());
+        let overwrite = vec!["Cargo.toml".to_string()];
+        assert_eq!(loaded.seed_overwriting(fresh.path(), &overwrite).unwrap(), overwrite);
+        assert!(loaded.conflicts(fresh.path()).is_empty());
+    }
+
+    #[test]
+    fn test_seeded_design_is_warm_start_context() {
+        let fresh = tempdir().unwrap();
+        assert_eq!(warm_start_context(fresh.path()), None);
+
+        let template = ProjectTemplate {
+            format_version: TEMPLATE_FORMAT_VERSION,
+            ralph_version: env!("CARGO_PKG_VERSION").to_string(),
+            name: "rest-api".to_string(),
+            created_at: String::new(),
+            design: DesignDocument::new("todo-api", "REST API for todos"),
+            directories: vec!["src".to_string()],
+            files: vec![TemplateFile { path: "Cargo.toml".to_string(), content: String::new() }],
+        };
+        template.seed(fresh.path()).unwrap();
+        let context = warm_start_context(fresh.path()).unwrap();
+        assert!(context.contains("seeded from the template 'rest-api'"));
+        assert!(context.contains("already in place: Cargo.toml"));
+        assert!(context.contains("REST API for todos"));
+    }
+
+    #[test]
+    fn test_template_versions() {
+        let design = serde_json::to_value(DesignDocument::new("todo-api", "REST API")).unwrap();
+
+        // Written before templates were versioned: no format, no skeleton
+        let old = serde_json::json!({
+            "name": "rest-api",
+            "ralph_version": "0.1.0",
+            "design": design
+        });
+        let (template, warnings) = ProjectTemplate::from_json(&old.to_string()).unwrap();
+        assert_eq!(template.format_version, TEMPLATE_FORMAT_VERSION);
+        assert!(template.directories.is_empty());
+        assert!(warnings[0].contains("created by ralph 0.1.0 (format 0)"));
+
+        let newer = serde_json::json!({
+            "format_version": TEMPLATE_FORMAT_VERSION + 1,
+            "ralph_version": "99.0.0",
+            "name": "rest-api",
+            "design": design
+        });
+        let err = ProjectTemplate::from_json(&newer.to_string()).unwrap_err();
+        assert!(err.to_string().contains("Upgrade ralph"));
+
+        let escaping = ProjectTemplate {
+            files: vec![TemplateFile { path: "../outside".to_string(), content: String::new() }],
+            ..template
+        };
+        assert!(escaping.seed(tempdir().unwrap().path()).is_err());
+    }
+}
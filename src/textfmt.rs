@@ -0,0 +1,98 @@
+//! Text shortening shared by all output.
+//!
+//! Model replies, test output and task titles can contain any Unicode, so
+//! text is never cut at a byte index: [`truncate_display`] counts characters,
+//! keeps combining marks, variation selectors and zero-width joiners with the
+//! character they modify, and marks the cut with `...`. It does not
+//! implement full grapheme segmentation (e.g. regional-indicator flag pairs
+//! can be split), but every cut is valid UTF-8 and never panics.
+
+use std::borrow::Cow;
+
+/// Appended where text was cut. ASCII, so it shows on every console.
+pub const ELLIPSIS: &str = "...";
+
+/// Shorten `text` to at most `max_chars` visible characters, including the
+/// ellipsis, and return it unchanged when it already fits.
+pub fn truncate_display(text: &str, max_chars: usize) -> Cow<'_, str> {
+    // Too narrow for an ellipsis: keep what fits without one
+    let with_ellipsis = max_chars >= ELLIPSIS.len();
+    let keep = if with_ellipsis { max_chars - ELLIPSIS.len() } else { max_chars };
+    let mut clusters = 0;
+    // Byte offset where the kept text ends
+    let mut cut = 0;
+    let mut after_joiner = false;
+    for (at, c) in text.char_indices() {
+        let joined = after_joiner || joins_previous(c);
+        after_joiner = c == '\u{200D}';
+        if joined {
+            continue;
+        }
+        if clusters == keep {
+            cut = at;
+        }
+        clusters += 1;
+        if clusters > max_chars {
+            let kept = &text[..cut];
+            if !with_ellipsis {
+                return Cow::Borrowed(kept);
+            }
+            return Cow::Owned(format!("{}{}", kept, ELLIPSIS));
+        }
+    }
+    Cow::Borrowed(text)
+}
+
+/// Characters drawn as part of the one before them, which a cut must not
+/// separate from it.
+fn joins_previous(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0300}'..='\u{036F}'     // combining diacritical marks
+            | '\u{1AB0}'..='\u{1AFF}'
+            | '\u{1DC0}'..='\u{1DFF}'
+            | '\u{200D}'            // zero-width joiner
+            | '\u{20D0}'..='\u{20FF}'
+            | '\u{FE00}'..='\u{FE0F}' // variation selectors
+            | '\u{FE20}'..='\u{FE2F}'
+            | '\u{1F3FB}'..='\u{1F3FF}' // skin-tone modifiers
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_text_is_unchanged() {
+        assert!(matches!(truncate_display("parser", 10), Cow::Borrowed("parser")));
+        assert_eq!(truncate_display("exactly10!", 10), "exactly10!");
+        assert_eq!(truncate_display("", 0), "");
+    }
+
+    #[test]
+    fn test_long_text_ends_with_an_ellipsis_within_the_limit() {
+        assert_eq!(truncate_display("abcdefghijkl", 10), "abcdefg...");
+        assert_eq!(truncate_display("abcdef", 2), "ab");
+    }
+
+    #[test]
+    fn test_multi_byte_text_is_cut_on_character_boundaries() {
+        // Each of these would panic if sliced at byte 7
+        assert_eq!(truncate_display("🦀🦀🦀🦀🦀🦀", 5), "🦀🦀...");
+        assert_eq!(truncate_display("数据库连接失败了", 6), "数据库...");
+        assert_eq!(truncate_display("naïve café über", 8), "naïve...");
+    }
+
+    #[test]
+    fn test_combining_characters_stay_with_their_base() {
+        // "e" + combining acute at the cut point
+        let decomposed = "cafe\u{301}s and more";
+        assert_eq!(truncate_display(decomposed, 7), "cafe\u{301}...");
+        // Family emoji joined with ZWJs, then a thumbs-up with a skin tone
+        let family = "👨\u{200D}👩\u{200D}👧 👍\u{1F3FD} done";
+        assert_eq!(truncate_display(family, 6), "👨\u{200D}👩\u{200D}👧 👍\u{1F3FD}...");
+        assert_eq!(truncate_display(family, 4), "👨\u{200D}👩\u{200D}👧...");
+        assert_eq!(truncate_display("ok👍\u{1F3FD}xxxxx", 6), "ok👍\u{1F3FD}...");
+    }
+}
@@ -0,0 +1,131 @@
+//! Duration and timestamp formatting shared by all output and artifacts.
+//!
+//! - Durations shown to people are humanized: `380ms`, `45s`, `12m 5s`,
+//!   `1h 12m`. Each unit is truncated, never rounded up, so a step that took
+//!   59.9 seconds reads `59s`, not `1m`.
+//! - Timestamps are stored in UTC as ISO-8601 (RFC 3339) with millisecond
+//!   precision and a `Z` suffix, e.g. `2026-10-16T09:30:00.123Z`. Every
+//!   persisted artifact (tasks.json, design, PRD, progress.json, provenance)
+//!   goes through [`now`] or [`timestamp`].
+//! - Timestamps shown to people are converted to local time with a fixed
+//!   numeric pattern, `2026-10-16 11:30:00`.
+//!
+//! Nothing here depends on the platform locale.
+
+use chrono::{DateTime, Local, SecondsFormat, TimeZone, Utc};
+use std::time::Duration;
+
+/// Pattern for timestamps shown to people.
+const DISPLAY_PATTERN: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Humanized duration, e.g. `380ms`, `45s`, `12m 5s`, `1h 12m`.
+///
+/// The two largest units are shown and a zero second unit is left out
+/// (`3m`, `2h`).
+pub fn duration(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    if secs == 0 {
+        return format!("{}ms", elapsed.as_millis());
+    }
+    let (hours, minutes, seconds) = (secs / 3600, secs % 3600 / 60, secs % 60);
+    let (major, minor) = match (hours, minutes) {
+        (0, 0) => return format!("{}s", seconds),
+        (0, _) => ((minutes, "m"), (seconds, "s")),
+        _ => ((hours, "h"), (minutes, "m")),
+    };
+    if minor.0 == 0 {
+        format!("{}{}", major.0, major.1)
+    } else {
+        format!("{}{} {}{}", major.0, major.1, minor.0, minor.1)
+    }
+}
+
+/// Humanized duration from milliseconds, as stored in artifacts.
+pub fn duration_ms(ms: u64) -> String {
+    duration(Duration::from_millis(ms))
+}
+
+/// The current time in the persisted form.
+pub fn now() -> String {
+    timestamp(Utc::now())
+}
+
+/// A UTC time in the persisted form, e.g. `2026-10-16T09:30:00.123Z`.
+pub fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Parse a persisted timestamp; any RFC 3339 offset is accepted and converted to UTC.
+pub fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value.trim())
+        .ok()
+        .map(|at| at.with_timezone(&Utc))
+}
+
+/// A time for display in the local time zone, e.g. `2026-10-16 11:30:00`.
+pub fn local(at: DateTime<Utc>) -> String {
+    display_in(at, &Local)
+}
+
+/// A time for display in `zone`.
+pub fn display_in<Tz: TimeZone>(at: DateTime<Utc>, zone: &Tz) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    at.with_timezone(zone).format(DISPLAY_PATTERN).to_string()
+}
+
+/// A persisted timestamp for display in local time, or as written if it does not parse.
+pub fn local_from_str(value: &str) -> String {
+    parse_timestamp(value)
+        .map(local)
+        .unwrap_or_else(|| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+
+    #[test]
+    fn test_durations_truncate_each_unit() {
+        let cases = [
+            (0, "0ms"),
+            (380, "380ms"),
+            (999, "999ms"),
+            (1_000, "1s"),
+            (45_900, "45s"),
+            (59_999, "59s"),
+            (60_000, "1m"),
+            (725_000, "12m 5s"),
+            (3_599_999, "59m 59s"),
+            (3_600_000, "1h"),
+            (4_320_000, "1h 12m"),
+            (4_379_000, "1h 12m"),
+            (93_600_000, "26h"),
+        ];
+        for (ms, expected) in cases {
+            assert_eq!(duration_ms(ms), expected, "{} ms", ms);
+        }
+    }
+
+    #[test]
+    fn test_timestamps_are_stored_as_utc_iso_8601() {
+        let at = Utc.with_ymd_and_hms(2026, 10, 16, 9, 30, 0).unwrap();
+        assert_eq!(timestamp(at), "2026-10-16T09:30:00.000Z");
+        assert_eq!(parse_timestamp("2026-10-16T09:30:00.000Z"), Some(at));
+
+        // Timestamps written before this module still load
+        assert_eq!(parse_timestamp("2026-10-16T11:30:00.000000+02:00"), Some(at));
+        assert_eq!(parse_timestamp("yesterday"), None);
+    }
+
+    #[test]
+    fn test_display_uses_a_fixed_numeric_pattern() {
+        let at = Utc.with_ymd_and_hms(2026, 1, 2, 23, 5, 9).unwrap();
+        assert_eq!(display_in(at, &Utc), "2026-01-02 23:05:09");
+        let plus_two = FixedOffset::east_opt(2 * 3600).unwrap();
+        assert_eq!(display_in(at, &plus_two), "2026-01-03 01:05:09");
+        assert_eq!(local_from_str("not a time"), "not a time");
+    }
+}
@@ -0,0 +1,114 @@
+//! Record of the tool calls made during a run.
+//!
+//! The loop writes every tool call and result to [`TOOL_LOG_FILE`] as one JSON
+//! object per line, with the task that was in progress:
+//!
+//! ```text
+//! {"ts":"2026-01-05T10:00:00Z","task_id":"T-001","type":"tool_call","tool":"run_tests","args":{}}
+//! {"ts":"2026-01-05T10:00:04Z","task_id":"T-001","type":"tool_result","tool":"run_tests","response":{"passed":3}}
+//! ```
+//!
+//! The log is started afresh by each run, so it always describes the last one
+//! (like the prompt journal), and is included in exported run archives.
+//! Write failures are logged once and then ignored; they never stop a run.
+
+use adk_rust::Part;
+use std::fs::File;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+
+/// Tool log (relative to the project) of the last run.
+pub const TOOL_LOG_FILE: &str = ".ralph/tool-log.jsonl";
+
+/// Appends a run's tool calls and results to [`TOOL_LOG_FILE`].
+#[derive(Debug)]
+pub struct ToolLog {
+    path: PathBuf,
+    file: Option<File>,
+}
+
+impl ToolLog {
+    /// Start a new tool log in a project, replacing the previous run's.
+    pub fn create(project_path: &Path) -> Self {
+        let path = project_path.join(TOOL_LOG_FILE);
+        let file = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| File::create(&path));
+        let file = match file {
+            Ok(file) => Some(file),
+            Err(e) => {
+                tracing::warn!(path = %path.display(), error = %e, "Could not start the tool log");
+                None
+            }
+        };
+        Self { path, file }
+    }
+
+    /// Record a tool call or result produced while `task_id` was in progress.
+    ///
+    /// Other parts are ignored.
+    pub fn record(&mut self, task_id: Option<&str>, part: &Part) {
+        let entry = match part {
+            Part::FunctionCall { name, args, .. } => serde_json::json!({
+                "ts": crate::timefmt::now(),
+                "task_id": task_id,
+                "type": "tool_call",
+                "tool": name,
+                "args": args,
+            }),
+            Part::FunctionResponse { function_response, .. } => serde_json::json!({
+                "ts": crate::timefmt::now(),
+                "task_id": task_id,
+                "type": "tool_result",
+                "tool": function_response.name,
+                "response": function_response.response,
+            }),
+            _ => return,
+        };
+        let Some(file) = self.file.as_mut() else {
+            return;
+        };
+        let mut line = entry.to_string();
+        line.push('\n');
+        if let Err(e) = file.write_all(line.as_bytes()).and_then(|_| file.flush()) {
+            tracing::warn!(path = %self.path.display(), error = %e, "Could not write the tool log; stopping it");
+            self.file = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records_calls_and_results_per_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let call = Part::FunctionCall {
+            name: "run_tests".to_string(),
+            args: serde_json::json!({ "filter": "parser" }),
+            id: None,
+        };
+        let text = Part::Text { text: "Running the tests".to_string() };
+
+        let mut log = ToolLog::create(dir.path());
+        log.record(Some("T-001"), &call);
+        log.record(Some("T-001"), &text);
+        log.record(None, &call);
+
+        let content = std::fs::read_to_string(dir.path().join(TOOL_LOG_FILE)).unwrap();
+        let entries: Vec<serde_json::Value> =
+            content.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["type"], "tool_call");
+        assert_eq!(entries[0]["task_id"], "T-001");
+        assert_eq!(entries[0]["args"]["filter"], "parser");
+        assert!(entries[1]["task_id"].is_null());
+
+        // The next run starts a new log
+        drop(log);
+        ToolLog::create(dir.path());
+        assert_eq!(std::fs::read_to_string(dir.path().join(TOOL_LOG_FILE)).unwrap(), "");
+    }
+}
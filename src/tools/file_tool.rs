@@ -7,6 +7,11 @@
 //! - Unified `FileTool` with operation-based interface
 //! - Individual tools (`ReadFileTool`, `WriteFileTool`, `ListFilesTool`)
 
+use crate::abort::WriteBackup;
+use crate::edit_guard::EditGuard;
+use crate::scope::FileScope;
+use crate::smoke::SmokeGate;
+use crate::warnings::{Warning, WarningCategory};
 use adk_rust::{Result, Tool, ToolContext};
 use async_trait::async_trait;
 use serde::Deserialize;
@@ -16,15 +21,76 @@ use std::sync::Arc;
 
 /// Unified file tool with operation-based interface.
 ///
-/// Supports operations: read, write, list, delete
+/// Supports operations: read, write, edit, list, delete
 pub struct FileTool {
     project_path: PathBuf,
+    /// Snapshots of files before the current task changed them
+    backup: Option<Arc<WriteBackup>>,
+    /// Per-task file budget
+    scope: Option<Arc<FileScope>>,
+    /// Quick syntax check of written source files
+    smoke: Option<Arc<SmokeGate>>,
+    /// Refuses whole-file rewrites of large files that should be edits
+    edit_guard: Option<EditGuard>,
 }
 
 impl FileTool {
     pub fn new(project_path: impl Into<PathBuf>) -> Self {
         Self {
             project_path: project_path.into(),
+            backup: None,
+            scope: None,
+            smoke: None,
+            edit_guard: None,
+        }
+    }
+
+    /// Snapshot files into `backup` before writing or deleting them.
+    pub fn with_backup(mut self, backup: Arc<WriteBackup>) -> Self {
+        self.backup = Some(backup);
+        self
+    }
+
+    /// Count writes and deletes against the current task's file budget.
+    pub fn with_scope(mut self, scope: Arc<FileScope>) -> Self {
+        self.scope = Some(scope);
+        self
+    }
+
+    /// Syntax-check each written source file in the background, reporting
+    /// errors in the next response.
+    pub fn with_smoke_gate(mut self, smoke: Arc<SmokeGate>) -> Self {
+        self.smoke = Some(smoke);
+        self
+    }
+
+    /// Refuse writes that change little of a large existing file, pointing
+    /// to the `edit` operation instead.
+    pub fn with_edit_guard(mut self, guard: EditGuard) -> Self {
+        self.edit_guard = Some(guard);
+        self
+    }
+
+    /// Attach syntax errors found since the last call, as a notification.
+    fn with_smoke_findings(&self, mut response: Value) -> Value {
+        let Some(smoke) = &self.smoke else {
+            return response;
+        };
+        let findings = smoke.take_findings();
+        if !findings.is_empty() {
+            response["smoke_check"] = json!({
+                "notice": "Syntax errors in files you wrote. Fix them before moving on.",
+                "errors": findings
+            });
+        }
+        response
+    }
+
+    /// Refuse to touch a file beyond the current task's budget.
+    fn admit(&self, rel_path: &str) -> Result<()> {
+        match &self.scope {
+            Some(scope) => scope.admit(rel_path).map_err(adk_rust::AdkError::Tool),
+            None => Ok(()),
         }
     }
 
@@ -74,6 +140,13 @@ impl FileTool {
                     sanitized = %&rel_path[prefix_slash.len()..],
                     "Stripped project folder prefix from path"
                 );
+                crate::warnings::record(
+                    Warning::new(
+                        WarningCategory::FilePath,
+                        format!("Stripped project folder prefix (now {})", &rel_path[prefix_slash.len()..]),
+                    )
+                    .with_file(rel_path),
+                );
                 return rel_path[prefix_slash.len()..].to_string();
             }
         }
@@ -96,7 +169,8 @@ impl Tool for FileTool {
     }
 
     fn description(&self) -> &str {
-        "File operations: read, write, list, delete files in the project directory."
+        "File operations: read, write, edit, list, delete files in the project directory. \
+         Use 'edit' to change part of an existing file."
     }
 
     fn parameters_schema(&self) -> Option<Value> {
@@ -105,7 +179,7 @@ impl Tool for FileTool {
             "properties": {
                 "operation": {
                     "type": "string",
-                    "enum": ["read", "write", "list", "delete"],
+                    "enum": ["read", "write", "edit", "list", "delete"],
                     "description": "The operation to perform"
                 },
                 "path": {
@@ -115,6 +189,14 @@ impl Tool for FileTool {
                 "content": {
                     "type": "string",
                     "description": "Content to write (required for 'write' operation)"
+                },
+                "old_text": {
+                    "type": "string",
+                    "description": "Exact text to replace, occurring once in the file (required for 'edit' operation)"
+                },
+                "new_text": {
+                    "type": "string",
+                    "description": "Replacement text (required for 'edit' operation)"
                 }
             },
             "required": ["operation", "path"]
@@ -127,12 +209,14 @@ impl Tool for FileTool {
             operation: String,
             path: String,
             content: Option<String>,
+            old_text: Option<String>,
+            new_text: Option<String>,
         }
 
         let args: Args = serde_json::from_value(args)
             .map_err(|e| adk_rust::AdkError::Tool(format!("Invalid arguments: {}", e)))?;
 
-        match args.operation.as_str() {
+        let response = match args.operation.as_str() {
             "read" => {
                 let full_path = self.validate_path(&args.path)?;
                 let content = std::fs::read_to_string(&full_path).map_err(|e| {
@@ -152,6 +236,14 @@ impl Tool for FileTool {
 
                 let sanitized_path = self.sanitize_path(&args.path);
                 let full_path = self.project_path.join(&sanitized_path);
+                if let Some(guard) = &self.edit_guard {
+                    let existing = std::fs::read_to_string(&full_path).ok();
+                    if let Some(refusal) = existing.and_then(|old| guard.check(&sanitized_path, &old, &content)) {
+                        tracing::info!(path = %sanitized_path, "Write refused; file should be edited");
+                        return Ok(self.with_smoke_findings(refusal));
+                    }
+                }
+                self.admit(&sanitized_path)?;
 
                 // Log the actual file creation path for debugging
                 tracing::info!(
@@ -173,6 +265,9 @@ impl Tool for FileTool {
                     })?;
                 }
 
+                if let Some(backup) = &self.backup {
+                    backup.record(&full_path);
+                }
                 std::fs::write(&full_path, &content).map_err(|e| {
                     adk_rust::AdkError::Tool(format!("Failed to write file: {}", e))
                 })?;
@@ -182,6 +277,9 @@ impl Tool for FileTool {
                     bytes = content.len(),
                     "File created successfully"
                 );
+                if let Some(smoke) = &self.smoke {
+                    smoke.on_write(&sanitized_path);
+                }
 
                 Ok(json!({
                     "success": true,
@@ -191,6 +289,55 @@ impl Tool for FileTool {
                     "bytes_written": content.len()
                 }))
             }
+            "edit" => {
+                let (Some(old_text), Some(new_text)) = (args.old_text, args.new_text) else {
+                    return Err(adk_rust::AdkError::Tool(
+                        "'old_text' and 'new_text' are required for edit operation".to_string(),
+                    ));
+                };
+                if old_text.is_empty() {
+                    return Err(adk_rust::AdkError::Tool("'old_text' must not be empty".to_string()));
+                }
+
+                let full_path = self.validate_path(&args.path)?;
+                let sanitized_path = self.sanitize_path(&args.path);
+                let content = std::fs::read_to_string(&full_path).map_err(|e| {
+                    adk_rust::AdkError::Tool(format!("Failed to read file: {}", e))
+                })?;
+                let matches = content.matches(old_text.as_str()).count();
+                if matches != 1 {
+                    return Ok(json!({
+                        "success": false,
+                        "operation": "edit",
+                        "path": args.path,
+                        "message": format!(
+                            "'old_text' occurs {} times in the file; it must match exactly once. \
+                             Read the file and include enough surrounding lines to make it unique.",
+                            matches
+                        )
+                    }));
+                }
+                self.admit(&sanitized_path)?;
+
+                if let Some(backup) = &self.backup {
+                    backup.record(&full_path);
+                }
+                let edited = content.replacen(old_text.as_str(), &new_text, 1);
+                std::fs::write(&full_path, &edited).map_err(|e| {
+                    adk_rust::AdkError::Tool(format!("Failed to write file: {}", e))
+                })?;
+                tracing::info!(path = %args.path, bytes = edited.len(), "File edited");
+                if let Some(smoke) = &self.smoke {
+                    smoke.on_write(&sanitized_path);
+                }
+
+                Ok(json!({
+                    "success": true,
+                    "operation": "edit",
+                    "path": args.path,
+                    "bytes_written": edited.len()
+                }))
+            }
             "list" => {
                 let sanitized_path = self.sanitize_path(&args.path);
                 let full_path = self.project_path.join(&sanitized_path);
@@ -224,6 +371,10 @@ impl Tool for FileTool {
                         adk_rust::AdkError::Tool(format!("Failed to delete directory: {}", e))
                     })?;
                 } else {
+                    self.admit(&self.sanitize_path(&args.path))?;
+                    if let Some(backup) = &self.backup {
+                        backup.record(&full_path);
+                    }
                     std::fs::remove_file(&full_path).map_err(|e| {
                         adk_rust::AdkError::Tool(format!("Failed to delete file: {}", e))
                     })?;
@@ -236,10 +387,11 @@ impl Tool for FileTool {
                 }))
             }
             op => Err(adk_rust::AdkError::Tool(format!(
-                "Unknown operation: {}. Use: read, write, list, delete",
+                "Unknown operation: {}. Use: read, write, edit, list, delete",
                 op
             ))),
-        }
+        };
+        response.map(|response| self.with_smoke_findings(response))
     }
 }
 
@@ -1,32 +1,203 @@
 //! Git tool for version control operations.
 //!
-//! Provides git operations: status, add, commit, diff
+//! Provides git operations: status, add, commit, diff, diff_stat
+//!
+//! When a commit is made with a `task_id` and the tool knows where `tasks.json`
+//! lives, the message gets the task's conventional-commit type as a prefix
+//! (`feat: ...`, unless it already has one) and the commit's diff stats are
+//! recorded on that task.
+//!
+//! Commits use the repository's configured identity unless the tool was given
+//! a [`CommitAuthor`], which then becomes both author and committer.
+//!
+//! With [`TaskBranches`], each task is implemented on its own branch
+//! (`ralph/T-003-add-auth`) cut from the base branch, merged back when the
+//! task completes and left in place for inspection when it is blocked.
+//!
+//! With [`TaskRollback`], a blocked task's changes are undone instead: the
+//! project is reset to the commit the task started from, after the work was
+//! saved in a commit of its own.
 
+use crate::models::{DiffStats, MergeStrategy, Task, TaskList};
 use adk_rust::{Result, Tool, ToolContext};
 use async_trait::async_trait;
 use serde::Deserialize;
 use serde_json::{json, Value};
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 use std::process::Command;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 /// Git tool with operation-based interface.
 ///
-/// Supports operations: status, add, commit, diff
+/// Supports operations: status, add, commit, diff, diff_stat
+#[derive(Clone)]
 pub struct GitTool {
     project_path: PathBuf,
+    tasks_path: Option<PathBuf>,
+    author: Option<CommitAuthor>,
+}
+
+/// Identity that commits are attributed to, e.g. `ralph-bot <ralph@company.com>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitAuthor {
+    /// Author name
+    pub name: String,
+    /// Author email
+    pub email: String,
+}
+
+impl CommitAuthor {
+    /// Create an identity from a name and email.
+    pub fn new(name: impl Into<String>, email: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            email: email.into(),
+        }
+    }
+
+    /// Parse `Name <email>`.
+    pub fn parse(s: &str) -> std::result::Result<Self, String> {
+        let invalid = || format!("Invalid commit author '{}': expected 'Name <email>'", s);
+        let (name, rest) = s.trim().split_once('<').ok_or_else(invalid)?;
+        let email = rest.strip_suffix('>').ok_or_else(invalid)?.trim();
+        let name = name.trim();
+        if name.is_empty() || email.is_empty() || email.contains(['<', '>']) {
+            return Err(invalid());
+        }
+        Ok(Self::new(name, email))
+    }
+
+    /// Environment that makes git record this identity as author and committer.
+    fn env(&self) -> [(&'static str, &str); 4] {
+        [
+            ("GIT_AUTHOR_NAME", &self.name),
+            ("GIT_AUTHOR_EMAIL", &self.email),
+            ("GIT_COMMITTER_NAME", &self.name),
+            ("GIT_COMMITTER_EMAIL", &self.email),
+        ]
+    }
+}
+
+impl std::fmt::Display for CommitAuthor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} <{}>", self.name, self.email)
+    }
 }
 
 impl GitTool {
     pub fn new(project_path: impl Into<PathBuf>) -> Self {
         Self {
             project_path: project_path.into(),
+            tasks_path: None,
+            author: None,
+        }
+    }
+
+    /// Set the tasks.json path used to record per-task diff stats.
+    pub fn with_tasks_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.tasks_path = Some(path.into());
+        self
+    }
+
+    /// Attribute commits to `author` instead of the repository's configured identity.
+    pub fn with_author(mut self, author: Option<CommitAuthor>) -> Self {
+        self.author = author;
+        self
+    }
+
+    /// Compute the diff stats of a single commit (e.g. "HEAD").
+    pub fn diff_stats(&self, rev: &str) -> Result<DiffStats> {
+        let output = self.run_git(&["show", "--numstat", "--format=", rev])?;
+        Ok(DiffStats::from_numstat(&output))
+    }
+
+    /// List the most recent commit hashes, newest first.
+    pub fn recent_commits(&self, limit: usize) -> Result<Vec<String>> {
+        let limit = format!("-{}", limit);
+        let output = self.run_git(&["log", &limit, "--format=%H"])?;
+        Ok(output.lines().map(|l| l.trim().to_string()).filter(|l| !l.is_empty()).collect())
+    }
+
+    /// `message` prefixed with the task's conventional-commit type.
+    ///
+    /// Unchanged when tasks.json is unknown or does not list the task.
+    fn conventional_message(&self, task_id: &str, message: &str) -> String {
+        let task_type = self
+            .tasks_path
+            .as_ref()
+            .and_then(|path| TaskList::load(path).ok())
+            .and_then(|list| list.get_task(task_id).map(Task::conventional_type));
+        match task_type {
+            Some(commit_type) => commit_type.apply(message),
+            None => message.to_string(),
+        }
+    }
+
+    /// Store diff stats on a task in tasks.json.
+    fn record_diff_stats(&self, task_id: &str, stats: &DiffStats) -> Result<bool> {
+        let Some(ref tasks_path) = self.tasks_path else {
+            return Ok(false);
+        };
+
+        let mut list = TaskList::load(tasks_path).map_err(adk_rust::AdkError::Tool)?;
+        list.set_diff_stats(task_id, stats.clone())
+            .map_err(adk_rust::AdkError::Tool)?;
+        list.save(tasks_path).map_err(adk_rust::AdkError::Tool)?;
+        Ok(true)
+    }
+
+    /// Commit the staged changes, as the configured author if there is one.
+    fn commit(&self, message: &str) -> Result<String> {
+        self.run_git_as_author(&["commit", "-m", message])
+    }
+
+    /// Run a git command that creates commits, as the configured author if there is one.
+    fn run_git_as_author(&self, args: &[&str]) -> Result<String> {
+        match &self.author {
+            Some(author) => self.run_git_with_env(args, &author.env()),
+            None => self.run_git(args),
         }
     }
 
     fn run_git(&self, args: &[&str]) -> Result<String> {
+        self.run_git_with_env(args, &[])
+    }
+
+    /// Whether `git args` exits successfully, for yes/no queries.
+    fn git_succeeds(&self, args: &[&str]) -> bool {
+        Command::new("git")
+            .args(args)
+            .current_dir(&self.project_path)
+            .output()
+            .is_ok_and(|output| output.status.success())
+    }
+
+    /// The checked-out branch, or `None` on a detached HEAD or outside a repository.
+    pub fn current_branch(&self) -> Option<String> {
+        let name = self.run_git(&["symbolic-ref", "--short", "-q", "HEAD"]).ok()?;
+        Some(name.trim().to_string()).filter(|name| !name.is_empty())
+    }
+
+    /// Whether a local branch named `name` exists.
+    fn branch_exists(&self, name: &str) -> bool {
+        let reference = format!("refs/heads/{}", name);
+        self.git_succeeds(&["rev-parse", "--verify", "-q", reference.as_str()])
+    }
+
+    /// Stage and commit everything in the working tree; false when it was clean.
+    fn commit_all(&self, message: &str) -> Result<bool> {
+        if self.run_git(&["status", "--porcelain"])?.trim().is_empty() {
+            return Ok(false);
+        }
+        self.run_git(&["add", "-A"])?;
+        self.commit(message)?;
+        Ok(true)
+    }
+
+    fn run_git_with_env(&self, args: &[&str], env: &[(&str, &str)]) -> Result<String> {
         let output = Command::new("git")
             .args(args)
+            .envs(env.iter().copied())
             .current_dir(&self.project_path)
             .output()
             .map_err(|e| adk_rust::AdkError::Tool(format!("Failed to run git: {}", e)))?;
@@ -49,10 +220,488 @@ impl std::fmt::Debug for GitTool {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("GitTool")
             .field("project_path", &self.project_path)
+            .field("tasks_path", &self.tasks_path)
+            .field("author", &self.author)
+            .finish()
+    }
+}
+
+/// Prefix of every task branch.
+pub const TASK_BRANCH_PREFIX: &str = "ralph/";
+
+/// Git config key remembering the base branch across runs.
+const BASE_BRANCH_KEY: &str = "ralph.baseBranch";
+
+/// Branch name for a task: the prefix, its ID and its title in kebab case,
+/// e.g. `ralph/T-003-add-auth`.
+pub fn task_branch_name(task: &Task) -> String {
+    let mut slug = String::new();
+    for word in task
+        .title
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .take(6)
+    {
+        slug.push('-');
+        slug.push_str(&word.to_ascii_lowercase());
+    }
+    let id: String = task
+        .id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+        .collect();
+    format!("{}{}{}", TASK_BRANCH_PREFIX, id, slug)
+}
+
+/// One branch per task, cut from and merged back into a base branch.
+///
+/// The base is the branch checked out when the first task starts; it is
+/// remembered in the repository's git config so a resumed run finds it even
+/// when it starts on a task branch. Work left uncommitted when leaving a task
+/// branch is committed on it first, so a blocked task's branch holds all of
+/// its work.
+///
+/// Files listed with [`TaskBranches::carrying`] (tasks.json, progress.json)
+/// keep their working-tree content across every switch and merge, so run
+/// state is never rolled back to another branch's copy.
+pub struct TaskBranches {
+    git: GitTool,
+    merge: MergeStrategy,
+    carried: Vec<PathBuf>,
+    base: Mutex<Option<String>>,
+}
+
+impl TaskBranches {
+    /// Branch with `git`'s repository and author, merging with `merge`.
+    pub fn new(git: GitTool, merge: MergeStrategy) -> Self {
+        Self {
+            git,
+            merge,
+            carried: Vec::new(),
+            base: Mutex::new(None),
+        }
+    }
+
+    /// Keep the working-tree content of `paths` across branch switches.
+    pub fn carrying(mut self, paths: impl IntoIterator<Item = PathBuf>) -> Self {
+        self.carried.extend(paths);
+        self
+    }
+
+    /// The base branch, once known.
+    pub fn base(&self) -> Option<String> {
+        self.base.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Switch to the branch for `task`, creating it from the base branch.
+    ///
+    /// A branch recorded on the task that still exists is checked out again
+    /// (a retried or resumed task continues its work); a name taken by
+    /// something else gets a numeric suffix. Returns `None` when the project
+    /// cannot branch yet: not a repository, no commits, or a detached HEAD.
+    pub fn start(&self, task: &Task) -> std::result::Result<Option<String>, String> {
+        if !self.git.git_succeeds(&["rev-parse", "--verify", "-q", "HEAD"]) {
+            return Ok(None);
+        }
+        let Some(base) = self.resolve_base()? else {
+            return Ok(None);
+        };
+
+        let current = self.git.current_branch();
+        let resume = task.branch.clone().filter(|name| self.git.branch_exists(name));
+        if current.is_some() && current == resume {
+            return Ok(resume);
+        }
+        self.carry(|| {
+            if let Some(current) = current.as_deref().filter(|c| *c != base) {
+                self.git
+                    .commit_all(&format!("wip: unfinished work on {}", current))
+                    .map_err(|e| e.to_string())?;
+                self.switch(&[base.as_str()])?;
+            }
+            let name = match resume {
+                Some(name) => {
+                    self.switch(&[name.as_str()])?;
+                    name
+                }
+                None => {
+                    let name = self.free_name(&task_branch_name(task));
+                    self.switch(&["-c", name.as_str()])?;
+                    name
+                }
+            };
+            Ok(Some(name))
+        })
+    }
+
+    /// Commit what is left on `branch`, merge it into the base branch and
+    /// switch back to the base.
+    ///
+    /// On a conflict the merge is undone, the base is left as it was and the
+    /// branch is kept; the error says so.
+    pub fn finish(&self, task: &Task, branch: &str) -> std::result::Result<(), String> {
+        let base = self.base().ok_or("no base branch recorded")?;
+        self.carry(|| {
+            self.leave(&task.id, branch, &base)?;
+            match self.merge {
+                MergeStrategy::FastForward => {
+                    if self.git.run_git(&["merge", "--ff-only", "-q", branch]).is_err() {
+                        if let Err(e) = self.git.run_git_as_author(&["merge", "--no-edit", "-q", branch]) {
+                            let _ = self.git.run_git(&["merge", "--abort"]);
+                            return Err(format!("could not merge {} into {}: {}", branch, base, e));
+                        }
+                    }
+                }
+                MergeStrategy::Squash => {
+                    if let Err(e) = self.git.run_git(&["merge", "--squash", "-q", branch]) {
+                        let _ = self.git.run_git(&["reset", "--merge"]);
+                        return Err(format!("could not squash {} into {}: {}", branch, base, e));
+                    }
+                    if !self.git.git_succeeds(&["diff", "--cached", "--quiet"]) {
+                        let message = task
+                            .conventional_type()
+                            .apply(&format!("{} ({})", task.title, task.id));
+                        self.git.commit(&message).map_err(|e| e.to_string())?;
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Commit what is left on a blocked task's `branch` and switch back to
+    /// the base branch, keeping the branch for inspection.
+    pub fn abandon(&self, task_id: &str, branch: &str) -> std::result::Result<(), String> {
+        let base = self.base().ok_or("no base branch recorded")?;
+        self.carry(|| self.leave(task_id, branch, &base))
+    }
+
+    /// Commit leftovers on `branch` if it is checked out, then switch to `base`.
+    fn leave(&self, task_id: &str, branch: &str, base: &str) -> std::result::Result<(), String> {
+        if self.git.current_branch().as_deref() == Some(branch) {
+            let message = self.git.conventional_message(task_id, &format!("remaining changes of {}", task_id));
+            self.git.commit_all(&message).map_err(|e| e.to_string())?;
+        }
+        if self.git.current_branch().as_deref() != Some(base) {
+            self.switch(&[base])?;
+        }
+        Ok(())
+    }
+
+    /// The base branch: remembered, or the current branch unless that is a task branch.
+    fn resolve_base(&self) -> std::result::Result<Option<String>, String> {
+        let mut base = self.base.lock().unwrap_or_else(|e| e.into_inner());
+        if base.is_none() {
+            let current = self.git.current_branch();
+            *base = match current {
+                Some(current) if !current.starts_with(TASK_BRANCH_PREFIX) => {
+                    self.git
+                        .run_git(&["config", BASE_BRANCH_KEY, current.as_str()])
+                        .map_err(|e| e.to_string())?;
+                    Some(current)
+                }
+                _ => self
+                    .git
+                    .run_git(&["config", "--get", BASE_BRANCH_KEY])
+                    .ok()
+                    .map(|name| name.trim().to_string())
+                    .filter(|name| self.git.branch_exists(name)),
+            };
+        }
+        Ok(base.clone())
+    }
+
+    /// `name`, or `name-2`, `name-3`, ... when a branch already has it.
+    fn free_name(&self, name: &str) -> String {
+        let mut candidate = name.to_string();
+        let mut n = 2;
+        while self.git.branch_exists(&candidate) {
+            candidate = format!("{}-{}", name, n);
+            n += 1;
+        }
+        candidate
+    }
+
+    fn switch(&self, args: &[&str]) -> std::result::Result<(), String> {
+        let mut switch = vec!["switch", "-q"];
+        switch.extend_from_slice(args);
+        self.git.run_git(&switch).map(drop).map_err(|e| e.to_string())
+    }
+
+    /// Run `step` keeping the carried files' content (see [`carry`]).
+    fn carry<T>(
+        &self,
+        step: impl FnOnce() -> std::result::Result<T, String>,
+    ) -> std::result::Result<T, String> {
+        carry(&self.git, &self.carried, step)
+    }
+}
+
+impl std::fmt::Debug for TaskBranches {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TaskBranches")
+            .field("project_path", &self.git.project_path)
+            .field("merge", &self.merge)
+            .field("base", &self.base())
+            .finish()
+    }
+}
+
+/// Prefix of the refs keeping rolled-back work, followed by the task ID.
+pub const ROLLED_BACK_REF_PREFIX: &str = "refs/ralph/rolled-back/";
+
+/// Undoes the changes of tasks that end up blocked, so the next task does
+/// not start on top of broken code.
+///
+/// When a task starts, the current HEAD is recorded on it
+/// (`start_commit`). Rolling it back first saves its work in a commit,
+/// recorded in `rolled_back` and kept at `refs/ralph/rolled-back/<id>`: the
+/// commits made during the task, the changes to tracked files, and the
+/// untracked files the task declared in `files_created`. The project is then
+/// reset to the start commit and those declared files are deleted.
+///
+/// No other untracked file is touched, and files staged but never committed
+/// are left as untracked files. A declared file is only deleted when
+/// it is inside the project, not a directory, and not tracked at the start
+/// commit; even then its content is in the saved commit.
+///
+/// Files listed with [`TaskRollback::carrying`] (tasks.json, progress.json)
+/// keep their working-tree content across the reset.
+pub struct TaskRollback {
+    git: GitTool,
+    carried: Vec<PathBuf>,
+}
+
+impl TaskRollback {
+    /// Roll back in `git`'s repository, committing as its author.
+    pub fn new(git: GitTool) -> Self {
+        Self {
+            git,
+            carried: Vec::new(),
+        }
+    }
+
+    /// Keep the working-tree content of `paths` across a rollback.
+    pub fn carrying(mut self, paths: impl IntoIterator<Item = PathBuf>) -> Self {
+        self.carried.extend(paths);
+        self
+    }
+
+    /// Record the current HEAD on a task that is starting, unless an earlier
+    /// attempt already did.
+    pub fn begin(&self, task: &mut Task) {
+        if task.start_commit.is_none() {
+            task.start_commit = self.head();
+        }
+    }
+
+    /// Roll back a blocked task to its start commit, noting the outcome on it.
+    ///
+    /// A task without a start commit (never started with rollback on) is left
+    /// alone. If anything fails the work stays in place and the error is noted.
+    pub fn apply(&self, task: &mut Task) {
+        let Some(start) = task.start_commit.clone() else {
+            return;
+        };
+        match self.roll_back(task, &start) {
+            Ok(saved) => {
+                let short = &start[..start.len().min(7)];
+                match &saved {
+                    Some(commit) => task.add_note(&format!(
+                        "Rollback: reset to {}; the task's work is in {}{} ({})",
+                        short,
+                        ROLLED_BACK_REF_PREFIX,
+                        task.id,
+                        &commit[..commit.len().min(7)]
+                    )),
+                    None => task.add_note(&format!("Rollback: nothing to undo since {}", short)),
+                }
+                task.rolled_back = saved;
+                task.start_commit = None;
+            }
+            Err(e) => task.add_note(&format!("Rollback: could not roll back, work left in place: {}", e)),
+        }
+    }
+
+    /// Save the task's work, reset to `start` and delete the task's untracked
+    /// files. Returns the commit holding the work, or `None` when there was none.
+    pub fn roll_back(&self, task: &Task, start: &str) -> std::result::Result<Option<String>, String> {
+        let target = format!("{}^{{commit}}", start);
+        if !self.git.git_succeeds(&["rev-parse", "--verify", "-q", target.as_str()]) {
+            return Err(format!("start commit {} not found", start));
+        }
+        let created = self.created_files(task);
+        carry(&self.git, &self.carried, || {
+            let saved = self.save_work(task, start, &created)?;
+            if let Some(commit) = &saved {
+                let reference = format!("{}{}", ROLLED_BACK_REF_PREFIX, task.id);
+                self.git
+                    .run_git(&["update-ref", reference.as_str(), commit.as_str()])
+                    .map_err(|e| e.to_string())?;
+            }
+            // Unstage first, so files the user staged but never committed
+            // stay in place as untracked files
+            self.git.run_git(&["reset", "-q"]).map_err(|e| e.to_string())?;
+            self.git
+                .run_git(&["reset", "-q", "--hard", start])
+                .map_err(|e| e.to_string())?;
+            for path in &created {
+                if !self.is_tracked(path) {
+                    if let Err(e) = std::fs::remove_file(self.git.project_path.join(path)) {
+                        tracing::warn!(path = %path, error = %e, "Could not delete a rolled-back task's file");
+                    }
+                }
+            }
+            Ok(saved)
+        })
+    }
+
+    /// Commit the staged and tracked changes and `created` on top of HEAD,
+    /// through a copy of the index so the user's staging is untouched.
+    ///
+    /// Returns `None` when HEAD is still `start` and nothing changed.
+    fn save_work(
+        &self,
+        task: &Task,
+        start: &str,
+        created: &[String],
+    ) -> std::result::Result<Option<String>, String> {
+        let head = self.head().ok_or("no commits to roll back to")?;
+        let start = self.output(&["rev-parse", start], &[])?;
+        let index = self
+            .git
+            .project_path
+            .join(self.output(&["rev-parse", "--git-path", "ralph-rollback.index"], &[])?);
+        let tree = self.work_tree(&index, created);
+        let _ = std::fs::remove_file(&index);
+        let tree = tree?;
+
+        if tree == self.output(&["rev-parse", "HEAD^{tree}"], &[])? {
+            return Ok((head != start).then_some(head));
+        }
+        let message = format!("wip: {} ({}) before rollback", task.title, task.id);
+        let commit = self
+            .git
+            .run_git_as_author(&["commit-tree", tree.as_str(), "-p", head.as_str(), "-m", message.as_str()])
+            .map_err(|e| e.to_string())?;
+        Ok(Some(commit.trim().to_string()))
+    }
+
+    /// Write a tree of the index plus the tracked changes and `created`,
+    /// staged in `index`, a copy of the repository's index.
+    fn work_tree(&self, index: &Path, created: &[String]) -> std::result::Result<String, String> {
+        let current = self.output(&["rev-parse", "--git-path", "index"], &[])?;
+        let copied = std::fs::copy(self.git.project_path.join(current), index).is_ok();
+        let index = index.to_string_lossy();
+        let env = [("GIT_INDEX_FILE", index.as_ref())];
+        if !copied {
+            self.output(&["read-tree", "HEAD"], &env)?;
+        }
+        self.output(&["add", "-u"], &env)?;
+        for path in created {
+            let spec = literal(path);
+            self.output(&["add", "-f", "--", spec.as_str()], &env)?;
+        }
+        self.output(&["write-tree"], &env)
+    }
+
+    /// Trimmed output of `git args` run with `env`.
+    fn output(&self, args: &[&str], env: &[(&str, &str)]) -> std::result::Result<String, String> {
+        self.git
+            .run_git_with_env(args, env)
+            .map(|out| out.trim().to_string())
+            .map_err(|e| e.to_string())
+    }
+
+    /// The task's declared files that exist in the project as plain files
+    /// (or symlinks), as paths relative to the project.
+    fn created_files(&self, task: &Task) -> Vec<String> {
+        let root = self.git.project_path.canonicalize().ok();
+        task.files_created
+            .iter()
+            .filter(|path| inside_project(path))
+            .filter(|path| {
+                let full = self.git.project_path.join(path);
+                let is_file = std::fs::symlink_metadata(&full).is_ok_and(|meta| !meta.is_dir());
+                // A symlinked directory on the way could lead out of the project
+                let parent_inside = full
+                    .parent()
+                    .and_then(|parent| parent.canonicalize().ok())
+                    .is_some_and(|parent| root.as_ref().is_some_and(|root| parent.starts_with(root)));
+                is_file && parent_inside
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Whether `path` is in the index, i.e. tracked at the current HEAD.
+    fn is_tracked(&self, path: &str) -> bool {
+        let spec = literal(path);
+        self.git
+            .git_succeeds(&["ls-files", "--error-unmatch", "--", spec.as_str()])
+    }
+
+    fn head(&self) -> Option<String> {
+        let head = self.git.run_git(&["rev-parse", "--verify", "-q", "HEAD"]).ok()?;
+        Some(head.trim().to_string()).filter(|head| !head.is_empty())
+    }
+}
+
+impl std::fmt::Debug for TaskRollback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TaskRollback")
+            .field("project_path", &self.git.project_path)
+            .field("carried", &self.carried)
             .finish()
     }
 }
 
+/// A relative path that stays inside the project: no root, no `..`.
+fn inside_project(path: &str) -> bool {
+    let path = Path::new(path);
+    path.components().next().is_some()
+        && path
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+}
+
+/// `path` as a pathspec matched literally, never as a glob.
+fn literal(path: &str) -> String {
+    format!(":(literal){}", path)
+}
+
+/// Run `step` with the `carried` files' current content restored afterwards,
+/// whether or not it succeeded.
+///
+/// The files are reset to their committed version (or removed, when
+/// untracked) first, so their changes never block a switch, merge or reset.
+fn carry<T>(
+    git: &GitTool,
+    carried: &[PathBuf],
+    step: impl FnOnce() -> std::result::Result<T, String>,
+) -> std::result::Result<T, String> {
+    let saved: Vec<(&PathBuf, Vec<u8>)> = carried
+        .iter()
+        .filter_map(|path| std::fs::read(path).ok().map(|content| (path, content)))
+        .collect();
+    for (path, _) in &saved {
+        let path = path.to_string_lossy();
+        if !git.git_succeeds(&["checkout", "-q", "HEAD", "--", path.as_ref()]) {
+            git.git_succeeds(&["rm", "-q", "--cached", "--ignore-unmatch", "--", path.as_ref()]);
+            let _ = std::fs::remove_file(path.as_ref());
+        }
+    }
+    let result = step();
+    for (path, content) in saved {
+        if std::fs::read(path).ok().as_deref() != Some(content.as_slice()) {
+            if let Err(e) = crate::persist::write_atomic(path, &content) {
+                tracing::warn!(path = %path.display(), error = %e, "Could not restore a carried file");
+            }
+        }
+    }
+    result
+}
+
 #[async_trait]
 impl Tool for GitTool {
     fn name(&self) -> &str {
@@ -60,7 +709,8 @@ impl Tool for GitTool {
     }
 
     fn description(&self) -> &str {
-        "Git operations: status, add, commit, diff for version control."
+        "Git operations: status, add, commit, diff, diff_stat for version control. \
+         Pass 'task_id' to 'commit' to record the commit's diff stats on that task."
     }
 
     fn parameters_schema(&self) -> Option<Value> {
@@ -69,7 +719,7 @@ impl Tool for GitTool {
             "properties": {
                 "operation": {
                     "type": "string",
-                    "enum": ["status", "add", "commit", "diff"],
+                    "enum": ["status", "add", "commit", "diff", "diff_stat"],
                     "description": "The git operation to perform"
                 },
                 "files": {
@@ -84,6 +734,14 @@ impl Tool for GitTool {
                 "path": {
                     "type": "string",
                     "description": "Path to diff (optional for 'diff' operation)"
+                },
+                "task_id": {
+                    "type": "string",
+                    "description": "Task the commit belongs to (optional for 'commit' and 'diff_stat')"
+                },
+                "rev": {
+                    "type": "string",
+                    "description": "Commit to measure (optional for 'diff_stat', defaults to HEAD)"
                 }
             },
             "required": ["operation"]
@@ -97,6 +755,8 @@ impl Tool for GitTool {
             files: Option<Vec<String>>,
             message: Option<String>,
             path: Option<String>,
+            task_id: Option<String>,
+            rev: Option<String>,
         }
 
         let args: Args = serde_json::from_value(args)
@@ -147,7 +807,11 @@ impl Tool for GitTool {
                     )
                 })?;
 
-                let output = self.run_git(&["commit", "-m", &message])?;
+                let message = match args.task_id {
+                    Some(ref task_id) => self.conventional_message(task_id, &message),
+                    None => message,
+                };
+                let output = self.commit(&message)?;
 
                 // Extract commit hash from output
                 let commit_hash = output
@@ -159,11 +823,22 @@ impl Tool for GitTool {
                     })
                     .map(|s| s.to_string());
 
+                // The commit already succeeded, so stats are best-effort
+                let diff_stats = self.diff_stats("HEAD").ok();
+                let recorded = match (&args.task_id, &diff_stats) {
+                    (Some(task_id), Some(stats)) => {
+                        self.record_diff_stats(task_id, stats).unwrap_or(false)
+                    }
+                    _ => false,
+                };
+
                 Ok(json!({
                     "success": true,
                     "operation": "commit",
                     "message": message,
                     "commit_hash": commit_hash,
+                    "diff_stats": diff_stats,
+                    "recorded_on_task": recorded,
                     "output": output.trim()
                 }))
             }
@@ -182,8 +857,25 @@ impl Tool for GitTool {
                     "diff": output
                 }))
             }
+            "diff_stat" => {
+                let rev = args.rev.as_deref().unwrap_or("HEAD");
+                let diff_stats = self.diff_stats(rev)?;
+                let recorded = match args.task_id {
+                    Some(ref task_id) => self.record_diff_stats(task_id, &diff_stats)?,
+                    None => false,
+                };
+
+                Ok(json!({
+                    "success": true,
+                    "operation": "diff_stat",
+                    "rev": rev,
+                    "diff_stats": diff_stats,
+                    "summary": diff_stats.to_string(),
+                    "recorded_on_task": recorded
+                }))
+            }
             op => Err(adk_rust::AdkError::Tool(format!(
-                "Unknown operation: {}. Use: status, add, commit, diff",
+                "Unknown operation: {}. Use: status, add, commit, diff, diff_stat",
                 op
             ))),
         }
@@ -200,5 +892,288 @@ mod tests {
         assert_eq!(tool.name(), "git");
         assert!(tool.description().contains("status"));
         assert!(tool.description().contains("commit"));
+        assert!(tool.description().contains("diff_stat"));
+    }
+
+    #[test]
+    fn test_commit_message_gets_task_type() {
+        let dir = tempfile::tempdir().unwrap();
+        let tasks_path = dir.path().join("tasks.json");
+        let mut list = TaskList::new("Test", "rust");
+        let mut task = Task::new("TASK-001", "Fix overflow in parser", "", 1);
+        task.files_modified.push("src/parser.rs".to_string());
+        list.add_task(task);
+        let mut release = Task::new("TASK-002", "Add parser", "", 1);
+        release.commit_type = Some(crate::models::CommitType::Chore);
+        list.add_task(release);
+        list.save(&tasks_path).unwrap();
+
+        let tool = GitTool::new(dir.path()).with_tasks_path(&tasks_path);
+        assert_eq!(tool.conventional_message("TASK-001", "Guard add"), "fix: Guard add");
+        assert_eq!(tool.conventional_message("TASK-002", "Bump"), "chore: Bump");
+        assert_eq!(tool.conventional_message("TASK-001", "feat(cli): x"), "feat(cli): x");
+        assert_eq!(tool.conventional_message("TASK-404", "Guard add"), "Guard add");
+    }
+
+    #[test]
+    fn test_diff_stats_recorded_on_task() {
+        let dir = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(dir.path())
+                .output()
+                .unwrap()
+        };
+        git(&["init", "-q"]);
+        git(&["config", "user.email", "ralph@example.com"]);
+        git(&["config", "user.name", "Ralph"]);
+
+        let tasks_path = dir.path().join("tasks.json");
+        let mut list = TaskList::new("Test", "rust");
+        list.add_task(Task::new("TASK-001", "First", "Desc", 1));
+        list.save(&tasks_path).unwrap();
+
+        std::fs::write(dir.path().join("main.rs"), "fn main() {}\n// one\n").unwrap();
+        git(&["add", "main.rs"]);
+        git(&["commit", "-q", "-m", "init"]);
+
+        let tool = GitTool::new(dir.path()).with_tasks_path(&tasks_path);
+        let stats = tool.diff_stats("HEAD").unwrap();
+        assert_eq!(stats.added, 2);
+        assert_eq!(stats.files, vec!["main.rs"]);
+
+        assert!(tool.record_diff_stats("TASK-001", &stats).unwrap());
+        let list = TaskList::load(&tasks_path).unwrap();
+        assert_eq!(list.get_task("TASK-001").unwrap().diff_stats, Some(stats));
+    }
+
+    #[test]
+    fn test_commit_author_parse() {
+        let author = CommitAuthor::parse(" ralph-bot <ralph@company.com> ").unwrap();
+        assert_eq!(author, CommitAuthor::new("ralph-bot", "ralph@company.com"));
+        assert_eq!(author.to_string(), "ralph-bot <ralph@company.com>");
+        let invalid_authors = ["ralph-bot", "<ralph@company.com>", "ralph-bot <>", "bot <a@b.c", "a <b> <c>"];
+        for invalid in invalid_authors {
+            assert!(CommitAuthor::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_commit_uses_configured_author() {
+        let dir = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            let output = Command::new("git").args(args).current_dir(dir.path()).output().unwrap();
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        };
+        git(&["init", "-q"]);
+        git(&["config", "user.email", "dev@example.com"]);
+        git(&["config", "user.name", "Dev"]);
+
+        let last_identity = || git(&["log", "-1", "--format=%an <%ae> / %cn <%ce>"]);
+        std::fs::write(dir.path().join("a.txt"), "a").unwrap();
+        git(&["add", "a.txt"]);
+        GitTool::new(dir.path()).commit("Default identity").unwrap();
+        assert_eq!(last_identity(), "Dev <dev@example.com> / Dev <dev@example.com>");
+
+        std::fs::write(dir.path().join("b.txt"), "b").unwrap();
+        git(&["add", "b.txt"]);
+        let bot = CommitAuthor::new("ralph-bot", "ralph@company.com");
+        GitTool::new(dir.path()).with_author(Some(bot)).commit("Bot identity").unwrap();
+        assert_eq!(
+            last_identity(),
+            "ralph-bot <ralph@company.com> / ralph-bot <ralph@company.com>"
+        );
+    }
+
+    #[test]
+    fn test_task_branch_names() {
+        let task = Task::new("T-003", "Add auth: JWT & sessions!", "", 1);
+        assert_eq!(task_branch_name(&task), "ralph/T-003-add-auth-jwt-sessions");
+        let task = Task::new("T 4", "Écrire le résumé", "", 1);
+        assert_eq!(task_branch_name(&task), "ralph/T-4-crire-le-r-sum");
+    }
+
+    #[test]
+    fn test_task_branches_merge_completed_and_keep_blocked_work() {
+        let dir = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            let output = Command::new("git").args(args).current_dir(dir.path()).output().unwrap();
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        };
+        git(&["init", "-q", "-b", "main"]);
+        git(&["config", "user.email", "ralph@example.com"]);
+        git(&["config", "user.name", "Ralph"]);
+        let tasks_path = dir.path().join("tasks.json");
+        std::fs::write(&tasks_path, "v1").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "init"]);
+
+        let branches = TaskBranches::new(GitTool::new(dir.path()), MergeStrategy::FastForward)
+            .carrying([tasks_path.clone()]);
+        let mut done = Task::new("T-001", "Add auth", "", 1);
+        let branch = branches.start(&done).unwrap().unwrap();
+        assert_eq!(branch, "ralph/T-001-add-auth");
+        assert_eq!(branches.base().as_deref(), Some("main"));
+        std::fs::write(dir.path().join("auth.rs"), "// auth").unwrap();
+        std::fs::write(&tasks_path, "v2").unwrap();
+        done.status = crate::models::TaskStatus::Completed;
+        branches.finish(&done, &branch).unwrap();
+        assert_eq!(git(&["branch", "--show-current"]), "main");
+        assert!(dir.path().join("auth.rs").exists());
+
+        // A blocked task's uncommitted work stays on its branch only
+        let mut blocked = Task::new("T-002", "Add billing", "", 1);
+        let branch = branches.start(&blocked).unwrap().unwrap();
+        std::fs::write(dir.path().join("billing.rs"), "// billing").unwrap();
+        std::fs::write(&tasks_path, "v3").unwrap();
+        branches.abandon(&blocked.id, &branch).unwrap();
+        assert_eq!(git(&["branch", "--show-current"]), "main");
+        assert!(!dir.path().join("billing.rs").exists());
+        assert_eq!(git(&["show", "ralph/T-002-add-billing:billing.rs"]), "// billing");
+        // Run state is never rolled back by a switch
+        assert_eq!(std::fs::read_to_string(&tasks_path).unwrap(), "v3");
+
+        // Retrying reuses the recorded branch; a taken name gets a suffix
+        blocked.branch = Some(branch.clone());
+        assert_eq!(branches.start(&blocked).unwrap().as_deref(), Some(branch.as_str()));
+        branches.abandon(&blocked.id, &branch).unwrap();
+        assert_eq!(
+            branches.start(&Task::new("T-002", "Add billing", "", 1)).unwrap().as_deref(),
+            Some("ralph/T-002-add-billing-2")
+        );
+    }
+
+    #[test]
+    fn test_task_branches_squash_and_skip_without_commits() {
+        let dir = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            let output = Command::new("git").args(args).current_dir(dir.path()).output().unwrap();
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        };
+        git(&["init", "-q", "-b", "main"]);
+        git(&["config", "user.email", "ralph@example.com"]);
+        git(&["config", "user.name", "Ralph"]);
+        let branches = TaskBranches::new(GitTool::new(dir.path()), MergeStrategy::Squash);
+        let task = Task::new("T-001", "Add parser", "", 1);
+        // Nothing to branch from before the first commit
+        assert_eq!(branches.start(&task).unwrap(), None);
+
+        std::fs::write(dir.path().join("README.md"), "hi").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "init"]);
+        let branch = branches.start(&task).unwrap().unwrap();
+        for n in 0..3 {
+            std::fs::write(dir.path().join(format!("p{}.rs", n)), "//").unwrap();
+            git(&["add", "."]);
+            git(&["commit", "-q", "-m", &format!("step {}", n)]);
+        }
+        branches.finish(&task, &branch).unwrap();
+        assert_eq!(git(&["log", "--format=%s", "main"]), "feat: Add parser (T-001)\ninit");
+    }
+
+    #[test]
+    fn test_task_rollback_deletes_only_declared_untracked_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            let output = Command::new("git").args(args).current_dir(dir.path()).output().unwrap();
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        };
+        let write = |path: &str, content: &str| {
+            let path = dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        git(&["init", "-q", "-b", "main"]);
+        git(&["config", "user.email", "ralph@example.com"]);
+        git(&["config", "user.name", "Ralph"]);
+        let tasks_path = dir.path().join("tasks.json");
+        write("tasks.json", "v1");
+        write("src/lib.rs", "pub fn old() {}");
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "init"]);
+        let start = git(&["rev-parse", "HEAD"]);
+
+        // The user's own files, some named by the task's file list
+        write("notes.txt", "mine");
+        std::fs::write(outside.path().join("keep.txt"), "outside").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(outside.path(), dir.path().join("linked")).unwrap();
+
+        let rollback = TaskRollback::new(GitTool::new(dir.path())).carrying([tasks_path.clone()]);
+        let mut task = Task::new("T-001", "Add parser", "", 1);
+        let outside_file = outside.path().join("keep.txt").to_string_lossy().to_string();
+        task.files_created = ["src/parser.rs", "src/lib.rs", "src", "../keep.txt", "linked/keep.txt"]
+            .iter()
+            .map(|p| p.to_string())
+            .chain([outside_file])
+            .collect();
+        rollback.begin(&mut task);
+        assert_eq!(task.start_commit.as_deref(), Some(start.as_str()));
+
+        // A commit during the task, then uncommitted work
+        write("src/committed.rs", "// committed");
+        git(&["add", "src/committed.rs"]);
+        git(&["commit", "-q", "-m", "wip"]);
+        // The user stages a file of their own
+        write("staged.txt", "staged");
+        git(&["add", "staged.txt"]);
+        rollback.begin(&mut task);
+        assert_eq!(task.start_commit.as_deref(), Some(start.as_str()));
+        write("src/lib.rs", "pub fn broken(");
+        write("src/parser.rs", "// parser");
+        write("tasks.json", "v2");
+
+        rollback.apply(&mut task);
+        let saved = task.rolled_back.clone().unwrap();
+        let at = |path: &str| format!("{}:{}", saved, path);
+        assert_eq!(task.start_commit, None);
+        assert!(task.notes.contains("refs/ralph/rolled-back/T-001"), "{}", task.notes);
+        assert_eq!(git(&["rev-parse", "HEAD"]), start);
+        assert_eq!(git(&["rev-parse", "refs/ralph/rolled-back/T-001"]), saved);
+        assert_eq!(git(&["show", at("src/parser.rs").as_str()]), "// parser");
+        assert_eq!(git(&["show", at("src/lib.rs").as_str()]), "pub fn broken(");
+        assert_eq!(git(&["show", at("src/committed.rs").as_str()]), "// committed");
+
+        // Tracked work is reset and the declared new file deleted
+        assert_eq!(std::fs::read_to_string(dir.path().join("src/lib.rs")).unwrap(), "pub fn old() {}");
+        assert!(!dir.path().join("src/parser.rs").exists());
+        assert!(!dir.path().join("src/committed.rs").exists());
+        // Nothing else is touched, and run state is kept
+        assert_eq!(std::fs::read_to_string(dir.path().join("notes.txt")).unwrap(), "mine");
+        assert_eq!(std::fs::read_to_string(dir.path().join("staged.txt")).unwrap(), "staged");
+        assert_eq!(std::fs::read_to_string(outside.path().join("keep.txt")).unwrap(), "outside");
+        assert_eq!(std::fs::read_to_string(&tasks_path).unwrap(), "v2");
+        assert_eq!(git(&["status", "--porcelain", "--untracked-files=no"]), "M tasks.json");
+    }
+
+    #[test]
+    fn test_task_rollback_without_work_or_start_commit() {
+        let dir = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            Command::new("git").args(args).current_dir(dir.path()).output().unwrap();
+        };
+        git(&["init", "-q", "-b", "main"]);
+        git(&["config", "user.email", "ralph@example.com"]);
+        git(&["config", "user.name", "Ralph"]);
+        std::fs::write(dir.path().join("README.md"), "hi").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "init"]);
+
+        let rollback = TaskRollback::new(GitTool::new(dir.path()));
+        // Started before rollback was enabled: left alone
+        let mut task = Task::new("T-001", "Add parser", "", 1);
+        rollback.apply(&mut task);
+        assert!(task.notes.is_empty());
+
+        rollback.begin(&mut task);
+        rollback.apply(&mut task);
+        assert_eq!(task.rolled_back, None);
+        assert!(task.notes.starts_with("Rollback: nothing to undo"), "{}", task.notes);
+
+        task.start_commit = Some("0123456789abcdef0123456789abcdef01234567".to_string());
+        rollback.apply(&mut task);
+        assert!(task.notes.contains("could not roll back"), "{}", task.notes);
     }
 }
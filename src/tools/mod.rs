@@ -27,7 +27,7 @@ pub mod web_search_tool;
 
 // Unified tools with operation-based interface
 pub use file_tool::FileTool;
-pub use git_tool::GitTool;
+pub use git_tool::{CommitAuthor, GitTool, TaskBranches, TaskRollback, task_branch_name};
 
 // Individual file tools (legacy)
 pub use file_tool::{ListFilesTool, ReadFileTool, WriteFileTool};
@@ -35,11 +35,12 @@ pub use file_tool::{ListFilesTool, ReadFileTool, WriteFileTool};
 // Core tools
 pub use progress_tool::ProgressTool;
 pub use task_tool::TaskTool;
-pub use test_tool::TestTool;
+pub use test_tool::{FlakyRetryPolicy, TestTool};
+pub use crate::language::Language;
 
 // Interactive mode tools
 pub use add_feature_tool::{AddFeatureMode, AddFeatureTool};
 pub use run_pipeline_tool::RunPipelineTool;
-pub use run_project_tool::{Language, RunProjectTool};
+pub use run_project_tool::RunProjectTool;
 pub use time_tool::GetTimeTool;
 pub use web_search_tool::{SearchResult, WebSearchTool};
@@ -16,6 +16,7 @@
 //! - 7.3: THE Progress_File SHALL include any gotchas or patterns discovered
 
 use crate::models::{ProgressEntry, ProgressLog, TestResults};
+use crate::persist::WriteBehind;
 use crate::telemetry::{start_timing, tool_call_span};
 use adk_rust::{Result as AdkResult, Tool, ToolContext};
 use async_trait::async_trait;
@@ -88,6 +89,8 @@ pub struct ProgressTool {
     cache: RwLock<Option<ProgressLog>>,
     /// Current iteration counter
     iteration: RwLock<u32>,
+    /// Buffer for saves during tool-call bursts (`None` = write through)
+    write_behind: Option<Arc<WriteBehind>>,
 }
 
 impl ProgressTool {
@@ -98,9 +101,19 @@ impl ProgressTool {
             project: project.into(),
             cache: RwLock::new(None),
             iteration: RwLock::new(0),
+            write_behind: None,
         }
     }
 
+    /// Buffer saves in `write_behind` instead of writing each one.
+    ///
+    /// The cache keeps reads current; the newest entries reach disk when the
+    /// buffer flushes.
+    pub fn with_write_behind(mut self, write_behind: Arc<WriteBehind>) -> Self {
+        self.write_behind = Some(write_behind);
+        self
+    }
+
     /// Set the current iteration number.
     pub async fn set_iteration(&self, iteration: u32) {
         *self.iteration.write().await = iteration;
@@ -142,7 +155,14 @@ impl ProgressTool {
 
     /// Save the progress log to disk and update cache.
     async fn save(&self, log: &ProgressLog) -> Result<(), String> {
-        log.save(&self.path)?;
+        match self.write_behind {
+            Some(ref buffer) => {
+                let content = serde_json::to_string_pretty(log)
+                    .map_err(|e| format!("Failed to serialize progress: {}", e))?;
+                buffer.write(&self.path, content)?;
+            }
+            None => log.save(&self.path)?,
+        }
 
         // Update cache
         {
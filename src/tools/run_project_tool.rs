@@ -10,39 +10,16 @@
 //! - 8.3: THE System SHALL capture and display stdout/stderr from the executed project
 //! - 8.5: THE System SHALL support running with arguments
 
+use crate::exec_env::ExecEnv;
+use crate::resources::{LimitBreach, ResourceUsage};
 use adk_rust::{AdkError, Result, Tool, ToolContext};
 use async_trait::async_trait;
 use serde::Deserialize;
 use serde_json::{json, Value};
 use std::path::PathBuf;
-use std::process::Stdio;
 use std::sync::Arc;
 
-/// Supported programming languages for project execution.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum Language {
-    Rust,
-    Go,
-    Python,
-    Node,
-    TypeScript,
-    Java,
-    Unknown,
-}
-
-impl std::fmt::Display for Language {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Language::Rust => write!(f, "rust"),
-            Language::Go => write!(f, "go"),
-            Language::Python => write!(f, "python"),
-            Language::Node => write!(f, "node"),
-            Language::TypeScript => write!(f, "typescript"),
-            Language::Java => write!(f, "java"),
-            Language::Unknown => write!(f, "unknown"),
-        }
-    }
-}
+pub use crate::language::Language;
 
 /// Tool for running and testing generated projects.
 ///
@@ -67,11 +44,17 @@ impl std::fmt::Display for Language {
 ///     "stdout": "...",
 ///     "stderr": "",
 ///     "language": "rust",
-///     "command": "cargo run -- --help"
+///     "command": "cargo run -- --help",
+///     "resources": { "wall_ms": 1840, "user_cpu_ms": 1510, "system_cpu_ms": 220, "peak_rss_kb": 48212 }
 /// }
 /// ```
+///
+/// A command that goes over a resource limit also has `limit_exceeded` and a
+/// `message` explaining it.
 pub struct RunProjectTool {
     project_path: PathBuf,
+    /// Variables injected into the project's commands only
+    exec_env: Arc<ExecEnv>,
 }
 
 impl RunProjectTool {
@@ -79,54 +62,21 @@ impl RunProjectTool {
     pub fn new(project_path: impl Into<PathBuf>) -> Self {
         Self {
             project_path: project_path.into(),
+            exec_env: Arc::new(ExecEnv::default()),
         }
     }
 
+    /// Set the environment variables injected into run and test commands.
+    pub fn with_exec_env(mut self, exec_env: Arc<ExecEnv>) -> Self {
+        self.exec_env = exec_env;
+        self
+    }
+
     /// Detect the programming language from project files.
     ///
-    /// Checks for language-specific manifest files:
-    /// - Cargo.toml → Rust
-    /// - go.mod → Go
-    /// - package.json → Node/TypeScript
-    /// - requirements.txt / pyproject.toml → Python
-    /// - pom.xml / build.gradle → Java
+    /// See [`crate::language::detect_language`] for the manifest files checked.
     pub fn detect_language(&self) -> Language {
-        // Check for Rust
-        if self.project_path.join("Cargo.toml").exists() {
-            return Language::Rust;
-        }
-
-        // Check for Go
-        if self.project_path.join("go.mod").exists() {
-            return Language::Go;
-        }
-
-        // Check for Node/TypeScript
-        if self.project_path.join("package.json").exists() {
-            // Check if it's TypeScript
-            if self.project_path.join("tsconfig.json").exists() {
-                return Language::TypeScript;
-            }
-            return Language::Node;
-        }
-
-        // Check for Python
-        if self.project_path.join("requirements.txt").exists()
-            || self.project_path.join("pyproject.toml").exists()
-            || self.project_path.join("setup.py").exists()
-        {
-            return Language::Python;
-        }
-
-        // Check for Java
-        if self.project_path.join("pom.xml").exists()
-            || self.project_path.join("build.gradle").exists()
-            || self.project_path.join("build.gradle.kts").exists()
-        {
-            return Language::Java;
-        }
-
-        Language::Unknown
+        crate::language::detect_language(&self.project_path).unwrap_or(Language::Unknown)
     }
 
     /// Get the run command for a language.
@@ -162,7 +112,7 @@ impl RunProjectTool {
                 cmd_args.extend(args.iter().cloned());
                 ("python".to_string(), cmd_args)
             }
-            Language::Node => {
+            Language::JavaScript => {
                 let mut cmd_args = vec!["start".to_string()];
                 if !args.is_empty() {
                     cmd_args.push("--".to_string());
@@ -208,7 +158,7 @@ impl RunProjectTool {
             Language::Rust => ("cargo".to_string(), vec!["test".to_string()]),
             Language::Go => ("go".to_string(), vec!["test".to_string(), "./...".to_string()]),
             Language::Python => ("pytest".to_string(), vec![]),
-            Language::Node | Language::TypeScript => ("npm".to_string(), vec!["test".to_string()]),
+            Language::JavaScript | Language::TypeScript => ("npm".to_string(), vec!["test".to_string()]),
             Language::Java => {
                 if self.project_path.join("pom.xml").exists() {
                     ("mvn".to_string(), vec!["test".to_string()])
@@ -221,37 +171,46 @@ impl RunProjectTool {
     }
 
     /// Execute a command and capture output.
-    async fn execute_command(
-        &self,
-        program: &str,
-        args: &[String],
-    ) -> Result<(i32, String, String)> {
+    async fn execute_command(&self, program: &str, args: &[String]) -> Result<CommandOutput> {
         use tokio::process::Command;
 
         let timeout_secs = 120;
-        let child = Command::new(program)
-            .args(args)
-            .current_dir(&self.project_path)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output();
-
-        let output = tokio::time::timeout(
-            std::time::Duration::from_secs(timeout_secs),
-            child,
-        )
-        .await
-        .map_err(|_| AdkError::Tool(format!("Command timed out after {}s: {} {}", timeout_secs, program, args.join(" "))))?
-        .map_err(|e| AdkError::Tool(format!("Failed to execute command: {}", e)))?;
-
-        let exit_code = output.status.code().unwrap_or(-1);
-        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-
-        Ok((exit_code, stdout, stderr))
+        let mut command = Command::new(program);
+        command.args(args).current_dir(&self.project_path);
+
+        let timeout = std::time::Duration::from_secs(timeout_secs);
+        let output = self.exec_env.run(&mut command, timeout).await.map_err(|e| {
+            if e.kind() == std::io::ErrorKind::TimedOut {
+                AdkError::Tool(format!(
+                    "Command timed out after {}: {} {}",
+                    crate::timefmt::duration(timeout),
+                    program,
+                    args.join(" ")
+                ))
+            } else {
+                AdkError::Tool(format!("Failed to execute command: {}", e))
+            }
+        })?;
+
+        Ok(CommandOutput {
+            exit_code: output.status.code().unwrap_or(-1),
+            stdout: self.exec_env.decode(&output.stdout, "stdout"),
+            stderr: self.exec_env.decode(&output.stderr, "stderr"),
+            usage: output.usage,
+            breach: output.breach,
+        })
     }
 }
 
+/// Decoded output of one project command.
+struct CommandOutput {
+    exit_code: i32,
+    stdout: String,
+    stderr: String,
+    usage: ResourceUsage,
+    breach: Option<LimitBreach>,
+}
+
 impl std::fmt::Debug for RunProjectTool {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("RunProjectTool")
@@ -328,24 +287,32 @@ impl Tool for RunProjectTool {
         tracing::debug!(command = %command_str, "Running command");
 
         // Execute the command
-        let (exit_code, stdout, stderr) = self.execute_command(&program, &cmd_args).await?;
+        let output = self.execute_command(&program, &cmd_args).await?;
 
-        let success = exit_code == 0;
+        let success = output.exit_code == 0 && output.breach.is_none();
 
         tracing::info!(
-            exit_code = exit_code,
+            exit_code = output.exit_code,
             success = success,
+            resources = %output.usage,
             "Command execution complete"
         );
 
-        Ok(json!({
+        let mut response = json!({
             "success": success,
-            "exit_code": exit_code,
-            "stdout": stdout,
-            "stderr": stderr,
+            "exit_code": output.exit_code,
+            "stdout": output.stdout,
+            "stderr": output.stderr,
             "language": language.to_string(),
-            "command": command_str
-        }))
+            "command": command_str,
+            "resources": output.usage
+        });
+        if let Some(breach) = output.breach {
+            let what = if args.operation == "test" { "Your tests" } else { "The project" };
+            response["limit_exceeded"] = json!(breach);
+            response["message"] = json!(breach.describe(what));
+        }
+        Ok(response)
     }
 }
 
@@ -410,7 +377,7 @@ mod tests {
         std::fs::write(temp_dir.path().join("package.json"), "{}").unwrap();
         
         let tool = RunProjectTool::new(temp_dir.path());
-        assert_eq!(tool.detect_language(), Language::Node);
+        assert_eq!(tool.detect_language(), Language::JavaScript);
     }
 
     #[test]
@@ -484,7 +451,7 @@ mod tests {
         assert_eq!(Language::Rust.to_string(), "rust");
         assert_eq!(Language::Go.to_string(), "go");
         assert_eq!(Language::Python.to_string(), "python");
-        assert_eq!(Language::Node.to_string(), "node");
+        assert_eq!(Language::JavaScript.to_string(), "javascript");
         assert_eq!(Language::TypeScript.to_string(), "typescript");
         assert_eq!(Language::Java.to_string(), "java");
         assert_eq!(Language::Unknown.to_string(), "unknown");
@@ -9,7 +9,26 @@
 //! - `list`: List all tasks with their status
 //! - `get_next`: Get the next task to work on (priority-based with dependency checking)
 //! - `update_status`: Update a task's status
-//! - `complete`: Mark a task as completed
+//! - `complete`: Mark a task as completed (after the optional acceptance judge approves)
+//!
+//! The judge's verdict is kept on the task: `unmet_criteria` lists the
+//! criteria it found unmet, and the task's context checks off the rest.
+//!
+//! With a [`FileScope`] attached, starting a task begins tracking the files it
+//! writes, and completing it reports touched files that it did not declare.
+//! With an [`ExecEnv`] attached, starting a task makes its `env` variables
+//! apply to the project's commands until it completes.
+//! With [`CompletionGates`] attached, `complete` (and `update_status` to
+//! completed) is refused until the loop has seen the task's tests pass; the
+//! task stays in progress and the response says what is missing.
+//! With a [`Fingerprinter`] attached, each completed task records what it was
+//! built from and the files it left, for `ralph resume --incremental`.
+//! With [`TaskBranches`] attached, starting a task checks out its own branch,
+//! completing it merges the branch back, and blocking it returns to the base
+//! branch with the task's branch left behind (recorded in `branch`).
+//! With [`TaskRollback`] attached, starting a task records the commit it
+//! starts from, and blocking it resets the project to that commit (the work
+//! is kept in the commit recorded in `rolled_back`).
 //!
 //! ## Requirements Validated
 //!
@@ -19,14 +38,21 @@
 //! - 4.4: IF a task is blocked by incomplete dependencies, THEN THE Ralph_Loop_Agent SHALL skip it
 //! - 4.5: THE Ralph_Loop_Agent SHALL update task status to in_progress when starting
 
-use crate::models::{Task, TaskList, TaskStatus};
+use crate::agents::JudgeAgent;
+use crate::exec_env::ExecEnv;
+use crate::gates::CompletionGates;
+use crate::incremental::Fingerprinter;
+use crate::models::{SchedulingStrategy, Task, TaskList, TaskStatus};
+use crate::scope::FileScope;
 use crate::telemetry::{start_timing, tool_call_span};
+use crate::tools::{TaskBranches, TaskRollback};
 use adk_rust::{Result as AdkResult, Tool, ToolContext};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::SystemTime;
 use tokio::sync::RwLock;
 use tracing::info;
 
@@ -53,8 +79,24 @@ pub struct TaskToolInput {
 pub struct TaskTool {
     /// Path to the tasks.json file
     path: PathBuf,
-    /// Cached task list (for performance)
-    cache: RwLock<Option<TaskList>>,
+    /// Cached task list and the file mtime it was read at (for performance)
+    cache: RwLock<Option<(TaskList, Option<SystemTime>)>>,
+    /// Judge that must approve acceptance criteria before completion (opt-in)
+    judge: Option<Arc<JudgeAgent>>,
+    /// File budget of the task in progress (shared with the file tool)
+    scope: Option<Arc<FileScope>>,
+    /// Command environment of the task in progress (shared with the test tool)
+    exec_env: Option<Arc<ExecEnv>>,
+    /// Evidence required before completion (shared with the test tool)
+    gates: Option<Arc<CompletionGates>>,
+    /// Records what each completed task was built from, for incremental re-runs
+    fingerprints: Option<Fingerprinter>,
+    /// Order in which ready tasks are handed out
+    scheduling: SchedulingStrategy,
+    /// A git branch per task, merged back on completion (opt-in)
+    branches: Option<Arc<TaskBranches>>,
+    /// Undo a task's changes when it is blocked (opt-in)
+    rollback: Option<Arc<TaskRollback>>,
 }
 
 impl TaskTool {
@@ -63,16 +105,168 @@ impl TaskTool {
         Self {
             path: path.into(),
             cache: RwLock::new(None),
+            judge: None,
+            scope: None,
+            exec_env: None,
+            gates: None,
+            fingerprints: None,
+            scheduling: SchedulingStrategy::default(),
+            branches: None,
+            rollback: None,
+        }
+    }
+
+    /// Require the judge to approve a task's acceptance criteria before it completes.
+    pub fn with_judge(mut self, judge: Arc<JudgeAgent>) -> Self {
+        self.judge = Some(judge);
+        self
+    }
+
+    /// Track each task's file writes in `scope`.
+    pub fn with_scope(mut self, scope: Arc<FileScope>) -> Self {
+        self.scope = Some(scope);
+        self
+    }
+
+    /// Apply each started task's `env` to the commands run through `exec_env`.
+    pub fn with_exec_env(mut self, exec_env: Arc<ExecEnv>) -> Self {
+        self.exec_env = Some(exec_env);
+        self
+    }
+
+    /// Implement each task on its own branch in `branches`.
+    pub fn with_branches(mut self, branches: Arc<TaskBranches>) -> Self {
+        self.branches = Some(branches);
+        self
+    }
+
+    /// Roll a task's changes back with `rollback` when it is blocked.
+    pub fn with_rollback(mut self, rollback: Arc<TaskRollback>) -> Self {
+        self.rollback = Some(rollback);
+        self
+    }
+
+    /// Record a fingerprint on each task as it completes.
+    pub fn with_fingerprints(mut self, fingerprints: Fingerprinter) -> Self {
+        self.fingerprints = Some(fingerprints);
+        self
+    }
+
+    /// Hand out ready tasks in the order `strategy` picks.
+    pub fn with_scheduling(mut self, strategy: SchedulingStrategy) -> Self {
+        self.scheduling = strategy;
+        self
+    }
+
+    /// Refuse completion until `gates` pass for the task.
+    pub fn with_gates(mut self, gates: Arc<CompletionGates>) -> Self {
+        self.gates = Some(gates);
+        self
+    }
+
+    /// Start collecting completion evidence for a task.
+    fn begin_gates(&self, task: &Task) {
+        if let Some(gates) = &self.gates {
+            if gates.task_id().as_deref() != Some(task.id.as_str()) {
+                gates.begin(&task.id);
+            }
+        }
+    }
+
+    /// Use a task's environment variables (and its own scratch directory,
+    /// when isolation is on) for the project's commands.
+    fn begin_env(&self, task: &Task) {
+        if let Some(exec_env) = &self.exec_env {
+            exec_env.set_task_env(&task.env);
+            exec_env.begin_scratch(&task.id);
         }
     }
 
+    /// Check out the task's own branch, recording it on the task.
+    ///
+    /// Failing to branch does not stop the task: it is noted and the work
+    /// stays on the current branch.
+    fn begin_branch(&self, task: &mut Task) {
+        let Some(branches) = &self.branches else {
+            return;
+        };
+        match branches.start(task) {
+            Ok(Some(branch)) => task.branch = Some(branch),
+            Ok(None) => {}
+            Err(e) => task.add_note(&format!("Branch: could not create a branch for this task: {}", e)),
+        }
+    }
+
+    /// Merge a completed task's branch, or leave a blocked one, and return to
+    /// the base branch. Problems are noted on the task; a branch that could
+    /// not be merged also flags the task for review.
+    fn end_branch(&self, task: &mut Task) {
+        let (Some(branches), Some(branch)) = (&self.branches, task.branch.clone()) else {
+            return;
+        };
+        if task.status == TaskStatus::Completed {
+            if let Err(e) = branches.finish(task, &branch) {
+                task.flag_for_review(&format!("Branch {} was not merged: {}", branch, e));
+            }
+        } else if let Err(e) = branches.abandon(&task.id, &branch) {
+            task.add_note(&format!("Branch: could not leave {}: {}", branch, e));
+        } else {
+            task.add_note(&format!("Branch: work left on {} for inspection", branch));
+        }
+    }
+
+    /// Record the commit a task starts from, for a later rollback.
+    fn begin_rollback(&self, task: &mut Task) {
+        if let Some(rollback) = &self.rollback {
+            rollback.begin(task);
+        }
+    }
+
+    /// Roll a blocked task back to its start commit. A requeued task keeps
+    /// its start commit for the next attempt; any other ending drops it.
+    fn end_rollback(&self, task: &mut Task) {
+        let Some(rollback) = &self.rollback else {
+            return;
+        };
+        match task.status {
+            TaskStatus::Blocked => rollback.apply(task),
+            TaskStatus::Pending | TaskStatus::InProgress => {}
+            _ => task.start_commit = None,
+        }
+    }
+
+    /// Start tracking the files written for a task.
+    fn begin_scope(&self, task: &Task) {
+        if let Some(scope) = &self.scope {
+            let declared = task
+                .files_created
+                .iter()
+                .chain(&task.files_modified)
+                .cloned()
+                .collect();
+            scope.begin(&task.id, declared);
+        }
+    }
+
+    /// Modification time of tasks.json, if it can be read.
+    fn modified_at(&self) -> Option<SystemTime> {
+        std::fs::metadata(&self.path).and_then(|m| m.modified()).ok()
+    }
+
     /// Load the task list from disk.
+    ///
+    /// The cache is bypassed when the file changed on disk since it was read,
+    /// e.g. when the git tool records diff stats on a task.
     async fn load(&self) -> Result<TaskList, String> {
+        let modified = self.modified_at();
+
         // Check cache first
         {
             let cache = self.cache.read().await;
-            if let Some(list) = cache.as_ref() {
-                return Ok(list.clone());
+            if let Some((list, cached_at)) = cache.as_ref() {
+                if *cached_at == modified {
+                    return Ok(list.clone());
+                }
             }
         }
 
@@ -82,7 +276,7 @@ impl TaskTool {
         // Update cache
         {
             let mut cache = self.cache.write().await;
-            *cache = Some(list.clone());
+            *cache = Some((list.clone(), modified));
         }
 
         Ok(list)
@@ -95,7 +289,7 @@ impl TaskTool {
         // Update cache
         {
             let mut cache = self.cache.write().await;
-            *cache = Some(list.clone());
+            *cache = Some((list.clone(), self.modified_at()));
         }
 
         Ok(())
@@ -151,16 +345,27 @@ impl TaskTool {
     async fn get_next(&self) -> Result<Value, String> {
         let mut list = self.load().await?;
 
-        // Find the next task using priority-based selection with dependency checking
-        let next_task = list.get_next_task();
+        // Find the next ready task, ordered by the scheduling strategy
+        // (ties by priority, then ID)
+        let next_task = list.next_ready(self.scheduling);
 
         match next_task {
             Some(task) => {
                 let task_id = task.id.clone();
                 let task_detail = task_detail(task);
 
-                // Update status to in_progress
-                list.update_task_status(&task_id, TaskStatus::InProgress)?;
+                // Update status to in_progress, counting the attempt
+                let mut branch = None;
+                if let Some(task) = list.get_task_mut(&task_id) {
+                    task.start();
+                    self.begin_branch(task);
+                    self.begin_rollback(task);
+                    branch = task.branch.clone();
+                    self.begin_scope(task);
+                    self.begin_env(task);
+                    self.begin_gates(task);
+                }
+                list.updated_at = Some(crate::timefmt::now());
                 self.save(&list).await?;
 
                 Ok(json!({
@@ -168,15 +373,26 @@ impl TaskTool {
                     "has_next": true,
                     "task": task_detail,
                     "status_updated": true,
+                    "branch": branch,
                     "message": format!("Task {} is now in progress", task_id)
                 }))
             }
             None => {
+                // Tasks behind a blocked dependency can never start: block them too
+                if !list.mark_blocked_tasks().is_empty() {
+                    self.save(&list).await?;
+                }
+
                 // Check if all tasks are complete or if there are blocked tasks
                 let stats = list.get_stats();
                 let all_complete = list.is_complete();
+                let waiting = list.blocked();
+                let reasons: Vec<String> = waiting
+                    .iter()
+                    .map(|task| format!("{} waits on: {}", task.id, task.reasons.join(", ")))
+                    .collect();
 
-                if all_complete {
+                if all_complete && stats.blocked == 0 {
                     Ok(json!({
                         "success": true,
                         "has_next": false,
@@ -184,21 +400,32 @@ impl TaskTool {
                         "message": "All tasks have been completed!"
                     }))
                 } else if stats.blocked > 0 {
+                    let mut message = format!("{} tasks are blocked. Check dependencies.", stats.blocked);
+                    if !reasons.is_empty() {
+                        message = format!("{} {}", message, reasons.join("; "));
+                    }
                     Ok(json!({
                         "success": true,
                         "has_next": false,
                         "all_complete": false,
                         "blocked_count": stats.blocked,
-                        "message": format!("{} tasks are blocked. Check dependencies.", stats.blocked)
+                        "waiting": waiting,
+                        "message": message
                     }))
                 } else {
                     // Tasks exist but none are selectable (dependency issues)
+                    let message = if reasons.is_empty() {
+                        "No tasks available. Pending tasks may have unmet dependencies.".to_string()
+                    } else {
+                        format!("No tasks available. {}", reasons.join("; "))
+                    };
                     Ok(json!({
                         "success": true,
                         "has_next": false,
                         "all_complete": false,
                         "pending_count": stats.pending,
-                        "message": "No tasks available. Pending tasks may have unmet dependencies."
+                        "waiting": waiting,
+                        "message": message
                     }))
                 }
             }
@@ -207,12 +434,36 @@ impl TaskTool {
 
     /// Update a task's status.
     async fn update_status(&self, task_id: &str, status_str: &str) -> Result<Value, String> {
-        let mut list = self.load().await?;
-
         let status = parse_status(status_str)?;
+        if status == TaskStatus::Completed {
+            // Completion goes through the same gates as `complete`
+            return self.complete(task_id, None).await;
+        }
+
+        let mut list = self.load().await?;
         list.update_task_status(task_id, status)?;
+        if let Some(task) = list.get_task_mut(task_id) {
+            if status == TaskStatus::InProgress {
+                self.begin_branch(task);
+                self.begin_rollback(task);
+            } else {
+                self.end_branch(task);
+                self.end_rollback(task);
+            }
+        }
         self.save(&list).await?;
 
+        if status == TaskStatus::InProgress {
+            if let Some(task) = list.get_task(task_id) {
+                let tracked = self.scope.as_ref().and_then(|scope| scope.task_id());
+                if self.scope.is_some() && tracked.as_deref() != Some(task_id) {
+                    self.begin_scope(task);
+                }
+                self.begin_env(task);
+                self.begin_gates(task);
+            }
+        }
+
         Ok(json!({
             "success": true,
             "task_id": task_id,
@@ -222,10 +473,83 @@ impl TaskTool {
     }
 
     /// Mark a task as completed.
+    ///
+    /// With a judge configured, the task stays in progress until every
+    /// acceptance criterion is met; unmet criteria are recorded in its notes.
+    /// The completion gates are checked first, so the judge only reviews
+    /// tasks whose tests passed.
     async fn complete(&self, task_id: &str, commit_hash: Option<String>) -> Result<Value, String> {
         let mut list = self.load().await?;
 
+        if let Some(gates) = &self.gates {
+            let verdict = gates.evaluate(task_id);
+            if !verdict.passed {
+                let message = verdict.corrective_message();
+                list.get_task_mut(task_id)
+                    .ok_or_else(|| format!("Task not found: {}", task_id))?
+                    .add_note(&format!("Completion refused: {}", verdict.failures.join("; ")));
+                self.save(&list).await?;
+                return Ok(json!({
+                    "success": false,
+                    "task_id": task_id,
+                    "gate": verdict,
+                    "message": message
+                }));
+            }
+        }
+
+        let mut verdict = None;
+        if let Some(judge) = &self.judge {
+            let task = list
+                .get_task(task_id)
+                .cloned()
+                .ok_or_else(|| format!("Task not found: {}", task_id))?;
+            let review = judge
+                .judge_task(&task)
+                .await
+                .map_err(|e| format!("Acceptance review failed: {}", e))?;
+
+            if !review.all_met() {
+                if let Some(task) = list.get_task_mut(task_id) {
+                    task.add_note(&format!("Acceptance review: {}", review.summary()));
+                    task.unmet_criteria = Some(review.unmet().iter().map(|v| v.criterion.clone()).collect());
+                }
+                self.save(&list).await?;
+                return Ok(json!({
+                    "success": false,
+                    "task_id": task_id,
+                    "judge": review,
+                    "message": format!(
+                        "Task {} was not completed. {}. Fix the unmet criteria, commit, and call complete again.",
+                        task_id,
+                        review.summary()
+                    )
+                }));
+            }
+            verdict = Some(review);
+        }
+
         list.complete_task(task_id, commit_hash.clone())?;
+        if let Some(task) = list.get_task_mut(task_id) {
+            if verdict.is_some() {
+                task.unmet_criteria = Some(Vec::new());
+            }
+            self.end_branch(task);
+            self.end_rollback(task);
+        }
+        if let Some(fingerprints) = &self.fingerprints {
+            fingerprints.record(&mut list, task_id);
+        }
+        let scope = self.record_scope(&mut list, task_id);
+        if let Some(exec_env) = &self.exec_env {
+            if let Some(task) = list.get_task_mut(task_id) {
+                task.resources = exec_env.take_usage();
+            }
+            exec_env.clear_task_env();
+        }
+        if let Some(gates) = &self.gates {
+            gates.clear();
+        }
         self.save(&list).await?;
 
         let stats = list.get_stats();
@@ -235,6 +559,8 @@ impl TaskTool {
             "success": true,
             "task_id": task_id,
             "commit_hash": commit_hash,
+            "judge": verdict,
+            "scope": scope,
             "stats": {
                 "completed": stats.completed,
                 "remaining": stats.pending + stats.in_progress,
@@ -249,6 +575,42 @@ impl TaskTool {
         }))
     }
 
+    /// Compare the files a completing task touched with those it declared.
+    ///
+    /// Flags the task for review when it hit the file cap and notes a large
+    /// deviation. Returns the findings for the tool response, if any.
+    fn record_scope(&self, list: &mut TaskList, task_id: &str) -> Option<Value> {
+        let scope = self.scope.as_ref()?;
+        let report = scope.report().filter(|r| r.task_id == task_id);
+        scope.clear();
+        let report = report?;
+
+        let review = report.review_reason();
+        let deviation = report.deviation.is_large().then(|| report.deviation.summary());
+        let skipped = report.skipped_summary();
+        if let Some(task) = list.get_task_mut(task_id) {
+            if let Some(reason) = &review {
+                task.flag_for_review(reason);
+            }
+            if let Some(summary) = &deviation {
+                task.add_note(&format!("Scope: {}", summary));
+            }
+            if let Some(summary) = &skipped {
+                task.add_note(&format!("Scope: {}: {}", summary, report.skipped.join(", ")));
+            }
+        }
+        if review.is_none() && deviation.is_none() && skipped.is_none() {
+            return None;
+        }
+        Some(json!({
+            "files_touched": report.touched.len(),
+            "needs_review": review,
+            "deviation": deviation,
+            "skipped": skipped,
+            "skipped_files": report.skipped,
+        }))
+    }
+
     /// Get a specific task by ID.
     async fn get_task(&self, task_id: &str) -> Result<Value, String> {
         let list = self.load().await?;
@@ -306,10 +668,13 @@ fn task_detail(task: &Task) -> Value {
         "status": task.status.to_string(),
         "dependencies": task.dependencies,
         "user_story_id": task.user_story_id,
+        "acceptance_criteria": task.acceptance_criteria,
+        "unmet_criteria": task.unmet_criteria,
         "complexity": task.estimated_complexity.to_string(),
         "files_created": task.files_created,
         "files_modified": task.files_modified,
         "commit_hash": task.commit_hash,
+        "diff_stats": task.diff_stats,
         "attempts": task.attempts,
         "notes": task.notes,
         "context": task.to_context()
@@ -411,3 +776,97 @@ impl std::fmt::Debug for TaskTool {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool_with_gates(dir: &std::path::Path) -> (TaskTool, Arc<CompletionGates>) {
+        let path = dir.join("tasks.json");
+        let mut list = TaskList::new("demo", "rust");
+        list.add_task(Task::new("T-001", "First", "Do the first thing", 1));
+        list.add_task(Task::new("T-002", "Second", "Do the second thing", 2));
+        list.save(&path).unwrap();
+
+        let gates = Arc::new(CompletionGates::new(true));
+        (TaskTool::new(path).with_gates(gates.clone()), gates)
+    }
+
+    #[tokio::test]
+    async fn test_get_next_explains_what_waits() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tasks.json");
+        let mut list = TaskList::new("demo", "rust");
+        let mut second = Task::new("T-002", "Second", "Needs the first", 1);
+        second.add_dependency("T-001");
+        list.add_task(second);
+        list.add_task(Task::new("T-001", "First", "Do the first thing", 2));
+        list.save(&path).unwrap();
+        let tool = TaskTool::new(path);
+
+        assert_eq!(tool.get_next().await.unwrap()["task"]["id"], "T-001");
+        let next = tool.get_next().await.unwrap();
+        assert_eq!(next["has_next"], false);
+        assert_eq!(next["pending_count"], 1);
+        assert_eq!(next["waiting"][0]["id"], "T-002");
+        assert_eq!(next["waiting"][0]["reasons"][0], "T-001 is in_progress");
+        assert!(next["message"].as_str().unwrap().contains("T-002 waits on: T-001 is in_progress"));
+
+        // Once T-001 is given up on, T-002 can never start and is blocked too
+        tool.update_status("T-001", "blocked").await.unwrap();
+        let next = tool.get_next().await.unwrap();
+        assert_eq!(next["blocked_count"], 2);
+        let list = TaskList::load(dir.path().join("tasks.json")).unwrap();
+        assert!(list.get_task("T-002").unwrap().is_blocked());
+    }
+
+    #[tokio::test]
+    async fn test_completion_claims_need_test_evidence() {
+        let dir = tempfile::tempdir().unwrap();
+        let (tool, gates) = tool_with_gates(dir.path());
+
+        // A model that claims completion without running tests is refused
+        let next = tool.get_next().await.unwrap();
+        assert_eq!(next["task"]["id"], "T-001");
+        let claim = tool.complete("T-001", None).await.unwrap();
+        assert_eq!(claim["success"], false);
+        assert_eq!(claim["gate"]["passed"], false);
+        assert!(claim["message"].as_str().unwrap().contains("not complete"));
+
+        // ... and so is one whose tests failed
+        gates.record_test_run(false);
+        let claim = tool.complete("T-001", None).await.unwrap();
+        assert_eq!(claim["success"], false);
+
+        let list = TaskList::load(dir.path().join("tasks.json")).unwrap();
+        let task = list.get_task("T-001").unwrap();
+        assert_eq!(task.status, TaskStatus::InProgress);
+        assert!(task.notes.contains("Completion refused"));
+
+        // A passing run lets the claim through
+        gates.record_test_run(true);
+        let claim = tool.complete("T-001", None).await.unwrap();
+        assert_eq!(claim["success"], true);
+        let list = TaskList::load(dir.path().join("tasks.json")).unwrap();
+        assert_eq!(list.get_task("T-001").unwrap().status, TaskStatus::Completed);
+        assert_eq!(gates.task_id(), None);
+    }
+
+    #[tokio::test]
+    async fn test_update_status_to_completed_is_gated() {
+        let dir = tempfile::tempdir().unwrap();
+        let (tool, gates) = tool_with_gates(dir.path());
+
+        tool.update_status("T-002", "in_progress").await.unwrap();
+        assert_eq!(gates.task_id().as_deref(), Some("T-002"));
+
+        let claim = tool.update_status("T-002", "completed").await.unwrap();
+        assert_eq!(claim["success"], false);
+
+        // Re-marking the task in progress keeps the evidence collected so far
+        gates.record_test_run(true);
+        tool.update_status("T-002", "in_progress").await.unwrap();
+        let claim = tool.update_status("T-002", "done").await.unwrap();
+        assert_eq!(claim["success"], true);
+    }
+}
@@ -17,11 +17,16 @@
 //! - 10.3: THE Ralph_Loop_Agent SHALL use language-appropriate testing frameworks
 //! - 10.5: THE system SHALL support at minimum: Rust, Python, TypeScript, Go, Java
 
+use crate::exec_env::ExecEnv;
+use crate::gates::CompletionGates;
 use crate::models::TestResults;
+use crate::resources::{LimitBreach, ResourceUsage};
+use crate::language::ExtensionMap;
+use crate::scope::FileScope;
 use crate::telemetry::{log_test_results, start_timing, test_execution_span, tool_call_span};
 use adk_rust::{Result as AdkResult, Tool, ToolContext};
 use async_trait::async_trait;
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 use serde_json::{json, Value};
 use std::path::PathBuf;
 use std::process::Stdio;
@@ -29,77 +34,55 @@ use std::sync::Arc;
 use tokio::process::Command;
 use tracing::info;
 
-/// Supported programming languages.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
-pub enum Language {
-    Rust,
-    Python,
-    TypeScript,
-    JavaScript,
-    Go,
-    Java,
-    Unknown,
-}
+pub use crate::language::Language;
 
-impl std::fmt::Display for Language {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Language::Rust => write!(f, "rust"),
-            Language::Python => write!(f, "python"),
-            Language::TypeScript => write!(f, "typescript"),
-            Language::JavaScript => write!(f, "javascript"),
-            Language::Go => write!(f, "go"),
-            Language::Java => write!(f, "java"),
-            Language::Unknown => write!(f, "unknown"),
-        }
-    }
+/// Re-run policy for flaky test suites.
+///
+/// A failing run may be retried up to `retries` times. Once any run has failed,
+/// a pass is only accepted after `confirm_runs` consecutive green runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlakyRetryPolicy {
+    /// Extra runs allowed after failing runs
+    pub retries: u32,
+    /// Consecutive green runs needed to accept a pass after a failure
+    pub confirm_runs: u32,
 }
 
-impl std::str::FromStr for Language {
-    type Err = std::convert::Infallible;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(match s.to_lowercase().as_str() {
-            "rust" | "rs" => Language::Rust,
-            "python" | "py" => Language::Python,
-            "typescript" | "ts" => Language::TypeScript,
-            "javascript" | "js" => Language::JavaScript,
-            "go" | "golang" => Language::Go,
-            "java" => Language::Java,
-            _ => Language::Unknown,
-        })
+impl Default for FlakyRetryPolicy {
+    fn default() -> Self {
+        Self {
+            retries: 0,
+            confirm_runs: 1,
+        }
     }
 }
 
-impl Language {
-    /// Parse a language from a string.
-    pub fn parse(s: &str) -> Self {
-        s.parse().unwrap_or(Language::Unknown)
-    }
-
-    /// Get the test command for this language.
-    pub fn test_command(&self) -> Option<(&str, Vec<&str>)> {
-        match self {
-            Language::Rust => Some(("cargo", vec!["test"])),
-            Language::Python => Some(("pytest", vec!["-v"])),
-            Language::TypeScript | Language::JavaScript => Some(("npx", vec!["vitest", "run"])),
-            Language::Go => Some(("go", vec!["test", "./..."])),
-            Language::Java => Some(("mvn", vec!["test"])),
-            Language::Unknown => None,
+impl FlakyRetryPolicy {
+    /// Create a policy with the given retry and confirmation counts.
+    pub fn new(retries: u32, confirm_runs: u32) -> Self {
+        Self {
+            retries,
+            confirm_runs: confirm_runs.max(1),
         }
     }
 
-    /// Get the test framework name for this language.
-    pub fn test_framework(&self) -> &str {
-        match self {
-            Language::Rust => "cargo test",
-            Language::Python => "pytest",
-            Language::TypeScript | Language::JavaScript => "vitest",
-            Language::Go => "go test",
-            Language::Java => "maven surefire",
-            Language::Unknown => "unknown",
+    /// Decide the verdict from the run outcomes so far.
+    ///
+    /// Returns `Some(passed)` once a verdict is reached, or `None` if another run is needed.
+    pub fn verdict(&self, outcomes: &[bool]) -> Option<bool> {
+        let failures = outcomes.iter().filter(|ok| !**ok).count() as u32;
+        let streak = outcomes.iter().rev().take_while(|ok| **ok).count() as u32;
+
+        if failures == 0 {
+            return (!outcomes.is_empty()).then_some(true);
+        }
+        if streak >= self.confirm_runs {
+            return Some(true);
         }
+        if failures > self.retries {
+            return Some(false);
+        }
+        None
     }
 }
 
@@ -112,6 +95,18 @@ pub struct TestTool {
     project_root: PathBuf,
     /// Override language (if set, skips detection)
     language_override: Option<Language>,
+    /// Re-run policy for flaky tests
+    flaky_policy: FlakyRetryPolicy,
+    /// Parallel job count passed to the test command (`None` = tool default)
+    jobs: Option<usize>,
+    /// Variables for the test commands (configured and per-task)
+    exec_env: Arc<ExecEnv>,
+    /// Completion evidence of the task in progress (shared with the task tool)
+    gates: Option<Arc<CompletionGates>>,
+    /// Files the task in progress touched (shared with the task and file tools)
+    scope: Option<Arc<FileScope>>,
+    /// Languages of those files, by extension
+    extensions: ExtensionMap,
 }
 
 impl TestTool {
@@ -120,9 +115,52 @@ impl TestTool {
         Self {
             project_root: project_root.into(),
             language_override: None,
+            flaky_policy: FlakyRetryPolicy::default(),
+            jobs: None,
+            exec_env: Arc::new(ExecEnv::default()),
+            gates: None,
+            scope: None,
+            extensions: ExtensionMap::default(),
         }
     }
 
+    /// Pick the test command by the files the task in progress touched.
+    ///
+    /// See [`ExtensionMap::task_language`]; without touched files the
+    /// project's language is used.
+    pub fn with_scope(mut self, scope: Arc<FileScope>, extensions: ExtensionMap) -> Self {
+        self.scope = Some(scope);
+        self.extensions = extensions;
+        self
+    }
+
+    /// Set the environment variables injected into test commands.
+    pub fn with_exec_env(mut self, exec_env: Arc<ExecEnv>) -> Self {
+        self.exec_env = exec_env;
+        self
+    }
+
+    /// Record each test verdict in `gates`.
+    ///
+    /// Only runs of the whole suite count as passing evidence; a failing run
+    /// of any path counts against the task.
+    pub fn with_gates(mut self, gates: Arc<CompletionGates>) -> Self {
+        self.gates = Some(gates);
+        self
+    }
+
+    /// Set the re-run policy for flaky tests.
+    pub fn with_flaky_policy(mut self, policy: FlakyRetryPolicy) -> Self {
+        self.flaky_policy = policy;
+        self
+    }
+
+    /// Limit the build tool's parallel jobs (see [`Language::jobs_flag`]).
+    pub fn with_jobs(mut self, jobs: Option<usize>) -> Self {
+        self.jobs = jobs;
+        self
+    }
+
     /// Set a language override (skips auto-detection).
     pub fn with_language(mut self, language: Language) -> Self {
         self.language_override = Some(language);
@@ -135,48 +173,19 @@ impl TestTool {
         if let Some(lang) = self.language_override {
             return lang;
         }
+        crate::language::detect_language(&self.project_root).unwrap_or(Language::Unknown)
+    }
 
-        // Check for language-specific files
-        let root = &self.project_root;
-
-        // Rust: Cargo.toml
-        if root.join("Cargo.toml").exists() {
-            return Language::Rust;
-        }
-
-        // Python: pyproject.toml, setup.py, requirements.txt
-        if root.join("pyproject.toml").exists()
-            || root.join("setup.py").exists()
-            || root.join("requirements.txt").exists()
-        {
-            return Language::Python;
-        }
-
-        // TypeScript: tsconfig.json
-        if root.join("tsconfig.json").exists() {
-            return Language::TypeScript;
-        }
-
-        // JavaScript: package.json (without tsconfig)
-        if root.join("package.json").exists() {
-            // Check if it's TypeScript by looking for tsconfig
-            if !root.join("tsconfig.json").exists() {
-                return Language::JavaScript;
-            }
-            return Language::TypeScript;
-        }
-
-        // Go: go.mod
-        if root.join("go.mod").exists() {
-            return Language::Go;
+    /// Language of the task in progress: that of the files it touched, or the project's.
+    pub fn task_language(&self) -> Language {
+        let project = self.detect_language();
+        if self.language_override.is_some() {
+            return project;
         }
-
-        // Java: pom.xml or build.gradle
-        if root.join("pom.xml").exists() || root.join("build.gradle").exists() {
-            return Language::Java;
+        match &self.scope {
+            Some(scope) => self.extensions.task_language(&scope.touched(), project),
+            None => project,
         }
-
-        Language::Unknown
     }
 
     /// Run tests and return results.
@@ -189,6 +198,12 @@ impl TestTool {
             .test_command()
             .ok_or_else(|| format!("No test command for language: {}", language))?;
 
+        // Cap the tool's own parallelism, right after the subcommand
+        let jobs = self.jobs.map(|n| n.to_string());
+        if let (Some(flag), Some(jobs)) = (language.jobs_flag(), jobs.as_deref()) {
+            args.splice(1..1, [flag, jobs]);
+        }
+
         // Add test path if specified
         if let Some(path) = test_path {
             match language {
@@ -226,50 +241,60 @@ impl TestTool {
             let pkg_json = self.project_root.join("package.json");
             let node_modules = self.project_root.join("node_modules");
             if pkg_json.exists() && !node_modules.exists() {
-                let install_future = Command::new("npm")
+                let mut install_command = Command::new("npm");
+                install_command
                     .args(["install", "--prefer-offline"])
                     .current_dir(&self.project_root)
                     .stdout(Stdio::piped())
-                    .stderr(Stdio::piped())
-                    .output();
+                    .stderr(Stdio::piped());
+                self.exec_env.apply(&mut install_command);
+                let install_future = install_command.output();
 
                 let install = tokio::time::timeout(
                     std::time::Duration::from_secs(timeout_secs),
                     install_future,
                 )
                 .await
-                .map_err(|_| format!("npm install timed out after {}s", timeout_secs))?
+                .map_err(|_| format!(
+                    "npm install timed out after {}",
+                    crate::timefmt::duration(std::time::Duration::from_secs(timeout_secs))
+                ))?
                 .map_err(|e| format!("Failed to run npm install: {}", e))?;
 
                 if !install.status.success() {
-                    let stderr = String::from_utf8_lossy(&install.stderr);
+                    let stderr = self.exec_env.decode(&install.stderr, "stderr");
                     return Err(format!("npm install failed: {}", stderr));
                 }
             }
         }
 
         // Execute the command with a timeout
-        let child = Command::new(cmd)
-            .args(&args)
-            .current_dir(&self.project_root)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output();
-
-        let output = tokio::time::timeout(
-            std::time::Duration::from_secs(timeout_secs),
-            child,
-        )
-        .await
-        .map_err(|_| format!("Test command timed out after {}s: {} {}", timeout_secs, cmd, args.join(" ")))?
-        .map_err(|e| format!("Failed to execute test command: {}", e))?;
-
-        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-        let success = output.status.success();
+        let mut command = Command::new(cmd);
+        command.args(&args).current_dir(&self.project_root);
+
+        let timeout = std::time::Duration::from_secs(timeout_secs);
+        let output = self.exec_env.run(&mut command, timeout).await.map_err(|e| {
+            if e.kind() == std::io::ErrorKind::TimedOut {
+                format!(
+                    "Test command timed out after {}: {} {}",
+                    crate::timefmt::duration(timeout),
+                    cmd,
+                    args.join(" ")
+                )
+            } else {
+                format!("Failed to execute test command: {}", e)
+            }
+        })?;
+
+        // Output goes back to the model, so secret values are scrubbed first.
+        // Decoding is lossy: a stray non-UTF-8 byte must not fail the run.
+        let stdout = self.exec_env.decode(&output.stdout, "stdout");
+        let stderr = self.exec_env.decode(&output.stderr, "stderr");
+        let success = output.status.success() && output.breach.is_none();
 
         // Parse test results from output
-        let results = parse_test_output(&stdout, &stderr, language);
+        let mut results = parse_test_output(&stdout, &stderr, language);
+        results.duration_ms = Some(output.usage.wall_ms);
 
         Ok(TestRunResult {
             success,
@@ -279,9 +304,20 @@ impl TestTool {
             stdout,
             stderr,
             results,
+            resources: output.usage,
+            limit_exceeded: output.breach,
         })
     }
 
+    /// Run the project's whole test suite once, with language auto-detection.
+    pub async fn run_suite(&self) -> Result<TestRunResult, String> {
+        let language = self.detect_language();
+        if language == Language::Unknown {
+            return Err("Could not detect project language".to_string());
+        }
+        self.run_tests(language, None).await
+    }
+
     /// Check if tests exist for the project.
     fn check_tests_exist(&self, language: Language) -> bool {
         let root = &self.project_root;
@@ -326,6 +362,10 @@ pub struct TestRunResult {
     pub stdout: String,
     pub stderr: String,
     pub results: TestResults,
+    /// What the test command used
+    pub resources: ResourceUsage,
+    /// The resource limit the tests went over (they were stopped)
+    pub limit_exceeded: Option<LimitBreach>,
 }
 
 /// Parse test output to extract results.
@@ -562,11 +602,11 @@ impl Tool for TestTool {
         // Start timing
         let _timing = start_timing(format!("test_tool_{}", operation));
 
-        // Get language (override or detect)
+        // Get language (argument, override, or from the task's files and the project)
         let language = if let Some(lang_str) = args["language"].as_str() {
             Language::parse(lang_str)
         } else {
-            self.detect_language()
+            self.task_language()
         };
 
         info!(operation = %operation, language = %language, "Executing test tool");
@@ -604,16 +644,35 @@ impl Tool for TestTool {
                 let _test_guard = test_span.enter();
 
                 let test_path = args["path"].as_str();
-                let result = self
-                    .run_tests(language, test_path)
-                    .await
-                    .map_err(adk_rust::AdkError::Tool)?;
+                let mut outcomes = Vec::new();
+                let (result, passed) = loop {
+                    let result = self
+                        .run_tests(language, test_path)
+                        .await
+                        .map_err(adk_rust::AdkError::Tool)?;
+                    outcomes.push(result.success);
+
+                    match self.flaky_policy.verdict(&outcomes) {
+                        Some(passed) => break (result, passed),
+                        None => info!(run = outcomes.len(), "Re-running tests for flaky check"),
+                    }
+                };
+                let failed_runs = outcomes.iter().filter(|ok| !**ok).count();
+                let passed_after_retry = passed && failed_runs > 0;
+                if let Some(gates) = &self.gates {
+                    if test_path.is_none() || !passed {
+                        gates.record_test_run(passed);
+                    }
+                }
 
                 // Log test results event
                 log_test_results(result.results.passed, result.results.failed, result.results.skipped);
 
                 Ok(json!({
-                    "success": result.success,
+                    "success": passed,
+                    "runs": outcomes.len(),
+                    "failed_runs": failed_runs,
+                    "passed_after_retry": passed_after_retry,
                     "language": result.language.to_string(),
                     "framework": result.framework,
                     "command": result.command,
@@ -626,7 +685,18 @@ impl Tool for TestTool {
                     },
                     "stdout": result.stdout,
                     "stderr": result.stderr,
-                    "message": if result.success {
+                    "resources": result.resources,
+                    "limit_exceeded": result.limit_exceeded,
+                    "message": if let Some(breach) = &result.limit_exceeded {
+                        breach.describe("Your tests")
+                    } else if passed_after_retry {
+                        format!(
+                            "Tests passed after retry ({} of {} runs failed, likely flaky): {}",
+                            failed_runs,
+                            outcomes.len(),
+                            result.results
+                        )
+                    } else if passed {
                         format!("All tests passed: {}", result.results)
                     } else {
                         format!("Tests failed: {}", result.results)
@@ -648,6 +718,8 @@ impl std::fmt::Debug for TestTool {
         f.debug_struct("TestTool")
             .field("project_root", &self.project_root)
             .field("language_override", &self.language_override)
+            .field("flaky_policy", &self.flaky_policy)
+            .field("jobs", &self.jobs)
             .finish()
     }
 }
@@ -704,4 +776,51 @@ FAIL
         assert_eq!(results.failed, 1);
         assert_eq!(results.skipped, 2);
     }
+
+    #[test]
+    fn test_flaky_policy_default_is_single_run() {
+        let policy = FlakyRetryPolicy::default();
+        assert_eq!(policy.verdict(&[]), None);
+        assert_eq!(policy.verdict(&[true]), Some(true));
+        assert_eq!(policy.verdict(&[false]), Some(false));
+    }
+
+    #[test]
+    fn test_flaky_policy_any_green() {
+        let policy = FlakyRetryPolicy::new(2, 1);
+        assert_eq!(policy.verdict(&[false]), None);
+        assert_eq!(policy.verdict(&[false, true]), Some(true));
+        assert_eq!(policy.verdict(&[false, false]), None);
+        assert_eq!(policy.verdict(&[false, false, false]), Some(false));
+    }
+
+    #[test]
+    fn test_flaky_policy_consecutive_confirmation() {
+        let policy = FlakyRetryPolicy::new(2, 2);
+        // A clean first run needs no confirmation
+        assert_eq!(policy.verdict(&[true]), Some(true));
+        // After a failure, two greens in a row are required
+        assert_eq!(policy.verdict(&[false, true]), None);
+        assert_eq!(policy.verdict(&[false, true, true]), Some(true));
+        assert_eq!(policy.verdict(&[false, true, false]), None);
+        assert_eq!(policy.verdict(&[false, true, false, false]), Some(false));
+    }
+
+    #[test]
+    fn test_task_language_follows_touched_files() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("Cargo.toml"), "[package]").unwrap();
+        let scope = Arc::new(FileScope::new(None));
+        let tool = TestTool::new(temp_dir.path()).with_scope(scope.clone(), ExtensionMap::default());
+
+        assert_eq!(tool.task_language(), Language::Rust);
+        scope.begin("T-007", Vec::new());
+        scope.admit("scripts/etl.py").unwrap();
+        scope.admit("scripts/test_etl.py").unwrap();
+        assert_eq!(tool.task_language(), Language::Python);
+
+        // An explicit language still wins
+        let tool = tool.with_language(Language::Go);
+        assert_eq!(tool.task_language(), Language::Go);
+    }
 }
@@ -110,7 +110,7 @@ impl Tool for GetTimeTool {
             let now: DateTime<Utc> = Utc::now();
             
             Ok(json!({
-                "datetime": now.to_rfc3339(),
+                "datetime": crate::timefmt::timestamp(now),
                 "formatted": now.format("%A, %B %d, %Y at %I:%M %p UTC").to_string(),
                 "date": now.format("%Y-%m-%d").to_string(),
                 "time": now.format("%H:%M:%S").to_string(),
@@ -0,0 +1,659 @@
+//! Pre-run validation of the plan against the design.
+//!
+//! `ralph validate` loads design.md and tasks.json and runs every
+//! cross-check in one pass, so a broken plan is caught before the loop
+//! spends model calls on it:
+//!
+//! - **Dependencies**: task IDs are unique and every dependency exists
+//! - **Cycles**: no task depends on itself, directly or transitively
+//! - **Components**: every component's file is created by some task
+//! - **Files**: every file in the design's structure is created or modified by some task
+//! - **Stories**: when a PRD is available, every user story has a task and
+//!   every task cites a known story
+//! - **Test helpers**: a warning, never a failure, when test files already in
+//!   the project define near-identical helper functions, with a suggested
+//!   follow-up task moving them into the shared helper module
+
+use crate::error::{RalphError, Result};
+use crate::models::{DesignDocument, PrdDocument, TaskList};
+use crate::scope::normalize_path;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// Outcome of one cross-check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationCheck {
+    /// Short name of the check
+    pub name: &'static str,
+    /// Problems found; empty when the check passed
+    pub problems: Vec<String>,
+    /// Findings worth a look that do not fail the check
+    pub warnings: Vec<String>,
+    /// Why the check did not run, if it was skipped
+    pub skipped: Option<String>,
+}
+
+impl ValidationCheck {
+    fn new(name: &'static str, problems: Vec<String>) -> Self {
+        Self { name, problems, warnings: Vec::new(), skipped: None }
+    }
+
+    fn warning(name: &'static str, warnings: Vec<String>) -> Self {
+        Self { name, problems: Vec::new(), warnings, skipped: None }
+    }
+
+    fn skipped(name: &'static str, reason: impl Into<String>) -> Self {
+        Self { name, problems: Vec::new(), warnings: Vec::new(), skipped: Some(reason.into()) }
+    }
+
+    /// Whether the check found no problems.
+    pub fn passed(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// Results of every cross-check, in the order they ran.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ValidationReport {
+    /// One entry per check
+    pub checks: Vec<ValidationCheck>,
+}
+
+impl ValidationReport {
+    /// Whether every check passed (skipped checks count as passed).
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(ValidationCheck::passed)
+    }
+
+    /// Checks that found problems.
+    pub fn failures(&self) -> Vec<&ValidationCheck> {
+        self.checks.iter().filter(|c| !c.passed()).collect()
+    }
+
+    /// The check named `name`, if it ran.
+    pub fn check(&self, name: &str) -> Option<&ValidationCheck> {
+        self.checks.iter().find(|c| c.name == name)
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            match (&check.skipped, check.passed()) {
+                (Some(reason), _) => writeln!(f, "SKIP {} ({})", check.name, reason)?,
+                (None, true) if !check.warnings.is_empty() => {
+                    writeln!(f, "WARN {}", check.name)?;
+                    for warning in &check.warnings {
+                        writeln!(f, "     - {}", warning)?;
+                    }
+                }
+                (None, true) => writeln!(f, "PASS {}", check.name)?,
+                (None, false) => {
+                    writeln!(f, "FAIL {}", check.name)?;
+                    for problem in &check.problems {
+                        writeln!(f, "     - {}", problem)?;
+                    }
+                }
+            }
+        }
+        let failed = self.failures().len();
+        if failed == 0 {
+            write!(f, "Plan is valid ({} checks)", self.checks.len())
+        } else {
+            write!(f, "Plan is invalid: {} of {} checks failed", failed, self.checks.len())
+        }
+    }
+}
+
+/// Validate `tasks_path` against `design_path`.
+///
+/// Story coverage runs when a `prd.md` sits next to the design; use
+/// [`validate_project_with_prd`] to point at a PRD elsewhere. Files that
+/// cannot be read or parsed are errors; everything else is reported.
+pub fn validate_project(design_path: &Path, tasks_path: &Path) -> Result<ValidationReport> {
+    let prd_path = design_path.with_file_name("prd.md");
+    let prd_path = prd_path.exists().then_some(prd_path);
+    validate_project_with_prd(design_path, tasks_path, prd_path.as_deref())
+}
+
+/// Validate `tasks_path` against `design_path` and, if given, `prd_path`.
+pub fn validate_project_with_prd(
+    design_path: &Path,
+    tasks_path: &Path,
+    prd_path: Option<&Path>,
+) -> Result<ValidationReport> {
+    let design = DesignDocument::load_markdown(design_path).map_err(RalphError::Design)?;
+    // Parsed without `TaskList::load`'s validation so integrity problems
+    // end up in the report instead of aborting it
+    let content = fs::read_to_string(tasks_path).map_err(|e| {
+        RalphError::Task(format!("Failed to read tasks file '{}': {}", tasks_path.display(), e))
+    })?;
+    let tasks: TaskList = serde_json::from_str(&content).map_err(|e| {
+        RalphError::Task(format!("Failed to parse tasks JSON '{}': {}", tasks_path.display(), e))
+    })?;
+    let prd = prd_path
+        .map(|path| PrdDocument::load_markdown(path).map_err(RalphError::Prd))
+        .transpose()?;
+    let mut report = validate_plan(&design, &tasks, prd.as_ref());
+    let project_dir = design_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let consolidate_into = design
+        .test_infrastructure
+        .as_ref()
+        .and_then(|infra| infra.helper_module.as_deref());
+    report.checks.push(ValidationCheck::warning(
+        "test helpers",
+        duplicate_helper_warnings(project_dir, consolidate_into),
+    ));
+    Ok(report)
+}
+
+/// Run every cross-check on documents already in memory.
+pub fn validate_plan(
+    design: &DesignDocument,
+    tasks: &TaskList,
+    prd: Option<&PrdDocument>,
+) -> ValidationReport {
+    let mut checks = vec![
+        ValidationCheck::new("dependencies", dependency_problems(tasks)),
+        ValidationCheck::new("cycles", cycle_problems(tasks)),
+    ];
+
+    checks.push(if design.components.is_empty() {
+        ValidationCheck::skipped("components", "design lists no components")
+    } else {
+        let problems = design
+            .uncovered_components(tasks)
+            .into_iter()
+            .map(|c| {
+                format!(
+                    "component '{}' ({}) is not created by any task",
+                    c.name,
+                    c.file_path.as_deref().unwrap_or_default()
+                )
+            })
+            .collect();
+        ValidationCheck::new("components", problems)
+    });
+
+    checks.push(match &design.file_structure {
+        None => ValidationCheck::skipped("files", "design has no file structure"),
+        Some(structure) => {
+            ValidationCheck::new("files", file_problems(&structure.file_paths(), tasks))
+        }
+    });
+
+    checks.push(match prd {
+        None => ValidationCheck::skipped("stories", "no PRD found"),
+        Some(prd) => ValidationCheck::new("stories", story_problems(prd, tasks)),
+    });
+
+    ValidationReport { checks }
+}
+
+fn dependency_problems(tasks: &TaskList) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut seen = HashSet::new();
+    for task in tasks.get_all_tasks() {
+        if !seen.insert(task.id.as_str()) {
+            problems.push(format!("duplicate task ID {}", task.id));
+        }
+    }
+    for task in tasks.get_all_tasks() {
+        for dep in &task.dependencies {
+            if !seen.contains(dep.as_str()) {
+                problems.push(format!("{} depends on unknown task {}", task.id, dep));
+            }
+        }
+    }
+    problems
+}
+
+/// Each dependency cycle once, as `A -> B -> A`.
+fn cycle_problems(tasks: &TaskList) -> Vec<String> {
+    let graph: BTreeMap<&str, Vec<&str>> = tasks
+        .get_all_tasks()
+        .iter()
+        .map(|t| (t.id.as_str(), t.dependencies.iter().map(String::as_str).collect()))
+        .collect();
+
+    // 0 = unvisited, 1 = on the current path, 2 = finished
+    let mut state: HashMap<&str, u8> = HashMap::new();
+    let mut problems = Vec::new();
+    for &start in graph.keys() {
+        if state.get(start).copied().unwrap_or(0) != 0 {
+            continue;
+        }
+        // The stack holds the current path and the next dependency to visit at each step
+        let mut stack = vec![(start, 0usize)];
+        state.insert(start, 1);
+        while let Some(&(node, next)) = stack.last() {
+            let deps = graph.get(node).map(Vec::as_slice).unwrap_or_default();
+            let Some(&dep) = deps.get(next) else {
+                state.insert(node, 2);
+                stack.pop();
+                continue;
+            };
+            if let Some(top) = stack.last_mut() {
+                top.1 += 1;
+            }
+            match state.get(dep).copied().unwrap_or(0) {
+                0 if graph.contains_key(dep) => {
+                    state.insert(dep, 1);
+                    stack.push((dep, 0));
+                }
+                1 => {
+                    let from = stack.iter().position(|&(n, _)| n == dep).unwrap_or(0);
+                    let mut cycle: Vec<&str> = stack[from..].iter().map(|&(n, _)| n).collect();
+                    cycle.push(dep);
+                    problems.push(format!("dependency cycle {}", cycle.join(" -> ")));
+                }
+                _ => {}
+            }
+        }
+    }
+    problems
+}
+
+fn file_problems(planned: &[String], tasks: &TaskList) -> Vec<String> {
+    let touched: HashSet<String> = tasks
+        .get_all_tasks()
+        .iter()
+        .flat_map(|t| t.files_created.iter().chain(&t.files_modified))
+        .map(|path| normalize_path(path))
+        .collect();
+    planned
+        .iter()
+        .filter(|path| !touched.contains(&normalize_path(path)))
+        .map(|path| format!("{} is in the file structure but no task creates it", path))
+        .collect()
+}
+
+fn story_problems(prd: &PrdDocument, tasks: &TaskList) -> Vec<String> {
+    let stories: HashSet<&str> = prd.user_stories.iter().map(|s| s.id.as_str()).collect();
+    let cited: HashSet<&str> = tasks
+        .get_all_tasks()
+        .iter()
+        .filter_map(|t| t.user_story_id.as_deref())
+        .collect();
+
+    let mut problems: Vec<String> = prd
+        .user_stories
+        .iter()
+        .filter(|s| !cited.contains(s.id.as_str()))
+        .map(|s| format!("user story {} ({}) has no task", s.id, s.title))
+        .collect();
+    for task in tasks.get_all_tasks() {
+        if let Some(story) = task.user_story_id.as_deref().filter(|s| !stories.contains(s)) {
+            problems.push(format!("{} cites unknown user story {}", task.id, story));
+        }
+    }
+    problems
+}
+
+/// Shortest helper body, in distinct tokens, worth comparing.
+const MIN_HELPER_TOKENS: usize = 12;
+
+/// Token overlap (Jaccard index) at which two helpers count as near-identical.
+const HELPER_SIMILARITY: f64 = 0.8;
+
+/// Directories never searched for test files.
+pub(crate) const SKIPPED_DIRS: &[&str] = &["target", "node_modules", "vendor", "dist", "build", "venv"];
+
+/// A helper function defined in a test file.
+#[derive(Debug)]
+struct TestHelper {
+    file: String,
+    name: String,
+    tokens: HashSet<String>,
+}
+
+/// Warnings for near-identical helpers defined in more than one test file.
+///
+/// Test functions themselves are ignored. Each pair is named once, with a
+/// suggested follow-up task moving the helper into `consolidate_into` (or a
+/// shared helper module, when the design plans none).
+fn duplicate_helper_warnings(project_dir: &Path, consolidate_into: Option<&str>) -> Vec<String> {
+    let mut files = Vec::new();
+    collect_test_files(project_dir, project_dir, &mut files);
+    files.sort();
+    let helpers: Vec<TestHelper> = files
+        .iter()
+        .filter_map(|(rel, path)| Some((rel, fs::read_to_string(path).ok()?)))
+        .flat_map(|(rel, content)| extract_helpers(rel, &content))
+        .collect();
+
+    let target = consolidate_into.map_or_else(
+        || "a shared test helper module".to_string(),
+        |module| format!("`{}`", module),
+    );
+    let mut warnings = Vec::new();
+    for (i, a) in helpers.iter().enumerate() {
+        for b in &helpers[i + 1..] {
+            if a.file == b.file {
+                continue;
+            }
+            let similarity = jaccard(&a.tokens, &b.tokens);
+            if similarity >= HELPER_SIMILARITY {
+                warnings.push(format!(
+                    "`{}` in {} and `{}` in {} are {:.0}% similar; add a follow-up task \
+                     moving them into {}",
+                    a.name,
+                    a.file,
+                    b.name,
+                    b.file,
+                    similarity * 100.0,
+                    target
+                ));
+            }
+        }
+    }
+    warnings
+}
+
+/// Test files under `dir`, as (path relative to `root`, full path).
+fn collect_test_files(root: &Path, dir: &Path, files: &mut Vec<(String, std::path::PathBuf)>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if path.is_dir() {
+            if !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_str()) {
+                collect_test_files(root, &path, files);
+            }
+            continue;
+        }
+        let rel = path.strip_prefix(root).unwrap_or(path.as_path());
+        let rel = rel.to_string_lossy().replace('\\', "/");
+        if is_test_file(&rel) {
+            files.push((rel, path));
+        }
+    }
+}
+
+/// Whether a project-relative path looks like a test file.
+fn is_test_file(rel: &str) -> bool {
+    let name = rel.rsplit('/').next().unwrap_or(rel);
+    let in_test_dir = rel
+        .split('/')
+        .rev()
+        .skip(1)
+        .any(|dir| matches!(dir, "tests" | "test" | "__tests__" | "spec"));
+    let source = [".rs", ".py", ".go", ".ts", ".js", ".java"].iter().any(|ext| name.ends_with(ext));
+    source
+        && (in_test_dir
+            || name.starts_with("test_")
+            || name.ends_with("_test.py")
+            || name.ends_with("_test.go")
+            || name.contains(".test.")
+            || name.contains(".spec.")
+            || name.ends_with("Test.java"))
+}
+
+/// Helper functions defined in a test file, with the tokens of their bodies.
+///
+/// A definition runs until the next line indented no deeper than it (a closing
+/// brace at its own indentation included). Functions named `test...` or marked
+/// with a `#[...test]` attribute are tests, not helpers.
+fn extract_helpers(file: &str, content: &str) -> Vec<TestHelper> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut helpers = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let Some(name) = definition_name(line) else {
+            i += 1;
+            continue;
+        };
+        let indent = line.len() - line.trim_start().len();
+        let mut end = i + 1;
+        while end < lines.len() {
+            let next = lines[end];
+            let next_indent = next.len() - next.trim_start().len();
+            if next.trim().is_empty() || next_indent > indent {
+                end += 1;
+            } else {
+                if next.trim_start().starts_with('}') {
+                    end += 1;
+                }
+                break;
+            }
+        }
+
+        let attribute = lines[..i].iter().rev().find(|l| !l.trim().is_empty());
+        let is_test = name.to_lowercase().starts_with("test")
+            || attribute.is_some_and(|l| l.trim_start().starts_with("#[") && l.contains("test"));
+        if !is_test {
+            let tokens: HashSet<String> = lines[i + 1..end]
+                .iter()
+                .flat_map(|l| l.split(|c: char| !c.is_alphanumeric() && c != '_'))
+                .filter(|t| !t.is_empty())
+                .map(String::from)
+                .collect();
+            if tokens.len() >= MIN_HELPER_TOKENS {
+                helpers.push(TestHelper { file: file.to_string(), name, tokens });
+            }
+        }
+        i = end.max(i + 1);
+    }
+    helpers
+}
+
+/// The function name a line defines, if it starts a function definition.
+fn definition_name(line: &str) -> Option<String> {
+    let trimmed = line.trim_start();
+    let keywords = [
+        "pub(crate) fn ",
+        "pub fn ",
+        "async fn ",
+        "fn ",
+        "async def ",
+        "def ",
+        "function ",
+        "func ",
+    ];
+    let rest = keywords.iter().find_map(|keyword| trimmed.strip_prefix(keyword))?;
+    let name: String = rest.chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect();
+    (!name.is_empty()).then_some(name)
+}
+
+/// Shared tokens over all tokens of the two sets.
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Component, FileStructure, Task, UserStory};
+    use tempfile::tempdir;
+
+    fn task(id: &str, deps: &[&str], creates: &[&str]) -> Task {
+        let mut task = Task::new(id, id, "", 1);
+        task.dependencies = deps.iter().map(|d| d.to_string()).collect();
+        task.files_created = creates.iter().map(|f| f.to_string()).collect();
+        task
+    }
+
+    fn design() -> DesignDocument {
+        let mut design = DesignDocument::new("calc", "A calculator");
+        let mut parser = Component::new("Parser", "Parses input");
+        parser.file_path = Some("src/parser.rs".to_string());
+        design.add_component(parser);
+        let mut root = FileStructure::directory("calc", "");
+        let mut src = FileStructure::directory("src", "");
+        src.add_child(FileStructure::file("main.rs", ""));
+        src.add_child(FileStructure::file("parser.rs", ""));
+        root.add_child(src);
+        design.file_structure = Some(root);
+        design
+    }
+
+    #[test]
+    fn test_valid_plan_passes_every_check() {
+        let mut tasks = TaskList::new("calc", "rust");
+        tasks.add_task(task("T-001", &[], &["src/main.rs"]));
+        tasks.add_task(task("T-002", &["T-001"], &["./src/parser.rs"]));
+
+        let report = validate_plan(&design(), &tasks, None);
+        assert!(report.passed(), "{}", report);
+        assert!(report.check("stories").unwrap().skipped.is_some());
+    }
+
+    #[test]
+    fn test_reports_every_problem_at_once() {
+        let mut tasks = TaskList::new("calc", "rust");
+        tasks.add_task(task("T-001", &["T-003"], &["src/main.rs"]));
+        tasks.add_task(task("T-002", &["T-001", "T-404"], &[]));
+        tasks.add_task(task("T-003", &["T-002"], &[]));
+        tasks.add_task(task("T-004", &[], &[]));
+        tasks.add_task(task("T-004", &[], &[]));
+
+        let report = validate_plan(&design(), &tasks, None);
+        assert!(!report.passed());
+        assert_eq!(
+            report.check("dependencies").unwrap().problems,
+            vec!["duplicate task ID T-004", "T-002 depends on unknown task T-404"]
+        );
+        assert_eq!(
+            report.check("cycles").unwrap().problems,
+            vec!["dependency cycle T-001 -> T-003 -> T-002 -> T-001"]
+        );
+        assert_eq!(report.check("components").unwrap().problems.len(), 1);
+        assert_eq!(
+            report.check("files").unwrap().problems,
+            vec!["src/parser.rs is in the file structure but no task creates it"]
+        );
+        assert!(report.to_string().ends_with("4 of 5 checks failed"));
+    }
+
+    #[test]
+    fn test_story_coverage() {
+        let mut prd = PrdDocument::new("calc", "A calculator");
+        prd.user_stories.push(UserStory::new("US-001", "Add", "As a user...", 1));
+        prd.user_stories.push(UserStory::new("US-002", "Divide", "As a user...", 2));
+        let mut tasks = TaskList::new("calc", "rust");
+        let mut add = task("T-001", &[], &[]);
+        add.user_story_id = Some("US-001".to_string());
+        let mut stray = task("T-002", &[], &[]);
+        stray.user_story_id = Some("US-009".to_string());
+        tasks.add_task(add);
+        tasks.add_task(stray);
+
+        assert_eq!(
+            story_problems(&prd, &tasks),
+            vec![
+                "user story US-002 (Divide) has no task",
+                "T-002 cites unknown user story US-009"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_project_reads_the_files() {
+        let dir = tempdir().unwrap();
+        let design_path = dir.path().join("design.md");
+        let tasks_path = dir.path().join("tasks.json");
+        fs::write(&design_path, design().to_markdown()).unwrap();
+        // An unknown dependency would make `TaskList::load` fail outright
+        let mut tasks = TaskList::new("calc", "rust");
+        tasks.add_task(task("T-001", &["T-000"], &["src/main.rs", "src/parser.rs"]));
+        fs::write(&tasks_path, serde_json::to_string(&tasks).unwrap()).unwrap();
+
+        let report = validate_project(&design_path, &tasks_path).unwrap();
+        assert_eq!(report.failures().len(), 1);
+        assert_eq!(report.failures()[0].name, "dependencies");
+        assert!(report.check("components").unwrap().passed());
+        assert!(report.check("files").unwrap().skipped.is_none());
+
+        assert!(validate_project(&dir.path().join("missing.md"), &tasks_path).is_err());
+    }
+
+    const USER_FACTORY: &str = "\
+fn make_user(name: &str) -> User {
+    let mut user = User::new(name);
+    user.email = format!(\"{}@example.com\", name);
+    user.active = true;
+    user.roles.push(Role::Member);
+    user
+}
+";
+
+    #[test]
+    fn test_extract_helpers_skips_tests_and_short_helpers() {
+        let content = format!(
+            "use super::*;\n\n{}\n#[test]\nfn creates_user() {{\n    let user = make_user(\"a\");\n    \
+             assert!(user.active);\n    assert_eq!(user.roles.len(), 1);\n    \
+             assert_eq!(user.email, \"a@example.com\");\n}}\n\nfn two() -> u32 {{\n    2\n}}\n",
+            USER_FACTORY
+        );
+        let helpers = extract_helpers("tests/users.rs", &content);
+        assert_eq!(helpers.len(), 1);
+        assert_eq!(helpers[0].name, "make_user");
+
+        let python = "def build_order(items):\n    order = Order()\n    for item in items:\n        \
+                      order.add(item, quantity=1, price=item.price)\n    order.total = sum(i.price for i in items)\n    \
+                      return order\n\ndef test_order():\n    assert build_order([]).total == 0\n";
+        let helpers = extract_helpers("tests/test_orders.py", python);
+        let names: Vec<&str> = helpers.iter().map(|h| h.name.as_str()).collect();
+        assert_eq!(names, vec!["build_order"]);
+    }
+
+    #[test]
+    fn test_is_test_file() {
+        let test_files =
+            ["tests/users.rs", "tests/common/mod.rs", "test_api.py", "pkg/api_test.go", "src/app.test.ts"];
+        for path in test_files {
+            assert!(is_test_file(path), "{}", path);
+        }
+        for path in ["src/main.rs", "tests/fixtures.json", "testing.py"] {
+            assert!(!is_test_file(path), "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_warns_about_duplicated_test_helpers() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("tests")).unwrap();
+        fs::write(dir.path().join("tests/users.rs"), USER_FACTORY).unwrap();
+        fs::write(dir.path().join("tests/admin.rs"), USER_FACTORY.replace("make_user", "new_user")).unwrap();
+        fs::write(dir.path().join("tests/orders.rs"), "fn total() -> u32 {\n    0\n}\n").unwrap();
+        // Helpers outside test files and in skipped directories are not compared
+        fs::create_dir_all(dir.path().join("target/tests")).unwrap();
+        fs::write(dir.path().join("target/tests/users.rs"), USER_FACTORY).unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/users.rs"), USER_FACTORY).unwrap();
+
+        assert_eq!(
+            duplicate_helper_warnings(dir.path(), Some("tests/common/mod.rs")),
+            vec![
+                "`new_user` in tests/admin.rs and `make_user` in tests/users.rs are 100% similar; \
+                 add a follow-up task moving them into `tests/common/mod.rs`"
+            ]
+        );
+
+        let design_path = dir.path().join("design.md");
+        let tasks_path = dir.path().join("tasks.json");
+        fs::write(&design_path, design().to_markdown()).unwrap();
+        let mut tasks = TaskList::new("calc", "rust");
+        tasks.add_task(task("T-001", &[], &["src/main.rs", "src/parser.rs"]));
+        fs::write(&tasks_path, serde_json::to_string(&tasks).unwrap()).unwrap();
+        let report = validate_project(&design_path, &tasks_path).unwrap();
+        let check = report.check("test helpers").unwrap();
+        assert!(check.passed());
+        assert_eq!(check.warnings.len(), 1);
+        assert!(report.passed());
+        assert!(report.to_string().contains("WARN test helpers\n     - `new_user`"));
+        assert!(report.to_string().contains("a shared test helper module"));
+    }
+}
@@ -0,0 +1,527 @@
+//! Continuous verification of the whole project during the implementation loop.
+//!
+//! Per-task test runs only exercise the code the current task touched, so a
+//! later task can silently break an earlier one. The verifier re-runs the full
+//! test suite at the configured [`VerificationCadence`] and, on failure:
+//!
+//! 1. Extracts the failing test files from the test output
+//! 2. Identifies the suspected breaking task: the most recent commit whose task
+//!    touched the subject files of the failing tests
+//! 3. Adds a priority-1 regression-fix task and makes pending work that depends
+//!    on the suspect wait for it
+//!
+//! Phase boundaries are the default cadence. Flat task lists (no phases) use
+//! priority tiers as their boundaries.
+
+use crate::exec_env::ExecEnv;
+use crate::models::{Task, TaskList, TestResults, VerificationCadence};
+use crate::tools::{GitTool, TestTool};
+use crate::{RalphError, Result};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// How many recent commits are inspected when looking for a suspect.
+const SUSPECT_COMMIT_WINDOW: usize = 50;
+
+/// Prefix for regression-fix task IDs.
+const REGRESSION_TASK_PREFIX: &str = "REGRESSION-";
+
+/// Source file extensions recognised in test output.
+const SOURCE_EXTENSIONS: &[&str] = &[
+    "rs", "py", "ts", "tsx", "js", "jsx", "mjs", "go", "java", "kt",
+];
+
+/// Outcome of a full-suite verification run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerificationReport {
+    /// Whether the full suite passed
+    pub passed: bool,
+    /// Parsed test counts
+    pub results: TestResults,
+    /// Task suspected of introducing the regression
+    pub suspect_task: Option<String>,
+    /// ID of the regression-fix task that was created
+    pub regression_task: Option<String>,
+}
+
+/// Re-runs the full test suite at a configured cadence.
+#[derive(Debug)]
+pub struct ContinuousVerifier {
+    cadence: VerificationCadence,
+    project_path: PathBuf,
+    tasks_path: PathBuf,
+    completed_since_last: usize,
+    /// Variables for the test commands
+    exec_env: Arc<ExecEnv>,
+    /// Parallel job count for the test command
+    jobs: Option<usize>,
+}
+
+impl ContinuousVerifier {
+    /// Create a verifier for the given project and tasks file.
+    pub fn new(
+        cadence: VerificationCadence,
+        project_path: impl Into<PathBuf>,
+        tasks_path: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            cadence,
+            project_path: project_path.into(),
+            tasks_path: tasks_path.into(),
+            completed_since_last: 0,
+            exec_env: Arc::new(ExecEnv::default()),
+            jobs: None,
+        }
+    }
+
+    /// Set the environment variables injected into the suite's test command.
+    pub fn with_exec_env(mut self, exec_env: Arc<ExecEnv>) -> Self {
+        self.exec_env = exec_env;
+        self
+    }
+
+    /// Limit the test command's parallel jobs.
+    pub fn with_jobs(mut self, jobs: Option<usize>) -> Self {
+        self.jobs = jobs;
+        self
+    }
+
+    /// Get the configured cadence.
+    pub fn cadence(&self) -> VerificationCadence {
+        self.cadence
+    }
+
+    /// Notify the verifier that a task completed.
+    ///
+    /// Runs the full suite if the cadence calls for it and returns the report,
+    /// or `None` if no verification was due.
+    pub async fn on_task_completed(&mut self, task_id: &str) -> Result<Option<VerificationReport>> {
+        if self.cadence == VerificationCadence::Off {
+            return Ok(None);
+        }
+
+        self.completed_since_last += 1;
+        let list = TaskList::load(&self.tasks_path).map_err(RalphError::Task)?;
+        let at_boundary = is_phase_boundary(&list, task_id);
+        if !should_verify(self.cadence, self.completed_since_last, at_boundary) {
+            return Ok(None);
+        }
+
+        self.completed_since_last = 0;
+        self.verify().await.map(Some)
+    }
+
+    /// Run the full test suite now and record a regression task on failure.
+    pub async fn verify(&self) -> Result<VerificationReport> {
+        let run = TestTool::new(&self.project_path)
+            .with_exec_env(self.exec_env.clone())
+            .with_jobs(self.jobs)
+            .run_suite()
+            .await
+            .map_err(RalphError::Test)?;
+
+        if run.success {
+            return Ok(VerificationReport {
+                passed: true,
+                results: run.results,
+                suspect_task: None,
+                regression_task: None,
+            });
+        }
+
+        let output = format!("{}\n{}", run.stdout, run.stderr);
+        let subjects = failing_test_subjects(&output);
+        let commits = GitTool::new(&self.project_path)
+            .recent_commits(SUSPECT_COMMIT_WINDOW)
+            .unwrap_or_default();
+
+        let mut list = TaskList::load(&self.tasks_path).map_err(RalphError::Task)?;
+        let suspect = identify_suspect(&list, &subjects, &commits);
+        let regression = create_regression_task(&mut list, suspect.as_deref(), &output);
+        list.save(&self.tasks_path).map_err(RalphError::Task)?;
+
+        Ok(VerificationReport {
+            passed: false,
+            results: run.results,
+            suspect_task: suspect,
+            regression_task: Some(regression),
+        })
+    }
+}
+
+/// Decide whether a full-suite run is due.
+pub fn should_verify(cadence: VerificationCadence, completed_since_last: usize, at_boundary: bool) -> bool {
+    match cadence {
+        VerificationCadence::Off => false,
+        VerificationCadence::PhaseBoundaries => at_boundary,
+        VerificationCadence::EveryNTasks(n) => n > 0 && completed_since_last >= n,
+    }
+}
+
+/// Check whether completing `task_id` closed out its phase.
+///
+/// For tasks in the flat list, the "phase" is every flat task sharing its priority.
+pub fn is_phase_boundary(list: &TaskList, task_id: &str) -> bool {
+    for phase in &list.phases {
+        if phase.get_all_tasks().iter().any(|t| t.id == task_id) {
+            return phase.is_complete();
+        }
+    }
+
+    match list.tasks.iter().find(|t| t.id == task_id) {
+        Some(task) => list
+            .tasks
+            .iter()
+            .filter(|t| t.priority == task.priority)
+            .all(|t| t.status.is_terminal()),
+        None => false,
+    }
+}
+
+/// Extract the test file paths named by failures in test runner output.
+///
+/// Only failure lines are read (`FAILED`, `FAIL`, `--- FAIL:`, `panicked at`,
+/// `---- name stdout ----`), along with the indented detail and `at` stack
+/// frames that follow them, so paths of passing tests are left out.
+pub fn failing_test_paths(output: &str) -> Vec<String> {
+    let mut paths: Vec<String> = Vec::new();
+    let mut in_failure = false;
+    for line in output.lines() {
+        let trimmed = line.trim();
+        in_failure = if is_failure_line(trimmed) {
+            true
+        } else if is_pass_line(trimmed) {
+            false
+        } else {
+            in_failure && (line.starts_with(char::is_whitespace) || trimmed.starts_with("at "))
+        };
+        if in_failure {
+            collect_test_paths(line, &mut paths);
+        }
+    }
+    paths
+}
+
+/// Whether a trimmed output line reports a test failure.
+fn is_failure_line(line: &str) -> bool {
+    line.starts_with("FAIL")
+        || line.starts_with("--- FAIL")
+        || line.contains("FAILED")
+        || line.contains("panicked at")
+        || (line.starts_with("---- ") && line.ends_with(" stdout ----"))
+}
+
+/// Whether a trimmed output line reports a passing test.
+fn is_pass_line(line: &str) -> bool {
+    line.starts_with("PASS")
+        || line.starts_with("--- PASS")
+        || line.starts_with("ok ")
+        || line.ends_with(" ok")
+        || line.ends_with("PASSED")
+}
+
+/// Add the test file paths mentioned in one line of output.
+fn collect_test_paths(line: &str, paths: &mut Vec<String>) {
+    for token in line.split(|c: char| c.is_whitespace() || c == '(' || c == ')' || c == '"' || c == '\'') {
+        // Drop `::test_name` and `:line:col` suffixes
+        let token = token.split("::").next().unwrap_or(token);
+        let token = token.split(':').next().unwrap_or(token);
+        let token = token.trim_matches(|c: char| c == ',' || c == '.' || c == '[' || c == ']');
+
+        let Some((_, ext)) = token.rsplit_once('.') else {
+            continue;
+        };
+        if !SOURCE_EXTENSIONS.contains(&ext) {
+            continue;
+        }
+        let lower = token.to_lowercase();
+        if !(lower.contains("test") || lower.contains("spec")) {
+            continue;
+        }
+        if !paths.iter().any(|p| p == token) {
+            paths.push(token.to_string());
+        }
+    }
+}
+
+/// Subjects of all failing tests in the output.
+///
+/// Combines test file paths with Rust's `test module::tests::name ... FAILED`
+/// lines, which name modules rather than files.
+pub fn failing_test_subjects(output: &str) -> Vec<String> {
+    let mut subjects: Vec<String> = failing_test_paths(output)
+        .iter()
+        .map(|p| test_subject(p))
+        .collect();
+
+    for line in output.lines() {
+        let Some(name) = line
+            .trim()
+            .strip_prefix("test ")
+            .and_then(|l| l.strip_suffix("... FAILED"))
+        else {
+            continue;
+        };
+        let module = name.trim().split("::tests").next().unwrap_or("");
+        if let Some(subject) = module.rsplit("::").next().filter(|m| !m.is_empty()) {
+            let subject = subject.to_lowercase();
+            if !subjects.contains(&subject) {
+                subjects.push(subject);
+            }
+        }
+    }
+    subjects
+}
+
+/// Derive the module a test file exercises, e.g. `tests/test_parser.py` → `parser`.
+pub fn test_subject(path: &str) -> String {
+    let stem = Path::new(path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(path);
+    // `parser.test.ts` has a stem of `parser.test`
+    let stem = stem
+        .trim_end_matches(".test")
+        .trim_end_matches(".spec");
+    let stem = stem
+        .strip_prefix("test_")
+        .or_else(|| stem.strip_suffix("_test"))
+        .or_else(|| stem.strip_suffix("_spec"))
+        .or_else(|| stem.strip_suffix("Test"))
+        .or_else(|| stem.strip_suffix("Tests"))
+        .unwrap_or(stem);
+    stem.to_lowercase()
+}
+
+/// Find the task most likely to have broken the failing tests.
+///
+/// Walks commits newest-first and returns the first completed task whose commit
+/// touched a file with the same stem as a failing test's subject. Falls back to
+/// the task behind the newest commit when nothing matches.
+pub fn identify_suspect(list: &TaskList, subjects: &[String], commits_newest_first: &[String]) -> Option<String> {
+    let all_tasks = list.get_all_tasks();
+    let tasks_by_commit: Vec<&Task> = commits_newest_first
+        .iter()
+        .filter_map(|hash| {
+            all_tasks.iter().copied().find(|t| {
+                t.is_completed() && t.commit_hash.as_deref().is_some_and(|c| same_commit(c, hash))
+            })
+        })
+        .collect();
+
+    let touches_subject = |task: &Task| {
+        task_files(task).iter().any(|file| {
+            let stem = Path::new(file)
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or(file)
+                .to_lowercase();
+            subjects.iter().any(|s| *s == stem)
+        })
+    };
+
+    tasks_by_commit
+        .iter()
+        .find(|task| touches_subject(task))
+        .or_else(|| tasks_by_commit.first())
+        .map(|task| task.id.clone())
+}
+
+/// Add a regression-fix task and make dependent pending work wait for it.
+///
+/// Pending tasks depending on the suspect get the regression task as an extra
+/// dependency; with no suspect, every pending task waits. Returns the new task ID.
+pub fn create_regression_task(list: &mut TaskList, suspect: Option<&str>, failure_output: &str) -> String {
+    let existing = list
+        .get_all_tasks()
+        .iter()
+        .filter(|t| t.id.starts_with(REGRESSION_TASK_PREFIX))
+        .count();
+    let id = format!("{}{:03}", REGRESSION_TASK_PREFIX, existing + 1);
+
+    let excerpt: String = failure_output.chars().take(1000).collect();
+    let description = match suspect {
+        Some(suspect) => format!(
+            "The full test suite is failing. Suspected cause: changes from {}. \
+             Fix the regression without reverting that task's functionality.\n\nTest output:\n{}",
+            suspect, excerpt
+        ),
+        None => format!(
+            "The full test suite is failing and no single task could be identified as the cause.\n\nTest output:\n{}",
+            excerpt
+        ),
+    };
+
+    let mut task = Task::new(&id, "Fix regression in full test suite", description, 1);
+    if let Some(suspect) = suspect {
+        task.add_note(&format!("Suspected breaking task: {}", suspect));
+    }
+
+    for pending in list.get_all_tasks_mut() {
+        if !pending.is_pending() || pending.id.starts_with(REGRESSION_TASK_PREFIX) {
+            continue;
+        }
+        let blocked = match suspect {
+            Some(suspect) => pending.dependencies.iter().any(|d| d == suspect),
+            None => true,
+        };
+        if blocked {
+            pending.add_dependency(&id);
+        }
+    }
+
+    list.add_task(task);
+    id
+}
+
+/// Compare full and abbreviated commit hashes.
+fn same_commit(a: &str, b: &str) -> bool {
+    !a.is_empty() && !b.is_empty() && (a.starts_with(b) || b.starts_with(a))
+}
+
+/// All files a task is known to have touched.
+fn task_files(task: &Task) -> Vec<&String> {
+    let mut files: Vec<&String> = task.files_created.iter().chain(task.files_modified.iter()).collect();
+    if let Some(ref stats) = task.diff_stats {
+        files.extend(stats.files.iter());
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TaskStatus;
+
+    fn completed(id: &str, commit: &str, files: &[&str]) -> Task {
+        let mut task = Task::new(id, id, "Desc", 2);
+        for file in files {
+            task.add_file_modified(*file);
+        }
+        task.complete(Some(commit.to_string()));
+        task
+    }
+
+    #[test]
+    fn test_should_verify() {
+        assert!(!should_verify(VerificationCadence::Off, 10, true));
+        assert!(should_verify(VerificationCadence::PhaseBoundaries, 1, true));
+        assert!(!should_verify(VerificationCadence::PhaseBoundaries, 5, false));
+        assert!(!should_verify(VerificationCadence::EveryNTasks(3), 2, true));
+        assert!(should_verify(VerificationCadence::EveryNTasks(3), 3, false));
+    }
+
+    #[test]
+    fn test_phase_boundary_for_flat_tasks_uses_priority() {
+        let mut list = TaskList::new("Test", "rust");
+        list.add_task(Task::new("TASK-001", "A", "Desc", 1));
+        list.add_task(Task::new("TASK-002", "B", "Desc", 1));
+        list.add_task(Task::new("TASK-003", "C", "Desc", 2));
+
+        list.complete_task("TASK-001", None).unwrap();
+        assert!(!is_phase_boundary(&list, "TASK-001"));
+
+        list.complete_task("TASK-002", None).unwrap();
+        assert!(is_phase_boundary(&list, "TASK-002"));
+    }
+
+    #[test]
+    fn test_failing_test_paths_and_subjects() {
+        let output = "FAILED tests/test_parser.py::test_empty - AssertionError\n\
+                      --- FAIL: TestTokenize (0.00s)\n    lexer_test.go:42: unexpected token\n\
+                      at src/utils/format.spec.ts:10:5\n src/main.py ok";
+        let paths = failing_test_paths(output);
+        assert_eq!(
+            paths,
+            vec!["tests/test_parser.py", "lexer_test.go", "src/utils/format.spec.ts"]
+        );
+
+        assert_eq!(test_subject("tests/test_parser.py"), "parser");
+        assert_eq!(test_subject("lexer_test.go"), "lexer");
+        assert_eq!(test_subject("src/utils/format.spec.ts"), "format");
+        assert_eq!(test_subject("src/test/java/CalculatorTest.java"), "calculator");
+    }
+
+    #[test]
+    fn test_failing_test_paths_skip_passing_tests() {
+        let pytest = "tests/test_lexer.py::test_tokens PASSED\n\
+                      tests/test_parser.py::test_empty FAILED\n\
+                      tests/test_cli.py::test_help PASSED";
+        assert_eq!(failing_test_paths(pytest), vec!["tests/test_parser.py"]);
+
+        let jest = "PASS src/lexer.test.ts\n\
+                    FAIL src/parser.test.ts\n  \
+                      ● parser › rejects empty input\n    \
+                        at Object.<anonymous> (src/parser.test.ts:5:3)\n\
+                    PASS src/cli.spec.ts";
+        assert_eq!(failing_test_paths(jest), vec!["src/parser.test.ts"]);
+
+        let cargo = "     Running tests/lexer_test.rs (target/debug/deps/lexer_test-1a2b)\n\
+                     test tokens ... ok\n\
+                     ---- empty_input stdout ----\n\
+                     thread 'empty_input' panicked at tests/parser_test.rs:12:5:\n\
+                     assertion failed";
+        assert_eq!(failing_test_paths(cargo), vec!["tests/parser_test.rs"]);
+    }
+
+    #[test]
+    fn test_failing_rust_test_subjects() {
+        let output = "test cli::tests::parses_args ... ok\n\
+                      test models::parser::tests::handles_empty ... FAILED\n\
+                      test lexer::tests::tokens ... FAILED";
+        assert_eq!(failing_test_subjects(output), vec!["parser", "lexer"]);
+    }
+
+    #[test]
+    fn test_identify_suspect_prefers_task_touching_subject() {
+        let mut list = TaskList::new("Test", "python");
+        list.add_task(completed("TASK-001", "aaaaaaa", &["src/parser.py"]));
+        list.add_task(completed("TASK-002", "bbbbbbb", &["src/cli.py"]));
+        list.add_task(completed("TASK-003", "ccccccc", &["README.md"]));
+
+        let commits = vec![
+            "ccccccc1111".to_string(),
+            "bbbbbbb2222".to_string(),
+            "aaaaaaa3333".to_string(),
+        ];
+
+        // Most recent commit touching the subject wins
+        let suspect = identify_suspect(&list, &["parser".to_string()], &commits);
+        assert_eq!(suspect.as_deref(), Some("TASK-001"));
+
+        // No match falls back to the newest task commit
+        let suspect = identify_suspect(&list, &["network".to_string()], &commits);
+        assert_eq!(suspect.as_deref(), Some("TASK-003"));
+
+        assert_eq!(identify_suspect(&list, &["parser".to_string()], &[]), None);
+    }
+
+    #[test]
+    fn test_create_regression_task_blocks_dependents() {
+        let mut list = TaskList::new("Test", "rust");
+        list.add_task(completed("TASK-001", "aaaaaaa", &["src/parser.rs"]));
+        let mut dependent = Task::new("TASK-002", "Uses parser", "Desc", 2);
+        dependent.add_dependency("TASK-001");
+        list.add_task(dependent);
+        list.add_task(Task::new("TASK-003", "Unrelated", "Desc", 2));
+
+        let id = create_regression_task(&mut list, Some("TASK-001"), "FAILED tests/test_parser.rs");
+        assert_eq!(id, "REGRESSION-001");
+
+        let regression = list.get_task(&id).unwrap();
+        assert_eq!(regression.priority, 1);
+        assert_eq!(regression.status, TaskStatus::Pending);
+        assert!(regression.description.contains("TASK-001"));
+
+        assert!(list.get_task("TASK-002").unwrap().dependencies.contains(&id));
+        assert!(!list.get_task("TASK-003").unwrap().dependencies.contains(&id));
+        assert!(list.validate().is_ok());
+
+        // Dependents must wait for the fix
+        assert_eq!(list.get_next_task().unwrap().id, id);
+
+        let second = create_regression_task(&mut list, None, "boom");
+        assert_eq!(second, "REGRESSION-002");
+        assert!(list.get_task("TASK-003").unwrap().dependencies.contains(&second));
+    }
+}
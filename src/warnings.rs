@@ -0,0 +1,231 @@
+//! Warnings collected over a run.
+//!
+//! Warnings printed inline scroll away long before a run ends. Everything that
+//! warns (the output handler, the plan validators, the file tool) also records
+//! a [`Warning`] in the process-wide [`WarningCollector`], and the loop prints
+//! one consolidated section at the end:
+//!
+//! ```text
+//! 12 warnings (5 file-path, 4 coverage, 3 legacy)
+//! ```
+//!
+//! The collected warnings are also saved with the last-run snapshot
+//! (`.ralph/last-run.json`).
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Mutex;
+
+/// What a warning is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WarningCategory {
+    /// A path was rewritten or rejected (e.g. a redundant project prefix)
+    FilePath,
+    /// A design component has no task creating its files
+    Coverage,
+    /// An input used a deprecated format
+    Legacy,
+    /// The project's files and the design disagree on the language
+    Language,
+    /// Tests passed only after a retry
+    FlakyTests,
+    /// An identifier uses a near-synonym of a glossary term
+    Naming,
+    /// Files a task wanted to write were refused
+    SkippedFiles,
+    /// Anything else
+    Other,
+}
+
+impl WarningCategory {
+    /// Short label used in the summary line.
+    pub fn label(&self) -> &'static str {
+        match self {
+            WarningCategory::FilePath => "file-path",
+            WarningCategory::Coverage => "coverage",
+            WarningCategory::Legacy => "legacy",
+            WarningCategory::Language => "language",
+            WarningCategory::FlakyTests => "flaky-tests",
+            WarningCategory::Naming => "naming",
+            WarningCategory::SkippedFiles => "skipped-files",
+            WarningCategory::Other => "other",
+        }
+    }
+}
+
+impl fmt::Display for WarningCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// One recorded warning, with the task or file it concerns when known.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Warning {
+    /// What the warning is about
+    pub category: WarningCategory,
+    /// The message as printed
+    pub message: String,
+    /// Task being worked on when the warning was raised
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task: Option<String>,
+    /// File the warning concerns
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+}
+
+impl Warning {
+    /// Create a warning without task or file context.
+    pub fn new(category: WarningCategory, message: impl Into<String>) -> Self {
+        Self {
+            category,
+            message: message.into(),
+            task: None,
+            file: None,
+        }
+    }
+
+    /// Attach the task the warning was raised for.
+    pub fn with_task(mut self, task_id: impl Into<String>) -> Self {
+        self.task = Some(task_id.into());
+        self
+    }
+
+    /// Attach the file the warning concerns.
+    pub fn with_file(mut self, path: impl Into<String>) -> Self {
+        self.file = Some(path.into());
+        self
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}]", self.category)?;
+        if let Some(task) = &self.task {
+            write!(f, " {}", task)?;
+        }
+        if let Some(file) = &self.file {
+            write!(f, " {}", file)?;
+        }
+        write!(f, " {}", self.message)
+    }
+}
+
+/// Accumulates warnings from anywhere in the process.
+#[derive(Debug, Default)]
+pub struct WarningCollector {
+    warnings: Mutex<Vec<Warning>>,
+}
+
+static GLOBAL: WarningCollector = WarningCollector::new();
+
+/// The process-wide collector that [`record`] and `RalphOutput` push to.
+pub fn global() -> &'static WarningCollector {
+    &GLOBAL
+}
+
+/// Record a warning in the process-wide collector.
+pub fn record(warning: Warning) {
+    GLOBAL.push(warning);
+}
+
+impl WarningCollector {
+    /// Create an empty collector.
+    pub const fn new() -> Self {
+        Self {
+            warnings: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Record a warning.
+    pub fn push(&self, warning: Warning) {
+        self.lock().push(warning);
+    }
+
+    /// Number of warnings recorded.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Whether nothing was recorded.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Copy of everything recorded so far.
+    pub fn snapshot(&self) -> Vec<Warning> {
+        self.lock().clone()
+    }
+
+    /// Everything recorded so far, leaving the collector empty for the next run.
+    pub fn take(&self) -> Vec<Warning> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Warning>> {
+        // A panic while pushing cannot leave the Vec half-written
+        self.warnings.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Count of warnings per category, most frequent first (ties in category order).
+pub fn counts(warnings: &[Warning]) -> Vec<(WarningCategory, usize)> {
+    let mut by_category: BTreeMap<WarningCategory, usize> = BTreeMap::new();
+    for warning in warnings {
+        *by_category.entry(warning.category).or_default() += 1;
+    }
+    let mut counts: Vec<_> = by_category.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    counts
+}
+
+/// One-line summary, e.g. `12 warnings (5 file-path, 4 coverage, 3 legacy)`.
+pub fn summary(warnings: &[Warning]) -> String {
+    let noun = if warnings.len() == 1 { "warning" } else { "warnings" };
+    let parts: Vec<String> = counts(warnings)
+        .into_iter()
+        .map(|(category, n)| format!("{} {}", n, category))
+        .collect();
+    format!("{} {} ({})", warnings.len(), noun, parts.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn warning(category: WarningCategory) -> Warning {
+        Warning::new(category, "something")
+    }
+
+    #[test]
+    fn test_summary_orders_categories_by_count() {
+        let mut warnings = Vec::new();
+        warnings.extend((0..3).map(|_| warning(WarningCategory::Legacy)));
+        warnings.extend((0..5).map(|_| warning(WarningCategory::FilePath)));
+        warnings.extend((0..4).map(|_| warning(WarningCategory::Coverage)));
+        assert_eq!(summary(&warnings), "12 warnings (5 file-path, 4 coverage, 3 legacy)");
+        assert_eq!(summary(&warnings[..1]), "1 warning (1 legacy)");
+    }
+
+    #[test]
+    fn test_collector_take_empties_it() {
+        let collector = WarningCollector::new();
+        assert!(collector.is_empty());
+        collector.push(warning(WarningCategory::Other).with_task("T-001"));
+        collector.push(warning(WarningCategory::FilePath).with_file("src/main.rs"));
+        assert_eq!(collector.len(), 2);
+
+        let taken = collector.take();
+        assert_eq!(taken[0].task.as_deref(), Some("T-001"));
+        assert_eq!(taken[1].to_string(), "[file-path] src/main.rs something");
+        assert!(collector.is_empty());
+    }
+
+    #[test]
+    fn test_warning_json_omits_missing_context() {
+        let json = serde_json::to_value(warning(WarningCategory::Coverage)).unwrap();
+        assert_eq!(json, serde_json::json!({"category": "coverage", "message": "something"}));
+    }
+}
@@ -0,0 +1,74 @@
+//! Output routing of the `ralph` binary.
+
+use std::process::Command;
+use tempfile::TempDir;
+
+/// Whether every non-empty line of `stdout` is a JSON object.
+fn all_json(stdout: &str) -> bool {
+    stdout
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .all(|line| serde_json::from_str::<serde_json::Value>(line).is_ok())
+}
+
+/// Run `ralph` in an empty project directory and return (stdout, stderr).
+fn ralph(project: &TempDir, args: &[&str]) -> (String, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_ralph"))
+        .args(args)
+        .current_dir(project.path())
+        .env("RALPH_PROJECT_PATH", project.path())
+        .env_remove("RALPH_EVENT_LOG")
+        .env_remove("RALPH_HUMAN_OUTPUT")
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "ralph {} failed: {}",
+        args.join(" "),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    (
+        String::from_utf8_lossy(&output.stdout).to_string(),
+        String::from_utf8_lossy(&output.stderr).to_string(),
+    )
+}
+
+#[test]
+fn test_json_output_keeps_human_text_off_stdout() {
+    let project = TempDir::new().unwrap();
+    let status: &[&str] = &["--output", "json", "status"];
+    let prompt: &[&str] = &["--output", "json", "prompt", "show", "prd", "--input", "a calculator"];
+    for (args, heading) in [(status, "Pipeline Status:"), (prompt, "[instruction] Instruction")] {
+        let (stdout, stderr) = ralph(&project, args);
+        assert!(all_json(&stdout), "non-JSON line on stdout for `{}`:\n{}", args.join(" "), stdout);
+        assert!(stderr.contains(heading), "`{}`: no {} on stderr", args.join(" "), heading);
+    }
+
+    let (stdout, _) = ralph(&project, &["status"]);
+    assert!(stdout.contains("Pipeline Status:"));
+}
+
+#[test]
+fn test_json_only_output_drops_human_text() {
+    let project = TempDir::new().unwrap();
+    let (stdout, stderr) = ralph(&project, &["--output", "json-only", "status"]);
+    assert!(all_json(&stdout), "non-JSON line on stdout:\n{}", stdout);
+    assert!(!stdout.contains("Pipeline Status:") && !stderr.contains("Pipeline Status:"));
+
+    // The same through configuration: events on stdout, human output off
+    let output = Command::new(env!("CARGO_BIN_EXE_ralph"))
+        .arg("status")
+        .current_dir(project.path())
+        .env("RALPH_PROJECT_PATH", project.path())
+        .env("RALPH_EVENT_LOG", "-")
+        .env("RALPH_HUMAN_OUTPUT", "false")
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(all_json(&stdout), "non-JSON line on stdout:\n{}", stdout);
+    assert!(!stderr.contains("Pipeline Status:"));
+}
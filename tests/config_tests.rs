@@ -1,7 +1,7 @@
 //! Tests for Ralph configuration.
 
 use adk_ralph::{
-    AgentModelConfig, ModelConfig, RalphConfig, TelemetryConfig, ValidationError,
+    AgentModelConfig, ModelConfig, Provider, RalphConfig, TelemetryConfig, ValidationError,
     MAX_ITERATIONS_LIMIT, MAX_RETRIES_LIMIT, MAX_TOKENS_LIMIT, SUPPORTED_PROVIDERS,
 };
 
@@ -67,7 +67,7 @@ fn test_agent_model_config_validation() {
 
     // Invalid PRD model
     let mut invalid = AgentModelConfig::default();
-    invalid.prd_model.provider = "invalid".to_string();
+    invalid.prd_model.provider = Provider::from_name("invalid");
     let err = invalid.validate().unwrap_err();
     assert!(err.field.contains("prd_model"));
 }
@@ -279,3 +279,54 @@ fn test_temperature_nan_and_infinity() {
     let err = config.validate().unwrap_err();
     assert_eq!(err.field, "temperature");
 }
+
+#[test]
+fn test_preflight_reports_every_problem_at_once() {
+    use adk_ralph::preflight::{run_checks, ConfigSources, ValueSource};
+
+    let dir = tempfile::tempdir().unwrap();
+    let not_a_dir = dir.path().join("project.txt");
+    std::fs::write(&not_a_dir, "").unwrap();
+
+    let mut config = RalphConfig::default();
+    config.project_path = not_a_dir.display().to_string();
+    config.max_iterations = 0;
+    config.agents.architect_model.temperature = 5.0;
+    config.telemetry.log_level = "chatty".to_string();
+    for model in [
+        &mut config.agents.prd_model,
+        &mut config.agents.architect_model,
+        &mut config.agents.ralph_model,
+    ] {
+        model.api_key_env = Some("RALPH_CONFIG_TESTS_UNSET_KEY".to_string());
+    }
+    let env_errors = [ValidationError::new("color", "Invalid color 'sometimes'")
+        .with_suggestion("Valid values: auto, always, never")];
+    let sources = ConfigSources::default()
+        .with_env_var("RALPH_COLOR")
+        .with_flag("project_path", "--project-path");
+
+    let report = run_checks(&config, &env_errors, &sources, true);
+    assert!(!report.passed());
+    let found: Vec<(&str, &str)> = report.problems.iter().map(|p| (p.check, p.field.as_str())).collect();
+    assert_eq!(
+        found,
+        [
+            ("environment", "color"),
+            ("models", "agents.architect_model.temperature"),
+            ("settings", "max_iterations"),
+            ("telemetry", "log_level"),
+            ("project path", "project_path"),
+            ("api keys", "agents.prd_model.api_key"),
+        ]
+    );
+    assert_eq!(report.problems[0].source, ValueSource::Env("RALPH_COLOR".to_string()));
+    assert_eq!(report.problems[4].source, ValueSource::Flag("--project-path".to_string()));
+    assert!(report.problems.iter().all(|p| p.suggestion.is_some()));
+
+    let err = report.into_result().unwrap_err();
+    assert_eq!(err.exit_code(), 2);
+    let text = err.to_string();
+    assert!(text.contains("6 configuration problem(s)"), "{}", text);
+    assert!(text.contains("(from flag --project-path)"), "{}", text);
+}
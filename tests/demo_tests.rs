@@ -0,0 +1,42 @@
+//! End-to-end test of `ralph demo`: the offline pipeline on the embedded recording.
+//!
+//! Needs `git` and `cargo` on the PATH, like the demo itself.
+
+use adk_ralph::{CompletionStatus, TaskList, TaskStatus};
+use std::process::Command;
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_demo_generates_a_project_that_builds_and_passes_its_tests() {
+    let temp_dir = TempDir::new().unwrap();
+    let report = adk_ralph::demo::run(temp_dir.path())
+        .await
+        .expect("demo pipeline failed");
+
+    assert!(
+        matches!(report.status, CompletionStatus::Complete { .. }),
+        "demo did not complete: {}",
+        report.status
+    );
+    for file in ["prd.md", "design.md", "tasks.json", "progress.json", "src/main.rs"] {
+        assert!(report.project_dir.join(file).exists(), "missing {}", file);
+    }
+
+    let tasks = TaskList::load(report.project_dir.join("tasks.json")).unwrap();
+    assert!(tasks.is_complete());
+    assert!(tasks.get_all_tasks().iter().all(|t| t.status == TaskStatus::Completed));
+
+    for command in [["build", "--offline"], ["test", "--offline"]] {
+        let output = Command::new("cargo")
+            .args(command)
+            .current_dir(&report.project_dir)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "cargo {} failed: {}",
+            command[0],
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+}
@@ -0,0 +1,69 @@
+//! Property-based tests for diff computation.
+//!
+//! These tests validate that the hunks produced for any pair of texts
+//! replay onto the old text to give exactly the new text.
+
+use adk_ralph::diff::{self, DiffFormat};
+use proptest::prelude::*;
+
+// ============================================================================
+// Generators
+// ============================================================================
+
+/// Generate a text from a small line alphabet, so that edits share lines,
+/// optionally with CRLF endings and without a final newline.
+fn arb_text() -> impl Strategy<Value = String> {
+    (
+        prop::collection::vec(prop::sample::select(vec!["a", "b", "c", "", "fn x() {}"]), 0..30),
+        any::<bool>(),
+        any::<bool>(),
+    )
+        .prop_map(|(lines, crlf, trailing_newline)| {
+            let eol = if crlf { "\r\n" } else { "\n" };
+            let mut text = lines.join(eol);
+            if trailing_newline && !lines.is_empty() {
+                text.push_str(eol);
+            }
+            text
+        })
+}
+
+// ============================================================================
+// Property Tests
+// ============================================================================
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(256))]
+
+    /// Applying the patch reconstructs the new content (after CRLF normalization).
+    #[test]
+    fn prop_patch_reconstructs_new(old in arb_text(), new in arb_text(), context in 0usize..5) {
+        let hunks = diff::unified_diff(&old, &new, context);
+        let patched = diff::apply_patch(&old, &hunks).unwrap();
+        prop_assert_eq!(patched, diff::normalize_line_endings(&new).into_owned());
+    }
+
+    /// Equal texts have no hunks and render to nothing.
+    #[test]
+    fn prop_equal_texts_render_empty(text in arb_text()) {
+        prop_assert!(diff::unified_diff(&text, &text.replace("\r\n", "\n"), 3).is_empty());
+        prop_assert_eq!(diff::render_diff(&text, &text, DiffFormat::Plain), "");
+    }
+
+    /// Every rendered plain line is a hunk header, a marked line or a note.
+    #[test]
+    fn prop_plain_lines_are_marked(old in arb_text(), new in arb_text()) {
+        for line in diff::render_diff(&old, &new, DiffFormat::Plain).lines() {
+            prop_assert!(
+                line.starts_with("@@ ")
+                    || line.starts_with(' ')
+                    || line.starts_with('-')
+                    || line.starts_with('+')
+                    || line.starts_with('\\')
+                    || line.ends_with("lines elided ..."),
+                "unexpected line {:?}",
+                line
+            );
+        }
+    }
+}
@@ -0,0 +1,8 @@
+pub mod eval;
+pub mod parser;
+
+#[cfg(unix)]
+#[path = "platform_unix.rs"]
+mod platform;
+
+// mod utils;
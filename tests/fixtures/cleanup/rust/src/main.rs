@@ -0,0 +1,3 @@
+fn main() {
+    println!("{}", calc::eval::run("1+1"));
+}
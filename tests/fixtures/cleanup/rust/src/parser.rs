@@ -0,0 +1,5 @@
+mod lexer;
+
+pub fn parse(input: &str) -> Vec<String> {
+    lexer::lex(input)
+}
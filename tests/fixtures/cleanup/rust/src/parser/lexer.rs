@@ -0,0 +1,3 @@
+pub fn lex(input: &str) -> Vec<String> {
+    input.split_whitespace().map(String::from).collect()
+}
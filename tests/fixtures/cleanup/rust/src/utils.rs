@@ -0,0 +1 @@
+// Helpers moved to eval.
@@ -0,0 +1,3 @@
+import { users } from './routes/users';
+
+test('users', () => expect(users()).toBeDefined());
@@ -0,0 +1,7 @@
+import express from 'express';
+import { users } from './routes';
+export * from "./db";
+
+const lazy = () => import('./lazy.js');
+
+express().get('/users', users);
@@ -0,0 +1 @@
+export { users } from './users';
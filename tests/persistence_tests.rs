@@ -0,0 +1,72 @@
+//! Crash-consistency tests for write-behind persistence.
+//!
+//! A run is killed in the middle of a tool-call burst. Buffered journal
+//! writes may be lost, but tasks.json must already hold every change.
+
+use adk_ralph::{
+    ProgressEntry, ProgressLog, Task, TaskList, TaskStatus, WriteBehind, WriteBehindPolicy,
+};
+use std::time::Duration;
+use tempfile::tempdir;
+
+#[test]
+fn test_killed_run_never_leaves_tasks_json_stale() {
+    let dir = tempdir().unwrap();
+    let tasks_path = dir.path().join("tasks.json");
+    let progress_path = dir.path().join("progress.json");
+
+    // Nothing flushes on its own during the burst
+    let journals = WriteBehind::new(WriteBehindPolicy {
+        max_delay: Duration::from_secs(3600),
+        max_bytes: usize::MAX,
+    });
+
+    let mut tasks = TaskList::new("demo", "rust");
+    let mut progress = ProgressLog::new("demo");
+    for i in 1..=20 {
+        let id = format!("T-{:03}", i);
+        tasks.add_task(Task::new(&id, &id, "", 1));
+        tasks.get_task_mut(&id).unwrap().start();
+        tasks.save(&tasks_path).unwrap();
+
+        progress.append(ProgressEntry::new(&id, &id, i, "burst"));
+        journals
+            .write(&progress_path, serde_json::to_string(&progress).unwrap())
+            .unwrap();
+
+        // Every step: tasks.json is current, the journal may lag behind it
+        let on_disk = TaskList::load(&tasks_path).unwrap();
+        assert_eq!(on_disk.get_task(&id).unwrap().status, TaskStatus::InProgress);
+    }
+
+    // Kill: the process dies without running destructors
+    std::mem::forget(journals);
+
+    let on_disk = TaskList::load(&tasks_path).unwrap();
+    assert_eq!(on_disk.get_all_tasks().len(), 20);
+    assert!(!progress_path.exists(), "buffered journal entries are allowed to be lost");
+    let leftovers: Vec<_> = std::fs::read_dir(dir.path())
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    assert_eq!(leftovers, vec!["tasks.json"]);
+}
+
+#[test]
+fn test_shutdown_flushes_buffered_journals() {
+    let dir = tempdir().unwrap();
+    let progress_path = dir.path().join("progress.json");
+    let journals = WriteBehind::new(WriteBehindPolicy {
+        max_delay: Duration::from_secs(3600),
+        max_bytes: usize::MAX,
+    });
+
+    let mut progress = ProgressLog::new("demo");
+    progress.append(ProgressEntry::new("T-001", "First", 1, "done"));
+    journals
+        .write(&progress_path, serde_json::to_string(&progress).unwrap())
+        .unwrap();
+    drop(journals);
+
+    assert_eq!(ProgressLog::load(&progress_path).unwrap().entry_count(), 1);
+}
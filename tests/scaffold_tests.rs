@@ -0,0 +1,58 @@
+//! Tests for the build check that runs right after the project-setup task.
+//!
+//! Needs `cargo` on the PATH; the scaffolds have no dependencies, so no network.
+
+use adk_ralph::{RalphError, ScaffoldCheck, Task, TaskList, TaskStatus};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+/// Write a one-binary Cargo project whose `main.rs` is `main_rs`.
+fn write_scaffold(dir: &Path, main_rs: &str) {
+    fs::write(
+        dir.join("Cargo.toml"),
+        "[package]\nname = \"scaffold\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[workspace]\n",
+    )
+    .unwrap();
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::write(dir.join("src/main.rs"), main_rs).unwrap();
+}
+
+/// Write a plan whose setup task T-001 is complete.
+fn write_tasks(dir: &Path) {
+    let mut list = TaskList::new("scaffold", "rust");
+    let mut setup = Task::new("T-001", "Set up project structure", "Cargo project", 1);
+    setup.status = TaskStatus::Completed;
+    list.add_task(setup);
+    list.add_task(Task::new("T-002", "Parse input", "Parser", 2));
+    list.save(dir.join("tasks.json")).unwrap();
+}
+
+#[tokio::test]
+async fn test_non_building_scaffold_fails_fast() {
+    let temp_dir = TempDir::new().unwrap();
+    write_scaffold(temp_dir.path(), "fn main() {\n    let answer: u32 = \"forty-two\";\n}\n");
+    write_tasks(temp_dir.path());
+    let check = ScaffoldCheck::new(temp_dir.path(), temp_dir.path().join("tasks.json"));
+
+    // Later tasks are not checked, even on a broken scaffold
+    assert!(!check.on_task_completed("T-002").await.unwrap());
+
+    let err = check.on_task_completed("T-001").await.unwrap_err();
+    assert!(matches!(&err, RalphError::ScaffoldBuild { task_id, .. } if task_id == "T-001"));
+    assert_eq!(err.exit_code(), 4);
+    let message = err.to_string();
+    assert!(message.starts_with("Project scaffold does not build after T-001"), "{}", message);
+    assert!(message.contains("cargo build failed"), "{}", message);
+    assert!(message.contains("mismatched types"), "{}", message);
+}
+
+#[tokio::test]
+async fn test_building_scaffold_passes() {
+    let temp_dir = TempDir::new().unwrap();
+    write_scaffold(temp_dir.path(), "fn main() {\n    println!(\"hello\");\n}\n");
+    write_tasks(temp_dir.path());
+    let check = ScaffoldCheck::new(temp_dir.path(), temp_dir.path().join("tasks.json"));
+
+    assert!(check.on_task_completed("T-001").await.unwrap());
+}
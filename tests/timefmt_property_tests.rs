@@ -0,0 +1,90 @@
+//! Property-based tests for duration and timestamp formatting.
+//!
+//! These tests validate that persisted timestamps round-trip through parsing,
+//! and that humanized durations keep a fixed, locale-independent shape.
+
+use adk_ralph::timefmt;
+use chrono::{DateTime, TimeZone, Utc};
+use proptest::prelude::*;
+use std::time::Duration;
+
+// ============================================================================
+// Generators
+// ============================================================================
+
+/// Generate a UTC time between 1970 and 2200 with millisecond precision.
+fn arb_utc() -> impl Strategy<Value = DateTime<Utc>> {
+    (0i64..7_258_118_400_000).prop_map(|ms| Utc.timestamp_millis_opt(ms).unwrap())
+}
+
+/// Generate a duration up to ten days.
+fn arb_duration() -> impl Strategy<Value = Duration> {
+    (0u64..864_000_000).prop_map(Duration::from_millis)
+}
+
+// ============================================================================
+// Property Tests
+// ============================================================================
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(256))]
+
+    /// Parsing the persisted form gives back the same instant.
+    #[test]
+    fn prop_persisted_timestamps_round_trip(at in arb_utc()) {
+        let stored = timefmt::timestamp(at);
+        prop_assert_eq!(timefmt::parse_timestamp(&stored), Some(at));
+    }
+
+    /// The persisted form is fixed-width UTC ISO-8601 whatever the platform.
+    #[test]
+    fn prop_persisted_timestamps_have_a_fixed_shape(at in arb_utc()) {
+        let stored = timefmt::timestamp(at);
+        prop_assert_eq!(stored.len(), "2026-10-16T09:30:00.123Z".len());
+        prop_assert!(stored.ends_with('Z'));
+        prop_assert!(stored.is_ascii());
+    }
+
+    /// Displayed times are numeric and never depend on the locale.
+    #[test]
+    fn prop_display_is_numeric(at in arb_utc()) {
+        let shown = timefmt::display_in(at, &Utc);
+        prop_assert_eq!(shown.len(), "2026-10-16 09:30:00".len());
+        prop_assert!(shown.chars().all(|c| c.is_ascii_digit() || "-: ".contains(c)));
+        prop_assert!(timefmt::timestamp(at).starts_with(&shown[..10]));
+    }
+
+    /// Humanized durations use only digits, unit suffixes and one space.
+    #[test]
+    fn prop_durations_have_a_fixed_shape(elapsed in arb_duration()) {
+        let shown = timefmt::duration(elapsed);
+        let units: Vec<&str> = shown.split(' ').collect();
+        prop_assert!(units.len() <= 2, "{}", shown);
+        for unit in units {
+            let digits = unit.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+            let numeric = !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit());
+            prop_assert!(numeric, "{}", shown);
+            prop_assert!(["ms", "s", "m", "h"].contains(&&unit[digits.len()..]), "{}", shown);
+        }
+    }
+
+    /// A duration never reads as longer than it was.
+    #[test]
+    fn prop_durations_never_round_up(elapsed in arb_duration()) {
+        let shown = timefmt::duration(elapsed);
+        let read: u64 = shown
+            .split(' ')
+            .map(|unit| {
+                let digits = unit.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+                let n: u64 = digits.parse().unwrap();
+                match &unit[digits.len()..] {
+                    "ms" => n,
+                    "s" => n * 1_000,
+                    "m" => n * 60_000,
+                    _ => n * 3_600_000,
+                }
+            })
+            .sum();
+        prop_assert!(u128::from(read) <= elapsed.as_millis(), "{} for {:?}", shown, elapsed);
+    }
+}